@@ -0,0 +1,129 @@
+//! Admin instructions, built from the program crate's Anchor-generated
+//! `accounts` and `instruction` modules so they can't drift from the program.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use zkcash::PoolStatus;
+
+pub fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &zkcash::ID).0
+}
+
+pub fn tree_account() -> Pubkey {
+    pda(&[b"merkle_tree"])
+}
+
+fn global_config() -> Pubkey {
+    pda(&[b"global_config"])
+}
+
+fn audit_log() -> Pubkey {
+    pda(&[b"audit_log"])
+}
+
+fn role_config() -> Pubkey {
+    pda(&[b"role_config"])
+}
+
+/// `queue_config_update` arguments; `None` leaves a field unchanged.
+#[derive(Debug, Default)]
+pub struct ConfigUpdate {
+    pub deposit_fee_rate: Option<u16>,
+    pub withdrawal_fee_rate: Option<u16>,
+    pub fee_error_margin: Option<u16>,
+    pub max_deposit_amount: Option<u64>,
+}
+
+pub fn initialize(authority: Pubkey) -> Instruction {
+    Instruction {
+        program_id: zkcash::ID,
+        accounts: zkcash::accounts::Initialize {
+            tree_account: tree_account(),
+            tree_token_account: pda(&[b"tree_token"]),
+            global_config: global_config(),
+            authority,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::Initialize {}.data(),
+    }
+}
+
+pub fn initialize_audit_log(payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: zkcash::ID,
+        accounts: zkcash::accounts::InitializeAuditLog {
+            audit_log: audit_log(),
+            payer,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::InitializeAuditLog {}.data(),
+    }
+}
+
+pub fn initialize_role_config(payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: zkcash::ID,
+        accounts: zkcash::accounts::InitializeRoleConfig {
+            role_config: role_config(),
+            payer,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::InitializeRoleConfig {}.data(),
+    }
+}
+
+pub fn queue_config_update(authority: Pubkey, update: &ConfigUpdate) -> Instruction {
+    Instruction {
+        program_id: zkcash::ID,
+        accounts: zkcash::accounts::QueueConfigUpdate {
+            pending_config: pda(&[b"pending_config"]),
+            global_config: global_config(),
+            authority,
+            system_program: system_program::ID,
+            audit_log: audit_log(),
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::QueueConfigUpdate {
+            deposit_fee_rate: update.deposit_fee_rate,
+            withdrawal_fee_rate: update.withdrawal_fee_rate,
+            fee_error_margin: update.fee_error_margin,
+            max_deposit_amount: update.max_deposit_amount,
+        }
+        .data(),
+    }
+}
+
+/// `authority` is the config authority, which receives the pending
+/// account's rent. The program lets anyone sign.
+pub fn execute_config_update(authority: Pubkey) -> Instruction {
+    Instruction {
+        program_id: zkcash::ID,
+        accounts: zkcash::accounts::ExecuteConfigUpdate {
+            pending_config: pda(&[b"pending_config"]),
+            global_config: global_config(),
+            tree_account: tree_account(),
+            authority,
+            audit_log: audit_log(),
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::ExecuteConfigUpdate {}.data(),
+    }
+}
+
+pub fn set_pool_status(authority: Pubkey, tree_account: Pubkey, status: PoolStatus) -> Instruction {
+    Instruction {
+        program_id: zkcash::ID,
+        accounts: zkcash::accounts::SetPoolStatus {
+            pool_registry: pda(&[b"pool_registry"]),
+            global_config: global_config(),
+            authority,
+            audit_log: audit_log(),
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::SetPoolStatus { tree_account, status }.data(),
+    }
+}
@@ -0,0 +1,241 @@
+//! Administers and inspects zkcash pools from the command line.
+//!
+//! ```text
+//! zkcash init-pool
+//!     Runs `initialize` with the keypair as authority, and creates the audit
+//!     log every admin action is recorded in.
+//!
+//! zkcash queue-config [--deposit-fee-rate BPS] [--withdrawal-fee-rate BPS]
+//!                     [--fee-error-margin BPS] [--max-deposit-amount LAMPORTS]
+//!     Queues a timelocked config update; omitted fields stay unchanged.
+//!
+//! zkcash execute-config
+//!     Applies the queued update once its delay has passed. Run as the config
+//!     authority, which gets the pending account's rent back.
+//!
+//! zkcash pause|resume|retire [TREE_ACCOUNT]
+//!     Sets the pool's registry status. Retiring is how a tree is rotated out:
+//!     it stays open for withdrawals while deposits go to its replacement.
+//!
+//! zkcash dump-tree [TREE_ACCOUNT]
+//!     Prints the tree account's state as JSON.
+//!
+//! zkcash verify-proof FIXTURE_JSON
+//!     Checks a proof in the fixture-gen `TransactFixture` format against the
+//!     program's verifying key, ExtData hash and public amount, offline.
+//! ```
+//!
+//! TREE_ACCOUNT defaults to the SOL pool's tree. The RPC endpoint is read
+//! from `ZKCASH_RPC_URL` (default `http://127.0.0.1:8899`) and the signing
+//! keypair from `ZKCASH_KEYPAIR` (default `~/.config/solana/id.json`).
+
+mod instructions;
+mod rpc;
+
+use anchor_lang::prelude::Pubkey;
+use ed25519_dalek::Keypair;
+use instructions::ConfigUpdate;
+use rpc::{keypair_pubkey, RpcClient};
+use serde::Deserialize;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use zkcash::utils::{verify_proof, VERIFYING_KEY};
+use zkcash::{merkle_tree_account_version, MerkleTreeAccount, PoolStatus, Proof};
+use zkcash_client::ext_data::ExtData;
+use zkcash_client::transaction::calculate_public_amount;
+
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+const USAGE: &str = "usage: zkcash init-pool
+       zkcash queue-config [--deposit-fee-rate BPS] [--withdrawal-fee-rate BPS] [--fee-error-margin BPS] [--max-deposit-amount LAMPORTS]
+       zkcash execute-config
+       zkcash pause|resume|retire [TREE_ACCOUNT]
+       zkcash dump-tree [TREE_ACCOUNT]
+       zkcash verify-proof FIXTURE_JSON";
+const DEFAULT_RPC_URL: &str = "http://127.0.0.1:8899";
+
+fn rpc_client() -> Result<RpcClient> {
+    let url = std::env::var("ZKCASH_RPC_URL").unwrap_or_else(|_| DEFAULT_RPC_URL.to_string());
+    RpcClient::new(&url)
+}
+
+fn load_keypair() -> Result<Keypair> {
+    let path = match std::env::var_os("ZKCASH_KEYPAIR") {
+        Some(path) => PathBuf::from(path),
+        None => PathBuf::from(std::env::var_os("HOME").ok_or("HOME is not set")?).join(".config/solana/id.json"),
+    };
+    let json = std::fs::read_to_string(&path).map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    let bytes: Vec<u8> = serde_json::from_str(&json)?;
+    Ok(Keypair::from_bytes(&bytes).map_err(|e| format!("invalid keypair {}: {}", path.display(), e))?)
+}
+
+fn tree_account_arg(arg: Option<&&str>) -> Result<Pubkey> {
+    match arg {
+        Some(address) => Ok(Pubkey::from_str(address)?),
+        None => Ok(instructions::tree_account()),
+    }
+}
+
+fn parse_config_update(options: &[&str]) -> Result<ConfigUpdate> {
+    let mut update = ConfigUpdate::default();
+    for pair in options.chunks(2) {
+        let [flag, value] = pair else {
+            return Err(format!("{} needs a value", pair[0]).into());
+        };
+        match *flag {
+            "--deposit-fee-rate" => update.deposit_fee_rate = Some(value.parse()?),
+            "--withdrawal-fee-rate" => update.withdrawal_fee_rate = Some(value.parse()?),
+            "--fee-error-margin" => update.fee_error_margin = Some(value.parse()?),
+            "--max-deposit-amount" => update.max_deposit_amount = Some(value.parse()?),
+            _ => return Err(format!("unknown option {}", flag).into()),
+        }
+    }
+    Ok(update)
+}
+
+fn send(instruction: anchor_lang::solana_program::instruction::Instruction, keypair: &Keypair) -> Result<()> {
+    send_all(&[instruction], keypair)
+}
+
+fn send_all(instructions: &[anchor_lang::solana_program::instruction::Instruction], keypair: &Keypair) -> Result<()> {
+    let signature = rpc_client()?.send(instructions, keypair)?;
+    println!("{}", signature);
+    Ok(())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn dump_tree(tree_account: &Pubkey) -> Result<()> {
+    let data = rpc_client()?
+        .account_data(tree_account)?
+        .ok_or_else(|| format!("tree account {} does not exist", tree_account))?;
+    let version = merkle_tree_account_version(&data).map_err(|e| format!("not a tree account: {}", e))?;
+    let tree: &MerkleTreeAccount = bytemuck::from_bytes(&data[8..8 + std::mem::size_of::<MerkleTreeAccount>()]);
+
+    let state = json!({
+        "address": tree_account.to_string(),
+        "layout_version": version,
+        "authority": tree.authority.to_string(),
+        "height": tree.height,
+        "next_index": tree.next_index,
+        "root": hex(&tree.root),
+        "root_index": tree.root_index,
+        "root_history_size": tree.root_history_size,
+        "max_deposit_amount": tree.max_deposit_amount,
+    });
+    println!("{}", serde_json::to_string_pretty(&state)?);
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct ProofFixture {
+    proof_a: Vec<u8>,
+    proof_b: Vec<u8>,
+    proof_c: Vec<u8>,
+    root: Vec<u8>,
+    public_amount: Vec<u8>,
+    ext_data_hash: Vec<u8>,
+    input_nullifiers: Vec<Vec<u8>>,
+    output_commitments: Vec<Vec<u8>>,
+}
+
+#[derive(Deserialize)]
+struct TransactFixture {
+    proof: ProofFixture,
+    ext_amount: i64,
+    fee: u64,
+    recipient: String,
+    fee_recipient: String,
+    encrypted_output1: Vec<u8>,
+    encrypted_output2: Vec<u8>,
+}
+
+fn array<const N: usize>(bytes: &[u8], field: &str) -> Result<[u8; N]> {
+    bytes.try_into().map_err(|_| format!("{} must be {} bytes", field, N).into())
+}
+
+fn verify_fixture(path: &Path) -> Result<()> {
+    let json = std::fs::read_to_string(path).map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    let fixture: TransactFixture = serde_json::from_str(&json)?;
+    let [nullifier0, nullifier1] = fixture.proof.input_nullifiers.as_slice() else {
+        return Err("input_nullifiers must have two entries".into());
+    };
+    let [commitment0, commitment1] = fixture.proof.output_commitments.as_slice() else {
+        return Err("output_commitments must have two entries".into());
+    };
+    let proof = Proof {
+        proof_a: array(&fixture.proof.proof_a, "proof_a")?,
+        proof_b: array(&fixture.proof.proof_b, "proof_b")?,
+        proof_c: array(&fixture.proof.proof_c, "proof_c")?,
+        root: array(&fixture.proof.root, "root")?,
+        public_amount: array(&fixture.proof.public_amount, "public_amount")?,
+        ext_data_hash: array(&fixture.proof.ext_data_hash, "ext_data_hash")?,
+        input_nullifiers: [array(nullifier0, "input_nullifiers[0]")?, array(nullifier1, "input_nullifiers[1]")?],
+        output_commitments: [array(commitment0, "output_commitments[0]")?, array(commitment1, "output_commitments[1]")?],
+    };
+
+    let ext_data = ExtData::builder()
+        .recipient(Pubkey::from_str(&fixture.recipient)?)
+        .ext_amount(fixture.ext_amount)
+        .encrypted_outputs(fixture.encrypted_output1, fixture.encrypted_output2)
+        .fee(fixture.fee)
+        .fee_recipient(Pubkey::from_str(&fixture.fee_recipient)?)
+        .build()?;
+    if ext_data.public_input() != proof.ext_data_hash {
+        return Err("proof is bound to a different ExtData".into());
+    }
+    if calculate_public_amount(fixture.ext_amount, fixture.fee)? != proof.public_amount {
+        return Err("public amount does not match ext_amount and fee".into());
+    }
+    if !verify_proof(proof, VERIFYING_KEY) {
+        return Err("proof does not verify".into());
+    }
+    println!("proof is valid");
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["init-pool"] => {
+            let keypair = load_keypair()?;
+            let authority = keypair_pubkey(&keypair);
+            send_all(
+                &[
+                    instructions::initialize(authority),
+                    instructions::initialize_audit_log(authority),
+                    instructions::initialize_role_config(authority),
+                ],
+                &keypair,
+            )
+        }
+        ["queue-config", options @ ..] => {
+            let update = parse_config_update(options)?;
+            let keypair = load_keypair()?;
+            send(instructions::queue_config_update(keypair_pubkey(&keypair), &update), &keypair)
+        }
+        ["execute-config"] => {
+            let keypair = load_keypair()?;
+            send(instructions::execute_config_update(keypair_pubkey(&keypair)), &keypair)
+        }
+        [command @ ("pause" | "resume" | "retire"), rest @ ..] if rest.len() <= 1 => {
+            let status = match *command {
+                "pause" => PoolStatus::Paused,
+                "resume" => PoolStatus::Active,
+                _ => PoolStatus::Retired,
+            };
+            let tree_account = tree_account_arg(rest.first())?;
+            let keypair = load_keypair()?;
+            send(instructions::set_pool_status(keypair_pubkey(&keypair), tree_account, status), &keypair)
+        }
+        ["dump-tree", rest @ ..] if rest.len() <= 1 => dump_tree(&tree_account_arg(rest.first())?),
+        ["verify-proof", fixture] => verify_fixture(Path::new(fixture)),
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    }
+}
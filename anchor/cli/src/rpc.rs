@@ -0,0 +1,108 @@
+//! The handful of JSON-RPC calls the CLI needs.
+
+use crate::Result;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::Hash;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::message::Message;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ed25519_dalek::{Keypair, Signer};
+use serde_json::{json, Value};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+const CONFIRM_ATTEMPTS: usize = 60;
+const CONFIRM_INTERVAL: Duration = Duration::from_millis(500);
+
+pub struct RpcClient {
+    url: String,
+    agent: ureq::Agent,
+}
+
+pub fn keypair_pubkey(keypair: &Keypair) -> Pubkey {
+    Pubkey::new_from_array(keypair.public.to_bytes())
+}
+
+impl RpcClient {
+    pub fn new(url: &str) -> Result<RpcClient> {
+        let agent = ureq::AgentBuilder::new()
+            .tls_connector(Arc::new(native_tls::TlsConnector::new()?))
+            .build();
+        Ok(RpcClient {
+            url: url.to_string(),
+            agent,
+        })
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response: Value = self.agent.post(&self.url).send_json(request)?.into_json()?;
+        if let Some(error) = response.get("error") {
+            return Err(format!("{} failed: {}", method, error).into());
+        }
+        Ok(response["result"].clone())
+    }
+
+    /// The account's data, or `None` if it doesn't exist.
+    pub fn account_data(&self, address: &Pubkey) -> Result<Option<Vec<u8>>> {
+        let result = self.call(
+            "getAccountInfo",
+            json!([address.to_string(), { "encoding": "base64", "commitment": "confirmed" }]),
+        )?;
+        let account = &result["value"];
+        if account.is_null() {
+            return Ok(None);
+        }
+        let data = account["data"][0].as_str().ok_or("getAccountInfo returned no data")?;
+        Ok(Some(BASE64.decode(data)?))
+    }
+
+    fn latest_blockhash(&self) -> Result<Hash> {
+        let result = self.call("getLatestBlockhash", json!([{ "commitment": "confirmed" }]))?;
+        let blockhash = result["value"]["blockhash"].as_str().ok_or("getLatestBlockhash returned no blockhash")?;
+        Ok(Hash::from_str(blockhash)?)
+    }
+
+    /// Sends `instructions` signed and paid for by `signer` alone, and waits
+    /// until the transaction is confirmed. Returns its signature.
+    pub fn send(&self, instructions: &[Instruction], signer: &Keypair) -> Result<String> {
+        let mut message = Message::new(instructions, Some(&keypair_pubkey(signer)));
+        if message.header.num_required_signatures != 1 {
+            return Err("instructions need signers other than the keypair".into());
+        }
+        message.recent_blockhash = self.latest_blockhash()?;
+        let message_bytes = message.serialize();
+
+        // Wire format: compact-u16 signature count, the signatures, the message
+        let mut transaction = vec![1u8];
+        transaction.extend_from_slice(&signer.sign(&message_bytes).to_bytes());
+        transaction.extend_from_slice(&message_bytes);
+
+        let result = self.call(
+            "sendTransaction",
+            json!([BASE64.encode(&transaction), { "encoding": "base64", "preflightCommitment": "confirmed" }]),
+        )?;
+        let signature = result.as_str().ok_or("sendTransaction returned no signature")?.to_string();
+        self.confirm(&signature)?;
+        Ok(signature)
+    }
+
+    fn confirm(&self, signature: &str) -> Result<()> {
+        for _ in 0..CONFIRM_ATTEMPTS {
+            let result = self.call("getSignatureStatuses", json!([[signature]]))?;
+            let status = &result["value"][0];
+            if !status.is_null() {
+                if !status["err"].is_null() {
+                    return Err(format!("transaction {} failed: {}", signature, status["err"]).into());
+                }
+                if matches!(status["confirmationStatus"].as_str(), Some("confirmed" | "finalized")) {
+                    return Ok(());
+                }
+            }
+            std::thread::sleep(CONFIRM_INTERVAL);
+        }
+        Err(format!("transaction {} was not confirmed in time", signature).into())
+    }
+}
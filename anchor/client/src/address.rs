@@ -0,0 +1,203 @@
+//! Diversified payment addresses.
+//!
+//! A payment address tells a sender everything needed to create a note for
+//! the recipient: the owner key that goes into the commitment and the x25519
+//! key the note is encrypted to. A wallet can hand out any number of them,
+//! one per diversifier, and none can be linked to another:
+//!
+//! - The owner key is `Poseidon(spending_key_d)` with
+//!   `spending_key_d = Poseidon(spending_key, d)`, so each address spends
+//!   with its own key.
+//! - The transmission key is `ivk * B_d`, where `B_d` is a base point hashed
+//!   from the diversifier and `ivk` the wallet's single encryption (incoming
+//!   viewing) key. Notes are encrypted with an ephemeral key `esk * B_d`, so
+//!   the recipient recovers the shared secret as `ivk * epk` without knowing
+//!   which address was paid, and the diversifier travels inside the note.
+//!
+//! The default diversifier (all zeros) keeps the undiversified keys: its
+//! owner key is `ShieldedKeys::public_key` and its base point the x25519
+//! base point, so it is the address older clients already used.
+//!
+//! Addresses are encoded as `PAYMENT_ADDRESS_PREFIX` followed by the base58
+//! of `version || diversifier || owner key || transmission key ||
+//! detection tag length || checksum`, where the checksum is the first four
+//! bytes of a double SHA-256. The detection tag length tells senders which
+//! tag the recipient scans for, so wallets agree on the note format from the
+//! address string alone. Version 1 addresses end at the transmission key and
+//! imply `DETECTION_TAG_LEN`; they are still parsed, but no longer produced.
+
+use crate::errors::ClientError;
+use crate::note::DETECTION_TAG_LEN;
+use crate::utils::{fr_to_be_bytes, poseidon_hash};
+use crate::utxo::Utxo;
+use anchor_lang::prelude::Pubkey;
+use ark_bn254::Fr;
+use ark_ff::PrimeField;
+use rand_core::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+use x25519_dalek::{PublicKey, StaticSecret, X25519_BASEPOINT_BYTES};
+
+pub const DIVERSIFIER_LEN: usize = 11;
+pub const PAYMENT_ADDRESS_PREFIX: &str = "zk";
+pub const PAYMENT_ADDRESS_VERSION: u8 = 2;
+pub const PAYMENT_ADDRESS_VERSION_UNTAGGED: u8 = 1;
+pub const PAYMENT_ADDRESS_LEN: usize = PAYMENT_ADDRESS_UNTAGGED_LEN + 1;
+pub const PAYMENT_ADDRESS_UNTAGGED_LEN: usize = 1 + DIVERSIFIER_LEN + 32 + 32;
+const CHECKSUM_LEN: usize = 4;
+const DIVERSIFIER_BASE_DOMAIN: &[u8] = b"zkcash-diversifier-base";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Diversifier(pub [u8; DIVERSIFIER_LEN]);
+
+impl Diversifier {
+    pub fn is_default(&self) -> bool {
+        self.0 == [0u8; DIVERSIFIER_LEN]
+    }
+
+    /// The x25519 point `B_d` this diversifier's transmission key is built on.
+    pub fn base_point(&self) -> [u8; 32] {
+        if self.is_default() {
+            return X25519_BASEPOINT_BYTES;
+        }
+        let scalar: [u8; 32] = Sha256::new()
+            .chain_update(DIVERSIFIER_BASE_DOMAIN)
+            .chain_update(self.0)
+            .finalize()
+            .into();
+        PublicKey::from(&StaticSecret::from(scalar)).to_bytes()
+    }
+
+    /// `spending_key_d`; the default diversifier leaves the key unchanged.
+    pub fn spending_key(&self, spending_key: Fr) -> Result<Fr, ClientError> {
+        if self.is_default() {
+            return Ok(spending_key);
+        }
+        poseidon_hash(&[spending_key, Fr::from_be_bytes_mod_order(&self.0)])
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PaymentAddress {
+    pub diversifier: Diversifier,
+    /// The circuit's `outPubkey` for notes paid to this address.
+    pub owner_pubkey: Fr,
+    /// x25519 key notes to this address are encrypted to.
+    pub transmission_key: [u8; 32],
+    /// Bytes of detection tag the recipient scans notes for.
+    pub detection_tag_len: u8,
+}
+
+fn checksum(payload: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let digest = Sha256::digest(Sha256::digest(payload));
+    digest[..CHECKSUM_LEN].try_into().expect("SHA-256 digests are 32 bytes")
+}
+
+impl PaymentAddress {
+    pub fn to_bytes(&self) -> [u8; PAYMENT_ADDRESS_LEN] {
+        let mut bytes = [0u8; PAYMENT_ADDRESS_LEN];
+        bytes[0] = PAYMENT_ADDRESS_VERSION;
+        bytes[1..12].copy_from_slice(&self.diversifier.0);
+        bytes[12..44].copy_from_slice(&fr_to_be_bytes(&self.owner_pubkey));
+        bytes[44..76].copy_from_slice(&self.transmission_key);
+        bytes[76] = self.detection_tag_len;
+        bytes
+    }
+
+    /// Parses either address version. Versions and tag lengths this client
+    /// can't pay are `UnsupportedPaymentAddress` rather than invalid, so
+    /// wallets can tell the user to upgrade instead of blaming a typo.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ClientError> {
+        let detection_tag_len = match (bytes.first(), bytes.len()) {
+            (Some(&PAYMENT_ADDRESS_VERSION), PAYMENT_ADDRESS_LEN) => bytes[76],
+            (Some(&PAYMENT_ADDRESS_VERSION_UNTAGGED), PAYMENT_ADDRESS_UNTAGGED_LEN) => DETECTION_TAG_LEN as u8,
+            (Some(&version), _) if version > PAYMENT_ADDRESS_VERSION => {
+                return Err(ClientError::UnsupportedPaymentAddress)
+            }
+            _ => return Err(ClientError::InvalidPaymentAddress),
+        };
+        if detection_tag_len as usize != DETECTION_TAG_LEN {
+            return Err(ClientError::UnsupportedPaymentAddress);
+        }
+        let owner_bytes: [u8; 32] = bytes[12..44].try_into().map_err(|_| ClientError::InvalidPaymentAddress)?;
+        let owner_pubkey = Fr::from_be_bytes_mod_order(&owner_bytes);
+        // Reject non-canonical encodings so each address has exactly one form
+        if fr_to_be_bytes(&owner_pubkey) != owner_bytes {
+            return Err(ClientError::InvalidPaymentAddress);
+        }
+        Ok(PaymentAddress {
+            diversifier: Diversifier(bytes[1..12].try_into().map_err(|_| ClientError::InvalidPaymentAddress)?),
+            owner_pubkey,
+            transmission_key: bytes[44..76].try_into().map_err(|_| ClientError::InvalidPaymentAddress)?,
+            detection_tag_len,
+        })
+    }
+
+    /// The address string, same as `to_string`.
+    pub fn encode(&self) -> String {
+        self.to_string()
+    }
+
+    /// Parses an address string, same as `from_str`.
+    pub fn parse(value: &str) -> Result<Self, ClientError> {
+        value.parse()
+    }
+
+    /// Parses `value` and checks its keys can actually be paid: a zero owner
+    /// key or a low-order transmission key would make notes unspendable or
+    /// readable by anyone.
+    pub fn validate(value: &str) -> Result<Self, ClientError> {
+        let address = Self::parse(value)?;
+        address.validate_keys()?;
+        Ok(address)
+    }
+
+    pub fn validate_keys(&self) -> Result<(), ClientError> {
+        // Clamping makes the scalar a multiple of 8, which maps every
+        // low-order point to zero
+        let probe = StaticSecret::from([1u8; 32]).diffie_hellman(&PublicKey::from(self.transmission_key));
+        if self.owner_pubkey == Fr::from(0u64) || probe.as_bytes() == &[0u8; 32] {
+            return Err(ClientError::InvalidPaymentAddress);
+        }
+        Ok(())
+    }
+
+    /// A fresh UTXO paying `amount` to this address, ready for `to_note` and
+    /// `encrypt_note_to_address`.
+    pub fn new_utxo<R: RngCore + CryptoRng>(&self, amount: u64, mint_address: Pubkey, rng: &mut R) -> Utxo {
+        let mut utxo = Utxo::new(amount, self.owner_pubkey, mint_address, rng);
+        utxo.diversifier = self.diversifier;
+        utxo
+    }
+}
+
+impl fmt::Display for PaymentAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bytes = self.to_bytes();
+        let mut payload = bytes.to_vec();
+        payload.extend_from_slice(&checksum(&bytes));
+        write!(f, "{}{}", PAYMENT_ADDRESS_PREFIX, bs58::encode(payload).into_string())
+    }
+}
+
+impl FromStr for PaymentAddress {
+    type Err = ClientError;
+
+    fn from_str(value: &str) -> Result<Self, ClientError> {
+        let encoded = value
+            .strip_prefix(PAYMENT_ADDRESS_PREFIX)
+            .ok_or(ClientError::InvalidPaymentAddress)?;
+        let payload = bs58::decode(encoded)
+            .into_vec()
+            .map_err(|_| ClientError::InvalidPaymentAddress)?;
+        if payload.len() < CHECKSUM_LEN {
+            return Err(ClientError::InvalidPaymentAddress);
+        }
+        let (bytes, expected) = payload.split_at(payload.len() - CHECKSUM_LEN);
+        if checksum(bytes) != expected {
+            return Err(ClientError::InvalidPaymentAddress);
+        }
+        PaymentAddress::from_bytes(bytes)
+    }
+}
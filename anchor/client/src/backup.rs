@@ -0,0 +1,170 @@
+//! Password-encrypted backups of a wallet's notes.
+//!
+//! A backup lets a wallet restore its balance on a new device without
+//! rescanning the chain for every encrypted output. A backup file is laid
+//! out as:
+//!
+//! | offset | length | field                                       |
+//! |--------|--------|---------------------------------------------|
+//! | 0      | 4      | magic, `BACKUP_MAGIC`                       |
+//! | 4      | 1      | format version (`BACKUP_VERSION`)           |
+//! | 5      | 4      | PBKDF2 rounds, little-endian u32            |
+//! | 9      | 16     | PBKDF2 salt                                 |
+//! | 25     | 12     | ChaCha20-Poly1305 nonce                     |
+//! | 37     | ..     | ciphertext of the Borsh `NoteBackup` + tag  |
+//!
+//! The key is PBKDF2-HMAC-SHA256 of the password. The 37-byte header is the
+//! AEAD's associated data, so the rounds can't be lowered without the
+//! backup failing to decrypt.
+
+use crate::address::{Diversifier, DIVERSIFIER_LEN};
+use crate::errors::ClientError;
+use crate::keys::ShieldedKeys;
+use crate::note::Note;
+use crate::utils::fr_to_be_bytes;
+use crate::utxo::Utxo;
+use anchor_lang::prelude::{borsh, AnchorDeserialize, AnchorSerialize, Pubkey};
+use chacha20poly1305::aead::{Aead, NewAead, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand_core::{CryptoRng, RngCore};
+use sha2::Sha256;
+
+pub const BACKUP_MAGIC: [u8; 4] = *b"ZKNB";
+pub const BACKUP_VERSION: u8 = 1;
+/// OWASP's recommendation for PBKDF2-HMAC-SHA256.
+pub const BACKUP_KDF_ROUNDS: u32 = 600_000;
+/// Bounds on the rounds a backup may ask for: weaker backups aren't
+/// produced, and a crafted header can't stall the importing wallet.
+pub const MIN_BACKUP_KDF_ROUNDS: u32 = 10_000;
+pub const MAX_BACKUP_KDF_ROUNDS: u32 = 10_000_000;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = 4 + 1 + 4 + SALT_LEN + NONCE_LEN;
+
+/// One unspent note and where it sits in the tree.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BackupNote {
+    /// Big-endian, as the program stores it.
+    pub commitment: [u8; 32],
+    pub leaf_index: u64,
+    pub amount: u64,
+    /// Big-endian BN254 field element.
+    pub blinding: [u8; 32],
+    pub mint_address: Pubkey,
+    pub diversifier: [u8; DIVERSIFIER_LEN],
+}
+
+impl BackupNote {
+    pub fn from_utxo(utxo: &Utxo) -> Result<Self, ClientError> {
+        Ok(BackupNote {
+            commitment: fr_to_be_bytes(&utxo.commitment()?),
+            leaf_index: utxo.index,
+            amount: utxo.amount,
+            blinding: fr_to_be_bytes(&utxo.blinding),
+            mint_address: utxo.mint_address,
+            diversifier: utxo.diversifier.0,
+        })
+    }
+
+    /// The UTXO back, owned by `keys`. Fails if `keys` doesn't reproduce the
+    /// commitment, i.e. the backup belongs to another wallet.
+    pub fn to_utxo(&self, keys: &ShieldedKeys) -> Result<Utxo, ClientError> {
+        let note = Note {
+            amount: self.amount,
+            blinding: self.blinding,
+            mint_address: self.mint_address,
+            diversifier: Diversifier(self.diversifier),
+        };
+        let utxo = Utxo::from_note(&note, keys.public_key_for(&note.diversifier)?, self.leaf_index);
+        if fr_to_be_bytes(&utxo.commitment()?) != self.commitment {
+            return Err(ClientError::BackupKeyMismatch);
+        }
+        Ok(utxo)
+    }
+}
+
+/// A wallet's notes as of leaf `synced_index`. After restoring, scan only
+/// commitments from `synced_index` on.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct NoteBackup {
+    pub synced_index: u64,
+    pub notes: Vec<BackupNote>,
+}
+
+fn backup_key(password: &[u8], salt: &[u8], rounds: u32) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(password, salt, rounds, &mut key);
+    key
+}
+
+impl NoteBackup {
+    pub fn from_utxos(utxos: &[Utxo], synced_index: u64) -> Result<Self, ClientError> {
+        Ok(NoteBackup {
+            synced_index,
+            notes: utxos.iter().map(BackupNote::from_utxo).collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// The notes as UTXOs owned by `keys`.
+    pub fn to_utxos(&self, keys: &ShieldedKeys) -> Result<Vec<Utxo>, ClientError> {
+        self.notes.iter().map(|note| note.to_utxo(keys)).collect()
+    }
+
+    pub fn encrypt<R: RngCore + CryptoRng>(&self, password: &str, rng: &mut R) -> Result<Vec<u8>, ClientError> {
+        self.encrypt_with_rounds(password, BACKUP_KDF_ROUNDS, rng)
+    }
+
+    /// `encrypt` with a chosen work factor, within the bounds `decrypt`
+    /// accepts.
+    pub fn encrypt_with_rounds<R: RngCore + CryptoRng>(
+        &self,
+        password: &str,
+        rounds: u32,
+        rng: &mut R,
+    ) -> Result<Vec<u8>, ClientError> {
+        if !(MIN_BACKUP_KDF_ROUNDS..=MAX_BACKUP_KDF_ROUNDS).contains(&rounds) {
+            return Err(ClientError::InvalidBackup);
+        }
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        header.extend_from_slice(&BACKUP_MAGIC);
+        header.push(BACKUP_VERSION);
+        header.extend_from_slice(&rounds.to_le_bytes());
+        let mut salt = [0u8; SALT_LEN];
+        rng.fill_bytes(&mut salt);
+        header.extend_from_slice(&salt);
+        let mut nonce = [0u8; NONCE_LEN];
+        rng.fill_bytes(&mut nonce);
+        header.extend_from_slice(&nonce);
+
+        let plaintext = self.try_to_vec().map_err(|_| ClientError::InvalidBackup)?;
+        let key = backup_key(password.as_bytes(), &salt, rounds);
+        let ciphertext = ChaCha20Poly1305::new(Key::from_slice(&key))
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: &plaintext, aad: &header })
+            .map_err(|_| ClientError::EncryptionFailed)?;
+        header.extend_from_slice(&ciphertext);
+        Ok(header)
+    }
+
+    pub fn decrypt(backup: &[u8], password: &str) -> Result<Self, ClientError> {
+        if backup.len() < HEADER_LEN || backup[..4] != BACKUP_MAGIC {
+            return Err(ClientError::InvalidBackup);
+        }
+        if backup[4] != BACKUP_VERSION {
+            return Err(ClientError::UnsupportedBackupVersion);
+        }
+        let (header, ciphertext) = backup.split_at(HEADER_LEN);
+        let rounds = u32::from_le_bytes(header[5..9].try_into().unwrap());
+        if !(MIN_BACKUP_KDF_ROUNDS..=MAX_BACKUP_KDF_ROUNDS).contains(&rounds) {
+            return Err(ClientError::InvalidBackup);
+        }
+        let key = backup_key(password.as_bytes(), &header[9..9 + SALT_LEN], rounds);
+        let plaintext = ChaCha20Poly1305::new(Key::from_slice(&key))
+            .decrypt(
+                Nonce::from_slice(&header[9 + SALT_LEN..]),
+                Payload { msg: ciphertext, aad: header },
+            )
+            .map_err(|_| ClientError::BackupDecryptionFailed)?;
+        NoteBackup::try_from_slice(&plaintext).map_err(|_| ClientError::InvalidBackup)
+    }
+}
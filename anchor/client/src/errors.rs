@@ -0,0 +1,103 @@
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ClientError {
+    #[error("Note encryption failed")]
+    EncryptionFailed,
+
+    #[error("Invalid BIP39 mnemonic")]
+    InvalidMnemonic,
+
+    #[error("Seed must be at least 16 bytes")]
+    InvalidSeedLength,
+
+    #[error("Poseidon hashing failed")]
+    PoseidonError,
+
+    #[error("Invalid ext amount")]
+    InvalidExtAmount,
+
+    #[error("Insufficient shielded funds")]
+    InsufficientFunds,
+
+    #[error("Note mint does not match the transaction mint")]
+    MintMismatch,
+
+    #[error("Arithmetic overflow")]
+    ArithmeticOverflow,
+
+    #[error("Merkle tree is full")]
+    MerkleTreeFull,
+
+    #[error("Leaf index is not in the local tree")]
+    LeafIndexOutOfRange,
+
+    #[error("Event conflicts with an already synced leaf")]
+    ConflictingLeaf,
+
+    #[error("Invalid tree snapshot")]
+    InvalidSnapshot,
+
+    #[error("Tree changed while reading snapshot pages")]
+    SnapshotChanged,
+
+    #[error("Missing ExtData field: {0}")]
+    MissingExtDataField(&'static str),
+
+    #[error("Invalid public key")]
+    InvalidPubkey,
+
+    #[error("Memo is longer than MAX_MEMO_LEN")]
+    MemoTooLong,
+
+    #[error("ExtData version is not supported")]
+    UnsupportedExtDataVersion,
+
+    #[error("ExtData field needs a newer ExtData version: {0}")]
+    UnsupportedExtDataField(&'static str),
+
+    #[error("Fee exceeds max fee")]
+    FeeExceedsMaxFee,
+
+    #[error("Invalid payment address")]
+    InvalidPaymentAddress,
+
+    #[error("Payment address needs a newer client")]
+    UnsupportedPaymentAddress,
+
+    #[error("Note diversifier does not match the payment address")]
+    DiversifierMismatch,
+
+    #[error("Invalid transact return data")]
+    InvalidReturnData,
+
+    #[error("ExtData commits to more than one withdrawal mode")]
+    ConflictingWithdrawalModes,
+
+    #[error("A memo CPI needs a withdrawal with a non-empty UTF-8 memo")]
+    InvalidMemoCpi,
+
+    #[error("Invalid token amount")]
+    InvalidTokenAmount,
+
+    #[error("Account data does not match the expected layout")]
+    InvalidAccountData,
+
+    #[error("Invalid note backup")]
+    InvalidBackup,
+
+    #[error("Note backup version is not supported")]
+    UnsupportedBackupVersion,
+
+    #[error("Wrong backup password or corrupted backup")]
+    BackupDecryptionFailed,
+
+    #[error("Backup notes don't belong to these keys")]
+    BackupKeyMismatch,
+
+    #[error("Chain source returned an inconsistent answer")]
+    InvalidChainSource,
+
+    #[error("Index store failed: {0}")]
+    IndexStore(String),
+}
@@ -0,0 +1,374 @@
+//! ExtData hashing, byte-for-byte compatible with `calculate_complete_ext_data_hash`.
+//!
+//! The proof commits to `sha256(borsh(ExtData))`. Field order and encodings must
+//! match the program exactly: Pubkeys as 32 raw bytes, `ext_amount` as i64 LE,
+//! encrypted outputs as u32-length-prefixed byte vectors, `fee` as u64 LE.
+//! A non-empty memo follows `mint_address` as another length-prefixed vector;
+//! an empty one is left out entirely.
+//!
+//! That is the legacy layout, version 0. From version 1 the serialization
+//! starts with the version byte and always includes the memo; version 2
+//! appends `expiry_slot` and version 3 `max_fee`, both as u64 LE, version 4
+//! `relayer` as a Borsh `Option<Pubkey>`, version 5 `swap` as a Borsh
+//! `Option<SwapParams>`, version 6 `cpi` as a Borsh `Option<CpiTarget>`,
+//! version 7 `foreign_recipient` as a Borsh `Option<ForeignRecipient>` and
+//! version 8 `memo_cpi` as a Borsh `bool`. The program is told which version
+//! the client hashed with.
+
+use crate::errors::ClientError;
+use crate::utils::fr_to_be_bytes;
+use crate::utxo::SOL_MINT_ADDRESS;
+use anchor_lang::prelude::{borsh, AnchorSerialize, Pubkey};
+use ark_bn254::Fr;
+use ark_ff::PrimeField;
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+
+/// Matches the program's `MAX_MEMO_LEN`.
+pub const MAX_MEMO_LEN: usize = 128;
+
+/// Match the program's `EXT_DATA_VERSION_*` constants.
+pub const EXT_DATA_VERSION_LEGACY: u8 = 0;
+pub const EXT_DATA_VERSION: u8 = 8;
+pub const EXT_DATA_VERSION_EXPIRY: u8 = 2;
+pub const EXT_DATA_VERSION_MAX_FEE: u8 = 3;
+pub const EXT_DATA_VERSION_RELAYER: u8 = 4;
+pub const EXT_DATA_VERSION_SWAP: u8 = 5;
+pub const EXT_DATA_VERSION_CPI: u8 = 6;
+pub const EXT_DATA_VERSION_FOREIGN_RECIPIENT: u8 = 7;
+pub const EXT_DATA_VERSION_MEMO_CPI: u8 = 8;
+
+/// Matches the program's `ForeignRecipient`: a Wormhole chain id and the
+/// recipient's address there, left-padded to 32 bytes.
+#[derive(AnchorSerialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ForeignRecipient {
+    pub chain_id: u16,
+    pub address: [u8; 32],
+}
+
+/// Matches the program's `CpiTarget`: the withdrawal exits into `program`
+/// with instruction data hashing to `data_hash`.
+#[derive(AnchorSerialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CpiTarget {
+    pub program: Pubkey,
+    pub data_hash: [u8; 32],
+}
+
+impl CpiTarget {
+    /// Commits to the exact instruction the relayer will submit.
+    pub fn new(program: Pubkey, data: &[u8]) -> Self {
+        CpiTarget {
+            program,
+            data_hash: Sha256::digest(data).into(),
+        }
+    }
+}
+
+/// Matches the program's `SwapParams`: swap the withdrawn amount through
+/// `program` with instruction data hashing to `route_hash`, delivering at
+/// least `min_output_amount` of `output_mint` to the recipient.
+#[derive(AnchorSerialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapParams {
+    pub program: Pubkey,
+    pub output_mint: Pubkey,
+    pub min_output_amount: u64,
+    pub route_hash: [u8; 32],
+}
+
+impl SwapParams {
+    /// Commits to the exact swap instruction the relayer will submit.
+    pub fn new(program: Pubkey, output_mint: Pubkey, min_output_amount: u64, route_data: &[u8]) -> Self {
+        SwapParams {
+            program,
+            output_mint,
+            min_output_amount,
+            route_hash: Sha256::digest(route_data).into(),
+        }
+    }
+}
+
+#[derive(AnchorSerialize, Clone, Debug, PartialEq, Eq)]
+pub struct ExtData {
+    pub recipient: Pubkey,
+    pub ext_amount: i64,
+    pub encrypted_output1: Vec<u8>,
+    pub encrypted_output2: Vec<u8>,
+    pub fee: u64,
+    pub fee_recipient: Pubkey,
+    pub mint_address: Pubkey,
+    /// Shown to the recipient of a withdrawal, e.g. an exchange deposit memo.
+    #[borsh_skip]
+    pub memo: Vec<u8>,
+    /// Last slot the transaction may land in, 0 for none. Needs
+    /// `EXT_DATA_VERSION_EXPIRY`.
+    #[borsh_skip]
+    pub expiry_slot: u64,
+    /// Highest fee a relayer may charge. Needs `EXT_DATA_VERSION_MAX_FEE`.
+    #[borsh_skip]
+    pub max_fee: u64,
+    /// The only signer allowed to submit the transaction. Needs
+    /// `EXT_DATA_VERSION_RELAYER`.
+    #[borsh_skip]
+    pub relayer: Option<Pubkey>,
+    /// Swap the withdrawal is routed through. Needs `EXT_DATA_VERSION_SWAP`.
+    #[borsh_skip]
+    pub swap: Option<SwapParams>,
+    /// Program the withdrawal exits into. Needs `EXT_DATA_VERSION_CPI`.
+    #[borsh_skip]
+    pub cpi: Option<CpiTarget>,
+    /// Recipient on another chain. Needs `EXT_DATA_VERSION_FOREIGN_RECIPIENT`.
+    #[borsh_skip]
+    pub foreign_recipient: Option<ForeignRecipient>,
+    /// Also post the memo through the SPL Memo program. Needs
+    /// `EXT_DATA_VERSION_MEMO_CPI`.
+    #[borsh_skip]
+    pub memo_cpi: bool,
+    /// Layout `serialize_for_hash` uses; not itself part of the fields.
+    #[borsh_skip]
+    pub version: u8,
+}
+
+impl ExtData {
+    pub fn builder() -> ExtDataBuilder {
+        ExtDataBuilder::default()
+    }
+
+    pub fn serialize_for_hash(&self) -> Vec<u8> {
+        let mut serialized = Vec::new();
+        if self.version != EXT_DATA_VERSION_LEGACY {
+            serialized.push(self.version);
+        }
+        self.serialize(&mut serialized)
+            .expect("serializing into a Vec cannot fail");
+        if self.version != EXT_DATA_VERSION_LEGACY || !self.memo.is_empty() {
+            self.memo
+                .serialize(&mut serialized)
+                .expect("serializing into a Vec cannot fail");
+        }
+        if self.version >= EXT_DATA_VERSION_EXPIRY {
+            serialized.extend_from_slice(&self.expiry_slot.to_le_bytes());
+        }
+        if self.version >= EXT_DATA_VERSION_MAX_FEE {
+            serialized.extend_from_slice(&self.max_fee.to_le_bytes());
+        }
+        if self.version >= EXT_DATA_VERSION_RELAYER {
+            self.relayer
+                .serialize(&mut serialized)
+                .expect("serializing into a Vec cannot fail");
+        }
+        if self.version >= EXT_DATA_VERSION_SWAP {
+            self.swap
+                .serialize(&mut serialized)
+                .expect("serializing into a Vec cannot fail");
+        }
+        if self.version >= EXT_DATA_VERSION_CPI {
+            self.cpi
+                .serialize(&mut serialized)
+                .expect("serializing into a Vec cannot fail");
+        }
+        if self.version >= EXT_DATA_VERSION_FOREIGN_RECIPIENT {
+            self.foreign_recipient
+                .serialize(&mut serialized)
+                .expect("serializing into a Vec cannot fail");
+        }
+        if self.version >= EXT_DATA_VERSION_MEMO_CPI {
+            serialized.push(self.memo_cpi as u8);
+        }
+        serialized
+    }
+
+    /// The digest the program recomputes from instruction data.
+    pub fn hash(&self) -> [u8; 32] {
+        Sha256::digest(self.serialize_for_hash()).into()
+    }
+
+    /// The `extDataHash` public input: the digest read as a little-endian field
+    /// element, encoded big-endian like every other public input.
+    pub fn public_input(&self) -> [u8; 32] {
+        fr_to_be_bytes(&Fr::from_le_bytes_mod_order(&self.hash()))
+    }
+}
+
+#[derive(Default)]
+pub struct ExtDataBuilder {
+    recipient: Option<Pubkey>,
+    ext_amount: i64,
+    encrypted_output1: Vec<u8>,
+    encrypted_output2: Vec<u8>,
+    fee: u64,
+    fee_recipient: Option<Pubkey>,
+    mint_address: Option<Pubkey>,
+    memo: Vec<u8>,
+    expiry_slot: u64,
+    max_fee: Option<u64>,
+    relayer: Option<Pubkey>,
+    swap: Option<SwapParams>,
+    cpi: Option<CpiTarget>,
+    foreign_recipient: Option<ForeignRecipient>,
+    memo_cpi: bool,
+    version: u8,
+}
+
+impl ExtDataBuilder {
+    pub fn recipient(mut self, recipient: Pubkey) -> Self {
+        self.recipient = Some(recipient);
+        self
+    }
+
+    pub fn ext_amount(mut self, ext_amount: i64) -> Self {
+        self.ext_amount = ext_amount;
+        self
+    }
+
+    pub fn encrypted_outputs(mut self, encrypted_output1: Vec<u8>, encrypted_output2: Vec<u8>) -> Self {
+        self.encrypted_output1 = encrypted_output1;
+        self.encrypted_output2 = encrypted_output2;
+        self
+    }
+
+    pub fn fee(mut self, fee: u64) -> Self {
+        self.fee = fee;
+        self
+    }
+
+    pub fn fee_recipient(mut self, fee_recipient: Pubkey) -> Self {
+        self.fee_recipient = Some(fee_recipient);
+        self
+    }
+
+    /// Defaults to the SOL pseudo mint when not set.
+    pub fn mint_address(mut self, mint_address: Pubkey) -> Self {
+        self.mint_address = Some(mint_address);
+        self
+    }
+
+    /// At most `MAX_MEMO_LEN` bytes; empty by default.
+    pub fn memo(mut self, memo: Vec<u8>) -> Self {
+        self.memo = memo;
+        self
+    }
+
+    /// Bounds how long a relayer can hold the transaction before submitting
+    /// it. Requires `version(EXT_DATA_VERSION_EXPIRY)` or later.
+    pub fn expiry_slot(mut self, expiry_slot: u64) -> Self {
+        self.expiry_slot = expiry_slot;
+        self
+    }
+
+    /// Caps the fee the program accepts. Defaults to `fee` itself from
+    /// `EXT_DATA_VERSION_MAX_FEE`, and can't be set for older versions.
+    pub fn max_fee(mut self, max_fee: u64) -> Self {
+        self.max_fee = Some(max_fee);
+        self
+    }
+
+    /// Binds the transaction to the relayer that will sign it, so nobody who
+    /// sees it pending can submit it instead. Requires
+    /// `version(EXT_DATA_VERSION_RELAYER)` or later.
+    pub fn relayer(mut self, relayer: Pubkey) -> Self {
+        self.relayer = Some(relayer);
+        self
+    }
+
+    /// Swaps the withdrawn amount before it reaches the recipient, who must
+    /// then be a token account for `swap.output_mint`. Requires
+    /// `version(EXT_DATA_VERSION_SWAP)` or later.
+    pub fn swap(mut self, swap: SwapParams) -> Self {
+        self.swap = Some(swap);
+        self
+    }
+
+    /// Pays the withdrawal out through a CPI into `cpi.program` instead of a
+    /// transfer. Requires `version(EXT_DATA_VERSION_CPI)` or later.
+    pub fn cpi(mut self, cpi: CpiTarget) -> Self {
+        self.cpi = Some(cpi);
+        self
+    }
+
+    /// Pays the withdrawal out on another chain through a Wormhole message.
+    /// Requires `version(EXT_DATA_VERSION_FOREIGN_RECIPIENT)` or later.
+    pub fn foreign_recipient(mut self, foreign_recipient: ForeignRecipient) -> Self {
+        self.foreign_recipient = Some(foreign_recipient);
+        self
+    }
+
+    /// Has the program post the memo through the SPL Memo program after
+    /// paying out, for exchanges that only credit deposits carrying a Memo
+    /// instruction. Needs a withdrawal with a UTF-8 memo, and
+    /// `version(EXT_DATA_VERSION_MEMO_CPI)` or later.
+    pub fn memo_cpi(mut self) -> Self {
+        self.memo_cpi = true;
+        self
+    }
+
+    /// Defaults to `EXT_DATA_VERSION_LEGACY`, which every deployed program
+    /// accepts.
+    pub fn version(mut self, version: u8) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub fn build(self) -> Result<ExtData, ClientError> {
+        if self.memo.len() > MAX_MEMO_LEN {
+            return Err(ClientError::MemoTooLong);
+        }
+        if self.version > EXT_DATA_VERSION {
+            return Err(ClientError::UnsupportedExtDataVersion);
+        }
+        // Older layouts would silently drop the deadline from the hash
+        if self.expiry_slot != 0 && self.version < EXT_DATA_VERSION_EXPIRY {
+            return Err(ClientError::UnsupportedExtDataField("expiry_slot"));
+        }
+        if self.max_fee.is_some() && self.version < EXT_DATA_VERSION_MAX_FEE {
+            return Err(ClientError::UnsupportedExtDataField("max_fee"));
+        }
+        if self.relayer.is_some() && self.version < EXT_DATA_VERSION_RELAYER {
+            return Err(ClientError::UnsupportedExtDataField("relayer"));
+        }
+        if self.swap.is_some() && self.version < EXT_DATA_VERSION_SWAP {
+            return Err(ClientError::UnsupportedExtDataField("swap"));
+        }
+        if self.cpi.is_some() && self.version < EXT_DATA_VERSION_CPI {
+            return Err(ClientError::UnsupportedExtDataField("cpi"));
+        }
+        if self.foreign_recipient.is_some() && self.version < EXT_DATA_VERSION_FOREIGN_RECIPIENT {
+            return Err(ClientError::UnsupportedExtDataField("foreign_recipient"));
+        }
+        if self.memo_cpi {
+            if self.version < EXT_DATA_VERSION_MEMO_CPI {
+                return Err(ClientError::UnsupportedExtDataField("memo_cpi"));
+            }
+            if self.ext_amount >= 0 || self.memo.is_empty() || std::str::from_utf8(&self.memo).is_err() {
+                return Err(ClientError::InvalidMemoCpi);
+            }
+        }
+        let modes = [self.swap.is_some(), self.cpi.is_some(), self.foreign_recipient.is_some()];
+        if modes.iter().filter(|set| **set).count() > 1 {
+            return Err(ClientError::ConflictingWithdrawalModes);
+        }
+        let max_fee = self.max_fee.unwrap_or(self.fee);
+        if self.fee > max_fee {
+            return Err(ClientError::FeeExceedsMaxFee);
+        }
+        Ok(ExtData {
+            recipient: self.recipient.ok_or(ClientError::MissingExtDataField("recipient"))?,
+            ext_amount: self.ext_amount,
+            encrypted_output1: self.encrypted_output1,
+            encrypted_output2: self.encrypted_output2,
+            fee: self.fee,
+            fee_recipient: self.fee_recipient.ok_or(ClientError::MissingExtDataField("fee_recipient"))?,
+            mint_address: self
+                .mint_address
+                .unwrap_or_else(|| Pubkey::from_str(SOL_MINT_ADDRESS).expect("SOL mint constant is valid base58")),
+            memo: self.memo,
+            expiry_slot: self.expiry_slot,
+            max_fee,
+            relayer: self.relayer,
+            swap: self.swap,
+            cpi: self.cpi,
+            foreign_recipient: self.foreign_recipient,
+            memo_cpi: self.memo_cpi,
+            version: self.version,
+        })
+    }
+}
@@ -0,0 +1,317 @@
+//! Indexing pool transactions into a canonical leaf list and nullifier set.
+//!
+//! An [`Indexer`] takes confirmed pool transactions from a
+//! [`TransactionStream`] (a `logsSubscribe` websocket followed by
+//! `getTransaction`, or a Geyser plugin's transaction notifications),
+//! decodes the commitment events from their logs and the spent nullifiers
+//! from their transact instruction data, and keeps both in an
+//! [`IndexStore`]. Relayers and explorers query it directly; wallets can
+//! scan it through [`ChainSource`].
+//!
+//! [`MemoryStore`] keeps everything in memory. With the `sled` feature,
+//! [`SledStore`] persists it in an embedded sled database, together with the
+//! cursor of the last transaction applied, so a restarted indexer resumes
+//! where it stopped.
+
+use crate::errors::ClientError;
+use crate::ledger_history::commitment_events_from_logs;
+use crate::recovery::ChainSource;
+use crate::tree_sync::CommitmentEvent;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+
+/// Offset of `proof.input_nullifiers` in transact instruction data: the
+/// discriminator, the three proof points, then root, public amount and
+/// ExtData hash.
+const TRANSACT_NULLIFIERS_OFFSET: usize = 8 + 64 + 128 + 64 + 3 * 32;
+
+/// Anchor's discriminator for the `transact` instruction.
+pub fn transact_discriminator() -> [u8; 8] {
+    Sha256::digest(b"global:transact")[..8]
+        .try_into()
+        .expect("sha256 is 32 bytes")
+}
+
+/// One confirmed transaction that invoked the pool, in the shape both
+/// websocket and Geyser sources can produce.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PoolTransaction {
+    pub signature: String,
+    pub slot: u64,
+    pub logs: Vec<String>,
+    /// Data of every instruction in the transaction addressed to the pool
+    /// program, inner instructions included.
+    pub instructions: Vec<Vec<u8>>,
+}
+
+/// What one transaction added to the pool.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PoolChanges {
+    pub commitments: Vec<CommitmentEvent>,
+    pub nullifiers: Vec<[u8; 32]>,
+}
+
+/// Spent nullifiers read straight from transact instruction data.
+pub fn nullifiers_from_instructions(instructions: &[Vec<u8>]) -> Vec<[u8; 32]> {
+    let discriminator = transact_discriminator();
+    instructions
+        .iter()
+        .filter(|data| data.starts_with(&discriminator))
+        .filter_map(|data| data.get(TRANSACT_NULLIFIERS_OFFSET..TRANSACT_NULLIFIERS_OFFSET + 64))
+        .flat_map(|nullifiers| nullifiers.chunks(32).map(|n| n.try_into().expect("32-byte chunk")))
+        .collect()
+}
+
+pub fn decode_pool_transaction(transaction: &PoolTransaction) -> PoolChanges {
+    PoolChanges {
+        commitments: commitment_events_from_logs(&transaction.logs),
+        nullifiers: nullifiers_from_instructions(&transaction.instructions),
+    }
+}
+
+/// Where a stream stopped: the last transaction the indexer applied.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IndexCursor {
+    pub slot: u64,
+    pub signature: String,
+}
+
+/// Confirmed pool transactions in ledger order. Implemented over a websocket
+/// or Geyser subscription by the caller.
+pub trait TransactionStream {
+    /// The next transaction, or `None` once the stream is closed.
+    fn next_transaction(&mut self) -> Result<Option<PoolTransaction>, ClientError>;
+}
+
+/// Storage for the index. Writes for one transaction are made through a
+/// single `apply` call, so a persistent store can make them atomic.
+pub trait IndexStore {
+    fn leaf(&self, index: u64) -> Result<Option<CommitmentEvent>, ClientError>;
+
+    /// Leaves with `index >= from_index`, in leaf order, at most `limit`.
+    fn leaves(&self, from_index: u64, limit: usize) -> Result<Vec<CommitmentEvent>, ClientError>;
+
+    fn is_spent(&self, nullifier: &[u8; 32]) -> Result<bool, ClientError>;
+
+    fn cursor(&self) -> Result<Option<IndexCursor>, ClientError>;
+
+    fn apply(&mut self, changes: &PoolChanges, cursor: &IndexCursor) -> Result<(), ClientError>;
+}
+
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    leaves: BTreeMap<u64, CommitmentEvent>,
+    nullifiers: HashSet<[u8; 32]>,
+    cursor: Option<IndexCursor>,
+}
+
+impl IndexStore for MemoryStore {
+    fn leaf(&self, index: u64) -> Result<Option<CommitmentEvent>, ClientError> {
+        Ok(self.leaves.get(&index).cloned())
+    }
+
+    fn leaves(&self, from_index: u64, limit: usize) -> Result<Vec<CommitmentEvent>, ClientError> {
+        Ok(self.leaves.range(from_index..).take(limit).map(|(_, event)| event.clone()).collect())
+    }
+
+    fn is_spent(&self, nullifier: &[u8; 32]) -> Result<bool, ClientError> {
+        Ok(self.nullifiers.contains(nullifier))
+    }
+
+    fn cursor(&self) -> Result<Option<IndexCursor>, ClientError> {
+        Ok(self.cursor.clone())
+    }
+
+    fn apply(&mut self, changes: &PoolChanges, cursor: &IndexCursor) -> Result<(), ClientError> {
+        for event in &changes.commitments {
+            self.leaves.insert(event.index, event.clone());
+        }
+        self.nullifiers.extend(changes.nullifiers.iter().copied());
+        self.cursor = Some(cursor.clone());
+        Ok(())
+    }
+}
+
+/// Leaves served per `ChainSource::commitment_events` page.
+pub const INDEX_PAGE_SIZE: usize = 1024;
+
+pub struct Indexer<S> {
+    store: S,
+}
+
+impl<S: IndexStore> Indexer<S> {
+    pub fn new(store: S) -> Self {
+        Self { store }
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    pub fn into_store(self) -> S {
+        self.store
+    }
+
+    /// Where to resume the subscription from, if anything was applied.
+    pub fn cursor(&self) -> Result<Option<IndexCursor>, ClientError> {
+        self.store.cursor()
+    }
+
+    /// Decodes and stores one transaction. A transaction seen again, e.g.
+    /// after a reconnect replays a few, is a no-op; an event that disagrees
+    /// with a leaf already indexed fails with `ConflictingLeaf` and stores
+    /// nothing.
+    pub fn apply(&mut self, transaction: &PoolTransaction) -> Result<PoolChanges, ClientError> {
+        let mut changes = decode_pool_transaction(transaction);
+        let mut new_commitments = Vec::with_capacity(changes.commitments.len());
+        for event in changes.commitments {
+            match self.store.leaf(event.index)? {
+                Some(existing) if existing != event => return Err(ClientError::ConflictingLeaf),
+                Some(_) => {}
+                None => new_commitments.push(event),
+            }
+        }
+        changes.commitments = new_commitments;
+
+        let cursor = IndexCursor {
+            slot: transaction.slot,
+            signature: transaction.signature.clone(),
+        };
+        self.store.apply(&changes, &cursor)?;
+        Ok(changes)
+    }
+
+    /// Applies transactions until the stream closes.
+    pub fn run(&mut self, stream: &mut impl TransactionStream) -> Result<(), ClientError> {
+        while let Some(transaction) = stream.next_transaction()? {
+            self.apply(&transaction)?;
+        }
+        Ok(())
+    }
+
+    /// Number of leaves from index 0 with no gap, i.e. the tree's
+    /// `next_index` as far as this index can vouch for it.
+    pub fn contiguous_leaves(&self) -> Result<u64, ClientError> {
+        let mut next = 0u64;
+        loop {
+            let page = self.store.leaves(next, INDEX_PAGE_SIZE)?;
+            for event in &page {
+                if event.index != next {
+                    return Ok(next);
+                }
+                next += 1;
+            }
+            if page.len() < INDEX_PAGE_SIZE {
+                return Ok(next);
+            }
+        }
+    }
+}
+
+impl<S: IndexStore> ChainSource for Indexer<S> {
+    fn commitment_events(&mut self, from_index: u64) -> Result<Vec<CommitmentEvent>, ClientError> {
+        self.store.leaves(from_index, INDEX_PAGE_SIZE)
+    }
+
+    fn nullifiers_spent(&mut self, nullifiers: &[[u8; 32]]) -> Result<Vec<bool>, ClientError> {
+        nullifiers.iter().map(|nullifier| self.store.is_spent(nullifier)).collect()
+    }
+}
+
+#[cfg(feature = "sled")]
+pub use sled_store::SledStore;
+
+#[cfg(feature = "sled")]
+mod sled_store {
+    use super::{IndexCursor, IndexStore, PoolChanges};
+    use crate::errors::ClientError;
+    use crate::tree_sync::CommitmentEvent;
+    use anchor_lang::prelude::{AnchorDeserialize, AnchorSerialize};
+    use sled::transaction::{ConflictableTransactionError, TransactionError};
+    use sled::Transactional;
+
+    const CURSOR_KEY: &[u8] = b"cursor";
+
+    fn store_error(error: impl std::fmt::Display) -> ClientError {
+        ClientError::IndexStore(error.to_string())
+    }
+
+    /// `IndexStore` in an embedded sled database: leaves keyed by big-endian
+    /// index so iteration is in leaf order, nullifiers as keys, and the
+    /// cursor, each transaction written atomically.
+    pub struct SledStore {
+        db: sled::Db,
+        leaves: sled::Tree,
+        nullifiers: sled::Tree,
+    }
+
+    impl SledStore {
+        pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self, ClientError> {
+            let db = sled::open(path).map_err(store_error)?;
+            let leaves = db.open_tree("leaves").map_err(store_error)?;
+            let nullifiers = db.open_tree("nullifiers").map_err(store_error)?;
+            Ok(Self { db, leaves, nullifiers })
+        }
+
+        pub fn flush(&self) -> Result<(), ClientError> {
+            self.db.flush().map_err(store_error)?;
+            Ok(())
+        }
+    }
+
+    fn decode_leaf(bytes: &[u8]) -> Result<CommitmentEvent, ClientError> {
+        CommitmentEvent::try_from_slice(bytes).map_err(store_error)
+    }
+
+    impl IndexStore for SledStore {
+        fn leaf(&self, index: u64) -> Result<Option<CommitmentEvent>, ClientError> {
+            self.leaves
+                .get(index.to_be_bytes())
+                .map_err(store_error)?
+                .map(|bytes| decode_leaf(&bytes))
+                .transpose()
+        }
+
+        fn leaves(&self, from_index: u64, limit: usize) -> Result<Vec<CommitmentEvent>, ClientError> {
+            self.leaves
+                .range(from_index.to_be_bytes()..)
+                .take(limit)
+                .map(|entry| decode_leaf(&entry.map_err(store_error)?.1))
+                .collect()
+        }
+
+        fn is_spent(&self, nullifier: &[u8; 32]) -> Result<bool, ClientError> {
+            self.nullifiers.contains_key(nullifier).map_err(store_error)
+        }
+
+        fn cursor(&self) -> Result<Option<IndexCursor>, ClientError> {
+            let Some(bytes) = self.db.get(CURSOR_KEY).map_err(store_error)? else {
+                return Ok(None);
+            };
+            let (slot, signature) = <(u64, String)>::try_from_slice(&bytes).map_err(store_error)?;
+            Ok(Some(IndexCursor { slot, signature }))
+        }
+
+        fn apply(&mut self, changes: &PoolChanges, cursor: &IndexCursor) -> Result<(), ClientError> {
+            let cursor = (cursor.slot, cursor.signature.clone()).try_to_vec().map_err(store_error)?;
+            let leaves = changes
+                .commitments
+                .iter()
+                .map(|event| Ok((event.index.to_be_bytes(), event.try_to_vec().map_err(store_error)?)))
+                .collect::<Result<Vec<_>, ClientError>>()?;
+
+            (&*self.db, &self.leaves, &self.nullifiers)
+                .transaction(|(db, leaves_tree, nullifiers_tree)| {
+                    for (index, event) in &leaves {
+                        leaves_tree.insert(index.as_slice(), event.as_slice())?;
+                    }
+                    for nullifier in &changes.nullifiers {
+                        nullifiers_tree.insert(nullifier.as_slice(), &[] as &[u8])?;
+                    }
+                    db.insert(CURSOR_KEY, cursor.as_slice())?;
+                    Ok::<(), ConflictableTransactionError<()>>(())
+                })
+                .map_err(|error: TransactionError<()>| store_error(format!("{error:?}")))
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Deterministic shielded key derivation from a BIP39 seed.
+//!
+//! The circuit has no signatures: a UTXO owner is whoever knows `privateKey`
+//! such that `publicKey = Poseidon(privateKey)`, and the same private key feeds
+//! the nullifier through `Signature()`. The spending key is therefore also the
+//! nullifier key; the x25519 encryption key is derived independently so it can
+//! be handed to a scanning service without exposing spend authority.
+//!
+//! All keys are HKDF-SHA256 expansions of the seed, domain separated by purpose
+//! and account index. Diversified payment addresses (see `address`) derive
+//! further spending keys from the account's; the encryption key is shared.
+
+use crate::address::{Diversifier, PaymentAddress, DIVERSIFIER_LEN};
+use crate::errors::ClientError;
+use crate::note::{EncryptionKeypair, DETECTION_TAG_LEN};
+use crate::utils::poseidon_hash;
+use ark_bn254::Fr;
+use ark_ff::PrimeField;
+use bip39::Mnemonic;
+use hkdf::Hkdf;
+use sha2::{Digest, Sha256};
+
+const KEY_DERIVATION_SALT: &[u8] = b"zkcash-shielded-keys";
+const SPENDING_KEY_INFO: &[u8] = b"spending";
+const ENCRYPTION_KEY_INFO: &[u8] = b"encryption";
+const DIVERSIFIER_KEY_INFO: &[u8] = b"diversifier";
+const MIN_SEED_LEN: usize = 16;
+
+pub struct ShieldedKeys {
+    pub account_index: u32,
+    spending_key: Fr,
+    public_key: Fr,
+    encryption_keypair: EncryptionKeypair,
+    diversifier_key: [u8; 32],
+}
+
+fn expand<const N: usize>(hkdf: &Hkdf<Sha256>, purpose: &[u8], account_index: u32) -> [u8; N] {
+    let info = [purpose, &account_index.to_le_bytes()].concat();
+    let mut okm = [0u8; N];
+    hkdf.expand(&info, &mut okm)
+        .expect("key sizes are valid HKDF-SHA256 output lengths");
+    okm
+}
+
+impl ShieldedKeys {
+    pub fn from_mnemonic(phrase: &str, passphrase: &str, account_index: u32) -> Result<Self, ClientError> {
+        let mnemonic = Mnemonic::parse(phrase).map_err(|_| ClientError::InvalidMnemonic)?;
+        Self::from_seed(&mnemonic.to_seed(passphrase), account_index)
+    }
+
+    pub fn from_seed(seed: &[u8], account_index: u32) -> Result<Self, ClientError> {
+        if seed.len() < MIN_SEED_LEN {
+            return Err(ClientError::InvalidSeedLength);
+        }
+        let hkdf = Hkdf::<Sha256>::new(Some(KEY_DERIVATION_SALT), seed);
+
+        // 64 bytes reduced mod r keeps the spending key's distribution uniform
+        let spending_bytes: [u8; 64] = expand(&hkdf, SPENDING_KEY_INFO, account_index);
+        let spending_key = Fr::from_be_bytes_mod_order(&spending_bytes);
+        let public_key = poseidon_hash(&[spending_key])?;
+
+        let encryption_bytes: [u8; 32] = expand(&hkdf, ENCRYPTION_KEY_INFO, account_index);
+        let diversifier_key: [u8; 32] = expand(&hkdf, DIVERSIFIER_KEY_INFO, account_index);
+
+        Ok(ShieldedKeys {
+            account_index,
+            spending_key,
+            public_key,
+            encryption_keypair: EncryptionKeypair::from_secret_bytes(encryption_bytes),
+            diversifier_key,
+        })
+    }
+
+    /// The circuit's `inPrivateKey`.
+    pub fn spending_key(&self) -> Fr {
+        self.spending_key
+    }
+
+    /// The circuit's `outPubkey`: `Poseidon(spending_key)`.
+    pub fn public_key(&self) -> Fr {
+        self.public_key
+    }
+
+    pub fn encryption_keypair(&self) -> &EncryptionKeypair {
+        &self.encryption_keypair
+    }
+
+    /// Diversifier of the `index`th payment address. Index 0 is the default
+    /// address; the others look random and can't be linked to each other
+    /// without the diversifier key.
+    pub fn diversifier(&self, index: u32) -> Diversifier {
+        if index == 0 {
+            return Diversifier::default();
+        }
+        let digest = Sha256::new()
+            .chain_update(self.diversifier_key)
+            .chain_update(index.to_le_bytes())
+            .finalize();
+        Diversifier(digest[..DIVERSIFIER_LEN].try_into().expect("SHA-256 digests are 32 bytes"))
+    }
+
+    pub fn payment_address(&self, index: u32) -> Result<PaymentAddress, ClientError> {
+        let diversifier = self.diversifier(index);
+        Ok(PaymentAddress {
+            diversifier,
+            owner_pubkey: self.public_key_for(&diversifier)?,
+            transmission_key: self.encryption_keypair.transmission_key(&diversifier),
+            detection_tag_len: DETECTION_TAG_LEN as u8,
+        })
+    }
+
+    /// The `inPrivateKey` of notes paid to `diversifier`'s address.
+    pub fn spending_key_for(&self, diversifier: &Diversifier) -> Result<Fr, ClientError> {
+        diversifier.spending_key(self.spending_key)
+    }
+
+    /// The `outPubkey` of `diversifier`'s address.
+    pub fn public_key_for(&self, diversifier: &Diversifier) -> Result<Fr, ClientError> {
+        if diversifier.is_default() {
+            return Ok(self.public_key);
+        }
+        poseidon_hash(&[self.spending_key_for(diversifier)?])
+    }
+
+    /// `Signature()` from keypair.circom: `Poseidon(privateKey, commitment, merklePath)`.
+    pub fn sign(&self, commitment: Fr, leaf_index: u64) -> Result<Fr, ClientError> {
+        self.sign_for(&Diversifier::default(), commitment, leaf_index)
+    }
+
+    pub fn sign_for(&self, diversifier: &Diversifier, commitment: Fr, leaf_index: u64) -> Result<Fr, ClientError> {
+        poseidon_hash(&[self.spending_key_for(diversifier)?, commitment, Fr::from(leaf_index)])
+    }
+
+    /// Nullifier of the note with `commitment` stored at `leaf_index`:
+    /// `Poseidon(commitment, merklePath, signature)`.
+    pub fn nullifier(&self, commitment: Fr, leaf_index: u64) -> Result<Fr, ClientError> {
+        self.nullifier_for(&Diversifier::default(), commitment, leaf_index)
+    }
+
+    /// Same as `nullifier`, for a note paid to `diversifier`'s address.
+    pub fn nullifier_for(&self, diversifier: &Diversifier, commitment: Fr, leaf_index: u64) -> Result<Fr, ClientError> {
+        let signature = self.sign_for(diversifier, commitment, leaf_index)?;
+        poseidon_hash(&[commitment, Fr::from(leaf_index), signature])
+    }
+}
@@ -0,0 +1,103 @@
+//! Recovery from transaction history alone.
+//!
+//! In `EventOnly` output storage the commitment PDAs carry no encrypted
+//! output, so the only copy of a note is the commitment event in the logs of
+//! the transaction that created it. [`LedgerHistory`] collects those events
+//! from transaction logs (fetched from an archival RPC or an indexer) and
+//! serves them to [`scan_notes`](crate::recovery::scan_notes) as a
+//! [`ChainSource`].
+
+use crate::errors::ClientError;
+use crate::recovery::ChainSource;
+use crate::tree_sync::CommitmentEvent;
+use anchor_lang::prelude::AnchorDeserialize;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+/// Name of the program's per-output event.
+pub const COMMITMENT_EVENT_NAME: &str = "CommitmentData";
+
+/// Prefix the runtime puts on `sol_log_data` lines, which `emit!` uses.
+const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+
+/// Anchor's event discriminator: the first 8 bytes of
+/// `sha256("event:<name>")`.
+pub fn commitment_event_discriminator() -> [u8; 8] {
+    let hash = Sha256::digest(format!("event:{COMMITMENT_EVENT_NAME}").as_bytes());
+    hash[..8].try_into().expect("sha256 is 32 bytes")
+}
+
+/// Decodes every commitment event in one transaction's log messages, in log
+/// order. Other program data lines, including other events, are skipped.
+pub fn commitment_events_from_logs(logs: &[String]) -> Vec<CommitmentEvent> {
+    let discriminator = commitment_event_discriminator();
+    logs.iter()
+        .filter_map(|line| line.strip_prefix(PROGRAM_DATA_PREFIX))
+        .filter_map(|data| STANDARD.decode(data).ok())
+        .filter_map(|data| {
+            let payload = data.strip_prefix(discriminator.as_slice())?;
+            CommitmentEvent::try_from_slice(payload).ok()
+        })
+        .collect()
+}
+
+/// Commitment events gathered from transaction logs, keyed by leaf index.
+/// Nullifier lookups go to `nullifiers_spent`, which the caller backs with
+/// `getMultipleAccounts` on the nullifier PDAs.
+pub struct LedgerHistory<N> {
+    events: BTreeMap<u64, CommitmentEvent>,
+    nullifiers_spent: N,
+}
+
+impl<N> LedgerHistory<N>
+where
+    N: FnMut(&[[u8; 32]]) -> Result<Vec<bool>, ClientError>,
+{
+    pub fn new(nullifiers_spent: N) -> Self {
+        Self {
+            events: BTreeMap::new(),
+            nullifiers_spent,
+        }
+    }
+
+    /// Adds the commitment events in one transaction's logs and returns how
+    /// many were new. Transactions may be added in any order and more than
+    /// once, but two different events for the same leaf are rejected.
+    pub fn add_transaction_logs(&mut self, logs: &[String]) -> Result<usize, ClientError> {
+        let mut added = 0;
+        for event in commitment_events_from_logs(logs) {
+            match self.events.get(&event.index) {
+                Some(existing) if *existing != event => return Err(ClientError::ConflictingLeaf),
+                Some(_) => {}
+                None => {
+                    self.events.insert(event.index, event);
+                    added += 1;
+                }
+            }
+        }
+        Ok(added)
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+impl<N> ChainSource for LedgerHistory<N>
+where
+    N: FnMut(&[[u8; 32]]) -> Result<Vec<bool>, ClientError>,
+{
+    fn commitment_events(&mut self, from_index: u64) -> Result<Vec<CommitmentEvent>, ClientError> {
+        Ok(self.events.range(from_index..).map(|(_, event)| event.clone()).collect())
+    }
+
+    fn nullifiers_spent(&mut self, nullifiers: &[[u8; 32]]) -> Result<Vec<bool>, ClientError> {
+        (self.nullifiers_spent)(nullifiers)
+    }
+}
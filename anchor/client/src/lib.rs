@@ -0,0 +1,27 @@
+//! Rust client SDK for the zkcash program.
+//!
+//! Everything in here runs off-chain: wallets and relayers use it to build the
+//! inputs the program expects without re-implementing the TypeScript client.
+
+pub mod address;
+pub mod backup;
+pub mod errors;
+pub mod ext_data;
+pub mod indexer;
+pub mod keys;
+pub mod ledger_history;
+pub mod note;
+pub mod nullifier_filter;
+#[cfg(feature = "program")]
+pub mod program;
+pub mod recovery;
+pub mod sync_checkpoint;
+pub mod transaction;
+pub mod tree_frontier;
+pub mod tree_snapshot;
+pub mod tree_sync;
+pub mod utils;
+pub mod utxo;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
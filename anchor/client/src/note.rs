@@ -0,0 +1,265 @@
+//! Encryption of the notes carried in `encrypted_output1` / `encrypted_output2`.
+//!
+//! The program only hashes these bytes into ExtData and emits them, so the format
+//! is purely a wallet concern. An encrypted note is laid out as:
+//!
+//! | offset | length | field                                           |
+//! |--------|--------|-------------------------------------------------|
+//! | 0      | 1      | format version (`NOTE_VERSION`)                 |
+//! | 1      | 4      | detection tag                                   |
+//! | 5      | 32     | ephemeral x25519 public key                     |
+//! | 37     | 99     | ChaCha20-Poly1305 ciphertext (83) + tag (16)    |
+//!
+//! The plaintext is `amount (u64 LE) || blinding (32, big-endian) || mint (32)
+//! || diversifier (11)`, the diversifier being that of the payment address the
+//! note was sent to (see `address`). The AEAD key is HKDF-SHA256 over the
+//! x25519 shared secret, salted with the ephemeral public key. Each note uses a
+//! fresh ephemeral key, so the nonce is fixed to zero.
+//!
+//! The detection tag is a second, short HKDF output of the same shared secret.
+//! Only the recipient's encryption (incoming viewing) key can recompute it, so
+//! it doesn't link notes, but it lets a wallet discard almost every output
+//! after the key agreement without deriving the AEAD key or decrypting. An
+//! indexer can also serve just the tag and ephemeral key of each output and
+//! leave the full ciphertext to be fetched for matches only.
+
+use crate::address::{Diversifier, PaymentAddress, DIVERSIFIER_LEN};
+use crate::errors::ClientError;
+use anchor_lang::prelude::Pubkey;
+use chacha20poly1305::aead::{Aead, NewAead, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use hkdf::Hkdf;
+use rand_core::{CryptoRng, RngCore};
+use sha2::Sha256;
+use x25519_dalek::{PublicKey, StaticSecret, X25519_BASEPOINT_BYTES};
+
+pub const NOTE_VERSION: u8 = 3;
+pub const NOTE_PLAINTEXT_LEN: usize = 8 + 32 + 32 + DIVERSIFIER_LEN;
+pub const NOTE_TAG_LEN: usize = 16;
+pub const DETECTION_TAG_LEN: usize = 4;
+pub const ENCRYPTED_NOTE_LEN: usize = 1 + DETECTION_TAG_LEN + 32 + NOTE_PLAINTEXT_LEN + NOTE_TAG_LEN;
+
+const NOTE_KDF_INFO: &[u8] = b"zkcash-note-v1";
+const DETECTION_TAG_INFO: &[u8] = b"zkcash-detection-tag-v1";
+const NOTE_NONCE: [u8; 12] = [0u8; 12];
+
+/// The part of a UTXO the recipient cannot derive on their own.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Note {
+    pub amount: u64,
+    /// Blinding factor as a big-endian BN254 field element.
+    pub blinding: [u8; 32],
+    pub mint_address: Pubkey,
+    /// Which of the recipient's payment addresses was paid; it selects the
+    /// key that spends the note.
+    pub diversifier: Diversifier,
+}
+
+impl Note {
+    fn to_plaintext(&self) -> [u8; NOTE_PLAINTEXT_LEN] {
+        let mut bytes = [0u8; NOTE_PLAINTEXT_LEN];
+        bytes[..8].copy_from_slice(&self.amount.to_le_bytes());
+        bytes[8..40].copy_from_slice(&self.blinding);
+        bytes[40..72].copy_from_slice(self.mint_address.as_ref());
+        bytes[72..].copy_from_slice(&self.diversifier.0);
+        bytes
+    }
+
+    fn from_plaintext(bytes: &[u8]) -> Option<Note> {
+        if bytes.len() != NOTE_PLAINTEXT_LEN {
+            return None;
+        }
+        Some(Note {
+            amount: u64::from_le_bytes(bytes[..8].try_into().ok()?),
+            blinding: bytes[8..40].try_into().ok()?,
+            mint_address: Pubkey::new_from_array(bytes[40..72].try_into().ok()?),
+            diversifier: Diversifier(bytes[72..].try_into().ok()?),
+        })
+    }
+}
+
+/// x25519 keypair notes are encrypted to.
+pub struct EncryptionKeypair {
+    secret: StaticSecret,
+    public: PublicKey,
+}
+
+impl EncryptionKeypair {
+    pub fn from_secret_bytes(bytes: [u8; 32]) -> Self {
+        let secret = StaticSecret::from(bytes);
+        let public = PublicKey::from(&secret);
+        EncryptionKeypair { secret, public }
+    }
+
+    pub fn generate<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        let secret = StaticSecret::new(rng);
+        let public = PublicKey::from(&secret);
+        EncryptionKeypair { secret, public }
+    }
+
+    pub fn public_key(&self) -> [u8; 32] {
+        self.public.to_bytes()
+    }
+
+    /// The key notes to `diversifier`'s address are encrypted to. For the
+    /// default diversifier this is `public_key`.
+    pub fn transmission_key(&self, diversifier: &Diversifier) -> [u8; 32] {
+        self.secret
+            .diffie_hellman(&PublicKey::from(diversifier.base_point()))
+            .to_bytes()
+    }
+}
+
+/// Salted with the ephemeral key only: the recipient can't know which of its
+/// transmission keys the sender used.
+fn note_kdf(shared_secret: &[u8; 32], ephemeral_public: &[u8; 32]) -> Hkdf<Sha256> {
+    Hkdf::<Sha256>::new(Some(ephemeral_public), shared_secret)
+}
+
+fn derive_note_key(kdf: &Hkdf<Sha256>) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    kdf.expand(NOTE_KDF_INFO, &mut key)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    key
+}
+
+fn derive_detection_tag(kdf: &Hkdf<Sha256>) -> [u8; DETECTION_TAG_LEN] {
+    let mut tag = [0u8; DETECTION_TAG_LEN];
+    kdf.expand(DETECTION_TAG_INFO, &mut tag)
+        .expect("DETECTION_TAG_LEN is a valid HKDF-SHA256 output length");
+    tag
+}
+
+/// The parts of an encrypted note that precede the ciphertext.
+struct NoteHeader<'a> {
+    /// The version byte and detection tag, which the AEAD authenticates so
+    /// neither can be swapped without failing decryption.
+    aad: &'a [u8],
+    detection_tag: [u8; DETECTION_TAG_LEN],
+    ephemeral_public: [u8; 32],
+    ciphertext: &'a [u8],
+}
+
+impl<'a> NoteHeader<'a> {
+    fn parse(encrypted: &'a [u8]) -> Option<Self> {
+        if encrypted.len() != ENCRYPTED_NOTE_LEN || encrypted[0] != NOTE_VERSION {
+            return None;
+        }
+        let (aad, rest) = encrypted.split_at(1 + DETECTION_TAG_LEN);
+        let (ephemeral_public, ciphertext) = rest.split_at(32);
+        Some(NoteHeader {
+            aad,
+            detection_tag: aad[1..].try_into().ok()?,
+            ephemeral_public: ephemeral_public.try_into().ok()?,
+            ciphertext,
+        })
+    }
+
+    /// Key derivation for `keypair`, or `None` when the shared secret is
+    /// degenerate.
+    fn kdf(&self, keypair: &EncryptionKeypair) -> Option<Hkdf<Sha256>> {
+        let shared_secret = keypair.secret.diffie_hellman(&PublicKey::from(self.ephemeral_public));
+        if shared_secret.as_bytes() == &[0u8; 32] {
+            return None;
+        }
+        Some(note_kdf(shared_secret.as_bytes(), &self.ephemeral_public))
+    }
+}
+
+/// Encrypts `note` to the recipient's x25519 public key, producing the bytes to
+/// pass as an encrypted output. This is the recipient's default address; use
+/// [`encrypt_note_to_address`] for diversified ones.
+pub fn encrypt_note<R: RngCore + CryptoRng>(
+    note: &Note,
+    recipient_public: &[u8; 32],
+    rng: &mut R,
+) -> Result<Vec<u8>, ClientError> {
+    encrypt_note_to(note, &X25519_BASEPOINT_BYTES, recipient_public, rng)
+}
+
+/// Encrypts `note` to `address`. The note must carry the address's
+/// diversifier, as notes from [`PaymentAddress::new_utxo`] do.
+pub fn encrypt_note_to_address<R: RngCore + CryptoRng>(
+    note: &Note,
+    address: &PaymentAddress,
+    rng: &mut R,
+) -> Result<Vec<u8>, ClientError> {
+    if note.diversifier != address.diversifier {
+        return Err(ClientError::DiversifierMismatch);
+    }
+    if address.detection_tag_len as usize != DETECTION_TAG_LEN {
+        return Err(ClientError::UnsupportedPaymentAddress);
+    }
+    encrypt_note_to(note, &address.diversifier.base_point(), &address.transmission_key, rng)
+}
+
+fn encrypt_note_to<R: RngCore + CryptoRng>(
+    note: &Note,
+    base_point: &[u8; 32],
+    transmission_key: &[u8; 32],
+    rng: &mut R,
+) -> Result<Vec<u8>, ClientError> {
+    // Both the ephemeral key and the shared secret come from this scalar, so it
+    // can't be an `EphemeralSecret`
+    let ephemeral_secret = StaticSecret::new(rng);
+    let ephemeral_public = ephemeral_secret.diffie_hellman(&PublicKey::from(*base_point)).to_bytes();
+    let shared_secret = ephemeral_secret.diffie_hellman(&PublicKey::from(*transmission_key));
+    // Low-order recipient keys would make the shared secret all zeros
+    if shared_secret.as_bytes() == &[0u8; 32] {
+        return Err(ClientError::EncryptionFailed);
+    }
+
+    let kdf = note_kdf(shared_secret.as_bytes(), &ephemeral_public);
+    let detection_tag = derive_detection_tag(&kdf);
+    let mut aad = [0u8; 1 + DETECTION_TAG_LEN];
+    aad[0] = NOTE_VERSION;
+    aad[1..].copy_from_slice(&detection_tag);
+    let ciphertext = ChaCha20Poly1305::new(Key::from_slice(&derive_note_key(&kdf)))
+        .encrypt(
+            Nonce::from_slice(&NOTE_NONCE),
+            Payload { msg: &note.to_plaintext(), aad: &aad },
+        )
+        .map_err(|_| ClientError::EncryptionFailed)?;
+
+    let mut encrypted = Vec::with_capacity(ENCRYPTED_NOTE_LEN);
+    encrypted.extend_from_slice(&aad);
+    encrypted.extend_from_slice(&ephemeral_public);
+    encrypted.extend_from_slice(&ciphertext);
+    Ok(encrypted)
+}
+
+/// The detection tag of an encrypted output, or `None` for malformed outputs.
+pub fn detection_tag(encrypted: &[u8]) -> Option<[u8; DETECTION_TAG_LEN]> {
+    Some(NoteHeader::parse(encrypted)?.detection_tag)
+}
+
+/// Whether an encrypted output may be addressed to `keypair`, checked from the
+/// detection tag alone. A match still has to be confirmed by
+/// [`try_decrypt_note`]; one in 2^32 foreign outputs matches by chance.
+pub fn matches_detection_tag(encrypted: &[u8], keypair: &EncryptionKeypair) -> bool {
+    NoteHeader::parse(encrypted)
+        .and_then(|header| Some(derive_detection_tag(&header.kdf(keypair)?) == header.detection_tag))
+        .unwrap_or(false)
+}
+
+/// Attempts to decrypt an encrypted output with `keypair`.
+///
+/// Returns `None` for outputs addressed to someone else as well as for malformed
+/// or tampered bytes; while scanning, both just mean "not our note". Outputs
+/// whose detection tag doesn't match are rejected before decryption.
+pub fn try_decrypt_note(encrypted: &[u8], keypair: &EncryptionKeypair) -> Option<Note> {
+    let header = NoteHeader::parse(encrypted)?;
+    let kdf = header.kdf(keypair)?;
+    if derive_detection_tag(&kdf) != header.detection_tag {
+        return None;
+    }
+
+    let plaintext = ChaCha20Poly1305::new(Key::from_slice(&derive_note_key(&kdf)))
+        .decrypt(
+            Nonce::from_slice(&NOTE_NONCE),
+            Payload { msg: header.ciphertext, aad: header.aad },
+        )
+        .ok()?;
+
+    Note::from_plaintext(&plaintext)
+}
@@ -0,0 +1,55 @@
+//! Local "probably spent" checks against the program's nullifier Bloom filter.
+//!
+//! One fetch of a tree's `["nullifier_bloom", tree_account]` account tells a
+//! wallet which of its notes are certainly unspent. Only the notes that hit
+//! the filter need their nullifier PDAs fetched to confirm, instead of every
+//! note or a full history scan.
+
+use crate::errors::ClientError;
+use sha2::{Digest, Sha256};
+
+/// Must match `zkcash::NULLIFIER_BLOOM_BYTES`.
+pub const NULLIFIER_BLOOM_BYTES: usize = 8192;
+/// Must match `zkcash::NULLIFIER_BLOOM_HASHES`.
+pub const NULLIFIER_BLOOM_HASHES: usize = 4;
+
+/// Discriminator, tree account and insert count precede the bits.
+const BITS_OFFSET: usize = 8 + 32 + 8;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NullifierFilter {
+    pub tree_account: [u8; 32],
+    pub inserted: u64,
+    bits: Vec<u8>,
+}
+
+impl NullifierFilter {
+    /// Parses the raw data of a `NullifierBloomFilter` account.
+    pub fn from_account_data(data: &[u8]) -> Result<Self, ClientError> {
+        let discriminator = &Sha256::digest(b"account:NullifierBloomFilter")[..8];
+        if data.len() != BITS_OFFSET + NULLIFIER_BLOOM_BYTES || &data[..8] != discriminator {
+            return Err(ClientError::InvalidAccountData);
+        }
+        Ok(NullifierFilter {
+            tree_account: data[8..40].try_into().unwrap(),
+            inserted: u64::from_le_bytes(data[40..48].try_into().unwrap()),
+            bits: data[BITS_OFFSET..].to_vec(),
+        })
+    }
+
+    /// False means the nullifier is definitely unspent. True means it
+    /// probably is; check its nullifier PDA to be sure.
+    pub fn probably_spent(&self, nullifier: &[u8; 32]) -> bool {
+        (0..NULLIFIER_BLOOM_HASHES).all(|i| {
+            let end = 32 - 2 * i;
+            let index = u16::from_be_bytes([nullifier[end - 2], nullifier[end - 1]]) as usize;
+            self.bits[index / 8] & (1 << (index % 8)) != 0
+        })
+    }
+
+    /// Expected false positive rate at the current fill.
+    pub fn false_positive_rate(&self) -> f64 {
+        let set = self.bits.iter().map(|byte| byte.count_ones() as f64).sum::<f64>();
+        (set / (NULLIFIER_BLOOM_BYTES * 8) as f64).powi(NULLIFIER_BLOOM_HASHES as i32)
+    }
+}
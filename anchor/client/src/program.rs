@@ -0,0 +1,611 @@
+//! Typed instruction builders, PDA helpers and account decoding for the
+//! zkcash program, behind the `program` feature.
+//!
+//! Builders use the program crate's Anchor-generated `accounts` and
+//! `instruction` modules, so account metas and argument encoding can't drift
+//! from the program. Fetching goes through `AccountSource`, which any RPC
+//! client can implement: solana-client itself can't resolve alongside
+//! light-hasher in this workspace, so the SDK doesn't pick one.
+
+use crate::errors::ClientError;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{system_program, AccountDeserialize, Discriminator, InstructionData, ToAccountMetas};
+use zkcash::{
+    AuditLog, CircuitBreaker, DepositLimits, ExtDataMinified, GlobalConfig, LeafPage, MerkleTreeAccount,
+    OutputStorageMode, PoolFreeze, PoolStats, Proof, Role, VerifyingKeyRotation, LEAVES_PER_PAGE,
+};
+
+pub use zkcash::ID as PROGRAM_ID;
+
+/// Fee math as transact checks it. Build `FeeRates` from a fetched
+/// `GlobalConfig` with `FeeRates::from(&global_config)`, and pass the pool's
+/// `FeeSchedule` (the default if the mint has none), the submitting relayer's
+/// `RelayerAccount` if it has one, and whether `FeeExemptions::waives_fee`
+/// for the withdrawal.
+pub use zkcash::utils::{expected_fee, fee_bounds, FeeBounds, FeeRates};
+
+pub fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &PROGRAM_ID).0
+}
+
+pub fn tree_account_pda() -> Pubkey {
+    pda(&[b"merkle_tree"])
+}
+
+pub fn tree_token_account_pda() -> Pubkey {
+    pda(&[b"tree_token"])
+}
+
+pub fn global_config_pda() -> Pubkey {
+    pda(&[b"global_config"])
+}
+
+pub fn pool_stats_pda() -> Pubkey {
+    pda(&[b"pool_stats"])
+}
+
+pub fn audit_log_pda() -> Pubkey {
+    pda(&[b"audit_log"])
+}
+
+pub fn circuit_breaker_pda() -> Pubkey {
+    pda(&[b"circuit_breaker"])
+}
+
+pub fn pool_freeze_pda() -> Pubkey {
+    pda(&[b"pool_freeze"])
+}
+
+pub fn pending_config_pda() -> Pubkey {
+    pda(&[b"pending_config"])
+}
+
+pub fn pending_authority_pda() -> Pubkey {
+    pda(&[b"pending_authority"])
+}
+
+pub fn deposit_limits_pda(tree_account: &Pubkey) -> Pubkey {
+    pda(&[b"deposit_limits", tree_account.as_ref()])
+}
+
+pub fn pending_deposit_limits_pda(tree_account: &Pubkey) -> Pubkey {
+    pda(&[b"pending_deposit_limits", tree_account.as_ref()])
+}
+
+pub fn output_storage_config_pda() -> Pubkey {
+    pda(&[b"output_storage_config"])
+}
+
+pub fn vk_rotation_pda() -> Pubkey {
+    pda(&[b"vk_rotation"])
+}
+
+pub fn leaf_storage_config_pda() -> Pubkey {
+    pda(&[b"leaf_storage_config"])
+}
+
+pub fn leaf_page_pda(tree_account: &Pubkey, page_index: u64) -> Pubkey {
+    pda(&[b"leaf_page", tree_account.as_ref(), &page_index.to_le_bytes()])
+}
+
+pub fn role_config_pda() -> Pubkey {
+    pda(&[b"role_config"])
+}
+
+pub fn fee_vault_pda() -> Pubkey {
+    pda(&[b"fee_vault"])
+}
+
+/// Nullifier PDAs in the order transact expects: nullifier0..3, where 2 and 3
+/// are the cross-check derivations with the prefixes swapped.
+pub fn nullifier_pdas(proof: &Proof) -> [Pubkey; 4] {
+    [
+        pda(&[b"nullifier0", &proof.input_nullifiers[0]]),
+        pda(&[b"nullifier1", &proof.input_nullifiers[1]]),
+        pda(&[b"nullifier0", &proof.input_nullifiers[1]]),
+        pda(&[b"nullifier1", &proof.input_nullifiers[0]]),
+    ]
+}
+
+pub fn commitment_pdas(proof: &Proof) -> [Pubkey; 2] {
+    [
+        pda(&[b"commitment0", &proof.output_commitments[0]]),
+        pda(&[b"commitment1", &proof.output_commitments[1]]),
+    ]
+}
+
+/// `queue_config_update` arguments; `None` leaves a field unchanged.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ConfigUpdate {
+    pub deposit_fee_rate: Option<u16>,
+    pub withdrawal_fee_rate: Option<u16>,
+    pub fee_error_margin: Option<u16>,
+    pub max_deposit_amount: Option<u64>,
+}
+
+/// Everything transact needs besides the PDAs, which are derived from the
+/// proof.
+#[derive(Clone, Debug)]
+pub struct TransactArgs {
+    pub proof: Proof,
+    pub ext_amount: i64,
+    pub fee: u64,
+    pub encrypted_output1: Vec<u8>,
+    pub encrypted_output2: Vec<u8>,
+    pub recipient: Pubkey,
+    pub fee_recipient: Pubkey,
+}
+
+pub fn initialize(authority: Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: zkcash::accounts::Initialize {
+            tree_account: tree_account_pda(),
+            tree_token_account: tree_token_account_pda(),
+            global_config: global_config_pda(),
+            authority,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::Initialize {}.data(),
+    }
+}
+
+pub fn initialize_pool_stats(payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: zkcash::accounts::InitializePoolStats {
+            pool_stats: pool_stats_pda(),
+            payer,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::InitializePoolStats {}.data(),
+    }
+}
+
+pub fn initialize_audit_log(payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: zkcash::accounts::InitializeAuditLog {
+            audit_log: audit_log_pda(),
+            payer,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::InitializeAuditLog {}.data(),
+    }
+}
+
+/// Must exist before any role-gated instruction; it starts with every role
+/// held by the authority.
+pub fn initialize_role_config(payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: zkcash::accounts::InitializeRoleConfig {
+            role_config: role_config_pda(),
+            payer,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::InitializeRoleConfig {}.data(),
+    }
+}
+
+/// Deposits, withdraws or transfers as described by `args`, signed and paid
+/// for by `signer`. Needs a compute budget of about 1M units, which the
+/// caller prepends.
+pub fn transact(signer: Pubkey, args: TransactArgs) -> Instruction {
+    let [nullifier0, nullifier1, nullifier2, nullifier3] = nullifier_pdas(&args.proof);
+    let [commitment0, commitment1] = commitment_pdas(&args.proof);
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: zkcash::accounts::Transact {
+            tree_account: tree_account_pda(),
+            nullifier0,
+            nullifier1,
+            nullifier2,
+            nullifier3,
+            commitment0,
+            commitment1,
+            recipient: args.recipient,
+            fee_recipient_account: args.fee_recipient,
+            tree_token_account: tree_token_account_pda(),
+            global_config: global_config_pda(),
+            signer,
+            system_program: system_program::ID,
+            pool_stats: pool_stats_pda(),
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::Transact {
+            proof: args.proof,
+            ext_data_minified: ExtDataMinified {
+                ext_amount: args.ext_amount,
+                fee: args.fee,
+            },
+            encrypted_output1: args.encrypted_output1,
+            encrypted_output2: args.encrypted_output2,
+        }
+        .data(),
+    }
+}
+
+pub fn queue_config_update(authority: Pubkey, update: &ConfigUpdate) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: zkcash::accounts::QueueConfigUpdate {
+            pending_config: pending_config_pda(),
+            global_config: global_config_pda(),
+            authority,
+            system_program: system_program::ID,
+            audit_log: audit_log_pda(),
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::QueueConfigUpdate {
+            deposit_fee_rate: update.deposit_fee_rate,
+            withdrawal_fee_rate: update.withdrawal_fee_rate,
+            fee_error_margin: update.fee_error_margin,
+            max_deposit_amount: update.max_deposit_amount,
+        }
+        .data(),
+    }
+}
+
+/// `authority` is the config authority, which receives the pending
+/// account's rent. The program lets anyone sign.
+pub fn execute_config_update(authority: Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: zkcash::accounts::ExecuteConfigUpdate {
+            pending_config: pending_config_pda(),
+            global_config: global_config_pda(),
+            tree_account: tree_account_pda(),
+            authority,
+            audit_log: audit_log_pda(),
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::ExecuteConfigUpdate {}.data(),
+    }
+}
+
+pub fn cancel_config_update(authority: Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: zkcash::accounts::CancelConfigUpdate {
+            pending_config: pending_config_pda(),
+            global_config: global_config_pda(),
+            authority,
+            audit_log: audit_log_pda(),
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::CancelConfigUpdate {}.data(),
+    }
+}
+
+pub fn propose_authority(authority: Pubkey, new_authority: Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: zkcash::accounts::ProposeAuthority {
+            pending_authority: pending_authority_pda(),
+            global_config: global_config_pda(),
+            authority,
+            system_program: system_program::ID,
+            audit_log: audit_log_pda(),
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::ProposeAuthority { new_authority }.data(),
+    }
+}
+
+pub fn accept_authority(new_authority: Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: zkcash::accounts::AcceptAuthority {
+            pending_authority: pending_authority_pda(),
+            tree_account: tree_account_pda(),
+            tree_token_account: tree_token_account_pda(),
+            global_config: global_config_pda(),
+            new_authority,
+            audit_log: audit_log_pda(),
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::AcceptAuthority {}.data(),
+    }
+}
+
+pub fn queue_deposit_limits(authority: Pubkey, tree_account: Pubkey, max_per_transaction: u64, max_per_slot: u64) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: zkcash::accounts::QueueDepositLimits {
+            pending_deposit_limits: pending_deposit_limits_pda(&tree_account),
+            tree_account,
+            authority,
+            system_program: system_program::ID,
+            audit_log: audit_log_pda(),
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::QueueDepositLimits { max_per_transaction, max_per_slot }.data(),
+    }
+}
+
+/// `authority` is the tree authority, which receives the pending account's
+/// rent. Anyone can sign as `payer`.
+pub fn execute_deposit_limits(authority: Pubkey, tree_account: Pubkey, payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: zkcash::accounts::ExecuteDepositLimits {
+            pending_deposit_limits: pending_deposit_limits_pda(&tree_account),
+            deposit_limits: deposit_limits_pda(&tree_account),
+            tree_account,
+            authority,
+            payer,
+            system_program: system_program::ID,
+            audit_log: audit_log_pda(),
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::ExecuteDepositLimits {}.data(),
+    }
+}
+
+pub fn cancel_deposit_limits(authority: Pubkey, tree_account: Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: zkcash::accounts::CancelDepositLimits {
+            pending_deposit_limits: pending_deposit_limits_pda(&tree_account),
+            tree_account,
+            authority,
+            audit_log: audit_log_pda(),
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::CancelDepositLimits {}.data(),
+    }
+}
+
+pub fn set_output_storage_mode(authority: Pubkey, mode: OutputStorageMode) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: zkcash::accounts::SetOutputStorageMode {
+            output_storage_config: output_storage_config_pda(),
+            global_config: global_config_pda(),
+            authority,
+            system_program: system_program::ID,
+            audit_log: audit_log_pda(),
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::SetOutputStorageMode { mode }.data(),
+    }
+}
+
+/// Signed by the tree operator.
+pub fn set_leaf_storage(tree_operator: Pubkey, enabled: bool) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: zkcash::accounts::SetLeafStorage {
+            leaf_storage_config: leaf_storage_config_pda(),
+            global_config: global_config_pda(),
+            role_config: role_config_pda(),
+            authority: tree_operator,
+            system_program: system_program::ID,
+            audit_log: audit_log_pda(),
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::SetLeafStorage { enabled }.data(),
+    }
+}
+
+/// Permissionless; send it before the transact that first appends to the
+/// page.
+pub fn initialize_leaf_page(payer: Pubkey, tree_account: Pubkey, page_index: u64) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: zkcash::accounts::InitializeLeafPage {
+            leaf_page: leaf_page_pda(&tree_account, page_index),
+            tree_account,
+            leaf_storage_config: leaf_storage_config_pda(),
+            payer,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::InitializeLeafPage { page_index }.data(),
+    }
+}
+
+pub fn resume_withdrawals(guardian: Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: zkcash::accounts::UpdateCircuitBreaker {
+            circuit_breaker: circuit_breaker_pda(),
+            guardian,
+            audit_log: audit_log_pda(),
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::ResumeWithdrawals {}.data(),
+    }
+}
+
+pub fn set_pool_freeze(guardian: Pubkey, withdrawals_frozen: bool, deposits_frozen: bool) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: zkcash::accounts::SetPoolFreeze {
+            pool_freeze: pool_freeze_pda(),
+            circuit_breaker: circuit_breaker_pda(),
+            guardian,
+            system_program: system_program::ID,
+            audit_log: audit_log_pda(),
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::SetPoolFreeze { withdrawals_frozen, deposits_frozen }.data(),
+    }
+}
+
+/// Delegates `role` to `holder`; `Pubkey::default()` hands it back to the
+/// authority.
+pub fn set_role(authority: Pubkey, role: Role, holder: Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: zkcash::accounts::SetRole {
+            role_config: role_config_pda(),
+            global_config: global_config_pda(),
+            authority,
+            audit_log: audit_log_pda(),
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::SetRole { role, holder }.data(),
+    }
+}
+
+/// Signed by the fee collector; `payout` must be the vault's configured
+/// payout address.
+pub fn sweep_fees(collector: Pubkey, payout: Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: zkcash::accounts::SweepFees {
+            fee_vault: fee_vault_pda(),
+            payout,
+            global_config: global_config_pda(),
+            role_config: role_config_pda(),
+            collector,
+            audit_log: audit_log_pda(),
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::SweepFees {}.data(),
+    }
+}
+
+pub fn queue_verifying_key_rotation(authority: Pubkey, vk_hash: [u8; 32]) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: zkcash::accounts::QueueVerifyingKeyRotation {
+            vk_rotation: vk_rotation_pda(),
+            global_config: global_config_pda(),
+            authority,
+            system_program: system_program::ID,
+            audit_log: audit_log_pda(),
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::QueueVerifyingKeyRotation { vk_hash }.data(),
+    }
+}
+
+/// Permissionless once the rotation's activation epoch is reached.
+pub fn activate_verifying_key_rotation() -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: zkcash::accounts::ActivateVerifyingKeyRotation {
+            vk_rotation: vk_rotation_pda(),
+        }
+        .to_account_metas(None),
+        data: zkcash::instruction::ActivateVerifyingKeyRotation {}.data(),
+    }
+}
+
+/// Where account data comes from, e.g. `getAccountInfo` over JSON-RPC.
+pub trait AccountSource {
+    /// The account's data, or `None` if it doesn't exist.
+    fn account_data(&self, address: &Pubkey) -> Result<Option<Vec<u8>>, ClientError>;
+}
+
+/// Decodes a Borsh account, checking its discriminator.
+pub fn decode_account<T: AccountDeserialize>(data: &[u8]) -> Result<T, ClientError> {
+    T::try_deserialize(&mut &data[..]).map_err(|_| ClientError::InvalidAccountData)
+}
+
+/// Decodes the zero-copy tree account. Bytes past `MerkleTreeAccount`, which
+/// later layout versions append, are ignored.
+pub fn decode_tree_account(data: &[u8]) -> Result<MerkleTreeAccount, ClientError> {
+    let body = data
+        .strip_prefix(MerkleTreeAccount::DISCRIMINATOR)
+        .and_then(|body| body.get(..std::mem::size_of::<MerkleTreeAccount>()))
+        .ok_or(ClientError::InvalidAccountData)?;
+    bytemuck::try_pod_read_unaligned(body).map_err(|_| ClientError::InvalidAccountData)
+}
+
+pub fn decode_leaf_page(data: &[u8]) -> Result<LeafPage, ClientError> {
+    let body = data
+        .strip_prefix(LeafPage::DISCRIMINATOR)
+        .filter(|body| body.len() == std::mem::size_of::<LeafPage>())
+        .ok_or(ClientError::InvalidAccountData)?;
+    let page: LeafPage = bytemuck::try_pod_read_unaligned(body).map_err(|_| ClientError::InvalidAccountData)?;
+    if page.len as usize > LEAVES_PER_PAGE {
+        return Err(ClientError::InvalidAccountData);
+    }
+    Ok(page)
+}
+
+/// Fetches and decodes a Borsh account; `None` if it doesn't exist.
+pub fn fetch_account<T: AccountDeserialize>(
+    source: &impl AccountSource,
+    address: &Pubkey,
+) -> Result<Option<T>, ClientError> {
+    source.account_data(address)?.map(|data| decode_account(&data)).transpose()
+}
+
+pub fn fetch_tree_account(source: &impl AccountSource) -> Result<Option<MerkleTreeAccount>, ClientError> {
+    source
+        .account_data(&tree_account_pda())?
+        .map(|data| decode_tree_account(&data))
+        .transpose()
+}
+
+pub fn fetch_global_config(source: &impl AccountSource) -> Result<Option<GlobalConfig>, ClientError> {
+    fetch_account(source, &global_config_pda())
+}
+
+pub fn fetch_pool_stats(source: &impl AccountSource) -> Result<Option<PoolStats>, ClientError> {
+    fetch_account(source, &pool_stats_pda())
+}
+
+pub fn fetch_audit_log(source: &impl AccountSource) -> Result<Option<AuditLog>, ClientError> {
+    fetch_account(source, &audit_log_pda())
+}
+
+pub fn fetch_circuit_breaker(source: &impl AccountSource) -> Result<Option<CircuitBreaker>, ClientError> {
+    fetch_account(source, &circuit_breaker_pda())
+}
+
+pub fn fetch_pool_freeze(source: &impl AccountSource) -> Result<Option<PoolFreeze>, ClientError> {
+    fetch_account(source, &pool_freeze_pda())
+}
+
+pub fn fetch_deposit_limits(
+    source: &impl AccountSource,
+    tree_account: &Pubkey,
+) -> Result<Option<DepositLimits>, ClientError> {
+    fetch_account(source, &deposit_limits_pda(tree_account))
+}
+
+pub fn fetch_vk_rotation(source: &impl AccountSource) -> Result<Option<VerifyingKeyRotation>, ClientError> {
+    fetch_account(source, &vk_rotation_pda())
+}
+
+pub fn fetch_leaf_page(
+    source: &impl AccountSource,
+    tree_account: &Pubkey,
+    page_index: u64,
+) -> Result<Option<LeafPage>, ClientError> {
+    source
+        .account_data(&leaf_page_pda(tree_account, page_index))?
+        .map(|data| decode_leaf_page(&data))
+        .transpose()
+}
+
+/// Every leaf of a tree with on-chain leaf storage, in index order, read
+/// from its leaf pages alone. Stops at the first missing or partly filled
+/// page; pass the result to `TreeSync` to rebuild the tree.
+pub fn fetch_tree_leaves(source: &impl AccountSource, tree_account: &Pubkey) -> Result<Vec<[u8; 32]>, ClientError> {
+    let mut leaves = Vec::new();
+    for page_index in 0.. {
+        let Some(page) = fetch_leaf_page(source, tree_account, page_index)? else {
+            break;
+        };
+        if page.tree_account != *tree_account || page.page_index != page_index {
+            return Err(ClientError::InvalidAccountData);
+        }
+        leaves.extend_from_slice(page.stored_leaves());
+        if page.len as usize != LEAVES_PER_PAGE {
+            break;
+        }
+    }
+    Ok(leaves)
+}
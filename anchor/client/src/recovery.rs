@@ -0,0 +1,140 @@
+//! Restoring a wallet from its seed alone.
+//!
+//! Every note is encrypted to the account's single encryption key, whichever
+//! payment address it paid, so trial-decrypting each emitted output with
+//! that key finds all of the wallet's notes. Their nullifiers then tell
+//! which are still spendable. [`recover_notes`] does this over a
+//! [`ChainSource`], which the caller backs with its RPC or indexer of choice:
+//! commitment events from transaction logs, or the encrypted output PDAs.
+//!
+//! Trial decryption is the cost of a scan: one X25519 agreement per output.
+//! Events are decrypted in batches of [`SCAN_BATCH_SIZE`], and with the
+//! `parallel` feature each batch is spread over rayon's thread pool. Tagged
+//! outputs addressed elsewhere are dropped on their detection tag before
+//! any ChaCha20-Poly1305 work, see [`try_decrypt_note`].
+
+use crate::errors::ClientError;
+use crate::keys::ShieldedKeys;
+use crate::note::try_decrypt_note;
+use crate::tree_sync::CommitmentEvent;
+use crate::utils::fr_to_be_bytes;
+use crate::utxo::Utxo;
+use anchor_lang::prelude::Pubkey;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+
+/// Events trial-decrypted together. Large enough to keep every thread busy,
+/// small enough that a page of events isn't held twice in memory.
+pub const SCAN_BATCH_SIZE: usize = 4096;
+
+/// Where recovery reads the chain from.
+pub trait ChainSource {
+    /// Commitment events with `index >= from_index`, in leaf order. An empty
+    /// page means the scan has caught up.
+    fn commitment_events(&mut self, from_index: u64) -> Result<Vec<CommitmentEvent>, ClientError>;
+
+    /// Whether each nullifier (big-endian) has been spent, i.e. its nullifier
+    /// account exists. One answer per nullifier, in order.
+    fn nullifiers_spent(&mut self, nullifiers: &[[u8; 32]]) -> Result<Vec<bool>, ClientError>;
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RecoveredNotes {
+    /// Spendable notes, with their leaf indices set.
+    pub unspent: Vec<Utxo>,
+    /// Notes found that have already been spent.
+    pub spent_count: usize,
+    /// Unspent balance per mint.
+    pub balances: BTreeMap<Pubkey, u64>,
+    /// The next leaf index to scan from to pick up newer notes.
+    pub next_index: u64,
+}
+
+/// Derives the account's keys from `seed` and finds every unspent note paid
+/// to it. Outputs that decrypt but don't match their commitment are
+/// ignored, so a forged ciphertext can't inflate the balance.
+pub fn recover_notes<S: ChainSource>(
+    seed: &[u8],
+    account_index: u32,
+    source: &mut S,
+) -> Result<RecoveredNotes, ClientError> {
+    let keys = ShieldedKeys::from_seed(seed, account_index)?;
+    scan_notes(&keys, 0, source)
+}
+
+/// `recover_notes` for existing keys, from leaf `from_index` on, e.g. the
+/// `synced_index` of a restored backup.
+pub fn scan_notes<S: ChainSource>(
+    keys: &ShieldedKeys,
+    from_index: u64,
+    source: &mut S,
+) -> Result<RecoveredNotes, ClientError> {
+    let mut found = Vec::new();
+    let mut next_index = from_index;
+    loop {
+        let events = source.commitment_events(next_index)?;
+        if events.is_empty() {
+            break;
+        }
+        let mut fresh = Vec::with_capacity(events.len());
+        for event in &events {
+            if event.index < next_index {
+                continue;
+            }
+            next_index = event.index + 1;
+            fresh.push(event);
+        }
+        for batch in fresh.chunks(SCAN_BATCH_SIZE) {
+            found.extend(decrypt_batch(keys, batch)?);
+        }
+    }
+
+    let nullifiers = found
+        .iter()
+        .map(|utxo| Ok(fr_to_be_bytes(&utxo.nullifier(keys)?)))
+        .collect::<Result<Vec<_>, ClientError>>()?;
+    let spent = if nullifiers.is_empty() {
+        Vec::new()
+    } else {
+        source.nullifiers_spent(&nullifiers)?
+    };
+    if spent.len() != found.len() {
+        return Err(ClientError::InvalidChainSource);
+    }
+
+    let mut recovered = RecoveredNotes {
+        next_index,
+        ..RecoveredNotes::default()
+    };
+    for (utxo, spent) in found.into_iter().zip(spent) {
+        if spent {
+            recovered.spent_count += 1;
+            continue;
+        }
+        let balance = recovered.balances.entry(utxo.mint_address).or_default();
+        *balance = balance.checked_add(utxo.amount).ok_or(ClientError::ArithmeticOverflow)?;
+        recovered.unspent.push(utxo);
+    }
+    Ok(recovered)
+}
+
+/// The notes in `batch` that decrypt with `keys` and match their
+/// commitment, in batch order.
+pub fn decrypt_batch(keys: &ShieldedKeys, batch: &[&CommitmentEvent]) -> Result<Vec<Utxo>, ClientError> {
+    #[cfg(feature = "parallel")]
+    let matches = batch.par_iter().map(|event| match_event(keys, event));
+    #[cfg(not(feature = "parallel"))]
+    let matches = batch.iter().map(|event| match_event(keys, event));
+
+    let matches: Vec<Option<Utxo>> = matches.collect::<Result<_, ClientError>>()?;
+    Ok(matches.into_iter().flatten().collect())
+}
+
+fn match_event(keys: &ShieldedKeys, event: &CommitmentEvent) -> Result<Option<Utxo>, ClientError> {
+    let Some(note) = try_decrypt_note(&event.encrypted_output, keys.encryption_keypair()) else {
+        return Ok(None);
+    };
+    let utxo = Utxo::from_note(&note, keys.public_key_for(&note.diversifier)?, event.index);
+    Ok((utxo.amount > 0 && fr_to_be_bytes(&utxo.commitment()?) == event.commitment).then_some(utxo))
+}
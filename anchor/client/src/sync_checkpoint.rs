@@ -0,0 +1,180 @@
+//! Resumable tree synchronization.
+//!
+//! Replaying the whole pool history on every wallet start gets slower as the
+//! pool grows. A [`SyncCheckpoint`] stores just enough to carry on: the last
+//! transaction signature processed, the tree's `next_index` and filled
+//! subtrees (a [`TreeFrontier`]), and any events that arrived ahead of a gap.
+//! [`ResumableSync`] applies new commitment events on top of it and asks the
+//! RPC only for signatures after `last_signature`.
+//!
+//! The frontier tracks the root but can't produce Merkle paths for leaves
+//! before the checkpoint; a wallet that needs those keeps a
+//! [`TreeSync`](crate::tree_sync::TreeSync) for its own notes, which can
+//! hand over to a checkpoint with [`TreeSync::checkpoint`].
+
+use crate::errors::ClientError;
+use crate::tree_frontier::TreeFrontier;
+use crate::tree_sync::{CommitmentEvent, TreeSync};
+use anchor_lang::prelude::{borsh, AnchorDeserialize, AnchorSerialize};
+use light_hasher::{Hasher, Poseidon};
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+
+const CHECKPOINT_VERSION: u8 = 1;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SyncCheckpoint {
+    pub version: u8,
+    /// Newest transaction signature fully processed.
+    pub last_signature: Option<String>,
+    pub next_index: u64,
+    /// The last left node written at each level, from the leaves up.
+    pub subtrees: Vec<[u8; 32]>,
+    pub root: [u8; 32],
+    /// Leaves at or past `next_index` waiting for a gap to fill.
+    pub pending: Vec<(u64, [u8; 32])>,
+}
+
+impl SyncCheckpoint {
+    pub fn save<W: Write>(&self, writer: &mut W) -> Result<(), ClientError> {
+        self.serialize(writer).map_err(|_| ClientError::InvalidSnapshot)
+    }
+
+    pub fn load<R: Read>(reader: &mut R) -> Result<Self, ClientError> {
+        let checkpoint = Self::deserialize_reader(reader).map_err(|_| ClientError::InvalidSnapshot)?;
+        if checkpoint.version != CHECKPOINT_VERSION {
+            return Err(ClientError::InvalidSnapshot);
+        }
+        Ok(checkpoint)
+    }
+}
+
+/// Follows the tree from a checkpoint without holding its leaves.
+#[derive(Clone, Debug)]
+pub struct ResumableSync {
+    frontier: TreeFrontier,
+    pending: BTreeMap<u64, [u8; 32]>,
+    /// Newest transaction signature fully processed; set by the caller once
+    /// all of a transaction's events are applied.
+    pub last_signature: Option<String>,
+}
+
+impl ResumableSync {
+    pub fn new(height: usize) -> Self {
+        Self::from_frontier(TreeFrontier::new(height), None)
+    }
+
+    /// Starts from a frontier read off a tree account or snapshot, with the
+    /// signature of the newest transaction it reflects.
+    pub fn from_frontier(frontier: TreeFrontier, last_signature: Option<String>) -> Self {
+        ResumableSync {
+            frontier,
+            pending: BTreeMap::new(),
+            last_signature,
+        }
+    }
+
+    pub fn from_checkpoint(checkpoint: SyncCheckpoint) -> Result<Self, ClientError> {
+        if checkpoint.subtrees.is_empty()
+            || checkpoint.next_index > 1u64 << checkpoint.subtrees.len()
+            || checkpoint.pending.iter().any(|(index, _)| *index < checkpoint.next_index)
+        {
+            return Err(ClientError::InvalidSnapshot);
+        }
+        Ok(ResumableSync {
+            frontier: TreeFrontier {
+                next_index: checkpoint.next_index,
+                subtrees: checkpoint.subtrees,
+                root: checkpoint.root,
+            },
+            pending: checkpoint.pending.into_iter().collect(),
+            last_signature: checkpoint.last_signature,
+        })
+    }
+
+    pub fn checkpoint(&self) -> SyncCheckpoint {
+        SyncCheckpoint {
+            version: CHECKPOINT_VERSION,
+            last_signature: self.last_signature.clone(),
+            next_index: self.frontier.next_index,
+            subtrees: self.frontier.subtrees.clone(),
+            root: self.frontier.root,
+            pending: self.pending.iter().map(|(index, leaf)| (*index, *leaf)).collect(),
+        }
+    }
+
+    pub fn frontier(&self) -> &TreeFrontier {
+        &self.frontier
+    }
+
+    pub fn next_index(&self) -> u64 {
+        self.frontier.next_index
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.frontier.root
+    }
+
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Applies one event. Events before `next_index` are already folded into
+    /// the frontier and are skipped: after a restart the first page of
+    /// history often overlaps the checkpoint.
+    pub fn apply_event(&mut self, event: &CommitmentEvent) -> Result<(), ClientError> {
+        if event.index < self.frontier.next_index {
+            return Ok(());
+        }
+        if let Some(existing) = self.pending.get(&event.index) {
+            if *existing != event.commitment {
+                return Err(ClientError::ConflictingLeaf);
+            }
+            return Ok(());
+        }
+
+        self.pending.insert(event.index, event.commitment);
+        while let Some(leaf) = self.pending.remove(&self.frontier.next_index) {
+            self.frontier.append(leaf)?;
+        }
+        Ok(())
+    }
+}
+
+impl TreeSync {
+    /// The frontier of the synced tree: at each level, the rightmost node
+    /// at an even index, or the zero subtree if the level is empty.
+    pub fn frontier(&self) -> TreeFrontier {
+        let tree = self.tree();
+        let next_index = tree.next_index();
+        let zero_bytes = Poseidon::zero_bytes();
+        let subtrees = (0..tree.height())
+            .map(|level| {
+                let filled = next_index.div_ceil(1u64 << level);
+                match filled {
+                    0 => zero_bytes[level],
+                    _ => tree.node(level, (filled - 1) & !1).expect("filled node exists"),
+                }
+            })
+            .collect();
+        TreeFrontier {
+            next_index,
+            subtrees,
+            root: tree.root(),
+        }
+    }
+
+    /// A checkpoint equivalent to this sync, for wallets moving to
+    /// [`ResumableSync`].
+    pub fn checkpoint(&self) -> SyncCheckpoint {
+        let frontier = self.frontier();
+        SyncCheckpoint {
+            version: CHECKPOINT_VERSION,
+            last_signature: self.last_signature.clone(),
+            next_index: frontier.next_index,
+            subtrees: frontier.subtrees,
+            root: frontier.root,
+            pending: self.pending.iter().map(|(index, leaf)| (*index, *leaf)).collect(),
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//! Coin selection and construction of the circuit's public inputs.
+//!
+//! The circuit is a fixed 2-in / 2-out JoinSplit enforcing
+//! `sum(inputs) + publicAmount == sum(outputs)` where `publicAmount = extAmount - fee`
+//! in the BN254 field. Unused slots are padded with zero-amount UTXOs.
+
+use crate::errors::ClientError;
+use crate::keys::ShieldedKeys;
+use crate::utils::fr_to_be_bytes;
+use crate::utxo::Utxo;
+use anchor_lang::prelude::{borsh, AnchorDeserialize, AnchorSerialize, Pubkey};
+use ark_bn254::Fr;
+use rand_core::{CryptoRng, RngCore};
+
+pub const CIRCUIT_INPUTS: usize = 2;
+pub const CIRCUIT_OUTPUTS: usize = 2;
+
+/**
+ * Computes the public amount the program's `check_public_amount` will accept.
+ *
+ * For deposits (ext_amount > 0): public_amount = ext_amount - fee, and the deposit must exceed the fee.
+ * For withdrawals (ext_amount < 0): public_amount = FIELD_SIZE - (abs(ext_amount) + fee).
+ */
+pub fn calculate_public_amount(ext_amount: i64, fee: u64) -> Result<[u8; 32], ClientError> {
+    if ext_amount == i64::MIN {
+        return Err(ClientError::InvalidExtAmount);
+    }
+    calculate_public_amount_u128(ext_amount as i128, fee as u128)
+}
+
+/// `calculate_public_amount` over 128-bit base-unit amounts, matching the
+/// program's `check_public_amount_u128`.
+pub fn calculate_public_amount_u128(ext_amount: i128, fee: u128) -> Result<[u8; 32], ClientError> {
+    if ext_amount == i128::MIN {
+        return Err(ClientError::InvalidExtAmount);
+    }
+
+    let fee_fr = Fr::from(fee);
+    let public_amount = if ext_amount >= 0 {
+        if ext_amount.unsigned_abs() <= fee {
+            return Err(ClientError::InvalidExtAmount);
+        }
+        Fr::from(ext_amount.unsigned_abs()) - fee_fr
+    } else {
+        -(Fr::from(ext_amount.unsigned_abs()) + fee_fr)
+    };
+
+    Ok(fr_to_be_bytes(&public_amount))
+}
+
+/// Total value the inputs must cover: `max(0, fee - ext_amount)`.
+pub fn required_input_amount(ext_amount: i64, fee: u64) -> Result<u64, ClientError> {
+    let required = fee as i128 - ext_amount as i128;
+    if required <= 0 {
+        return Ok(0);
+    }
+    u64::try_from(required).map_err(|_| ClientError::ArithmeticOverflow)
+}
+
+/// Picks at most two notes covering `required`.
+///
+/// Prefers the smallest single note that suffices, falling back to the two
+/// largest notes. Returns an empty selection when nothing is required.
+pub fn select_inputs(notes: &[Utxo], required: u64) -> Result<Vec<Utxo>, ClientError> {
+    if required == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut sorted: Vec<&Utxo> = notes.iter().filter(|note| note.amount > 0).collect();
+    sorted.sort_by_key(|note| note.amount);
+
+    if let Some(single) = sorted.iter().find(|note| note.amount >= required) {
+        return Ok(vec![(*single).clone()]);
+    }
+
+    if sorted.len() >= 2 {
+        let largest = sorted[sorted.len() - 1];
+        let second = sorted[sorted.len() - 2];
+        if largest.amount as u128 + second.amount as u128 >= required as u128 {
+            return Ok(vec![second.clone(), largest.clone()]);
+        }
+    }
+
+    Err(ClientError::InsufficientFunds)
+}
+
+/// Mirror of the program's `TransactResult`, the return data of `transact`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TransactResult {
+    /// Leaf index of each output commitment, in output order.
+    pub leaf_indices: [u64; CIRCUIT_OUTPUTS],
+    /// Tree root after both outputs were appended.
+    pub root: [u8; 32],
+}
+
+impl TransactResult {
+    /// Decodes the `returnData` bytes of a `transact` transaction or
+    /// simulation, after base64-decoding them.
+    pub fn from_return_data(data: &[u8]) -> Result<Self, ClientError> {
+        TransactResult::try_from_slice(data).map_err(|_| ClientError::InvalidReturnData)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransactionPlan {
+    pub inputs: [Utxo; CIRCUIT_INPUTS],
+    pub outputs: [Utxo; CIRCUIT_OUTPUTS],
+    pub ext_amount: i64,
+    pub fee: u64,
+    pub public_amount: [u8; 32],
+}
+
+/// Selects inputs from `notes` and builds outputs for a deposit
+/// (`ext_amount > 0`), withdrawal (`ext_amount < 0`) or self-transfer.
+///
+/// Output 0 carries the resulting balance (deposit plus any merged inputs, or
+/// change) back to `keys`; output 1 is a zero-amount padding note.
+pub fn plan_transaction<R: RngCore + CryptoRng>(
+    keys: &ShieldedKeys,
+    notes: &[Utxo],
+    ext_amount: i64,
+    fee: u64,
+    mint_address: Pubkey,
+    rng: &mut R,
+) -> Result<TransactionPlan, ClientError> {
+    if notes.iter().any(|note| note.mint_address != mint_address) {
+        return Err(ClientError::MintMismatch);
+    }
+    let public_amount = calculate_public_amount(ext_amount, fee)?;
+
+    let required = required_input_amount(ext_amount, fee)?;
+    let selected = select_inputs(notes, required)?;
+    let input_total: i128 = selected.iter().map(|note| note.amount as i128).sum();
+
+    let change = input_total + ext_amount as i128 - fee as i128;
+    let change = u64::try_from(change).map_err(|_| ClientError::InsufficientFunds)?;
+
+    let mut selected = selected.into_iter();
+    let inputs = [
+        selected.next().unwrap_or_else(|| Utxo::zero(keys.public_key(), mint_address, rng)),
+        selected.next().unwrap_or_else(|| Utxo::zero(keys.public_key(), mint_address, rng)),
+    ];
+    let outputs = [
+        Utxo::new(change, keys.public_key(), mint_address, rng),
+        Utxo::zero(keys.public_key(), mint_address, rng),
+    ];
+
+    Ok(TransactionPlan {
+        inputs,
+        outputs,
+        ext_amount,
+        fee,
+        public_amount,
+    })
+}
+
+impl TransactionPlan {
+    pub fn input_nullifiers(&self, keys: &ShieldedKeys) -> Result<[[u8; 32]; CIRCUIT_INPUTS], ClientError> {
+        Ok([
+            fr_to_be_bytes(&self.inputs[0].nullifier(keys)?),
+            fr_to_be_bytes(&self.inputs[1].nullifier(keys)?),
+        ])
+    }
+
+    pub fn output_commitments(&self) -> Result<[[u8; 32]; CIRCUIT_OUTPUTS], ClientError> {
+        Ok([
+            fr_to_be_bytes(&self.outputs[0].commitment()?),
+            fr_to_be_bytes(&self.outputs[1].commitment()?),
+        ])
+    }
+
+    /// The plan's outputs with the leaf indices `transact` assigned them, ready
+    /// to be spent once the local tree has caught up to `result.root`.
+    pub fn inserted_outputs(&self, result: &TransactResult) -> [Utxo; CIRCUIT_OUTPUTS] {
+        let mut outputs = self.outputs.clone();
+        for (output, index) in outputs.iter_mut().zip(result.leaf_indices) {
+            output.index = index;
+        }
+        outputs
+    }
+
+    /// Public inputs in the order `verify_proof` passes them to the verifier:
+    /// root, public amount, ext data hash, two nullifiers, two commitments.
+    pub fn public_inputs(
+        &self,
+        keys: &ShieldedKeys,
+        root: [u8; 32],
+        ext_data_hash: [u8; 32],
+    ) -> Result<[[u8; 32]; 7], ClientError> {
+        let nullifiers = self.input_nullifiers(keys)?;
+        let commitments = self.output_commitments()?;
+        Ok([
+            root,
+            self.public_amount,
+            ext_data_hash,
+            nullifiers[0],
+            nullifiers[1],
+            commitments[0],
+            commitments[1],
+        ])
+    }
+}
@@ -0,0 +1,102 @@
+//! Appends to the commitment tree from its filled subtrees alone.
+//!
+//! `append` on-chain only reads `next_index` and one filled subtree per
+//! level, so a light client holding those can follow new commitments and
+//! track the root without keeping, or replaying, every leaf. Seed a
+//! [`TreeFrontier`] from a fetched tree account or an exported snapshot.
+//! It can't produce Merkle paths for older leaves; use
+//! [`crate::tree_sync::LocalMerkleTree`] for notes you need to spend.
+
+use crate::errors::ClientError;
+use crate::tree_snapshot::TreeSnapshot;
+use light_hasher::{Hasher, Poseidon};
+use sha2::{Digest, Sha256};
+
+/// Offsets into `MerkleTreeAccount` data. Must match
+/// `zkcash::merkle_tree::layout`.
+pub mod layout {
+    pub const SUBTREE_SLOTS: usize = 26;
+    pub const ROOT_HISTORY_SLOTS: usize = 100;
+
+    pub const NEXT_INDEX: usize = 40;
+    pub const SUBTREES: usize = NEXT_INDEX + 8;
+    pub const ROOT: usize = SUBTREES + 32 * SUBTREE_SLOTS;
+    pub const ROOT_INDEX: usize = ROOT + 32 + 32 * ROOT_HISTORY_SLOTS;
+    pub const HEIGHT: usize = ROOT_INDEX + 16;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TreeFrontier {
+    pub next_index: u64,
+    /// The last left node written at each level, from the leaves up.
+    pub subtrees: Vec<[u8; 32]>,
+    pub root: [u8; 32],
+}
+
+impl TreeFrontier {
+    /// An empty tree of `height`, as `initialize` creates it.
+    pub fn new(height: usize) -> Self {
+        let zero_bytes = Poseidon::zero_bytes();
+        TreeFrontier {
+            next_index: 0,
+            subtrees: zero_bytes[..height].to_vec(),
+            root: zero_bytes[height],
+        }
+    }
+
+    /// Parses the raw data of a `MerkleTreeAccount`.
+    pub fn from_account_data(data: &[u8]) -> Result<Self, ClientError> {
+        let discriminator = &Sha256::digest(b"account:MerkleTreeAccount")[..8];
+        if data.len() <= layout::HEIGHT || &data[..8] != discriminator {
+            return Err(ClientError::InvalidAccountData);
+        }
+        let height = data[layout::HEIGHT] as usize;
+        if height == 0 || height > layout::SUBTREE_SLOTS {
+            return Err(ClientError::InvalidAccountData);
+        }
+        Ok(TreeFrontier {
+            next_index: u64::from_le_bytes(data[layout::NEXT_INDEX..layout::NEXT_INDEX + 8].try_into().unwrap()),
+            subtrees: data[layout::SUBTREES..layout::SUBTREES + 32 * height]
+                .chunks(32)
+                .map(|node| node.try_into().unwrap())
+                .collect(),
+            root: data[layout::ROOT..layout::ROOT + 32].try_into().unwrap(),
+        })
+    }
+
+    pub fn from_snapshot(snapshot: &TreeSnapshot) -> Self {
+        TreeFrontier {
+            next_index: snapshot.next_index,
+            subtrees: snapshot.subtrees.clone(),
+            root: snapshot.root,
+        }
+    }
+
+    pub fn height(&self) -> usize {
+        self.subtrees.len()
+    }
+
+    /// Appends `leaf` the way the program does and returns its index.
+    pub fn append(&mut self, leaf: [u8; 32]) -> Result<u64, ClientError> {
+        let index = self.next_index;
+        if index >= 1u64 << self.height() {
+            return Err(ClientError::MerkleTreeFull);
+        }
+        let zero_bytes = Poseidon::zero_bytes();
+        let mut current_index = index;
+        let mut node = leaf;
+        for (level, subtree) in self.subtrees.iter_mut().enumerate() {
+            let (left, right) = if current_index.is_multiple_of(2) {
+                *subtree = node;
+                (node, zero_bytes[level])
+            } else {
+                (*subtree, node)
+            };
+            node = Poseidon::hashv(&[&left, &right]).map_err(|_| ClientError::PoseidonError)?;
+            current_index /= 2;
+        }
+        self.root = node;
+        self.next_index += 1;
+        Ok(index)
+    }
+}
@@ -0,0 +1,88 @@
+//! Reassembles the paged tree snapshots `export_tree_snapshot` returns.
+//!
+//! Simulate the instruction for page 0, then for each remaining page, and
+//! feed the return data to a [`SnapshotAssembler`]. The result carries the
+//! tree's filled subtrees and its root history, so a light client can append
+//! from the current state and know which roots the program still accepts
+//! without replaying every commitment event.
+
+use crate::errors::ClientError;
+use anchor_lang::prelude::{borsh, AnchorDeserialize, AnchorSerialize};
+
+/// Must match `zkcash::TREE_SNAPSHOT_VERSION`.
+pub const TREE_SNAPSHOT_VERSION: u8 = 1;
+
+/// Client-side mirror of `zkcash::TreeSnapshot`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TreeSnapshot {
+    pub version: u8,
+    pub tree_account: [u8; 32],
+    pub height: u8,
+    pub next_index: u64,
+    pub root: [u8; 32],
+    pub subtrees: Vec<[u8; 32]>,
+    pub root_history: Vec<[u8; 32]>,
+    pub root_index: u64,
+}
+
+/// Client-side mirror of `zkcash::TreeSnapshotPage`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TreeSnapshotPage {
+    pub next_index: u64,
+    pub page: u16,
+    pub total_pages: u16,
+    pub bytes: Vec<u8>,
+}
+
+impl TreeSnapshot {
+    /// Whether the program would still accept `root`.
+    pub fn is_known_root(&self, root: &[u8; 32]) -> bool {
+        *root != [0u8; 32] && self.root_history.contains(root)
+    }
+}
+
+/// Collects pages in order. A page from a different tree state means the
+/// tree moved on between reads; start over from page 0.
+#[derive(Default)]
+pub struct SnapshotAssembler {
+    next_index: u64,
+    total_pages: u16,
+    pages_read: u16,
+    bytes: Vec<u8>,
+}
+
+impl SnapshotAssembler {
+    /// Adds the return data of `export_tree_snapshot` for the next page.
+    /// Returns the snapshot once the last page is in.
+    pub fn push(&mut self, return_data: &[u8]) -> Result<Option<TreeSnapshot>, ClientError> {
+        let page = TreeSnapshotPage::try_from_slice(return_data).map_err(|_| ClientError::InvalidSnapshot)?;
+        if page.page != self.pages_read {
+            return Err(ClientError::InvalidSnapshot);
+        }
+        if page.page == 0 {
+            self.next_index = page.next_index;
+            self.total_pages = page.total_pages;
+        } else if page.next_index != self.next_index || page.total_pages != self.total_pages {
+            return Err(ClientError::SnapshotChanged);
+        }
+        self.bytes.extend_from_slice(&page.bytes);
+        self.pages_read += 1;
+        if self.pages_read < self.total_pages {
+            return Ok(None);
+        }
+
+        let snapshot = TreeSnapshot::try_from_slice(&self.bytes).map_err(|_| ClientError::InvalidSnapshot)?;
+        if snapshot.version != TREE_SNAPSHOT_VERSION
+            || snapshot.subtrees.len() != snapshot.height as usize
+            || snapshot.root_history.get(snapshot.root_index as usize) != Some(&snapshot.root)
+        {
+            return Err(ClientError::InvalidSnapshot);
+        }
+        Ok(Some(snapshot))
+    }
+
+    /// The page to request next.
+    pub fn next_page(&self) -> u16 {
+        self.pages_read
+    }
+}
@@ -0,0 +1,216 @@
+//! Local replica of the on-chain commitment tree.
+//!
+//! Wallets replay the program's commitment events (`index`, `commitment`,
+//! `encrypted_output`) into a [`LocalMerkleTree`] to obtain the Merkle paths the
+//! circuit needs. The tree hashes exactly like `zkcash::merkle_tree::MerkleTree`
+//! (light-hasher Poseidon with its zero-byte table), so its root after N leaves
+//! equals the on-chain root after N appends and is accepted by `is_known_root`
+//! while it stays within the root history window.
+
+use crate::errors::ClientError;
+use anchor_lang::prelude::{borsh, AnchorDeserialize, AnchorSerialize};
+use light_hasher::{Hasher, Poseidon};
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+
+pub const DEFAULT_TREE_HEIGHT: usize = 26;
+const SNAPSHOT_VERSION: u8 = 1;
+
+/// Client-side mirror of the commitment event the program emits per output.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CommitmentEvent {
+    pub index: u64,
+    pub commitment: [u8; 32],
+    pub encrypted_output: Vec<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerklePath {
+    pub leaf_index: u64,
+    /// Sibling hashes from the leaf level upward; the circuit's `inPathElements`.
+    pub path_elements: Vec<[u8; 32]>,
+    pub root: [u8; 32],
+}
+
+#[derive(Clone, Debug)]
+pub struct LocalMerkleTree {
+    height: usize,
+    /// `layers[0]` holds the leaves; `layers[i]` the filled nodes at level i.
+    layers: Vec<Vec<[u8; 32]>>,
+}
+
+impl LocalMerkleTree {
+    pub fn new(height: usize) -> Self {
+        LocalMerkleTree {
+            height,
+            layers: vec![Vec::new(); height + 1],
+        }
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn next_index(&self) -> u64 {
+        self.layers[0].len() as u64
+    }
+
+    pub fn leaves(&self) -> &[[u8; 32]] {
+        &self.layers[0]
+    }
+
+    /// The node at `index` on `level`, as currently filled; `None` if no
+    /// leaf below it has been inserted yet.
+    pub fn node(&self, level: usize, index: u64) -> Option<[u8; 32]> {
+        self.layers.get(level)?.get(index as usize).copied()
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.layers[self.height]
+            .first()
+            .copied()
+            .unwrap_or(Poseidon::zero_bytes()[self.height])
+    }
+
+    pub fn insert(&mut self, leaf: [u8; 32]) -> Result<u64, ClientError> {
+        let index = self.next_index();
+        if index >= 1u64 << self.height {
+            return Err(ClientError::MerkleTreeFull);
+        }
+
+        self.layers[0].push(leaf);
+        let zero_bytes = Poseidon::zero_bytes();
+        let mut current_index = index as usize;
+        let mut current_hash = leaf;
+        for (level, zero) in zero_bytes.iter().enumerate().take(self.height) {
+            let (left, right) = if current_index.is_multiple_of(2) {
+                (current_hash, *zero)
+            } else {
+                (self.layers[level][current_index - 1], current_hash)
+            };
+            current_hash = Poseidon::hashv(&[&left, &right]).map_err(|_| ClientError::PoseidonError)?;
+            current_index /= 2;
+
+            let parent_level = &mut self.layers[level + 1];
+            if current_index < parent_level.len() {
+                parent_level[current_index] = current_hash;
+            } else {
+                parent_level.push(current_hash);
+            }
+        }
+
+        Ok(index)
+    }
+
+    pub fn path(&self, leaf_index: u64) -> Result<MerklePath, ClientError> {
+        if leaf_index >= self.next_index() {
+            return Err(ClientError::LeafIndexOutOfRange);
+        }
+
+        let zero_bytes = Poseidon::zero_bytes();
+        let mut current_index = leaf_index as usize;
+        let mut path_elements = Vec::with_capacity(self.height);
+        for (layer, zero) in self.layers.iter().zip(zero_bytes.iter()).take(self.height) {
+            let sibling = layer.get(current_index ^ 1).copied().unwrap_or(*zero);
+            path_elements.push(sibling);
+            current_index /= 2;
+        }
+
+        Ok(MerklePath {
+            leaf_index,
+            path_elements,
+            root: self.root(),
+        })
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+struct TreeSyncSnapshot {
+    version: u8,
+    height: u8,
+    leaves: Vec<[u8; 32]>,
+    pending: Vec<(u64, [u8; 32])>,
+    last_signature: Option<String>,
+}
+
+/// Applies commitment events to a [`LocalMerkleTree`] in leaf order.
+///
+/// Events may arrive out of order (e.g. when fetching signatures in pages);
+/// leaves ahead of `next_index` are buffered until the gap is filled.
+#[derive(Clone, Debug)]
+pub struct TreeSync {
+    tree: LocalMerkleTree,
+    pub(crate) pending: BTreeMap<u64, [u8; 32]>,
+    /// Newest transaction signature fully processed, so a restarted sync can
+    /// ask the RPC only for newer history.
+    pub last_signature: Option<String>,
+}
+
+impl TreeSync {
+    pub fn new(height: usize) -> Self {
+        TreeSync {
+            tree: LocalMerkleTree::new(height),
+            pending: BTreeMap::new(),
+            last_signature: None,
+        }
+    }
+
+    pub fn tree(&self) -> &LocalMerkleTree {
+        &self.tree
+    }
+
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn apply_event(&mut self, event: &CommitmentEvent) -> Result<(), ClientError> {
+        let next_index = self.tree.next_index();
+        if event.index < next_index {
+            // Replays of already synced events are fine, contradictions are not
+            if self.tree.leaves()[event.index as usize] != event.commitment {
+                return Err(ClientError::ConflictingLeaf);
+            }
+            return Ok(());
+        }
+        if let Some(existing) = self.pending.get(&event.index) {
+            if *existing != event.commitment {
+                return Err(ClientError::ConflictingLeaf);
+            }
+            return Ok(());
+        }
+
+        self.pending.insert(event.index, event.commitment);
+        while let Some(leaf) = self.pending.remove(&self.tree.next_index()) {
+            self.tree.insert(leaf)?;
+        }
+        Ok(())
+    }
+
+    pub fn save<W: Write>(&self, writer: &mut W) -> Result<(), ClientError> {
+        let snapshot = TreeSyncSnapshot {
+            version: SNAPSHOT_VERSION,
+            height: self.tree.height() as u8,
+            leaves: self.tree.leaves().to_vec(),
+            pending: self.pending.iter().map(|(index, leaf)| (*index, *leaf)).collect(),
+            last_signature: self.last_signature.clone(),
+        };
+        snapshot.serialize(writer).map_err(|_| ClientError::InvalidSnapshot)
+    }
+
+    pub fn load<R: Read>(reader: &mut R) -> Result<Self, ClientError> {
+        let snapshot = TreeSyncSnapshot::deserialize_reader(reader).map_err(|_| ClientError::InvalidSnapshot)?;
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(ClientError::InvalidSnapshot);
+        }
+
+        let mut tree = LocalMerkleTree::new(snapshot.height as usize);
+        for leaf in snapshot.leaves {
+            tree.insert(leaf)?;
+        }
+        Ok(TreeSync {
+            tree,
+            pending: snapshot.pending.into_iter().collect(),
+            last_signature: snapshot.last_signature,
+        })
+    }
+}
@@ -0,0 +1,59 @@
+use crate::errors::ClientError;
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, PrimeField};
+use light_poseidon::{Poseidon, PoseidonHasher};
+
+/// Circom-compatible Poseidon over BN254, the same hash the circuit uses for
+/// keys, commitments and nullifiers.
+pub fn poseidon_hash(inputs: &[Fr]) -> Result<Fr, ClientError> {
+    let mut hasher = Poseidon::<Fr>::new_circom(inputs.len()).map_err(|_| ClientError::PoseidonError)?;
+    hasher.hash(inputs).map_err(|_| ClientError::PoseidonError)
+}
+
+/// Big-endian encoding, as used for every public input passed to the program.
+pub fn fr_to_be_bytes(fr: &Fr) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    let be = fr.into_bigint().to_bytes_be();
+    bytes[32 - be.len()..].copy_from_slice(&be);
+    bytes
+}
+
+pub fn fr_from_be_bytes(bytes: &[u8; 32]) -> Fr {
+    Fr::from_be_bytes_mod_order(bytes)
+}
+
+/// Parses a decimal amount such as `"1.5"` into base units of a mint with
+/// `decimals` decimals, exactly. Rejects more fractional digits than the mint
+/// has rather than rounding them away.
+pub fn parse_token_amount(amount: &str, decimals: u8) -> Result<u128, ClientError> {
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    let digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+    if whole.is_empty() || !digits(whole) || !digits(fraction) || fraction.len() > decimals as usize {
+        return Err(ClientError::InvalidTokenAmount);
+    }
+    let scale = 10u128.checked_pow(decimals as u32).ok_or(ClientError::ArithmeticOverflow)?;
+    let whole: u128 = whole.parse().map_err(|_| ClientError::ArithmeticOverflow)?;
+    let fraction = if fraction.is_empty() {
+        0
+    } else {
+        let padding = 10u128.pow((decimals as usize - fraction.len()) as u32);
+        fraction.parse::<u128>().map_err(|_| ClientError::InvalidTokenAmount)? * padding
+    };
+    whole
+        .checked_mul(scale)
+        .and_then(|base| base.checked_add(fraction))
+        .ok_or(ClientError::ArithmeticOverflow)
+}
+
+/// Formats base units of a mint with `decimals` decimals, dropping trailing
+/// fractional zeros.
+pub fn format_token_amount(amount: u128, decimals: u8) -> String {
+    let digits = format!("{:0>width$}", amount, width = decimals as usize + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals as usize);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, fraction)
+    }
+}
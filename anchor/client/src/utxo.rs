@@ -0,0 +1,102 @@
+//! Shielded UTXOs as the circuit sees them.
+//!
+//! `commitment = Poseidon(amount, pubkey, blinding, mintAddress)`
+
+use crate::address::Diversifier;
+use crate::errors::ClientError;
+use crate::keys::ShieldedKeys;
+use crate::note::Note;
+use crate::utils::{fr_from_be_bytes, fr_to_be_bytes, poseidon_hash};
+use anchor_lang::prelude::Pubkey;
+use ark_bn254::Fr;
+use ark_ff::PrimeField;
+use rand_core::{CryptoRng, RngCore};
+use std::str::FromStr;
+
+/// Native SOL is represented by this pseudo mint in ExtData and in commitments.
+pub const SOL_MINT_ADDRESS: &str = "11111111111111111111111111111112";
+
+/// Field element a mint address contributes to commitments.
+///
+/// The TypeScript client feeds the SOL pseudo mint to Poseidon as the decimal
+/// string `11111111111111111111111111111112`; SPL mints use their first 31
+/// bytes so the value always fits in the field.
+pub fn mint_address_field(mint_address: &Pubkey) -> Fr {
+    if mint_address.to_string() == SOL_MINT_ADDRESS {
+        return Fr::from_str(SOL_MINT_ADDRESS).expect("SOL mint constant is a valid decimal field element");
+    }
+    Fr::from_be_bytes_mod_order(&mint_address.to_bytes()[..31])
+}
+
+pub fn random_blinding<R: RngCore + CryptoRng>(rng: &mut R) -> Fr {
+    let mut bytes = [0u8; 32];
+    rng.fill_bytes(&mut bytes);
+    fr_from_be_bytes(&bytes)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Utxo {
+    pub amount: u64,
+    pub blinding: Fr,
+    /// Owner's shielded public key, `Poseidon(spending_key)`.
+    pub pubkey: Fr,
+    pub mint_address: Pubkey,
+    /// Leaf index in the Merkle tree; only meaningful for notes already inserted.
+    pub index: u64,
+    /// Payment address `pubkey` belongs to; selects the spending key.
+    pub diversifier: Diversifier,
+}
+
+impl Utxo {
+    pub fn new<R: RngCore + CryptoRng>(amount: u64, pubkey: Fr, mint_address: Pubkey, rng: &mut R) -> Self {
+        Utxo {
+            amount,
+            blinding: random_blinding(rng),
+            pubkey,
+            mint_address,
+            index: 0,
+            diversifier: Diversifier::default(),
+        }
+    }
+
+    /// Zero-amount UTXO used to pad unused circuit inputs and outputs.
+    pub fn zero<R: RngCore + CryptoRng>(pubkey: Fr, mint_address: Pubkey, rng: &mut R) -> Self {
+        Utxo::new(0, pubkey, mint_address, rng)
+    }
+
+    /// `pubkey` must be the owner key of `note.diversifier`, see
+    /// `ShieldedKeys::public_key_for`.
+    pub fn from_note(note: &Note, pubkey: Fr, index: u64) -> Self {
+        Utxo {
+            amount: note.amount,
+            blinding: fr_from_be_bytes(&note.blinding),
+            pubkey,
+            mint_address: note.mint_address,
+            index,
+            diversifier: note.diversifier,
+        }
+    }
+
+    pub fn to_note(&self) -> Note {
+        Note {
+            amount: self.amount,
+            blinding: fr_to_be_bytes(&self.blinding),
+            mint_address: self.mint_address,
+            diversifier: self.diversifier,
+        }
+    }
+
+    pub fn commitment(&self) -> Result<Fr, ClientError> {
+        poseidon_hash(&[
+            Fr::from(self.amount),
+            self.pubkey,
+            self.blinding,
+            mint_address_field(&self.mint_address),
+        ])
+    }
+
+    /// Nullifier of this UTXO when spent by `keys`, which must own it.
+    pub fn nullifier(&self, keys: &ShieldedKeys) -> Result<Fr, ClientError> {
+        keys.nullifier_for(&self.diversifier, self.commitment()?, self.index)
+    }
+}
@@ -0,0 +1,239 @@
+//! wasm-bindgen wrappers so browser wallets share this implementation.
+//!
+//! Byte arrays cross the boundary as `Uint8Array`, pubkeys as base58 strings and
+//! u64/i64 amounts as `BigInt`. Field elements are 32-byte big-endian arrays.
+//! `ClientError`s surface as thrown JS `Error`s.
+
+use crate::address::{Diversifier, PaymentAddress};
+use crate::errors::ClientError;
+use crate::ext_data::{ExtData, EXT_DATA_VERSION_LEGACY};
+use crate::keys::ShieldedKeys;
+use crate::note::{encrypt_note, encrypt_note_to_address, matches_detection_tag, try_decrypt_note, Note};
+use crate::transaction::TransactResult;
+use crate::utils::fr_to_be_bytes;
+use anchor_lang::prelude::Pubkey;
+use rand_core::OsRng;
+use std::str::FromStr;
+use wasm_bindgen::prelude::*;
+
+fn parse_pubkey(value: &str) -> Result<Pubkey, ClientError> {
+    Pubkey::from_str(value).map_err(|_| ClientError::InvalidPubkey)
+}
+
+fn to_array<const N: usize>(bytes: &[u8]) -> Result<[u8; N], JsError> {
+    bytes
+        .try_into()
+        .map_err(|_| JsError::new(&format!("expected {} bytes, got {}", N, bytes.len())))
+}
+
+#[wasm_bindgen(js_name = ShieldedKeys)]
+pub struct WasmShieldedKeys(ShieldedKeys);
+
+#[wasm_bindgen(js_class = ShieldedKeys)]
+impl WasmShieldedKeys {
+    #[wasm_bindgen(js_name = fromMnemonic)]
+    pub fn from_mnemonic(phrase: &str, passphrase: &str, account_index: u32) -> Result<WasmShieldedKeys, JsError> {
+        Ok(WasmShieldedKeys(ShieldedKeys::from_mnemonic(phrase, passphrase, account_index)?))
+    }
+
+    #[wasm_bindgen(js_name = fromSeed)]
+    pub fn from_seed(seed: &[u8], account_index: u32) -> Result<WasmShieldedKeys, JsError> {
+        Ok(WasmShieldedKeys(ShieldedKeys::from_seed(seed, account_index)?))
+    }
+
+    #[wasm_bindgen(getter, js_name = accountIndex)]
+    pub fn account_index(&self) -> u32 {
+        self.0.account_index
+    }
+
+    #[wasm_bindgen(getter, js_name = spendingKey)]
+    pub fn spending_key(&self) -> Vec<u8> {
+        fr_to_be_bytes(&self.0.spending_key()).to_vec()
+    }
+
+    #[wasm_bindgen(getter, js_name = publicKey)]
+    pub fn public_key(&self) -> Vec<u8> {
+        fr_to_be_bytes(&self.0.public_key()).to_vec()
+    }
+
+    #[wasm_bindgen(getter, js_name = encryptionPublicKey)]
+    pub fn encryption_public_key(&self) -> Vec<u8> {
+        self.0.encryption_keypair().public_key().to_vec()
+    }
+
+    /// The encoded `index`th payment address; 0 is the default address.
+    #[wasm_bindgen(js_name = paymentAddress)]
+    pub fn payment_address(&self, index: u32) -> Result<String, JsError> {
+        Ok(self.0.payment_address(index)?.to_string())
+    }
+
+    /// The `outPubkey` for notes carrying `diversifier`, as found in decrypted notes.
+    #[wasm_bindgen(js_name = publicKeyFor)]
+    pub fn public_key_for(&self, diversifier: &[u8]) -> Result<Vec<u8>, JsError> {
+        let public_key = self.0.public_key_for(&Diversifier(to_array(diversifier)?))?;
+        Ok(fr_to_be_bytes(&public_key).to_vec())
+    }
+}
+
+#[wasm_bindgen(js_name = Note)]
+pub struct WasmNote(Note);
+
+#[wasm_bindgen(js_class = Note)]
+impl WasmNote {
+    /// `diversifier` is that of the payment address being paid, default address if omitted.
+    #[wasm_bindgen(constructor)]
+    pub fn new(amount: u64, blinding: &[u8], mint_address: &str, diversifier: Option<Vec<u8>>) -> Result<WasmNote, JsError> {
+        Ok(WasmNote(Note {
+            amount,
+            blinding: to_array(blinding)?,
+            mint_address: parse_pubkey(mint_address)?,
+            diversifier: diversifier.map(|bytes| to_array(&bytes)).transpose()?.map(Diversifier).unwrap_or_default(),
+        }))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn amount(&self) -> u64 {
+        self.0.amount
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn blinding(&self) -> Vec<u8> {
+        self.0.blinding.to_vec()
+    }
+
+    #[wasm_bindgen(getter, js_name = mintAddress)]
+    pub fn mint_address(&self) -> String {
+        self.0.mint_address.to_string()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn diversifier(&self) -> Vec<u8> {
+        self.0.diversifier.0.to_vec()
+    }
+}
+
+#[wasm_bindgen(js_name = encryptNote)]
+pub fn encrypt_note_js(note: &WasmNote, recipient_encryption_key: &[u8]) -> Result<Vec<u8>, JsError> {
+    Ok(encrypt_note(&note.0, &to_array(recipient_encryption_key)?, &mut OsRng)?)
+}
+
+/// The `outPubkey` and diversifier to build a note paying an encoded payment
+/// address, concatenated (32 + 11 bytes).
+#[wasm_bindgen(js_name = paymentAddressRecipient)]
+pub fn payment_address_recipient_js(address: &str) -> Result<Vec<u8>, JsError> {
+    let address = PaymentAddress::from_str(address)?;
+    Ok([&fr_to_be_bytes(&address.owner_pubkey)[..], &address.diversifier.0].concat())
+}
+
+/// Throws unless `address` parses and its keys can be paid.
+#[wasm_bindgen(js_name = validatePaymentAddress)]
+pub fn validate_payment_address_js(address: &str) -> Result<(), JsError> {
+    PaymentAddress::validate(address)?;
+    Ok(())
+}
+
+/// Encrypts `note` to an encoded payment address.
+#[wasm_bindgen(js_name = encryptNoteToAddress)]
+pub fn encrypt_note_to_address_js(note: &WasmNote, address: &str) -> Result<Vec<u8>, JsError> {
+    let address = PaymentAddress::from_str(address)?;
+    Ok(encrypt_note_to_address(&note.0, &address, &mut OsRng)?)
+}
+
+#[wasm_bindgen(js_name = tryDecryptNote)]
+pub fn try_decrypt_note_js(encrypted: &[u8], keys: &WasmShieldedKeys) -> Option<WasmNote> {
+    try_decrypt_note(encrypted, keys.0.encryption_keypair()).map(WasmNote)
+}
+
+/// Cheap prefilter for scanning: `false` means the output is not ours.
+#[wasm_bindgen(js_name = matchesDetectionTag)]
+pub fn matches_detection_tag_js(encrypted: &[u8], keys: &WasmShieldedKeys) -> bool {
+    matches_detection_tag(encrypted, keys.0.encryption_keypair())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn build_ext_data(
+    recipient: &str,
+    ext_amount: i64,
+    encrypted_output1: &[u8],
+    encrypted_output2: &[u8],
+    fee: u64,
+    fee_recipient: &str,
+    mint_address: &str,
+    memo: Option<Vec<u8>>,
+    version: Option<u8>,
+    expiry_slot: Option<u64>,
+    max_fee: Option<u64>,
+    relayer: Option<String>,
+) -> Result<ExtData, ClientError> {
+    let mut builder = ExtData::builder()
+        .recipient(parse_pubkey(recipient)?)
+        .ext_amount(ext_amount)
+        .encrypted_outputs(encrypted_output1.to_vec(), encrypted_output2.to_vec())
+        .fee(fee)
+        .fee_recipient(parse_pubkey(fee_recipient)?)
+        .mint_address(parse_pubkey(mint_address)?)
+        .memo(memo.unwrap_or_default())
+        .version(version.unwrap_or(EXT_DATA_VERSION_LEGACY))
+        .expiry_slot(expiry_slot.unwrap_or_default());
+    if let Some(max_fee) = max_fee {
+        builder = builder.max_fee(max_fee);
+    }
+    if let Some(relayer) = relayer {
+        builder = builder.relayer(parse_pubkey(&relayer)?);
+    }
+    builder.build()
+}
+
+/// sha256 of the Borsh-serialized ExtData, as recomputed on-chain.
+#[wasm_bindgen(js_name = extDataHash)]
+#[allow(clippy::too_many_arguments)]
+pub fn ext_data_hash_js(
+    recipient: &str,
+    ext_amount: i64,
+    encrypted_output1: &[u8],
+    encrypted_output2: &[u8],
+    fee: u64,
+    fee_recipient: &str,
+    mint_address: &str,
+    memo: Option<Vec<u8>>,
+    version: Option<u8>,
+    expiry_slot: Option<u64>,
+    max_fee: Option<u64>,
+    relayer: Option<String>,
+) -> Result<Vec<u8>, JsError> {
+    let ext_data = build_ext_data(
+        recipient, ext_amount, encrypted_output1, encrypted_output2, fee, fee_recipient, mint_address, memo, version,
+        expiry_slot, max_fee, relayer,
+    )?;
+    Ok(ext_data.hash().to_vec())
+}
+
+/// The `extDataHash` public input to feed the prover.
+#[wasm_bindgen(js_name = extDataPublicInput)]
+#[allow(clippy::too_many_arguments)]
+pub fn ext_data_public_input_js(
+    recipient: &str,
+    ext_amount: i64,
+    encrypted_output1: &[u8],
+    encrypted_output2: &[u8],
+    fee: u64,
+    fee_recipient: &str,
+    mint_address: &str,
+    memo: Option<Vec<u8>>,
+    version: Option<u8>,
+    expiry_slot: Option<u64>,
+    max_fee: Option<u64>,
+    relayer: Option<String>,
+) -> Result<Vec<u8>, JsError> {
+    let ext_data = build_ext_data(
+        recipient, ext_amount, encrypted_output1, encrypted_output2, fee, fee_recipient, mint_address, memo, version,
+        expiry_slot, max_fee, relayer,
+    )?;
+    Ok(ext_data.public_input().to_vec())
+}
+
+/// Leaf indices of a `transact`'s two outputs, read from its return data.
+#[wasm_bindgen(js_name = transactLeafIndices)]
+pub fn transact_leaf_indices_js(return_data: &[u8]) -> Result<Vec<u64>, JsError> {
+    Ok(TransactResult::from_return_data(return_data)?.leaf_indices.to_vec())
+}
@@ -0,0 +1,158 @@
+use anchor_lang::prelude::Pubkey;
+use rand_core::OsRng;
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+use zkcash_client::address::{
+    Diversifier, PaymentAddress, PAYMENT_ADDRESS_PREFIX, PAYMENT_ADDRESS_UNTAGGED_LEN, PAYMENT_ADDRESS_VERSION,
+    PAYMENT_ADDRESS_VERSION_UNTAGGED,
+};
+use zkcash_client::errors::ClientError;
+use zkcash_client::keys::ShieldedKeys;
+use zkcash_client::note::{encrypt_note_to_address, try_decrypt_note, DETECTION_TAG_LEN};
+use zkcash_client::utils::poseidon_hash;
+use zkcash_client::utxo::Utxo;
+
+fn test_keys() -> ShieldedKeys {
+    ShieldedKeys::from_seed(&[5u8; 32], 0).unwrap()
+}
+
+#[test]
+fn test_default_address_uses_undiversified_keys() {
+    let keys = test_keys();
+    let address = keys.payment_address(0).unwrap();
+
+    assert!(address.diversifier.is_default());
+    assert_eq!(address.owner_pubkey, keys.public_key());
+    assert_eq!(address.transmission_key, keys.encryption_keypair().public_key());
+}
+
+#[test]
+fn test_diversified_addresses_are_unlinkable() {
+    let keys = test_keys();
+    let first = keys.payment_address(1).unwrap();
+    let second = keys.payment_address(2).unwrap();
+
+    assert_ne!(first.diversifier, second.diversifier);
+    assert_ne!(first.owner_pubkey, second.owner_pubkey);
+    assert_ne!(first.transmission_key, second.transmission_key);
+    assert_ne!(first.owner_pubkey, keys.public_key());
+    assert_ne!(first.transmission_key, keys.encryption_keypair().public_key());
+}
+
+#[test]
+fn test_diversified_owner_key_matches_circuit_keypair() {
+    // Each address still satisfies publicKey = Poseidon(privateKey)
+    let keys = test_keys();
+    let diversifier = keys.diversifier(3);
+    let spending_key = keys.spending_key_for(&diversifier).unwrap();
+
+    assert_eq!(keys.payment_address(3).unwrap().owner_pubkey, poseidon_hash(&[spending_key]).unwrap());
+}
+
+#[test]
+fn test_address_encoding_roundtrip() {
+    let address = test_keys().payment_address(7).unwrap();
+    let encoded = address.to_string();
+
+    assert!(encoded.starts_with(PAYMENT_ADDRESS_PREFIX));
+    assert_eq!(PaymentAddress::from_str(&encoded).unwrap(), address);
+}
+
+#[test]
+fn test_corrupted_address_rejected() {
+    let encoded = test_keys().payment_address(7).unwrap().to_string();
+
+    let mut corrupted: Vec<char> = encoded.chars().collect();
+    let last = corrupted.len() - 1;
+    corrupted[last] = if corrupted[last] == '2' { '3' } else { '2' };
+    let corrupted: String = corrupted.into_iter().collect();
+
+    assert_eq!(PaymentAddress::from_str(&corrupted), Err(ClientError::InvalidPaymentAddress));
+    assert_eq!(PaymentAddress::from_str(&encoded[PAYMENT_ADDRESS_PREFIX.len()..]), Err(ClientError::InvalidPaymentAddress));
+    assert_eq!(PaymentAddress::from_str(""), Err(ClientError::InvalidPaymentAddress));
+}
+
+#[test]
+fn test_note_to_diversified_address_decrypts_with_the_viewing_key() {
+    let keys = test_keys();
+    let address = keys.payment_address(4).unwrap();
+    let utxo = address.new_utxo(2_000_000, Pubkey::new_unique(), &mut OsRng);
+
+    let encrypted = encrypt_note_to_address(&utxo.to_note(), &address, &mut OsRng).unwrap();
+    let note = try_decrypt_note(&encrypted, keys.encryption_keypair()).unwrap();
+
+    assert_eq!(note.diversifier, address.diversifier);
+    let received = Utxo::from_note(&note, keys.public_key_for(&note.diversifier).unwrap(), 0);
+    assert_eq!(received.commitment().unwrap(), utxo.commitment().unwrap());
+}
+
+#[test]
+fn test_diversified_nullifier_uses_the_diversified_spending_key() {
+    let keys = test_keys();
+    let address = keys.payment_address(4).unwrap();
+    let utxo = address.new_utxo(1_000, Pubkey::new_unique(), &mut OsRng);
+    let commitment = utxo.commitment().unwrap();
+
+    assert_eq!(utxo.nullifier(&keys).unwrap(), keys.nullifier_for(&address.diversifier, commitment, 0).unwrap());
+    assert_ne!(utxo.nullifier(&keys).unwrap(), keys.nullifier(commitment, 0).unwrap());
+}
+
+#[test]
+fn test_note_diversifier_must_match_address() {
+    let keys = test_keys();
+    let address = keys.payment_address(4).unwrap();
+    let mut note = address.new_utxo(1_000, Pubkey::new_unique(), &mut OsRng).to_note();
+    note.diversifier = Diversifier::default();
+
+    assert_eq!(encrypt_note_to_address(&note, &address, &mut OsRng), Err(ClientError::DiversifierMismatch));
+}
+
+#[test]
+fn test_address_carries_detection_tag_len() {
+    let address = test_keys().payment_address(2).unwrap();
+    assert_eq!(address.detection_tag_len as usize, DETECTION_TAG_LEN);
+    assert_eq!(address.to_bytes()[0], PAYMENT_ADDRESS_VERSION);
+    assert_eq!(PaymentAddress::parse(&address.encode()).unwrap(), address);
+}
+
+/// Encodes `bytes` the way `Display` does, with a valid checksum.
+fn encode_raw(bytes: &[u8]) -> String {
+    let checksum = Sha256::digest(Sha256::digest(bytes));
+    let payload = [bytes, &checksum[..4]].concat();
+    format!("{}{}", PAYMENT_ADDRESS_PREFIX, bs58::encode(payload).into_string())
+}
+
+#[test]
+fn test_version_1_addresses_still_parse() {
+    let address = test_keys().payment_address(2).unwrap();
+    let mut bytes = address.to_bytes()[..PAYMENT_ADDRESS_UNTAGGED_LEN].to_vec();
+    bytes[0] = PAYMENT_ADDRESS_VERSION_UNTAGGED;
+    assert_eq!(PaymentAddress::parse(&encode_raw(&bytes)).unwrap(), address);
+}
+
+#[test]
+fn test_unsupported_addresses_are_distinguished() {
+    let mut bytes = test_keys().payment_address(2).unwrap().to_bytes();
+    bytes[76] = 8;
+    assert_eq!(PaymentAddress::parse(&encode_raw(&bytes)), Err(ClientError::UnsupportedPaymentAddress));
+    bytes[76] = DETECTION_TAG_LEN as u8;
+    bytes[0] = PAYMENT_ADDRESS_VERSION + 1;
+    assert_eq!(PaymentAddress::parse(&encode_raw(&bytes)), Err(ClientError::UnsupportedPaymentAddress));
+    bytes[0] = 0;
+    assert_eq!(PaymentAddress::parse(&encode_raw(&bytes)), Err(ClientError::InvalidPaymentAddress));
+}
+
+#[test]
+fn test_validate_rejects_unpayable_keys() {
+    let address = test_keys().payment_address(2).unwrap();
+    assert_eq!(PaymentAddress::validate(&address.encode()).unwrap(), address);
+
+    // The identity is a low-order point: every shared secret with it is zero
+    let mut low_order = address.clone();
+    low_order.transmission_key = [0u8; 32];
+    assert_eq!(PaymentAddress::validate(&low_order.encode()), Err(ClientError::InvalidPaymentAddress));
+
+    let mut zero_owner = address;
+    zero_owner.owner_pubkey = 0u64.into();
+    assert_eq!(PaymentAddress::validate(&zero_owner.encode()), Err(ClientError::InvalidPaymentAddress));
+}
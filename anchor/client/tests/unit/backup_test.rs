@@ -0,0 +1,82 @@
+use anchor_lang::prelude::Pubkey;
+use rand_core::OsRng;
+use zkcash_client::backup::{NoteBackup, BACKUP_VERSION, MIN_BACKUP_KDF_ROUNDS};
+use zkcash_client::errors::ClientError;
+use zkcash_client::keys::ShieldedKeys;
+use zkcash_client::utxo::Utxo;
+
+fn test_keys() -> ShieldedKeys {
+    ShieldedKeys::from_seed(&[5u8; 32], 0).unwrap()
+}
+
+fn wallet_utxos(keys: &ShieldedKeys) -> Vec<Utxo> {
+    let mint = Pubkey::new_unique();
+    (0..3)
+        .map(|i| {
+            let mut utxo = keys.payment_address(i).unwrap().new_utxo(1_000 * (i as u64 + 1), mint, &mut OsRng);
+            utxo.index = 10 + i as u64;
+            utxo
+        })
+        .collect()
+}
+
+fn encrypted_backup(keys: &ShieldedKeys, password: &str) -> Vec<u8> {
+    NoteBackup::from_utxos(&wallet_utxos(keys), 42)
+        .unwrap()
+        .encrypt_with_rounds(password, MIN_BACKUP_KDF_ROUNDS, &mut OsRng)
+        .unwrap()
+}
+
+#[test]
+fn test_backup_roundtrip() {
+    let keys = test_keys();
+    let utxos = wallet_utxos(&keys);
+    let backup = NoteBackup::from_utxos(&utxos, 42).unwrap();
+    let encrypted = backup.encrypt_with_rounds("hunter2", MIN_BACKUP_KDF_ROUNDS, &mut OsRng).unwrap();
+
+    let restored = NoteBackup::decrypt(&encrypted, "hunter2").unwrap();
+    assert_eq!(restored, backup);
+    assert_eq!(restored.synced_index, 42);
+    assert_eq!(restored.to_utxos(&keys).unwrap(), utxos);
+}
+
+#[test]
+fn test_wrong_password_fails() {
+    let encrypted = encrypted_backup(&test_keys(), "hunter2");
+    assert_eq!(NoteBackup::decrypt(&encrypted, "hunter3"), Err(ClientError::BackupDecryptionFailed));
+}
+
+#[test]
+fn test_tampered_header_fails() {
+    let encrypted = encrypted_backup(&test_keys(), "hunter2");
+    // One more round, still in range, changes the key and the associated data
+    let mut rounds = encrypted.clone();
+    let raised = u32::from_le_bytes(rounds[5..9].try_into().unwrap()) + 1;
+    rounds[5..9].copy_from_slice(&raised.to_le_bytes());
+    assert_eq!(NoteBackup::decrypt(&rounds, "hunter2"), Err(ClientError::BackupDecryptionFailed));
+
+    let mut salt = encrypted;
+    salt[10] ^= 1;
+    assert_eq!(NoteBackup::decrypt(&salt, "hunter2"), Err(ClientError::BackupDecryptionFailed));
+}
+
+#[test]
+fn test_rejects_other_formats() {
+    let mut encrypted = encrypted_backup(&test_keys(), "hunter2");
+    encrypted[4] = BACKUP_VERSION + 1;
+    assert_eq!(NoteBackup::decrypt(&encrypted, "hunter2"), Err(ClientError::UnsupportedBackupVersion));
+    encrypted[0] = b'X';
+    assert_eq!(NoteBackup::decrypt(&encrypted, "hunter2"), Err(ClientError::InvalidBackup));
+    assert_eq!(NoteBackup::decrypt(&[], "hunter2"), Err(ClientError::InvalidBackup));
+
+    let weak = NoteBackup::default().encrypt_with_rounds("hunter2", 1, &mut OsRng);
+    assert_eq!(weak, Err(ClientError::InvalidBackup));
+}
+
+#[test]
+fn test_restore_checks_keys() {
+    let encrypted = encrypted_backup(&test_keys(), "hunter2");
+    let backup = NoteBackup::decrypt(&encrypted, "hunter2").unwrap();
+    let other_keys = ShieldedKeys::from_seed(&[6u8; 32], 0).unwrap();
+    assert_eq!(backup.to_utxos(&other_keys), Err(ClientError::BackupKeyMismatch));
+}
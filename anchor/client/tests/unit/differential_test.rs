@@ -0,0 +1,188 @@
+//! Differential tests against the TypeScript client in `anchor/tests/lib`.
+//!
+//! The same inputs go through this crate and, via a node subprocess running
+//! `tests/differential/vectors.ts`, through the TS client; every key,
+//! commitment, nullifier, ExtData hash, public amount and root must come out
+//! identical, or one of the two would build proofs the program rejects.
+//!
+//! Ignored by default since they need node and `anchor/node_modules`
+//! (`yarn install` in `anchor/`). Run with
+//! `cargo test -p zkcash-client -- --ignored differential`.
+//!
+//! The TS client only encrypts with a JSON mock and only commits to the SOL
+//! pseudo mint, so encryptions and SPL mint commitments have nothing to be
+//! compared against.
+
+use anchor_lang::prelude::Pubkey;
+use ark_bn254::Fr;
+use ark_ff::PrimeField;
+use serde_json::{json, Value};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+use zkcash_client::address::Diversifier;
+use zkcash_client::ext_data::ExtData;
+use zkcash_client::keys::ShieldedKeys;
+use zkcash_client::transaction::calculate_public_amount;
+use zkcash_client::tree_sync::LocalMerkleTree;
+use zkcash_client::utils::{fr_from_be_bytes, fr_to_be_bytes};
+use zkcash_client::utxo::{Utxo, SOL_MINT_ADDRESS};
+
+const ANCHOR_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/..");
+
+const TREE_HEIGHT: usize = 26;
+
+fn run_ts_client(input: &Value) -> Value {
+    let mut child = Command::new("npx")
+        .args(["ts-node", "--transpile-only", "tests/differential/vectors.ts"])
+        .current_dir(ANCHOR_DIR)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to start npx, is node installed?");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.to_string().as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "tests/differential/vectors.ts failed");
+    serde_json::from_slice(&output.stdout).expect("TS client printed invalid JSON")
+}
+
+/// Field elements as the TS client prints them.
+fn decimal(fr: &Fr) -> String {
+    fr.into_bigint().to_string()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn strings(value: &Value) -> Vec<String> {
+    value
+        .as_array()
+        .expect("expected an array")
+        .iter()
+        .map(|item| item.as_str().expect("expected a string").to_string())
+        .collect()
+}
+
+fn test_keys() -> Vec<ShieldedKeys> {
+    (1..=3u8).map(|i| ShieldedKeys::from_seed(&[i; 32], 0).unwrap()).collect()
+}
+
+fn sol_mint() -> Pubkey {
+    Pubkey::from_str(SOL_MINT_ADDRESS).unwrap()
+}
+
+fn test_utxos(keys: &[ShieldedKeys]) -> Vec<Utxo> {
+    let amounts = [0, 1, 1_000_000_000, u64::MAX];
+    amounts
+        .iter()
+        .enumerate()
+        .map(|(i, amount)| Utxo {
+            amount: *amount,
+            blinding: Fr::from(123_456_789u64 * (i as u64 + 1)),
+            pubkey: keys[i % keys.len()].public_key(),
+            mint_address: sol_mint(),
+            index: i as u64 * 7,
+            diversifier: Diversifier::default(),
+        })
+        .collect()
+}
+
+fn test_ext_data() -> Vec<ExtData> {
+    let spl_mint = Pubkey::new_from_array([7u8; 32]);
+    [(1_000_000_000i64, 5_000u64, None), (-500_000_000, 2_500_000, None), (-1, 0, Some(spl_mint))]
+        .into_iter()
+        .enumerate()
+        .map(|(i, (ext_amount, fee, mint))| {
+            let mut builder = ExtData::builder()
+                .recipient(Pubkey::new_from_array([i as u8 + 1; 32]))
+                .ext_amount(ext_amount)
+                .encrypted_outputs(vec![0xab; 80 + i], vec![0xcd; 80 + i])
+                .fee(fee)
+                .fee_recipient(Pubkey::new_from_array([0x42; 32]));
+            if let Some(mint) = mint {
+                builder = builder.mint_address(mint);
+            }
+            builder.build().unwrap()
+        })
+        .collect()
+}
+
+const PUBLIC_AMOUNTS: [(i64, u64); 4] = [(1_000_000_000, 5_000), (2, 1), (-500_000_000, 2_500_000), (-1, 0)];
+
+#[test]
+#[ignore = "needs node and anchor/node_modules"]
+fn test_differential_against_ts_client() {
+    let keys = test_keys();
+    let utxos = test_utxos(&keys);
+    let ext_data = test_ext_data();
+    let commitments: Vec<Fr> = utxos.iter().map(|utxo| utxo.commitment().unwrap()).collect();
+    let tree_leaves = [&commitments[..0], &commitments[..1], &commitments[..3]];
+
+    let input = json!({
+        "privkeys": keys.iter().map(|keys| format!("0x{}", hex(&fr_to_be_bytes(&keys.spending_key())))).collect::<Vec<_>>(),
+        "utxos": utxos.iter().enumerate().map(|(i, utxo)| json!({
+            "privkey": format!("0x{}", hex(&fr_to_be_bytes(&keys[i % keys.len()].spending_key()))),
+            "amount": utxo.amount.to_string(),
+            "blinding": decimal(&utxo.blinding),
+            "index": utxo.index,
+            "mintAddress": SOL_MINT_ADDRESS,
+        })).collect::<Vec<_>>(),
+        "extData": ext_data.iter().map(|ext_data| json!({
+            "recipient": ext_data.recipient.to_string(),
+            "extAmount": ext_data.ext_amount.to_string(),
+            "encryptedOutput1": hex(&ext_data.encrypted_output1),
+            "encryptedOutput2": hex(&ext_data.encrypted_output2),
+            "fee": ext_data.fee.to_string(),
+            "feeRecipient": ext_data.fee_recipient.to_string(),
+            "mintAddress": ext_data.mint_address.to_string(),
+        })).collect::<Vec<_>>(),
+        "publicAmounts": PUBLIC_AMOUNTS.iter().map(|(ext_amount, fee)| json!({
+            "extAmount": ext_amount.to_string(),
+            "fee": fee.to_string(),
+        })).collect::<Vec<_>>(),
+        "trees": tree_leaves.iter().map(|leaves| json!({
+            "levels": TREE_HEIGHT,
+            "leaves": leaves.iter().map(decimal).collect::<Vec<_>>(),
+        })).collect::<Vec<_>>(),
+    });
+    let ts = run_ts_client(&input);
+
+    let pubkeys: Vec<String> = keys.iter().map(|keys| decimal(&keys.public_key())).collect();
+    assert_eq!(strings(&ts["pubkeys"]), pubkeys, "shielded public keys");
+
+    assert_eq!(strings(&ts["commitments"]), commitments.iter().map(decimal).collect::<Vec<_>>(), "commitments");
+
+    let nullifiers: Vec<String> = utxos
+        .iter()
+        .enumerate()
+        .map(|(i, utxo)| decimal(&utxo.nullifier(&keys[i % keys.len()]).unwrap()))
+        .collect();
+    assert_eq!(strings(&ts["nullifiers"]), nullifiers, "nullifiers");
+
+    let hashes: Vec<String> = ext_data.iter().map(|ext_data| hex(&ext_data.hash())).collect();
+    assert_eq!(strings(&ts["extDataHashes"]), hashes, "ExtData hashes");
+
+    let public_amounts: Vec<String> = PUBLIC_AMOUNTS
+        .iter()
+        .map(|(ext_amount, fee)| decimal(&fr_from_be_bytes(&calculate_public_amount(*ext_amount, *fee).unwrap())))
+        .collect();
+    assert_eq!(strings(&ts["publicAmounts"]), public_amounts, "public amounts");
+
+    let roots: Vec<String> = tree_leaves
+        .iter()
+        .map(|leaves| {
+            let mut tree = LocalMerkleTree::new(TREE_HEIGHT);
+            for leaf in leaves.iter() {
+                tree.insert(fr_to_be_bytes(leaf)).unwrap();
+            }
+            decimal(&fr_from_be_bytes(&tree.root()))
+        })
+        .collect();
+    assert_eq!(strings(&ts["roots"]), roots, "Merkle roots");
+}
@@ -0,0 +1,442 @@
+use anchor_lang::prelude::Pubkey;
+#[cfg(feature = "program")]
+use ark_bn254::Fr;
+#[cfg(feature = "program")]
+use ark_ff::PrimeField;
+use std::str::FromStr;
+#[cfg(feature = "program")]
+use zkcash::utils::{
+    calculate_complete_ext_data_hash, calculate_ext_data_hash_with_memo, calculate_versioned_ext_data_hash, ExtDataFields,
+};
+use zkcash_client::errors::ClientError;
+use zkcash_client::ext_data::{
+    ExtData, EXT_DATA_VERSION, EXT_DATA_VERSION_EXPIRY, EXT_DATA_VERSION_LEGACY, EXT_DATA_VERSION_MAX_FEE,
+    EXT_DATA_VERSION_RELAYER, EXT_DATA_VERSION_SWAP, EXT_DATA_VERSION_CPI, EXT_DATA_VERSION_FOREIGN_RECIPIENT, EXT_DATA_VERSION_MEMO_CPI, MAX_MEMO_LEN, CpiTarget, ForeignRecipient,
+    SwapParams,
+};
+use zkcash_client::utxo::SOL_MINT_ADDRESS;
+
+#[cfg(feature = "program")]
+fn onchain_hash(ext_data: &ExtData) -> [u8; 32] {
+    calculate_complete_ext_data_hash(
+        ext_data.recipient,
+        ext_data.ext_amount,
+        &ext_data.encrypted_output1,
+        &ext_data.encrypted_output2,
+        ext_data.fee,
+        ext_data.fee_recipient,
+        ext_data.mint_address,
+    )
+    .unwrap()
+}
+
+fn sample_ext_data(ext_amount: i64, fee: u64, output_len: usize) -> ExtData {
+    ExtData::builder()
+        .recipient(Pubkey::new_unique())
+        .ext_amount(ext_amount)
+        .encrypted_outputs(vec![0xab; output_len], vec![0xcd; output_len / 2])
+        .fee(fee)
+        .fee_recipient(Pubkey::new_unique())
+        .build()
+        .unwrap()
+}
+
+#[test]
+#[cfg(feature = "program")]
+fn test_hash_matches_onchain_for_deposit() {
+    let ext_data = sample_ext_data(1_000_000, 0, 121);
+    assert_eq!(ext_data.hash(), onchain_hash(&ext_data));
+}
+
+#[test]
+#[cfg(feature = "program")]
+fn test_hash_matches_onchain_for_withdrawal() {
+    let ext_data = sample_ext_data(-1_000_000, 10_000, 121);
+    assert_eq!(ext_data.hash(), onchain_hash(&ext_data));
+}
+
+#[test]
+#[cfg(feature = "program")]
+fn test_hash_matches_onchain_for_edge_values() {
+    for (ext_amount, fee, output_len) in [
+        (i64::MAX, u64::MAX, 0),
+        (-i64::MAX, 0, 1),
+        (0, 1, 255),
+        (1, 0, 256),
+        (-1, 0, 65_536),
+    ] {
+        let ext_data = sample_ext_data(ext_amount, fee, output_len);
+        assert_eq!(ext_data.hash(), onchain_hash(&ext_data), "ext_amount {ext_amount}, fee {fee}, output_len {output_len}");
+    }
+}
+
+#[test]
+#[cfg(feature = "program")]
+fn test_hash_matches_onchain_for_spl_mint() {
+    let ext_data = ExtData::builder()
+        .recipient(Pubkey::new_unique())
+        .ext_amount(-42)
+        .fee_recipient(Pubkey::new_unique())
+        .mint_address(Pubkey::new_unique())
+        .build()
+        .unwrap();
+    assert_eq!(ext_data.hash(), onchain_hash(&ext_data));
+}
+
+#[test]
+fn test_serialized_layout() {
+    let recipient = Pubkey::new_from_array([1u8; 32]);
+    let fee_recipient = Pubkey::new_from_array([2u8; 32]);
+    let mint_address = Pubkey::new_from_array([3u8; 32]);
+    let ext_data = ExtData::builder()
+        .recipient(recipient)
+        .ext_amount(-5)
+        .encrypted_outputs(vec![9, 9], vec![7])
+        .fee(6)
+        .fee_recipient(fee_recipient)
+        .mint_address(mint_address)
+        .build()
+        .unwrap();
+
+    let mut expected = Vec::new();
+    expected.extend_from_slice(&[1u8; 32]);
+    expected.extend_from_slice(&(-5i64).to_le_bytes());
+    expected.extend_from_slice(&2u32.to_le_bytes());
+    expected.extend_from_slice(&[9, 9]);
+    expected.extend_from_slice(&1u32.to_le_bytes());
+    expected.extend_from_slice(&[7]);
+    expected.extend_from_slice(&6u64.to_le_bytes());
+    expected.extend_from_slice(&[2u8; 32]);
+    expected.extend_from_slice(&[3u8; 32]);
+
+    assert_eq!(ext_data.serialize_for_hash(), expected);
+}
+
+#[test]
+fn test_builder_defaults_to_sol_mint() {
+    let ext_data = sample_ext_data(100, 0, 0);
+    assert_eq!(ext_data.mint_address, Pubkey::from_str(SOL_MINT_ADDRESS).unwrap());
+}
+
+#[test]
+fn test_builder_requires_recipients() {
+    let missing_recipient = ExtData::builder().fee_recipient(Pubkey::new_unique()).build();
+    assert_eq!(missing_recipient.err(), Some(ClientError::MissingExtDataField("recipient")));
+
+    let missing_fee_recipient = ExtData::builder().recipient(Pubkey::new_unique()).build();
+    assert_eq!(missing_fee_recipient.err(), Some(ClientError::MissingExtDataField("fee_recipient")));
+}
+
+#[test]
+#[cfg(feature = "program")]
+fn test_public_input_is_le_hash_as_field_element() {
+    // Same comparison the program performs between the recomputed hash and proof.ext_data_hash
+    let ext_data = sample_ext_data(-1_000, 10, 121);
+    let public_input = ext_data.public_input();
+
+    assert_eq!(
+        Fr::from_be_bytes_mod_order(&public_input),
+        Fr::from_le_bytes_mod_order(&onchain_hash(&ext_data))
+    );
+}
+
+#[test]
+#[cfg(feature = "program")]
+fn test_memo_hash_matches_onchain() {
+    let ext_data = ExtData::builder()
+        .recipient(Pubkey::new_unique())
+        .ext_amount(-1_000_000)
+        .encrypted_outputs(vec![0xab; 121], vec![0xcd; 121])
+        .fee(10_000)
+        .fee_recipient(Pubkey::new_unique())
+        .memo(b"104837261".to_vec())
+        .build()
+        .unwrap();
+
+    let onchain = calculate_ext_data_hash_with_memo(
+        ext_data.recipient,
+        ext_data.ext_amount,
+        &ext_data.encrypted_output1,
+        &ext_data.encrypted_output2,
+        ext_data.fee,
+        ext_data.fee_recipient,
+        ext_data.mint_address,
+        &ext_data.memo,
+    )
+    .unwrap();
+    assert_eq!(ext_data.hash(), onchain);
+    assert_ne!(ext_data.hash(), onchain_hash(&ext_data));
+}
+
+#[test]
+#[cfg(feature = "program")]
+fn test_empty_memo_is_not_serialized() {
+    let ext_data = sample_ext_data(-5, 6, 2);
+    assert!(ext_data.memo.is_empty());
+    assert_eq!(ext_data.hash(), onchain_hash(&ext_data));
+}
+
+#[test]
+fn test_builder_rejects_long_memo() {
+    let builder = || ExtData::builder().recipient(Pubkey::new_unique()).fee_recipient(Pubkey::new_unique());
+    assert!(builder().memo(vec![1; MAX_MEMO_LEN]).build().is_ok());
+    assert_eq!(builder().memo(vec![1; MAX_MEMO_LEN + 1]).build().err(), Some(ClientError::MemoTooLong));
+}
+
+#[test]
+#[cfg(feature = "program")]
+fn test_versioned_hash_matches_onchain() {
+    for version in EXT_DATA_VERSION_LEGACY..=EXT_DATA_VERSION {
+        for memo in [Vec::new(), b"104837261".to_vec()] {
+            let expiry_slot = if version >= EXT_DATA_VERSION_EXPIRY { 350_000_000 } else { 0 };
+            let mut builder = ExtData::builder();
+            if version >= EXT_DATA_VERSION_RELAYER {
+                builder = builder.relayer(Pubkey::new_unique());
+            }
+            // A swap and a CPI can't be combined, so each gets its own version
+            if version == EXT_DATA_VERSION_SWAP {
+                builder = builder.swap(SwapParams::new(Pubkey::new_unique(), Pubkey::new_unique(), 990, b"route"));
+            }
+            if version == EXT_DATA_VERSION_CPI {
+                builder = builder.cpi(CpiTarget::new(Pubkey::new_unique(), b"deposit"));
+            }
+            if version >= EXT_DATA_VERSION_FOREIGN_RECIPIENT {
+                builder = builder.foreign_recipient(ForeignRecipient { chain_id: 2, address: [7u8; 32] });
+            }
+            if version >= EXT_DATA_VERSION_MEMO_CPI && !memo.is_empty() {
+                builder = builder.memo_cpi();
+            }
+            let ext_data = builder
+                .recipient(Pubkey::new_unique())
+                .ext_amount(-1_000_000)
+                .encrypted_outputs(vec![0xab; 121], vec![0xcd; 121])
+                .fee(10_000)
+                .fee_recipient(Pubkey::new_unique())
+                .memo(memo)
+                .version(version)
+                .expiry_slot(expiry_slot)
+                .build()
+                .unwrap();
+
+            let onchain = calculate_versioned_ext_data_hash(
+                version,
+                &ExtDataFields {
+                    recipient: ext_data.recipient,
+                    ext_amount: ext_data.ext_amount,
+                    encrypted_output1: &ext_data.encrypted_output1,
+                    encrypted_output2: &ext_data.encrypted_output2,
+                    fee: ext_data.fee,
+                    fee_recipient: ext_data.fee_recipient,
+                    mint_address: ext_data.mint_address,
+                    memo: &ext_data.memo,
+                    expiry_slot: ext_data.expiry_slot,
+                    max_fee: ext_data.max_fee,
+                    relayer: ext_data.relayer,
+                    swap: ext_data.swap.map(|swap| zkcash::SwapParams {
+                        program: swap.program,
+                        output_mint: swap.output_mint,
+                        min_output_amount: swap.min_output_amount,
+                        route_hash: swap.route_hash,
+                    }),
+                    cpi: ext_data.cpi.map(|cpi| zkcash::CpiTarget {
+                        program: cpi.program,
+                        data_hash: cpi.data_hash,
+                    }),
+                    foreign_recipient: ext_data.foreign_recipient.map(|recipient| zkcash::ForeignRecipient {
+                        chain_id: recipient.chain_id,
+                        address: recipient.address,
+                    }),
+                    memo_cpi: ext_data.memo_cpi,
+                },
+            )
+            .unwrap();
+            assert_eq!(ext_data.hash(), onchain, "version {version}, memo {:?}", ext_data.memo);
+        }
+    }
+}
+
+#[test]
+fn test_builder_defaults_to_legacy_version() {
+    let ext_data = sample_ext_data(-5, 6, 2);
+    assert_eq!(ext_data.version, EXT_DATA_VERSION_LEGACY);
+    assert_eq!(ext_data.serialize_for_hash()[..32], ext_data.recipient.to_bytes());
+}
+
+#[test]
+fn test_builder_rejects_unknown_version() {
+    let result = ExtData::builder()
+        .recipient(Pubkey::new_unique())
+        .fee_recipient(Pubkey::new_unique())
+        .version(EXT_DATA_VERSION + 1)
+        .build();
+    assert_eq!(result.err(), Some(ClientError::UnsupportedExtDataVersion));
+}
+
+#[test]
+fn test_expiry_slot_needs_expiry_version() {
+    let builder = || ExtData::builder().recipient(Pubkey::new_unique()).fee_recipient(Pubkey::new_unique());
+    for version in [EXT_DATA_VERSION_LEGACY, 1] {
+        assert_eq!(
+            builder().version(version).expiry_slot(100).build().err(),
+            Some(ClientError::UnsupportedExtDataField("expiry_slot"))
+        );
+        assert!(builder().version(version).build().is_ok());
+    }
+    assert!(builder().version(EXT_DATA_VERSION_EXPIRY).expiry_slot(100).build().is_ok());
+}
+
+#[test]
+fn test_expiry_slot_ends_the_v2_preimage() {
+    let ext_data = ExtData::builder()
+        .recipient(Pubkey::new_unique())
+        .fee_recipient(Pubkey::new_unique())
+        .version(EXT_DATA_VERSION_EXPIRY)
+        .expiry_slot(0x0102_0304)
+        .build()
+        .unwrap();
+    let serialized = ext_data.serialize_for_hash();
+    assert_eq!(serialized[0], EXT_DATA_VERSION_EXPIRY);
+    assert_eq!(serialized[serialized.len() - 8..], 0x0102_0304u64.to_le_bytes());
+}
+
+#[test]
+fn test_max_fee_defaults_to_fee() {
+    let ext_data = ExtData::builder()
+        .recipient(Pubkey::new_unique())
+        .fee_recipient(Pubkey::new_unique())
+        .fee(5_000)
+        .version(EXT_DATA_VERSION_MAX_FEE)
+        .build()
+        .unwrap();
+    assert_eq!(ext_data.max_fee, 5_000);
+    let serialized = ext_data.serialize_for_hash();
+    assert_eq!(serialized[serialized.len() - 8..], 5_000u64.to_le_bytes());
+}
+
+#[test]
+fn test_builder_rejects_fee_above_max_fee() {
+    let builder = || {
+        ExtData::builder()
+            .recipient(Pubkey::new_unique())
+            .fee_recipient(Pubkey::new_unique())
+            .fee(5_000)
+            .version(EXT_DATA_VERSION_MAX_FEE)
+    };
+    assert!(builder().max_fee(5_000).build().is_ok());
+    assert!(builder().max_fee(6_000).build().is_ok());
+    assert_eq!(builder().max_fee(4_999).build().err(), Some(ClientError::FeeExceedsMaxFee));
+}
+
+#[test]
+fn test_max_fee_needs_max_fee_version() {
+    let result = ExtData::builder()
+        .recipient(Pubkey::new_unique())
+        .fee_recipient(Pubkey::new_unique())
+        .version(EXT_DATA_VERSION_EXPIRY)
+        .max_fee(1)
+        .build();
+    assert_eq!(result.err(), Some(ClientError::UnsupportedExtDataField("max_fee")));
+}
+
+#[test]
+fn test_relayer_needs_relayer_version() {
+    let builder = || ExtData::builder().recipient(Pubkey::new_unique()).fee_recipient(Pubkey::new_unique());
+    assert_eq!(
+        builder().version(EXT_DATA_VERSION_MAX_FEE).relayer(Pubkey::new_unique()).build().err(),
+        Some(ClientError::UnsupportedExtDataField("relayer"))
+    );
+    assert!(builder().version(EXT_DATA_VERSION_RELAYER).relayer(Pubkey::new_unique()).build().is_ok());
+}
+
+#[test]
+fn test_relayer_ends_the_v4_preimage() {
+    let relayer = Pubkey::new_unique();
+    let builder = || {
+        ExtData::builder()
+            .recipient(Pubkey::new_unique())
+            .fee_recipient(Pubkey::new_unique())
+            .version(EXT_DATA_VERSION_RELAYER)
+    };
+
+    let bound = builder().relayer(relayer).build().unwrap().serialize_for_hash();
+    assert_eq!(bound[bound.len() - 33], 1);
+    assert_eq!(bound[bound.len() - 32..], relayer.to_bytes());
+
+    let unbound = builder().build().unwrap().serialize_for_hash();
+    assert_eq!(unbound[unbound.len() - 1], 0);
+}
+
+
+#[test]
+fn test_swap_needs_swap_version() {
+    let builder = || ExtData::builder().recipient(Pubkey::new_unique()).fee_recipient(Pubkey::new_unique());
+    let swap = SwapParams::new(Pubkey::new_unique(), Pubkey::new_unique(), 990, b"route");
+    assert_eq!(
+        builder().version(EXT_DATA_VERSION_RELAYER).swap(swap).build().err(),
+        Some(ClientError::UnsupportedExtDataField("swap"))
+    );
+    assert!(builder().version(EXT_DATA_VERSION_SWAP).swap(swap).build().is_ok());
+}
+
+#[test]
+fn test_cpi_needs_cpi_version_and_excludes_swap() {
+    let builder = || ExtData::builder().recipient(Pubkey::new_unique()).fee_recipient(Pubkey::new_unique());
+    let cpi = CpiTarget::new(Pubkey::new_unique(), b"deposit");
+    assert_eq!(
+        builder().version(EXT_DATA_VERSION_SWAP).cpi(cpi).build().err(),
+        Some(ClientError::UnsupportedExtDataField("cpi"))
+    );
+    assert!(builder().version(EXT_DATA_VERSION_CPI).cpi(cpi).build().is_ok());
+
+    let swap = SwapParams::new(Pubkey::new_unique(), Pubkey::new_unique(), 990, b"route");
+    assert_eq!(
+        builder().version(EXT_DATA_VERSION_CPI).swap(swap).cpi(cpi).build().err(),
+        Some(ClientError::ConflictingWithdrawalModes)
+    );
+}
+
+#[test]
+fn test_foreign_recipient_needs_its_version() {
+    let builder = || ExtData::builder().recipient(Pubkey::new_unique()).fee_recipient(Pubkey::new_unique());
+    let recipient = ForeignRecipient { chain_id: 2, address: [7u8; 32] };
+    assert_eq!(
+        builder().version(EXT_DATA_VERSION_CPI).foreign_recipient(recipient).build().err(),
+        Some(ClientError::UnsupportedExtDataField("foreign_recipient"))
+    );
+    assert!(builder().version(EXT_DATA_VERSION_FOREIGN_RECIPIENT).foreign_recipient(recipient).build().is_ok());
+
+    let cpi = CpiTarget::new(Pubkey::new_unique(), b"deposit");
+    assert_eq!(
+        builder().version(EXT_DATA_VERSION_FOREIGN_RECIPIENT).cpi(cpi).foreign_recipient(recipient).build().err(),
+        Some(ClientError::ConflictingWithdrawalModes)
+    );
+}
+
+#[test]
+fn test_memo_cpi_needs_its_version_and_a_withdrawal_memo() {
+    let builder = || {
+        ExtData::builder()
+            .recipient(Pubkey::new_unique())
+            .fee_recipient(Pubkey::new_unique())
+            .ext_amount(-1_000)
+            .memo(b"104837261".to_vec())
+            .memo_cpi()
+    };
+    assert_eq!(
+        builder().version(EXT_DATA_VERSION_FOREIGN_RECIPIENT).build().err(),
+        Some(ClientError::UnsupportedExtDataField("memo_cpi"))
+    );
+    let ext_data = builder().version(EXT_DATA_VERSION_MEMO_CPI).build().unwrap();
+    assert_eq!(ext_data.serialize_for_hash().last(), Some(&1));
+
+    for invalid in [
+        builder().memo(Vec::new()),
+        builder().memo(vec![0xff, 0xfe]),
+        builder().ext_amount(1_000),
+    ] {
+        assert_eq!(
+            invalid.version(EXT_DATA_VERSION_MEMO_CPI).build().err(),
+            Some(ClientError::InvalidMemoCpi)
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use anchor_lang::prelude::AnchorSerialize;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use zkcash_client::errors::ClientError;
+use zkcash_client::indexer::{
+    nullifiers_from_instructions, transact_discriminator, IndexCursor, IndexStore, Indexer, MemoryStore,
+    PoolTransaction, TransactionStream,
+};
+use zkcash_client::ledger_history::commitment_event_discriminator;
+use zkcash_client::recovery::ChainSource;
+use zkcash_client::tree_sync::CommitmentEvent;
+
+fn event(index: u64) -> CommitmentEvent {
+    CommitmentEvent {
+        index,
+        commitment: [index as u8 + 1; 32],
+        encrypted_output: vec![index as u8; 16],
+    }
+}
+
+fn event_log(event: &CommitmentEvent) -> String {
+    let mut data = commitment_event_discriminator().to_vec();
+    event.serialize(&mut data).unwrap();
+    format!("Program data: {}", STANDARD.encode(data))
+}
+
+/// Transact data with the given nullifiers and every other proof byte zero.
+fn transact_data(nullifiers: [[u8; 32]; 2]) -> Vec<u8> {
+    let mut data = transact_discriminator().to_vec();
+    data.extend_from_slice(&[0u8; 64 + 128 + 64 + 3 * 32]);
+    data.extend_from_slice(&nullifiers[0]);
+    data.extend_from_slice(&nullifiers[1]);
+    data.extend_from_slice(&[0u8; 64]);
+    data
+}
+
+fn transaction(slot: u64, leaves: [u64; 2], nullifiers: [[u8; 32]; 2]) -> PoolTransaction {
+    PoolTransaction {
+        signature: format!("sig{slot}"),
+        slot,
+        logs: leaves.iter().map(|&index| event_log(&event(index))).collect(),
+        instructions: vec![transact_data(nullifiers)],
+    }
+}
+
+struct VecStream(Vec<PoolTransaction>);
+
+impl TransactionStream for VecStream {
+    fn next_transaction(&mut self) -> Result<Option<PoolTransaction>, ClientError> {
+        Ok((!self.0.is_empty()).then(|| self.0.remove(0)))
+    }
+}
+
+#[test]
+fn reads_nullifiers_from_transact_data_only() {
+    let nullifiers = [[7u8; 32], [8u8; 32]];
+    let other = vec![1u8; 600];
+    assert_eq!(nullifiers_from_instructions(&[other, transact_data(nullifiers)]), nullifiers.to_vec());
+}
+
+#[test]
+fn indexes_leaves_nullifiers_and_cursor() {
+    let mut indexer = Indexer::new(MemoryStore::default());
+    let mut stream = VecStream(vec![
+        transaction(10, [0, 1], [[1u8; 32], [2u8; 32]]),
+        transaction(11, [2, 3], [[3u8; 32], [4u8; 32]]),
+    ]);
+    indexer.run(&mut stream).unwrap();
+
+    assert_eq!(indexer.contiguous_leaves().unwrap(), 4);
+    assert_eq!(indexer.store().leaf(2).unwrap(), Some(event(2)));
+    assert!(indexer.store().is_spent(&[3u8; 32]).unwrap());
+    assert!(!indexer.store().is_spent(&[9u8; 32]).unwrap());
+    assert_eq!(
+        indexer.cursor().unwrap(),
+        Some(IndexCursor { slot: 11, signature: "sig11".to_string() })
+    );
+}
+
+#[test]
+fn replayed_transactions_add_nothing() {
+    let mut indexer = Indexer::new(MemoryStore::default());
+    let tx = transaction(10, [0, 1], [[1u8; 32], [2u8; 32]]);
+    assert_eq!(indexer.apply(&tx).unwrap().commitments.len(), 2);
+    assert!(indexer.apply(&tx).unwrap().commitments.is_empty());
+}
+
+#[test]
+fn rejects_conflicting_leaf() {
+    let mut indexer = Indexer::new(MemoryStore::default());
+    indexer.apply(&transaction(10, [0, 1], [[1u8; 32], [2u8; 32]])).unwrap();
+
+    let mut forged = transaction(11, [0, 5], [[3u8; 32], [4u8; 32]]);
+    forged.logs[0] = event_log(&CommitmentEvent { commitment: [0xff; 32], ..event(0) });
+    assert_eq!(indexer.apply(&forged).unwrap_err(), ClientError::ConflictingLeaf);
+    assert!(!indexer.store().is_spent(&[3u8; 32]).unwrap());
+}
+
+#[test]
+fn contiguous_leaves_stops_at_gap() {
+    let mut indexer = Indexer::new(MemoryStore::default());
+    indexer.apply(&transaction(10, [0, 1], [[1u8; 32], [2u8; 32]])).unwrap();
+    indexer.apply(&transaction(12, [4, 5], [[3u8; 32], [4u8; 32]])).unwrap();
+    assert_eq!(indexer.contiguous_leaves().unwrap(), 2);
+}
+
+#[test]
+fn serves_wallet_scans_as_chain_source() {
+    let mut indexer = Indexer::new(MemoryStore::default());
+    indexer.apply(&transaction(10, [0, 1], [[1u8; 32], [2u8; 32]])).unwrap();
+
+    assert_eq!(indexer.commitment_events(1).unwrap(), vec![event(1)]);
+    assert_eq!(indexer.nullifiers_spent(&[[2u8; 32], [5u8; 32]]).unwrap(), vec![true, false]);
+}
@@ -0,0 +1,78 @@
+use ark_bn254::Fr;
+use zkcash_client::errors::ClientError;
+use zkcash_client::keys::ShieldedKeys;
+use zkcash_client::utils::poseidon_hash;
+
+const TEST_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+#[test]
+fn test_derivation_is_deterministic() {
+    let first = ShieldedKeys::from_mnemonic(TEST_MNEMONIC, "", 0).unwrap();
+    let second = ShieldedKeys::from_mnemonic(TEST_MNEMONIC, "", 0).unwrap();
+
+    assert_eq!(first.spending_key(), second.spending_key());
+    assert_eq!(first.public_key(), second.public_key());
+    assert_eq!(first.encryption_keypair().public_key(), second.encryption_keypair().public_key());
+}
+
+#[test]
+fn test_account_index_separates_keys() {
+    let account0 = ShieldedKeys::from_mnemonic(TEST_MNEMONIC, "", 0).unwrap();
+    let account1 = ShieldedKeys::from_mnemonic(TEST_MNEMONIC, "", 1).unwrap();
+
+    assert_ne!(account0.spending_key(), account1.spending_key());
+    assert_ne!(account0.encryption_keypair().public_key(), account1.encryption_keypair().public_key());
+}
+
+#[test]
+fn test_passphrase_separates_keys() {
+    let plain = ShieldedKeys::from_mnemonic(TEST_MNEMONIC, "", 0).unwrap();
+    let protected = ShieldedKeys::from_mnemonic(TEST_MNEMONIC, "hunter2", 0).unwrap();
+
+    assert_ne!(plain.spending_key(), protected.spending_key());
+}
+
+#[test]
+fn test_public_key_matches_circuit_keypair() {
+    // keypair.circom: publicKey = Poseidon(privateKey)
+    let keys = ShieldedKeys::from_mnemonic(TEST_MNEMONIC, "", 0).unwrap();
+    let expected = poseidon_hash(&[keys.spending_key()]).unwrap();
+
+    assert_eq!(keys.public_key(), expected);
+}
+
+#[test]
+fn test_nullifier_matches_circuit_definition() {
+    // nullifier = Poseidon(commitment, merklePath, Poseidon(privateKey, commitment, merklePath))
+    let keys = ShieldedKeys::from_mnemonic(TEST_MNEMONIC, "", 0).unwrap();
+    let commitment = Fr::from(123456u64);
+    let index = 42u64;
+
+    let signature = poseidon_hash(&[keys.spending_key(), commitment, Fr::from(index)]).unwrap();
+    let expected = poseidon_hash(&[commitment, Fr::from(index), signature]).unwrap();
+
+    assert_eq!(keys.sign(commitment, index).unwrap(), signature);
+    assert_eq!(keys.nullifier(commitment, index).unwrap(), expected);
+}
+
+#[test]
+fn test_nullifier_depends_on_leaf_index() {
+    let keys = ShieldedKeys::from_mnemonic(TEST_MNEMONIC, "", 0).unwrap();
+    let commitment = Fr::from(1u64);
+
+    assert_ne!(keys.nullifier(commitment, 0).unwrap(), keys.nullifier(commitment, 1).unwrap());
+}
+
+#[test]
+fn test_invalid_mnemonic_rejected() {
+    let result = ShieldedKeys::from_mnemonic("not a valid mnemonic phrase", "", 0);
+    assert_eq!(result.err(), Some(ClientError::InvalidMnemonic));
+}
+
+#[test]
+fn test_short_seed_rejected() {
+    let result = ShieldedKeys::from_seed(&[1u8; 15], 0);
+    assert_eq!(result.err(), Some(ClientError::InvalidSeedLength));
+
+    assert!(ShieldedKeys::from_seed(&[1u8; 16], 0).is_ok());
+}
@@ -0,0 +1,87 @@
+use anchor_lang::prelude::{AnchorSerialize, Pubkey};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use rand_core::OsRng;
+use zkcash_client::errors::ClientError;
+use zkcash_client::keys::ShieldedKeys;
+use zkcash_client::ledger_history::{commitment_event_discriminator, commitment_events_from_logs, LedgerHistory};
+use zkcash_client::note::encrypt_note_to_address;
+use zkcash_client::recovery::scan_notes;
+use zkcash_client::tree_sync::CommitmentEvent;
+use zkcash_client::utils::fr_to_be_bytes;
+
+fn event_log(event: &CommitmentEvent) -> String {
+    let mut data = commitment_event_discriminator().to_vec();
+    event.serialize(&mut data).unwrap();
+    format!("Program data: {}", STANDARD.encode(data))
+}
+
+fn transaction_logs(events: &[CommitmentEvent]) -> Vec<String> {
+    let mut logs = vec!["Program 11111111111111111111111111111111 invoke [1]".to_string()];
+    logs.push(format!("Program data: {}", STANDARD.encode([7u8; 24])));
+    logs.extend(events.iter().map(event_log));
+    logs.push("Program log: Instruction: Transact".to_string());
+    logs
+}
+
+fn event(index: u64) -> CommitmentEvent {
+    CommitmentEvent {
+        index,
+        commitment: [index as u8 + 1; 32],
+        encrypted_output: vec![index as u8; 3],
+    }
+}
+
+#[test]
+fn test_parses_commitment_events_from_logs() {
+    let events = vec![event(4), event(5)];
+    assert_eq!(commitment_events_from_logs(&transaction_logs(&events)), events);
+    assert!(commitment_events_from_logs(&["Program data: not base64!".to_string()]).is_empty());
+}
+
+#[test]
+fn test_deduplicates_and_orders_events() {
+    let mut history = LedgerHistory::new(|nullifiers: &[[u8; 32]]| Ok(vec![false; nullifiers.len()]));
+    assert_eq!(history.add_transaction_logs(&transaction_logs(&[event(2), event(3)])).unwrap(), 2);
+    assert_eq!(history.add_transaction_logs(&transaction_logs(&[event(0), event(1)])).unwrap(), 2);
+    assert_eq!(history.add_transaction_logs(&transaction_logs(&[event(2), event(3)])).unwrap(), 0);
+    assert_eq!(history.len(), 4);
+
+    let mut conflicting = event(1);
+    conflicting.commitment = [9u8; 32];
+    assert_eq!(
+        history.add_transaction_logs(&transaction_logs(&[conflicting])),
+        Err(ClientError::ConflictingLeaf)
+    );
+}
+
+#[test]
+fn test_recovers_notes_from_transaction_logs() {
+    let keys = ShieldedKeys::from_seed(&[5u8; 32], 0).unwrap();
+    let mint = Pubkey::new_unique();
+    let address = keys.payment_address(0).unwrap();
+    let mut utxos = Vec::new();
+    let mut events = Vec::new();
+    for (index, amount) in [1_000u64, 2_000].into_iter().enumerate() {
+        let mut utxo = address.new_utxo(amount, mint, &mut OsRng);
+        utxo.index = index as u64;
+        events.push(CommitmentEvent {
+            index: utxo.index,
+            commitment: fr_to_be_bytes(&utxo.commitment().unwrap()),
+            encrypted_output: encrypt_note_to_address(&utxo.to_note(), &address, &mut OsRng).unwrap(),
+        });
+        utxos.push(utxo);
+    }
+    let spent = fr_to_be_bytes(&utxos[0].nullifier(&keys).unwrap());
+
+    let mut history = LedgerHistory::new(|nullifiers: &[[u8; 32]]| {
+        Ok(nullifiers.iter().map(|nullifier| *nullifier == spent).collect())
+    });
+    history.add_transaction_logs(&transaction_logs(&events)).unwrap();
+
+    let recovered = scan_notes(&keys, 0, &mut history).unwrap();
+    assert_eq!(recovered.unspent, vec![utxos[1].clone()]);
+    assert_eq!(recovered.spent_count, 1);
+    assert_eq!(recovered.balances[&mint], 2_000);
+    assert_eq!(recovered.next_index, 2);
+}
@@ -0,0 +1,21 @@
+mod note_test;
+mod keys_test;
+mod transaction_test;
+mod tree_sync_test;
+mod ext_data_test;
+mod address_test;
+mod utils_test;
+#[cfg(feature = "program")]
+mod nullifier_filter_test;
+#[cfg(feature = "program")]
+mod tree_snapshot_test;
+#[cfg(feature = "program")]
+mod tree_frontier_test;
+mod backup_test;
+mod recovery_test;
+mod ledger_history_test;
+#[cfg(feature = "program")]
+mod program_test;
+mod indexer_test;
+mod sync_checkpoint_test;
+mod differential_test;
\ No newline at end of file
@@ -0,0 +1,145 @@
+use anchor_lang::prelude::Pubkey;
+use rand_core::OsRng;
+use zkcash_client::address::Diversifier;
+use zkcash_client::note::{
+    detection_tag, encrypt_note, matches_detection_tag, try_decrypt_note, EncryptionKeypair, Note,
+    DETECTION_TAG_LEN, ENCRYPTED_NOTE_LEN, NOTE_VERSION,
+};
+
+fn sample_note() -> Note {
+    Note {
+        amount: 1_500_000_000,
+        blinding: [7u8; 32],
+        mint_address: Pubkey::new_unique(),
+        diversifier: Diversifier::default(),
+    }
+}
+
+#[test]
+fn test_encrypt_decrypt_roundtrip() {
+    let recipient = EncryptionKeypair::generate(&mut OsRng);
+    let note = sample_note();
+
+    let encrypted = encrypt_note(&note, &recipient.public_key(), &mut OsRng).unwrap();
+    let decrypted = try_decrypt_note(&encrypted, &recipient);
+
+    assert_eq!(decrypted, Some(note));
+}
+
+#[test]
+fn test_encrypted_note_layout() {
+    let recipient = EncryptionKeypair::generate(&mut OsRng);
+    let encrypted = encrypt_note(&sample_note(), &recipient.public_key(), &mut OsRng).unwrap();
+
+    assert_eq!(encrypted.len(), ENCRYPTED_NOTE_LEN);
+    assert_eq!(encrypted[0], NOTE_VERSION);
+}
+
+#[test]
+fn test_encryption_is_randomized() {
+    // Fresh ephemeral keys mean the same note never encrypts to the same bytes
+    let recipient = EncryptionKeypair::generate(&mut OsRng);
+    let note = sample_note();
+
+    let first = encrypt_note(&note, &recipient.public_key(), &mut OsRng).unwrap();
+    let second = encrypt_note(&note, &recipient.public_key(), &mut OsRng).unwrap();
+
+    assert_ne!(first, second);
+}
+
+#[test]
+fn test_decrypt_with_wrong_key_fails() {
+    let recipient = EncryptionKeypair::generate(&mut OsRng);
+    let someone_else = EncryptionKeypair::generate(&mut OsRng);
+
+    let encrypted = encrypt_note(&sample_note(), &recipient.public_key(), &mut OsRng).unwrap();
+
+    assert_eq!(try_decrypt_note(&encrypted, &someone_else), None);
+}
+
+#[test]
+fn test_decrypt_tampered_ciphertext_fails() {
+    let recipient = EncryptionKeypair::generate(&mut OsRng);
+    let mut encrypted = encrypt_note(&sample_note(), &recipient.public_key(), &mut OsRng).unwrap();
+
+    let last = encrypted.len() - 1;
+    encrypted[last] ^= 1;
+
+    assert_eq!(try_decrypt_note(&encrypted, &recipient), None);
+}
+
+#[test]
+fn test_decrypt_unknown_version_fails() {
+    let recipient = EncryptionKeypair::generate(&mut OsRng);
+    let mut encrypted = encrypt_note(&sample_note(), &recipient.public_key(), &mut OsRng).unwrap();
+
+    encrypted[0] = NOTE_VERSION + 1;
+
+    assert_eq!(try_decrypt_note(&encrypted, &recipient), None);
+}
+
+#[test]
+fn test_decrypt_malformed_length_fails() {
+    let recipient = EncryptionKeypair::generate(&mut OsRng);
+
+    assert_eq!(try_decrypt_note(&[], &recipient), None);
+    assert_eq!(try_decrypt_note(&[NOTE_VERSION; ENCRYPTED_NOTE_LEN - 1], &recipient), None);
+    // The TypeScript test client passes JSON strings as encrypted outputs
+    assert_eq!(try_decrypt_note(b"{\"amount\":\"800\"}", &recipient), None);
+}
+
+#[test]
+fn test_keypair_from_secret_bytes_is_deterministic() {
+    let first = EncryptionKeypair::from_secret_bytes([42u8; 32]);
+    let second = EncryptionKeypair::from_secret_bytes([42u8; 32]);
+
+    assert_eq!(first.public_key(), second.public_key());
+
+    let encrypted = encrypt_note(&sample_note(), &first.public_key(), &mut OsRng).unwrap();
+    assert!(try_decrypt_note(&encrypted, &second).is_some());
+}
+
+#[test]
+fn test_detection_tag_matches_only_the_recipient() {
+    let recipient = EncryptionKeypair::generate(&mut OsRng);
+    let someone_else = EncryptionKeypair::generate(&mut OsRng);
+    let encrypted = encrypt_note(&sample_note(), &recipient.public_key(), &mut OsRng).unwrap();
+
+    assert_eq!(detection_tag(&encrypted).unwrap()[..], encrypted[1..1 + DETECTION_TAG_LEN]);
+    assert!(matches_detection_tag(&encrypted, &recipient));
+    assert!(!matches_detection_tag(&encrypted, &someone_else));
+}
+
+#[test]
+fn test_detection_tag_is_unlinkable() {
+    // Tags come from the per-note shared secret, not the recipient key alone
+    let recipient = EncryptionKeypair::generate(&mut OsRng);
+    let note = sample_note();
+
+    let first = encrypt_note(&note, &recipient.public_key(), &mut OsRng).unwrap();
+    let second = encrypt_note(&note, &recipient.public_key(), &mut OsRng).unwrap();
+
+    assert_ne!(detection_tag(&first), detection_tag(&second));
+}
+
+#[test]
+fn test_tampered_detection_tag_fails() {
+    let recipient = EncryptionKeypair::generate(&mut OsRng);
+    let mut encrypted = encrypt_note(&sample_note(), &recipient.public_key(), &mut OsRng).unwrap();
+
+    encrypted[1] ^= 1;
+
+    assert!(!matches_detection_tag(&encrypted, &recipient));
+    assert_eq!(try_decrypt_note(&encrypted, &recipient), None);
+}
+
+#[test]
+fn test_malformed_outputs_never_match() {
+    let recipient = EncryptionKeypair::generate(&mut OsRng);
+    let encrypted = encrypt_note(&sample_note(), &recipient.public_key(), &mut OsRng).unwrap();
+
+    for malformed in [&encrypted[..ENCRYPTED_NOTE_LEN - 1], &b"{\"amount\":\"800\"}"[..]] {
+        assert_eq!(detection_tag(malformed), None);
+        assert!(!matches_detection_tag(malformed, &recipient));
+    }
+}
@@ -0,0 +1,48 @@
+use anchor_lang::Discriminator;
+use zkcash::{NullifierBloomFilter, NULLIFIER_BLOOM_BYTES};
+use zkcash_client::errors::ClientError;
+use zkcash_client::nullifier_filter::NullifierFilter;
+
+fn account_data(filter: &NullifierBloomFilter) -> Vec<u8> {
+    let mut data = NullifierBloomFilter::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(filter.tree_account.as_ref());
+    data.extend_from_slice(&filter.inserted.to_le_bytes());
+    data.extend_from_slice(&filter.bits);
+    data
+}
+
+#[test]
+fn test_matches_program_filter() {
+    let mut onchain = NullifierBloomFilter {
+        tree_account: Default::default(),
+        inserted: 0,
+        bits: [0; NULLIFIER_BLOOM_BYTES],
+    };
+    let spent: Vec<[u8; 32]> = (1..50u8).map(|i| [i; 32]).collect();
+    for nullifier in &spent {
+        onchain.insert(nullifier);
+    }
+
+    let filter = NullifierFilter::from_account_data(&account_data(&onchain)).unwrap();
+    assert_eq!(filter.inserted, spent.len() as u64);
+    for nullifier in spent.iter().chain([[200u8; 32], [0xab; 32]].iter()) {
+        assert_eq!(filter.probably_spent(nullifier), onchain.might_contain(nullifier));
+    }
+    assert!(filter.false_positive_rate() > 0.0 && filter.false_positive_rate() < 1e-3);
+}
+
+#[test]
+fn test_rejects_other_accounts() {
+    let onchain = NullifierBloomFilter {
+        tree_account: Default::default(),
+        inserted: 0,
+        bits: [0; NULLIFIER_BLOOM_BYTES],
+    };
+    let mut data = account_data(&onchain);
+    data.pop();
+    assert_eq!(NullifierFilter::from_account_data(&data), Err(ClientError::InvalidAccountData));
+
+    let mut data = account_data(&onchain);
+    data[0] ^= 1;
+    assert_eq!(NullifierFilter::from_account_data(&data), Err(ClientError::InvalidAccountData));
+}
@@ -0,0 +1,148 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountSerialize, Discriminator};
+use std::collections::HashMap;
+use zkcash::{LeafPage, PoolFreeze, Proof, LEAVES_PER_PAGE};
+use zkcash_client::errors::ClientError;
+use zkcash_client::program::{
+    commitment_pdas, decode_account, fetch_pool_freeze, fetch_tree_leaves, global_config_pda, initialize,
+    leaf_page_pda, nullifier_pdas, pool_freeze_pda, role_config_pda, set_leaf_storage, set_pool_freeze, transact,
+    tree_account_pda, AccountSource, TransactArgs, PROGRAM_ID,
+};
+
+struct Accounts(HashMap<Pubkey, Vec<u8>>);
+
+impl AccountSource for Accounts {
+    fn account_data(&self, address: &Pubkey) -> Result<Option<Vec<u8>>, ClientError> {
+        Ok(self.0.get(address).cloned())
+    }
+}
+
+fn proof() -> Proof {
+    Proof {
+        proof_a: [1u8; 64],
+        proof_b: [2u8; 128],
+        proof_c: [3u8; 64],
+        root: [4u8; 32],
+        public_amount: [5u8; 32],
+        ext_data_hash: [6u8; 32],
+        input_nullifiers: [[7u8; 32], [8u8; 32]],
+        output_commitments: [[9u8; 32], [10u8; 32]],
+    }
+}
+
+fn pool_freeze_data(freeze: &PoolFreeze) -> Vec<u8> {
+    let mut data = Vec::new();
+    freeze.try_serialize(&mut data).unwrap();
+    data
+}
+
+#[test]
+fn initialize_uses_the_global_pdas() {
+    let authority = Pubkey::new_unique();
+    let instruction = initialize(authority);
+
+    assert_eq!(instruction.program_id, PROGRAM_ID);
+    assert_eq!(instruction.accounts[0].pubkey, tree_account_pda());
+    assert_eq!(instruction.accounts[2].pubkey, global_config_pda());
+    assert!(instruction.accounts[3].is_signer && instruction.accounts[3].pubkey == authority);
+    assert_eq!(instruction.data, zkcash::instruction::Initialize::DISCRIMINATOR);
+}
+
+#[test]
+fn transact_derives_nullifier_and_commitment_accounts_from_the_proof() {
+    let signer = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+    let args = TransactArgs {
+        proof: proof(),
+        ext_amount: -1_000,
+        fee: 10,
+        encrypted_output1: vec![1; 8],
+        encrypted_output2: vec![2; 8],
+        recipient,
+        fee_recipient: Pubkey::new_unique(),
+    };
+    let instruction = transact(signer, args);
+    let keys: Vec<Pubkey> = instruction.accounts.iter().map(|meta| meta.pubkey).collect();
+
+    assert_eq!(keys[1..5], nullifier_pdas(&proof()));
+    assert_eq!(keys[5..7], commitment_pdas(&proof()));
+    assert_eq!(keys[7], recipient);
+    let signers: Vec<Pubkey> = instruction.accounts.iter().filter(|meta| meta.is_signer).map(|meta| meta.pubkey).collect();
+    assert_eq!(signers, vec![signer]);
+    assert!(instruction.data.starts_with(zkcash::instruction::Transact::DISCRIMINATOR));
+}
+
+#[test]
+fn cross_check_nullifiers_swap_prefixes() {
+    let [nullifier0, nullifier1, nullifier2, nullifier3] = nullifier_pdas(&proof());
+    assert_ne!(nullifier0, nullifier3);
+    assert_ne!(nullifier1, nullifier2);
+    assert_eq!(
+        nullifier2,
+        Pubkey::find_program_address(&[b"nullifier0", &proof().input_nullifiers[1]], &PROGRAM_ID).0
+    );
+}
+
+#[test]
+fn set_pool_freeze_encodes_its_arguments() {
+    let instruction = set_pool_freeze(Pubkey::new_unique(), true, false);
+    let mut expected = zkcash::instruction::SetPoolFreeze::DISCRIMINATOR.to_vec();
+    expected.extend_from_slice(&[1, 0]);
+    assert_eq!(instruction.data, expected);
+    assert_eq!(instruction.accounts[0].pubkey, pool_freeze_pda());
+}
+
+#[test]
+fn set_leaf_storage_always_passes_the_role_config() {
+    let operator = Pubkey::new_unique();
+    let instruction = set_leaf_storage(operator, true);
+    assert_eq!(instruction.accounts[2].pubkey, role_config_pda());
+    assert!(instruction.accounts[3].is_signer && instruction.accounts[3].pubkey == operator);
+}
+
+#[test]
+fn fetches_and_decodes_accounts() {
+    let freeze = PoolFreeze { withdrawals_frozen: true, deposits_frozen: false, bump: 254 };
+    let source = Accounts(HashMap::from([(pool_freeze_pda(), pool_freeze_data(&freeze))]));
+
+    let fetched = fetch_pool_freeze(&source).unwrap().unwrap();
+    assert!(fetched.withdrawals_frozen && !fetched.deposits_frozen);
+    assert_eq!(fetched.bump, 254);
+
+    assert!(fetch_pool_freeze(&Accounts(HashMap::new())).unwrap().is_none());
+}
+
+#[test]
+fn decode_rejects_wrong_discriminator() {
+    let mut data = pool_freeze_data(&PoolFreeze::default());
+    data[0] ^= 1;
+    assert_eq!(decode_account::<PoolFreeze>(&data).unwrap_err(), ClientError::InvalidAccountData);
+}
+
+fn leaf_page_data(tree_account: Pubkey, page_index: u64, leaves: &[[u8; 32]]) -> Vec<u8> {
+    let mut page: LeafPage = bytemuck::Zeroable::zeroed();
+    page.init(tree_account, page_index);
+    for (offset, leaf) in leaves.iter().enumerate() {
+        page.push(page.first_leaf_index() + offset as u64, *leaf).unwrap();
+    }
+    let mut data = LeafPage::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(bytemuck::bytes_of(&page));
+    data
+}
+
+#[test]
+fn fetches_tree_leaves_from_leaf_pages() {
+    let tree_account = Pubkey::new_unique();
+    let leaves: Vec<[u8; 32]> = (0..LEAVES_PER_PAGE + 3).map(|i| [(i % 251) as u8 + 1; 32]).collect();
+    let source = Accounts(HashMap::from([
+        (leaf_page_pda(&tree_account, 0), leaf_page_data(tree_account, 0, &leaves[..LEAVES_PER_PAGE])),
+        (leaf_page_pda(&tree_account, 1), leaf_page_data(tree_account, 1, &leaves[LEAVES_PER_PAGE..])),
+    ]));
+    assert_eq!(fetch_tree_leaves(&source, &tree_account).unwrap(), leaves);
+    assert!(fetch_tree_leaves(&Accounts(HashMap::new()), &tree_account).unwrap().is_empty());
+
+    // A page for another tree stored at this tree's address is rejected
+    let other = Pubkey::new_unique();
+    let source = Accounts(HashMap::from([(leaf_page_pda(&tree_account, 0), leaf_page_data(other, 0, &leaves[..1]))]));
+    assert_eq!(fetch_tree_leaves(&source, &tree_account).unwrap_err(), ClientError::InvalidAccountData);
+}
@@ -0,0 +1,122 @@
+use anchor_lang::prelude::Pubkey;
+use rand_core::OsRng;
+use std::collections::HashSet;
+use zkcash_client::errors::ClientError;
+use zkcash_client::keys::ShieldedKeys;
+use zkcash_client::note::encrypt_note_to_address;
+use zkcash_client::recovery::{decrypt_batch, recover_notes, scan_notes, ChainSource};
+use zkcash_client::tree_sync::CommitmentEvent;
+use zkcash_client::utils::fr_to_be_bytes;
+use zkcash_client::utxo::Utxo;
+
+const SEED: [u8; 32] = [5u8; 32];
+
+/// Serves events two at a time, like a paginated indexer.
+#[derive(Default)]
+struct MemorySource {
+    events: Vec<CommitmentEvent>,
+    spent: HashSet<[u8; 32]>,
+}
+
+impl ChainSource for MemorySource {
+    fn commitment_events(&mut self, from_index: u64) -> Result<Vec<CommitmentEvent>, ClientError> {
+        Ok(self.events.iter().skip(from_index as usize).take(2).cloned().collect())
+    }
+
+    fn nullifiers_spent(&mut self, nullifiers: &[[u8; 32]]) -> Result<Vec<bool>, ClientError> {
+        Ok(nullifiers.iter().map(|nullifier| self.spent.contains(nullifier)).collect())
+    }
+}
+
+impl MemorySource {
+    /// Appends an output paying `amount` to `keys`' `address`th address.
+    fn pay(&mut self, keys: &ShieldedKeys, address: u32, amount: u64, mint: Pubkey) -> Utxo {
+        let address = keys.payment_address(address).unwrap();
+        let mut utxo = address.new_utxo(amount, mint, &mut OsRng);
+        utxo.index = self.events.len() as u64;
+        self.events.push(CommitmentEvent {
+            index: utxo.index,
+            commitment: fr_to_be_bytes(&utxo.commitment().unwrap()),
+            encrypted_output: encrypt_note_to_address(&utxo.to_note(), &address, &mut OsRng).unwrap(),
+        });
+        utxo
+    }
+
+    fn spend(&mut self, keys: &ShieldedKeys, utxo: &Utxo) {
+        self.spent.insert(fr_to_be_bytes(&utxo.nullifier(keys).unwrap()));
+    }
+}
+
+#[test]
+fn test_recovers_unspent_notes_across_addresses() {
+    let keys = ShieldedKeys::from_seed(&SEED, 0).unwrap();
+    let stranger = ShieldedKeys::from_seed(&[9u8; 32], 0).unwrap();
+    let (sol, usdc) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut source = MemorySource::default();
+
+    let first = source.pay(&keys, 0, 1_000, sol);
+    source.pay(&stranger, 0, 7_000, sol);
+    let second = source.pay(&keys, 3, 2_000, sol);
+    let third = source.pay(&keys, 1, 500, usdc);
+    source.pay(&keys, 2, 0, sol);
+    source.spend(&keys, &first);
+
+    let recovered = recover_notes(&SEED, 0, &mut source).unwrap();
+    assert_eq!(recovered.unspent, vec![second, third]);
+    assert_eq!(recovered.spent_count, 1);
+    assert_eq!(recovered.balances[&sol], 2_000);
+    assert_eq!(recovered.balances[&usdc], 500);
+    assert_eq!(recovered.next_index, 5);
+}
+
+#[test]
+fn test_ignores_outputs_that_dont_match_their_commitment() {
+    let keys = ShieldedKeys::from_seed(&SEED, 0).unwrap();
+    let mut source = MemorySource::default();
+    source.pay(&keys, 0, 1_000, Pubkey::new_unique());
+    source.events[0].commitment = [1u8; 32];
+
+    let recovered = recover_notes(&SEED, 0, &mut source).unwrap();
+    assert!(recovered.unspent.is_empty());
+    assert_eq!(recovered.next_index, 1);
+}
+
+#[test]
+fn test_scan_resumes_from_index() {
+    let keys = ShieldedKeys::from_seed(&SEED, 0).unwrap();
+    let mint = Pubkey::new_unique();
+    let mut source = MemorySource::default();
+    source.pay(&keys, 0, 1_000, mint);
+    let newer = source.pay(&keys, 0, 2_000, mint);
+
+    let recovered = scan_notes(&keys, 1, &mut source).unwrap();
+    assert_eq!(recovered.unspent, vec![newer]);
+    assert_eq!(recovered.next_index, 2);
+}
+
+#[test]
+fn test_empty_chain() {
+    let recovered = recover_notes(&SEED, 0, &mut MemorySource::default()).unwrap();
+    assert_eq!(recovered.next_index, 0);
+    assert!(recovered.balances.is_empty());
+}
+
+#[test]
+fn test_decrypt_batch_keeps_leaf_order() {
+    let keys = ShieldedKeys::from_seed(&SEED, 0).unwrap();
+    let stranger = ShieldedKeys::from_seed(&[9u8; 32], 0).unwrap();
+    let mint = Pubkey::new_unique();
+    let mut source = MemorySource::default();
+    let mut ours = Vec::new();
+    for i in 0..32u64 {
+        if i % 3 == 0 {
+            ours.push(source.pay(&keys, (i % 4) as u32, 100 + i, mint));
+        } else {
+            source.pay(&stranger, 0, 100 + i, mint);
+        }
+    }
+
+    let batch: Vec<&CommitmentEvent> = source.events.iter().collect();
+    assert_eq!(decrypt_batch(&keys, &batch).unwrap(), ours);
+    assert!(decrypt_batch(&keys, &[]).unwrap().is_empty());
+}
@@ -0,0 +1,87 @@
+use zkcash_client::errors::ClientError;
+use zkcash_client::sync_checkpoint::{ResumableSync, SyncCheckpoint};
+use zkcash_client::tree_frontier::TreeFrontier;
+use zkcash_client::tree_sync::{CommitmentEvent, TreeSync};
+
+const HEIGHT: usize = 8;
+
+fn event(index: u64) -> CommitmentEvent {
+    CommitmentEvent {
+        index,
+        commitment: [index as u8 + 1; 32],
+        encrypted_output: vec![],
+    }
+}
+
+fn synced(count: u64) -> TreeSync {
+    let mut sync = TreeSync::new(HEIGHT);
+    for index in 0..count {
+        sync.apply_event(&event(index)).unwrap();
+    }
+    sync
+}
+
+#[test]
+fn tree_sync_frontier_matches_replayed_frontier() {
+    for count in [0, 1, 2, 3, 5, 8, 13] {
+        let mut frontier = TreeFrontier::new(HEIGHT);
+        for index in 0..count {
+            frontier.append(event(index).commitment).unwrap();
+        }
+        assert_eq!(synced(count).frontier(), frontier, "{count} leaves");
+    }
+}
+
+#[test]
+fn resumes_from_saved_checkpoint() {
+    let mut sync = synced(5);
+    sync.last_signature = Some("sig5".to_string());
+
+    let mut bytes = Vec::new();
+    sync.checkpoint().save(&mut bytes).unwrap();
+    let checkpoint = SyncCheckpoint::load(&mut bytes.as_slice()).unwrap();
+    let mut resumed = ResumableSync::from_checkpoint(checkpoint).unwrap();
+    assert_eq!(resumed.last_signature.as_deref(), Some("sig5"));
+
+    // The first page after a restart overlaps the checkpoint
+    for index in 3..9 {
+        resumed.apply_event(&event(index)).unwrap();
+    }
+    assert_eq!(resumed.next_index(), 9);
+    assert_eq!(resumed.root(), synced(9).tree().root());
+}
+
+#[test]
+fn buffers_events_across_a_gap() {
+    let mut resumed = ResumableSync::new(HEIGHT);
+    resumed.apply_event(&event(1)).unwrap();
+    assert_eq!((resumed.next_index(), resumed.pending_len()), (0, 1));
+
+    let checkpoint = resumed.checkpoint();
+    let mut reloaded = ResumableSync::from_checkpoint(checkpoint).unwrap();
+    reloaded.apply_event(&event(0)).unwrap();
+    assert_eq!((reloaded.next_index(), reloaded.pending_len()), (2, 0));
+    assert_eq!(reloaded.root(), synced(2).tree().root());
+}
+
+#[test]
+fn rejects_conflicting_pending_leaf() {
+    let mut resumed = ResumableSync::new(HEIGHT);
+    resumed.apply_event(&event(3)).unwrap();
+    let conflicting = CommitmentEvent { commitment: [0xff; 32], ..event(3) };
+    assert_eq!(resumed.apply_event(&conflicting).unwrap_err(), ClientError::ConflictingLeaf);
+}
+
+#[test]
+fn rejects_inconsistent_checkpoint() {
+    let mut checkpoint = ResumableSync::new(HEIGHT).checkpoint();
+    checkpoint.next_index = 4;
+    checkpoint.pending = vec![(2, [1u8; 32])];
+    assert_eq!(ResumableSync::from_checkpoint(checkpoint).unwrap_err(), ClientError::InvalidSnapshot);
+
+    let mut bytes = Vec::new();
+    let mut checkpoint = ResumableSync::new(HEIGHT).checkpoint();
+    checkpoint.version = 9;
+    checkpoint.save(&mut bytes).unwrap();
+    assert_eq!(SyncCheckpoint::load(&mut bytes.as_slice()).unwrap_err(), ClientError::InvalidSnapshot);
+}
@@ -0,0 +1,218 @@
+use anchor_lang::prelude::Pubkey;
+#[cfg(feature = "program")]
+use anchor_lang::AnchorSerialize;
+use ark_bn254::Fr;
+use rand_core::OsRng;
+use std::str::FromStr;
+#[cfg(feature = "program")]
+use zkcash::utils::{check_public_amount, check_public_amount_u128};
+use zkcash_client::errors::ClientError;
+use zkcash_client::keys::ShieldedKeys;
+#[cfg(feature = "program")]
+use zkcash_client::transaction::calculate_public_amount_u128;
+use zkcash_client::transaction::{
+    calculate_public_amount, plan_transaction, required_input_amount, select_inputs, TransactResult,
+};
+use zkcash_client::utxo::{Utxo, SOL_MINT_ADDRESS};
+
+fn sol_mint() -> Pubkey {
+    Pubkey::from_str(SOL_MINT_ADDRESS).unwrap()
+}
+
+fn test_keys() -> ShieldedKeys {
+    ShieldedKeys::from_seed(&[9u8; 32], 0).unwrap()
+}
+
+fn notes_with_amounts(keys: &ShieldedKeys, amounts: &[u64]) -> Vec<Utxo> {
+    amounts
+        .iter()
+        .enumerate()
+        .map(|(i, amount)| {
+            let mut utxo = Utxo::new(*amount, keys.public_key(), sol_mint(), &mut OsRng);
+            utxo.index = i as u64;
+            utxo
+        })
+        .collect()
+}
+
+#[test]
+#[cfg(feature = "program")]
+fn test_public_amount_matches_program_for_deposit() {
+    let public_amount = calculate_public_amount(1_000, 10).unwrap();
+    assert!(check_public_amount(1_000, 10, public_amount));
+}
+
+#[test]
+#[cfg(feature = "program")]
+fn test_public_amount_matches_program_for_withdrawal() {
+    let public_amount = calculate_public_amount(-1_000, 10).unwrap();
+    assert!(check_public_amount(-1_000, 10, public_amount));
+}
+
+#[test]
+#[cfg(feature = "program")]
+fn test_public_amount_matches_program_at_extremes() {
+    let public_amount = calculate_public_amount(-i64::MAX, u64::MAX).unwrap();
+    assert!(check_public_amount(-i64::MAX, u64::MAX, public_amount));
+
+    let public_amount = calculate_public_amount(i64::MAX, 0).unwrap();
+    assert!(check_public_amount(i64::MAX, 0, public_amount));
+}
+
+#[test]
+#[cfg(feature = "program")]
+fn test_wide_public_amount_matches_program_beyond_i64() {
+    let amount = u64::MAX as i128 * 1_000;
+    let public_amount = calculate_public_amount_u128(amount, 5).unwrap();
+    assert!(check_public_amount_u128(amount, 5, public_amount));
+
+    let public_amount = calculate_public_amount_u128(-i128::MAX, u128::MAX).unwrap();
+    assert!(check_public_amount_u128(-i128::MAX, u128::MAX, public_amount));
+
+    // Agrees with the i64 path where both apply
+    assert_eq!(calculate_public_amount_u128(-1_000, 10), calculate_public_amount(-1_000, 10));
+    assert_eq!(calculate_public_amount_u128(i128::MIN, 0), Err(ClientError::InvalidExtAmount));
+}
+
+#[test]
+fn test_public_amount_rejects_what_program_rejects() {
+    assert_eq!(calculate_public_amount(i64::MIN, 0), Err(ClientError::InvalidExtAmount));
+    // deposit barely covering the fee
+    assert_eq!(calculate_public_amount(10, 10), Err(ClientError::InvalidExtAmount));
+    assert_eq!(calculate_public_amount(10, 11), Err(ClientError::InvalidExtAmount));
+}
+
+#[test]
+fn test_required_input_amount() {
+    assert_eq!(required_input_amount(1_000, 10).unwrap(), 0);
+    assert_eq!(required_input_amount(-1_000, 10).unwrap(), 1_010);
+    assert_eq!(required_input_amount(0, 10).unwrap(), 10);
+    assert_eq!(required_input_amount(-i64::MAX, u64::MAX), Err(ClientError::ArithmeticOverflow));
+}
+
+#[test]
+fn test_select_prefers_smallest_sufficient_note() {
+    let keys = test_keys();
+    let notes = notes_with_amounts(&keys, &[500, 2_000, 1_200, 0]);
+
+    let selected = select_inputs(&notes, 1_100).unwrap();
+    assert_eq!(selected.len(), 1);
+    assert_eq!(selected[0].amount, 1_200);
+}
+
+#[test]
+fn test_select_combines_two_largest_notes() {
+    let keys = test_keys();
+    let notes = notes_with_amounts(&keys, &[100, 600, 700]);
+
+    let selected = select_inputs(&notes, 1_200).unwrap();
+    let amounts: Vec<u64> = selected.iter().map(|note| note.amount).collect();
+    assert_eq!(amounts, vec![600, 700]);
+}
+
+#[test]
+fn test_select_insufficient_funds() {
+    let keys = test_keys();
+    // Total is enough but the circuit only takes two inputs
+    let notes = notes_with_amounts(&keys, &[400, 400, 400]);
+
+    assert_eq!(select_inputs(&notes, 1_000), Err(ClientError::InsufficientFunds));
+    assert_eq!(select_inputs(&[], 1), Err(ClientError::InsufficientFunds));
+}
+
+#[test]
+fn test_select_nothing_required() {
+    assert!(select_inputs(&[], 0).unwrap().is_empty());
+}
+
+#[test]
+fn test_plan_deposit() {
+    let keys = test_keys();
+    let plan = plan_transaction(&keys, &[], 1_000, 0, sol_mint(), &mut OsRng).unwrap();
+
+    assert_eq!(plan.inputs[0].amount, 0);
+    assert_eq!(plan.inputs[1].amount, 0);
+    assert_eq!(plan.outputs[0].amount, 1_000);
+    assert_eq!(plan.outputs[1].amount, 0);
+    #[cfg(feature = "program")]
+    assert!(check_public_amount(1_000, 0, plan.public_amount));
+}
+
+#[test]
+fn test_plan_withdrawal_with_change() {
+    let keys = test_keys();
+    let notes = notes_with_amounts(&keys, &[800, 5_000]);
+
+    let plan = plan_transaction(&keys, &notes, -600, 6, sol_mint(), &mut OsRng).unwrap();
+
+    assert_eq!(plan.inputs[0].amount, 800);
+    assert_eq!(plan.inputs[1].amount, 0);
+    assert_eq!(plan.outputs[0].amount, 194);
+    #[cfg(feature = "program")]
+    assert!(check_public_amount(-600, 6, plan.public_amount));
+
+    // sum(inputs) + publicAmount == sum(outputs) in the field
+    let public_amount = -(Fr::from(600u64) + Fr::from(6u64));
+    let ins = Fr::from(plan.inputs[0].amount) + Fr::from(plan.inputs[1].amount);
+    let outs = Fr::from(plan.outputs[0].amount) + Fr::from(plan.outputs[1].amount);
+    assert_eq!(ins + public_amount, outs);
+}
+
+#[test]
+fn test_plan_rejects_foreign_mint_notes() {
+    let keys = test_keys();
+    let notes = vec![Utxo::new(100, keys.public_key(), Pubkey::new_unique(), &mut OsRng)];
+
+    let result = plan_transaction(&keys, &notes, -50, 0, sol_mint(), &mut OsRng);
+    assert_eq!(result.err(), Some(ClientError::MintMismatch));
+}
+
+#[test]
+fn test_public_inputs_order() {
+    let keys = test_keys();
+    let notes = notes_with_amounts(&keys, &[1_000]);
+    let plan = plan_transaction(&keys, &notes, -500, 5, sol_mint(), &mut OsRng).unwrap();
+
+    let root = [1u8; 32];
+    let ext_data_hash = [2u8; 32];
+    let inputs = plan.public_inputs(&keys, root, ext_data_hash).unwrap();
+    let nullifiers = plan.input_nullifiers(&keys).unwrap();
+    let commitments = plan.output_commitments().unwrap();
+
+    assert_eq!(inputs[0], root);
+    assert_eq!(inputs[1], plan.public_amount);
+    assert_eq!(inputs[2], ext_data_hash);
+    assert_eq!(&inputs[3..5], &nullifiers);
+    assert_eq!(&inputs[5..7], &commitments);
+    assert_ne!(nullifiers[0], nullifiers[1]);
+    assert_ne!(commitments[0], commitments[1]);
+}
+
+#[test]
+#[cfg(feature = "program")]
+fn test_transact_result_decodes_program_return_data() {
+    let program_result = zkcash::TransactResult::new(10, [3u8; 32]).unwrap();
+    let mut data = Vec::new();
+    program_result.serialize(&mut data).unwrap();
+
+    let result = TransactResult::from_return_data(&data).unwrap();
+    assert_eq!(result.leaf_indices, [10, 11]);
+    assert_eq!(result.root, [3u8; 32]);
+
+    assert_eq!(TransactResult::from_return_data(&data[..47]), Err(ClientError::InvalidReturnData));
+    assert_eq!(TransactResult::from_return_data(&[data.clone(), vec![0]].concat()), Err(ClientError::InvalidReturnData));
+}
+
+#[test]
+fn test_inserted_outputs_carry_assigned_indices() {
+    let keys = test_keys();
+    let plan = plan_transaction(&keys, &[], 1_000, 0, sol_mint(), &mut OsRng).unwrap();
+    let result = TransactResult {
+        leaf_indices: [6, 7],
+        root: [0u8; 32],
+    };
+
+    let outputs = plan.inserted_outputs(&result);
+    assert_eq!((outputs[0].index, outputs[1].index), (6, 7));
+    assert_eq!(outputs[0].commitment().unwrap(), plan.outputs[0].commitment().unwrap());
+}
@@ -0,0 +1,95 @@
+use anchor_lang::Discriminator;
+use light_hasher::Poseidon;
+use std::mem::MaybeUninit;
+use zkcash::{merkle_tree::{layout, MerkleTree}, MerkleTreeAccount};
+use zkcash_client::errors::ClientError;
+use zkcash_client::tree_frontier::TreeFrontier;
+use zkcash_client::tree_sync::LocalMerkleTree;
+
+fn tree_with_leaves(count: u8) -> MerkleTreeAccount {
+    let mut uninit: MaybeUninit<MerkleTreeAccount> = MaybeUninit::uninit();
+    let mut account = unsafe {
+        let ptr = uninit.as_mut_ptr();
+        std::ptr::write_bytes(ptr, 0, 1);
+        (*ptr).height = 26;
+        (*ptr).root_history_size = 100;
+        uninit.assume_init()
+    };
+    MerkleTree::initialize::<Poseidon>(&mut account).unwrap();
+    for i in 1..=count {
+        MerkleTree::append::<Poseidon>([i; 32], &mut account).unwrap();
+    }
+    account
+}
+
+/// The fields a frontier reads, written where the program's layout puts them.
+fn account_data(tree: &MerkleTreeAccount) -> Vec<u8> {
+    let mut data = vec![0u8; layout::HEIGHT + 1];
+    data[..8].copy_from_slice(MerkleTreeAccount::DISCRIMINATOR);
+    data[layout::NEXT_INDEX..layout::NEXT_INDEX + 8].copy_from_slice(&tree.next_index.to_le_bytes());
+    for (level, subtree) in tree.subtrees.iter().enumerate() {
+        let offset = layout::SUBTREES + 32 * level;
+        data[offset..offset + 32].copy_from_slice(subtree);
+    }
+    data[layout::ROOT..layout::ROOT + 32].copy_from_slice(&tree.root);
+    data[layout::HEIGHT] = tree.height;
+    data
+}
+
+#[test]
+fn test_client_layout_matches_program() {
+    use zkcash_client::tree_frontier::layout as client;
+    assert_eq!(client::NEXT_INDEX, layout::NEXT_INDEX);
+    assert_eq!(client::SUBTREES, layout::SUBTREES);
+    assert_eq!(client::ROOT, layout::ROOT);
+    assert_eq!(client::ROOT_INDEX, layout::ROOT_INDEX);
+    assert_eq!(client::HEIGHT, layout::HEIGHT);
+}
+
+#[test]
+fn test_new_matches_initialized_tree() {
+    let tree = tree_with_leaves(0);
+    let frontier = TreeFrontier::new(26);
+    assert_eq!(frontier.root, tree.root);
+    assert_eq!(frontier.subtrees, MerkleTree::filled_subtrees(&tree));
+}
+
+#[test]
+fn test_resumes_appends_from_account() {
+    let mut tree = tree_with_leaves(5);
+    let mut frontier = TreeFrontier::from_account_data(&account_data(&tree)).unwrap();
+    assert_eq!((frontier.next_index, frontier.root), (5, tree.root));
+
+    for i in 6..=9u8 {
+        MerkleTree::append::<Poseidon>([i; 32], &mut tree).unwrap();
+        assert_eq!(frontier.append([i; 32]).unwrap(), i as u64 - 1);
+        assert_eq!(frontier.root, tree.root);
+    }
+}
+
+#[test]
+fn test_agrees_with_local_tree() {
+    let mut local = LocalMerkleTree::new(26);
+    let mut frontier = TreeFrontier::new(26);
+    for i in 1..=7u8 {
+        local.insert([i; 32]).unwrap();
+        frontier.append([i; 32]).unwrap();
+    }
+    assert_eq!(frontier.root, local.root());
+}
+
+#[test]
+fn test_rejects_other_accounts() {
+    let mut data = account_data(&tree_with_leaves(1));
+    data[0] ^= 1;
+    assert_eq!(TreeFrontier::from_account_data(&data), Err(ClientError::InvalidAccountData));
+    assert_eq!(TreeFrontier::from_account_data(&data[..40]), Err(ClientError::InvalidAccountData));
+}
+
+#[test]
+fn test_full_tree_rejects_appends() {
+    let mut frontier = TreeFrontier::new(1);
+    frontier.append([1; 32]).unwrap();
+    frontier.append([2; 32]).unwrap();
+    assert_eq!(frontier.append([3; 32]), Err(ClientError::MerkleTreeFull));
+}
@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use light_hasher::Poseidon;
+use std::mem::MaybeUninit;
+use zkcash::{merkle_tree::MerkleTree, MerkleTreeAccount};
+use zkcash_client::errors::ClientError;
+use zkcash_client::tree_snapshot::SnapshotAssembler;
+
+fn tree_with_leaves(count: u8) -> MerkleTreeAccount {
+    let mut uninit: MaybeUninit<MerkleTreeAccount> = MaybeUninit::uninit();
+    let mut account = unsafe {
+        let ptr = uninit.as_mut_ptr();
+        std::ptr::write_bytes(ptr, 0, 1);
+        (*ptr).height = 26;
+        (*ptr).root_history_size = 100;
+        uninit.assume_init()
+    };
+    MerkleTree::initialize::<Poseidon>(&mut account).unwrap();
+    for i in 1..=count {
+        MerkleTree::append::<Poseidon>([i; 32], &mut account).unwrap();
+    }
+    account
+}
+
+fn pages(tree: &MerkleTreeAccount) -> Vec<Vec<u8>> {
+    let snapshot = zkcash::TreeSnapshot::from_tree(Pubkey::default(), tree);
+    let total = snapshot.page(0).unwrap().total_pages;
+    (0..total).map(|page| snapshot.page(page).unwrap().try_to_vec().unwrap()).collect()
+}
+
+#[test]
+fn test_assembles_program_pages() {
+    let tree = tree_with_leaves(3);
+    let mut assembler = SnapshotAssembler::default();
+    let mut snapshot = None;
+    for page in pages(&tree) {
+        assert!(snapshot.is_none());
+        snapshot = assembler.push(&page).unwrap();
+    }
+    let snapshot = snapshot.expect("last page completes the snapshot");
+    assert_eq!(snapshot.next_index, 3);
+    assert_eq!(snapshot.root, tree.root);
+    assert!(snapshot.is_known_root(&tree.root));
+    assert!(!snapshot.is_known_root(&[0u8; 32]));
+}
+
+#[test]
+fn test_detects_tree_moving_between_pages() {
+    let before = pages(&tree_with_leaves(3));
+    let after = pages(&tree_with_leaves(4));
+    let mut assembler = SnapshotAssembler::default();
+    assembler.push(&before[0]).unwrap();
+    assert_eq!(assembler.push(&after[1]), Err(ClientError::SnapshotChanged));
+}
+
+#[test]
+fn test_rejects_pages_out_of_order() {
+    let pages = pages(&tree_with_leaves(1));
+    let mut assembler = SnapshotAssembler::default();
+    assert_eq!(assembler.push(&pages[1]), Err(ClientError::InvalidSnapshot));
+    assert_eq!(assembler.next_page(), 0);
+}
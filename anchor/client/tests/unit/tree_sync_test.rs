@@ -0,0 +1,163 @@
+use light_hasher::{Hasher, Poseidon};
+#[cfg(feature = "program")]
+use std::mem::MaybeUninit;
+#[cfg(feature = "program")]
+use zkcash::{MerkleTreeAccount, merkle_tree::MerkleTree};
+use zkcash_client::errors::ClientError;
+use zkcash_client::tree_sync::{CommitmentEvent, LocalMerkleTree, TreeSync, DEFAULT_TREE_HEIGHT};
+
+#[cfg(feature = "program")]
+fn create_onchain_account() -> MerkleTreeAccount {
+    let mut uninit: MaybeUninit<MerkleTreeAccount> = MaybeUninit::uninit();
+
+    unsafe {
+        let ptr = uninit.as_mut_ptr();
+        std::ptr::write_bytes(ptr, 0, 1);
+        (*ptr).height = DEFAULT_TREE_HEIGHT as u8;
+        (*ptr).root_history_size = 100;
+        uninit.assume_init()
+    }
+}
+
+fn leaf(i: u64) -> [u8; 32] {
+    let mut leaf = [0u8; 32];
+    leaf[24..].copy_from_slice(&(i + 1).to_be_bytes());
+    leaf
+}
+
+fn event(i: u64) -> CommitmentEvent {
+    CommitmentEvent {
+        index: i,
+        commitment: leaf(i),
+        encrypted_output: vec![i as u8; 4],
+    }
+}
+
+// Recomputes the root from a leaf and its path the way the circuit's MerkleProof does
+fn root_from_path(leaf: [u8; 32], index: u64, path: &[[u8; 32]]) -> [u8; 32] {
+    let mut current = leaf;
+    for (level, sibling) in path.iter().enumerate() {
+        current = if (index >> level) & 1 == 0 {
+            Poseidon::hashv(&[&current, sibling]).unwrap()
+        } else {
+            Poseidon::hashv(&[sibling, &current]).unwrap()
+        };
+    }
+    current
+}
+
+#[test]
+#[cfg(feature = "program")]
+fn test_empty_tree_root_matches_onchain() {
+    let mut account = create_onchain_account();
+    MerkleTree::initialize::<Poseidon>(&mut account).unwrap();
+
+    let tree = LocalMerkleTree::new(DEFAULT_TREE_HEIGHT);
+    assert_eq!(tree.root(), account.root);
+}
+
+#[test]
+#[cfg(feature = "program")]
+fn test_roots_and_paths_match_onchain_appends() {
+    let mut account = create_onchain_account();
+    MerkleTree::initialize::<Poseidon>(&mut account).unwrap();
+    let mut tree = LocalMerkleTree::new(DEFAULT_TREE_HEIGHT);
+
+    for i in 0..9 {
+        let onchain_path = MerkleTree::append::<Poseidon>(leaf(i), &mut account).unwrap();
+        let index = tree.insert(leaf(i)).unwrap();
+
+        assert_eq!(index, i);
+        assert_eq!(tree.root(), account.root, "root mismatch after {} leaves", i + 1);
+        assert_eq!(tree.path(i).unwrap().path_elements, onchain_path);
+        assert!(MerkleTree::is_known_root(&account, tree.root()));
+    }
+}
+
+#[test]
+fn test_paths_stay_valid_for_older_leaves() {
+    let mut tree = LocalMerkleTree::new(DEFAULT_TREE_HEIGHT);
+    for i in 0..13 {
+        tree.insert(leaf(i)).unwrap();
+    }
+
+    for i in 0..13 {
+        let path = tree.path(i).unwrap();
+        assert_eq!(path.path_elements.len(), DEFAULT_TREE_HEIGHT);
+        assert_eq!(root_from_path(leaf(i), i, &path.path_elements), tree.root());
+    }
+    assert_eq!(tree.path(13), Err(ClientError::LeafIndexOutOfRange));
+}
+
+#[test]
+fn test_small_tree_becomes_full() {
+    let mut tree = LocalMerkleTree::new(2);
+    for i in 0..4 {
+        tree.insert(leaf(i)).unwrap();
+    }
+    assert_eq!(tree.insert(leaf(4)), Err(ClientError::MerkleTreeFull));
+}
+
+#[test]
+fn test_sync_buffers_out_of_order_events() {
+    let mut sync = TreeSync::new(DEFAULT_TREE_HEIGHT);
+
+    sync.apply_event(&event(2)).unwrap();
+    sync.apply_event(&event(1)).unwrap();
+    assert_eq!(sync.tree().next_index(), 0);
+    assert_eq!(sync.pending_len(), 2);
+
+    sync.apply_event(&event(0)).unwrap();
+    assert_eq!(sync.tree().next_index(), 3);
+    assert_eq!(sync.pending_len(), 0);
+
+    let mut in_order = LocalMerkleTree::new(DEFAULT_TREE_HEIGHT);
+    for i in 0..3 {
+        in_order.insert(leaf(i)).unwrap();
+    }
+    assert_eq!(sync.tree().root(), in_order.root());
+}
+
+#[test]
+fn test_sync_ignores_replays_and_rejects_conflicts() {
+    let mut sync = TreeSync::new(DEFAULT_TREE_HEIGHT);
+    sync.apply_event(&event(0)).unwrap();
+    sync.apply_event(&event(0)).unwrap();
+    assert_eq!(sync.tree().next_index(), 1);
+
+    let mut conflicting = event(0);
+    conflicting.commitment = [0xff; 32];
+    assert_eq!(sync.apply_event(&conflicting), Err(ClientError::ConflictingLeaf));
+
+    sync.apply_event(&event(5)).unwrap();
+    let mut conflicting_pending = event(5);
+    conflicting_pending.commitment = [0xff; 32];
+    assert_eq!(sync.apply_event(&conflicting_pending), Err(ClientError::ConflictingLeaf));
+}
+
+#[test]
+fn test_sync_save_and_load_roundtrip() {
+    let mut sync = TreeSync::new(DEFAULT_TREE_HEIGHT);
+    for i in [0, 1, 2, 4] {
+        sync.apply_event(&event(i)).unwrap();
+    }
+    sync.last_signature = Some("5VERYLONGSIGNATURE".to_string());
+
+    let mut bytes = Vec::new();
+    sync.save(&mut bytes).unwrap();
+    let mut restored = TreeSync::load(&mut bytes.as_slice()).unwrap();
+
+    assert_eq!(restored.tree().root(), sync.tree().root());
+    assert_eq!(restored.tree().next_index(), 3);
+    assert_eq!(restored.pending_len(), 1);
+    assert_eq!(restored.last_signature, sync.last_signature);
+
+    // The buffered leaf is applied once the gap is filled after resuming
+    restored.apply_event(&event(3)).unwrap();
+    assert_eq!(restored.tree().next_index(), 5);
+}
+
+#[test]
+fn test_load_rejects_garbage() {
+    assert_eq!(TreeSync::load(&mut [0xffu8; 3].as_slice()).err(), Some(ClientError::InvalidSnapshot));
+}
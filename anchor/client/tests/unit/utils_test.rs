@@ -0,0 +1,31 @@
+use zkcash_client::errors::ClientError;
+use zkcash_client::utils::{format_token_amount, parse_token_amount};
+
+#[test]
+fn test_parse_token_amount_is_exact() {
+    assert_eq!(parse_token_amount("1.5", 9), Ok(1_500_000_000));
+    assert_eq!(parse_token_amount("42", 6), Ok(42_000_000));
+    assert_eq!(parse_token_amount("0.000000000000000001", 18), Ok(1));
+    // Beyond u64 at 18 decimals
+    assert_eq!(parse_token_amount("100000", 18), Ok(100_000 * 10u128.pow(18)));
+}
+
+#[test]
+fn test_parse_token_amount_rejects_bad_input() {
+    assert_eq!(parse_token_amount("1.0000001", 6), Err(ClientError::InvalidTokenAmount));
+    assert_eq!(parse_token_amount(".5", 9), Err(ClientError::InvalidTokenAmount));
+    assert_eq!(parse_token_amount("1e9", 9), Err(ClientError::InvalidTokenAmount));
+    assert_eq!(parse_token_amount("-1", 9), Err(ClientError::InvalidTokenAmount));
+    assert_eq!(parse_token_amount("1", 39), Err(ClientError::ArithmeticOverflow));
+    assert_eq!(parse_token_amount(&u128::MAX.to_string(), 1), Err(ClientError::ArithmeticOverflow));
+}
+
+#[test]
+fn test_format_token_amount_round_trips() {
+    assert_eq!(format_token_amount(1_500_000_000, 9), "1.5");
+    assert_eq!(format_token_amount(1, 18), "0.000000000000000001");
+    assert_eq!(format_token_amount(42, 0), "42");
+    for (amount, decimals) in [(0u128, 9u8), (123_456_789, 6), (u128::MAX, 18)] {
+        assert_eq!(parse_token_amount(&format_token_amount(amount, decimals), decimals), Ok(amount));
+    }
+}
@@ -0,0 +1,264 @@
+//! Witness generation and Groth16 proving for the `transaction2` circuit, using
+//! the committed artifacts in `artifacts/circuits`. Witnesses are computed by
+//! the circom-generated `generate_witness.js`, so `node` must be on the PATH.
+
+use crate::fixture::ProofFixture;
+use crate::Result;
+use ark_bn254::{Bn254, Fq, Fr, G1Affine, G2Affine};
+use ark_circom::circom::{R1CSFile, R1CS};
+use ark_circom::{read_zkey, CircomCircuit, CircomReduction};
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::{prepare_verifying_key, Groth16, ProvingKey};
+use ark_std::rand::{CryptoRng, RngCore};
+use num_bigint::{BigInt, BigUint};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use zkcash_client::utils::fr_to_be_bytes;
+
+const CIRCUIT_NAME: &str = "transaction2";
+const PUBLIC_INPUTS: usize = 7;
+
+type CircomGroth16 = Groth16<Bn254, CircomReduction>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Signal {
+    Scalar(BigInt),
+    Array(Vec<BigInt>),
+}
+
+/// Circuit input signals by name, i.e. the contents of a snarkjs `input.json`.
+/// Multi-dimensional arrays are kept flattened in row-major order, which is
+/// also how the witness calculator consumes them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CircuitInputs(BTreeMap<String, Signal>);
+
+fn fr_to_bigint(value: &Fr) -> BigInt {
+    BigUint::from_bytes_be(&value.into_bigint().to_bytes_be()).into()
+}
+
+fn flatten_json(value: &Value, name: &str, out: &mut Vec<BigInt>) -> Result<()> {
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                flatten_json(item, name, out)?;
+            }
+        }
+        Value::String(s) => out.push(s.parse().map_err(|_| format!("{}: {:?} is not a decimal integer", name, s))?),
+        Value::Number(n) => out.push(n.to_string().parse().map_err(|_| format!("{}: {} is not an integer", name, n))?),
+        other => return Err(format!("{}: unsupported value {}", name, other).into()),
+    }
+    Ok(())
+}
+
+impl CircuitInputs {
+    pub fn set(&mut self, name: &str, value: Fr) {
+        self.0.insert(name.to_string(), Signal::Scalar(fr_to_bigint(&value)));
+    }
+
+    pub fn set_array<'a>(&mut self, name: &str, values: impl IntoIterator<Item = &'a Fr>) {
+        let values = values.into_iter().map(fr_to_bigint).collect();
+        self.0.insert(name.to_string(), Signal::Array(values));
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let object = match serde_json::from_str::<Value>(json)? {
+            Value::Object(object) => object,
+            _ => return Err("circuit inputs must be a JSON object".into()),
+        };
+
+        let mut inputs = CircuitInputs::default();
+        for (name, value) in &object {
+            let mut values = Vec::new();
+            flatten_json(value, name, &mut values)?;
+            let signal = match value {
+                Value::Array(_) => Signal::Array(values),
+                _ => Signal::Scalar(values.remove(0)),
+            };
+            inputs.0.insert(name.clone(), signal);
+        }
+        Ok(inputs)
+    }
+
+    /// Serializes with decimal strings, the format snarkjs reads.
+    pub fn to_json(&self) -> Value {
+        let object = self
+            .0
+            .iter()
+            .map(|(name, signal)| {
+                let value = match signal {
+                    Signal::Scalar(v) => Value::String(v.to_string()),
+                    Signal::Array(vs) => Value::Array(vs.iter().map(|v| Value::String(v.to_string())).collect()),
+                };
+                (name.clone(), value)
+            })
+            .collect();
+        Value::Object(object)
+    }
+
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32> {
+    let word = bytes.get(offset..offset + 4).ok_or("truncated witness file")?;
+    Ok(u32::from_le_bytes(word.try_into()?))
+}
+
+/// Decodes a snarkjs `.wtns` file: a `wtns` header followed by a field section
+/// (`n8`, prime, witness count) and the witness values, `n8` bytes each, LE.
+fn parse_wtns(bytes: &[u8]) -> Result<Vec<Fr>> {
+    if bytes.get(..4) != Some(b"wtns".as_slice()) {
+        return Err("not a wtns file".into());
+    }
+    let sections = read_u32(bytes, 8)?;
+    let mut offset = 12;
+    let (mut n8, mut count, mut values) = (0usize, 0usize, None);
+    for _ in 0..sections {
+        let kind = read_u32(bytes, offset)?;
+        let size = u64::from_le_bytes(bytes.get(offset + 4..offset + 12).ok_or("truncated witness file")?.try_into()?);
+        let start = offset + 12;
+        match kind {
+            1 => {
+                n8 = read_u32(bytes, start)? as usize;
+                count = read_u32(bytes, start + 4 + n8)? as usize;
+            }
+            2 => values = Some(start),
+            _ => {}
+        }
+        offset = start + size as usize;
+    }
+
+    let start = values.ok_or("witness file has no values section")?;
+    let data = bytes.get(start..start + n8 * count).ok_or("truncated witness file")?;
+    Ok(data.chunks(n8).map(Fr::from_le_bytes_mod_order).collect())
+}
+
+fn fq_to_be_bytes(value: &Fq) -> Vec<u8> {
+    let mut bytes = vec![0u8; 32];
+    let be = value.into_bigint().to_bytes_be();
+    bytes[32 - be.len()..].copy_from_slice(&be);
+    bytes
+}
+
+/// `x || y`, big-endian, which `verify_proof` expects for proof_a and proof_c.
+fn g1_to_bytes(point: &G1Affine) -> Vec<u8> {
+    [fq_to_be_bytes(&point.x), fq_to_be_bytes(&point.y)].concat()
+}
+
+/// `x.c1 || x.c0 || y.c1 || y.c0`, big-endian, matching the TypeScript prover.
+fn g2_to_bytes(point: &G2Affine) -> Vec<u8> {
+    [
+        fq_to_be_bytes(&point.x.c1),
+        fq_to_be_bytes(&point.x.c0),
+        fq_to_be_bytes(&point.y.c1),
+        fq_to_be_bytes(&point.y.c0),
+    ]
+    .concat()
+}
+
+pub struct Prover {
+    artifacts_dir: PathBuf,
+    r1cs: R1CS<Fr>,
+    proving_key: ProvingKey<Bn254>,
+}
+
+fn open(path: &Path) -> Result<BufReader<File>> {
+    Ok(BufReader::new(
+        File::open(path).map_err(|e| format!("failed to open {}: {}", path.display(), e))?,
+    ))
+}
+
+impl Prover {
+    /// Loads `transaction2.{wasm,r1cs,zkey}` from `artifacts_dir`.
+    pub fn load(artifacts_dir: &Path) -> Result<Self> {
+        let (proving_key, _) = read_zkey(&mut open(&artifacts_dir.join(format!("{}.zkey", CIRCUIT_NAME)))?)?;
+        let mut r1cs: R1CS<Fr> = R1CSFile::new(open(&artifacts_dir.join(format!("{}.r1cs", CIRCUIT_NAME)))?)?.into();
+        // `.wtns` values are already in wire order, so the label mapping must not be applied again
+        r1cs.wire_mapping = None;
+
+        Ok(Prover {
+            artifacts_dir: artifacts_dir.to_path_buf(),
+            r1cs,
+            proving_key,
+        })
+    }
+
+    /// Runs `generate_witness.js` over `inputs` in a scratch directory.
+    fn witness(&self, inputs: &CircuitInputs) -> Result<Vec<Fr>> {
+        let scratch = std::env::temp_dir().join(format!("zkcash-fixture-gen-{}", std::process::id()));
+        std::fs::create_dir_all(&scratch)?;
+        let input_path = scratch.join("input.json");
+        let wtns_path = scratch.join("witness.wtns");
+        std::fs::write(&input_path, inputs.to_json().to_string())?;
+
+        let js_dir = self.artifacts_dir.join(format!("{}_js", CIRCUIT_NAME));
+        let output = Command::new("node")
+            .arg(js_dir.join("generate_witness.js"))
+            .arg(self.artifacts_dir.join(format!("{}.wasm", CIRCUIT_NAME)))
+            .arg(&input_path)
+            .arg(&wtns_path)
+            .output()
+            .map_err(|e| format!("failed to run node: {}", e))?;
+        let witness = std::fs::read(&wtns_path);
+        std::fs::remove_dir_all(&scratch)?;
+
+        if !output.status.success() {
+            return Err(format!("witness generation failed: {}", String::from_utf8_lossy(&output.stderr)).into());
+        }
+        parse_wtns(&witness.map_err(|_| {
+            format!("witness generation wrote no witness: {}", String::from_utf8_lossy(&output.stderr))
+        })?)
+    }
+
+    /// Computes the witness for `inputs`, proves it and checks the proof against
+    /// the zkey's verifying key before returning it.
+    pub fn prove<R: RngCore + CryptoRng>(&self, inputs: &CircuitInputs, rng: &mut R) -> Result<ProofFixture> {
+        let witness = self.witness(inputs)?;
+        if witness.len() != self.r1cs.num_variables {
+            return Err(format!("witness has {} values, the r1cs expects {}", witness.len(), self.r1cs.num_variables).into());
+        }
+        let circuit = CircomCircuit { r1cs: self.r1cs.clone(), witness: Some(witness) };
+        let public_inputs = circuit
+            .get_public_inputs()
+            .ok_or("witness generation produced no public inputs")?;
+        if public_inputs.len() != PUBLIC_INPUTS {
+            return Err(format!("expected {} public inputs, got {}", PUBLIC_INPUTS, public_inputs.len()).into());
+        }
+
+        let proof = CircomGroth16::create_random_proof_with_reduction(circuit, &self.proving_key, rng)?;
+        if !CircomGroth16::verify_proof(&prepare_verifying_key(&self.proving_key.vk), &proof, &public_inputs)? {
+            return Err("generated proof does not verify against the zkey".into());
+        }
+
+        let public: Vec<Vec<u8>> = public_inputs.iter().map(|input| fr_to_be_bytes(input).to_vec()).collect();
+        Ok(ProofFixture {
+            proof_a: g1_to_bytes(&proof.a),
+            proof_b: g2_to_bytes(&proof.b),
+            proof_c: g1_to_bytes(&proof.c),
+            root: public[0].clone(),
+            public_amount: public[1].clone(),
+            ext_data_hash: public[2].clone(),
+            input_nullifiers: vec![public[3].clone(), public[4].clone()],
+            output_commitments: vec![public[5].clone(), public[6].clone()],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inputs_json_round_trip_flattens_nested_arrays() {
+        let json = r#"{"root": "5", "inPathElements": [["1", "2"], ["3", 4]]}"#;
+        let inputs = CircuitInputs::from_json(json).unwrap();
+
+        let mut expected = CircuitInputs::default();
+        expected.set("root", Fr::from(5u64));
+        expected.set_array("inPathElements", &[Fr::from(1u64), Fr::from(2u64), Fr::from(3u64), Fr::from(4u64)]);
+        assert_eq!(inputs, expected);
+        assert_eq!(CircuitInputs::from_json(&inputs.to_json().to_string()).unwrap(), inputs);
+    }
+}
@@ -0,0 +1,30 @@
+//! JSON fixture formats. Byte fields are plain arrays of numbers; the readers
+//! in program-tests and the program's unit tests decode the same shapes.
+
+use serde::{Deserialize, Serialize};
+
+/// A proof and its public inputs, in the byte encodings `transact` takes.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofFixture {
+    pub proof_a: Vec<u8>,
+    pub proof_b: Vec<u8>,
+    pub proof_c: Vec<u8>,
+    pub root: Vec<u8>,
+    pub public_amount: Vec<u8>,
+    pub ext_data_hash: Vec<u8>,
+    pub input_nullifiers: Vec<Vec<u8>>,
+    pub output_commitments: Vec<Vec<u8>>,
+}
+
+/// Everything needed to submit one `transact`: the proof plus the ExtData
+/// fields it was generated for. Pubkeys are base58.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactFixture {
+    pub proof: ProofFixture,
+    pub ext_amount: i64,
+    pub fee: u64,
+    pub recipient: String,
+    pub fee_recipient: String,
+    pub encrypted_output1: Vec<u8>,
+    pub encrypted_output2: Vec<u8>,
+}
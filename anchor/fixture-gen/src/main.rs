@@ -0,0 +1,97 @@
+//! Regenerates the Groth16 proof fixtures used by the test suites.
+//!
+//! ```text
+//! zkcash-fixture-gen scenario [OUT_DIR]
+//!     Proves the deposit -> withdraw flow and writes deposit.json and
+//!     withdraw.json (plus their circuit inputs as *.input.json) to OUT_DIR,
+//!     by default program-tests/tests/fixtures.
+//!
+//! zkcash-fixture-gen prove INPUT_JSON OUTPUT_JSON
+//!     Proves a snarkjs-style circuit input file and writes the proof and its
+//!     public inputs.
+//! ```
+//!
+//! Circuit artifacts are read from `artifacts/circuits` at the repository root,
+//! or from `ZKCASH_ARTIFACTS_DIR` if set. Re-run `scenario` after changing the
+//! circuit, zkey or verifying key.
+
+mod circuit;
+mod fixture;
+mod scenario;
+
+use ark_std::rand::rngs::StdRng;
+use ark_std::rand::SeedableRng;
+use circuit::{CircuitInputs, Prover};
+use fixture::TransactFixture;
+use std::path::{Path, PathBuf};
+
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+const USAGE: &str = "usage: zkcash-fixture-gen scenario [OUT_DIR]\n       zkcash-fixture-gen prove INPUT_JSON OUTPUT_JSON";
+// Fixed so that re-running without changes reproduces the committed fixtures
+const PROOF_SEED: u64 = 0x7a6b_6361_7368;
+
+fn manifest_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+fn artifacts_dir() -> PathBuf {
+    std::env::var_os("ZKCASH_ARTIFACTS_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| manifest_dir().join("../../artifacts/circuits"))
+}
+
+fn write_json<T: serde::Serialize>(path: &Path, value: &T) -> Result<()> {
+    std::fs::write(path, serde_json::to_string_pretty(value)? + "\n")
+        .map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+    println!("wrote {}", path.display());
+    Ok(())
+}
+
+fn run_scenario(out_dir: &Path) -> Result<()> {
+    let prover = Prover::load(&artifacts_dir())?;
+    let mut rng = StdRng::seed_from_u64(PROOF_SEED);
+    std::fs::create_dir_all(out_dir)?;
+
+    for case in scenario::deposit_then_withdraw()? {
+        write_json(&out_dir.join(format!("{}.input.json", case.name)), &case.inputs.to_json())?;
+
+        let proof = prover.prove(&case.inputs, &mut rng)?;
+        if proof.ext_data_hash[..] != case.ext_data.public_input()[..] {
+            return Err(format!("{}: proof is bound to a different ExtData", case.name).into());
+        }
+        let fixture = TransactFixture {
+            proof,
+            ext_amount: case.ext_data.ext_amount,
+            fee: case.ext_data.fee,
+            recipient: case.ext_data.recipient.to_string(),
+            fee_recipient: case.ext_data.fee_recipient.to_string(),
+            encrypted_output1: case.ext_data.encrypted_output1.clone(),
+            encrypted_output2: case.ext_data.encrypted_output2.clone(),
+        };
+        write_json(&out_dir.join(format!("{}.json", case.name)), &fixture)?;
+    }
+    Ok(())
+}
+
+fn run_prove(input_path: &Path, output_path: &Path) -> Result<()> {
+    let json = std::fs::read_to_string(input_path)
+        .map_err(|e| format!("failed to read {}: {}", input_path.display(), e))?;
+    let inputs = CircuitInputs::from_json(&json)?;
+    let prover = Prover::load(&artifacts_dir())?;
+    let proof = prover.prove(&inputs, &mut StdRng::seed_from_u64(PROOF_SEED))?;
+    write_json(output_path, &proof)
+}
+
+fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["scenario"] => run_scenario(&manifest_dir().join("../program-tests/tests/fixtures")),
+        ["scenario", out_dir] => run_scenario(Path::new(out_dir)),
+        ["prove", input, output] => run_prove(Path::new(input), Path::new(output)),
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    }
+}
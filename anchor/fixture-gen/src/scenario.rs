@@ -0,0 +1,124 @@
+//! The deposit -> withdraw flow the integration tests replay, built with the
+//! client SDK from a fixed seed so regenerated fixtures only differ in the
+//! proof randomness.
+
+use crate::circuit::CircuitInputs;
+use crate::Result;
+use anchor_lang::prelude::Pubkey;
+use ark_bn254::Fr;
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+use zkcash_client::ext_data::ExtData;
+use zkcash_client::keys::ShieldedKeys;
+use zkcash_client::note::encrypt_note;
+use zkcash_client::transaction::{plan_transaction, TransactionPlan};
+use zkcash_client::tree_sync::{LocalMerkleTree, DEFAULT_TREE_HEIGHT};
+use zkcash_client::utils::fr_from_be_bytes;
+use zkcash_client::utxo::{mint_address_field, Utxo, SOL_MINT_ADDRESS};
+
+const FIXTURE_SEED: &[u8] = b"zkcash test fixture seed";
+const FEE_RECIPIENT_ACCOUNT: &str = "AWexibGxNFKTa1b5R5MN4PJr9HWnWRwf8EW9g8cLx3dM";
+
+pub const DEPOSIT_AMOUNT: i64 = 1_000_000_000;
+pub const WITHDRAW_AMOUNT: i64 = 500_000_000;
+/// 1% of the withdrawal, the default `withdrawal_fee_rate`.
+pub const WITHDRAW_FEE: u64 = 5_000_000;
+
+/// One transact to prove: the circuit inputs plus the ExtData they commit to.
+pub struct TransactCase {
+    pub name: &'static str,
+    pub inputs: CircuitInputs,
+    pub ext_data: ExtData,
+}
+
+fn fixture_recipient() -> Pubkey {
+    Pubkey::new_from_array(Sha256::digest(b"zkcash fixture recipient").into())
+}
+
+fn build_ext_data<R: rand_chacha::rand_core::RngCore + rand_chacha::rand_core::CryptoRng>(
+    keys: &ShieldedKeys,
+    plan: &TransactionPlan,
+    rng: &mut R,
+) -> Result<ExtData> {
+    let encryption_key = keys.encryption_keypair().public_key();
+    Ok(ExtData::builder()
+        .recipient(fixture_recipient())
+        .ext_amount(plan.ext_amount)
+        .encrypted_outputs(
+            encrypt_note(&plan.outputs[0].to_note(), &encryption_key, rng)?,
+            encrypt_note(&plan.outputs[1].to_note(), &encryption_key, rng)?,
+        )
+        .fee(plan.fee)
+        .fee_recipient(Pubkey::from_str(FEE_RECIPIENT_ACCOUNT)?)
+        .build()?)
+}
+
+/// Assembles the circuit's private and public inputs for `plan`, proving
+/// membership of each input against `tree`.
+fn circuit_inputs(
+    keys: &ShieldedKeys,
+    plan: &TransactionPlan,
+    tree: &LocalMerkleTree,
+    ext_data: &ExtData,
+) -> Result<CircuitInputs> {
+    let root = tree.root();
+    let mut path_elements = Vec::new();
+    for input in &plan.inputs {
+        if input.amount == 0 {
+            // The circuit only checks membership for non-zero inputs
+            path_elements.extend(std::iter::repeat_n(Fr::from(0u64), tree.height()));
+        } else {
+            let path = tree.path(input.index)?;
+            path_elements.extend(path.path_elements.iter().map(fr_from_be_bytes));
+        }
+    }
+
+    let public = plan.public_inputs(keys, root, ext_data.public_input())?;
+    let mut inputs = CircuitInputs::default();
+    inputs.set("root", fr_from_be_bytes(&root));
+    inputs.set("publicAmount", fr_from_be_bytes(&plan.public_amount));
+    inputs.set("extDataHash", fr_from_be_bytes(&ext_data.public_input()));
+    inputs.set("mintAddress", mint_address_field(&ext_data.mint_address));
+    inputs.set_array("inputNullifier", &[fr_from_be_bytes(&public[3]), fr_from_be_bytes(&public[4])]);
+    inputs.set_array("inAmount", &plan.inputs.iter().map(|input| Fr::from(input.amount)).collect::<Vec<_>>());
+    inputs.set_array("inPrivateKey", &[keys.spending_key(), keys.spending_key()]);
+    inputs.set_array("inBlinding", &plan.inputs.iter().map(|input| input.blinding).collect::<Vec<_>>());
+    inputs.set_array("inPathIndices", &plan.inputs.iter().map(|input| Fr::from(input.index)).collect::<Vec<_>>());
+    inputs.set_array("inPathElements", &path_elements);
+    inputs.set_array("outputCommitment", &[fr_from_be_bytes(&public[5]), fr_from_be_bytes(&public[6])]);
+    inputs.set_array("outAmount", &plan.outputs.iter().map(|output| Fr::from(output.amount)).collect::<Vec<_>>());
+    inputs.set_array("outPubkey", &plan.outputs.iter().map(|output| output.pubkey).collect::<Vec<_>>());
+    inputs.set_array("outBlinding", &plan.outputs.iter().map(|output| output.blinding).collect::<Vec<_>>());
+    Ok(inputs)
+}
+
+/// A deposit of `DEPOSIT_AMOUNT` into an empty pool, followed by a withdrawal of
+/// `WITHDRAW_AMOUNT` that spends the deposited note.
+pub fn deposit_then_withdraw() -> Result<Vec<TransactCase>> {
+    let mut rng = ChaCha20Rng::from_seed(Sha256::digest(FIXTURE_SEED).into());
+    let keys = ShieldedKeys::from_seed(FIXTURE_SEED, 0)?;
+    let sol_mint = Pubkey::from_str(SOL_MINT_ADDRESS)?;
+    let mut tree = LocalMerkleTree::new(DEFAULT_TREE_HEIGHT);
+
+    let deposit = plan_transaction(&keys, &[], DEPOSIT_AMOUNT, 0, sol_mint, &mut rng)?;
+    let deposit_ext_data = build_ext_data(&keys, &deposit, &mut rng)?;
+    let deposit_inputs = circuit_inputs(&keys, &deposit, &tree, &deposit_ext_data)?;
+
+    let mut deposited: Vec<Utxo> = Vec::new();
+    for (output, commitment) in deposit.outputs.iter().zip(deposit.output_commitments()?) {
+        let mut note = output.clone();
+        note.index = tree.insert(commitment)?;
+        deposited.push(note);
+    }
+
+    let withdraw = plan_transaction(&keys, &deposited, -WITHDRAW_AMOUNT, WITHDRAW_FEE, sol_mint, &mut rng)?;
+    let withdraw_ext_data = build_ext_data(&keys, &withdraw, &mut rng)?;
+    let withdraw_inputs = circuit_inputs(&keys, &withdraw, &tree, &withdraw_ext_data)?;
+
+    Ok(vec![
+        TransactCase { name: "deposit", inputs: deposit_inputs, ext_data: deposit_ext_data },
+        TransactCase { name: "withdraw", inputs: withdraw_inputs, ext_data: withdraw_ext_data },
+    ])
+}
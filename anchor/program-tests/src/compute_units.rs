@@ -0,0 +1,159 @@
+//! Compute unit metering for the CU regression tests.
+//!
+//! Whole-instruction cost comes from the runtime's `consumed N of M compute
+//! units` log line. Sections inside an instruction (proof verification, Merkle
+//! appends) are measured from the `cu-bench: <label>:start` / `:end` markers the
+//! program emits when built with `--features cu-bench`.
+//!
+//! Measurements are compared against `tests/compute_units.json`. A missing entry
+//! is recorded on first run; `UPDATE_CU_BASELINE=1` rewrites existing ones after
+//! an intentional change.
+
+use crate::PROGRAM_ID;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// How far above the recorded baseline a measurement may drift before failing.
+pub const CU_TOLERANCE_PERCENT: u64 = 5;
+
+const MARKER_PREFIX: &str = "Program log: cu-bench: ";
+const CONSUMPTION_PREFIX: &str = "Program consumption: ";
+
+// Tests in one binary run on separate threads and share the baseline file
+static BASELINE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Logs, total compute units and return data of one processed transaction.
+#[derive(Debug)]
+pub struct ExecutionReport {
+    pub logs: Vec<String>,
+    pub compute_units: u64,
+    /// Set by the last instruction that returned data, empty if none did.
+    pub return_data: Vec<u8>,
+}
+
+impl ExecutionReport {
+    /// Units consumed by the zkcash instruction(s), excluding e.g. the compute
+    /// budget instruction.
+    pub fn program_units(&self) -> u64 {
+        let prefix = format!("Program {} consumed ", PROGRAM_ID);
+        self.logs
+            .iter()
+            .filter_map(|line| line.strip_prefix(&prefix))
+            .filter_map(|rest| rest.split_whitespace().next()?.parse::<u64>().ok())
+            .sum()
+    }
+
+    /// Units spent between each `<label>:start` and the following `<label>:end`
+    /// marker, summed over every occurrence. `None` if the program wasn't built
+    /// with `cu-bench` or the section never completed.
+    pub fn section_units(&self, label: &str) -> Option<u64> {
+        let start = format!("{}:start", label);
+        let end = format!("{}:end", label);
+        let mut started_at = None;
+        let mut total = None;
+
+        let mut lines = self.logs.iter();
+        while let Some(line) = lines.next() {
+            let Some(marker) = line.strip_prefix(MARKER_PREFIX) else {
+                continue;
+            };
+            let Some(remaining) = lines.next().and_then(|next| parse_remaining(next)) else {
+                continue;
+            };
+            if marker == start {
+                started_at = Some(remaining);
+            } else if marker == end {
+                if let Some(started) = started_at.take() {
+                    *total.get_or_insert(0) += started.saturating_sub(remaining);
+                }
+            }
+        }
+        total
+    }
+}
+
+fn parse_remaining(line: &str) -> Option<u64> {
+    line.strip_prefix(CONSUMPTION_PREFIX)?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+fn baseline_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/compute_units.json")
+}
+
+fn load_baseline() -> BTreeMap<String, u64> {
+    match std::fs::read_to_string(baseline_path()) {
+        Ok(json) => serde_json::from_str(&json).expect("tests/compute_units.json is malformed"),
+        Err(_) => BTreeMap::new(),
+    }
+}
+
+/// Checks `units` against the recorded baseline for `name`, recording it if
+/// there is none yet or `UPDATE_CU_BASELINE` is set.
+pub fn assert_within_baseline(name: &str, units: u64) {
+    let _guard = BASELINE_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut baseline = load_baseline();
+    let update = std::env::var_os("UPDATE_CU_BASELINE").is_some();
+
+    match baseline.get(name) {
+        Some(&recorded) if !update => {
+            let limit = recorded + recorded * CU_TOLERANCE_PERCENT / 100;
+            println!("{}: {} CU (baseline {}, limit {})", name, units, recorded, limit);
+            assert!(
+                units <= limit,
+                "{} regressed: {} CU exceeds baseline {} by more than {}%",
+                name,
+                units,
+                recorded,
+                CU_TOLERANCE_PERCENT
+            );
+        }
+        _ => {
+            println!("{}: recording {} CU", name, units);
+            baseline.insert(name.to_string(), units);
+            let json = serde_json::to_string_pretty(&baseline).expect("a map of integers serializes");
+            std::fs::write(baseline_path(), json + "\n").expect("failed to write tests/compute_units.json");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(logs: &[&str]) -> ExecutionReport {
+        ExecutionReport {
+            logs: logs.iter().map(|line| line.to_string()).collect(),
+            compute_units: 0,
+        }
+    }
+
+    #[test]
+    fn test_section_units_sums_every_occurrence() {
+        let report = report(&[
+            "Program log: cu-bench: append:start",
+            "Program consumption: 900000 units remaining",
+            "Program log: cu-bench: append:end",
+            "Program consumption: 880000 units remaining",
+            "Program log: cu-bench: append:start",
+            "Program consumption: 870000 units remaining",
+            "Program log: cu-bench: append:end",
+            "Program consumption: 850000 units remaining",
+        ]);
+        assert_eq!(report.section_units("append"), Some(40_000));
+        assert_eq!(report.section_units("verify_proof"), None);
+    }
+
+    #[test]
+    fn test_program_units_ignores_other_programs() {
+        let report = report(&[
+            "Program ComputeBudget111111111111111111111111111111 consumed 150 of 1000000 compute units",
+            &format!("Program {} consumed 250000 of 999850 compute units", PROGRAM_ID),
+        ]);
+        assert_eq!(report.program_units(), 250_000);
+    }
+}
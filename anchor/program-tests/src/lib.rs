@@ -0,0 +1,2283 @@
+//! Harness for running the zkcash program's real instructions on
+//! solana-program-test.
+//!
+//! The program is loaded from its SBF build, so run
+//! `anchor build -- --features localnet` first: without the localnet feature
+//! `initialize` only accepts the mainnet admin key. `SBF_OUT_DIR` overrides
+//! where `zkcash.so` is looked up and defaults to `../target/deploy`.
+//!
+//! Instruction data and account layouts are encoded by hand here and must be
+//! kept in sync with `programs/zkcash/src/lib.rs`.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    compute_budget::ComputeBudgetInstruction,
+    instruction::{AccountMeta, Instruction},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use solana_system_interface::{instruction as system_instruction, program as system_program};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+pub mod compute_units;
+
+pub use compute_units::ExecutionReport;
+
+pub const PROGRAM_ID: Pubkey = pubkey!("9fhQBbumKEFuXtMBDw8AaQyAjCorLGJQiS3skWZdQyQD");
+pub const FEE_RECIPIENT_ACCOUNT: Pubkey = pubkey!("AWexibGxNFKTa1b5R5MN4PJr9HWnWRwf8EW9g8cLx3dM");
+/// Pseudo mint the program records for native SOL.
+pub const SOL_ADDRESS: Pubkey = pubkey!("11111111111111111111111111111112");
+pub const MERKLE_TREE_HEIGHT: usize = 26;
+pub const ROOT_HISTORY_SIZE: usize = 100;
+pub const ROOT_HISTORY_CAPACITY: usize = 1024;
+pub const ROOT_INDEX_BUCKETS: usize = 2 * ROOT_HISTORY_CAPACITY;
+pub const LEAVES_PER_PAGE: usize = 256;
+/// Same limit the TypeScript tests request; transact's proof check needs it.
+pub const TRANSACT_COMPUTE_UNITS: u32 = 1_000_000;
+pub const CONFIG_UPDATE_DELAY_SLOTS: u64 = 216_000;
+pub const MERKLE_TREE_ACCOUNT_V0_LEN: usize = 4136;
+pub const MERKLE_TREE_ACCOUNT_VERSION: u8 = 3;
+pub const MIN_POOL_CREATION_FEE: u64 = 100_000_000;
+pub const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+const MINT_LEN: usize = 82;
+const TOKEN_ACCOUNT_LEN: usize = 165;
+
+/// Anchor's 8-byte discriminator for `namespace:name`.
+fn discriminator(preimage: &str) -> [u8; 8] {
+    Sha256::digest(preimage.as_bytes())[..8]
+        .try_into()
+        .expect("sha256 is longer than 8 bytes")
+}
+
+fn instruction_data<T: BorshSerialize>(name: &str, args: &T) -> Vec<u8> {
+    let mut data = discriminator(&format!("global:{}", name)).to_vec();
+    args.serialize(&mut data).expect("serializing into a Vec cannot fail");
+    data
+}
+
+pub fn tree_account_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"merkle_tree"], &PROGRAM_ID).0
+}
+
+pub fn tree_token_account_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"tree_token"], &PROGRAM_ID).0
+}
+
+pub fn global_config_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"global_config"], &PROGRAM_ID).0
+}
+
+pub fn pool_stats_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"pool_stats"], &PROGRAM_ID).0
+}
+
+pub fn anonymity_stats_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"anonymity_stats"], &PROGRAM_ID).0
+}
+
+pub fn circuit_breaker_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"circuit_breaker"], &PROGRAM_ID).0
+}
+
+pub fn pool_freeze_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"pool_freeze"], &PROGRAM_ID).0
+}
+
+pub fn pending_config_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"pending_config"], &PROGRAM_ID).0
+}
+
+pub fn pending_authority_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"pending_authority"], &PROGRAM_ID).0
+}
+
+pub fn pool_registry_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"pool_registry"], &PROGRAM_ID).0
+}
+
+pub fn pool_factory_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"pool_factory"], &PROGRAM_ID).0
+}
+
+pub fn deposit_limits_pda(tree_account: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"deposit_limits", tree_account.as_ref()], &PROGRAM_ID).0
+}
+
+pub fn pending_deposit_limits_pda(tree_account: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"pending_deposit_limits", tree_account.as_ref()], &PROGRAM_ID).0
+}
+
+pub fn deposit_queue_pda(tree_account: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"deposit_queue", tree_account.as_ref()], &PROGRAM_ID).0
+}
+
+pub fn fee_recipient_config_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"fee_recipient_config"], &PROGRAM_ID).0
+}
+
+/// The fee schedule of the pool for `mint`, `SOL_ADDRESS` for the SOL pool.
+pub fn fee_schedule_pda(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"fee_schedule", mint.as_ref()], &PROGRAM_ID).0
+}
+
+pub fn pending_fee_schedule_pda(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"pending_fee_schedule", mint.as_ref()], &PROGRAM_ID).0
+}
+
+pub fn fee_exemptions_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"fee_exemptions"], &PROGRAM_ID).0
+}
+
+pub fn pending_fee_exemptions_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"pending_fee_exemptions"], &PROGRAM_ID).0
+}
+
+pub fn relayer_pda(relayer: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"relayer", relayer.as_ref()], &PROGRAM_ID).0
+}
+
+pub fn submission_commitment_pda(commitment: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[b"submission_commitment", commitment.as_ref()], &PROGRAM_ID).0
+}
+
+pub fn nullifier_seed_config_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"nullifier_seed_config"], &PROGRAM_ID).0
+}
+
+/// A nullifier's PDA under the program's v1 seeds.
+pub fn nullifier_v1_pda(tree_account: &Pubkey, nullifier: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[b"nullifier", tree_account.as_ref(), nullifier], &PROGRAM_ID).0
+}
+
+pub fn nullifier_bloom_pda(tree_account: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"nullifier_bloom", tree_account.as_ref()], &PROGRAM_ID).0
+}
+
+pub fn root_history_pda(tree_account: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"root_history", tree_account.as_ref()], &PROGRAM_ID).0
+}
+
+pub fn tree_storage_pda(tree_account: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"tree_storage", tree_account.as_ref()], &PROGRAM_ID).0
+}
+
+pub fn leaf_storage_config_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"leaf_storage_config"], &PROGRAM_ID).0
+}
+
+pub fn leaf_page_pda(tree_account: &Pubkey, page_index: u64) -> Pubkey {
+    Pubkey::find_program_address(&[b"leaf_page", tree_account.as_ref(), &page_index.to_le_bytes()], &PROGRAM_ID).0
+}
+
+pub fn root_checkpoints_pda(tree_account: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"root_checkpoints", tree_account.as_ref()], &PROGRAM_ID).0
+}
+
+pub fn role_config_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"role_config"], &PROGRAM_ID).0
+}
+
+pub fn fee_vault_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"fee_vault"], &PROGRAM_ID).0
+}
+
+pub fn verifying_key_record_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"verifying_key_record"], &PROGRAM_ID).0
+}
+
+pub fn vk_rotation_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"vk_rotation"], &PROGRAM_ID).0
+}
+
+pub fn root_policy_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"root_policy"], &PROGRAM_ID).0
+}
+
+pub fn circuit_version_config_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"circuit_version_config"], &PROGRAM_ID).0
+}
+
+pub fn audit_log_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"audit_log"], &PROGRAM_ID).0
+}
+
+pub fn encrypted_output_config_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"encrypted_output_config"], &PROGRAM_ID).0
+}
+
+pub fn output_storage_config_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"output_storage_config"], &PROGRAM_ID).0
+}
+
+/// A token pool's accounts: tree, `TokenPool` and vault, in that order.
+pub fn token_pool_pdas(mint: &Pubkey) -> [Pubkey; 3] {
+    [b"merkle_tree".as_slice(), b"token_pool", b"tree_token"]
+        .map(|seed| Pubkey::find_program_address(&[seed, mint.as_ref()], &PROGRAM_ID).0)
+}
+
+/// Nullifier PDAs in the order transact expects: nullifier0..3, where 2 and 3
+/// are the cross-check derivations with the prefixes swapped.
+pub fn nullifier_pdas(proof: &Proof) -> [Pubkey; 4] {
+    let pda = |prefix: &[u8], nullifier: &[u8; 32]| Pubkey::find_program_address(&[prefix, nullifier], &PROGRAM_ID).0;
+    [
+        pda(b"nullifier0", &proof.input_nullifiers[0]),
+        pda(b"nullifier1", &proof.input_nullifiers[1]),
+        pda(b"nullifier0", &proof.input_nullifiers[1]),
+        pda(b"nullifier1", &proof.input_nullifiers[0]),
+    ]
+}
+
+pub fn commitment_pdas(proof: &Proof) -> [Pubkey; 2] {
+    [
+        Pubkey::find_program_address(&[b"commitment0", &proof.output_commitments[0]], &PROGRAM_ID).0,
+        Pubkey::find_program_address(&[b"commitment1", &proof.output_commitments[1]], &PROGRAM_ID).0,
+    ]
+}
+
+/// Roots of empty subtrees by height, from the empty leaf up to the empty
+/// tree, i.e. the siblings on the path of a leaf with nothing beside it.
+pub fn zero_hashes() -> Vec<[u8; 32]> {
+    use ark_bn254::Fr;
+    use ark_ff::{BigInteger, PrimeField};
+    use light_poseidon::{Poseidon, PoseidonHasher};
+
+    let mut poseidon = Poseidon::<Fr>::new_circom(2).expect("width 2 is supported");
+    let mut node = Fr::from(0u64);
+    let mut levels = Vec::with_capacity(MERKLE_TREE_HEIGHT + 1);
+    for level in 0..=MERKLE_TREE_HEIGHT {
+        levels.push(
+            node.into_bigint()
+                .to_bytes_be()
+                .try_into()
+                .expect("field elements are 32 bytes"),
+        );
+        if level < MERKLE_TREE_HEIGHT {
+            node = poseidon.hash(&[node, node]).expect("two inputs match the width");
+        }
+    }
+    levels
+}
+
+/// Root of an empty tree, i.e. what `initialize` stores.
+pub fn empty_tree_root() -> [u8; 32] {
+    zero_hashes()[MERKLE_TREE_HEIGHT]
+}
+
+/// Mirrors the program's `Proof` argument.
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct Proof {
+    pub proof_a: [u8; 64],
+    pub proof_b: [u8; 128],
+    pub proof_c: [u8; 64],
+    pub root: [u8; 32],
+    pub public_amount: [u8; 32],
+    pub ext_data_hash: [u8; 32],
+    pub input_nullifiers: [[u8; 32]; 2],
+    pub output_commitments: [[u8; 32]; 2],
+}
+
+#[derive(BorshSerialize)]
+struct TransactArgs<'a> {
+    proof: &'a Proof,
+    ext_amount: i64,
+    fee: u64,
+    encrypted_output1: &'a Vec<u8>,
+    encrypted_output2: &'a Vec<u8>,
+}
+
+/// The fields of `MerkleTreeAccount` the tests look at.
+#[derive(Debug)]
+pub struct TreeState {
+    pub authority: Pubkey,
+    pub next_index: u64,
+    pub root: [u8; 32],
+    pub root_index: u64,
+    pub max_deposit_amount: u64,
+    pub height: u8,
+}
+
+impl TreeState {
+    /// Decodes the zero-copy account: discriminator, authority, next_index,
+    /// subtrees, root, root_history, root_index, max_deposit_amount, height.
+    fn decode(data: &[u8]) -> TreeState {
+        let read_u64 = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+        let root_offset = 8 + 32 + 8 + 32 * MERKLE_TREE_HEIGHT;
+        let root_index_offset = root_offset + 32 + 32 * ROOT_HISTORY_SIZE;
+        TreeState {
+            authority: Pubkey::new_from_array(data[8..40].try_into().unwrap()),
+            next_index: read_u64(40),
+            root: data[root_offset..root_offset + 32].try_into().unwrap(),
+            root_index: read_u64(root_index_offset),
+            max_deposit_amount: read_u64(root_index_offset + 8),
+            height: data[root_index_offset + 16],
+        }
+    }
+}
+
+/// Mirrors the program's `PoolStats` account.
+#[derive(BorshDeserialize, Debug, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    pub deposit_count: u64,
+    pub withdrawal_count: u64,
+    pub transfer_count: u64,
+    pub total_deposited: u64,
+    pub total_withdrawn: u64,
+    pub total_fees: u64,
+    pub bump: u8,
+}
+
+/// Mirrors the program's `CircuitBreaker` account.
+#[derive(BorshDeserialize, Debug)]
+pub struct CircuitBreaker {
+    pub guardian: Pubkey,
+    pub max_outflow_bps: u16,
+    pub window_slots: u64,
+    pub window_start: u64,
+    pub window_outflow: u64,
+    pub previous_window_outflow: u64,
+    pub withdrawals_paused: bool,
+    pub bump: u8,
+}
+
+/// Mirrors the program's `PoolFreeze` account.
+#[derive(BorshDeserialize, Debug)]
+pub struct PoolFreeze {
+    pub withdrawals_frozen: bool,
+    pub deposits_frozen: bool,
+    pub bump: u8,
+}
+
+/// Arguments of `queue_config_update`; `None` leaves a field unchanged.
+#[derive(BorshSerialize, Clone, Debug, Default)]
+pub struct ConfigUpdate {
+    pub deposit_fee_rate: Option<u16>,
+    pub withdrawal_fee_rate: Option<u16>,
+    pub fee_error_margin: Option<u16>,
+    pub max_deposit_amount: Option<u64>,
+}
+
+/// Mirrors the program's `PoolStatus`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolStatus {
+    Active,
+    Paused,
+    Retired,
+}
+
+/// Mirrors the program's `PoolEntry`.
+#[derive(BorshDeserialize, Debug, PartialEq, Eq)]
+pub struct PoolEntry {
+    pub mint: Pubkey,
+    pub tree_account: Pubkey,
+    pub config: Pubkey,
+    pub status: PoolStatus,
+}
+
+/// Mirrors the program's `PoolRegistry` account.
+#[derive(BorshDeserialize, Debug)]
+pub struct PoolRegistry {
+    pub pools: Vec<PoolEntry>,
+    pub bump: u8,
+}
+
+/// Mirrors the program's `PoolFactory` account.
+#[derive(BorshDeserialize, Debug)]
+pub struct PoolFactory {
+    pub treasury: Pubkey,
+    pub creation_fee: u64,
+    pub bump: u8,
+}
+
+/// Mirrors the program's `TokenPool` account.
+#[derive(BorshDeserialize, Debug)]
+pub struct TokenPool {
+    pub mint: Pubkey,
+    pub tree_account: Pubkey,
+    pub vault: Pubkey,
+    pub creator: Pubkey,
+    pub decimals: u8,
+    pub bump: u8,
+    pub vault_bump: u8,
+}
+
+/// Mirrors the program's `DepositLimits` account.
+#[derive(BorshDeserialize, Debug)]
+pub struct DepositLimits {
+    pub tree_account: Pubkey,
+    pub max_per_transaction: u64,
+    pub max_per_slot: u64,
+    pub current_slot: u64,
+    pub deposited_in_slot: u64,
+    pub bump: u8,
+}
+
+/// Mirrors the program's `DepositQueue` account.
+#[derive(BorshDeserialize, Debug)]
+pub struct DepositQueue {
+    pub tree_account: Pubkey,
+    pub pending: Vec<[u8; 32]>,
+    pub total_enqueued: u64,
+    pub bump: u8,
+}
+
+/// Mirrors the program's `FeeRecipientConfig` account.
+#[derive(BorshDeserialize, Debug)]
+pub struct FeeRecipientConfig {
+    pub fee_recipient: Pubkey,
+    pub bump: u8,
+}
+
+/// Mirrors the program's `FeeTier`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeTier {
+    pub min_amount: u64,
+    pub fee_rate: u16,
+}
+
+/// Mirrors the program's `FeeSchedule` account.
+#[derive(BorshDeserialize, Debug)]
+pub struct FeeSchedule {
+    pub mint: Pubkey,
+    pub base_fee: u64,
+    pub tiers: Vec<FeeTier>,
+    pub fee_cap: u64,
+    pub bump: u8,
+}
+
+/// Mirrors the program's `FeeExemptions` account.
+#[derive(BorshDeserialize, Debug)]
+pub struct FeeExemptions {
+    pub exempt: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+/// Mirrors the program's `RelayerAccount` account.
+#[derive(BorshDeserialize, Debug)]
+pub struct RelayerAccount {
+    pub relayer: Pubkey,
+    pub withdrawal_fee_rate: u16,
+    pub registered_slot: u64,
+    pub bond: u64,
+    pub unbonding_slot: u64,
+    pub bump: u8,
+}
+
+/// Mirrors the program's `SubmissionCommitment` account.
+#[derive(BorshDeserialize, Debug)]
+pub struct SubmissionCommitment {
+    pub relayer: Pubkey,
+    pub commitment: [u8; 32],
+    pub committed_slot: u64,
+    pub bump: u8,
+}
+
+/// Mirrors the program's `NullifierSeedVersion`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NullifierSeedVersion {
+    V0,
+    V1,
+}
+
+/// Mirrors the program's `NullifierSeedConfig` account.
+#[derive(BorshDeserialize, Debug)]
+pub struct NullifierSeedConfig {
+    pub version: NullifierSeedVersion,
+    pub legacy_version: Option<NullifierSeedVersion>,
+    pub migration_end_slot: u64,
+    pub bump: u8,
+}
+
+/// Mirrors the program's zero-copy `NullifierBloomFilter` account, which has
+/// no padding and so reads as Borsh.
+#[derive(BorshDeserialize, Debug)]
+pub struct NullifierBloomFilter {
+    pub tree_account: Pubkey,
+    pub inserted: u64,
+    pub bits: [u8; 8192],
+}
+
+/// Mirrors the program's zero-copy `RootHistory` account, which has no
+/// padding and so reads as Borsh.
+#[derive(BorshDeserialize, Debug)]
+pub struct RootHistory {
+    pub tree_account: Pubkey,
+    pub root_index: u64,
+    pub roots: [[u8; 32]; ROOT_HISTORY_CAPACITY],
+    pub index: [u16; ROOT_INDEX_BUCKETS],
+}
+
+/// Mirrors the program's `BalanceAudit` return data.
+#[derive(BorshDeserialize, Debug, PartialEq, Eq)]
+pub struct BalanceAudit {
+    pub actual_lamports: u64,
+    pub expected_lamports: u64,
+    pub drift: i128,
+}
+
+/// Mirrors the program's `InvariantReport` return data.
+#[derive(BorshDeserialize, Debug, PartialEq, Eq)]
+pub struct InvariantReport {
+    pub solvent: bool,
+    pub within_capacity: bool,
+    pub actual_lamports: u64,
+    pub liabilities: u64,
+    pub next_index: u64,
+    pub capacity: u64,
+}
+
+/// Mirrors the program's `AnonymityMetrics` return data.
+#[derive(BorshDeserialize, Debug, PartialEq, Eq)]
+pub struct AnonymityMetrics {
+    pub leaf_count: u64,
+    pub capacity: u64,
+    pub occupancy_bps: u16,
+    pub unspent_upper_bound: u64,
+    pub recent_deposits: u64,
+    pub epochs: u8,
+}
+
+/// Mirrors the program's `TreeSnapshotPage` return data.
+#[derive(BorshDeserialize, Debug)]
+pub struct TreeSnapshotPage {
+    pub next_index: u64,
+    pub page: u16,
+    pub total_pages: u16,
+    pub bytes: Vec<u8>,
+}
+
+/// Mirrors the program's `RootPolicy` account.
+#[derive(BorshDeserialize, Debug)]
+pub struct RootPolicy {
+    pub max_root_age_slots: u64,
+    pub bump: u8,
+}
+
+/// Mirrors the program's `CircuitVersionConfig` account.
+#[derive(BorshDeserialize, Debug)]
+pub struct CircuitVersionConfig {
+    pub min_version: u8,
+    pub bump: u8,
+}
+
+/// Mirrors the program's zero-copy `TreeStorage` account, which has no
+/// padding and so reads as Borsh.
+#[derive(BorshDeserialize, Debug)]
+pub struct TreeStorage {
+    pub tree_account: Pubkey,
+    pub root_index: u64,
+    pub subtrees: [[u8; 32]; MERKLE_TREE_HEIGHT],
+    pub root_history: [[u8; 32]; ROOT_HISTORY_SIZE],
+}
+
+/// Mirrors the program's zero-copy `LeafPage` account.
+#[derive(BorshDeserialize, Debug)]
+pub struct LeafPage {
+    pub tree_account: Pubkey,
+    pub page_index: u64,
+    pub len: u64,
+    pub leaves: [[u8; 32]; LEAVES_PER_PAGE],
+}
+
+/// Mirrors the header of the program's zero-copy `RootCheckpoints` account.
+#[derive(BorshDeserialize, Debug)]
+pub struct RootCheckpointsHeader {
+    pub tree_account: Pubkey,
+    pub interval_slots: u64,
+    pub len: u64,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+}
+
+/// Mirrors the program's `RootCheckpoint`, stored after the header.
+#[derive(BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RootCheckpoint {
+    pub slot: u64,
+    pub next_index: u64,
+    pub root: [u8; 32],
+}
+
+/// Mirrors the program's `Role`.
+#[derive(BorshSerialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    FeeManager,
+    TreeOperator,
+    FeeCollector,
+}
+
+/// Mirrors the program's `RoleConfig` account.
+#[derive(BorshDeserialize, Debug)]
+pub struct RoleConfig {
+    pub fee_manager: Pubkey,
+    pub tree_operator: Pubkey,
+    pub fee_collector: Pubkey,
+    pub bump: u8,
+}
+
+/// Mirrors the program's `FeeVault` account.
+#[derive(BorshDeserialize, Debug)]
+pub struct FeeVault {
+    pub payout: Pubkey,
+    pub total_swept: u64,
+    pub bump: u8,
+}
+
+/// A `RootCheckpoints` account: its header and the checkpoints after it.
+#[derive(Debug)]
+pub struct RootCheckpoints {
+    pub header: RootCheckpointsHeader,
+    pub checkpoints: Vec<RootCheckpoint>,
+}
+
+/// Mirrors the program's `SlashReason`.
+#[derive(BorshSerialize, Clone, Copy, Debug)]
+pub enum SlashReason {
+    FeeManipulation,
+    Censorship,
+}
+
+/// Mirrors the program's `EncryptedOutputConfig` account.
+#[derive(BorshDeserialize, Debug)]
+pub struct EncryptedOutputConfig {
+    pub max_len: u16,
+    pub bump: u8,
+}
+
+/// Mirrors the program's `OutputStorageMode`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputStorageMode {
+    Account,
+    EventOnly,
+}
+
+/// Mirrors the program's `OutputStorageConfig` account.
+#[derive(BorshDeserialize, Debug)]
+pub struct OutputStorageConfig {
+    pub mode: OutputStorageMode,
+    pub bump: u8,
+}
+
+/// Mirrors the program's `VerifyingKeyRecord` account.
+#[derive(BorshDeserialize, Debug)]
+pub struct VerifyingKeyRecord {
+    pub vk_hash: [u8; 32],
+    pub updated_slot: u64,
+    pub bump: u8,
+}
+
+/// Mirrors the program's `VerifyingKeyRotation` account.
+#[derive(BorshDeserialize, Debug)]
+pub struct VerifyingKeyRotation {
+    pub active_hash: [u8; 32],
+    pub pending_hash: Option<[u8; 32]>,
+    pub activation_epoch: u64,
+    pub previous_hash: Option<[u8; 32]>,
+    pub grace_end_epoch: u64,
+    pub bump: u8,
+}
+
+/// Mirrors the program's `AdminAction`.
+#[derive(BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdminAction {
+    QueueConfigUpdate,
+    ExecuteConfigUpdate,
+    CancelConfigUpdate,
+    ProposeAuthority,
+    AcceptAuthority,
+    MigrateAccount,
+    PausePool,
+    ResumePool,
+    RetirePool,
+    SetFeeRecipient,
+    SetFeeSchedule,
+    SetFeeExemptions,
+    SlashRelayer,
+    BeginNullifierSeedMigration,
+    RescueTokens,
+    SetRootPolicy,
+    InitializeRootHistory,
+    SetOutputStorageMode,
+    QueueVerifyingKeyRotation,
+    SetMinCircuitVersion,
+    SplitTreeStorage,
+    SetLeafStorage,
+    InitializeRootCheckpoints,
+    SetGuardian,
+    SetRole,
+    SetFeePayout,
+    SweepFees,
+    QueueFeeSchedule,
+    CancelFeeSchedule,
+    QueueFeeExemptions,
+    CancelFeeExemptions,
+    ConfigurePoolFactory,
+    SetMaxEncryptedOutputLen,
+    UpdateCircuitBreaker,
+    ResumeWithdrawals,
+    SetPoolFreeze,
+    ConfigureYield,
+    ConfigureLstPool,
+    SetSwapPrograms,
+    SetCpiPrograms,
+    QueueDepositLimits,
+    SetDepositLimits,
+    CancelDepositLimits,
+}
+
+/// Mirrors the program's `AuditEntry`.
+#[derive(BorshDeserialize, Debug, PartialEq, Eq)]
+pub struct AuditEntry {
+    pub sequence: u64,
+    pub slot: u64,
+    pub actor: Pubkey,
+    pub action: AdminAction,
+    pub target: Pubkey,
+}
+
+/// Mirrors the program's `AuditLog` account.
+#[derive(BorshDeserialize, Debug)]
+pub struct AuditLog {
+    pub entries: Vec<AuditEntry>,
+    pub next_sequence: u64,
+    pub bump: u8,
+}
+
+/// Mirrors the program's `GlobalConfig` account.
+#[derive(BorshDeserialize, Debug)]
+pub struct GlobalConfig {
+    pub authority: Pubkey,
+    pub deposit_fee_rate: u16,
+    pub withdrawal_fee_rate: u16,
+    pub fee_error_margin: u16,
+    pub bump: u8,
+}
+
+#[derive(Deserialize)]
+struct ProofJson {
+    proof_a: Vec<u8>,
+    proof_b: Vec<u8>,
+    proof_c: Vec<u8>,
+    root: Vec<u8>,
+    public_amount: Vec<u8>,
+    ext_data_hash: Vec<u8>,
+    input_nullifiers: Vec<Vec<u8>>,
+    output_commitments: Vec<Vec<u8>>,
+}
+
+#[derive(Deserialize)]
+struct TransactFixtureJson {
+    proof: ProofJson,
+    ext_amount: i64,
+    fee: u64,
+    recipient: String,
+    fee_recipient: String,
+    encrypted_output1: Vec<u8>,
+    encrypted_output2: Vec<u8>,
+}
+
+/// A proof plus the ExtData it was generated for, loaded from tests/fixtures.
+pub struct TransactFixture {
+    pub proof: Proof,
+    pub ext_amount: i64,
+    pub fee: u64,
+    pub recipient: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub encrypted_output1: Vec<u8>,
+    pub encrypted_output2: Vec<u8>,
+}
+
+fn to_array<const N: usize>(bytes: &[u8], field: &str) -> [u8; N] {
+    bytes
+        .try_into()
+        .unwrap_or_else(|_| panic!("fixture field {} must be {} bytes, got {}", field, N, bytes.len()))
+}
+
+impl TransactFixture {
+    pub fn load(name: &str) -> TransactFixture {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(format!("{}.json", name));
+        let json = std::fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("failed to read fixture {}: {}", path.display(), e));
+        let fixture: TransactFixtureJson = serde_json::from_str(&json)
+            .unwrap_or_else(|e| panic!("failed to parse fixture {}: {}", path.display(), e));
+
+        let proof = &fixture.proof;
+        TransactFixture {
+            proof: Proof {
+                proof_a: to_array(&proof.proof_a, "proof_a"),
+                proof_b: to_array(&proof.proof_b, "proof_b"),
+                proof_c: to_array(&proof.proof_c, "proof_c"),
+                root: to_array(&proof.root, "root"),
+                public_amount: to_array(&proof.public_amount, "public_amount"),
+                ext_data_hash: to_array(&proof.ext_data_hash, "ext_data_hash"),
+                input_nullifiers: [
+                    to_array(&proof.input_nullifiers[0], "input_nullifiers[0]"),
+                    to_array(&proof.input_nullifiers[1], "input_nullifiers[1]"),
+                ],
+                output_commitments: [
+                    to_array(&proof.output_commitments[0], "output_commitments[0]"),
+                    to_array(&proof.output_commitments[1], "output_commitments[1]"),
+                ],
+            },
+            ext_amount: fixture.ext_amount,
+            fee: fixture.fee,
+            recipient: Pubkey::from_str(&fixture.recipient).expect("fixture recipient must be base58"),
+            fee_recipient: Pubkey::from_str(&fixture.fee_recipient).expect("fixture fee_recipient must be base58"),
+            encrypted_output1: fixture.encrypted_output1,
+            encrypted_output2: fixture.encrypted_output2,
+        }
+    }
+}
+
+/// A freshly initialized pool.
+pub struct PoolHarness {
+    pub context: ProgramTestContext,
+    pub authority: Keypair,
+    pub tree_account: Pubkey,
+    pub tree_token_account: Pubkey,
+    pub global_config: Pubkey,
+    pub pool_stats: Pubkey,
+}
+
+impl PoolHarness {
+    pub async fn start() -> PoolHarness {
+        let mut pool = PoolHarness::start_uninitialized().await;
+        let initialize = pool.initialize_instruction();
+        let initialize_pool_stats = pool.initialize_pool_stats_instruction();
+        let initialize_audit_log = pool.initialize_audit_log_instruction();
+        let initialize_role_config = pool.initialize_role_config_instruction();
+        let authority = pool.authority.insecure_clone();
+        let instructions = [initialize, initialize_pool_stats, initialize_audit_log, initialize_role_config];
+        pool.process(&instructions, &[&authority])
+            .await
+            .expect("initialize failed");
+        pool
+    }
+
+    /// Boots the validator with the program loaded but `initialize` not yet sent.
+    pub async fn start_uninitialized() -> PoolHarness {
+        if std::env::var_os("SBF_OUT_DIR").is_none() {
+            let deploy_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../target/deploy");
+            std::env::set_var("SBF_OUT_DIR", deploy_dir);
+        }
+
+        let authority = Keypair::new();
+        let mut program_test = ProgramTest::new("zkcash", PROGRAM_ID, None);
+        program_test.add_account(
+            authority.pubkey(),
+            Account {
+                lamports: 10 * LAMPORTS_PER_SOL,
+                ..Account::default()
+            },
+        );
+
+        PoolHarness {
+            context: program_test.start_with_context().await,
+            authority,
+            tree_account: tree_account_pda(),
+            tree_token_account: tree_token_account_pda(),
+            global_config: global_config_pda(),
+            pool_stats: pool_stats_pda(),
+        }
+    }
+
+    pub fn initialize_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(self.tree_account, false),
+                AccountMeta::new(self.tree_token_account, false),
+                AccountMeta::new(self.global_config, false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: discriminator("global:initialize").to_vec(),
+        }
+    }
+
+    pub fn initialize_pool_stats_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(self.pool_stats, false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: discriminator("global:initialize_pool_stats").to_vec(),
+        }
+    }
+
+    pub fn initialize_audit_log_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(audit_log_pda(), false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: discriminator("global:initialize_audit_log").to_vec(),
+        }
+    }
+
+    pub fn initialize_role_config_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(role_config_pda(), false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: discriminator("global:initialize_role_config").to_vec(),
+        }
+    }
+
+    pub fn initialize_circuit_breaker_instruction(&self, max_outflow_bps: u16, window_slots: u64) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(circuit_breaker_pda(), false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: instruction_data("initialize_circuit_breaker", &(max_outflow_bps, window_slots)),
+        }
+    }
+
+    pub fn update_circuit_breaker_instruction(
+        &self,
+        guardian: &Pubkey,
+        max_outflow_bps: Option<u16>,
+        window_slots: Option<u64>,
+    ) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(circuit_breaker_pda(), false),
+                AccountMeta::new_readonly(*guardian, true),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: instruction_data("update_circuit_breaker", &(max_outflow_bps, window_slots)),
+        }
+    }
+
+    pub fn resume_withdrawals_instruction(&self, guardian: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(circuit_breaker_pda(), false),
+                AccountMeta::new_readonly(*guardian, true),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: discriminator("global:resume_withdrawals").to_vec(),
+        }
+    }
+
+    pub fn set_pool_freeze_instruction(&self, guardian: &Pubkey, withdrawals_frozen: bool, deposits_frozen: bool) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(pool_freeze_pda(), false),
+                AccountMeta::new_readonly(circuit_breaker_pda(), false),
+                AccountMeta::new(*guardian, true),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: instruction_data("set_pool_freeze", &(withdrawals_frozen, deposits_frozen)),
+        }
+    }
+
+    pub fn set_guardian_instruction(&self, guardian: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(circuit_breaker_pda(), false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new_readonly(self.authority.pubkey(), true),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: instruction_data("set_guardian", guardian),
+        }
+    }
+
+    pub fn set_role_instruction(&self, role: Role, holder: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(role_config_pda(), false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new_readonly(self.authority.pubkey(), true),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: instruction_data("set_role", &(role, *holder)),
+        }
+    }
+
+    pub fn set_fee_payout_instruction(&self, payout: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(fee_vault_pda(), false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: instruction_data("set_fee_payout", payout),
+        }
+    }
+
+    pub fn sweep_fees_instruction(&self, collector: &Pubkey, payout: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(fee_vault_pda(), false),
+                AccountMeta::new(*payout, false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new_readonly(role_config_pda(), false),
+                AccountMeta::new_readonly(*collector, true),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: discriminator("global:sweep_fees").to_vec(),
+        }
+    }
+
+    pub fn queue_config_update_instruction(&self, update: &ConfigUpdate) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(pending_config_pda(), false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: instruction_data("queue_config_update", update),
+        }
+    }
+
+    /// Executable by anyone once the delay has passed; `authority` only receives the rent.
+    pub fn execute_config_update_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(pending_config_pda(), false),
+                AccountMeta::new(self.global_config, false),
+                AccountMeta::new(self.tree_account, false),
+                AccountMeta::new(self.authority.pubkey(), false),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: discriminator("global:execute_config_update").to_vec(),
+        }
+    }
+
+    pub fn cancel_config_update_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(pending_config_pda(), false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: discriminator("global:cancel_config_update").to_vec(),
+        }
+    }
+
+    pub fn propose_authority_instruction(&self, new_authority: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(pending_authority_pda(), false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: instruction_data("propose_authority", new_authority),
+        }
+    }
+
+    pub fn accept_authority_instruction(&self, new_authority: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(pending_authority_pda(), false),
+                AccountMeta::new(self.tree_account, false),
+                AccountMeta::new(self.tree_token_account, false),
+                AccountMeta::new(self.global_config, false),
+                AccountMeta::new(*new_authority, true),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: discriminator("global:accept_authority").to_vec(),
+        }
+    }
+
+    pub fn migrate_account_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(self.tree_account, false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: discriminator("global:migrate_account").to_vec(),
+        }
+    }
+
+    pub fn split_tree_storage_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(tree_storage_pda(&self.tree_account), false),
+                AccountMeta::new(self.tree_account, false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new_readonly(role_config_pda(), false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: discriminator("global:split_tree_storage").to_vec(),
+        }
+    }
+
+    pub fn set_leaf_storage_instruction(&self, enabled: bool) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(leaf_storage_config_pda(), false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new_readonly(role_config_pda(), false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: instruction_data("set_leaf_storage", &enabled),
+        }
+    }
+
+    pub fn initialize_leaf_page_instruction(&self, payer: &Pubkey, page_index: u64) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(leaf_page_pda(&self.tree_account, page_index), false),
+                AccountMeta::new_readonly(self.tree_account, false),
+                AccountMeta::new_readonly(leaf_storage_config_pda(), false),
+                AccountMeta::new(*payer, true),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: instruction_data("initialize_leaf_page", &page_index),
+        }
+    }
+
+    pub fn initialize_root_checkpoints_instruction(&self, interval_slots: u64) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(root_checkpoints_pda(&self.tree_account), false),
+                AccountMeta::new_readonly(self.tree_account, false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new_readonly(role_config_pda(), false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: instruction_data("initialize_root_checkpoints", &interval_slots),
+        }
+    }
+
+    pub fn checkpoint_root_instruction(&self, payer: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(root_checkpoints_pda(&self.tree_account), false),
+                AccountMeta::new_readonly(self.tree_account, false),
+                AccountMeta::new(*payer, true),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: discriminator("global:checkpoint_root").to_vec(),
+        }
+    }
+
+    pub fn initialize_pool_registry_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(pool_registry_pda(), false),
+                AccountMeta::new_readonly(self.tree_account, false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: discriminator("global:initialize_pool_registry").to_vec(),
+        }
+    }
+
+    pub fn set_pool_status_instruction(&self, tree_account: &Pubkey, status: PoolStatus) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(pool_registry_pda(), false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new_readonly(self.authority.pubkey(), true),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: instruction_data("set_pool_status", &(*tree_account, status)),
+        }
+    }
+
+    pub fn configure_pool_factory_instruction(&self, treasury: &Pubkey, creation_fee: u64) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(pool_factory_pda(), false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: instruction_data("configure_pool_factory", &(*treasury, creation_fee)),
+        }
+    }
+
+    /// `payer` can be anyone; `treasury` has to match the factory's.
+    pub fn initialize_token_pool_instruction(&self, mint: &Pubkey, treasury: &Pubkey, payer: &Pubkey) -> Instruction {
+        let [tree_account, token_pool, vault] = token_pool_pdas(mint);
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(pool_factory_pda(), false),
+                AccountMeta::new(*treasury, false),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new(tree_account, false),
+                AccountMeta::new(token_pool, false),
+                AccountMeta::new(vault, false),
+                AccountMeta::new(pool_registry_pda(), false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new(*payer, true),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: discriminator("global:initialize_token_pool").to_vec(),
+        }
+    }
+
+    pub fn queue_deposit_limits_instruction(
+        &self,
+        tree_account: &Pubkey,
+        max_per_transaction: u64,
+        max_per_slot: u64,
+    ) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(pending_deposit_limits_pda(tree_account), false),
+                AccountMeta::new_readonly(*tree_account, false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: instruction_data("queue_deposit_limits", &(max_per_transaction, max_per_slot)),
+        }
+    }
+
+    /// Signed by the harness payer, which also pays for `deposit_limits`.
+    pub fn execute_deposit_limits_instruction(&self, tree_account: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(pending_deposit_limits_pda(tree_account), false),
+                AccountMeta::new(deposit_limits_pda(tree_account), false),
+                AccountMeta::new_readonly(*tree_account, false),
+                AccountMeta::new(self.authority.pubkey(), false),
+                AccountMeta::new(self.context.payer.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: discriminator("global:execute_deposit_limits").to_vec(),
+        }
+    }
+
+    pub fn cancel_deposit_limits_instruction(&self, tree_account: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(pending_deposit_limits_pda(tree_account), false),
+                AccountMeta::new_readonly(*tree_account, false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: discriminator("global:cancel_deposit_limits").to_vec(),
+        }
+    }
+
+    /// Queues `max_per_transaction` and `max_per_slot` for `tree_account`,
+    /// waits out the timelock and executes them.
+    pub async fn set_deposit_limits(
+        &mut self,
+        tree_account: &Pubkey,
+        max_per_transaction: u64,
+        max_per_slot: u64,
+    ) -> Result<(), BanksClientError> {
+        let authority = self.authority.insecure_clone();
+        let queue = self.queue_deposit_limits_instruction(tree_account, max_per_transaction, max_per_slot);
+        self.process(&[queue], &[&authority]).await?;
+        self.advance_slots(CONFIG_UPDATE_DELAY_SLOTS + 1).await;
+        let execute = self.execute_deposit_limits_instruction(tree_account);
+        self.process(&[execute], &[]).await
+    }
+
+    pub fn set_max_encrypted_output_len_instruction(&self, max_len: u16) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(encrypted_output_config_pda(), false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: instruction_data("set_max_encrypted_output_len", &max_len),
+        }
+    }
+
+    pub fn set_output_storage_mode_instruction(&self, mode: OutputStorageMode) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(output_storage_config_pda(), false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: instruction_data("set_output_storage_mode", &mode),
+        }
+    }
+
+    pub fn set_fee_recipient_instruction(&self, fee_recipient: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(fee_recipient_config_pda(), false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: instruction_data("set_fee_recipient", fee_recipient),
+        }
+    }
+
+    /// Queues a schedule for the pool of `mint`, passing its `TokenPool` unless
+    /// it is the SOL pool.
+    pub fn queue_fee_schedule_instruction(
+        &self,
+        mint: &Pubkey,
+        base_fee: u64,
+        tiers: &[FeeTier],
+        fee_cap: u64,
+    ) -> Instruction {
+        let token_pool = if *mint == SOL_ADDRESS { PROGRAM_ID } else { token_pool_pdas(mint)[1] };
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(pending_fee_schedule_pda(mint), false),
+                AccountMeta::new_readonly(token_pool, false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new_readonly(role_config_pda(), false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: instruction_data("queue_fee_schedule", &(*mint, base_fee, tiers.to_vec(), fee_cap)),
+        }
+    }
+
+    /// Signed by the harness payer; the rent goes back to `self.authority`,
+    /// which must be who queued the schedule.
+    pub fn execute_fee_schedule_instruction(&self, mint: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(pending_fee_schedule_pda(mint), false),
+                AccountMeta::new(fee_schedule_pda(mint), false),
+                AccountMeta::new(self.authority.pubkey(), false),
+                AccountMeta::new(self.context.payer.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: instruction_data("execute_fee_schedule", mint),
+        }
+    }
+
+    pub fn cancel_fee_schedule_instruction(&self, mint: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(pending_fee_schedule_pda(mint), false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new_readonly(role_config_pda(), false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: instruction_data("cancel_fee_schedule", mint),
+        }
+    }
+
+    /// Queues a schedule for `mint` as `self.authority`, waits out the
+    /// timelock and executes it.
+    pub async fn set_fee_schedule(
+        &mut self,
+        mint: &Pubkey,
+        base_fee: u64,
+        tiers: &[FeeTier],
+        fee_cap: u64,
+    ) -> Result<(), BanksClientError> {
+        let authority = self.authority.insecure_clone();
+        let queue = self.queue_fee_schedule_instruction(mint, base_fee, tiers, fee_cap);
+        self.process(&[queue], &[&authority]).await?;
+        self.advance_slots(CONFIG_UPDATE_DELAY_SLOTS + 1).await;
+        let execute = self.execute_fee_schedule_instruction(mint);
+        self.process(&[execute], &[]).await
+    }
+
+    pub fn queue_fee_exemptions_instruction(&self, exempt: &[Pubkey]) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(pending_fee_exemptions_pda(), false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new_readonly(role_config_pda(), false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: instruction_data("queue_fee_exemptions", &exempt.to_vec()),
+        }
+    }
+
+    /// Signed by the harness payer; the rent goes back to `self.authority`,
+    /// which must be who queued the list.
+    pub fn execute_fee_exemptions_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(pending_fee_exemptions_pda(), false),
+                AccountMeta::new(fee_exemptions_pda(), false),
+                AccountMeta::new(self.authority.pubkey(), false),
+                AccountMeta::new(self.context.payer.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: discriminator("global:execute_fee_exemptions").to_vec(),
+        }
+    }
+
+    pub fn cancel_fee_exemptions_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(pending_fee_exemptions_pda(), false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new_readonly(role_config_pda(), false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: discriminator("global:cancel_fee_exemptions").to_vec(),
+        }
+    }
+
+    /// Queues `exempt` as `self.authority`, waits out the timelock and
+    /// executes it.
+    pub async fn set_fee_exemptions(&mut self, exempt: &[Pubkey]) -> Result<(), BanksClientError> {
+        let authority = self.authority.insecure_clone();
+        let queue = self.queue_fee_exemptions_instruction(exempt);
+        self.process(&[queue], &[&authority]).await?;
+        self.advance_slots(CONFIG_UPDATE_DELAY_SLOTS + 1).await;
+        let execute = self.execute_fee_exemptions_instruction();
+        self.process(&[execute], &[]).await
+    }
+
+    pub fn set_root_policy_instruction(&self, max_root_age_slots: u64) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(root_policy_pda(), false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: instruction_data("set_root_policy", &max_root_age_slots),
+        }
+    }
+
+    pub fn set_min_circuit_version_instruction(&self, min_version: u8) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(circuit_version_config_pda(), false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: instruction_data("set_min_circuit_version", &min_version),
+        }
+    }
+
+    pub fn set_relayer_fee_rate_instruction(&self, relayer: &Pubkey, withdrawal_fee_rate: u16) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(relayer_pda(relayer), false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new(*relayer, true),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: instruction_data("set_relayer_fee_rate", &withdrawal_fee_rate),
+        }
+    }
+
+    pub fn bond_relayer_instruction(&self, relayer: &Pubkey, amount: u64) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(relayer_pda(relayer), false),
+                AccountMeta::new(*relayer, true),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: instruction_data("bond_relayer", &amount),
+        }
+    }
+
+    pub fn request_relayer_deregistration_instruction(&self, relayer: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(relayer_pda(relayer), false),
+                AccountMeta::new(*relayer, true),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: discriminator("global:request_relayer_deregistration").to_vec(),
+        }
+    }
+
+    pub fn slash_relayer_instruction(
+        &self,
+        guardian: &Pubkey,
+        relayer: &Pubkey,
+        treasury: &Pubkey,
+        amount: u64,
+        reason: SlashReason,
+        evidence: [u8; 32],
+    ) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(relayer_pda(relayer), false),
+                AccountMeta::new_readonly(fee_recipient_config_pda(), false),
+                AccountMeta::new(*treasury, false),
+                AccountMeta::new_readonly(circuit_breaker_pda(), false),
+                AccountMeta::new_readonly(*guardian, true),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: instruction_data("slash_relayer", &(amount, reason, evidence)),
+        }
+    }
+
+    pub fn deregister_relayer_instruction(&self, relayer: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(relayer_pda(relayer), false),
+                AccountMeta::new(*relayer, true),
+            ],
+            data: discriminator("global:deregister_relayer").to_vec(),
+        }
+    }
+
+    pub fn commit_submission_instruction(&self, relayer: &Pubkey, commitment: [u8; 32]) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(submission_commitment_pda(&commitment), false),
+                AccountMeta::new(*relayer, true),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: instruction_data("commit_submission", &commitment),
+        }
+    }
+
+    pub fn close_submission_commitment_instruction(&self, relayer: &Pubkey, commitment: [u8; 32]) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(submission_commitment_pda(&commitment), false),
+                AccountMeta::new(*relayer, true),
+            ],
+            data: discriminator("global:close_submission_commitment").to_vec(),
+        }
+    }
+
+    pub fn begin_nullifier_seed_migration_instruction(
+        &self,
+        version: NullifierSeedVersion,
+        window_slots: u64,
+    ) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(nullifier_seed_config_pda(), false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: instruction_data("begin_nullifier_seed_migration", &(version, window_slots)),
+        }
+    }
+
+    /// Migrates `nullifier`, spent as input `index`, from v0 to v1 seeds.
+    pub fn migrate_nullifier_instruction(&self, payer: &Pubkey, index: u8, nullifier: [u8; 32]) -> Instruction {
+        let prefix: &[u8] = if index == 0 { b"nullifier0" } else { b"nullifier1" };
+        let legacy = Pubkey::find_program_address(&[prefix, &nullifier], &PROGRAM_ID).0;
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(nullifier_seed_config_pda(), false),
+                AccountMeta::new_readonly(legacy, false),
+                AccountMeta::new(nullifier_v1_pda(&self.tree_account, &nullifier), false),
+                AccountMeta::new(*payer, true),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: instruction_data("migrate_nullifier", &(self.tree_account, index, nullifier)),
+        }
+    }
+
+    pub fn initialize_nullifier_bloom_filter_instruction(&self, payer: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(nullifier_bloom_pda(&self.tree_account), false),
+                AccountMeta::new_readonly(self.tree_account, false),
+                AccountMeta::new(*payer, true),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: discriminator("global:initialize_nullifier_bloom_filter").to_vec(),
+        }
+    }
+
+    pub fn record_verifying_key_instruction(&self, payer: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(verifying_key_record_pda(), false),
+                AccountMeta::new(*payer, true),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: discriminator("global:record_verifying_key").to_vec(),
+        }
+    }
+
+    pub fn assert_verifying_key_instruction(&self, vk_hash: [u8; 32]) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![],
+            data: instruction_data("assert_verifying_key", &vk_hash),
+        }
+    }
+
+    pub fn queue_verifying_key_rotation_instruction(&self, vk_hash: [u8; 32]) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(vk_rotation_pda(), false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: instruction_data("queue_verifying_key_rotation", &vk_hash),
+        }
+    }
+
+    pub fn activate_verifying_key_rotation_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![AccountMeta::new(vk_rotation_pda(), false)],
+            data: discriminator("global:activate_verifying_key_rotation").to_vec(),
+        }
+    }
+
+    /// Leaves out the optional root history, as a tree without one does.
+    pub fn verify_inclusion_instruction(
+        &self,
+        leaf: [u8; 32],
+        leaf_index: u64,
+        path: &[[u8; 32]],
+        root: [u8; 32],
+    ) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(self.tree_account, false),
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+            ],
+            data: instruction_data("verify_inclusion", &(leaf, leaf_index, path.to_vec(), root)),
+        }
+    }
+
+    pub fn initialize_root_history_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(root_history_pda(&self.tree_account), false),
+                AccountMeta::new_readonly(self.tree_account, false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new_readonly(role_config_pda(), false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: discriminator("global:initialize_root_history").to_vec(),
+        }
+    }
+
+    pub fn grow_root_history_instruction(&self, payer: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(root_history_pda(&self.tree_account), false),
+                AccountMeta::new_readonly(self.tree_account, false),
+                AccountMeta::new(*payer, true),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: discriminator("global:grow_root_history").to_vec(),
+        }
+    }
+
+    pub fn initialize_anonymity_stats_instruction(&self, payer: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(anonymity_stats_pda(), false),
+                AccountMeta::new(*payer, true),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: discriminator("global:initialize_anonymity_stats").to_vec(),
+        }
+    }
+
+    pub fn anonymity_metrics_instruction(&self, epochs: u8) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(self.tree_account, false),
+                AccountMeta::new_readonly(anonymity_stats_pda(), false),
+            ],
+            data: instruction_data("anonymity_metrics", &epochs),
+        }
+    }
+
+    /// Leaves out the optional yield config, as a pool that never staked does.
+    pub fn audit_balances_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(self.pool_stats, false),
+                AccountMeta::new_readonly(self.tree_token_account, false),
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+            ],
+            data: discriminator("global:audit_balances").to_vec(),
+        }
+    }
+
+    /// Leaves out the optional yield config, as a pool that never staked does.
+    pub fn check_invariants_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(self.tree_account, false),
+                AccountMeta::new_readonly(self.pool_stats, false),
+                AccountMeta::new_readonly(self.tree_token_account, false),
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+            ],
+            data: discriminator("global:check_invariants").to_vec(),
+        }
+    }
+
+    /// Rescues `source`, owned by the SOL pool, or by the token pool for
+    /// `pool_mint` if given.
+    pub fn rescue_tokens_instruction(&self, source: &Pubkey, recovery: &Pubkey, pool_mint: Option<&Pubkey>) -> Instruction {
+        let token_pool = pool_mint.map_or(PROGRAM_ID, |mint| token_pool_pdas(mint)[1]);
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*source, false),
+                AccountMeta::new(*recovery, false),
+                AccountMeta::new_readonly(self.tree_token_account, false),
+                AccountMeta::new_readonly(token_pool, false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new_readonly(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+                AccountMeta::new(audit_log_pda(), false),
+            ],
+            data: discriminator("global:rescue_tokens").to_vec(),
+        }
+    }
+
+    pub fn export_tree_snapshot_instruction(&self, page: u16) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![AccountMeta::new_readonly(self.tree_account, false)],
+            data: instruction_data("export_tree_snapshot", &page),
+        }
+    }
+
+    pub fn initialize_deposit_queue_instruction(&self, tree_account: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(deposit_queue_pda(tree_account), false),
+                AccountMeta::new_readonly(*tree_account, false),
+                AccountMeta::new(self.authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: discriminator("global:initialize_deposit_queue").to_vec(),
+        }
+    }
+
+    pub fn process_queue_instruction(&self, tree_account: &Pubkey, payer: &Pubkey, max_batch: u8) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(deposit_queue_pda(tree_account), false),
+                AccountMeta::new(*tree_account, false),
+                AccountMeta::new_readonly(*payer, true),
+            ],
+            data: instruction_data("process_queue", &max_batch),
+        }
+    }
+
+    /// Builds transact for `fixture`, preceded by the compute budget request.
+    pub fn transact_instructions(&self, fixture: &TransactFixture, signer: &Pubkey) -> Vec<Instruction> {
+        let nullifiers = nullifier_pdas(&fixture.proof);
+        let commitments = commitment_pdas(&fixture.proof);
+        let args = TransactArgs {
+            proof: &fixture.proof,
+            ext_amount: fixture.ext_amount,
+            fee: fixture.fee,
+            encrypted_output1: &fixture.encrypted_output1,
+            encrypted_output2: &fixture.encrypted_output2,
+        };
+        let transact = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(self.tree_account, false),
+                AccountMeta::new(nullifiers[0], false),
+                AccountMeta::new(nullifiers[1], false),
+                AccountMeta::new_readonly(nullifiers[2], false),
+                AccountMeta::new_readonly(nullifiers[3], false),
+                AccountMeta::new(commitments[0], false),
+                AccountMeta::new(commitments[1], false),
+                AccountMeta::new(fixture.recipient, false),
+                AccountMeta::new(fixture.fee_recipient, false),
+                AccountMeta::new(self.tree_token_account, false),
+                AccountMeta::new_readonly(self.global_config, false),
+                AccountMeta::new(*signer, true),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(self.pool_stats, false),
+            ],
+            data: instruction_data("transact", &args),
+        };
+        vec![ComputeBudgetInstruction::set_compute_unit_limit(TRANSACT_COMPUTE_UNITS), transact]
+    }
+
+    async fn sign(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<Transaction, BanksClientError> {
+        let blockhash = self.context.get_new_latest_blockhash().await?;
+        let mut all_signers: Vec<&Keypair> = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+
+        let mut transaction = Transaction::new_with_payer(instructions, Some(&self.context.payer.pubkey()));
+        transaction.sign(&all_signers, blockhash);
+        Ok(transaction)
+    }
+
+    pub async fn process(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<(), BanksClientError> {
+        let transaction = self.sign(instructions, signers).await?;
+        self.context.banks_client.process_transaction(transaction).await
+    }
+
+    /// Like `process`, but keeps the logs so compute units can be read back.
+    pub async fn process_with_report(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<ExecutionReport, BanksClientError> {
+        let transaction = self.sign(instructions, signers).await?;
+        let outcome = self
+            .context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await?;
+        outcome.result?;
+        let metadata = outcome.metadata.expect("processed transactions carry metadata");
+        Ok(ExecutionReport {
+            logs: metadata.log_messages,
+            compute_units: metadata.compute_units_consumed,
+            return_data: metadata.return_data.map(|data| data.data).unwrap_or_default(),
+        })
+    }
+
+    /// Moves the bank forward by `slots`, e.g. past a timelock.
+    pub async fn advance_slots(&mut self, slots: u64) {
+        let slot = self.context.banks_client.get_root_slot().await.expect("failed to fetch slot");
+        self.context.warp_to_slot(slot + slots).expect("failed to warp");
+    }
+
+    /// Creates a keypair holding `lamports`, e.g. a depositor or relayer.
+    pub async fn funded_keypair(&mut self, lamports: u64) -> Keypair {
+        let keypair = Keypair::new();
+        self.transfer(&keypair.pubkey(), lamports).await;
+        keypair
+    }
+
+    pub async fn transfer(&mut self, to: &Pubkey, lamports: u64) {
+        let instruction = system_instruction::transfer(&self.context.payer.pubkey(), to, lamports);
+        self.process(&[instruction], &[]).await.expect("transfer failed");
+    }
+
+    /// Creates an SPL mint with the test payer as mint authority.
+    pub async fn create_mint(&mut self, decimals: u8) -> Pubkey {
+        let mint = Keypair::new();
+        let rent = self.context.banks_client.get_rent().await.expect("failed to fetch rent");
+        let create = system_instruction::create_account(
+            &self.context.payer.pubkey(),
+            &mint.pubkey(),
+            rent.minimum_balance(MINT_LEN),
+            MINT_LEN as u64,
+            &TOKEN_PROGRAM_ID,
+        );
+        // InitializeMint2: decimals, mint authority, no freeze authority
+        let mut data = vec![20, decimals];
+        data.extend_from_slice(self.context.payer.pubkey().as_ref());
+        data.push(0);
+        let initialize = Instruction {
+            program_id: TOKEN_PROGRAM_ID,
+            accounts: vec![AccountMeta::new(mint.pubkey(), false)],
+            data,
+        };
+        self.process(&[create, initialize], &[&mint]).await.expect("create mint failed");
+        mint.pubkey()
+    }
+
+    /// Creates a token account for `mint` owned by `owner`, which may be a PDA.
+    pub async fn create_token_account(&mut self, mint: &Pubkey, owner: &Pubkey) -> Pubkey {
+        let account = Keypair::new();
+        let rent = self.context.banks_client.get_rent().await.expect("failed to fetch rent");
+        let create = system_instruction::create_account(
+            &self.context.payer.pubkey(),
+            &account.pubkey(),
+            rent.minimum_balance(TOKEN_ACCOUNT_LEN),
+            TOKEN_ACCOUNT_LEN as u64,
+            &TOKEN_PROGRAM_ID,
+        );
+        // InitializeAccount3: owner
+        let mut data = vec![18];
+        data.extend_from_slice(owner.as_ref());
+        let initialize = Instruction {
+            program_id: TOKEN_PROGRAM_ID,
+            accounts: vec![AccountMeta::new(account.pubkey(), false), AccountMeta::new_readonly(*mint, false)],
+            data,
+        };
+        self.process(&[create, initialize], &[&account]).await.expect("create token account failed");
+        account.pubkey()
+    }
+
+    /// Mints `amount` of a mint made by `create_mint` into `account`.
+    pub async fn mint_to(&mut self, mint: &Pubkey, account: &Pubkey, amount: u64) {
+        // MintTo: amount
+        let mut data = vec![7];
+        data.extend_from_slice(&amount.to_le_bytes());
+        let instruction = Instruction {
+            program_id: TOKEN_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*mint, false),
+                AccountMeta::new(*account, false),
+                AccountMeta::new_readonly(self.context.payer.pubkey(), true),
+            ],
+            data,
+        };
+        self.process(&[instruction], &[]).await.expect("mint_to failed");
+    }
+
+    pub async fn token_balance(&mut self, account: &Pubkey) -> u64 {
+        let data = self.account_data(account).await;
+        u64::from_le_bytes(data[64..72].try_into().unwrap())
+    }
+
+    pub async fn lamports(&mut self, address: &Pubkey) -> u64 {
+        self.context
+            .banks_client
+            .get_balance(*address)
+            .await
+            .expect("failed to fetch balance")
+    }
+
+    pub async fn account_exists(&mut self, address: &Pubkey) -> bool {
+        self.context
+            .banks_client
+            .get_account(*address)
+            .await
+            .expect("failed to fetch account")
+            .is_some()
+    }
+
+    async fn account_data(&mut self, address: &Pubkey) -> Vec<u8> {
+        self.context
+            .banks_client
+            .get_account(*address)
+            .await
+            .expect("failed to fetch account")
+            .unwrap_or_else(|| panic!("account {} does not exist", address))
+            .data
+    }
+
+    pub async fn tree(&mut self) -> TreeState {
+        self.tree_at(&self.tree_account.clone()).await
+    }
+
+    /// Any pool's tree, e.g. a token pool's from `token_pool_pdas`.
+    pub async fn tree_at(&mut self, tree_account: &Pubkey) -> TreeState {
+        let data = self.account_data(tree_account).await;
+        assert_eq!(data[..8], discriminator("account:MerkleTreeAccount"));
+        TreeState::decode(&data)
+    }
+
+    /// Layout version of the tree account and its data length.
+    pub async fn tree_version(&mut self) -> (u8, usize) {
+        let data = self.account_data(&self.tree_account.clone()).await;
+        let version = if data.len() == MERKLE_TREE_ACCOUNT_V0_LEN { 0 } else { data[MERKLE_TREE_ACCOUNT_V0_LEN] };
+        (version, data.len())
+    }
+
+    /// Creation slot of each root history entry, from the v2 layout; 0 where
+    /// unknown.
+    pub async fn tree_root_slots(&mut self) -> Vec<u64> {
+        let data = self.account_data(&self.tree_account.clone()).await;
+        let offset = MERKLE_TREE_ACCOUNT_V0_LEN + 8;
+        data[offset..offset + 8 * ROOT_HISTORY_SIZE]
+            .chunks(8)
+            .map(|slot| u64::from_le_bytes(slot.try_into().unwrap()))
+            .collect()
+    }
+
+    /// The `TreeStorage` the tree links to from the v3 layout, if split.
+    pub async fn tree_storage_link(&mut self) -> Option<Pubkey> {
+        let data = self.account_data(&self.tree_account.clone()).await;
+        let offset = MERKLE_TREE_ACCOUNT_V0_LEN + 8 + 8 * ROOT_HISTORY_SIZE;
+        let key = Pubkey::new_from_array(data.get(offset..offset + 32)?.try_into().unwrap());
+        (key != Pubkey::default()).then_some(key)
+    }
+
+    pub async fn tree_storage(&mut self) -> TreeStorage {
+        let data = self.account_data(&tree_storage_pda(&self.tree_account.clone())).await;
+        assert_eq!(data[..8], discriminator("account:TreeStorage"));
+        TreeStorage::deserialize(&mut &data[8..]).expect("failed to decode tree storage")
+    }
+
+    pub async fn leaf_page(&mut self, page_index: u64) -> LeafPage {
+        let data = self.account_data(&leaf_page_pda(&self.tree_account.clone(), page_index)).await;
+        assert_eq!(data[..8], discriminator("account:LeafPage"));
+        LeafPage::deserialize(&mut &data[8..]).expect("failed to decode leaf page")
+    }
+
+    pub async fn root_checkpoints(&mut self) -> RootCheckpoints {
+        let data = self.account_data(&root_checkpoints_pda(&self.tree_account.clone())).await;
+        assert_eq!(data[..8], discriminator("account:RootCheckpoints"));
+        let mut rest = &data[8..];
+        let header = RootCheckpointsHeader::deserialize(&mut rest).expect("failed to decode root checkpoints");
+        let checkpoints = rest
+            .chunks(48)
+            .map(|mut entry| RootCheckpoint::deserialize(&mut entry).expect("failed to decode root checkpoint"))
+            .collect();
+        RootCheckpoints { header, checkpoints }
+    }
+
+    pub async fn pool_stats(&mut self) -> PoolStats {
+        let data = self.account_data(&self.pool_stats.clone()).await;
+        assert_eq!(data[..8], discriminator("account:PoolStats"));
+        PoolStats::deserialize(&mut &data[8..]).expect("failed to decode pool stats")
+    }
+
+    pub async fn circuit_breaker(&mut self) -> CircuitBreaker {
+        let data = self.account_data(&circuit_breaker_pda()).await;
+        assert_eq!(data[..8], discriminator("account:CircuitBreaker"));
+        CircuitBreaker::deserialize(&mut &data[8..]).expect("failed to decode circuit breaker")
+    }
+
+    pub async fn pool_freeze(&mut self) -> PoolFreeze {
+        let data = self.account_data(&pool_freeze_pda()).await;
+        assert_eq!(data[..8], discriminator("account:PoolFreeze"));
+        PoolFreeze::deserialize(&mut &data[8..]).expect("failed to decode pool freeze")
+    }
+
+    pub async fn pool_registry(&mut self) -> PoolRegistry {
+        let data = self.account_data(&pool_registry_pda()).await;
+        assert_eq!(data[..8], discriminator("account:PoolRegistry"));
+        PoolRegistry::deserialize(&mut &data[8..]).expect("failed to decode pool registry")
+    }
+
+    pub async fn pool_factory(&mut self) -> PoolFactory {
+        let data = self.account_data(&pool_factory_pda()).await;
+        assert_eq!(data[..8], discriminator("account:PoolFactory"));
+        PoolFactory::deserialize(&mut &data[8..]).expect("failed to decode pool factory")
+    }
+
+    pub async fn deposit_limits(&mut self, tree_account: &Pubkey) -> DepositLimits {
+        let data = self.account_data(&deposit_limits_pda(tree_account)).await;
+        assert_eq!(data[..8], discriminator("account:DepositLimits"));
+        DepositLimits::deserialize(&mut &data[8..]).expect("failed to decode deposit limits")
+    }
+
+    pub async fn deposit_queue(&mut self, tree_account: &Pubkey) -> DepositQueue {
+        let data = self.account_data(&deposit_queue_pda(tree_account)).await;
+        assert_eq!(data[..8], discriminator("account:DepositQueue"));
+        DepositQueue::deserialize(&mut &data[8..]).expect("failed to decode deposit queue")
+    }
+
+    pub async fn fee_recipient_config(&mut self) -> FeeRecipientConfig {
+        let data = self.account_data(&fee_recipient_config_pda()).await;
+        assert_eq!(data[..8], discriminator("account:FeeRecipientConfig"));
+        FeeRecipientConfig::deserialize(&mut &data[8..]).expect("failed to decode fee recipient config")
+    }
+
+    pub async fn fee_schedule(&mut self, mint: &Pubkey) -> FeeSchedule {
+        let data = self.account_data(&fee_schedule_pda(mint)).await;
+        assert_eq!(data[..8], discriminator("account:FeeSchedule"));
+        FeeSchedule::deserialize(&mut &data[8..]).expect("failed to decode fee schedule")
+    }
+
+    pub async fn fee_exemptions(&mut self) -> FeeExemptions {
+        let data = self.account_data(&fee_exemptions_pda()).await;
+        assert_eq!(data[..8], discriminator("account:FeeExemptions"));
+        FeeExemptions::deserialize(&mut &data[8..]).expect("failed to decode fee exemptions")
+    }
+
+    pub async fn relayer_account(&mut self, relayer: &Pubkey) -> RelayerAccount {
+        let data = self.account_data(&relayer_pda(relayer)).await;
+        assert_eq!(data[..8], discriminator("account:RelayerAccount"));
+        RelayerAccount::deserialize(&mut &data[8..]).expect("failed to decode relayer account")
+    }
+
+    pub async fn submission_commitment(&mut self, commitment: [u8; 32]) -> SubmissionCommitment {
+        let data = self.account_data(&submission_commitment_pda(&commitment)).await;
+        assert_eq!(data[..8], discriminator("account:SubmissionCommitment"));
+        SubmissionCommitment::deserialize(&mut &data[8..]).expect("failed to decode submission commitment")
+    }
+
+    pub async fn nullifier_seed_config(&mut self) -> NullifierSeedConfig {
+        let data = self.account_data(&nullifier_seed_config_pda()).await;
+        assert_eq!(data[..8], discriminator("account:NullifierSeedConfig"));
+        NullifierSeedConfig::deserialize(&mut &data[8..]).expect("failed to decode nullifier seed config")
+    }
+
+    pub async fn nullifier_bloom_filter(&mut self) -> NullifierBloomFilter {
+        let data = self.account_data(&nullifier_bloom_pda(&self.tree_account.clone())).await;
+        assert_eq!(data[..8], discriminator("account:NullifierBloomFilter"));
+        NullifierBloomFilter::deserialize(&mut &data[8..]).expect("failed to decode nullifier bloom filter")
+    }
+
+    pub async fn root_history(&mut self) -> RootHistory {
+        let data = self.account_data(&root_history_pda(&self.tree_account.clone())).await;
+        assert_eq!(data[..8], discriminator("account:RootHistory"));
+        RootHistory::deserialize(&mut &data[8..]).expect("failed to decode root history")
+    }
+
+    pub async fn root_policy(&mut self) -> RootPolicy {
+        let data = self.account_data(&root_policy_pda()).await;
+        assert_eq!(data[..8], discriminator("account:RootPolicy"));
+        RootPolicy::deserialize(&mut &data[8..]).expect("failed to decode root policy")
+    }
+
+    pub async fn role_config(&mut self) -> RoleConfig {
+        let data = self.account_data(&role_config_pda()).await;
+        assert_eq!(data[..8], discriminator("account:RoleConfig"));
+        RoleConfig::deserialize(&mut &data[8..]).expect("failed to decode role config")
+    }
+
+    pub async fn fee_vault(&mut self) -> FeeVault {
+        let data = self.account_data(&fee_vault_pda()).await;
+        assert_eq!(data[..8], discriminator("account:FeeVault"));
+        FeeVault::deserialize(&mut &data[8..]).expect("failed to decode fee vault")
+    }
+
+    pub async fn circuit_version_config(&mut self) -> CircuitVersionConfig {
+        let data = self.account_data(&circuit_version_config_pda()).await;
+        assert_eq!(data[..8], discriminator("account:CircuitVersionConfig"));
+        CircuitVersionConfig::deserialize(&mut &data[8..]).expect("failed to decode circuit version config")
+    }
+
+    pub async fn verifying_key_record(&mut self) -> VerifyingKeyRecord {
+        let data = self.account_data(&verifying_key_record_pda()).await;
+        assert_eq!(data[..8], discriminator("account:VerifyingKeyRecord"));
+        VerifyingKeyRecord::deserialize(&mut &data[8..]).expect("failed to decode verifying key record")
+    }
+
+    pub async fn vk_rotation(&mut self) -> VerifyingKeyRotation {
+        let data = self.account_data(&vk_rotation_pda()).await;
+        assert_eq!(data[..8], discriminator("account:VerifyingKeyRotation"));
+        VerifyingKeyRotation::deserialize(&mut &data[8..]).expect("failed to decode verifying key rotation")
+    }
+
+    pub async fn audit_log(&mut self) -> AuditLog {
+        let data = self.account_data(&audit_log_pda()).await;
+        assert_eq!(data[..8], discriminator("account:AuditLog"));
+        AuditLog::deserialize(&mut &data[8..]).expect("failed to decode audit log")
+    }
+
+    pub async fn encrypted_output_config(&mut self) -> EncryptedOutputConfig {
+        let data = self.account_data(&encrypted_output_config_pda()).await;
+        assert_eq!(data[..8], discriminator("account:EncryptedOutputConfig"));
+        EncryptedOutputConfig::deserialize(&mut &data[8..]).expect("failed to decode encrypted output config")
+    }
+
+    pub async fn output_storage_config(&mut self) -> OutputStorageConfig {
+        let data = self.account_data(&output_storage_config_pda()).await;
+        assert_eq!(data[..8], discriminator("account:OutputStorageConfig"));
+        OutputStorageConfig::deserialize(&mut &data[8..]).expect("failed to decode output storage config")
+    }
+
+    pub async fn token_pool(&mut self, mint: &Pubkey) -> TokenPool {
+        let data = self.account_data(&token_pool_pdas(mint)[1]).await;
+        assert_eq!(data[..8], discriminator("account:TokenPool"));
+        TokenPool::deserialize(&mut &data[8..]).expect("failed to decode token pool")
+    }
+
+    /// The raw account at `address`, for layouts the harness doesn't mirror.
+    pub async fn account(&mut self, address: &Pubkey) -> Option<Account> {
+        self.context
+            .banks_client
+            .get_account(*address)
+            .await
+            .expect("failed to fetch account")
+    }
+
+    pub async fn global_config(&mut self) -> GlobalConfig {
+        let data = self.account_data(&self.global_config.clone()).await;
+        assert_eq!(data[..8], discriminator("account:GlobalConfig"));
+        GlobalConfig::deserialize(&mut &data[8..]).expect("failed to decode global config")
+    }
+}
@@ -0,0 +1,47 @@
+use borsh::BorshDeserialize;
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, signature::Signer};
+use zkcash_program_tests::{AnonymityMetrics, PoolHarness};
+
+async fn metrics(pool: &mut PoolHarness, epochs: u8) -> Result<AnonymityMetrics, ()> {
+    let instruction = pool.anonymity_metrics_instruction(epochs);
+    let report = pool.process_with_report(&[instruction], &[]).await.map_err(|_| ())?;
+    Ok(AnonymityMetrics::try_from_slice(&report.return_data).expect("failed to decode anonymity metrics"))
+}
+
+#[tokio::test]
+async fn test_metrics_for_an_empty_pool() {
+    let mut pool = PoolHarness::start().await;
+    let payer = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+    let initialize = pool.initialize_anonymity_stats_instruction(&payer.pubkey());
+    pool.process(&[initialize], &[&payer]).await.expect("initialize_anonymity_stats failed");
+
+    let metrics = metrics(&mut pool, 4).await.expect("anonymity_metrics failed");
+    assert_eq!(
+        metrics,
+        AnonymityMetrics {
+            leaf_count: 0,
+            capacity: 1 << 26,
+            occupancy_bps: 0,
+            unspent_upper_bound: 0,
+            recent_deposits: 0,
+            epochs: 4,
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_metrics_window_is_bounded() {
+    let mut pool = PoolHarness::start().await;
+    let payer = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+    let initialize = pool.initialize_anonymity_stats_instruction(&payer.pubkey());
+    pool.process(&[initialize], &[&payer]).await.expect("initialize_anonymity_stats failed");
+
+    assert!(metrics(&mut pool, 0).await.is_err());
+    assert!(metrics(&mut pool, 9).await.is_err());
+}
+
+#[tokio::test]
+async fn test_metrics_need_the_stats_account() {
+    let mut pool = PoolHarness::start().await;
+    assert!(metrics(&mut pool, 1).await.is_err());
+}
@@ -0,0 +1,84 @@
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, signature::Signer};
+use zkcash_program_tests::{
+    circuit_breaker_pda, encrypted_output_config_pda, pool_freeze_pda, AdminAction, PoolHarness, PoolStatus,
+    FEE_RECIPIENT_ACCOUNT, MIN_POOL_CREATION_FEE,
+};
+
+#[tokio::test]
+async fn test_admin_actions_are_logged_in_order() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let tree_account = pool.tree_account;
+    assert!(pool.audit_log().await.entries.is_empty());
+
+    let initialize = pool.initialize_pool_registry_instruction();
+    let pause = pool.set_pool_status_instruction(&tree_account, PoolStatus::Paused);
+    let set_fee_recipient = pool.set_fee_recipient_instruction(&FEE_RECIPIENT_ACCOUNT);
+    pool.process(&[initialize, pause, set_fee_recipient], &[&authority])
+        .await
+        .expect("admin actions failed");
+
+    let log = pool.audit_log().await;
+    assert_eq!(log.next_sequence, 2);
+    let actions: Vec<_> = log.entries.iter().map(|entry| (entry.sequence, entry.action, entry.target)).collect();
+    assert_eq!(
+        actions,
+        [
+            (0, AdminAction::PausePool, tree_account),
+            (1, AdminAction::SetFeeRecipient, FEE_RECIPIENT_ACCOUNT),
+        ]
+    );
+    assert!(log.entries.iter().all(|entry| entry.actor == authority.pubkey()));
+}
+
+#[tokio::test]
+async fn test_config_and_guardian_actions_are_logged() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let tree_account = pool.tree_account;
+    let treasury = Pubkey::new_unique();
+
+    // The authority starts out as the guardian
+    let initialize = pool.initialize_circuit_breaker_instruction(1000, 216_000);
+    let update = pool.update_circuit_breaker_instruction(&authority.pubkey(), Some(500), None);
+    let freeze = pool.set_pool_freeze_instruction(&authority.pubkey(), true, false);
+    let resume = pool.resume_withdrawals_instruction(&authority.pubkey());
+    let configure_factory = pool.configure_pool_factory_instruction(&treasury, MIN_POOL_CREATION_FEE);
+    let set_max_len = pool.set_max_encrypted_output_len_instruction(200);
+    pool.process(&[initialize, update, freeze, resume, configure_factory, set_max_len], &[&authority])
+        .await
+        .expect("config actions failed");
+    pool.set_deposit_limits(&tree_account, LAMPORTS_PER_SOL, 10 * LAMPORTS_PER_SOL)
+        .await
+        .expect("set_deposit_limits failed");
+
+    let log = pool.audit_log().await;
+    let actions: Vec<_> = log.entries.iter().map(|entry| (entry.action, entry.target)).collect();
+    assert_eq!(
+        actions,
+        [
+            (AdminAction::UpdateCircuitBreaker, circuit_breaker_pda()),
+            (AdminAction::SetPoolFreeze, pool_freeze_pda()),
+            (AdminAction::ResumeWithdrawals, circuit_breaker_pda()),
+            (AdminAction::ConfigurePoolFactory, treasury),
+            (AdminAction::SetMaxEncryptedOutputLen, encrypted_output_config_pda()),
+            (AdminAction::QueueDepositLimits, tree_account),
+            (AdminAction::SetDepositLimits, tree_account),
+        ]
+    );
+    assert!(log.entries.iter().all(|entry| entry.actor == authority.pubkey()));
+}
+
+#[tokio::test]
+async fn test_rejected_actions_are_not_logged() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+
+    // Not a registered pool
+    let initialize = pool.initialize_pool_registry_instruction();
+    pool.process(&[initialize], &[&authority]).await.expect("initialize_pool_registry failed");
+    let retire = pool.set_pool_status_instruction(&Pubkey::new_unique(), PoolStatus::Retired);
+    assert!(pool.process(&[retire], &[&authority]).await.is_err());
+
+    assert_eq!(pool.audit_log().await.next_sequence, 0);
+}
@@ -0,0 +1,58 @@
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, signature::Signer};
+use zkcash_program_tests::{pending_authority_pda, ConfigUpdate, PoolHarness};
+
+fn fee_update() -> ConfigUpdate {
+    ConfigUpdate { withdrawal_fee_rate: Some(200), ..ConfigUpdate::default() }
+}
+
+#[tokio::test]
+async fn test_authority_transfer_moves_every_stored_authority() {
+    let mut pool = PoolHarness::start().await;
+    let old_authority = pool.authority.insecure_clone();
+    // Stands in for a multisig vault PDA, which signs through invoke_signed
+    let vault = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+
+    let propose = pool.propose_authority_instruction(&vault.pubkey());
+    pool.process(&[propose], &[&old_authority]).await.expect("propose_authority failed");
+    // Nothing changes until the new authority accepts
+    assert_eq!(pool.global_config().await.authority, old_authority.pubkey());
+
+    let accept = pool.accept_authority_instruction(&vault.pubkey());
+    pool.process(&[accept], &[&vault]).await.expect("accept_authority failed");
+
+    assert_eq!(pool.global_config().await.authority, vault.pubkey());
+    assert_eq!(pool.tree().await.authority, vault.pubkey());
+    assert!(!pool.account_exists(&pending_authority_pda()).await);
+
+    // The old key lost its admin rights, the vault has them
+    let queue = pool.queue_config_update_instruction(&fee_update());
+    assert!(pool.process(&[queue], &[&old_authority]).await.is_err());
+    pool.authority = vault.insecure_clone();
+    let queue = pool.queue_config_update_instruction(&fee_update());
+    pool.process(&[queue], &[&vault]).await.expect("queue_config_update by the new authority failed");
+}
+
+#[tokio::test]
+async fn test_only_the_proposed_authority_can_accept() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let vault = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+    let outsider = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+
+    let propose = pool.propose_authority_instruction(&vault.pubkey());
+    pool.process(&[propose], &[&authority]).await.expect("propose_authority failed");
+
+    let accept = pool.accept_authority_instruction(&outsider.pubkey());
+    assert!(pool.process(&[accept], &[&outsider]).await.is_err());
+    assert_eq!(pool.global_config().await.authority, authority.pubkey());
+}
+
+#[tokio::test]
+async fn test_only_the_authority_can_propose() {
+    let mut pool = PoolHarness::start().await;
+    let outsider = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+
+    pool.authority = outsider.insecure_clone();
+    let propose = pool.propose_authority_instruction(&outsider.pubkey());
+    assert!(pool.process(&[propose], &[&outsider]).await.is_err());
+}
@@ -0,0 +1,37 @@
+use borsh::BorshDeserialize;
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, signature::Signer};
+use zkcash_program_tests::{BalanceAudit, PoolHarness, TransactFixture};
+
+async fn audit(pool: &mut PoolHarness) -> BalanceAudit {
+    let instruction = pool.audit_balances_instruction();
+    let report = pool.process_with_report(&[instruction], &[]).await.expect("audit_balances failed");
+    BalanceAudit::try_from_slice(&report.return_data).expect("failed to decode balance audit")
+}
+
+#[tokio::test]
+async fn test_audit_reconciles_after_deposit() {
+    let mut pool = PoolHarness::start().await;
+    assert_eq!(audit(&mut pool).await.drift, 0);
+
+    let deposit = TransactFixture::load("deposit");
+    let depositor = pool.funded_keypair(2 * LAMPORTS_PER_SOL).await;
+    let instructions = pool.transact_instructions(&deposit, &depositor.pubkey());
+    pool.process(&instructions, &[&depositor]).await.expect("deposit failed");
+
+    let result = audit(&mut pool).await;
+    assert_eq!(result.expected_lamports, deposit.ext_amount as u64 - deposit.fee);
+    assert_eq!(result.actual_lamports, result.expected_lamports);
+    assert_eq!(result.drift, 0);
+}
+
+#[tokio::test]
+async fn test_audit_reports_surplus_sent_directly() {
+    let mut pool = PoolHarness::start().await;
+    let sender = pool.funded_keypair(2 * LAMPORTS_PER_SOL).await;
+    let tree_token_account = pool.tree_token_account;
+    let transfer = solana_system_interface::instruction::transfer(&sender.pubkey(), &tree_token_account, 1_000);
+    pool.process(&[transfer], &[&sender]).await.expect("transfer failed");
+
+    let result = audit(&mut pool).await;
+    assert_eq!(result.drift, 1_000);
+}
@@ -0,0 +1,112 @@
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, signature::Signer};
+use zkcash_program_tests::{AdminAction, ConfigUpdate, PoolHarness};
+
+#[tokio::test]
+async fn test_initialize_circuit_breaker_makes_authority_guardian() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+
+    let initialize = pool.initialize_circuit_breaker_instruction(1000, 216_000);
+    pool.process(&[initialize], &[&authority]).await.expect("initialize_circuit_breaker failed");
+
+    let breaker = pool.circuit_breaker().await;
+    assert_eq!(breaker.guardian, authority.pubkey());
+    assert_eq!(breaker.max_outflow_bps, 1000);
+    assert_eq!(breaker.window_slots, 216_000);
+    assert!(!breaker.withdrawals_paused);
+}
+
+#[tokio::test]
+async fn test_initialize_circuit_breaker_rejects_bad_config() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+
+    let initialize = pool.initialize_circuit_breaker_instruction(10001, 216_000);
+    assert!(pool.process(&[initialize], &[&authority]).await.is_err());
+    let initialize = pool.initialize_circuit_breaker_instruction(1000, 0);
+    assert!(pool.process(&[initialize], &[&authority]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_is_guardian_only() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let initialize = pool.initialize_circuit_breaker_instruction(1000, 216_000);
+    pool.process(&[initialize], &[&authority]).await.expect("initialize_circuit_breaker failed");
+
+    let outsider = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+    let update = pool.update_circuit_breaker_instruction(&outsider.pubkey(), Some(10000), None);
+    assert!(pool.process(&[update], &[&outsider]).await.is_err());
+    let resume = pool.resume_withdrawals_instruction(&outsider.pubkey());
+    assert!(pool.process(&[resume], &[&outsider]).await.is_err());
+
+    let update = pool.update_circuit_breaker_instruction(&authority.pubkey(), Some(500), None);
+    pool.process(&[update], &[&authority]).await.expect("update_circuit_breaker failed");
+    let resume = pool.resume_withdrawals_instruction(&authority.pubkey());
+    pool.process(&[resume], &[&authority]).await.expect("resume_withdrawals failed");
+
+    let breaker = pool.circuit_breaker().await;
+    assert_eq!(breaker.max_outflow_bps, 500);
+    assert_eq!(breaker.window_slots, 216_000);
+}
+
+#[tokio::test]
+async fn test_guardian_freezes_each_direction() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let initialize = pool.initialize_circuit_breaker_instruction(1000, 216_000);
+    pool.process(&[initialize], &[&authority]).await.expect("initialize_circuit_breaker failed");
+
+    let freeze = pool.set_pool_freeze_instruction(&authority.pubkey(), true, false);
+    pool.process(&[freeze], &[&authority]).await.expect("set_pool_freeze failed");
+    let state = pool.pool_freeze().await;
+    assert!(state.withdrawals_frozen);
+    assert!(!state.deposits_frozen);
+
+    let freeze = pool.set_pool_freeze_instruction(&authority.pubkey(), false, true);
+    pool.process(&[freeze], &[&authority]).await.expect("set_pool_freeze failed");
+    let state = pool.pool_freeze().await;
+    assert!(!state.withdrawals_frozen);
+    assert!(state.deposits_frozen);
+
+    let outsider = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+    let freeze = pool.set_pool_freeze_instruction(&outsider.pubkey(), false, false);
+    assert!(pool.process(&[freeze], &[&outsider]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_guardian_role_moves_off_the_authority() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let initialize = pool.initialize_circuit_breaker_instruction(1000, 216_000);
+    pool.process(&[initialize], &[&authority]).await.expect("initialize_circuit_breaker failed");
+
+    let guardian = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+    let set_guardian = pool.set_guardian_instruction(&guardian.pubkey());
+    pool.process(&[set_guardian], &[&authority]).await.expect("set_guardian failed");
+    assert_eq!(pool.circuit_breaker().await.guardian, guardian.pubkey());
+    let entry = pool.audit_log().await.entries.pop().unwrap();
+    assert_eq!((entry.action, entry.target), (AdminAction::SetGuardian, guardian.pubkey()));
+
+    // The guardian halts and resumes flows; the authority no longer can
+    let freeze = pool.set_pool_freeze_instruction(&guardian.pubkey(), true, true);
+    pool.process(&[freeze], &[&guardian]).await.expect("set_pool_freeze failed");
+    let resume = pool.resume_withdrawals_instruction(&guardian.pubkey());
+    pool.process(&[resume], &[&guardian]).await.expect("resume_withdrawals failed");
+    let freeze = pool.set_pool_freeze_instruction(&authority.pubkey(), false, false);
+    assert!(pool.process(&[freeze], &[&authority]).await.is_err());
+
+    // ...but holds none of the authority's powers
+    pool.authority = guardian.insecure_clone();
+    let queue = pool.queue_config_update_instruction(&ConfigUpdate {
+        withdrawal_fee_rate: Some(0),
+        ..ConfigUpdate::default()
+    });
+    assert!(pool.process(&[queue], &[&guardian]).await.is_err());
+    let set_guardian = pool.set_guardian_instruction(&guardian.pubkey());
+    assert!(pool.process(&[set_guardian], &[&guardian]).await.is_err());
+
+    pool.authority = authority.insecure_clone();
+    let clear = pool.set_guardian_instruction(&Pubkey::default());
+    assert!(pool.process(&[clear], &[&authority]).await.is_err());
+}
@@ -0,0 +1,32 @@
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use zkcash_program_tests::{circuit_version_config_pda, AdminAction, PoolHarness};
+
+#[tokio::test]
+async fn test_authority_sets_min_circuit_version() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+
+    let set = pool.set_min_circuit_version_instruction(1);
+    pool.process(&[set], &[&authority]).await.expect("set_min_circuit_version failed");
+    assert_eq!(pool.circuit_version_config().await.min_version, 1);
+    let entry = pool.audit_log().await.entries.pop().unwrap();
+    assert_eq!(
+        (entry.action, entry.target),
+        (AdminAction::SetMinCircuitVersion, circuit_version_config_pda())
+    );
+
+    // No verifying key for v2 yet, so it can't become the minimum
+    let unsupported = pool.set_min_circuit_version_instruction(2);
+    assert!(pool.process(&[unsupported], &[&authority]).await.is_err());
+    let zero = pool.set_min_circuit_version_instruction(0);
+    assert!(pool.process(&[zero], &[&authority]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_only_authority_sets_min_circuit_version() {
+    let mut pool = PoolHarness::start().await;
+    let other = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+    pool.authority = other.insecure_clone();
+    let set = pool.set_min_circuit_version_instruction(1);
+    assert!(pool.process(&[set], &[&other]).await.is_err());
+}
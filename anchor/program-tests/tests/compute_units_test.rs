@@ -0,0 +1,51 @@
+//! CU regression checks. Section measurements need the program built with
+//! `anchor build -- --features localnet,cu-bench`; whole-instruction numbers
+//! work with any localnet build.
+
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, signature::Signer};
+use zkcash_program_tests::compute_units::assert_within_baseline;
+use zkcash_program_tests::{ExecutionReport, PoolHarness, TransactFixture};
+
+fn assert_transact_within_baseline(name: &str, report: &ExecutionReport) {
+    assert_within_baseline(&format!("{}/transact", name), report.program_units());
+    if let Some(units) = report.section_units("verify_proof") {
+        assert_within_baseline(&format!("{}/verify_proof", name), units);
+    }
+    if let Some(units) = report.section_units("append") {
+        assert_within_baseline(&format!("{}/merkle_appends", name), units);
+    }
+}
+
+#[tokio::test]
+async fn test_initialize_compute_units() {
+    let mut pool = PoolHarness::start_uninitialized().await;
+    let initialize = pool.initialize_instruction();
+    let authority = pool.authority.insecure_clone();
+    let report = pool
+        .process_with_report(&[initialize], &[&authority])
+        .await
+        .expect("initialize failed");
+    assert_within_baseline("initialize", report.program_units());
+}
+
+#[tokio::test]
+async fn test_transact_compute_units() {
+    let mut pool = PoolHarness::start().await;
+    let deposit = TransactFixture::load("deposit");
+    let withdraw = TransactFixture::load("withdraw");
+    let user = pool.funded_keypair(2 * LAMPORTS_PER_SOL).await;
+
+    let instructions = pool.transact_instructions(&deposit, &user.pubkey());
+    let report = pool
+        .process_with_report(&instructions, &[&user])
+        .await
+        .expect("deposit failed");
+    assert_transact_within_baseline("deposit", &report);
+
+    let instructions = pool.transact_instructions(&withdraw, &user.pubkey());
+    let report = pool
+        .process_with_report(&instructions, &[&user])
+        .await
+        .expect("withdraw failed");
+    assert_transact_within_baseline("withdraw", &report);
+}
@@ -0,0 +1,108 @@
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, signature::Signer};
+use zkcash_program_tests::{
+    deposit_limits_pda, pending_deposit_limits_pda, token_pool_pdas, PoolHarness, CONFIG_UPDATE_DELAY_SLOTS,
+    MIN_POOL_CREATION_FEE,
+};
+
+#[tokio::test]
+async fn test_authority_sets_and_updates_limits() {
+    let mut pool = PoolHarness::start().await;
+    let tree_account = pool.tree_account;
+
+    pool.set_deposit_limits(&tree_account, LAMPORTS_PER_SOL, 10 * LAMPORTS_PER_SOL)
+        .await
+        .expect("setting deposit limits failed");
+    let limits = pool.deposit_limits(&tree_account).await;
+    assert_eq!(limits.tree_account, tree_account);
+    assert_eq!((limits.max_per_transaction, limits.max_per_slot), (LAMPORTS_PER_SOL, 10 * LAMPORTS_PER_SOL));
+    assert!(!pool.account_exists(&pending_deposit_limits_pda(&tree_account)).await);
+
+    pool.set_deposit_limits(&tree_account, 2 * LAMPORTS_PER_SOL, 2 * LAMPORTS_PER_SOL)
+        .await
+        .expect("updating deposit limits failed");
+    let limits = pool.deposit_limits(&tree_account).await;
+    assert_eq!((limits.max_per_transaction, limits.max_per_slot), (2 * LAMPORTS_PER_SOL, 2 * LAMPORTS_PER_SOL));
+
+    // A per-slot cap below the per-transaction cap is rejected
+    let authority = pool.authority.insecure_clone();
+    let invalid = pool.queue_deposit_limits_instruction(&tree_account, 2 * LAMPORTS_PER_SOL, LAMPORTS_PER_SOL);
+    assert!(pool.process(&[invalid], &[&authority]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_limits_apply_only_after_delay() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let tree_account = pool.tree_account;
+
+    let queue = pool.queue_deposit_limits_instruction(&tree_account, LAMPORTS_PER_SOL, LAMPORTS_PER_SOL);
+    pool.process(&[queue], &[&authority]).await.expect("queue_deposit_limits failed");
+
+    // Too early: the pool keeps having no limits of its own
+    let execute = pool.execute_deposit_limits_instruction(&tree_account);
+    assert!(pool.process(&[execute], &[]).await.is_err());
+    assert!(!pool.account_exists(&deposit_limits_pda(&tree_account)).await);
+
+    pool.advance_slots(CONFIG_UPDATE_DELAY_SLOTS + 1).await;
+    let execute = pool.execute_deposit_limits_instruction(&tree_account);
+    pool.process(&[execute], &[]).await.expect("execute_deposit_limits failed");
+    assert_eq!(pool.deposit_limits(&tree_account).await.max_per_transaction, LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn test_cancelled_limits_cannot_execute() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let tree_account = pool.tree_account;
+
+    let queue = pool.queue_deposit_limits_instruction(&tree_account, LAMPORTS_PER_SOL, LAMPORTS_PER_SOL);
+    pool.process(&[queue], &[&authority]).await.expect("queue_deposit_limits failed");
+    let cancel = pool.cancel_deposit_limits_instruction(&tree_account);
+    pool.process(&[cancel], &[&authority]).await.expect("cancel_deposit_limits failed");
+
+    pool.advance_slots(CONFIG_UPDATE_DELAY_SLOTS + 1).await;
+    let execute = pool.execute_deposit_limits_instruction(&tree_account);
+    assert!(pool.process(&[execute], &[]).await.is_err());
+    assert!(!pool.account_exists(&deposit_limits_pda(&tree_account)).await);
+}
+
+#[tokio::test]
+async fn test_only_the_tree_authority_sets_limits() {
+    let mut pool = PoolHarness::start().await;
+    let tree_account = pool.tree_account;
+    let outsider = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+
+    pool.authority = outsider.insecure_clone();
+    let queue = pool.queue_deposit_limits_instruction(&tree_account, 1, 1);
+    assert!(pool.process(&[queue], &[&outsider]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_token_pool_limits_are_separate() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let treasury = Pubkey::new_unique();
+    let instructions = [
+        pool.initialize_pool_registry_instruction(),
+        pool.configure_pool_factory_instruction(&treasury, MIN_POOL_CREATION_FEE),
+    ];
+    pool.process(&instructions, &[&authority]).await.expect("factory setup failed");
+
+    let mint = pool.create_mint(6).await;
+    let creator = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+    let initialize = pool.initialize_token_pool_instruction(&mint, &treasury, &creator.pubkey());
+    pool.process(&[initialize], &[&creator]).await.expect("initialize_token_pool failed");
+
+    // 1,000 tokens at 6 decimals
+    let [token_tree, _, _] = token_pool_pdas(&mint);
+    pool.set_deposit_limits(&token_tree, 1_000_000_000, 5_000_000_000)
+        .await
+        .expect("setting token pool limits failed");
+    let sol_tree = pool.tree_account;
+    pool.set_deposit_limits(&sol_tree, LAMPORTS_PER_SOL, LAMPORTS_PER_SOL)
+        .await
+        .expect("setting SOL pool limits failed");
+
+    assert_eq!(pool.deposit_limits(&token_tree).await.max_per_transaction, 1_000_000_000);
+    assert_eq!(pool.deposit_limits(&sol_tree).await.max_per_transaction, LAMPORTS_PER_SOL);
+}
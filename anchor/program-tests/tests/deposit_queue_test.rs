@@ -0,0 +1,35 @@
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, signature::Signer};
+use zkcash_program_tests::PoolHarness;
+
+#[tokio::test]
+async fn test_authority_initializes_deposit_queue() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let tree_account = pool.tree_account;
+
+    let initialize = pool.initialize_deposit_queue_instruction(&tree_account);
+    pool.process(&[initialize], &[&authority]).await.expect("initialize_deposit_queue failed");
+    let queue = pool.deposit_queue(&tree_account).await;
+    assert_eq!(queue.tree_account, tree_account);
+    assert!(queue.pending.is_empty());
+    assert_eq!(queue.total_enqueued, 0);
+
+    // The queue PDA is unique per tree
+    let initialize = pool.initialize_deposit_queue_instruction(&tree_account);
+    assert!(pool.process(&[initialize], &[&authority]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_process_queue_rejects_an_empty_queue() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let tree_account = pool.tree_account;
+    let initialize = pool.initialize_deposit_queue_instruction(&tree_account);
+    pool.process(&[initialize], &[&authority]).await.expect("initialize_deposit_queue failed");
+
+    // Anyone may crank, but there is nothing to insert yet
+    let cranker = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+    let crank = pool.process_queue_instruction(&tree_account, &cranker.pubkey(), 1);
+    assert!(pool.process(&[crank], &[&cranker]).await.is_err());
+    assert_eq!(pool.tree().await.next_index, 0);
+}
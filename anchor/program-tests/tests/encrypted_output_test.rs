@@ -0,0 +1,55 @@
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use zkcash_program_tests::{output_storage_config_pda, AdminAction, OutputStorageMode, PoolHarness};
+
+#[tokio::test]
+async fn test_authority_sets_max_encrypted_output_len() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+
+    let set = pool.set_max_encrypted_output_len_instruction(200);
+    pool.process(&[set], &[&authority]).await.expect("set_max_encrypted_output_len failed");
+    assert_eq!(pool.encrypted_output_config().await.max_len, 200);
+
+    // Below the note ciphertext size or above the cap is rejected
+    for max_len in [135, 257] {
+        let set = pool.set_max_encrypted_output_len_instruction(max_len);
+        assert!(pool.process(&[set], &[&authority]).await.is_err(), "max_len {max_len}");
+    }
+    assert_eq!(pool.encrypted_output_config().await.max_len, 200);
+}
+
+#[tokio::test]
+async fn test_only_the_authority_sets_max_encrypted_output_len() {
+    let mut pool = PoolHarness::start().await;
+    let outsider = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+
+    pool.authority = outsider.insecure_clone();
+    let set = pool.set_max_encrypted_output_len_instruction(200);
+    assert!(pool.process(&[set], &[&outsider]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_authority_switches_to_event_only_outputs() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+
+    let set = pool.set_output_storage_mode_instruction(OutputStorageMode::EventOnly);
+    pool.process(&[set], &[&authority]).await.expect("set_output_storage_mode failed");
+    assert_eq!(pool.output_storage_config().await.mode, OutputStorageMode::EventOnly);
+    let entry = pool.audit_log().await.entries.pop().unwrap();
+    assert_eq!((entry.action, entry.target), (AdminAction::SetOutputStorageMode, output_storage_config_pda()));
+
+    let set = pool.set_output_storage_mode_instruction(OutputStorageMode::Account);
+    pool.process(&[set], &[&authority]).await.expect("set_output_storage_mode failed");
+    assert_eq!(pool.output_storage_config().await.mode, OutputStorageMode::Account);
+}
+
+#[tokio::test]
+async fn test_only_the_authority_sets_output_storage_mode() {
+    let mut pool = PoolHarness::start().await;
+    let outsider = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+
+    pool.authority = outsider.insecure_clone();
+    let set = pool.set_output_storage_mode_instruction(OutputStorageMode::EventOnly);
+    assert!(pool.process(&[set], &[&outsider]).await.is_err());
+}
@@ -0,0 +1,50 @@
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, signature::Signer};
+use zkcash_program_tests::{AdminAction, PoolHarness, CONFIG_UPDATE_DELAY_SLOTS};
+
+#[tokio::test]
+async fn test_authority_sets_fee_exemptions() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let treasury = Pubkey::new_unique();
+
+    pool.set_fee_exemptions(&[treasury]).await.expect("setting fee exemptions failed");
+    assert_eq!(pool.fee_exemptions().await.exempt, [treasury]);
+    let mut entries = pool.audit_log().await.entries;
+    assert_eq!(entries.pop().unwrap().action, AdminAction::SetFeeExemptions);
+    assert_eq!(entries.pop().unwrap().action, AdminAction::QueueFeeExemptions);
+
+    // Duplicates are rejected
+    let duplicate = pool.queue_fee_exemptions_instruction(&[treasury, treasury]);
+    assert!(pool.process(&[duplicate], &[&authority]).await.is_err());
+
+    pool.set_fee_exemptions(&[]).await.expect("clearing fee exemptions failed");
+    assert!(pool.fee_exemptions().await.exempt.is_empty());
+}
+
+#[tokio::test]
+async fn test_fee_exemptions_wait_out_the_timelock() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let treasury = Pubkey::new_unique();
+
+    let queue = pool.queue_fee_exemptions_instruction(&[treasury]);
+    pool.process(&[queue], &[&authority]).await.expect("queue_fee_exemptions failed");
+    let execute = pool.execute_fee_exemptions_instruction();
+    assert!(pool.process(&[execute.clone()], &[]).await.is_err());
+
+    // Cancelling drops the queued list
+    let cancel = pool.cancel_fee_exemptions_instruction();
+    pool.process(&[cancel], &[&authority]).await.expect("cancel_fee_exemptions failed");
+    pool.advance_slots(CONFIG_UPDATE_DELAY_SLOTS + 1).await;
+    assert!(pool.process(&[execute], &[]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_only_the_authority_sets_fee_exemptions() {
+    let mut pool = PoolHarness::start().await;
+    let outsider = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+
+    pool.authority = outsider.insecure_clone();
+    let queue = pool.queue_fee_exemptions_instruction(&[outsider.pubkey()]);
+    assert!(pool.process(&[queue], &[&outsider]).await.is_err());
+}
@@ -0,0 +1,31 @@
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, signature::Signer};
+use zkcash_program_tests::{PoolHarness, FEE_RECIPIENT_ACCOUNT};
+
+#[tokio::test]
+async fn test_authority_sets_fee_recipient() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+
+    let set = pool.set_fee_recipient_instruction(&FEE_RECIPIENT_ACCOUNT);
+    pool.process(&[set], &[&authority]).await.expect("set_fee_recipient failed");
+    assert_eq!(pool.fee_recipient_config().await.fee_recipient, FEE_RECIPIENT_ACCOUNT);
+
+    let new_recipient = Pubkey::new_unique();
+    let update = pool.set_fee_recipient_instruction(&new_recipient);
+    pool.process(&[update], &[&authority]).await.expect("updating the fee recipient failed");
+    assert_eq!(pool.fee_recipient_config().await.fee_recipient, new_recipient);
+
+    // The default pubkey would burn fees
+    let invalid = pool.set_fee_recipient_instruction(&Pubkey::default());
+    assert!(pool.process(&[invalid], &[&authority]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_only_the_authority_sets_fee_recipient() {
+    let mut pool = PoolHarness::start().await;
+    let outsider = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+
+    pool.authority = outsider.insecure_clone();
+    let set = pool.set_fee_recipient_instruction(&outsider.pubkey());
+    assert!(pool.process(&[set], &[&outsider]).await.is_err());
+}
@@ -0,0 +1,139 @@
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, signature::Signer};
+use zkcash_program_tests::{
+    fee_schedule_pda, pending_fee_schedule_pda, AdminAction, FeeTier, PoolHarness, CONFIG_UPDATE_DELAY_SLOTS,
+    MIN_POOL_CREATION_FEE, SOL_ADDRESS,
+};
+
+#[tokio::test]
+async fn test_authority_sets_base_fee() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+
+    pool.set_fee_schedule(&SOL_ADDRESS, 5_000, &[], 0).await.expect("setting the fee schedule failed");
+    assert_eq!(pool.fee_schedule(&SOL_ADDRESS).await.base_fee, 5_000);
+
+    let mut entries = pool.audit_log().await.entries;
+    let executed = entries.pop().unwrap();
+    assert_eq!((executed.action, executed.actor), (AdminAction::SetFeeSchedule, authority.pubkey()));
+    assert_eq!(executed.target, fee_schedule_pda(&SOL_ADDRESS));
+    let queued = entries.pop().unwrap();
+    assert_eq!((queued.action, queued.target), (AdminAction::QueueFeeSchedule, pending_fee_schedule_pda(&SOL_ADDRESS)));
+
+    // Above MAX_BASE_FEE
+    let too_high = pool.queue_fee_schedule_instruction(&SOL_ADDRESS, LAMPORTS_PER_SOL, &[], 0);
+    assert!(pool.process(&[too_high], &[&authority]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_fee_schedule_waits_out_the_timelock() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+
+    let queue = pool.queue_fee_schedule_instruction(&SOL_ADDRESS, 5_000, &[], 0);
+    pool.process(&[queue], &[&authority]).await.expect("queue_fee_schedule failed");
+    let execute = pool.execute_fee_schedule_instruction(&SOL_ADDRESS);
+    assert!(pool.process(&[execute.clone()], &[]).await.is_err());
+
+    pool.advance_slots(CONFIG_UPDATE_DELAY_SLOTS + 1).await;
+    pool.process(&[execute], &[]).await.expect("execute_fee_schedule failed");
+    assert_eq!(pool.fee_schedule(&SOL_ADDRESS).await.base_fee, 5_000);
+}
+
+#[tokio::test]
+async fn test_cancelled_fee_schedule_is_not_applied() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+
+    let queue = pool.queue_fee_schedule_instruction(&SOL_ADDRESS, 5_000, &[], 0);
+    pool.process(&[queue], &[&authority]).await.expect("queue_fee_schedule failed");
+    let cancel = pool.cancel_fee_schedule_instruction(&SOL_ADDRESS);
+    pool.process(&[cancel], &[&authority]).await.expect("cancel_fee_schedule failed");
+    assert_eq!(pool.audit_log().await.entries.pop().unwrap().action, AdminAction::CancelFeeSchedule);
+
+    pool.advance_slots(CONFIG_UPDATE_DELAY_SLOTS + 1).await;
+    let execute = pool.execute_fee_schedule_instruction(&SOL_ADDRESS);
+    assert!(pool.process(&[execute], &[]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_authority_sets_fee_tiers() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let tiers = [
+        FeeTier { min_amount: 100 * LAMPORTS_PER_SOL, fee_rate: 20 },
+        FeeTier { min_amount: 1_000 * LAMPORTS_PER_SOL, fee_rate: 10 },
+    ];
+
+    pool.set_fee_schedule(&SOL_ADDRESS, 0, &tiers, 0).await.expect("setting the fee schedule failed");
+    assert_eq!(pool.fee_schedule(&SOL_ADDRESS).await.tiers, tiers);
+
+    // Thresholds must ascend
+    let unsorted = pool.queue_fee_schedule_instruction(&SOL_ADDRESS, 0, &[tiers[1], tiers[0]], 0);
+    assert!(pool.process(&[unsorted], &[&authority]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_authority_caps_withdrawal_fee() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+
+    pool.set_fee_schedule(&SOL_ADDRESS, 5_000, &[], LAMPORTS_PER_SOL / 10)
+        .await
+        .expect("setting the fee schedule failed");
+    assert_eq!(pool.fee_schedule(&SOL_ADDRESS).await.fee_cap, LAMPORTS_PER_SOL / 10);
+
+    // A cap below the base fee is rejected
+    let below_base = pool.queue_fee_schedule_instruction(&SOL_ADDRESS, 5_000, &[], 4_999);
+    assert!(pool.process(&[below_base], &[&authority]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_only_the_authority_sets_base_fee() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let outsider = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+
+    pool.authority = outsider.insecure_clone();
+    let queue = pool.queue_fee_schedule_instruction(&SOL_ADDRESS, 0, &[], 0);
+    assert!(pool.process(&[queue], &[&outsider]).await.is_err());
+
+    // Nor cancels one the authority queued
+    pool.authority = authority.insecure_clone();
+    let queue = pool.queue_fee_schedule_instruction(&SOL_ADDRESS, 0, &[], 0);
+    pool.process(&[queue], &[&authority]).await.expect("queue_fee_schedule failed");
+    pool.authority = outsider.insecure_clone();
+    let cancel = pool.cancel_fee_schedule_instruction(&SOL_ADDRESS);
+    assert!(pool.process(&[cancel], &[&outsider]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_token_pool_has_its_own_schedule() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let treasury = Pubkey::new_unique();
+    let setup = [
+        pool.initialize_pool_registry_instruction(),
+        pool.configure_pool_factory_instruction(&treasury, MIN_POOL_CREATION_FEE),
+    ];
+    pool.process(&setup, &[&authority]).await.expect("factory setup failed");
+    let mint = pool.create_mint(6).await;
+    let creator = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+    let initialize = pool.initialize_token_pool_instruction(&mint, &treasury, &creator.pubkey());
+    pool.process(&[initialize], &[&creator]).await.expect("initialize_token_pool failed");
+
+    // 0.01 SOL is fine for the SOL pool but would be 10 tokens at 6 decimals
+    pool.set_fee_schedule(&SOL_ADDRESS, 10_000_000, &[], 0).await.expect("setting the SOL schedule failed");
+    let too_high = pool.queue_fee_schedule_instruction(&mint, 10_000_000, &[], 0);
+    assert!(pool.process(&[too_high], &[&authority]).await.is_err());
+
+    // 0.01 tokens
+    pool.set_fee_schedule(&mint, 10_000, &[], 1_000_000).await.expect("setting the token schedule failed");
+    let schedule = pool.fee_schedule(&mint).await;
+    assert_eq!((schedule.mint, schedule.base_fee, schedule.fee_cap), (mint, 10_000, 1_000_000));
+    assert_eq!(pool.fee_schedule(&SOL_ADDRESS).await.base_fee, 10_000_000);
+
+    // A mint without a pool has no decimals to check against
+    let no_pool = pool.create_mint(6).await;
+    let orphan = pool.queue_fee_schedule_instruction(&no_pool, 0, &[], 0);
+    assert!(pool.process(&[orphan], &[&authority]).await.is_err());
+}
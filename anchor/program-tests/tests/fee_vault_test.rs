@@ -0,0 +1,45 @@
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, signature::Signer};
+use zkcash_program_tests::{fee_vault_pda, AdminAction, PoolHarness, Role};
+
+#[tokio::test]
+async fn test_fee_collector_sweeps_to_the_payout_only() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let collector = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+    let payout = pool.funded_keypair(LAMPORTS_PER_SOL).await.pubkey();
+
+    let set_payout = pool.set_fee_payout_instruction(&payout);
+    let delegate = pool.set_role_instruction(Role::FeeCollector, &collector.pubkey());
+    pool.process(&[set_payout, delegate], &[&authority]).await.expect("fee vault setup failed");
+    let entries = pool.audit_log().await.entries;
+    assert_eq!(entries[entries.len() - 2].action, AdminAction::SetFeePayout);
+    assert_eq!(pool.fee_vault().await.payout, payout);
+
+    // Nothing accumulated yet
+    let sweep = pool.sweep_fees_instruction(&collector.pubkey(), &payout);
+    assert!(pool.process(&[sweep], &[&collector]).await.is_err());
+
+    pool.transfer(&fee_vault_pda(), 3 * LAMPORTS_PER_SOL).await;
+    let vault_before = pool.lamports(&fee_vault_pda()).await;
+
+    // Only to the configured payout, and only by the collector
+    let elsewhere = pool.sweep_fees_instruction(&collector.pubkey(), &collector.pubkey());
+    assert!(pool.process(&[elsewhere], &[&collector]).await.is_err());
+    let by_authority = pool.sweep_fees_instruction(&authority.pubkey(), &payout);
+    assert!(pool.process(&[by_authority], &[&authority]).await.is_err());
+
+    let payout_before = pool.lamports(&payout).await;
+    let sweep = pool.sweep_fees_instruction(&collector.pubkey(), &payout);
+    pool.process(&[sweep], &[&collector]).await.expect("sweep_fees failed");
+    let swept = pool.lamports(&payout).await - payout_before;
+    assert_eq!(swept, 3 * LAMPORTS_PER_SOL);
+    assert_eq!(pool.lamports(&fee_vault_pda()).await, vault_before - swept);
+    assert_eq!(pool.fee_vault().await.total_swept, swept);
+    let entry = pool.audit_log().await.entries.pop().unwrap();
+    assert_eq!((entry.action, entry.actor, entry.target), (AdminAction::SweepFees, collector.pubkey(), payout));
+
+    // The collector can't redirect the fees
+    pool.authority = collector.insecure_clone();
+    let redirect = pool.set_fee_payout_instruction(&collector.pubkey());
+    assert!(pool.process(&[redirect], &[&collector]).await.is_err());
+}
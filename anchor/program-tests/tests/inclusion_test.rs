@@ -0,0 +1,36 @@
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, signature::Signer};
+use zkcash_program_tests::{empty_tree_root, zero_hashes, PoolHarness, TransactFixture};
+
+#[tokio::test]
+async fn test_verifies_deposited_commitments() {
+    let mut pool = PoolHarness::start().await;
+    let deposit = TransactFixture::load("deposit");
+    let depositor = pool.funded_keypair(2 * LAMPORTS_PER_SOL).await;
+    let instructions = pool.transact_instructions(&deposit, &depositor.pubkey());
+    pool.process(&instructions, &[&depositor]).await.expect("deposit failed");
+
+    // The two outputs are leaves 0 and 1: each other's sibling, with empty
+    // subtrees above
+    let root = pool.tree().await.root;
+    let [first, second] = deposit.proof.output_commitments;
+    let mut path = zero_hashes();
+    path.pop();
+    path[0] = second;
+    let verify = pool.verify_inclusion_instruction(first, 0, &path, root);
+    pool.process(&[verify], &[]).await.expect("leaf 0 should verify");
+    path[0] = first;
+    let verify = pool.verify_inclusion_instruction(second, 1, &path, root);
+    pool.process(&[verify], &[]).await.expect("leaf 1 should verify");
+
+    let wrong_index = pool.verify_inclusion_instruction(second, 0, &path, root);
+    assert!(pool.process(&[wrong_index], &[]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_empty_tree_includes_nothing() {
+    let mut pool = PoolHarness::start().await;
+    let mut path = zero_hashes();
+    path.pop();
+    let verify = pool.verify_inclusion_instruction([0u8; 32], 0, &path, empty_tree_root());
+    assert!(pool.process(&[verify], &[]).await.is_err());
+}
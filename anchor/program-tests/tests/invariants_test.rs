@@ -0,0 +1,33 @@
+use borsh::BorshDeserialize;
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, signature::Signer};
+use zkcash_program_tests::{InvariantReport, PoolHarness, TransactFixture};
+
+async fn check(pool: &mut PoolHarness) -> InvariantReport {
+    let instruction = pool.check_invariants_instruction();
+    let report = pool.process_with_report(&[instruction], &[]).await.expect("check_invariants failed");
+    InvariantReport::try_from_slice(&report.return_data).expect("failed to decode invariant report")
+}
+
+#[tokio::test]
+async fn test_fresh_pool_passes() {
+    let mut pool = PoolHarness::start().await;
+    let report = check(&mut pool).await;
+    assert!(report.solvent && report.within_capacity);
+    assert_eq!(report.liabilities, 0);
+    assert_eq!(report.next_index, 0);
+}
+
+#[tokio::test]
+async fn test_passes_after_deposit() {
+    let mut pool = PoolHarness::start().await;
+    let deposit = TransactFixture::load("deposit");
+    let depositor = pool.funded_keypair(2 * LAMPORTS_PER_SOL).await;
+    let instructions = pool.transact_instructions(&deposit, &depositor.pubkey());
+    pool.process(&instructions, &[&depositor]).await.expect("deposit failed");
+
+    let report = check(&mut pool).await;
+    assert!(report.solvent && report.within_capacity);
+    assert_eq!(report.liabilities, deposit.ext_amount as u64 - deposit.fee);
+    assert_eq!(report.actual_lamports, report.liabilities);
+    assert_eq!(report.next_index, 2);
+}
@@ -0,0 +1,36 @@
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, signature::Signer};
+use zkcash_program_tests::{leaf_storage_config_pda, AdminAction, PoolHarness};
+
+#[tokio::test]
+async fn test_leaf_pages_need_leaf_storage_enabled() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let payer = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+
+    let page = pool.initialize_leaf_page_instruction(&payer.pubkey(), 0);
+    assert!(pool.process(&[page], &[&payer]).await.is_err());
+
+    let enable = pool.set_leaf_storage_instruction(true);
+    pool.process(&[enable], &[&authority]).await.expect("set_leaf_storage failed");
+    let entry = pool.audit_log().await.entries.pop().unwrap();
+    assert_eq!((entry.action, entry.target), (AdminAction::SetLeafStorage, leaf_storage_config_pda()));
+
+    let page = pool.initialize_leaf_page_instruction(&payer.pubkey(), 0);
+    pool.process(&[page], &[&payer]).await.expect("initialize_leaf_page failed");
+    let leaf_page = pool.leaf_page(0).await;
+    assert_eq!(leaf_page.tree_account, pool.tree_account);
+    assert_eq!((leaf_page.page_index, leaf_page.len), (0, 0));
+
+    // At most one page ahead of the one being filled
+    let too_far = pool.initialize_leaf_page_instruction(&payer.pubkey(), 2);
+    assert!(pool.process(&[too_far], &[&payer]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_only_authority_sets_leaf_storage() {
+    let mut pool = PoolHarness::start().await;
+    let other = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+    pool.authority = other.insecure_clone();
+    let enable = pool.set_leaf_storage_instruction(true);
+    assert!(pool.process(&[enable], &[&other]).await.is_err());
+}
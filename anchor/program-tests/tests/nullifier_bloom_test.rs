@@ -0,0 +1,18 @@
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, signature::Signer};
+use zkcash_program_tests::PoolHarness;
+
+#[tokio::test]
+async fn test_anyone_initializes_the_filter_once() {
+    let mut pool = PoolHarness::start().await;
+    let payer = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+
+    let initialize = pool.initialize_nullifier_bloom_filter_instruction(&payer.pubkey());
+    pool.process(&[initialize], &[&payer]).await.expect("initialize_nullifier_bloom_filter failed");
+    let filter = pool.nullifier_bloom_filter().await;
+    assert_eq!(filter.tree_account, pool.tree_account);
+    assert_eq!(filter.inserted, 0);
+    assert!(filter.bits.iter().all(|byte| *byte == 0));
+
+    let again = pool.initialize_nullifier_bloom_filter_instruction(&payer.pubkey());
+    assert!(pool.process(&[again], &[&payer]).await.is_err());
+}
@@ -0,0 +1,42 @@
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, signature::Signer};
+use zkcash_program_tests::{nullifier_v1_pda, AdminAction, NullifierSeedVersion, PoolHarness, TransactFixture};
+
+const WINDOW_SLOTS: u64 = 3_024_000;
+
+#[tokio::test]
+async fn test_spent_nullifiers_carry_over_to_v1_seeds() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let deposit = TransactFixture::load("deposit");
+    let depositor = pool.funded_keypair(2 * LAMPORTS_PER_SOL).await;
+    let instructions = pool.transact_instructions(&deposit, &depositor.pubkey());
+    pool.process(&instructions, &[&depositor]).await.expect("deposit failed");
+
+    let begin = pool.begin_nullifier_seed_migration_instruction(NullifierSeedVersion::V1, WINDOW_SLOTS);
+    pool.process(&[begin], &[&authority]).await.expect("begin_nullifier_seed_migration failed");
+    let config = pool.nullifier_seed_config().await;
+    assert_eq!(config.version, NullifierSeedVersion::V1);
+    assert_eq!(config.legacy_version, Some(NullifierSeedVersion::V0));
+    let entry = pool.audit_log().await.entries.pop().unwrap();
+    assert_eq!(entry.action, AdminAction::BeginNullifierSeedMigration);
+
+    let tree_account = pool.tree_account;
+    for (index, nullifier) in deposit.proof.input_nullifiers.into_iter().enumerate() {
+        let migrate = pool.migrate_nullifier_instruction(&depositor.pubkey(), index as u8, nullifier);
+        pool.process(&[migrate], &[&depositor]).await.expect("migrate_nullifier failed");
+        assert!(pool.account_exists(&nullifier_v1_pda(&tree_account, &nullifier)).await);
+    }
+
+    // A nullifier that was never spent has nothing to carry over
+    let migrate = pool.migrate_nullifier_instruction(&depositor.pubkey(), 0, [9u8; 32]);
+    assert!(pool.process(&[migrate], &[&depositor]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_only_authority_begins_migration() {
+    let mut pool = PoolHarness::start().await;
+    let other = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+    pool.authority = other.insecure_clone();
+    let begin = pool.begin_nullifier_seed_migration_instruction(NullifierSeedVersion::V1, WINDOW_SLOTS);
+    assert!(pool.process(&[begin], &[&other]).await.is_err());
+}
@@ -0,0 +1,47 @@
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
+use zkcash_program_tests::{PoolHarness, PoolStatus, SOL_ADDRESS};
+
+#[tokio::test]
+async fn test_registry_lists_the_sol_pool() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+
+    let initialize = pool.initialize_pool_registry_instruction();
+    pool.process(&[initialize], &[&authority]).await.expect("initialize_pool_registry failed");
+
+    let registry = pool.pool_registry().await;
+    assert_eq!(registry.pools.len(), 1);
+    let entry = &registry.pools[0];
+    assert_eq!(entry.mint, SOL_ADDRESS);
+    assert_eq!(entry.tree_account, pool.tree_account);
+    assert_eq!(entry.config, pool.global_config);
+    assert_eq!(entry.status, PoolStatus::Active);
+
+    // The registry PDA is unique
+    let initialize = pool.initialize_pool_registry_instruction();
+    assert!(pool.process(&[initialize], &[&authority]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_authority_sets_pool_status() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let tree_account = pool.tree_account;
+    let initialize = pool.initialize_pool_registry_instruction();
+    pool.process(&[initialize], &[&authority]).await.expect("initialize_pool_registry failed");
+
+    let pause = pool.set_pool_status_instruction(&tree_account, PoolStatus::Paused);
+    pool.process(&[pause], &[&authority]).await.expect("set_pool_status failed");
+    assert_eq!(pool.pool_registry().await.pools[0].status, PoolStatus::Paused);
+
+    // Unknown pools are rejected
+    let unknown = pool.set_pool_status_instruction(&Pubkey::new_unique(), PoolStatus::Retired);
+    assert!(pool.process(&[unknown], &[&authority]).await.is_err());
+
+    // So is anyone but the authority
+    let outsider = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+    pool.authority = outsider.insecure_clone();
+    let resume = pool.set_pool_status_instruction(&tree_account, PoolStatus::Active);
+    assert!(pool.process(&[resume], &[&outsider]).await.is_err());
+    assert_eq!(pool.pool_registry().await.pools[0].status, PoolStatus::Paused);
+}
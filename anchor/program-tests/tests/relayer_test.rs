@@ -0,0 +1,123 @@
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, signature::Signer};
+use zkcash_program_tests::{relayer_pda, AdminAction, PoolHarness, SlashReason, FEE_RECIPIENT_ACCOUNT};
+
+#[tokio::test]
+async fn test_relayer_registers_and_updates_its_rate() {
+    let mut pool = PoolHarness::start().await;
+    let relayer = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+    let global_rate = pool.global_config().await.withdrawal_fee_rate;
+
+    let register = pool.set_relayer_fee_rate_instruction(&relayer.pubkey(), global_rate + 15);
+    pool.process(&[register], &[&relayer]).await.expect("registering failed");
+    let account = pool.relayer_account(&relayer.pubkey()).await;
+    assert_eq!(account.relayer, relayer.pubkey());
+    assert_eq!(account.withdrawal_fee_rate, global_rate + 15);
+
+    let update = pool.set_relayer_fee_rate_instruction(&relayer.pubkey(), global_rate);
+    pool.process(&[update], &[&relayer]).await.expect("updating the rate failed");
+    let updated = pool.relayer_account(&relayer.pubkey()).await;
+    assert_eq!(updated.withdrawal_fee_rate, global_rate);
+    assert_eq!(updated.registered_slot, account.registered_slot);
+
+    // Below the global rate
+    let too_low = pool.set_relayer_fee_rate_instruction(&relayer.pubkey(), global_rate - 1);
+    assert!(pool.process(&[too_low], &[&relayer]).await.is_err());
+    // Above MAX_FEE_RATE
+    let too_high = pool.set_relayer_fee_rate_instruction(&relayer.pubkey(), 501);
+    assert!(pool.process(&[too_high], &[&relayer]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_relayer_deregisters_after_unbonding() {
+    let mut pool = PoolHarness::start().await;
+    let relayer = pool.funded_keypair(3 * LAMPORTS_PER_SOL).await;
+
+    let register = pool.set_relayer_fee_rate_instruction(&relayer.pubkey(), 150);
+    let bond = pool.bond_relayer_instruction(&relayer.pubkey(), LAMPORTS_PER_SOL);
+    pool.process(&[register, bond], &[&relayer]).await.expect("bonding failed");
+    assert_eq!(pool.relayer_account(&relayer.pubkey()).await.bond, LAMPORTS_PER_SOL);
+
+    // Not before requesting it
+    let deregister = pool.deregister_relayer_instruction(&relayer.pubkey());
+    assert!(pool.process(&[deregister], &[&relayer]).await.is_err());
+
+    let request = pool.request_relayer_deregistration_instruction(&relayer.pubkey());
+    pool.process(&[request], &[&relayer]).await.expect("requesting deregistration failed");
+    assert_ne!(pool.relayer_account(&relayer.pubkey()).await.unbonding_slot, 0);
+
+    pool.advance_slots(432_000).await;
+    let balance = pool.lamports(&relayer.pubkey()).await;
+    let deregister = pool.deregister_relayer_instruction(&relayer.pubkey());
+    pool.process(&[deregister], &[&relayer]).await.expect("deregistering failed");
+    assert!(!pool.account_exists(&relayer_pda(&relayer.pubkey())).await);
+    // The bond comes back with the rent
+    assert!(pool.lamports(&relayer.pubkey()).await > balance + LAMPORTS_PER_SOL - 10_000);
+}
+
+#[tokio::test]
+async fn test_guardian_slashes_bond_into_treasury() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let guardian = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+    let relayer = pool.funded_keypair(3 * LAMPORTS_PER_SOL).await;
+    let set_treasury = pool.set_fee_recipient_instruction(&FEE_RECIPIENT_ACCOUNT);
+    let initialize = pool.initialize_circuit_breaker_instruction(1000, 216_000);
+    let set_guardian = pool.set_guardian_instruction(&guardian.pubkey());
+    pool.process(&[set_treasury, initialize, set_guardian], &[&authority])
+        .await
+        .expect("setup failed");
+    let register = pool.set_relayer_fee_rate_instruction(&relayer.pubkey(), 150);
+    let bond = pool.bond_relayer_instruction(&relayer.pubkey(), 2 * LAMPORTS_PER_SOL);
+    pool.process(&[register, bond], &[&relayer]).await.expect("bonding failed");
+
+    let treasury_before = pool.lamports(&FEE_RECIPIENT_ACCOUNT).await;
+    let slash = pool.slash_relayer_instruction(
+        &guardian.pubkey(),
+        &relayer.pubkey(),
+        &FEE_RECIPIENT_ACCOUNT,
+        LAMPORTS_PER_SOL,
+        SlashReason::FeeManipulation,
+        [7u8; 32],
+    );
+    pool.process(&[slash], &[&guardian]).await.expect("slash_relayer failed");
+    assert_eq!(pool.relayer_account(&relayer.pubkey()).await.bond, LAMPORTS_PER_SOL);
+    assert_eq!(pool.lamports(&FEE_RECIPIENT_ACCOUNT).await, treasury_before + LAMPORTS_PER_SOL);
+    let entry = pool.audit_log().await.entries.pop().unwrap();
+    assert_eq!(
+        (entry.actor, entry.action, entry.target),
+        (guardian.pubkey(), AdminAction::SlashRelayer, relayer.pubkey())
+    );
+
+    // Never more than the remaining bond
+    let slash = pool.slash_relayer_instruction(
+        &guardian.pubkey(),
+        &relayer.pubkey(),
+        &FEE_RECIPIENT_ACCOUNT,
+        LAMPORTS_PER_SOL + 1,
+        SlashReason::Censorship,
+        [8u8; 32],
+    );
+    assert!(pool.process(&[slash], &[&guardian]).await.is_err());
+
+    // Only the guardian slashes, and only into the treasury
+    let slash = pool.slash_relayer_instruction(
+        &guardian.pubkey(),
+        &relayer.pubkey(),
+        &relayer.pubkey(),
+        1,
+        SlashReason::Censorship,
+        [9u8; 32],
+    );
+    assert!(pool.process(&[slash], &[&guardian]).await.is_err());
+    for signer in [&authority, &relayer] {
+        let slash = pool.slash_relayer_instruction(
+            &signer.pubkey(),
+            &relayer.pubkey(),
+            &FEE_RECIPIENT_ACCOUNT,
+            1,
+            SlashReason::Censorship,
+            [9u8; 32],
+        );
+        assert!(pool.process(&[slash], &[signer]).await.is_err());
+    }
+}
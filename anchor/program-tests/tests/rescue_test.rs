@@ -0,0 +1,74 @@
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, signature::Signer};
+use zkcash_program_tests::{token_pool_pdas, AdminAction, PoolHarness, MIN_POOL_CREATION_FEE};
+
+#[tokio::test]
+async fn test_authority_rescues_tokens_sent_to_sol_pool() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let mint = pool.create_mint(6).await;
+    let tree_token_account = pool.tree_token_account;
+    let stray = pool.create_token_account(&mint, &tree_token_account).await;
+    pool.mint_to(&mint, &stray, 5_000).await;
+    let recovery = pool.create_token_account(&mint, &Pubkey::new_unique()).await;
+
+    let rescue = pool.rescue_tokens_instruction(&stray, &recovery, None);
+    pool.process(&[rescue], &[&authority]).await.expect("rescue_tokens failed");
+    assert_eq!(pool.token_balance(&stray).await, 0);
+    assert_eq!(pool.token_balance(&recovery).await, 5_000);
+    let entry = pool.audit_log().await.entries.pop().unwrap();
+    assert_eq!((entry.action, entry.target), (AdminAction::RescueTokens, stray));
+}
+
+#[tokio::test]
+async fn test_rescue_never_touches_pool_asset() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let treasury = Pubkey::new_unique();
+    let setup = [
+        pool.initialize_pool_registry_instruction(),
+        pool.configure_pool_factory_instruction(&treasury, MIN_POOL_CREATION_FEE),
+    ];
+    pool.process(&setup, &[&authority]).await.expect("factory setup failed");
+    let mint = pool.create_mint(6).await;
+    let creator = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+    let initialize = pool.initialize_token_pool_instruction(&mint, &treasury, &creator.pubkey());
+    pool.process(&[initialize], &[&creator]).await.expect("initialize_token_pool failed");
+
+    let [_, token_pool, vault] = token_pool_pdas(&mint);
+    pool.mint_to(&mint, &vault, 1_000).await;
+    let recovery = pool.create_token_account(&mint, &Pubkey::new_unique()).await;
+
+    // The vault itself
+    let rescue = pool.rescue_tokens_instruction(&vault, &recovery, Some(&mint));
+    assert!(pool.process(&[rescue], &[&authority]).await.is_err());
+
+    // Another account of the pool's mint owned by the pool
+    let second = pool.create_token_account(&mint, &token_pool).await;
+    pool.mint_to(&mint, &second, 1_000).await;
+    let rescue = pool.rescue_tokens_instruction(&second, &recovery, Some(&mint));
+    assert!(pool.process(&[rescue], &[&authority]).await.is_err());
+    assert_eq!(pool.token_balance(&vault).await, 1_000);
+
+    // A foreign mint sent to the token pool is fine
+    let foreign = pool.create_mint(9).await;
+    let stray = pool.create_token_account(&foreign, &token_pool).await;
+    pool.mint_to(&foreign, &stray, 42).await;
+    let foreign_recovery = pool.create_token_account(&foreign, &Pubkey::new_unique()).await;
+    let rescue = pool.rescue_tokens_instruction(&stray, &foreign_recovery, Some(&mint));
+    pool.process(&[rescue], &[&authority]).await.expect("rescue_tokens failed");
+    assert_eq!(pool.token_balance(&foreign_recovery).await, 42);
+}
+
+#[tokio::test]
+async fn test_only_authority_rescues() {
+    let mut pool = PoolHarness::start().await;
+    let mint = pool.create_mint(6).await;
+    let tree_token_account = pool.tree_token_account;
+    let stray = pool.create_token_account(&mint, &tree_token_account).await;
+    let other = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+    let recovery = pool.create_token_account(&mint, &other.pubkey()).await;
+
+    pool.authority = other.insecure_clone();
+    let rescue = pool.rescue_tokens_instruction(&stray, &recovery, None);
+    assert!(pool.process(&[rescue], &[&other]).await.is_err());
+}
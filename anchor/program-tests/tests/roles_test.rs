@@ -0,0 +1,52 @@
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, signature::Signer};
+use zkcash_program_tests::{role_config_pda, AdminAction, PoolHarness, Role, PROGRAM_ID, SOL_ADDRESS};
+
+#[tokio::test]
+async fn test_delegated_role_moves_its_instructions_off_the_authority() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let fee_manager = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+
+    let delegate = pool.set_role_instruction(Role::FeeManager, &fee_manager.pubkey());
+    pool.process(&[delegate], &[&authority]).await.expect("set_role failed");
+    let roles = pool.role_config().await;
+    assert_eq!((roles.fee_manager, roles.tree_operator), (fee_manager.pubkey(), Pubkey::default()));
+    let entry = pool.audit_log().await.entries.pop().unwrap();
+    assert_eq!((entry.action, entry.target), (AdminAction::SetRole, fee_manager.pubkey()));
+
+    // The authority no longer manages fees...
+    let queue = pool.queue_fee_schedule_instruction(&SOL_ADDRESS, 5_000, &[], 0);
+    assert!(pool.process(&[queue.clone()], &[&authority]).await.is_err());
+    // ...even by leaving the role config out
+    let mut without_roles = queue;
+    let role_config = without_roles.accounts.iter_mut().find(|meta| meta.pubkey == role_config_pda()).unwrap();
+    role_config.pubkey = PROGRAM_ID;
+    assert!(pool.process(&[without_roles], &[&authority]).await.is_err());
+    // ...but still holds the roles it hasn't delegated
+    let enable = pool.set_leaf_storage_instruction(true);
+    pool.process(&[enable], &[&authority]).await.expect("set_leaf_storage failed");
+
+    pool.authority = fee_manager.insecure_clone();
+    pool.set_fee_schedule(&SOL_ADDRESS, 5_000, &[], 0).await.expect("setting the fee schedule failed");
+    assert_eq!(pool.fee_schedule(&SOL_ADDRESS).await.base_fee, 5_000);
+    let enable = pool.set_leaf_storage_instruction(false);
+    assert!(pool.process(&[enable], &[&fee_manager]).await.is_err());
+    let delegate = pool.set_role_instruction(Role::TreeOperator, &fee_manager.pubkey());
+    assert!(pool.process(&[delegate], &[&fee_manager]).await.is_err());
+
+    // The default key hands the role back
+    pool.authority = authority.insecure_clone();
+    let revoke = pool.set_role_instruction(Role::FeeManager, &Pubkey::default());
+    pool.process(&[revoke], &[&authority]).await.expect("set_role failed");
+    pool.set_fee_schedule(&SOL_ADDRESS, 6_000, &[], 0).await.expect("setting the fee schedule failed");
+    assert_eq!(pool.fee_schedule(&SOL_ADDRESS).await.base_fee, 6_000);
+}
+
+#[tokio::test]
+async fn test_only_authority_sets_roles() {
+    let mut pool = PoolHarness::start().await;
+    let other = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+    pool.authority = other.insecure_clone();
+    let delegate = pool.set_role_instruction(Role::TreeOperator, &other.pubkey());
+    assert!(pool.process(&[delegate], &[&other]).await.is_err());
+}
@@ -0,0 +1,58 @@
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, signature::Signer};
+use zkcash_program_tests::{root_checkpoints_pda, AdminAction, PoolHarness};
+
+#[tokio::test]
+async fn test_checkpoint_root_records_root_once_per_interval() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let cranker = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+
+    let initialize = pool.initialize_root_checkpoints_instruction(150);
+    pool.process(&[initialize], &[&authority]).await.expect("initialize_root_checkpoints failed");
+    let entry = pool.audit_log().await.entries.pop().unwrap();
+    assert_eq!(
+        (entry.action, entry.target),
+        (AdminAction::InitializeRootCheckpoints, root_checkpoints_pda(&pool.tree_account))
+    );
+    let checkpoints = pool.root_checkpoints().await;
+    assert_eq!(checkpoints.header.tree_account, pool.tree_account);
+    assert_eq!((checkpoints.header.interval_slots, checkpoints.header.len), (150, 0));
+    assert!(checkpoints.checkpoints.is_empty());
+
+    let crank = pool.checkpoint_root_instruction(&cranker.pubkey());
+    pool.process(&[crank], &[&cranker]).await.expect("checkpoint_root failed");
+    let tree = pool.tree().await;
+    let checkpoints = pool.root_checkpoints().await;
+    assert_eq!(checkpoints.header.len, 1);
+    assert_eq!(checkpoints.checkpoints.len(), 1);
+    let first = checkpoints.checkpoints[0];
+    assert_eq!((first.root, first.next_index), (tree.root, tree.next_index));
+
+    // Not due again until the interval has passed
+    let crank = pool.checkpoint_root_instruction(&cranker.pubkey());
+    assert!(pool.process(&[crank], &[&cranker]).await.is_err());
+
+    pool.advance_slots(150).await;
+    let crank = pool.checkpoint_root_instruction(&cranker.pubkey());
+    pool.process(&[crank], &[&cranker]).await.expect("checkpoint_root failed");
+    let checkpoints = pool.root_checkpoints().await;
+    assert_eq!(checkpoints.checkpoints.len(), 2);
+    assert!(checkpoints.checkpoints[1].slot >= first.slot + 150);
+}
+
+#[tokio::test]
+async fn test_root_checkpoint_interval_has_a_floor() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let initialize = pool.initialize_root_checkpoints_instruction(149);
+    assert!(pool.process(&[initialize], &[&authority]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_only_authority_initializes_root_checkpoints() {
+    let mut pool = PoolHarness::start().await;
+    let other = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+    pool.authority = other.insecure_clone();
+    let initialize = pool.initialize_root_checkpoints_instruction(150);
+    assert!(pool.process(&[initialize], &[&other]).await.is_err());
+}
@@ -0,0 +1,41 @@
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, signature::Signer};
+use zkcash_program_tests::{root_history_pda, AdminAction, PoolHarness};
+
+#[tokio::test]
+async fn test_root_history_is_grown_to_full_size() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let payer = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+
+    let initialize = pool.initialize_root_history_instruction();
+    pool.process(&[initialize], &[&authority]).await.expect("initialize_root_history failed");
+    let entry = pool.audit_log().await.entries.pop().unwrap();
+    assert_eq!(entry.action, AdminAction::InitializeRootHistory);
+    assert_eq!(entry.target, root_history_pda(&pool.tree_account));
+
+    // 10 KiB at a time, from 10 KiB to about 36 KiB
+    for _ in 0..3 {
+        let grow = pool.grow_root_history_instruction(&payer.pubkey());
+        pool.process(&[grow], &[&payer]).await.expect("grow_root_history failed");
+    }
+    let root_history = pool.root_history().await;
+    assert_eq!(root_history.tree_account, pool.tree_account);
+    assert_eq!(root_history.root_index, 0);
+    assert_eq!(root_history.roots[0], pool.tree().await.root);
+    assert!(root_history.roots[1..].iter().all(|root| *root == [0u8; 32]));
+    // Only the seeded root is indexed, pointing at slot 0
+    assert_eq!(root_history.index.iter().filter(|entry| **entry != 0).collect::<Vec<_>>(), [&1]);
+
+    let grow = pool.grow_root_history_instruction(&payer.pubkey());
+    assert!(pool.process(&[grow], &[&payer]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_only_the_authority_initializes_root_history() {
+    let mut pool = PoolHarness::start().await;
+    let outsider = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+
+    pool.authority = outsider.insecure_clone();
+    let initialize = pool.initialize_root_history_instruction();
+    assert!(pool.process(&[initialize], &[&outsider]).await.is_err());
+}
@@ -0,0 +1,32 @@
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use zkcash_program_tests::{root_policy_pda, AdminAction, PoolHarness};
+
+#[tokio::test]
+async fn test_authority_sets_root_policy() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+
+    let set = pool.set_root_policy_instruction(9_000);
+    pool.process(&[set], &[&authority]).await.expect("set_root_policy failed");
+    assert_eq!(pool.root_policy().await.max_root_age_slots, 9_000);
+    let entry = pool.audit_log().await.entries.pop().unwrap();
+    assert_eq!((entry.action, entry.target), (AdminAction::SetRootPolicy, root_policy_pda()));
+
+    // Back to unbounded
+    let disable = pool.set_root_policy_instruction(0);
+    pool.process(&[disable], &[&authority]).await.expect("disabling the policy failed");
+    assert_eq!(pool.root_policy().await.max_root_age_slots, 0);
+
+    // Too tight to leave withdrawals a usable root
+    let too_tight = pool.set_root_policy_instruction(10);
+    assert!(pool.process(&[too_tight], &[&authority]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_only_authority_sets_root_policy() {
+    let mut pool = PoolHarness::start().await;
+    let other = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+    pool.authority = other.insecure_clone();
+    let set = pool.set_root_policy_instruction(9_000);
+    assert!(pool.process(&[set], &[&other]).await.is_err());
+}
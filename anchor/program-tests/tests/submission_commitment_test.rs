@@ -0,0 +1,28 @@
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, signature::Signer};
+use zkcash_program_tests::{submission_commitment_pda, PoolHarness};
+
+#[tokio::test]
+async fn test_relayer_commits_and_closes_submission() {
+    let mut pool = PoolHarness::start().await;
+    let relayer = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+    let other = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+    let commitment = [7u8; 32];
+
+    let commit = pool.commit_submission_instruction(&relayer.pubkey(), commitment);
+    pool.process(&[commit], &[&relayer]).await.expect("commit_submission failed");
+    let account = pool.submission_commitment(commitment).await;
+    assert_eq!(account.relayer, relayer.pubkey());
+    assert_eq!(account.commitment, commitment);
+
+    // The same commitment can't be claimed twice
+    let duplicate = pool.commit_submission_instruction(&other.pubkey(), commitment);
+    assert!(pool.process(&[duplicate], &[&other]).await.is_err());
+
+    // Only the committing relayer can close it
+    let close = pool.close_submission_commitment_instruction(&other.pubkey(), commitment);
+    assert!(pool.process(&[close], &[&other]).await.is_err());
+
+    let close = pool.close_submission_commitment_instruction(&relayer.pubkey(), commitment);
+    pool.process(&[close], &[&relayer]).await.expect("close_submission_commitment failed");
+    assert!(!pool.account_exists(&submission_commitment_pda(&commitment)).await);
+}
@@ -0,0 +1,65 @@
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use zkcash_program_tests::{pending_config_pda, ConfigUpdate, PoolHarness, CONFIG_UPDATE_DELAY_SLOTS};
+
+fn fee_update() -> ConfigUpdate {
+    ConfigUpdate {
+        withdrawal_fee_rate: Some(250),
+        max_deposit_amount: Some(500 * LAMPORTS_PER_SOL),
+        ..ConfigUpdate::default()
+    }
+}
+
+#[tokio::test]
+async fn test_config_update_applies_only_after_delay() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+
+    let queue = pool.queue_config_update_instruction(&fee_update());
+    pool.process(&[queue], &[&authority]).await.expect("queue_config_update failed");
+    assert!(pool.account_exists(&pending_config_pda()).await);
+
+    // Too early: nothing changes
+    let execute = pool.execute_config_update_instruction();
+    assert!(pool.process(&[execute], &[]).await.is_err());
+    assert_eq!(pool.global_config().await.withdrawal_fee_rate, 100);
+
+    pool.advance_slots(CONFIG_UPDATE_DELAY_SLOTS + 1).await;
+    let execute = pool.execute_config_update_instruction();
+    pool.process(&[execute], &[]).await.expect("execute_config_update failed");
+
+    let config = pool.global_config().await;
+    assert_eq!(config.withdrawal_fee_rate, 250);
+    assert_eq!(config.deposit_fee_rate, 0);
+    assert_eq!(config.fee_error_margin, 500);
+    assert_eq!(pool.tree().await.max_deposit_amount, 500 * LAMPORTS_PER_SOL);
+    assert!(!pool.account_exists(&pending_config_pda()).await);
+}
+
+#[tokio::test]
+async fn test_cancelled_config_update_cannot_execute() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+
+    let queue = pool.queue_config_update_instruction(&fee_update());
+    pool.process(&[queue], &[&authority]).await.expect("queue_config_update failed");
+    let cancel = pool.cancel_config_update_instruction();
+    pool.process(&[cancel], &[&authority]).await.expect("cancel_config_update failed");
+
+    pool.advance_slots(CONFIG_UPDATE_DELAY_SLOTS + 1).await;
+    let execute = pool.execute_config_update_instruction();
+    assert!(pool.process(&[execute], &[]).await.is_err());
+    assert_eq!(pool.global_config().await.withdrawal_fee_rate, 100);
+}
+
+#[tokio::test]
+async fn test_queue_config_update_rejects_invalid_values() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+
+    let too_high = ConfigUpdate { deposit_fee_rate: Some(501), ..ConfigUpdate::default() };
+    let queue = pool.queue_config_update_instruction(&too_high);
+    assert!(pool.process(&[queue], &[&authority]).await.is_err());
+
+    let queue = pool.queue_config_update_instruction(&ConfigUpdate::default());
+    assert!(pool.process(&[queue], &[&authority]).await.is_err());
+}
@@ -0,0 +1,110 @@
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, signature::Signer};
+use zkcash_program_tests::{
+    token_pool_pdas, PoolHarness, PoolStatus, MERKLE_TREE_ACCOUNT_V0_LEN, MERKLE_TREE_ACCOUNT_VERSION,
+    MIN_POOL_CREATION_FEE, TOKEN_PROGRAM_ID,
+};
+
+const CREATION_FEE: u64 = LAMPORTS_PER_SOL / 10;
+
+/// A pool with the registry and a factory charging `CREATION_FEE` to a fresh treasury.
+async fn start_with_factory() -> (PoolHarness, Pubkey) {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let treasury = Pubkey::new_unique();
+    let instructions = [
+        pool.initialize_pool_registry_instruction(),
+        pool.configure_pool_factory_instruction(&treasury, CREATION_FEE),
+    ];
+    pool.process(&instructions, &[&authority]).await.expect("factory setup failed");
+    (pool, treasury)
+}
+
+#[tokio::test]
+async fn test_anyone_can_create_a_token_pool() {
+    let (mut pool, treasury) = start_with_factory().await;
+    let factory = pool.pool_factory().await;
+    assert_eq!((factory.treasury, factory.creation_fee), (treasury, CREATION_FEE));
+
+    let mint = pool.create_mint(6).await;
+    let creator = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+    let initialize = pool.initialize_token_pool_instruction(&mint, &treasury, &creator.pubkey());
+    pool.process(&[initialize], &[&creator]).await.expect("initialize_token_pool failed");
+
+    // The creator paid the fee
+    assert_eq!(pool.lamports(&treasury).await, CREATION_FEE);
+
+    let [tree_account, token_pool, vault] = token_pool_pdas(&mint);
+    let state = pool.token_pool(&mint).await;
+    assert_eq!(state.mint, mint);
+    assert_eq!(state.tree_account, tree_account);
+    assert_eq!(state.vault, vault);
+    assert_eq!(state.creator, creator.pubkey());
+    assert_eq!(state.decimals, 6);
+    // 1000 tokens at 6 decimals
+    assert_eq!(pool.tree_at(&tree_account).await.max_deposit_amount, 1_000_000_000);
+
+    // The vault is a token account for the mint, owned by the pool PDA
+    let vault_account = pool.account(&vault).await.expect("vault missing");
+    assert_eq!(vault_account.owner, TOKEN_PROGRAM_ID);
+    assert_eq!(vault_account.data[..32], mint.to_bytes());
+    assert_eq!(vault_account.data[32..64], token_pool.to_bytes());
+
+    // The tree is created at the current layout
+    let tree = pool.account(&tree_account).await.expect("tree missing");
+    assert_eq!(tree.data[MERKLE_TREE_ACCOUNT_V0_LEN], MERKLE_TREE_ACCOUNT_VERSION);
+
+    // And the registry lists it next to the SOL pool
+    let registry = pool.pool_registry().await;
+    assert_eq!(registry.pools.len(), 2);
+    let entry = &registry.pools[1];
+    assert_eq!((entry.mint, entry.tree_account, entry.config), (mint, tree_account, token_pool));
+    assert_eq!(entry.status, PoolStatus::Active);
+}
+
+#[tokio::test]
+async fn test_one_pool_per_mint() {
+    let (mut pool, treasury) = start_with_factory().await;
+    let mint = pool.create_mint(9).await;
+    let creator = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+
+    let initialize = pool.initialize_token_pool_instruction(&mint, &treasury, &creator.pubkey());
+    pool.process(&[initialize], &[&creator]).await.expect("initialize_token_pool failed");
+    assert_eq!(pool.token_pool(&mint).await.decimals, 9);
+    let initialize = pool.initialize_token_pool_instruction(&mint, &treasury, &creator.pubkey());
+    assert!(pool.process(&[initialize], &[&creator]).await.is_err());
+    assert_eq!(pool.pool_registry().await.pools.len(), 2);
+}
+
+#[tokio::test]
+async fn test_creation_fee_must_go_to_the_treasury() {
+    let (mut pool, _treasury) = start_with_factory().await;
+    let mint = pool.create_mint(6).await;
+    let creator = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+
+    let initialize = pool.initialize_token_pool_instruction(&mint, &creator.pubkey(), &creator.pubkey());
+    assert!(pool.process(&[initialize], &[&creator]).await.is_err());
+    assert!(pool.account(&token_pool_pdas(&mint)[1]).await.is_none());
+}
+
+#[tokio::test]
+async fn test_only_the_authority_configures_the_factory() {
+    let (mut pool, treasury) = start_with_factory().await;
+    let outsider = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+
+    pool.authority = outsider.insecure_clone();
+    let configure = pool.configure_pool_factory_instruction(&outsider.pubkey(), CREATION_FEE);
+    assert!(pool.process(&[configure], &[&outsider]).await.is_err());
+    assert_eq!(pool.pool_factory().await.treasury, treasury);
+}
+
+#[tokio::test]
+async fn test_creation_fee_has_a_floor() {
+    let (mut pool, treasury) = start_with_factory().await;
+    let authority = pool.authority.insecure_clone();
+
+    for fee in [0, MIN_POOL_CREATION_FEE - 1] {
+        let configure = pool.configure_pool_factory_instruction(&treasury, fee);
+        assert!(pool.process(&[configure], &[&authority]).await.is_err());
+    }
+    assert_eq!(pool.pool_factory().await.creation_fee, CREATION_FEE);
+}
@@ -0,0 +1,104 @@
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, signature::Signer};
+use zkcash_program_tests::{empty_tree_root, nullifier_pdas, PoolHarness, PoolStats, TransactFixture};
+
+#[tokio::test]
+async fn test_initialize_creates_pool_accounts() {
+    let mut pool = PoolHarness::start().await;
+
+    // The tree starts empty with the all-zero-leaves root
+    let tree = pool.tree().await;
+    assert_eq!(tree.next_index, 0);
+    assert_eq!(tree.height, 26);
+    assert_eq!(tree.root, empty_tree_root());
+    assert_eq!(tree.authority, pool.authority.pubkey());
+
+    // Fee defaults set by initialize
+    let config = pool.global_config().await;
+    assert_eq!(config.authority, pool.authority.pubkey());
+    assert_eq!(config.deposit_fee_rate, 0);
+    assert_eq!(config.withdrawal_fee_rate, 100);
+    assert_eq!(config.fee_error_margin, 500);
+
+    // Stats start at zero
+    let stats = pool.pool_stats().await;
+    assert_eq!(stats, PoolStats { bump: stats.bump, ..PoolStats::default() });
+}
+
+#[tokio::test]
+async fn test_initialize_twice_fails() {
+    let mut pool = PoolHarness::start().await;
+
+    // The PDAs already exist, so a second initialize must be rejected
+    let tree_before = pool.tree().await;
+    let initialize = pool.initialize_instruction();
+    let authority = pool.authority.insecure_clone();
+    let result = pool.process(&[initialize], &[&authority]).await;
+    assert!(result.is_err());
+    assert_eq!(pool.tree().await.root, tree_before.root);
+}
+
+#[tokio::test]
+async fn test_deposit_then_withdraw() {
+    let mut pool = PoolHarness::start().await;
+    let deposit = TransactFixture::load("deposit");
+    let withdraw = TransactFixture::load("withdraw");
+    let depositor = pool.funded_keypair(2 * LAMPORTS_PER_SOL).await;
+
+    // Deposit: lamports move from the signer into the tree token account
+    let vault_before = pool.lamports(&pool.tree_token_account.clone()).await;
+    let instructions = pool.transact_instructions(&deposit, &depositor.pubkey());
+    pool.process(&instructions, &[&depositor]).await.expect("deposit failed");
+
+    let tree = pool.tree().await;
+    assert_eq!(tree.next_index, 2);
+    assert_eq!(
+        pool.lamports(&pool.tree_token_account.clone()).await,
+        vault_before + deposit.ext_amount as u64
+    );
+    for nullifier in &nullifier_pdas(&deposit.proof)[..2] {
+        assert!(pool.account_exists(nullifier).await);
+    }
+
+    // Withdraw: the recipient gets |ext_amount| and the fee recipient gets the fee
+    let relayer = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+    let recipient_before = pool.lamports(&withdraw.recipient).await;
+    let fee_recipient_before = pool.lamports(&withdraw.fee_recipient).await;
+    let vault_before = pool.lamports(&pool.tree_token_account.clone()).await;
+
+    let instructions = pool.transact_instructions(&withdraw, &relayer.pubkey());
+    pool.process(&instructions, &[&relayer]).await.expect("withdraw failed");
+
+    let withdrawn = withdraw.ext_amount.unsigned_abs();
+    assert_eq!(pool.lamports(&withdraw.recipient).await, recipient_before + withdrawn);
+    assert_eq!(pool.lamports(&withdraw.fee_recipient).await, fee_recipient_before + withdraw.fee);
+    assert_eq!(
+        pool.lamports(&pool.tree_token_account.clone()).await,
+        vault_before - withdrawn - withdraw.fee
+    );
+    assert_eq!(pool.tree().await.next_index, 4);
+
+    let stats = pool.pool_stats().await;
+    assert_eq!(stats.deposit_count, 1);
+    assert_eq!(stats.withdrawal_count, 1);
+    assert_eq!(stats.total_deposited, deposit.ext_amount as u64);
+    assert_eq!(stats.total_withdrawn, withdrawn);
+    assert_eq!(stats.total_fees, deposit.fee + withdraw.fee);
+}
+
+#[tokio::test]
+async fn test_replayed_withdraw_is_rejected() {
+    let mut pool = PoolHarness::start().await;
+    let deposit = TransactFixture::load("deposit");
+    let withdraw = TransactFixture::load("withdraw");
+    let depositor = pool.funded_keypair(2 * LAMPORTS_PER_SOL).await;
+
+    let instructions = pool.transact_instructions(&deposit, &depositor.pubkey());
+    pool.process(&instructions, &[&depositor]).await.expect("deposit failed");
+    let instructions = pool.transact_instructions(&withdraw, &depositor.pubkey());
+    pool.process(&instructions, &[&depositor]).await.expect("withdraw failed");
+
+    // The nullifier PDAs already exist, so the same proof can't be spent twice
+    let result = pool.process(&instructions, &[&depositor]).await;
+    assert!(result.is_err());
+    assert_eq!(pool.tree().await.next_index, 4);
+}
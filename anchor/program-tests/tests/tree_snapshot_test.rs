@@ -0,0 +1,29 @@
+use borsh::BorshDeserialize;
+use zkcash_program_tests::{PoolHarness, TreeSnapshotPage, MERKLE_TREE_HEIGHT, ROOT_HISTORY_SIZE};
+
+async fn read_page(pool: &mut PoolHarness, page: u16) -> TreeSnapshotPage {
+    let instruction = pool.export_tree_snapshot_instruction(page);
+    let report = pool.process_with_report(&[instruction], &[]).await.expect("export_tree_snapshot failed");
+    TreeSnapshotPage::try_from_slice(&report.return_data).expect("failed to decode snapshot page")
+}
+
+#[tokio::test]
+async fn test_exports_whole_tree_in_pages() {
+    let mut pool = PoolHarness::start().await;
+    let first = read_page(&mut pool, 0).await;
+
+    let mut bytes = first.bytes.clone();
+    for page in 1..first.total_pages {
+        let page = read_page(&mut pool, page).await;
+        assert_eq!(page.next_index, first.next_index);
+        bytes.extend(page.bytes);
+    }
+    // version, tree, height, next_index, root, subtrees, root history, root_index
+    let expected_len = 1 + 32 + 1 + 8 + 32 + 4 + 32 * MERKLE_TREE_HEIGHT + 4 + 32 * ROOT_HISTORY_SIZE + 8;
+    assert_eq!(bytes.len(), expected_len);
+    assert_eq!(bytes[1..33], pool.tree_account.to_bytes());
+    assert_eq!(bytes[42..74], pool.tree().await.root);
+
+    let past_end = pool.export_tree_snapshot_instruction(first.total_pages);
+    assert!(pool.process(&[past_end], &[]).await.is_err());
+}
@@ -0,0 +1,46 @@
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use zkcash_program_tests::{tree_storage_pda, AdminAction, PoolHarness};
+
+#[tokio::test]
+async fn test_split_moves_bulk_storage_out_of_the_tree() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+
+    // initialize creates v0, which has no room for the link
+    let split = pool.split_tree_storage_instruction();
+    assert!(pool.process(&[split], &[&authority]).await.is_err());
+
+    let migrate = pool.migrate_account_instruction();
+    pool.process(&[migrate], &[&authority]).await.expect("migrate_account failed");
+    assert_eq!(pool.tree_storage_link().await, None);
+
+    let split = pool.split_tree_storage_instruction();
+    pool.process(&[split], &[&authority]).await.expect("split_tree_storage failed");
+
+    let storage_key = tree_storage_pda(&pool.tree_account);
+    assert_eq!(pool.tree_storage_link().await, Some(storage_key));
+    let tree = pool.tree().await;
+    let storage = pool.tree_storage().await;
+    assert_eq!(storage.tree_account, pool.tree_account);
+    assert_eq!(storage.root_index, tree.root_index);
+    assert_eq!(storage.root_history[tree.root_index as usize], tree.root);
+    let entry = pool.audit_log().await.entries.pop().unwrap();
+    assert_eq!((entry.action, entry.target), (AdminAction::SplitTreeStorage, storage_key));
+
+    // The storage PDA already exists, and the tree is already linked
+    let again = pool.split_tree_storage_instruction();
+    assert!(pool.process(&[again], &[&authority]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_only_authority_splits_tree_storage() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let migrate = pool.migrate_account_instruction();
+    pool.process(&[migrate], &[&authority]).await.expect("migrate_account failed");
+
+    let other = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+    pool.authority = other.insecure_clone();
+    let split = pool.split_tree_storage_instruction();
+    assert!(pool.process(&[split], &[&other]).await.is_err());
+}
@@ -0,0 +1,59 @@
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, signature::Signer};
+use zkcash_program_tests::{PoolHarness, TransactFixture, MERKLE_TREE_ACCOUNT_V0_LEN, MERKLE_TREE_ACCOUNT_VERSION};
+
+#[tokio::test]
+async fn test_migrate_upgrades_tree_in_place() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let tree_account = pool.tree_account;
+
+    // initialize still creates the unversioned v0 layout
+    assert_eq!(pool.tree_version().await, (0, MERKLE_TREE_ACCOUNT_V0_LEN));
+    let tree_before = pool.tree().await;
+
+    let migrate = pool.migrate_account_instruction();
+    pool.process(&[migrate], &[&authority]).await.expect("migrate_account failed");
+
+    let (version, len) = pool.tree_version().await;
+    assert_eq!(version, MERKLE_TREE_ACCOUNT_VERSION);
+    assert!(len > MERKLE_TREE_ACCOUNT_V0_LEN);
+    let tree = pool.tree().await;
+    assert_eq!(tree.root, tree_before.root);
+    assert_eq!(tree.next_index, tree_before.next_index);
+    assert_eq!(tree.authority, tree_before.authority);
+    // Roots from before the migration have no known slot
+    assert!(pool.tree_root_slots().await.iter().all(|slot| *slot == 0));
+
+    // The authority paid for the extra bytes
+    let rent = pool.context.banks_client.get_rent().await.expect("failed to fetch rent");
+    assert!(pool.lamports(&tree_account).await >= rent.minimum_balance(len));
+
+    // Already current, so a second migration is rejected
+    let migrate = pool.migrate_account_instruction();
+    assert!(pool.process(&[migrate], &[&authority]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_migrated_tree_still_accepts_deposits() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+    let migrate = pool.migrate_account_instruction();
+    pool.process(&[migrate], &[&authority]).await.expect("migrate_account failed");
+
+    let deposit = TransactFixture::load("deposit");
+    let depositor = pool.funded_keypair(2 * LAMPORTS_PER_SOL).await;
+    let instructions = pool.transact_instructions(&deposit, &depositor.pubkey());
+    pool.process(&instructions, &[&depositor]).await.expect("deposit after migration failed");
+    assert_eq!(pool.tree().await.next_index, 2);
+}
+
+#[tokio::test]
+async fn test_only_the_authority_can_migrate() {
+    let mut pool = PoolHarness::start().await;
+    let outsider = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+
+    pool.authority = outsider.insecure_clone();
+    let migrate = pool.migrate_account_instruction();
+    assert!(pool.process(&[migrate], &[&outsider]).await.is_err());
+    assert_eq!(pool.tree_version().await.0, 0);
+}
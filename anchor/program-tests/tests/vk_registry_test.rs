@@ -0,0 +1,56 @@
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, signature::Signer};
+use zkcash_program_tests::{vk_rotation_pda, AdminAction, PoolHarness};
+
+#[tokio::test]
+async fn test_recorded_hash_matches_the_program_key() {
+    let mut pool = PoolHarness::start().await;
+    let payer = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+
+    let record = pool.record_verifying_key_instruction(&payer.pubkey());
+    pool.process(&[record], &[&payer]).await.expect("record_verifying_key failed");
+    let recorded = pool.verifying_key_record().await;
+    assert_ne!(recorded.vk_hash, [0u8; 32]);
+
+    // Recording again without an upgrade leaves the record as it was
+    let record = pool.record_verifying_key_instruction(&payer.pubkey());
+    pool.process(&[record], &[&payer]).await.expect("record_verifying_key failed");
+    assert_eq!(pool.verifying_key_record().await.updated_slot, recorded.updated_slot);
+
+    let assert = pool.assert_verifying_key_instruction(recorded.vk_hash);
+    pool.process(&[assert], &[]).await.expect("assert_verifying_key failed");
+}
+
+#[tokio::test]
+async fn test_assert_rejects_a_different_key() {
+    let mut pool = PoolHarness::start().await;
+    let assert = pool.assert_verifying_key_instruction([7u8; 32]);
+    assert!(pool.process(&[assert], &[]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_queued_key_waits_for_its_epoch() {
+    let mut pool = PoolHarness::start().await;
+    let authority = pool.authority.insecure_clone();
+
+    let queue = pool.queue_verifying_key_rotation_instruction([3u8; 32]);
+    pool.process(&[queue], &[&authority]).await.expect("queue_verifying_key_rotation failed");
+    let rotation = pool.vk_rotation().await;
+    assert_eq!(rotation.pending_hash, Some([3u8; 32]));
+    assert_ne!(rotation.active_hash, [0u8; 32]);
+    let entry = pool.audit_log().await.entries.pop().unwrap();
+    assert_eq!((entry.action, entry.target), (AdminAction::QueueVerifyingKeyRotation, vk_rotation_pda()));
+
+    let activate = pool.activate_verifying_key_rotation_instruction();
+    assert!(pool.process(&[activate], &[]).await.is_err());
+    assert_eq!(pool.vk_rotation().await.active_hash, rotation.active_hash);
+}
+
+#[tokio::test]
+async fn test_only_the_authority_queues_keys() {
+    let mut pool = PoolHarness::start().await;
+    let outsider = pool.funded_keypair(LAMPORTS_PER_SOL).await;
+
+    pool.authority = outsider.insecure_clone();
+    let queue = pool.queue_verifying_key_rotation_instruction([3u8; 32]);
+    assert!(pool.process(&[queue], &[&outsider]).await.is_err());
+}
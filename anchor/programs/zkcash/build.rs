@@ -0,0 +1,49 @@
+//! Generates the empty-subtree hash tables used by `merkle_tree` into
+//! `$OUT_DIR/zero_hashes.rs`, one per supported hasher.
+//!
+//! Level 0 is the empty leaf (32 zero bytes) and level `i + 1` is
+//! `H(level_i, level_i)`, so `TABLE[height]` is the root of an empty tree of
+//! that height.
+
+use ark_bn254::Fr;
+use light_poseidon::{Poseidon, PoseidonBytesHasher};
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+use std::fmt::Write;
+
+/// Highest tree height the tables cover.
+const MAX_HEIGHT: usize = 40;
+
+fn table(mut hash: impl FnMut(&[u8; 32]) -> [u8; 32]) -> Vec<[u8; 32]> {
+    let mut levels = vec![[0u8; 32]];
+    for i in 0..MAX_HEIGHT {
+        let next = hash(&levels[i]);
+        levels.push(next);
+    }
+    levels
+}
+
+fn write_table(out: &mut String, name: &str, levels: &[[u8; 32]]) {
+    writeln!(out, "pub const {}: [[u8; 32]; {}] = [", name, levels.len()).unwrap();
+    for level in levels {
+        writeln!(out, "    {:?},", level).unwrap();
+    }
+    writeln!(out, "];").unwrap();
+}
+
+fn main() {
+    let mut poseidon = Poseidon::<Fr>::new_circom(2).unwrap();
+    let poseidon = table(|zero| poseidon.hash_bytes_be(&[zero, zero]).unwrap());
+    let sha256 = table(|zero| Sha256::new().chain_update(zero).chain_update(zero).finalize().into());
+    let keccak = table(|zero| Keccak256::new().chain_update(zero).chain_update(zero).finalize().into());
+
+    let mut out = String::new();
+    writeln!(out, "pub const MAX_HEIGHT: usize = {};", MAX_HEIGHT).unwrap();
+    write_table(&mut out, "POSEIDON_ZERO_HASHES", &poseidon);
+    write_table(&mut out, "SHA256_ZERO_HASHES", &sha256);
+    write_table(&mut out, "KECCAK_ZERO_HASHES", &keccak);
+
+    let path = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("zero_hashes.rs");
+    std::fs::write(path, out).unwrap();
+    println!("cargo:rerun-if-changed=build.rs");
+}
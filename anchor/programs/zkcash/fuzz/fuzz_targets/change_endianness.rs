@@ -0,0 +1,6 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zkcash::fuzzing::change_endianness_roundtrip;
+
+fuzz_target!(|data: &[u8]| change_endianness_roundtrip(data));
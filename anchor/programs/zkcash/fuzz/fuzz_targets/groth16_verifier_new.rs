@@ -0,0 +1,6 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zkcash::fuzzing::{groth16_verifier_new, VerifierInput};
+
+fuzz_target!(|input: VerifierInput| groth16_verifier_new(&input));
@@ -0,0 +1,6 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zkcash::fuzzing::transact_data_bytes;
+
+fuzz_target!(|data: &[u8]| transact_data_bytes(data));
@@ -0,0 +1,6 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zkcash::fuzzing::{transact_data_structured, TransactInput};
+
+fuzz_target!(|input: TransactInput| transact_data_structured(&input));
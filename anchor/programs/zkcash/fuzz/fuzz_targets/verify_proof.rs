@@ -0,0 +1,6 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zkcash::fuzzing::verify_proof_bytes;
+
+fuzz_target!(|data: &[u8]| verify_proof_bytes(data));
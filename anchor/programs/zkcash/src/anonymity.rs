@@ -0,0 +1,116 @@
+use anchor_lang::prelude::*;
+use crate::errors::PoolError;
+use crate::{ErrorCode, MerkleTreeAccount};
+
+/// Epochs of deposit counts kept, and so the longest window
+/// `anonymity_metrics` can report on.
+pub const DEPOSIT_EPOCH_WINDOW: usize = 8;
+
+/// Counters behind `anonymity_metrics`, at `["anonymity_stats"]`. Kept apart
+/// from `PoolStats` so that account's layout doesn't change.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct AnonymityStats {
+    /// Transacts that must have spent at least one real note: withdrawals,
+    /// and transfers paying a fee. Deposits and free transfers can spend
+    /// only zero-amount dummies, so they aren't counted.
+    pub spent_lower_bound: u64,
+    /// Ring of `(epoch, deposits)`, indexed by `epoch % DEPOSIT_EPOCH_WINDOW`.
+    pub deposit_epochs: [u64; DEPOSIT_EPOCH_WINDOW],
+    pub deposit_counts: [u64; DEPOSIT_EPOCH_WINDOW],
+    pub bump: u8,
+}
+
+/// What `anonymity_metrics` hands back through return data.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AnonymityMetrics {
+    /// Leaves in the tree, i.e. notes ever created.
+    pub leaf_count: u64,
+    /// Leaves the tree can hold, `2^height`.
+    pub capacity: u64,
+    /// `leaf_count` over `capacity`, in basis points rounded down.
+    pub occupancy_bps: u16,
+    /// At most this many notes are unspent. Zero-amount padding notes are
+    /// leaves too, so the real figure is lower.
+    pub unspent_upper_bound: u64,
+    /// Deposits in the current epoch and the `epochs - 1` before it.
+    pub recent_deposits: u64,
+    pub epochs: u8,
+}
+
+impl AnonymityStats {
+    /// Records one transact. Must be called from `transact` after the
+    /// transfers succeeded, with the same `ext_amount` and `fee`.
+    pub fn record(&mut self, ext_amount: i64, fee: u64, epoch: u64) -> Result<()> {
+        if ext_amount > 0 {
+            let slot = epoch as usize % DEPOSIT_EPOCH_WINDOW;
+            if self.deposit_epochs[slot] != epoch {
+                self.deposit_epochs[slot] = epoch;
+                self.deposit_counts[slot] = 0;
+            }
+            self.deposit_counts[slot] = self.deposit_counts[slot].checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        } else if ext_amount < 0 || fee > 0 {
+            self.spent_lower_bound = self.spent_lower_bound.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        }
+        Ok(())
+    }
+
+    /// Deposits in `epoch` and the `epochs - 1` epochs before it.
+    pub fn deposits_in_window(&self, epoch: u64, epochs: u8) -> Result<u64> {
+        require!(
+            (1..=DEPOSIT_EPOCH_WINDOW).contains(&(epochs as usize)),
+            PoolError::InvalidMetricsWindow
+        );
+        Ok(self
+            .deposit_epochs
+            .iter()
+            .zip(self.deposit_counts)
+            .filter(|(recorded, _)| **recorded <= epoch && epoch - **recorded < epochs as u64)
+            .map(|(_, count)| count)
+            .sum())
+    }
+
+    pub fn metrics(&self, tree: &MerkleTreeAccount, epoch: u64, epochs: u8) -> Result<AnonymityMetrics> {
+        let capacity = 1u64 << tree.height;
+        Ok(AnonymityMetrics {
+            leaf_count: tree.next_index,
+            capacity,
+            occupancy_bps: (tree.next_index as u128 * 10_000 / capacity as u128) as u16,
+            unspent_upper_bound: tree.next_index.saturating_sub(self.spent_lower_bound),
+            recent_deposits: self.deposits_in_window(epoch, epochs)?,
+            epochs,
+        })
+    }
+}
+
+/// Creates the stats PDA. Counts only cover transacts after creation, so on
+/// an older pool the unspent bound starts out looser.
+#[derive(Accounts)]
+pub struct InitializeAnonymityStats<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + AnonymityStats::INIT_SPACE,
+        seeds = [b"anonymity_stats"],
+        bump
+    )]
+    pub anonymity_stats: Account<'info, AnonymityStats>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Permissionless and read-only, so frontends can run it by simulation.
+#[derive(Accounts)]
+pub struct GetAnonymityMetrics<'info> {
+    #[account(
+        seeds = [b"merkle_tree"],
+        bump = tree_account.load()?.bump
+    )]
+    pub tree_account: AccountLoader<'info, MerkleTreeAccount>,
+    #[account(
+        seeds = [b"anonymity_stats"],
+        bump = anonymity_stats.bump
+    )]
+    pub anonymity_stats: Account<'info, AnonymityStats>,
+}
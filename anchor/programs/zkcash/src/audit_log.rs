@@ -0,0 +1,139 @@
+use anchor_lang::prelude::*;
+use crate::ErrorCode;
+use crate::PoolStatus;
+
+/// Entries kept on-chain. Older ones are evicted, but every entry is also
+/// emitted as `AdminActionRecorded` and `sequence` has no gaps, so a missing
+/// entry is always detectable.
+pub const MAX_AUDIT_ENTRIES: usize = 64;
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdminAction {
+    QueueConfigUpdate,
+    ExecuteConfigUpdate,
+    CancelConfigUpdate,
+    ProposeAuthority,
+    AcceptAuthority,
+    MigrateAccount,
+    PausePool,
+    ResumePool,
+    /// How a tree is rotated out; see `PoolStatus::Retired`.
+    RetirePool,
+    SetFeeRecipient,
+    /// Recorded when `execute_fee_schedule` applies a queued schedule.
+    SetFeeSchedule,
+    /// Recorded when `execute_fee_exemptions` applies a queued list.
+    SetFeeExemptions,
+    SlashRelayer,
+    BeginNullifierSeedMigration,
+    RescueTokens,
+    SetRootPolicy,
+    InitializeRootHistory,
+    SetOutputStorageMode,
+    QueueVerifyingKeyRotation,
+    SetMinCircuitVersion,
+    SplitTreeStorage,
+    SetLeafStorage,
+    InitializeRootCheckpoints,
+    SetGuardian,
+    SetRole,
+    SetFeePayout,
+    SweepFees,
+    QueueFeeSchedule,
+    CancelFeeSchedule,
+    QueueFeeExemptions,
+    CancelFeeExemptions,
+    ConfigurePoolFactory,
+    SetMaxEncryptedOutputLen,
+    UpdateCircuitBreaker,
+    ResumeWithdrawals,
+    SetPoolFreeze,
+    ConfigureYield,
+    ConfigureLstPool,
+    SetSwapPrograms,
+    SetCpiPrograms,
+    QueueDepositLimits,
+    /// Recorded when `execute_deposit_limits` applies queued limits.
+    SetDepositLimits,
+    CancelDepositLimits,
+}
+
+impl AdminAction {
+    pub fn for_pool_status(status: PoolStatus) -> AdminAction {
+        match status {
+            PoolStatus::Active => AdminAction::ResumePool,
+            PoolStatus::Paused => AdminAction::PausePool,
+            PoolStatus::Retired => AdminAction::RetirePool,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AuditEntry {
+    pub sequence: u64,
+    pub slot: u64,
+    /// The authority that signed, or for permissionless steps like
+    /// `execute_config_update`, the authority whose action took effect.
+    pub actor: Pubkey,
+    pub action: AdminAction,
+    /// What the action applied to: the pool's tree, the proposed authority,
+    /// the new fee recipient, and so on.
+    pub target: Pubkey,
+}
+
+/// Append-only record of authority-gated actions, so governance can be
+/// checked from one account instead of transaction history. Every
+/// instruction that records here takes the log as a required account.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct AuditLog {
+    #[max_len(MAX_AUDIT_ENTRIES)]
+    pub entries: Vec<AuditEntry>,
+    /// Sequence number the next entry gets, i.e. the number of actions ever
+    /// recorded.
+    pub next_sequence: u64,
+    pub bump: u8,
+}
+
+#[event]
+pub struct AdminActionRecorded {
+    pub entry: AuditEntry,
+}
+
+impl AuditLog {
+    pub fn record(&mut self, actor: Pubkey, action: AdminAction, target: Pubkey, slot: u64) -> Result<()> {
+        let entry = AuditEntry {
+            sequence: self.next_sequence,
+            slot,
+            actor,
+            action,
+            target,
+        };
+        self.next_sequence = self.next_sequence
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        if self.entries.len() == MAX_AUDIT_ENTRIES {
+            self.entries.remove(0);
+        }
+        self.entries.push(entry);
+        emit!(AdminActionRecorded { entry });
+        Ok(())
+    }
+}
+
+/// Creates the audit log PDA. Separate from `initialize` so pools deployed
+/// before the log existed can add it.
+#[derive(Accounts)]
+pub struct InitializeAuditLog<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + AuditLog::INIT_SPACE,
+        seeds = [b"audit_log"],
+        bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+use crate::{AuditLog, ErrorCode, GlobalConfig, MerkleTreeAccount, TreeTokenAccount};
+
+/// An authority handover waiting for the new authority to accept.
+///
+/// `ADMIN_PUBKEY` only gates `initialize`; every later admin check is against
+/// the authority stored in the pool accounts. Handing that stored authority to
+/// a Squads vault or a Realms governance PDA is how a pool migrates off the
+/// compile-time key. Accepting has to be signed by the new authority, which
+/// PDAs do through `invoke_signed` when their proposal executes, so a typo in
+/// `new_authority` can't lock the pool.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct PendingAuthority {
+    pub authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub bump: u8,
+}
+
+#[event]
+pub struct AuthorityTransferProposed {
+    pub authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferred {
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + PendingAuthority::INIT_SPACE,
+        seeds = [b"pending_authority"],
+        bump
+    )]
+    pub pending_authority: Account<'info, PendingAuthority>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
+
+/// Moves the stored authority on all three pool accounts at once, so they
+/// can't end up with different admins.
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"pending_authority"],
+        bump = pending_authority.bump,
+        has_one = new_authority @ ErrorCode::Unauthorized,
+        close = new_authority
+    )]
+    pub pending_authority: Account<'info, PendingAuthority>,
+    #[account(
+        mut,
+        seeds = [b"merkle_tree"],
+        bump = tree_account.load()?.bump,
+        constraint = tree_account.load()?.authority == pending_authority.authority @ ErrorCode::Unauthorized
+    )]
+    pub tree_account: AccountLoader<'info, MerkleTreeAccount>,
+    #[account(
+        mut,
+        seeds = [b"tree_token"],
+        bump = tree_token_account.bump,
+        constraint = tree_token_account.authority == pending_authority.authority @ ErrorCode::Unauthorized
+    )]
+    pub tree_token_account: Account<'info, TreeTokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        constraint = global_config.authority == pending_authority.authority @ ErrorCode::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(mut)]
+    pub new_authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+use crate::{ErrorCode, PoolStats, TreeTokenAccount, YieldConfig};
+
+/// What `audit_balances` hands back through return data.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BalanceAudit {
+    /// Lamports the tree token account holds above rent, plus any staked
+    /// through `YieldConfig` at cost.
+    pub actual_lamports: u64,
+    /// Deposits minus withdrawals and fees, from `PoolStats`.
+    pub expected_lamports: u64,
+    /// `actual_lamports - expected_lamports`. A surplus is benign: funds
+    /// sent in directly, realized yield, or activity from before the stats
+    /// account existed. A deficit means funds left the pool unaccounted for.
+    pub drift: i128,
+}
+
+#[event]
+pub struct BalancesAudited {
+    pub actual_lamports: u64,
+    pub expected_lamports: u64,
+    pub drift: i128,
+    pub slot: u64,
+}
+
+impl BalanceAudit {
+    pub fn new(stats: &PoolStats, liquid_lamports: u64, staked_lamports: u64) -> Result<Self> {
+        let actual_lamports = liquid_lamports
+            .checked_add(staked_lamports)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        // Fees are paid out of the pool on deposits too, so they come off
+        // the total either way
+        let expected = stats.total_deposited as i128 - stats.total_withdrawn as i128 - stats.total_fees as i128;
+        Ok(BalanceAudit {
+            actual_lamports,
+            expected_lamports: u64::try_from(expected.max(0)).map_err(|_| ErrorCode::ArithmeticOverflow)?,
+            drift: actual_lamports as i128 - expected,
+        })
+    }
+
+    pub fn has_deficit(&self) -> bool {
+        self.drift < 0
+    }
+}
+
+/// Permissionless and read-only, so monitors can run it by simulation.
+#[derive(Accounts)]
+pub struct AuditBalances<'info> {
+    #[account(
+        seeds = [b"pool_stats"],
+        bump = pool_stats.bump
+    )]
+    pub pool_stats: Account<'info, PoolStats>,
+    #[account(
+        seeds = [b"tree_token"],
+        bump = tree_token_account.bump
+    )]
+    pub tree_token_account: Account<'info, TreeTokenAccount>,
+    /// Only needed once idle SOL has been staked.
+    #[account(
+        seeds = [b"yield_config"],
+        bump = yield_config.bump
+    )]
+    pub yield_config: Option<Account<'info, YieldConfig>>,
+}
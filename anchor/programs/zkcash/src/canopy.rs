@@ -0,0 +1,68 @@
+//! Canopy: the top levels of a concurrent tree kept in its account.
+//!
+//! With the top `CANOPY_DEPTH` levels stored on-chain, a proof only needs the
+//! `depth - CANOPY_DEPTH` siblings below them and the program fills in the
+//! rest. That shortens the proofs integrations pass in and the paths clients
+//! have to keep in sync.
+//!
+//! Nodes are stored level by level from just under the root down, left to
+//! right, without the root itself: the root's two children first, then their
+//! four children, and so on. Slots no append has written yet stay zeroed and
+//! read as the empty subtree at their level.
+
+use crate::errors::PoolError;
+use crate::zero_hashes::ZeroHashes;
+use crate::ChangeLog;
+use anchor_lang::prelude::*;
+
+pub const CANOPY_DEPTH: usize = 8;
+
+pub const CANOPY_NODES: usize = canopy_node_count(CANOPY_DEPTH);
+
+/// Nodes in a canopy of `canopy_depth` levels.
+pub const fn canopy_node_count(canopy_depth: usize) -> usize {
+    (1 << (canopy_depth + 1)) - 2
+}
+
+/// Slot of the node at `level` (0 being the leaves) above leaf `index`.
+fn canopy_slot(max_depth: usize, level: usize, index: u64) -> usize {
+    let depth_from_root = max_depth - level;
+    (1 << depth_from_root) - 2 + (index >> level) as usize
+}
+
+/// Levels of a `max_depth` tree a canopy of `canopy.len()` nodes covers.
+fn canopy_depth_of(canopy: &[[u8; 32]]) -> usize {
+    (canopy.len() + 2).trailing_zeros() as usize - 1
+}
+
+/// Writes the nodes `change_log` changed in the canopy's levels. Call after
+/// every append.
+pub fn update_canopy<const MAX_DEPTH: usize>(canopy: &mut [[u8; 32]], change_log: &ChangeLog<MAX_DEPTH>) {
+    let canopy_depth = canopy_depth_of(canopy);
+    for level in MAX_DEPTH - canopy_depth..MAX_DEPTH {
+        canopy[canopy_slot(MAX_DEPTH, level, change_log.index)] = change_log.path[level];
+    }
+}
+
+/// Completes a proof of the lower `MAX_DEPTH - canopy depth` siblings with the
+/// siblings stored in the canopy. Full-length proofs are returned unchanged.
+pub fn fill_in_proof_from_canopy<H: ZeroHashes, const MAX_DEPTH: usize>(
+    canopy: &[[u8; 32]],
+    index: u64,
+    proof: &[[u8; 32]],
+) -> Result<[[u8; 32]; MAX_DEPTH]> {
+    let lower_levels = MAX_DEPTH - canopy_depth_of(canopy);
+    require!(
+        proof.len() == lower_levels || proof.len() == MAX_DEPTH,
+        PoolError::InvalidProofLength
+    );
+
+    let zero_hashes = H::zero_hashes_for_height::<MAX_DEPTH>()?;
+    let mut full = [[0u8; 32]; MAX_DEPTH];
+    full[..proof.len()].copy_from_slice(proof);
+    for level in proof.len()..MAX_DEPTH {
+        let node = canopy[canopy_slot(MAX_DEPTH, level, index ^ (1 << level))];
+        full[level] = if node == [0u8; 32] { zero_hashes[level] } else { node };
+    }
+    Ok(full)
+}
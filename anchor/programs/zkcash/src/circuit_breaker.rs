@@ -0,0 +1,256 @@
+use anchor_lang::prelude::*;
+use crate::errors::PoolError;
+use crate::{AuditLog, ErrorCode, GlobalConfig};
+
+/// Halts withdrawals when outflow over a sliding window exceeds a fraction of
+/// the pool's balance, bounding what a soundness bug could drain before a
+/// guardian looks at it.
+///
+/// The window is approximated with two fixed buckets: outflow in the current
+/// window plus the previous window's outflow, weighted by how much of it still
+/// overlaps. A withdrawal larger than the whole limit is rejected outright. The
+/// withdrawal that pushes the window over the limit still goes through, since
+/// rejecting it would also roll back the pause; every withdrawal after it fails
+/// until the guardian calls `resume_withdrawals`.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct CircuitBreaker {
+    pub guardian: Pubkey,
+    /// Limit per window, in basis points of the pool balance.
+    pub max_outflow_bps: u16,
+    pub window_slots: u64,
+    pub window_start: u64,
+    pub window_outflow: u64,
+    pub previous_window_outflow: u64,
+    pub withdrawals_paused: bool,
+    pub bump: u8,
+}
+
+#[event]
+pub struct CircuitBreakerTripped {
+    pub window_outflow: u64,
+    pub limit: u64,
+    pub pool_balance: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct WithdrawalsResumed {
+    pub guardian: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct GuardianUpdated {
+    pub previous_guardian: Pubkey,
+    pub guardian: Pubkey,
+}
+
+/// The guardian can't be cleared: without one, nobody could resume a
+/// tripped breaker.
+pub fn validate_guardian(guardian: &Pubkey) -> Result<()> {
+    require!(*guardian != Pubkey::default(), PoolError::InvalidGuardian);
+    Ok(())
+}
+
+pub fn validate_circuit_breaker_config(max_outflow_bps: u16, window_slots: u64) -> Result<()> {
+    require!(
+        max_outflow_bps > 0 && max_outflow_bps <= 10000 && window_slots > 0,
+        PoolError::InvalidCircuitBreakerConfig
+    );
+    Ok(())
+}
+
+impl CircuitBreaker {
+    /// Moves the window forward so that it contains `slot`.
+    fn roll_window(&mut self, slot: u64) {
+        let elapsed = slot.saturating_sub(self.window_start);
+        if elapsed < self.window_slots {
+            return;
+        }
+        let windows = elapsed / self.window_slots;
+        self.previous_window_outflow = if windows == 1 { self.window_outflow } else { 0 };
+        self.window_outflow = 0;
+        self.window_start += windows * self.window_slots;
+    }
+
+    /// Sliding-window outflow at `slot`, which must be inside the current window.
+    fn estimated_outflow(&self, slot: u64) -> u64 {
+        let remaining = self.window_slots - slot.saturating_sub(self.window_start);
+        let carried = self.previous_window_outflow as u128 * remaining as u128 / self.window_slots as u128;
+        self.window_outflow.saturating_add(carried as u64)
+    }
+
+    pub fn outflow_limit(&self, pool_balance: u64) -> u64 {
+        (pool_balance as u128 * self.max_outflow_bps as u128 / 10000) as u64
+    }
+
+    /// Records `amount` lamports leaving a pool that held `pool_balance` before
+    /// the transfer. Must be called from `transact` before paying out the
+    /// recipient and fee recipient. Returns whether this call tripped the breaker.
+    pub fn record_outflow(&mut self, amount: u64, pool_balance: u64, slot: u64) -> Result<bool> {
+        require!(!self.withdrawals_paused, PoolError::WithdrawalsPaused);
+        if amount == 0 {
+            return Ok(false);
+        }
+
+        let limit = self.outflow_limit(pool_balance);
+        require!(amount <= limit, PoolError::OutflowLimitExceeded);
+
+        self.roll_window(slot);
+        self.window_outflow = self.window_outflow
+            .checked_add(amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        let window_outflow = self.estimated_outflow(slot);
+        if window_outflow <= limit {
+            return Ok(false);
+        }
+
+        self.withdrawals_paused = true;
+        msg!("Circuit breaker tripped: {} lamports out this window, limit {}", window_outflow, limit);
+        emit!(CircuitBreakerTripped {
+            window_outflow,
+            limit,
+            pool_balance,
+            slot,
+        });
+        Ok(true)
+    }
+
+    /// Clears the pause and starts a fresh window at `slot`.
+    pub fn resume(&mut self, slot: u64) {
+        self.withdrawals_paused = false;
+        self.window_start = slot;
+        self.window_outflow = 0;
+        self.previous_window_outflow = 0;
+    }
+}
+
+/// Manual freezes the guardian sets during an incident, at `["pool_freeze"]`.
+/// Unlike pausing the pool in the registry, each direction is halted on its
+/// own: freezing withdrawals keeps deposits open and vice versa. Transfers
+/// inside the pool move no funds in or out and are never frozen here.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct PoolFreeze {
+    pub withdrawals_frozen: bool,
+    pub deposits_frozen: bool,
+    pub bump: u8,
+}
+
+#[event]
+pub struct PoolFreezeUpdated {
+    pub guardian: Pubkey,
+    pub withdrawals_frozen: bool,
+    pub deposits_frozen: bool,
+    pub slot: u64,
+}
+
+impl PoolFreeze {
+    /// Must be called from `transact` before any transfer.
+    pub fn check(&self, ext_amount: i64) -> Result<()> {
+        if ext_amount < 0 {
+            require!(!self.withdrawals_frozen, PoolError::WithdrawalsFrozen);
+        } else if ext_amount > 0 {
+            require!(!self.deposits_frozen, PoolError::DepositsFrozen);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeCircuitBreaker<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + CircuitBreaker::INIT_SPACE,
+        seeds = [b"circuit_breaker"],
+        bump
+    )]
+    pub circuit_breaker: Account<'info, CircuitBreaker>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCircuitBreaker<'info> {
+    #[account(
+        mut,
+        seeds = [b"circuit_breaker"],
+        bump = circuit_breaker.bump,
+        has_one = guardian @ ErrorCode::Unauthorized
+    )]
+    pub circuit_breaker: Account<'info, CircuitBreaker>,
+    pub guardian: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
+
+/// Guardian-only, so the same key that resumes the breaker can freeze
+/// either direction without waiting on the authority.
+#[derive(Accounts)]
+pub struct SetPoolFreeze<'info> {
+    #[account(
+        init_if_needed,
+        payer = guardian,
+        space = 8 + PoolFreeze::INIT_SPACE,
+        seeds = [b"pool_freeze"],
+        bump
+    )]
+    pub pool_freeze: Account<'info, PoolFreeze>,
+    #[account(
+        seeds = [b"circuit_breaker"],
+        bump = circuit_breaker.bump,
+        has_one = guardian @ ErrorCode::Unauthorized
+    )]
+    pub circuit_breaker: Account<'info, CircuitBreaker>,
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
+
+/// Hands the guardian role to a key other than the authority. The guardian
+/// can only halt and resume flows and slash relayer bonds into the
+/// treasury: fees, keys and pool funds stay behind `global_config.authority`,
+/// so it can be held with lighter operational security, e.g. by an on-call
+/// hot wallet.
+#[derive(Accounts)]
+pub struct SetGuardian<'info> {
+    #[account(
+        mut,
+        seeds = [b"circuit_breaker"],
+        bump = circuit_breaker.bump
+    )]
+    pub circuit_breaker: Account<'info, CircuitBreaker>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
@@ -0,0 +1,129 @@
+use anchor_lang::prelude::*;
+use crate::errors::PoolError;
+use crate::groth16::Groth16Verifyingkey;
+use crate::utils::{verify_proof_with_inputs, VERIFYING_KEY};
+use crate::{AuditLog, ErrorCode, GlobalConfig, Proof};
+
+/// `transaction2.circom`: seven public inputs and no version among them.
+pub const CIRCUIT_VERSION_LEGACY: u8 = 1;
+
+/// `transaction3.circom`: the legacy inputs, then `protocolVersion` as an
+/// eighth public input, so a proof is only valid for the version it was
+/// generated for.
+pub const CIRCUIT_VERSION_V2: u8 = 2;
+
+/// Newest circuit the program understands.
+pub const CIRCUIT_VERSION: u8 = CIRCUIT_VERSION_V2;
+
+/// Verifying key for `version`, if its setup has been run and the key
+/// compiled in. The v2 key is added once its ceremony completes; until then
+/// v2 proofs are rejected as unsupported.
+pub fn circuit_verifying_key(version: u8) -> Option<Groth16Verifyingkey<'static>> {
+    match version {
+        CIRCUIT_VERSION_LEGACY => Some(VERIFYING_KEY),
+        _ => None,
+    }
+}
+
+/// The public inputs of a versioned circuit: the proof's seven, then the
+/// version as a big-endian field element.
+pub fn versioned_public_inputs(proof: &Proof, version: u8) -> [[u8; 32]; 8] {
+    let [root, public_amount, ext_data_hash, nullifier0, nullifier1, commitment0, commitment1] = proof.public_inputs();
+    let mut version_input = [0u8; 32];
+    version_input[31] = version;
+    [
+        root,
+        public_amount,
+        ext_data_hash,
+        nullifier0,
+        nullifier1,
+        commitment0,
+        commitment1,
+        version_input,
+    ]
+}
+
+/// Verifies `proof` as generated by circuit `version`. For v2 and later the
+/// version is a public input, so a proof claimed under the wrong version
+/// fails the pairing.
+pub fn verify_versioned_proof(proof: &Proof, version: u8) -> Result<()> {
+    let verifying_key = circuit_verifying_key(version).ok_or(PoolError::UnsupportedCircuitVersion)?;
+    if version == CIRCUIT_VERSION_LEGACY {
+        return verify_proof_with_inputs(proof, &proof.public_inputs(), &verifying_key);
+    }
+    verify_proof_with_inputs(proof, &versioned_public_inputs(proof, version), &verifying_key)
+}
+
+/// Oldest circuit transact still accepts, at `["circuit_version_config"]`.
+/// Raising it retires a circuit, e.g. after a soundness bug; without the
+/// account every supported version is accepted.
+#[account]
+#[derive(InitSpace, Debug)]
+pub struct CircuitVersionConfig {
+    pub min_version: u8,
+    pub bump: u8,
+}
+
+impl Default for CircuitVersionConfig {
+    fn default() -> Self {
+        CircuitVersionConfig {
+            min_version: CIRCUIT_VERSION_LEGACY,
+            bump: 0,
+        }
+    }
+}
+
+#[event]
+pub struct MinCircuitVersionUpdated {
+    pub min_version: u8,
+}
+
+impl CircuitVersionConfig {
+    /// Fails with `DeprecatedCircuitVersion` below `min_version` and with
+    /// `UnsupportedCircuitVersion` for versions the program has no key for.
+    pub fn check(&self, version: u8) -> Result<()> {
+        require!(version >= self.min_version, PoolError::DeprecatedCircuitVersion);
+        require!(
+            version <= CIRCUIT_VERSION && circuit_verifying_key(version).is_some(),
+            PoolError::UnsupportedCircuitVersion
+        );
+        Ok(())
+    }
+}
+
+/// A minimum must itself be verifiable, or no proof could be accepted.
+pub fn validate_min_circuit_version(min_version: u8) -> Result<()> {
+    require!(
+        (CIRCUIT_VERSION_LEGACY..=CIRCUIT_VERSION).contains(&min_version)
+            && circuit_verifying_key(min_version).is_some(),
+        PoolError::InvalidCircuitVersion
+    );
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetMinCircuitVersion<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + CircuitVersionConfig::INIT_SPACE,
+        seeds = [b"circuit_version_config"],
+        bump
+    )]
+    pub circuit_version_config: Account<'info, CircuitVersionConfig>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
@@ -0,0 +1,253 @@
+//! Concurrent Merkle tree backend, following the layout of
+//! spl-account-compression's `ConcurrentMerkleTree` but hashing with any
+//! `ZeroHashes` hasher so roots match `MerkleTree` for the same leaves.
+//!
+//! Besides the current root the tree keeps a ring buffer of the last
+//! `MAX_BUFFER_SIZE` change logs (each append's new root and the nodes on the
+//! appended leaf's path). A proof built against any root still in the buffer
+//! can be fast-forwarded to the current root on-chain, so a client whose
+//! proof raced other appends does not have to refetch and resubmit. Appends
+//! themselves need no proof: the tree keeps the rightmost leaf's path.
+//!
+//! The account is about 70 KiB at the pool's height, canopy included, so it
+//! is created by the client with `create_account` and handed to
+//! `initialize_concurrent_tree`, behind the `concurrent-tree` feature.
+
+use crate::zero_hashes::ZeroHashes;
+#[cfg(feature = "concurrent-tree")]
+use crate::canopy::{fill_in_proof_from_canopy, update_canopy, CANOPY_DEPTH, CANOPY_NODES};
+use crate::{ErrorCode, GlobalConfig};
+use anchor_lang::prelude::*;
+use bytemuck::{Pod, Zeroable};
+
+pub const CONCURRENT_TREE_DEPTH: usize = 26;
+
+/// Roots a proof may lag behind and still be fast-forwarded.
+pub const CONCURRENT_TREE_BUFFER_SIZE: usize = 64;
+
+/// The nodes one append wrote, leaf first, and the root it produced.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChangeLog<const MAX_DEPTH: usize> {
+    pub root: [u8; 32],
+    pub path: [[u8; 32]; MAX_DEPTH],
+    pub index: u64,
+}
+
+/// A leaf and its siblings from the bottom level up.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Path<const MAX_DEPTH: usize> {
+    pub proof: [[u8; 32]; MAX_DEPTH],
+    pub leaf: [u8; 32],
+    pub index: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConcurrentMerkleTree<const MAX_DEPTH: usize, const MAX_BUFFER_SIZE: usize> {
+    /// Number of appends so far.
+    pub sequence_number: u64,
+    /// Slot of `change_logs` holding the current root.
+    pub active_index: u64,
+    /// Filled slots of `change_logs`, up to `MAX_BUFFER_SIZE`.
+    pub buffer_size: u64,
+    pub change_logs: [ChangeLog<MAX_DEPTH>; MAX_BUFFER_SIZE],
+    /// Path of the last appended leaf; `index` is the next free leaf.
+    pub rightmost_proof: Path<MAX_DEPTH>,
+}
+
+// Every field is a byte array or a u64 and each struct's size is a multiple of
+// 8, so there is no padding and any bit pattern is valid.
+unsafe impl<const D: usize> Zeroable for ChangeLog<D> {}
+unsafe impl<const D: usize> Pod for ChangeLog<D> {}
+unsafe impl<const D: usize> Zeroable for Path<D> {}
+unsafe impl<const D: usize> Pod for Path<D> {}
+unsafe impl<const D: usize, const B: usize> Zeroable for ConcurrentMerkleTree<D, B> {}
+unsafe impl<const D: usize, const B: usize> Pod for ConcurrentMerkleTree<D, B> {}
+
+fn hash_pair<H: ZeroHashes>(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    H::hashv(&[left, right]).unwrap()
+}
+
+impl<const MAX_DEPTH: usize, const MAX_BUFFER_SIZE: usize> ConcurrentMerkleTree<MAX_DEPTH, MAX_BUFFER_SIZE> {
+    pub fn initialize<H: ZeroHashes>(&mut self) -> Result<()> {
+        let path = *H::zero_hashes_for_height::<MAX_DEPTH>()?;
+        let root = H::zero_hash(MAX_DEPTH)?;
+
+        self.sequence_number = 0;
+        self.active_index = 0;
+        self.buffer_size = 1;
+        self.change_logs[0] = ChangeLog { root, path, index: 0 };
+        self.rightmost_proof = Path {
+            proof: path,
+            leaf: [0u8; 32],
+            index: 0,
+        };
+        Ok(())
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.change_logs[self.active_index as usize].root
+    }
+
+    pub fn next_index(&self) -> u64 {
+        self.rightmost_proof.index
+    }
+
+    /// The change log of the last append, or of `initialize`.
+    pub fn latest_change_log(&self) -> &ChangeLog<MAX_DEPTH> {
+        self.change_log_at(0)
+    }
+
+    /// Appends `leaf` at `next_index` and returns its Merkle proof.
+    pub fn append<H: ZeroHashes>(&mut self, leaf: [u8; 32]) -> Result<[[u8; 32]; MAX_DEPTH]> {
+        let index = self.rightmost_proof.index;
+        require!(index < 1u64 << MAX_DEPTH, ErrorCode::MerkleTreeFull);
+
+        // Below `intersection` the new leaf's siblings are empty subtrees;
+        // at it, the sibling is the previous leaf's ancestor; above it, the
+        // siblings are shared with the previous leaf.
+        let intersection = if index == 0 {
+            MAX_DEPTH
+        } else {
+            (index.trailing_zeros() as usize).min(MAX_DEPTH)
+        };
+        let zero_hashes = H::zero_hashes_for_height::<MAX_DEPTH>()?;
+        let mut previous = self.rightmost_proof.leaf;
+        let mut node = leaf;
+        let mut path = [[0u8; 32]; MAX_DEPTH];
+        let mut proof = self.rightmost_proof.proof;
+
+        for level in 0..MAX_DEPTH {
+            path[level] = node;
+            if level < intersection {
+                // The previous leaf is a right child on every level below
+                if index > 0 {
+                    previous = hash_pair::<H>(&proof[level], &previous);
+                }
+                proof[level] = zero_hashes[level];
+                node = hash_pair::<H>(&node, &proof[level]);
+            } else if level == intersection {
+                proof[level] = previous;
+                node = hash_pair::<H>(&previous, &node);
+            } else if (index >> level) & 1 == 1 {
+                node = hash_pair::<H>(&proof[level], &node);
+            } else {
+                node = hash_pair::<H>(&node, &proof[level]);
+            }
+        }
+
+        self.sequence_number = self.sequence_number
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        self.active_index = (self.active_index + 1) % MAX_BUFFER_SIZE as u64;
+        self.buffer_size = (self.buffer_size + 1).min(MAX_BUFFER_SIZE as u64);
+        self.change_logs[self.active_index as usize] = ChangeLog { root: node, path, index };
+        self.rightmost_proof = Path {
+            proof,
+            leaf,
+            index: index + 1,
+        };
+        Ok(proof)
+    }
+
+    /// Whether `leaf` is at `index` in the current tree, given a `proof`
+    /// against `root`. `root` may be any root still in the change log buffer;
+    /// the proof is updated with every later append before checking.
+    pub fn verify_leaf<H: ZeroHashes>(
+        &self,
+        root: [u8; 32],
+        leaf: [u8; 32],
+        proof: &[[u8; 32]; MAX_DEPTH],
+        index: u64,
+    ) -> bool {
+        if root == [0u8; 32] || index >= self.rightmost_proof.index {
+            return false;
+        }
+        let Some(age) = (0..self.buffer_size).find(|age| self.change_log_at(*age).root == root) else {
+            return false;
+        };
+
+        let mut proof = *proof;
+        for later in (0..age).rev() {
+            let change_log = self.change_log_at(later);
+            if change_log.index == index {
+                // Appends only write empty leaves, so the leaf was not in `root`
+                return false;
+            }
+            // The level where the paths meet; the changed node there is our sibling
+            let level = 63 - (index ^ change_log.index).leading_zeros() as usize;
+            proof[level] = change_log.path[level];
+        }
+
+        let mut node = leaf;
+        for (level, sibling) in proof.iter().enumerate() {
+            node = if (index >> level) & 1 == 1 {
+                hash_pair::<H>(sibling, &node)
+            } else {
+                hash_pair::<H>(&node, sibling)
+            };
+        }
+        node == self.root()
+    }
+
+    /// The change log `age` appends before the current one.
+    fn change_log_at(&self, age: u64) -> &ChangeLog<MAX_DEPTH> {
+        let buffer = MAX_BUFFER_SIZE as u64;
+        &self.change_logs[((self.active_index + buffer - age) % buffer) as usize]
+    }
+}
+
+pub type PoolConcurrentMerkleTree = ConcurrentMerkleTree<CONCURRENT_TREE_DEPTH, CONCURRENT_TREE_BUFFER_SIZE>;
+
+#[cfg(feature = "concurrent-tree")]
+#[account(zero_copy)]
+pub struct ConcurrentMerkleTreeAccount {
+    pub authority: Pubkey,
+    pub tree: PoolConcurrentMerkleTree,
+    pub canopy: [[u8; 32]; CANOPY_NODES],
+}
+
+#[cfg(feature = "concurrent-tree")]
+impl ConcurrentMerkleTreeAccount {
+    /// Appends `leaf` and returns its proof below the canopy, the part
+    /// clients need to store or put in events.
+    pub fn append<H: ZeroHashes>(&mut self, leaf: [u8; 32]) -> Result<Vec<[u8; 32]>> {
+        let proof = self.tree.append::<H>(leaf)?;
+        update_canopy(&mut self.canopy, self.tree.latest_change_log());
+        Ok(proof[..CONCURRENT_TREE_DEPTH - CANOPY_DEPTH].to_vec())
+    }
+
+    /// `ConcurrentMerkleTree::verify_leaf` for a proof that is either full
+    /// length or stops at the canopy.
+    pub fn verify_leaf<H: ZeroHashes>(
+        &self,
+        root: [u8; 32],
+        leaf: [u8; 32],
+        proof: &[[u8; 32]],
+        index: u64,
+    ) -> Result<bool> {
+        let proof = fill_in_proof_from_canopy::<H, CONCURRENT_TREE_DEPTH>(&self.canopy, index, proof)?;
+        Ok(self.tree.verify_leaf::<H>(root, leaf, &proof, index))
+    }
+}
+
+#[cfg(feature = "concurrent-tree")]
+pub const CONCURRENT_MERKLE_TREE_ACCOUNT_LEN: usize = 8 + std::mem::size_of::<ConcurrentMerkleTreeAccount>();
+
+/// Takes a zeroed account of `CONCURRENT_MERKLE_TREE_ACCOUNT_LEN` bytes owned
+/// by this program, created by the authority in the same transaction.
+#[cfg(feature = "concurrent-tree")]
+#[derive(Accounts)]
+pub struct InitializeConcurrentTree<'info> {
+    #[account(zero)]
+    pub tree_account: AccountLoader<'info, ConcurrentMerkleTreeAccount>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    pub authority: Signer<'info>,
+}
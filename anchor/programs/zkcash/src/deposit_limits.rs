@@ -0,0 +1,173 @@
+use anchor_lang::prelude::*;
+use crate::errors::PoolError;
+use crate::{AuditLog, ErrorCode, MerkleTreeAccount};
+
+/// Deposit caps for one pool, in the pool mint's base units. A single
+/// lamport `max_deposit_amount` means nothing for a 6-decimal token, so each
+/// pool sets its own. The tree's `max_deposit_amount` still applies on top,
+/// which keeps the SOL pool's timelocked limit as the upper bound there.
+/// Changes go through `PendingDepositLimits` and the same delay.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct DepositLimits {
+    pub tree_account: Pubkey,
+    pub max_per_transaction: u64,
+    /// Total deposits allowed in one slot, across all transactions.
+    pub max_per_slot: u64,
+    pub current_slot: u64,
+    pub deposited_in_slot: u64,
+    pub bump: u8,
+}
+
+/// Limits queued for one pool, applied by `execute_deposit_limits` once
+/// `CONFIG_UPDATE_DELAY_SLOTS` have passed. Queueing again replaces them and
+/// restarts the delay.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct PendingDepositLimits {
+    pub tree_account: Pubkey,
+    pub max_per_transaction: u64,
+    pub max_per_slot: u64,
+    pub effective_slot: u64,
+    pub bump: u8,
+}
+
+#[event]
+pub struct DepositLimitsQueued {
+    pub tree_account: Pubkey,
+    pub max_per_transaction: u64,
+    pub max_per_slot: u64,
+    pub effective_slot: u64,
+}
+
+#[event]
+pub struct DepositLimitsUpdated {
+    pub tree_account: Pubkey,
+    pub max_per_transaction: u64,
+    pub max_per_slot: u64,
+}
+
+#[event]
+pub struct DepositLimitsCancelled {
+    pub tree_account: Pubkey,
+    pub effective_slot: u64,
+}
+
+pub fn validate_deposit_limits(max_per_transaction: u64, max_per_slot: u64) -> Result<()> {
+    require!(
+        max_per_transaction > 0 && max_per_slot >= max_per_transaction,
+        PoolError::InvalidDepositLimits
+    );
+    Ok(())
+}
+
+impl DepositLimits {
+    /// Records a deposit of `amount` at `slot`. Must be called from `transact`
+    /// for every deposit into `tree_account`, before moving the funds.
+    pub fn record_deposit(&mut self, amount: u64, slot: u64) -> Result<()> {
+        require!(amount <= self.max_per_transaction, PoolError::DepositLimitExceeded);
+        if slot != self.current_slot {
+            self.current_slot = slot;
+            self.deposited_in_slot = 0;
+        }
+        let deposited = self.deposited_in_slot
+            .checked_add(amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        require!(deposited <= self.max_per_slot, PoolError::SlotDepositLimitExceeded);
+        self.deposited_in_slot = deposited;
+        Ok(())
+    }
+}
+
+impl PendingDepositLimits {
+    pub fn is_ready(&self, slot: u64) -> bool {
+        slot >= self.effective_slot
+    }
+}
+
+/// Queues new limits for the pool whose tree is `tree_account`. Signed by
+/// the authority stored on that tree.
+#[derive(Accounts)]
+pub struct QueueDepositLimits<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + PendingDepositLimits::INIT_SPACE,
+        seeds = [b"pending_deposit_limits", tree_account.key().as_ref()],
+        bump
+    )]
+    pub pending_deposit_limits: Account<'info, PendingDepositLimits>,
+    #[account(
+        constraint = tree_account.load()?.authority == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub tree_account: AccountLoader<'info, MerkleTreeAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
+
+/// Anyone can execute ready limits and pays for `deposit_limits` if the pool
+/// has none yet; the pending account's rent goes back to the tree authority.
+#[derive(Accounts)]
+pub struct ExecuteDepositLimits<'info> {
+    #[account(
+        mut,
+        seeds = [b"pending_deposit_limits", tree_account.key().as_ref()],
+        bump = pending_deposit_limits.bump,
+        close = authority
+    )]
+    pub pending_deposit_limits: Account<'info, PendingDepositLimits>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + DepositLimits::INIT_SPACE,
+        seeds = [b"deposit_limits", tree_account.key().as_ref()],
+        bump
+    )]
+    pub deposit_limits: Account<'info, DepositLimits>,
+    #[account(
+        constraint = tree_account.load()?.authority == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub tree_account: AccountLoader<'info, MerkleTreeAccount>,
+    /// CHECK: rent recipient only, pinned to the tree authority
+    #[account(mut)]
+    pub authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
+
+#[derive(Accounts)]
+pub struct CancelDepositLimits<'info> {
+    #[account(
+        mut,
+        seeds = [b"pending_deposit_limits", tree_account.key().as_ref()],
+        bump = pending_deposit_limits.bump,
+        close = authority
+    )]
+    pub pending_deposit_limits: Account<'info, PendingDepositLimits>,
+    #[account(
+        constraint = tree_account.load()?.authority == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub tree_account: AccountLoader<'info, MerkleTreeAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
@@ -0,0 +1,107 @@
+use anchor_lang::prelude::*;
+use crate::errors::PoolError;
+use crate::{ErrorCode, MerkleTreeAccount};
+
+/// Commitments the queue can hold. Keeps the account under the 10 KiB that
+/// `init` can allocate.
+pub const MAX_QUEUED_DEPOSITS: usize = 256;
+
+/// Most commitments one `process_queue` call inserts. Each insert hashes a
+/// full tree path, so a larger batch would not fit the compute budget.
+pub const MAX_QUEUE_BATCH: u8 = 8;
+
+/// Pending commitments of deposits into `tree_account`. A deposit only pays
+/// for a 32-byte write here, and the permissionless `process_queue` crank
+/// later inserts them into the tree in order. Leaf indices are assigned at
+/// insertion and reported in `DepositQueueProcessed`.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct DepositQueue {
+    pub tree_account: Pubkey,
+    /// Oldest first.
+    #[max_len(MAX_QUEUED_DEPOSITS)]
+    pub pending: Vec<[u8; 32]>,
+    /// Commitments ever enqueued, so indexers can spot gaps.
+    pub total_enqueued: u64,
+    pub bump: u8,
+}
+
+#[event]
+pub struct DepositQueued {
+    pub tree_account: Pubkey,
+    pub commitment: [u8; 32],
+    pub queue_position: u64,
+}
+
+#[event]
+pub struct DepositQueueProcessed {
+    pub tree_account: Pubkey,
+    /// Leaf index of `commitments[0]`; the rest follow consecutively.
+    pub first_leaf_index: u64,
+    pub commitments: Vec<[u8; 32]>,
+    pub root: [u8; 32],
+}
+
+impl DepositQueue {
+    /// Queues a deposit's output commitment. Must be called from `transact`
+    /// instead of appending to the tree when the pool has a queue.
+    pub fn enqueue(&mut self, commitment: [u8; 32]) -> Result<()> {
+        require!(self.pending.len() < MAX_QUEUED_DEPOSITS, PoolError::DepositQueueFull);
+        self.pending.push(commitment);
+        self.total_enqueued = self.total_enqueued
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        emit!(DepositQueued {
+            tree_account: self.tree_account,
+            commitment,
+            queue_position: self.total_enqueued - 1,
+        });
+        Ok(())
+    }
+
+    /// Removes and returns up to `max_batch` of the oldest commitments.
+    pub fn take_batch(&mut self, max_batch: u8) -> Result<Vec<[u8; 32]>> {
+        require!(
+            max_batch > 0 && max_batch <= MAX_QUEUE_BATCH,
+            PoolError::InvalidQueueBatchSize
+        );
+        require!(!self.pending.is_empty(), PoolError::DepositQueueEmpty);
+        let count = self.pending.len().min(max_batch as usize);
+        Ok(self.pending.drain(..count).collect())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeDepositQueue<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + DepositQueue::INIT_SPACE,
+        seeds = [b"deposit_queue", tree_account.key().as_ref()],
+        bump
+    )]
+    pub deposit_queue: Account<'info, DepositQueue>,
+    #[account(
+        constraint = tree_account.load()?.authority == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub tree_account: AccountLoader<'info, MerkleTreeAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Anyone can crank the queue; the inserted commitments and their order are
+/// fixed by the queue, so the caller only pays the fee.
+#[derive(Accounts)]
+pub struct ProcessQueue<'info> {
+    #[account(
+        mut,
+        seeds = [b"deposit_queue", tree_account.key().as_ref()],
+        bump = deposit_queue.bump,
+        has_one = tree_account
+    )]
+    pub deposit_queue: Account<'info, DepositQueue>,
+    #[account(mut)]
+    pub tree_account: AccountLoader<'info, MerkleTreeAccount>,
+    pub payer: Signer<'info>,
+}
@@ -0,0 +1,145 @@
+use anchor_lang::prelude::*;
+use crate::errors::PoolError;
+use crate::{AuditLog, ErrorCode, GlobalConfig};
+
+/// Size of a client note ciphertext: version byte, detection tag, ephemeral
+/// key, amount, blinding, mint and diversifier, and the AEAD tag. The limit
+/// can't go below it or no client could transact.
+pub const MIN_ENCRYPTED_OUTPUT_LEN: u16 = 136;
+
+/// Hard cap on the limit. Much past this, two outputs plus the proof no
+/// longer fit in a 1232-byte transaction even with address lookup tables.
+pub const MAX_ENCRYPTED_OUTPUT_LEN: u16 = 256;
+
+/// Limit in effect until the authority sets one.
+pub const DEFAULT_MAX_ENCRYPTED_OUTPUT_LEN: u16 = 256;
+
+/// How large each encrypted output passed to `transact` may be. Outputs are
+/// hashed into ExtData and emitted in events, so an unbounded length bloats
+/// both the transaction and the logs.
+#[account]
+#[derive(InitSpace, Debug)]
+pub struct EncryptedOutputConfig {
+    pub max_len: u16,
+    pub bump: u8,
+}
+
+impl Default for EncryptedOutputConfig {
+    fn default() -> Self {
+        EncryptedOutputConfig {
+            max_len: DEFAULT_MAX_ENCRYPTED_OUTPUT_LEN,
+            bump: 0,
+        }
+    }
+}
+
+impl EncryptedOutputConfig {
+    /// Must be called from `transact` before the outputs are hashed.
+    pub fn validate_outputs(&self, encrypted_output1: &[u8], encrypted_output2: &[u8]) -> Result<()> {
+        let max_len = self.max_len as usize;
+        require!(
+            encrypted_output1.len() <= max_len && encrypted_output2.len() <= max_len,
+            PoolError::EncryptedOutputTooLarge
+        );
+        Ok(())
+    }
+}
+
+pub fn validate_max_encrypted_output_len(max_len: u16) -> Result<()> {
+    require!(
+        (MIN_ENCRYPTED_OUTPUT_LEN..=MAX_ENCRYPTED_OUTPUT_LEN).contains(&max_len),
+        PoolError::InvalidEncryptedOutputLimit
+    );
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetMaxEncryptedOutputLen<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + EncryptedOutputConfig::INIT_SPACE,
+        seeds = [b"encrypted_output_config"],
+        bump
+    )]
+    pub encrypted_output_config: Account<'info, EncryptedOutputConfig>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
+
+/// Where a deployment keeps encrypted outputs.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputStorageMode {
+    /// In the commitment PDAs as well as events, so any RPC can serve them.
+    #[default]
+    Account,
+    /// In events only. Commitment PDAs carry no output and cost less rent,
+    /// but wallets need transaction history (an archival RPC or an indexer)
+    /// to recover their notes.
+    EventOnly,
+}
+
+/// The storage mode, at `["output_storage_config"]`. Kept apart from
+/// `EncryptedOutputConfig` so that account's layout doesn't change.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct OutputStorageConfig {
+    pub mode: OutputStorageMode,
+    pub bump: u8,
+}
+
+#[event]
+pub struct OutputStorageModeUpdated {
+    pub mode: OutputStorageMode,
+}
+
+impl OutputStorageConfig {
+    /// The bytes of `encrypted_output` to store in its commitment PDA.
+    /// `transact` sizes the PDA by this and always emits the full output.
+    pub fn stored_output<'a>(&self, encrypted_output: &'a [u8]) -> &'a [u8] {
+        match self.mode {
+            OutputStorageMode::Account => encrypted_output,
+            OutputStorageMode::EventOnly => &[],
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct SetOutputStorageMode<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + OutputStorageConfig::INIT_SPACE,
+        seeds = [b"output_storage_config"],
+        bump
+    )]
+    pub output_storage_config: Account<'info, OutputStorageConfig>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
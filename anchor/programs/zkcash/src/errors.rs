@@ -1,3 +1,4 @@
+use anchor_lang::prelude::*;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq, Eq)]
@@ -22,4 +23,272 @@ pub enum Groth16Error {
     
     #[error("Proof verification failed")]
     ProofVerificationFailed,
-} 
\ No newline at end of file
+
+    #[error("Proof batch is empty or mixes verifying keys")]
+    InvalidBatch,
+
+    #[error("G1 proof point is not on the curve")]
+    G1PointNotOnCurve,
+
+    #[error("G1 proof point is not in the prime-order subgroup")]
+    G1PointNotInSubgroup,
+
+    #[error("G2 proof point is not on the curve")]
+    G2PointNotOnCurve,
+
+    #[error("G2 proof point is not in the prime-order subgroup")]
+    G2PointNotInSubgroup,
+} 
+
+#[error_code(offset = 7000)]
+pub enum PoolError {
+    #[msg("Merkle tree height is zero or exceeds the supported maximum")]
+    InvalidTreeHeight,
+    #[msg("Withdrawals are paused by the circuit breaker")]
+    WithdrawalsPaused,
+    #[msg("Withdrawal exceeds the circuit breaker's outflow limit")]
+    OutflowLimitExceeded,
+    #[msg("Circuit breaker limit must be 1-10000 bps over a non-empty window")]
+    InvalidCircuitBreakerConfig,
+    #[msg("Deposit fee rate exceeds MAX_FEE_RATE")]
+    DepositFeeRateTooHigh,
+    #[msg("Withdrawal fee rate exceeds MAX_FEE_RATE")]
+    WithdrawalFeeRateTooHigh,
+    #[msg("Fee error margin exceeds MAX_FEE_ERROR_MARGIN")]
+    FeeErrorMarginTooHigh,
+    #[msg("Config update changes nothing")]
+    EmptyConfigUpdate,
+    #[msg("Config update delay has not elapsed")]
+    ConfigUpdateNotReady,
+    #[msg("New authority must not be the default pubkey")]
+    InvalidAuthority,
+    #[msg("Account data does not match any known layout version")]
+    InvalidAccountLayout,
+    #[msg("Account is already at the current layout version")]
+    AccountAlreadyMigrated,
+    #[msg("Pool registry is full")]
+    PoolRegistryFull,
+    #[msg("Pool is already in the registry")]
+    PoolAlreadyRegistered,
+    #[msg("Pool is not in the registry")]
+    PoolNotRegistered,
+    #[msg("Deposit limits must be non-zero with the per-slot cap at least the per-transaction cap")]
+    InvalidDepositLimits,
+    #[msg("Deposit exceeds the pool's per-transaction limit")]
+    DepositLimitExceeded,
+    #[msg("Deposit exceeds the pool's per-slot limit")]
+    SlotDepositLimitExceeded,
+    #[msg("Memo exceeds MAX_MEMO_LEN")]
+    MemoTooLong,
+    #[msg("ExtData version is not supported")]
+    UnsupportedExtDataVersion,
+    #[msg("Encrypted output exceeds the configured maximum length")]
+    EncryptedOutputTooLarge,
+    #[msg("Max encrypted output length is outside the supported range")]
+    InvalidEncryptedOutputLimit,
+    #[msg("Transaction landed after its ExtData expiry slot")]
+    ProofExpired,
+    #[msg("Fee exceeds the max fee committed to in ExtData")]
+    FeeExceedsMaxFee,
+    #[msg("Transaction signer is not the relayer bound in ExtData")]
+    RelayerMismatch,
+    #[msg("Deposit queue is full")]
+    DepositQueueFull,
+    #[msg("Deposit queue is empty")]
+    DepositQueueEmpty,
+    #[msg("Queue batch size must be between 1 and MAX_QUEUE_BATCH")]
+    InvalidQueueBatchSize,
+    #[msg("Merkle proof must cover every level or stop at the canopy")]
+    InvalidProofLength,
+    #[msg("Proof spends the same nullifier twice")]
+    DuplicateNullifier,
+    #[msg("Proof creates the same output commitment twice")]
+    DuplicateCommitment,
+    #[msg("Fee recipient is not the configured protocol fee address")]
+    FeeRecipientMismatch,
+    #[msg("Protocol fee address must not be the default pubkey")]
+    InvalidFeeRecipient,
+    #[msg("Max staked share exceeds MAX_STAKED_BPS")]
+    InvalidYieldConfig,
+    #[msg("Stake would exceed the pool's max staked share")]
+    StakeLimitExceeded,
+    #[msg("Only the authority may unstake while the pool has enough liquid SOL")]
+    UnstakeNotNeeded,
+    #[msg("Pool token account is not the yield vault's associated token account")]
+    InvalidYieldTokenAccount,
+    #[msg("Mint is not the preset's liquid staking token")]
+    LstMintMismatch,
+    #[msg("Exchange rate account is not the preset's or holds no valid rate")]
+    InvalidLstRate,
+    #[msg("Liquid staking token exchange rate needs a refresh")]
+    StaleLstRate,
+    #[msg("Swap allowlist is too long or names an invalid program")]
+    InvalidSwapAllowlist,
+    #[msg("Swap program is not allowlisted")]
+    SwapProgramNotAllowed,
+    #[msg("Swap instruction data does not match the route committed in ExtData")]
+    SwapRouteMismatch,
+    #[msg("Swap delivered less than the committed minimum output")]
+    SwapOutputTooLow,
+    #[msg("CPI allowlist is too long or names a disallowed program")]
+    InvalidCpiAllowlist,
+    #[msg("Withdrawal CPI program is not allowlisted")]
+    CpiProgramNotAllowed,
+    #[msg("Withdrawal CPI data does not match the instruction committed in ExtData")]
+    CpiDataMismatch,
+    #[msg("ExtData commits to more than one withdrawal mode")]
+    ConflictingWithdrawalModes,
+    #[msg("Foreign recipient needs a non-Solana chain id and a non-zero address")]
+    InvalidForeignRecipient,
+    #[msg("Wormhole bridge account is too short to hold its config")]
+    InvalidWormholeBridge,
+    #[msg("Proof point is not a valid BN254 curve point encoding")]
+    InvalidProofEncoding,
+    #[msg("Public input is not less than the BN254 field size")]
+    PublicInputOutOfField,
+    #[msg("Groth16 pairing check failed")]
+    ProofPairingFailed,
+    #[msg("Verifying key is malformed or does not match the public inputs")]
+    MalformedVerifyingKey,
+    #[msg("Proof data is not the length of a serialized proof")]
+    InvalidProofDataLength,
+    #[msg("Base fee exceeds a hundredth of a whole token of the pool's mint")]
+    BaseFeeTooHigh,
+    #[msg("Fee tiers must be at most MAX_FEE_TIERS, ascending by amount, with rates up to MAX_FEE_RATE")]
+    InvalidFeeTiers,
+    #[msg("Withdrawal fee cap is below the base fee")]
+    MaxFeeBelowBaseFee,
+    #[msg("Fee exemption list is too long or has an invalid or duplicate entry")]
+    InvalidFeeExemptions,
+    #[msg("Slash amount must be non-zero and at most the relayer's bond")]
+    InvalidSlashAmount,
+    #[msg("Relayer must request deregistration and wait out the unbonding period")]
+    RelayerStillBonded,
+    #[msg("Bond amount must be non-zero")]
+    InvalidBondAmount,
+    #[msg("Queued withdrawal's delay has not passed")]
+    WithdrawalNotYetExecutable,
+    #[msg("SlotHashes sysvar holds no hash")]
+    InvalidSlotHashes,
+    #[msg("Revealed withdrawal doesn't match the relayer's commitment")]
+    SubmissionCommitmentMismatch,
+    #[msg("Reveal is too early or too late for its commitment")]
+    RevealOutsideWindow,
+    #[msg("Nullifier seed migration is already open, unchanged or too short")]
+    InvalidNullifierMigration,
+    #[msg("Nullifier is not a spent legacy nullifier, or the migration is closed")]
+    InvalidNullifierMigrationSource,
+    #[msg("Token account is pool funds or not owned by the pool")]
+    CannotRescuePoolAsset,
+    #[msg("Snapshot page is out of range")]
+    InvalidSnapshotPage,
+    #[msg("Root is older than the root policy allows")]
+    StaleRoot,
+    #[msg("Max root age must be 0 or at least MIN_MAX_ROOT_AGE_SLOTS")]
+    InvalidRootPolicy,
+    #[msg("Root history account is already fully allocated")]
+    RootHistoryAlreadyAllocated,
+    #[msg("Merkle path does not lead from the leaf to the root")]
+    InvalidInclusionProof,
+    #[msg("Root is not in the tree's root history")]
+    UnknownInclusionRoot,
+    #[msg("Withdrawals must carry two output commitments and two equal-length encrypted outputs")]
+    DegenerateOutputShape,
+    #[msg("Metrics window must be 1 to DEPOSIT_EPOCH_WINDOW epochs")]
+    InvalidMetricsWindow,
+    #[msg("Withdrawals are frozen by the guardian")]
+    WithdrawalsFrozen,
+    #[msg("Deposits are frozen by the guardian")]
+    DepositsFrozen,
+    #[msg("Verifying key does not match the claimed hash")]
+    VerifyingKeyMismatch,
+    #[msg("Verifying key rotation needs a new, nonzero key hash and a pending key to activate")]
+    InvalidVerifyingKeyRotation,
+    #[msg("Queued verifying key is not active yet")]
+    VerifyingKeyRotationNotReady,
+    #[msg("Proof names a verifying key that is not accepted")]
+    VerifyingKeyNotAccepted,
+    #[msg("Proof batch is empty or mixes verifying keys")]
+    InvalidProofBatch,
+    #[msg("Proof point is not on the curve")]
+    ProofPointNotOnCurve,
+    #[msg("Proof point is not in the prime-order subgroup")]
+    ProofPointNotInSubgroup,
+    #[msg("ext_amount is i64::MIN or above the pool's max deposit amount")]
+    ExtAmountOutOfRange,
+    #[msg("Fee does not fit alongside ext_amount, or a deposit does not cover it")]
+    FeeOutOfRange,
+    #[msg("Encrypted output is empty")]
+    EmptyEncryptedOutput,
+    #[msg("Withdrawal recipient must not be the default pubkey")]
+    InvalidRecipient,
+    #[msg("Account is not owned by the expected program")]
+    InvalidAccountOwner,
+    #[msg("Transact instruction data does not match its header")]
+    InvalidTransactData,
+    #[msg("Circuit version is not supported by this program")]
+    UnsupportedCircuitVersion,
+    #[msg("Circuit version is below the pool's minimum")]
+    DeprecatedCircuitVersion,
+    #[msg("Minimum circuit version must be a supported version")]
+    InvalidCircuitVersion,
+    #[msg("Tree storage is not the one the tree account links to")]
+    TreeStorageMismatch,
+    #[msg("Tree storage has already been split out")]
+    TreeStorageAlreadySplit,
+    #[msg("Leaf page or leaf is out of append order")]
+    LeafPageOutOfOrder,
+    #[msg("On-chain leaf storage is not enabled")]
+    LeafStorageDisabled,
+    #[msg("Checkpoint interval is below the minimum")]
+    InvalidCheckpointInterval,
+    #[msg("A root checkpoint was taken too recently")]
+    CheckpointTooSoon,
+    #[msg("The tree has moved past the next_index being attested")]
+    StaleRootAttestation,
+    #[msg("The guardian must be a non-default key")]
+    InvalidGuardian,
+    #[msg("The fee payout address must be a non-default key")]
+    InvalidFeePayout,
+    #[msg("Payout account does not match the fee vault's payout address")]
+    FeePayoutMismatch,
+    #[msg("The fee vault holds nothing above its rent-exempt minimum")]
+    NoFeesToSweep,
+    #[msg("Nullifier has already been spent")]
+    NullifierAlreadySpent,
+    #[msg("A memo CPI needs a withdrawal with a non-empty UTF-8 memo")]
+    InvalidMemoCpi,
+    #[msg("Memo program account is not SPL Memo")]
+    InvalidMemoProgram,
+    #[msg("Pool creation fee is below MIN_POOL_CREATION_FEE")]
+    CreationFeeTooLow,
+    #[msg("Mint account data is too short to be an SPL mint")]
+    InvalidMintAccount,
+    #[msg("Mint has no pool, or the token pool account is not its own")]
+    UnknownPoolMint,
+    #[msg("Fee schedule belongs to another mint's pool")]
+    FeeScheduleMintMismatch,
+    #[msg("Relayer fee rate is below the global withdrawal fee rate")]
+    RelayerFeeRateBelowGlobal,
+}
+
+/// Maps each verifier failure to the instruction error a relayer or user
+/// sees in the transaction logs.
+impl From<Groth16Error> for PoolError {
+    fn from(error: Groth16Error) -> Self {
+        match error {
+            Groth16Error::InvalidG1Length | Groth16Error::InvalidG2Length => PoolError::InvalidProofEncoding,
+            Groth16Error::PublicInputGreaterThanFieldSize => PoolError::PublicInputOutOfField,
+            Groth16Error::ProofVerificationFailed => PoolError::ProofPairingFailed,
+            Groth16Error::InvalidBatch => PoolError::InvalidProofBatch,
+            Groth16Error::G1PointNotOnCurve | Groth16Error::G2PointNotOnCurve => PoolError::ProofPointNotOnCurve,
+            Groth16Error::G1PointNotInSubgroup | Groth16Error::G2PointNotInSubgroup => {
+                PoolError::ProofPointNotInSubgroup
+            }
+            // Inputs are in the field by then, so only bad IC points fail these
+            Groth16Error::InvalidPublicInputsLength
+            | Groth16Error::PreparingInputsG1MulFailed
+            | Groth16Error::PreparingInputsG1AdditionFailed => PoolError::MalformedVerifyingKey,
+        }
+    }
+}
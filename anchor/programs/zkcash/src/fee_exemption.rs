@@ -0,0 +1,172 @@
+use anchor_lang::prelude::*;
+use crate::errors::PoolError;
+use crate::{holds_role, AuditLog, ErrorCode, GlobalConfig, Role, RoleConfig};
+
+/// Parties the exemption list can hold.
+pub const MAX_FEE_EXEMPTIONS: usize = 16;
+
+/// Parties whose withdrawals owe no fee, e.g. the DAO treasury or an audited
+/// integration. A withdrawal is exempt when either its recipient or the
+/// signer submitting it is listed. Changes go through `PendingFeeExemptions`
+/// and the config update delay.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct FeeExemptions {
+    #[max_len(MAX_FEE_EXEMPTIONS)]
+    pub exempt: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+/// A list queued to replace `FeeExemptions`, applied by
+/// `execute_fee_exemptions` once `CONFIG_UPDATE_DELAY_SLOTS` have passed.
+/// Queueing again replaces it and restarts the delay.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct PendingFeeExemptions {
+    #[max_len(MAX_FEE_EXEMPTIONS)]
+    pub exempt: Vec<Pubkey>,
+    /// The fee manager that queued it, who paid the rent and gets it back.
+    pub queued_by: Pubkey,
+    pub effective_slot: u64,
+    pub bump: u8,
+}
+
+#[event]
+pub struct FeeExemptionsQueued {
+    pub exempt: Vec<Pubkey>,
+    pub effective_slot: u64,
+}
+
+#[event]
+pub struct FeeExemptionsCancelled {
+    pub effective_slot: u64,
+}
+
+#[event]
+pub struct FeeExemptionsUpdated {
+    pub exempt: Vec<Pubkey>,
+}
+
+pub fn validate_fee_exemptions(exempt: &[Pubkey]) -> Result<()> {
+    require!(exempt.len() <= MAX_FEE_EXEMPTIONS, PoolError::InvalidFeeExemptions);
+    require!(
+        exempt.iter().enumerate().all(|(i, party)| *party != Pubkey::default() && !exempt[..i].contains(party)),
+        PoolError::InvalidFeeExemptions
+    );
+    Ok(())
+}
+
+impl FeeExemptions {
+    /// Whether `transact` skips `FeeSchedule::validate_fee`, allowing a zero
+    /// fee. Only withdrawals are ever exempt.
+    pub fn waives_fee(&self, ext_amount: i128, recipient: &Pubkey, signer: &Pubkey) -> bool {
+        ext_amount < 0 && (self.exempt.contains(recipient) || self.exempt.contains(signer))
+    }
+}
+
+impl PendingFeeExemptions {
+    pub fn is_ready(&self, slot: u64) -> bool {
+        slot >= self.effective_slot
+    }
+}
+
+#[derive(Accounts)]
+pub struct QueueFeeExemptions<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + PendingFeeExemptions::INIT_SPACE,
+        seeds = [b"pending_fee_exemptions"],
+        bump
+    )]
+    pub pending_fee_exemptions: Account<'info, PendingFeeExemptions>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(
+        seeds = [b"role_config"],
+        bump = role_config.bump
+    )]
+    pub role_config: Account<'info, RoleConfig>,
+    #[account(
+        mut,
+        constraint = holds_role(&role_config, Role::FeeManager, &global_config.authority, &authority.key())
+            @ ErrorCode::Unauthorized
+    )]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
+
+/// Anyone can execute a ready list and pays for `fee_exemptions` if there is
+/// none yet; the pending account's rent goes back to `queued_by`.
+#[derive(Accounts)]
+pub struct ExecuteFeeExemptions<'info> {
+    #[account(
+        mut,
+        seeds = [b"pending_fee_exemptions"],
+        bump = pending_fee_exemptions.bump,
+        close = queued_by
+    )]
+    pub pending_fee_exemptions: Account<'info, PendingFeeExemptions>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + FeeExemptions::INIT_SPACE,
+        seeds = [b"fee_exemptions"],
+        bump
+    )]
+    pub fee_exemptions: Account<'info, FeeExemptions>,
+    /// CHECK: rent recipient only, pinned to whoever queued the list
+    #[account(mut, address = pending_fee_exemptions.queued_by)]
+    pub queued_by: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
+
+#[derive(Accounts)]
+pub struct CancelFeeExemptions<'info> {
+    #[account(
+        mut,
+        seeds = [b"pending_fee_exemptions"],
+        bump = pending_fee_exemptions.bump,
+        close = authority
+    )]
+    pub pending_fee_exemptions: Account<'info, PendingFeeExemptions>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(
+        seeds = [b"role_config"],
+        bump = role_config.bump
+    )]
+    pub role_config: Account<'info, RoleConfig>,
+    #[account(
+        mut,
+        constraint = holds_role(&role_config, Role::FeeManager, &global_config.authority, &authority.key())
+            @ ErrorCode::Unauthorized
+    )]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use crate::errors::PoolError;
+use crate::{AuditLog, ErrorCode, GlobalConfig};
+
+/// Address the protocol's share of fees must be paid to. The proof only
+/// commits to the `fee_recipient` in ExtData, which the client chooses, so
+/// without this a modified frontend could route the protocol's cut to
+/// itself. The relayer's share, see `FeeSplit`, still goes to the ExtData
+/// `fee_recipient`.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct FeeRecipientConfig {
+    pub fee_recipient: Pubkey,
+    pub bump: u8,
+}
+
+#[event]
+pub struct FeeRecipientUpdated {
+    pub old_fee_recipient: Pubkey,
+    pub new_fee_recipient: Pubkey,
+}
+
+impl FeeRecipientConfig {
+    /// Must be called from `transact` with the account `FeeSplit::protocol`
+    /// is transferred to. The ExtData `fee_recipient` is the relayer's and
+    /// is not checked against this.
+    pub fn validate_protocol_fee_recipient(&self, protocol_fee_recipient: &Pubkey) -> Result<()> {
+        require!(*protocol_fee_recipient == self.fee_recipient, PoolError::FeeRecipientMismatch);
+        Ok(())
+    }
+}
+
+pub fn validate_configured_fee_recipient(fee_recipient: &Pubkey) -> Result<()> {
+    require!(*fee_recipient != Pubkey::default(), PoolError::InvalidFeeRecipient);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetFeeRecipient<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + FeeRecipientConfig::INIT_SPACE,
+        seeds = [b"fee_recipient_config"],
+        bump
+    )]
+    pub fee_recipient_config: Account<'info, FeeRecipientConfig>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
@@ -0,0 +1,382 @@
+use anchor_lang::prelude::*;
+use crate::errors::PoolError;
+use crate::utils::MAX_FEE_RATE;
+use crate::{holds_role, AuditLog, ErrorCode, GlobalConfig, Role, RoleConfig, TokenPool};
+
+/// Highest flat withdrawal fee the fee manager can set for the SOL pool, 0.01
+/// SOL: enough for a relayer's signature fee, priority fee and rent top-ups.
+/// Token pools get the same hundredth of a whole token, see `max_base_fee`.
+pub const MAX_BASE_FEE: u64 = 10_000_000;
+
+/// Withdrawal fee tiers a schedule can hold.
+pub const MAX_FEE_TIERS: usize = 4;
+
+/// Withdrawals of at least `min_amount` pay `fee_rate` basis points instead of
+/// `GlobalConfig::withdrawal_fee_rate`.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeTier {
+    pub min_amount: u64,
+    pub fee_rate: u16,
+}
+
+/// One pool's fee terms on top of `GlobalConfig`'s basis-point rates, at
+/// `["fee_schedule", mint]` with `SOL_ADDRESS` for the SOL pool. A percentage
+/// alone pays a relayer almost nothing for a tiny withdrawal, though it costs
+/// them the same to submit, so withdrawals also owe a flat `base_fee`. Large
+/// withdrawals can be given lower rates through `tiers`, and their fee is
+/// bounded outright by `fee_cap`. `base_fee` and `fee_cap` are in the mint's
+/// base units, so each pool needs its own schedule. Changes go through
+/// `PendingFeeSchedule` and the config update delay.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct FeeSchedule {
+    pub mint: Pubkey,
+    /// Base units added to every withdrawal's expected fee.
+    pub base_fee: u64,
+    /// Sorted by `min_amount`, strictly ascending.
+    #[max_len(MAX_FEE_TIERS)]
+    pub tiers: Vec<FeeTier>,
+    /// Most a withdrawal's expected fee can be, however large it is, in the
+    /// mint's base units. Zero means no cap.
+    pub fee_cap: u64,
+    pub bump: u8,
+}
+
+/// A schedule queued for one pool, applied by `execute_fee_schedule` once
+/// `CONFIG_UPDATE_DELAY_SLOTS` have passed. Queueing again replaces it and
+/// restarts the delay.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct PendingFeeSchedule {
+    pub mint: Pubkey,
+    pub base_fee: u64,
+    #[max_len(MAX_FEE_TIERS)]
+    pub tiers: Vec<FeeTier>,
+    pub fee_cap: u64,
+    /// The fee manager that queued it, who paid the rent and gets it back.
+    pub queued_by: Pubkey,
+    pub effective_slot: u64,
+    pub bump: u8,
+}
+
+/// A transaction's fee as `transact` pays it out. `protocol` goes to
+/// `FeeRecipientConfig::fee_recipient`, `relayer` to the ExtData
+/// `fee_recipient`, which the relayer chooses and the proof binds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeSplit {
+    pub protocol: u128,
+    pub relayer: u128,
+}
+
+#[event]
+pub struct FeeScheduleQueued {
+    pub mint: Pubkey,
+    pub base_fee: u64,
+    pub tiers: Vec<FeeTier>,
+    pub fee_cap: u64,
+    pub effective_slot: u64,
+}
+
+#[event]
+pub struct FeeScheduleCancelled {
+    pub mint: Pubkey,
+    pub effective_slot: u64,
+}
+
+#[event]
+pub struct FeeScheduleUpdated {
+    pub mint: Pubkey,
+    pub base_fee: u64,
+    pub tiers: Vec<FeeTier>,
+    pub fee_cap: u64,
+}
+
+/// The highest `base_fee` for a mint with `decimals`: a hundredth of a whole
+/// token, `MAX_BASE_FEE` for SOL.
+pub fn max_base_fee(decimals: u8) -> u64 {
+    10u64.checked_pow(decimals as u32).map_or(u64::MAX, |unit| unit / 100)
+}
+
+pub fn validate_base_fee(base_fee: u64, decimals: u8) -> Result<()> {
+    require!(base_fee <= max_base_fee(decimals), PoolError::BaseFeeTooHigh);
+    Ok(())
+}
+
+/// A cap below the base fee would silently override it.
+pub fn validate_fee_cap(fee_cap: u64, base_fee: u64) -> Result<()> {
+    require!(
+        fee_cap == 0 || fee_cap >= base_fee,
+        PoolError::MaxFeeBelowBaseFee
+    );
+    Ok(())
+}
+
+pub fn validate_fee_tiers(tiers: &[FeeTier]) -> Result<()> {
+    require!(tiers.len() <= MAX_FEE_TIERS, PoolError::InvalidFeeTiers);
+    require!(
+        tiers.iter().all(|tier| tier.min_amount > 0 && tier.fee_rate <= MAX_FEE_RATE),
+        PoolError::InvalidFeeTiers
+    );
+    require!(
+        tiers.windows(2).all(|pair| pair[0].min_amount < pair[1].min_amount),
+        PoolError::InvalidFeeTiers
+    );
+    Ok(())
+}
+
+/// The lowest fee accepted against `expected_fee`:
+/// `expected_fee * (1 - fee_error_margin/10000)`, rounded down.
+pub fn min_acceptable_fee(expected_fee: u128, fee_error_margin: u16) -> Result<u128> {
+    if expected_fee == 0 {
+        return Ok(0);
+    }
+    let error_multiplier = 10000u128.checked_sub(fee_error_margin as u128)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    Ok(expected_fee
+        .checked_mul(error_multiplier)
+        .ok_or(ErrorCode::ArithmeticOverflow)?
+        .checked_div(10000)
+        .ok_or(ErrorCode::ArithmeticOverflow)?)
+}
+
+impl FeeSchedule {
+    /// The rate a withdrawal of `amount` pays: the highest tier it reaches,
+    /// or `withdrawal_fee_rate` below every tier.
+    pub fn withdrawal_fee_rate(&self, amount: u128, withdrawal_fee_rate: u16) -> u16 {
+        self.tiers
+            .iter()
+            .rev()
+            .find(|tier| amount >= tier.min_amount as u128)
+            .map_or(withdrawal_fee_rate, |tier| tier.fee_rate)
+    }
+
+    /// The protocol's cut of the fee for `ext_amount`: the rate's share of
+    /// the amount, at the tier's rate for withdrawals, never a relayer's own
+    /// rate. `base_fee` is the relayer's, so under `fee_cap` this cut gives
+    /// way first.
+    pub fn protocol_fee(&self, ext_amount: i128, deposit_fee_rate: u16, withdrawal_fee_rate: u16) -> Result<u128> {
+        if ext_amount == 0 {
+            return Ok(0);
+        }
+        let amount = ext_amount.unsigned_abs();
+        let fee_rate = if ext_amount > 0 {
+            deposit_fee_rate
+        } else {
+            self.withdrawal_fee_rate(amount, withdrawal_fee_rate)
+        };
+        let rate_fee = amount
+            .checked_mul(fee_rate as u128)
+            .ok_or(ErrorCode::ArithmeticOverflow)?
+            .checked_div(10000)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        if ext_amount > 0 {
+            return Ok(rate_fee);
+        }
+        Ok(match self.fee_cap {
+            0 => rate_fee,
+            cap => rate_fee.min(cap.saturating_sub(self.base_fee) as u128),
+        })
+    }
+
+    /// The fee a transaction moving `ext_amount` is expected to pay, before
+    /// the error margin, as the protocol's cut and the relayer's share. On
+    /// withdrawals the relayer is owed `base_fee` plus `relayer_fee_rate`
+    /// basis points of the amount, its premium over the global rate, which
+    /// never adds to the protocol's cut. Under `fee_cap` the premium gives
+    /// way first, then the protocol's cut, then the base fee.
+    pub fn expected_split(
+        &self,
+        ext_amount: i128,
+        deposit_fee_rate: u16,
+        withdrawal_fee_rate: u16,
+        relayer_fee_rate: u16,
+    ) -> Result<FeeSplit> {
+        let protocol = self.protocol_fee(ext_amount, deposit_fee_rate, withdrawal_fee_rate)?;
+        if ext_amount >= 0 {
+            return Ok(FeeSplit { protocol, relayer: 0 });
+        }
+        let premium = ext_amount
+            .unsigned_abs()
+            .checked_mul(relayer_fee_rate as u128)
+            .ok_or(ErrorCode::ArithmeticOverflow)?
+            .checked_div(10000)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        let premium = match self.fee_cap {
+            0 => premium,
+            cap => premium.min((cap as u128).saturating_sub(self.base_fee as u128 + protocol)),
+        };
+        Ok(FeeSplit {
+            protocol,
+            relayer: premium + self.base_fee as u128,
+        })
+    }
+
+    /// The total of `expected_split`. At most `fee_cap` for withdrawals.
+    pub fn expected_fee(
+        &self,
+        ext_amount: i128,
+        deposit_fee_rate: u16,
+        withdrawal_fee_rate: u16,
+        relayer_fee_rate: u16,
+    ) -> Result<u128> {
+        let split = self.expected_split(ext_amount, deposit_fee_rate, withdrawal_fee_rate, relayer_fee_rate)?;
+        Ok(split.protocol.checked_add(split.relayer).ok_or(ErrorCode::ArithmeticOverflow)?)
+    }
+
+    /// Divides a fee `validate_fee` accepted between the protocol and the
+    /// relayer. The protocol takes its cut first, so a fee paid short within
+    /// the error margin comes out of the relayer's share, and anything paid
+    /// above the expected fee goes to the relayer.
+    pub fn split_fee(
+        &self,
+        ext_amount: i128,
+        provided_fee: u128,
+        deposit_fee_rate: u16,
+        withdrawal_fee_rate: u16,
+    ) -> Result<FeeSplit> {
+        let protocol = provided_fee.min(self.protocol_fee(ext_amount, deposit_fee_rate, withdrawal_fee_rate)?);
+        Ok(FeeSplit {
+            protocol,
+            relayer: provided_fee - protocol,
+        })
+    }
+
+    /// `validate_fee_u128` under this schedule, with the submitting relayer's
+    /// premium from `relayer_fee_premium`. The error margin applies to the
+    /// whole expected fee, base fee included, after the cap.
+    pub fn validate_fee(
+        &self,
+        ext_amount: i128,
+        provided_fee: u128,
+        deposit_fee_rate: u16,
+        withdrawal_fee_rate: u16,
+        relayer_fee_rate: u16,
+        fee_error_margin: u16,
+    ) -> Result<()> {
+        let expected_fee = self.expected_fee(ext_amount, deposit_fee_rate, withdrawal_fee_rate, relayer_fee_rate)?;
+        let min_acceptable_fee = min_acceptable_fee(expected_fee, fee_error_margin)?;
+
+        require!(
+            provided_fee >= min_acceptable_fee,
+            ErrorCode::InvalidFeeAmount
+        );
+        Ok(())
+    }
+}
+
+impl PendingFeeSchedule {
+    pub fn is_ready(&self, slot: u64) -> bool {
+        slot >= self.effective_slot
+    }
+}
+
+/// Queues a schedule for the pool of `mint`. A token pool's mint needs its
+/// `TokenPool` for the decimals; the SOL pool has none.
+#[derive(Accounts)]
+#[instruction(mint: Pubkey)]
+pub struct QueueFeeSchedule<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + PendingFeeSchedule::INIT_SPACE,
+        seeds = [b"pending_fee_schedule", mint.as_ref()],
+        bump
+    )]
+    pub pending_fee_schedule: Account<'info, PendingFeeSchedule>,
+    #[account(
+        seeds = [b"token_pool", mint.as_ref()],
+        bump = token_pool.bump
+    )]
+    pub token_pool: Option<Account<'info, TokenPool>>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(
+        seeds = [b"role_config"],
+        bump = role_config.bump
+    )]
+    pub role_config: Account<'info, RoleConfig>,
+    #[account(
+        mut,
+        constraint = holds_role(&role_config, Role::FeeManager, &global_config.authority, &authority.key())
+            @ ErrorCode::Unauthorized
+    )]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
+
+/// Anyone can execute a ready schedule and pays for `fee_schedule` if the
+/// pool has none yet; the pending account's rent goes back to `queued_by`.
+#[derive(Accounts)]
+#[instruction(mint: Pubkey)]
+pub struct ExecuteFeeSchedule<'info> {
+    #[account(
+        mut,
+        seeds = [b"pending_fee_schedule", mint.as_ref()],
+        bump = pending_fee_schedule.bump,
+        close = queued_by
+    )]
+    pub pending_fee_schedule: Account<'info, PendingFeeSchedule>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + FeeSchedule::INIT_SPACE,
+        seeds = [b"fee_schedule", mint.as_ref()],
+        bump
+    )]
+    pub fee_schedule: Account<'info, FeeSchedule>,
+    /// CHECK: rent recipient only, pinned to whoever queued the schedule
+    #[account(mut, address = pending_fee_schedule.queued_by)]
+    pub queued_by: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
+
+#[derive(Accounts)]
+#[instruction(mint: Pubkey)]
+pub struct CancelFeeSchedule<'info> {
+    #[account(
+        mut,
+        seeds = [b"pending_fee_schedule", mint.as_ref()],
+        bump = pending_fee_schedule.bump,
+        close = authority
+    )]
+    pub pending_fee_schedule: Account<'info, PendingFeeSchedule>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(
+        seeds = [b"role_config"],
+        bump = role_config.bump
+    )]
+    pub role_config: Account<'info, RoleConfig>,
+    #[account(
+        mut,
+        constraint = holds_role(&role_config, Role::FeeManager, &global_config.authority, &authority.key())
+            @ ErrorCode::Unauthorized
+    )]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
@@ -0,0 +1,121 @@
+use anchor_lang::prelude::*;
+use crate::errors::PoolError;
+use crate::{holds_role, AuditLog, ErrorCode, GlobalConfig, Role, RoleConfig};
+
+/// A program-owned treasury at `["fee_vault"]` that protocol fees can
+/// accumulate in: point `fee_recipient_config` at this address and
+/// transact and relayer slashing pay into it. The fee collector then sweeps
+/// it to `payout`, which only the authority can change, so routine fee
+/// collection needs neither the admin key nor a key that could redirect
+/// the fees.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct FeeVault {
+    pub payout: Pubkey,
+    /// Lamports swept to a payout address over the vault's lifetime.
+    pub total_swept: u64,
+    pub bump: u8,
+}
+
+#[event]
+pub struct FeePayoutUpdated {
+    pub old_payout: Pubkey,
+    pub new_payout: Pubkey,
+}
+
+#[event]
+pub struct FeesSwept {
+    pub collector: Pubkey,
+    pub payout: Pubkey,
+    pub amount: u64,
+}
+
+pub fn validate_fee_payout(payout: &Pubkey) -> Result<()> {
+    require!(*payout != Pubkey::default(), PoolError::InvalidFeePayout);
+    Ok(())
+}
+
+/// Lamports a vault holding `lamports` can release while staying rent
+/// exempt at `rent_exempt_minimum`.
+pub fn sweepable_fees(lamports: u64, rent_exempt_minimum: u64) -> u64 {
+    lamports.saturating_sub(rent_exempt_minimum)
+}
+
+#[derive(Accounts)]
+pub struct SetFeePayout<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + FeeVault::INIT_SPACE,
+        seeds = [b"fee_vault"],
+        bump
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
+
+/// Gated on the fee collector, which can do nothing else: the lamports can
+/// only go to the vault's `payout`.
+#[derive(Accounts)]
+pub struct SweepFees<'info> {
+    #[account(
+        mut,
+        seeds = [b"fee_vault"],
+        bump = fee_vault.bump,
+        has_one = payout @ PoolError::FeePayoutMismatch
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+    /// CHECK: receives lamports only, pinned by `has_one`
+    #[account(mut)]
+    pub payout: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(
+        seeds = [b"role_config"],
+        bump = role_config.bump
+    )]
+    pub role_config: Account<'info, RoleConfig>,
+    #[account(
+        constraint = holds_role(&role_config, Role::FeeCollector, &global_config.authority, &collector.key())
+            @ ErrorCode::Unauthorized
+    )]
+    pub collector: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
+
+impl<'info> SweepFees<'info> {
+    /// Moves everything above the vault's rent-exempt minimum to `payout`.
+    pub fn sweep(&mut self) -> Result<u64> {
+        let rent_exempt_minimum = Rent::get()?.minimum_balance(self.fee_vault.to_account_info().data_len());
+        let amount = sweepable_fees(self.fee_vault.get_lamports(), rent_exempt_minimum);
+        require!(amount > 0, PoolError::NoFeesToSweep);
+        self.fee_vault.sub_lamports(amount)?;
+        self.payout.add_lamports(amount)?;
+        self.fee_vault.total_swept = self.fee_vault.total_swept
+            .checked_add(amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        Ok(amount)
+    }
+}
@@ -0,0 +1,159 @@
+//! Fuzz harnesses for the code that reads attacker-controlled bytes before
+//! any proof has been checked: the transact instruction-data decoder, the
+//! proof parser, the Groth16 verifier and the endianness conversion between
+//! them. Built only with `--features fuzzing`.
+//!
+//! The cargo-fuzz targets in `fuzz/` and `tests/unit/fuzzing_test.rs` call
+//! the same harnesses, so libFuzzer and `cargo test` check the same
+//! properties. Every harness must return for any input: a panic, an
+//! overflow or an out-of-bounds read is the bug being looked for.
+
+use anchor_lang::prelude::*;
+use arbitrary::Arbitrary;
+use crate::groth16::{Groth16Verifier, Groth16Verifyingkey};
+use crate::transact_data::{encode_transact_data, parse_transact_data};
+use crate::utils::{change_endianness, change_endianness_inplace, verify_proof, ExtDataFields, VERIFYING_KEY};
+use crate::{CpiTarget, ForeignRecipient, Proof, SwapParams, PROOF_LEN};
+
+/// Inputs to `Groth16Verifier::new`, with a verifying key that keeps the
+/// real key's points but takes any number of `vk_ic` entries.
+#[derive(Arbitrary, Debug)]
+pub struct VerifierInput {
+    pub proof_a: [u8; 64],
+    pub proof_b: [u8; 128],
+    pub proof_c: [u8; 64],
+    pub public_inputs: [[u8; 32]; 7],
+    pub vk_ic: Vec<[u8; 64]>,
+}
+
+/// `new` accepts exactly the keys with one `vk_ic` entry per public input
+/// plus one, and a verifier it builds from arbitrary bytes never accepts.
+pub fn groth16_verifier_new(input: &VerifierInput) {
+    let verifying_key = Groth16Verifyingkey { vk_ic: &input.vk_ic, ..VERIFYING_KEY };
+    let verifier = Groth16Verifier::new(
+        &input.proof_a,
+        &input.proof_b,
+        &input.proof_c,
+        &input.public_inputs,
+        &verifying_key,
+    );
+    assert_eq!(verifier.is_ok(), input.vk_ic.len() == input.public_inputs.len() + 1);
+    if let Ok(mut verifier) = verifier {
+        assert!(!matches!(verifier.verify(), Ok(true)), "verifier accepted an arbitrary proof");
+    }
+}
+
+/// Borsh-encoded proof bytes through `Proof::try_from` and, if they parse,
+/// `verify_proof`. Seeded with a real proof both succeed, so only the
+/// absence of a panic is asserted.
+pub fn verify_proof_bytes(bytes: &[u8]) {
+    if let Ok(proof) = Proof::try_from(bytes) {
+        let _ = verify_proof(proof, VERIFYING_KEY);
+    }
+}
+
+/// `change_endianness` keeps the length, is its own inverse, and agrees
+/// with `change_endianness_inplace` on fixed-size prefixes.
+pub fn change_endianness_roundtrip(bytes: &[u8]) {
+    let swapped = change_endianness(bytes);
+    assert_eq!(swapped.len(), bytes.len());
+    assert_eq!(change_endianness(&swapped), bytes);
+
+    if let Some(prefix) = bytes.get(..64) {
+        let mut inplace: [u8; 64] = prefix.try_into().unwrap();
+        change_endianness_inplace(&mut inplace);
+        assert_eq!(inplace[..], swapped[..64]);
+    }
+}
+
+/// Raw instruction data through `parse_transact_data`. Whatever parses
+/// must encode back to the same bytes, or two encodings of one transaction
+/// would exist and the header's lengths couldn't be trusted.
+pub fn transact_data_bytes(bytes: &[u8]) {
+    let Ok(data) = parse_transact_data(bytes) else {
+        return;
+    };
+    let encoded = encode_transact_data(&Proof::from(data.proof), data.version, &data.ext_data).unwrap();
+    assert_eq!(encoded, bytes, "transact data did not round trip");
+}
+
+/// A transact instruction built field by field, so the fuzzer spends its
+/// time past the header checks instead of on lengths that don't add up.
+#[derive(Arbitrary, Debug)]
+pub struct TransactInput {
+    pub proof: [u8; PROOF_LEN],
+    pub version: u8,
+    pub recipient: [u8; 32],
+    pub ext_amount: i64,
+    pub encrypted_output1: Vec<u8>,
+    pub encrypted_output2: Vec<u8>,
+    pub fee: u64,
+    pub fee_recipient: [u8; 32],
+    pub mint_address: [u8; 32],
+    pub memo: Vec<u8>,
+    pub expiry_slot: u64,
+    pub max_fee: u64,
+    pub relayer: Option<[u8; 32]>,
+    /// Program, output mint, minimum output and route hash.
+    pub swap: Option<([u8; 32], [u8; 32], u64, [u8; 32])>,
+    pub cpi: Option<([u8; 32], [u8; 32])>,
+    pub foreign_recipient: Option<(u16, [u8; 32])>,
+    pub memo_cpi: bool,
+}
+
+/// Encodes `input` and checks the decoder gives back every field.
+pub fn transact_data_structured(input: &TransactInput) {
+    let proof = Proof::try_from_slice(&input.proof).unwrap();
+    let ext_data = ExtDataFields {
+        recipient: Pubkey::new_from_array(input.recipient),
+        ext_amount: input.ext_amount,
+        encrypted_output1: &input.encrypted_output1,
+        encrypted_output2: &input.encrypted_output2,
+        fee: input.fee,
+        fee_recipient: Pubkey::new_from_array(input.fee_recipient),
+        mint_address: Pubkey::new_from_array(input.mint_address),
+        memo: &input.memo,
+        expiry_slot: input.expiry_slot,
+        max_fee: input.max_fee,
+        relayer: input.relayer.map(Pubkey::new_from_array),
+        swap: input.swap.map(|(program, output_mint, min_output_amount, route_hash)| SwapParams {
+            program: Pubkey::new_from_array(program),
+            output_mint: Pubkey::new_from_array(output_mint),
+            min_output_amount,
+            route_hash,
+        }),
+        cpi: input.cpi.map(|(program, data_hash)| CpiTarget { program: Pubkey::new_from_array(program), data_hash }),
+        foreign_recipient: input
+            .foreign_recipient
+            .map(|(chain_id, address)| ForeignRecipient { chain_id, address }),
+        memo_cpi: input.memo_cpi,
+    };
+    // Only fields longer than a u16 length can describe fail to encode
+    let Ok(bytes) = encode_transact_data(&proof, input.version, &ext_data) else {
+        return;
+    };
+
+    let data = parse_transact_data(&bytes).unwrap();
+    assert_eq!(bytemuck::bytes_of(data.proof), &input.proof[..]);
+    assert_eq!(data.version, input.version);
+    assert_eq!(data.ext_data.recipient, ext_data.recipient);
+    assert_eq!(data.ext_data.ext_amount, ext_data.ext_amount);
+    assert_eq!(data.ext_data.encrypted_output1, ext_data.encrypted_output1);
+    assert_eq!(data.ext_data.encrypted_output2, ext_data.encrypted_output2);
+    assert_eq!(data.ext_data.fee, ext_data.fee);
+    assert_eq!(data.ext_data.fee_recipient, ext_data.fee_recipient);
+    assert_eq!(data.ext_data.mint_address, ext_data.mint_address);
+    assert_eq!(data.ext_data.memo, ext_data.memo);
+    assert_eq!(data.ext_data.expiry_slot, ext_data.expiry_slot);
+    assert_eq!(data.ext_data.max_fee, ext_data.max_fee);
+    assert_eq!(data.ext_data.relayer, ext_data.relayer);
+    assert_eq!(data.ext_data.swap, ext_data.swap);
+    assert_eq!(data.ext_data.cpi, ext_data.cpi);
+    assert_eq!(data.ext_data.foreign_recipient, ext_data.foreign_recipient);
+    assert_eq!(data.ext_data.memo_cpi, ext_data.memo_cpi);
+
+    // And any truncation of it is rejected, not misread
+    for len in [0, PROOF_LEN - 1, PROOF_LEN, bytes.len() - 1] {
+        assert!(parse_transact_data(&bytes[..len]).is_err());
+    }
+}
@@ -28,7 +28,8 @@
 //! See functional test for a running example how to use this library.
 //!
 use crate::errors::Groth16Error;
-use ark_ff::PrimeField;
+use anchor_lang::solana_program::hash::hashv;
+use ark_serialize::{CanonicalDeserialize, Compress, Validate};
 use num_bigint::BigUint;
 use solana_bn254::prelude::{alt_bn128_addition, alt_bn128_multiplication, alt_bn128_pairing};
 
@@ -87,44 +88,47 @@ impl<const NR_INPUTS: usize> Groth16Verifier<'_, NR_INPUTS> {
     }
 
     pub fn prepare_inputs<const CHECK: bool>(&mut self) -> Result<(), Groth16Error> {
-        let mut prepared_public_inputs = self.verifyingkey.vk_ic[0];
-
-        for (i, input) in self.public_inputs.iter().enumerate() {
-            if CHECK && !is_less_than_bn254_field_size_be(input) {
-                return Err(Groth16Error::PublicInputGreaterThanFieldSize);
-            }
-            let mul_res = alt_bn128_multiplication(
-                &[&self.verifyingkey.vk_ic[i + 1][..], &input[..]].concat(),
-            )
-            .map_err(|_| Groth16Error::PreparingInputsG1MulFailed)?;
-            prepared_public_inputs =
-                alt_bn128_addition(&[&mul_res[..], &prepared_public_inputs[..]].concat())
-                    .map_err(|_| Groth16Error::PreparingInputsG1AdditionFailed)?[..]
-                    .try_into()
-                    .map_err(|_| Groth16Error::PreparingInputsG1AdditionFailed)?;
+        if CHECK && !self.public_inputs.iter().all(is_less_than_bn254_field_size_be) {
+            return Err(Groth16Error::PublicInputGreaterThanFieldSize);
         }
-
-        self.prepared_public_inputs = prepared_public_inputs;
+        self.prepared_public_inputs =
+            g1_msm(&self.verifyingkey.vk_ic[0], &self.verifyingkey.vk_ic[1..], self.public_inputs)?;
 
         Ok(())
     }
 
-    /// Verifies the proof, and checks that public inputs are smaller than
-    /// field size.
+    /// Checks that `proof_a` and `proof_c` are on G1 and `proof_b` is on G2
+    /// and in its prime-order subgroup. The syscalls check curve membership
+    /// but not the G2 subgroup, which has a large cofactor, so a point
+    /// outside it would otherwise reach the pairing.
+    pub fn validate_points(&self) -> Result<(), Groth16Error> {
+        validate_g1_point(self.proof_a)?;
+        validate_g2_point(self.proof_b)?;
+        validate_g1_point(self.proof_c)
+    }
+
+    /// Verifies the proof, and checks that the proof points are valid and
+    /// public inputs are smaller than field size.
     pub fn verify(&mut self) -> Result<bool, Groth16Error> {
         self.verify_common::<true>()
     }
 
-    /// Verifies the proof, and does not check that public inputs are smaller
-    /// than field size.
+    /// Verifies the proof, and does not check the proof points or that
+    /// public inputs are smaller than field size. Not for on-chain use.
     pub fn verify_unchecked(&mut self) -> Result<bool, Groth16Error> {
         self.verify_common::<false>()
     }
 
     fn verify_common<const CHECK: bool>(&mut self) -> Result<bool, Groth16Error> {
+        if CHECK {
+            self.validate_points()?;
+        }
         self.prepare_inputs::<CHECK>()?;
 
-        let pairing_input = [
+        // Four (G1, G2) pairs, assembled on the stack
+        let mut pairing_input = [0u8; 4 * 192];
+        let mut offset = 0;
+        for part in [
             self.proof_a.as_slice(),
             self.proof_b.as_slice(),
             self.prepared_public_inputs.as_slice(),
@@ -133,8 +137,10 @@ impl<const NR_INPUTS: usize> Groth16Verifier<'_, NR_INPUTS> {
             self.verifyingkey.vk_delta_g2.as_slice(),
             self.verifyingkey.vk_alpha_g1.as_slice(),
             self.verifyingkey.vk_beta_g2.as_slice(),
-        ]
-        .concat();
+        ] {
+            pairing_input[offset..offset + part.len()].copy_from_slice(part);
+            offset += part.len();
+        }
 
         let pairing_res = alt_bn128_pairing(pairing_input.as_slice())
             .map_err(|_| Groth16Error::ProofVerificationFailed)?;
@@ -146,7 +152,203 @@ impl<const NR_INPUTS: usize> Groth16Verifier<'_, NR_INPUTS> {
     }
 }
 
+impl<'a, const NR_INPUTS: usize> Groth16Verifier<'a, NR_INPUTS> {
+    /// Verifies every proof in `verifiers` with one multi-pairing. Each
+    /// proof's equation is scaled by a random 128-bit weight `r_i` and the
+    /// equations are summed:
+    ///
+    /// `prod e(-r_i A_i, B_i) * e(sum r_i P_i, gamma) * e(sum r_i C_i, delta)
+    ///  * e((sum r_i) alpha, beta) == 1`
+    ///
+    /// so K proofs cost K + 3 pairings and 3K scalar multiplications instead
+    /// of 4K pairings. The weights are derived from a hash of every proof and
+    /// public input, so no prover can pick proofs that cancel out; a batch
+    /// holding one invalid proof passes with probability about 2^-128.
+    /// Proof points and public inputs are checked as in `verify`.
+    ///
+    /// All proofs must share one verifying key. A failing batch doesn't say
+    /// which proof is bad; callers that need to know retry one by one.
+    pub fn verify_batch(verifiers: &mut [Groth16Verifier<'a, NR_INPUTS>]) -> Result<bool, Groth16Error> {
+        let verifyingkey = match verifiers {
+            [] => return Err(Groth16Error::InvalidBatch),
+            [verifier] => return verifier.verify(),
+            [first, ..] => first.verifyingkey,
+        };
+        if verifiers.iter().any(|verifier| verifier.verifyingkey != verifyingkey) {
+            return Err(Groth16Error::InvalidBatch);
+        }
+        for verifier in verifiers.iter_mut() {
+            verifier.validate_points()?;
+            verifier.prepare_inputs::<true>()?;
+        }
+
+        let weights = batch_weights(verifiers);
+        let mut pairing_input = Vec::with_capacity((verifiers.len() + 3) * 192);
+        let mut weight_sum = BigUint::default();
+        for (verifier, weight) in verifiers.iter().zip(&weights) {
+            pairing_input.extend_from_slice(&g1_mul(verifier.proof_a, weight)?);
+            pairing_input.extend_from_slice(verifier.proof_b);
+            weight_sum += BigUint::from_bytes_be(weight);
+        }
+        let weight_sum = scalar_be(&weight_sum);
+        let prepared: Vec<[u8; 64]> = verifiers.iter().map(|verifier| verifier.prepared_public_inputs).collect();
+        let proofs_c: Vec<[u8; 64]> = verifiers.iter().map(|verifier| *verifier.proof_c).collect();
+        let inputs_sum = g1_msm(&G1_INFINITY, &prepared, &weights).map_err(|_| Groth16Error::ProofVerificationFailed)?;
+        let c_sum = g1_msm(&G1_INFINITY, &proofs_c, &weights).map_err(|_| Groth16Error::ProofVerificationFailed)?;
+        for (g1, g2) in [
+            (inputs_sum, &verifyingkey.vk_gamme_g2),
+            (c_sum, &verifyingkey.vk_delta_g2),
+            (g1_mul(&verifyingkey.vk_alpha_g1, &weight_sum)?, &verifyingkey.vk_beta_g2),
+        ] {
+            pairing_input.extend_from_slice(&g1);
+            pairing_input.extend_from_slice(g2);
+        }
+
+        let pairing_res = alt_bn128_pairing(pairing_input.as_slice())
+            .map_err(|_| Groth16Error::ProofVerificationFailed)?;
+
+        if pairing_res[31] != 1 {
+            return Err(Groth16Error::ProofVerificationFailed);
+        }
+        Ok(true)
+    }
+}
+
+/// One 128-bit weight per verifier, as a big-endian scalar: the first 16
+/// bytes of `sha256(transcript || i)`, where the transcript hashes every
+/// proof and public input in the batch. Never zero, or that proof would
+/// drop out of the check.
+fn batch_weights<const NR_INPUTS: usize>(verifiers: &[Groth16Verifier<'_, NR_INPUTS>]) -> Vec<[u8; 32]> {
+    let mut transcript: Vec<&[u8]> = Vec::with_capacity(verifiers.len() * (3 + NR_INPUTS));
+    for verifier in verifiers {
+        transcript.extend([verifier.proof_a.as_slice(), verifier.proof_b.as_slice(), verifier.proof_c.as_slice()]);
+        transcript.extend(verifier.public_inputs.iter().map(|input| input.as_slice()));
+    }
+    let transcript = hashv(&transcript).to_bytes();
+    (0..verifiers.len() as u64)
+        .map(|i| {
+            let digest = hashv(&[transcript.as_slice(), &i.to_le_bytes()]).to_bytes();
+            let mut weight = [0u8; 32];
+            weight[16..].copy_from_slice(&digest[..16]);
+            if weight == [0u8; 32] {
+                weight[31] = 1;
+            }
+            weight
+        })
+        .collect()
+}
+
+fn scalar_be(value: &BigUint) -> [u8; 32] {
+    let bytes = value.to_bytes_be();
+    let mut scalar = [0u8; 32];
+    scalar[32 - bytes.len()..].copy_from_slice(&bytes);
+    scalar
+}
+
+/// The point at infinity in the syscalls' encoding.
+pub const G1_INFINITY: [u8; 64] = [0u8; 64];
+
+/// `base + sum scalars[i] * points[i]` over G1, with big-endian points and
+/// scalars as the alt_bn128 syscalls take them.
+///
+/// There is no multi-scalar multiplication syscall, so this still costs one
+/// multiplication and one addition per term, but skips both for zero
+/// scalars and the multiplication for scalars of one, which public inputs
+/// such as a zero `public_amount` often are. Syscall inputs are built on the
+/// stack rather than concatenated into a new Vec per term. Once an MSM
+/// syscall exists, this is the one place to switch over.
+pub fn g1_msm(base: &[u8; 64], points: &[[u8; 64]], scalars: &[[u8; 32]]) -> Result<[u8; 64], Groth16Error> {
+    if points.len() != scalars.len() {
+        return Err(Groth16Error::InvalidPublicInputsLength);
+    }
+    let mut one = [0u8; 32];
+    one[31] = 1;
+    let mut sum = *base;
+    for (point, scalar) in points.iter().zip(scalars) {
+        if *scalar == [0u8; 32] {
+            continue;
+        }
+        let term = if *scalar == one {
+            *point
+        } else {
+            g1_mul(point, scalar).map_err(|_| Groth16Error::PreparingInputsG1MulFailed)?
+        };
+        sum = g1_add(&term, &sum).map_err(|_| Groth16Error::PreparingInputsG1AdditionFailed)?;
+    }
+    Ok(sum)
+}
+
+fn g1_mul(point: &[u8; 64], scalar: &[u8; 32]) -> Result<[u8; 64], Groth16Error> {
+    let mut input = [0u8; 96];
+    input[..64].copy_from_slice(point);
+    input[64..].copy_from_slice(scalar);
+    alt_bn128_multiplication(&input)
+        .map_err(|_| Groth16Error::ProofVerificationFailed)?
+        .try_into()
+        .map_err(|_| Groth16Error::ProofVerificationFailed)
+}
+
+fn g1_add(left: &[u8; 64], right: &[u8; 64]) -> Result<[u8; 64], Groth16Error> {
+    let mut input = [0u8; 128];
+    input[..64].copy_from_slice(left);
+    input[64..].copy_from_slice(right);
+    alt_bn128_addition(&input)
+        .map_err(|_| Groth16Error::ProofVerificationFailed)?
+        .try_into()
+        .map_err(|_| Groth16Error::ProofVerificationFailed)
+}
+
+/// Checks a big-endian `(x, y)` point, as the syscalls take it, is on G1.
+/// G1 has cofactor 1, so the subgroup check only guards against a future
+/// curve swap.
+pub fn validate_g1_point(point: &[u8; 64]) -> Result<(), Groth16Error> {
+    let mut le = *point;
+    le[..32].reverse();
+    le[32..].reverse();
+    let point = ark_bn254::G1Affine::deserialize_with_mode(&le[..], Compress::No, Validate::No)
+        .map_err(|_| Groth16Error::G1PointNotOnCurve)?;
+    if !point.is_on_curve() {
+        return Err(Groth16Error::G1PointNotOnCurve);
+    }
+    if !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(Groth16Error::G1PointNotInSubgroup);
+    }
+    Ok(())
+}
+
+/// Checks a big-endian `(x_c1, x_c0, y_c1, y_c0)` point, as the syscalls
+/// take it, is on G2 and in its prime-order subgroup.
+pub fn validate_g2_point(point: &[u8; 128]) -> Result<(), Groth16Error> {
+    // Reversing each 64-byte half turns `c1 || c0` big-endian into the
+    // `c0 || c1` little-endian order arkworks reads
+    let mut le = *point;
+    le[..64].reverse();
+    le[64..].reverse();
+    let point = ark_bn254::G2Affine::deserialize_with_mode(&le[..], Compress::No, Validate::No)
+        .map_err(|_| Groth16Error::G2PointNotOnCurve)?;
+    if !point.is_on_curve() {
+        return Err(Groth16Error::G2PointNotOnCurve);
+    }
+    if !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(Groth16Error::G2PointNotInSubgroup);
+    }
+    Ok(())
+}
+
+/// The BN254 scalar field modulus `r`, big-endian.
+pub const BN254_FR_MODULUS_BE: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x28, 0x33, 0xe8, 0x48, 0x79, 0xb9, 0x70, 0x91, 0x43, 0xe1, 0xf5, 0x93, 0xf0, 0x00, 0x00, 0x01,
+];
+
+/// The BN254 base field modulus `q`, big-endian.
+pub const BN254_FQ_MODULUS_BE: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x97, 0x81, 0x6a, 0x91, 0x68, 0x71, 0xca, 0x8d, 0x3c, 0x20, 0x8c, 0x16, 0xd8, 0x7c, 0xfd, 0x47,
+];
+
+/// Big-endian byte arrays of equal length compare like the integers they
+/// encode, so this needs no bignum allocation.
 pub fn is_less_than_bn254_field_size_be(bytes: &[u8; 32]) -> bool {
-    let bigint = BigUint::from_bytes_be(bytes);
-    bigint < ark_bn254::Fr::MODULUS.into()
+    *bytes < BN254_FR_MODULUS_BE
 } 
\ No newline at end of file
@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use crate::errors::PoolError;
+use crate::zero_hashes::ZeroHashes;
+use crate::{is_known_root, MerkleTreeAccount, RootHistory};
+
+/// The root a Merkle `path` leads to from `leaf` at `index`. `path` holds
+/// the siblings from the leaf level up, one per level of the tree.
+pub fn merkle_path_root<H: ZeroHashes>(leaf: [u8; 32], index: u64, path: &[[u8; 32]]) -> Result<[u8; 32]> {
+    require!(path.len() < 64 && index >> path.len() == 0, PoolError::InvalidInclusionProof);
+    let mut node = leaf;
+    for (level, sibling) in path.iter().enumerate() {
+        node = if (index >> level) & 1 == 1 {
+            H::hashv(&[sibling, &node])
+        } else {
+            H::hashv(&[&node, sibling])
+        }
+        .map_err(|_| PoolError::InvalidInclusionProof)?;
+    }
+    Ok(node)
+}
+
+/// Whether `leaf` is at `index` under `root`. For programs that link this
+/// crate; others can CPI into `verify_inclusion`, which also checks `root`
+/// against the tree.
+pub fn verify_inclusion<H: ZeroHashes>(leaf: [u8; 32], index: u64, path: &[[u8; 32]], root: [u8; 32]) -> bool {
+    merkle_path_root::<H>(leaf, index, path).is_ok_and(|computed| computed == root)
+}
+
+/// Errors unless `leaf` is at `index` of `tree` under a root the tree still
+/// knows, i.e. one a withdrawal could use. `path` must span the tree's
+/// height, and `index` must already be filled.
+pub fn validate_inclusion<H: ZeroHashes>(
+    tree: &MerkleTreeAccount,
+    root_history: Option<&RootHistory>,
+    leaf: [u8; 32],
+    index: u64,
+    path: &[[u8; 32]],
+    root: [u8; 32],
+) -> Result<()> {
+    require!(is_known_root(tree, root_history, &root), PoolError::UnknownInclusionRoot);
+    require!(
+        index < tree.next_index
+            && path.len() == tree.height as usize
+            && verify_inclusion::<H>(leaf, index, path, root),
+        PoolError::InvalidInclusionProof
+    );
+    Ok(())
+}
+
+/// Read-only and permissionless, so other programs can prove a commitment
+/// is in the pool with a CPI. Pass the tree's `root_history` if it has one;
+/// roots are then checked against it instead of the tree's own history.
+#[derive(Accounts)]
+pub struct VerifyInclusion<'info> {
+    pub tree_account: AccountLoader<'info, MerkleTreeAccount>,
+    #[account(
+        seeds = [b"root_history", tree_account.key().as_ref()],
+        bump
+    )]
+    pub root_history: Option<AccountLoader<'info, RootHistory>>,
+}
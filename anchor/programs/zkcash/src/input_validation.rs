@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use crate::errors::PoolError;
+use crate::utils::{validate_withdrawal_mode, ExtDataFields};
+use crate::{validate_memo, validate_memo_cpi};
+
+/// Config values the ExtData of a transact is checked against.
+#[derive(Clone, Copy, Debug)]
+pub struct InputLimits {
+    /// `EncryptedOutputConfig::max_len`.
+    pub max_encrypted_output_len: u16,
+    /// The tree account's `max_deposit_amount`.
+    pub max_deposit_amount: u64,
+}
+
+/// Sanity checks on a transact's ExtData, run at instruction entry before
+/// any hashing, proof work or transfer. Each failure has its own error, so a
+/// fuzzer or a hostile client learns which field was off instead of
+/// reaching an arithmetic panic or a confusing proof failure later:
+///
+/// - `ext_amount` is not `i64::MIN` (its absolute value doesn't fit) and a
+///   deposit is at most `max_deposit_amount`: `ExtAmountOutOfRange`
+/// - `|ext_amount| + fee` fits an `i64`, and a deposit covers its fee:
+///   `FeeOutOfRange`
+/// - both encrypted outputs are non-empty and at most the configured
+///   length: `EmptyEncryptedOutput` or `EncryptedOutputTooLarge`
+/// - the memo is at most `MAX_MEMO_LEN`: `MemoTooLong`
+/// - a memo CPI is a withdrawal with a UTF-8 memo: `InvalidMemoCpi`
+/// - a withdrawal names a recipient: `InvalidRecipient`
+/// - at most one withdrawal mode: `ConflictingWithdrawalModes`
+///
+/// Proof bytes are length-checked on their own by `Proof::try_from`.
+pub fn validate_ext_data_inputs(ext_data: &ExtDataFields, limits: &InputLimits) -> Result<()> {
+    let ext_amount = ext_data.ext_amount;
+    require!(
+        ext_amount != i64::MIN && ext_amount <= limits.max_deposit_amount.min(i64::MAX as u64) as i64,
+        PoolError::ExtAmountOutOfRange
+    );
+    let total = ext_amount.unsigned_abs() as u128 + ext_data.fee as u128;
+    require!(
+        total <= i64::MAX as u128 && (ext_amount <= 0 || ext_data.fee < ext_amount as u64),
+        PoolError::FeeOutOfRange
+    );
+
+    let max_len = limits.max_encrypted_output_len as usize;
+    for encrypted_output in [ext_data.encrypted_output1, ext_data.encrypted_output2] {
+        require!(!encrypted_output.is_empty(), PoolError::EmptyEncryptedOutput);
+        require!(encrypted_output.len() <= max_len, PoolError::EncryptedOutputTooLarge);
+    }
+    validate_memo(ext_data.memo)?;
+    validate_memo_cpi(ext_data.memo, ext_data.memo_cpi, ext_amount)?;
+
+    if ext_amount < 0 {
+        require!(ext_data.recipient != Pubkey::default(), PoolError::InvalidRecipient);
+    }
+    validate_withdrawal_mode(ext_data)
+}
+
+/// Fails unless `account` is owned by `owner`, e.g. a recipient token
+/// account passed unchecked that must belong to the token program.
+pub fn validate_account_owner(account: &AccountInfo, owner: &Pubkey) -> Result<()> {
+    require_keys_eq!(*account.owner, *owner, PoolError::InvalidAccountOwner);
+    Ok(())
+}
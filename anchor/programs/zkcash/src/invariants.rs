@@ -0,0 +1,76 @@
+use anchor_lang::prelude::*;
+use crate::{BalanceAudit, MerkleTreeAccount, PoolStats, TreeTokenAccount, YieldConfig};
+
+/// What `check_invariants` hands back through return data. The instruction
+/// succeeds either way, so a monitor reads `passed` and the figures behind
+/// it rather than parsing an error.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvariantReport {
+    /// The pool holds at least what it owes: `actual_lamports >= liabilities`.
+    pub solvent: bool,
+    /// The tree hasn't been filled past its leaves: `next_index <= capacity`.
+    pub within_capacity: bool,
+    /// Lamports the tree token account holds above rent, plus any staked.
+    pub actual_lamports: u64,
+    /// Deposits minus withdrawals and fees, from `PoolStats`.
+    pub liabilities: u64,
+    pub next_index: u64,
+    /// `2^height`.
+    pub capacity: u64,
+}
+
+#[event]
+pub struct InvariantViolated {
+    pub solvent: bool,
+    pub within_capacity: bool,
+    pub actual_lamports: u64,
+    pub liabilities: u64,
+    pub next_index: u64,
+    pub capacity: u64,
+    pub slot: u64,
+}
+
+impl InvariantReport {
+    pub fn new(audit: &BalanceAudit, next_index: u64, height: u8) -> Self {
+        let capacity = 1u64.checked_shl(height as u32).unwrap_or(u64::MAX);
+        InvariantReport {
+            solvent: !audit.has_deficit(),
+            within_capacity: next_index <= capacity,
+            actual_lamports: audit.actual_lamports,
+            liabilities: audit.expected_lamports,
+            next_index,
+            capacity,
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.solvent && self.within_capacity
+    }
+}
+
+/// Permissionless and read-only, so a monitor can run it every slot by
+/// simulation and page operators when `passed` is false.
+#[derive(Accounts)]
+pub struct CheckInvariants<'info> {
+    #[account(
+        seeds = [b"merkle_tree"],
+        bump = tree_account.load()?.bump
+    )]
+    pub tree_account: AccountLoader<'info, MerkleTreeAccount>,
+    #[account(
+        seeds = [b"pool_stats"],
+        bump = pool_stats.bump
+    )]
+    pub pool_stats: Account<'info, PoolStats>,
+    #[account(
+        seeds = [b"tree_token"],
+        bump = tree_token_account.bump
+    )]
+    pub tree_token_account: Account<'info, TreeTokenAccount>,
+    /// Only needed once idle SOL has been staked.
+    #[account(
+        seeds = [b"yield_config"],
+        bump = yield_config.bump
+    )]
+    pub yield_config: Option<Account<'info, YieldConfig>>,
+}
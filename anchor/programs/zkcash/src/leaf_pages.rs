@@ -0,0 +1,154 @@
+use anchor_lang::prelude::*;
+use crate::errors::PoolError;
+use crate::{holds_role, AuditLog, ErrorCode, GlobalConfig, MerkleTreeAccount, Role, RoleConfig};
+
+/// Leaves per page. Even, so the two outputs of one transact always land in
+/// the same page, and small enough that a page is created in one
+/// instruction.
+pub const LEAVES_PER_PAGE: usize = 256;
+
+pub const LEAF_PAGE_ACCOUNT_LEN: usize = 8 + std::mem::size_of::<LeafPage>();
+
+/// Whether transact also writes every leaf to a `LeafPage`, at
+/// `["leaf_storage_config"]`. Off by default: pages cost about 0.06 SOL of
+/// rent per 256 leaves, paid by whoever creates them.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct LeafStorageConfig {
+    pub enabled: bool,
+    pub bump: u8,
+}
+
+#[event]
+pub struct LeafStorageUpdated {
+    pub enabled: bool,
+}
+
+/// Leaves `page_index * LEAVES_PER_PAGE ..` of a tree, at
+/// `["leaf_page", tree_account, page_index as u64 LE]`, filled in append
+/// order. With leaf storage on from the tree's first deposit, a client can
+/// rebuild the whole tree by reading pages `0..` until one isn't full, with
+/// no archival RPC or indexer. Turned on later, pages start at the next page
+/// boundary and a client needs a snapshot for the leaves before it.
+#[account(zero_copy)]
+pub struct LeafPage {
+    pub tree_account: Pubkey,
+    pub page_index: u64,
+    /// Leaves written so far; `leaves[..len]` are valid.
+    pub len: u64,
+    pub leaves: [[u8; 32]; LEAVES_PER_PAGE],
+}
+
+#[event]
+pub struct LeafPageInitialized {
+    pub tree_account: Pubkey,
+    pub leaf_page: Pubkey,
+    pub page_index: u64,
+}
+
+/// The page holding leaf `leaf_index`.
+pub fn leaf_page_index(leaf_index: u64) -> u64 {
+    leaf_index / LEAVES_PER_PAGE as u64
+}
+
+/// A new page must start at or after the tree's next leaf, so it never
+/// misses leaves appended before it existed, and at most one page ahead of
+/// the one being filled.
+pub fn validate_new_leaf_page(page_index: u64, next_index: u64) -> Result<()> {
+    let first_leaf_index = page_index
+        .checked_mul(LEAVES_PER_PAGE as u64)
+        .ok_or(PoolError::LeafPageOutOfOrder)?;
+    require!(
+        first_leaf_index >= next_index && page_index <= leaf_page_index(next_index) + 1,
+        PoolError::LeafPageOutOfOrder
+    );
+    Ok(())
+}
+
+impl LeafPage {
+    pub fn init(&mut self, tree_account: Pubkey, page_index: u64) {
+        self.tree_account = tree_account;
+        self.page_index = page_index;
+        self.len = 0;
+    }
+
+    pub fn first_leaf_index(&self) -> u64 {
+        self.page_index * LEAVES_PER_PAGE as u64
+    }
+
+    /// Stores the leaf the tree just appended at `leaf_index`. Leaves must
+    /// arrive in order, so a page never has gaps.
+    pub fn push(&mut self, leaf_index: u64, leaf: [u8; 32]) -> Result<()> {
+        require!(
+            leaf_index == self.first_leaf_index() + self.len && (self.len as usize) < LEAVES_PER_PAGE,
+            PoolError::LeafPageOutOfOrder
+        );
+        self.leaves[self.len as usize] = leaf;
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn stored_leaves(&self) -> &[[u8; 32]] {
+        &self.leaves[..self.len as usize]
+    }
+}
+
+#[derive(Accounts)]
+pub struct SetLeafStorage<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + LeafStorageConfig::INIT_SPACE,
+        seeds = [b"leaf_storage_config"],
+        bump
+    )]
+    pub leaf_storage_config: Account<'info, LeafStorageConfig>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(
+        seeds = [b"role_config"],
+        bump = role_config.bump
+    )]
+    pub role_config: Account<'info, RoleConfig>,
+    #[account(
+        mut,
+        constraint = holds_role(&role_config, Role::TreeOperator, &global_config.authority, &authority.key())
+            @ ErrorCode::Unauthorized
+    )]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
+
+/// Permissionless: a relayer or depositor creates the next page before the
+/// transact that first writes to it, and pays its rent.
+#[derive(Accounts)]
+#[instruction(page_index: u64)]
+pub struct InitializeLeafPage<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = LEAF_PAGE_ACCOUNT_LEN,
+        seeds = [b"leaf_page", tree_account.key().as_ref(), &page_index.to_le_bytes()],
+        bump
+    )]
+    pub leaf_page: AccountLoader<'info, LeafPage>,
+    pub tree_account: AccountLoader<'info, MerkleTreeAccount>,
+    #[account(
+        seeds = [b"leaf_storage_config"],
+        bump = leaf_storage_config.bump,
+        constraint = leaf_storage_config.enabled @ PoolError::LeafStorageDisabled
+    )]
+    pub leaf_storage_config: Account<'info, LeafStorageConfig>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 use light_hasher::Poseidon;
 use anchor_lang::solana_program::sysvar::rent::Rent;
+use anchor_lang::system_program;
 use ark_ff::PrimeField;
 use ark_bn254::Fr;
 
@@ -10,8 +11,110 @@ pub mod merkle_tree;
 pub mod utils;
 pub mod groth16;
 pub mod errors;
+pub mod zero_hashes;
+pub mod stats;
+pub mod anonymity;
+pub mod circuit_breaker;
+pub mod timelock;
+pub mod authority;
+pub mod versioning;
+pub mod registry;
+pub mod token_pool;
+pub mod deposit_limits;
+pub mod memo;
+pub mod encrypted_output;
+pub mod deposit_queue;
+pub mod concurrent_merkle_tree;
+pub mod canopy;
+pub mod transact_result;
+pub mod fee_recipient;
+pub mod fee_schedule;
+pub mod fee_exemption;
+pub mod relayer;
+pub mod withdrawal_queue;
+pub mod submission_commitment;
+pub mod nullifier_seeds;
+pub mod nullifier_bloom;
+pub mod balance_audit;
+pub mod rescue;
+pub mod tree_snapshot;
+pub mod root_slots;
+pub mod root_policy;
+pub mod root_history;
+pub mod inclusion;
+pub mod audit_log;
+pub mod treasury_yield;
+pub mod lst;
+pub mod swap;
+pub mod withdraw_cpi;
+pub mod wormhole;
+pub mod proof;
+pub mod vk_registry;
+pub mod input_validation;
+pub mod transact_data;
+pub mod circuit_version;
+pub mod tree_storage;
+pub mod leaf_pages;
+pub mod root_checkpoints;
+pub mod root_attestation;
+pub mod roles;
+pub mod fee_vault;
+pub mod invariants;
+#[cfg(all(kani, feature = "verification"))]
+mod verification;
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
 
 use merkle_tree::MerkleTree;
+use errors::PoolError;
+pub use stats::*;
+pub use anonymity::*;
+pub use circuit_breaker::*;
+pub use timelock::*;
+pub use authority::*;
+pub use versioning::*;
+pub use registry::*;
+pub use token_pool::*;
+pub use deposit_limits::*;
+pub use memo::*;
+pub use encrypted_output::*;
+pub use deposit_queue::*;
+pub use concurrent_merkle_tree::*;
+pub use canopy::*;
+pub use transact_result::*;
+pub use fee_recipient::*;
+pub use fee_schedule::*;
+pub use fee_exemption::*;
+pub use relayer::*;
+pub use withdrawal_queue::*;
+pub use submission_commitment::*;
+pub use nullifier_seeds::*;
+pub use nullifier_bloom::*;
+pub use balance_audit::*;
+pub use rescue::*;
+pub use tree_snapshot::*;
+pub use root_slots::*;
+pub use root_policy::*;
+pub use root_history::*;
+pub use inclusion::*;
+pub use audit_log::*;
+pub use treasury_yield::*;
+pub use lst::*;
+pub use swap::*;
+pub use withdraw_cpi::*;
+pub use wormhole::*;
+pub use proof::*;
+pub use vk_registry::*;
+pub use input_validation::*;
+pub use transact_data::*;
+pub use circuit_version::*;
+pub use tree_storage::*;
+pub use leaf_pages::*;
+pub use root_checkpoints::*;
+pub use root_attestation::*;
+pub use roles::*;
+pub use fee_vault::*;
+pub use invariants::*;
 
 // Constants
 const MERKLE_TREE_HEIGHT: u8 = 26;
@@ -55,6 +158,11 @@ pub mod zkcash {
         global_config.withdrawal_fee_rate = 100; // 1% (100 basis points)
         global_config.fee_error_margin = 500; // 5% (500 basis points)
         global_config.bump = ctx.bumps.global_config;
+        utils::validate_fee_config(
+            global_config.deposit_fee_rate,
+            global_config.withdrawal_fee_rate,
+            global_config.fee_error_margin,
+        )?;
         
         msg!("Sparse Merkle Tree initialized successfully with height: {}, root history size: {}, deposit limit: {} lamports, \
             deposit fee rate: {}, withdrawal fee rate: {}, fee error margin: {}",
@@ -62,5 +170,1382 @@ pub mod zkcash {
         Ok(())
     }
 
+    pub fn initialize_audit_log(ctx: Context<InitializeAuditLog>) -> Result<()> {
+        ctx.accounts.audit_log.bump = ctx.bumps.audit_log;
+        Ok(())
+    }
+
+    pub fn initialize_role_config(ctx: Context<InitializeRoleConfig>) -> Result<()> {
+        ctx.accounts.role_config.bump = ctx.bumps.role_config;
+        Ok(())
+    }
+
+    pub fn initialize_pool_stats(ctx: Context<InitializePoolStats>) -> Result<()> {
+        ctx.accounts.pool_stats.bump = ctx.bumps.pool_stats;
+        Ok(())
+    }
+
+    pub fn initialize_anonymity_stats(ctx: Context<InitializeAnonymityStats>) -> Result<()> {
+        ctx.accounts.anonymity_stats.bump = ctx.bumps.anonymity_stats;
+        Ok(())
+    }
+
+    pub fn initialize_nullifier_bloom_filter(ctx: Context<InitializeNullifierBloomFilter>) -> Result<()> {
+        ctx.accounts.nullifier_bloom.load_init()?.tree_account = ctx.accounts.tree_account.key();
+        Ok(())
+    }
+
+    /// Reconciles the SOL pool's balance against `PoolStats` and returns the
+    /// result.
+    pub fn audit_balances(ctx: Context<AuditBalances>) -> Result<BalanceAudit> {
+        let liquid = liquid_lamports(&ctx.accounts.tree_token_account.to_account_info())?;
+        let staked = ctx.accounts.yield_config.as_ref().map_or(0, |config| config.staked_lamports);
+        let audit = BalanceAudit::new(&ctx.accounts.pool_stats, liquid, staked)?;
+        if audit.has_deficit() {
+            msg!("Pool balance is {} lamports short of its recorded flows", audit.drift.unsigned_abs());
+        }
+
+        emit!(BalancesAudited {
+            actual_lamports: audit.actual_lamports,
+            expected_lamports: audit.expected_lamports,
+            drift: audit.drift,
+            slot: Clock::get()?.slot,
+        });
+        Ok(audit)
+    }
+
+    /// Checks the pool holds at least its recorded liabilities and the tree
+    /// hasn't outgrown its height, returning the detail either way.
+    pub fn check_invariants(ctx: Context<CheckInvariants>) -> Result<InvariantReport> {
+        let liquid = liquid_lamports(&ctx.accounts.tree_token_account.to_account_info())?;
+        let staked = ctx.accounts.yield_config.as_ref().map_or(0, |config| config.staked_lamports);
+        let audit = BalanceAudit::new(&ctx.accounts.pool_stats, liquid, staked)?;
+        let report = {
+            let tree = ctx.accounts.tree_account.load()?;
+            InvariantReport::new(&audit, tree.next_index, tree.height)
+        };
+
+        if !report.passed() {
+            msg!("Pool invariants violated: solvent {}, within capacity {}", report.solvent, report.within_capacity);
+            emit!(InvariantViolated {
+                solvent: report.solvent,
+                within_capacity: report.within_capacity,
+                actual_lamports: report.actual_lamports,
+                liabilities: report.liabilities,
+                next_index: report.next_index,
+                capacity: report.capacity,
+                slot: Clock::get()?.slot,
+            });
+        }
+        Ok(report)
+    }
+
+    /// Returns the anonymity set figures for the last `epochs` epochs, so
+    /// frontends can warn before a withdrawal from a thin pool.
+    pub fn anonymity_metrics(ctx: Context<GetAnonymityMetrics>, epochs: u8) -> Result<AnonymityMetrics> {
+        let tree = ctx.accounts.tree_account.load()?;
+        ctx.accounts.anonymity_stats.metrics(&tree, Clock::get()?.epoch, epochs)
+    }
+
+    /// Returns page `page` of the tree's snapshot, for bootstrapping a local
+    /// tree without replaying history.
+    pub fn export_tree_snapshot(ctx: Context<ExportTreeSnapshot>, page: u16) -> Result<TreeSnapshotPage> {
+        let tree = ctx.accounts.tree_account.load()?;
+        TreeSnapshot::from_tree(ctx.accounts.tree_account.key(), &tree).page(page)
+    }
+
+    /// Succeeds only if `leaf` is at `leaf_index` of the tree under `root`,
+    /// and `root` is still in its history. Meant for CPI from protocols that
+    /// accept "this commitment is in the pool" claims.
+    pub fn verify_inclusion(
+        ctx: Context<VerifyInclusion>,
+        leaf: [u8; 32],
+        leaf_index: u64,
+        path: Vec<[u8; 32]>,
+        root: [u8; 32],
+    ) -> Result<()> {
+        let tree = ctx.accounts.tree_account.load()?;
+        let root_history = ctx.accounts.root_history.as_ref().map(|account| account.load()).transpose()?;
+        validate_inclusion::<Poseidon>(&tree, root_history.as_deref(), leaf, leaf_index, &path, root)
+    }
+
+    /// Records the hash of the compiled-in verifying key, emitting
+    /// `VerifyingKeyUpdated` if it differs from the last one recorded.
+    pub fn record_verifying_key(ctx: Context<RecordVerifyingKey>) -> Result<()> {
+        let vk_hash = active_verifying_key_hash();
+        let record = &mut ctx.accounts.verifying_key_record;
+        record.bump = ctx.bumps.verifying_key_record;
+        if record.update(vk_hash, Clock::get()?.slot) {
+            msg!("Verifying key recorded: {:?}", vk_hash);
+        }
+        Ok(())
+    }
+
+    /// Succeeds only if the program verifies proofs with the key hashing to
+    /// `vk_hash`, so an auditor can check a deployment by simulation.
+    pub fn assert_verifying_key(_ctx: Context<AssertVerifyingKey>, vk_hash: [u8; 32]) -> Result<()> {
+        validate_verifying_key_hash(&vk_hash)
+    }
+
+    pub fn queue_verifying_key_rotation(ctx: Context<QueueVerifyingKeyRotation>, vk_hash: [u8; 32]) -> Result<()> {
+        let clock = Clock::get()?;
+        let rotation = &mut ctx.accounts.vk_rotation;
+        rotation.queue(vk_hash, clock.epoch)?;
+        rotation.bump = ctx.bumps.vk_rotation;
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::QueueVerifyingKeyRotation,
+            rotation.key(),
+            clock.slot,
+        )?;
+        msg!("Verifying key rotation queued, active from epoch {}", rotation.activation_epoch);
+        emit!(VerifyingKeyRotationQueued {
+            vk_hash,
+            activation_epoch: rotation.activation_epoch,
+        });
+        Ok(())
+    }
+
+    pub fn activate_verifying_key_rotation(ctx: Context<ActivateVerifyingKeyRotation>) -> Result<()> {
+        let rotation = &mut ctx.accounts.vk_rotation;
+        rotation.activate(Clock::get()?.epoch)?;
+        emit!(VerifyingKeyRotated {
+            previous_hash: rotation.previous_hash.unwrap_or_default(),
+            vk_hash: rotation.active_hash,
+            grace_end_epoch: rotation.grace_end_epoch,
+        });
+        Ok(())
+    }
+
+    /// Retires every circuit older than `min_version`. The new minimum must
+    /// have a verifying key in this build.
+    pub fn set_min_circuit_version(ctx: Context<SetMinCircuitVersion>, min_version: u8) -> Result<()> {
+        validate_min_circuit_version(min_version)?;
+        let config = &mut ctx.accounts.circuit_version_config;
+        config.min_version = min_version;
+        config.bump = ctx.bumps.circuit_version_config;
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::SetMinCircuitVersion,
+            config.key(),
+            Clock::get()?.slot,
+        )?;
+        emit!(MinCircuitVersionUpdated { min_version });
+        Ok(())
+    }
+
+    pub fn initialize_circuit_breaker(
+        ctx: Context<InitializeCircuitBreaker>,
+        max_outflow_bps: u16,
+        window_slots: u64,
+    ) -> Result<()> {
+        validate_circuit_breaker_config(max_outflow_bps, window_slots)?;
+
+        let circuit_breaker = &mut ctx.accounts.circuit_breaker;
+        circuit_breaker.guardian = ctx.accounts.authority.key();
+        circuit_breaker.max_outflow_bps = max_outflow_bps;
+        circuit_breaker.window_slots = window_slots;
+        circuit_breaker.window_start = Clock::get()?.slot;
+        circuit_breaker.bump = ctx.bumps.circuit_breaker;
+
+        msg!("Circuit breaker initialized: {} bps per {} slots", max_outflow_bps, window_slots);
+        Ok(())
+    }
+
+    pub fn update_circuit_breaker(
+        ctx: Context<UpdateCircuitBreaker>,
+        max_outflow_bps: Option<u16>,
+        window_slots: Option<u64>,
+    ) -> Result<()> {
+        let circuit_breaker = &mut ctx.accounts.circuit_breaker;
+        let max_outflow_bps = max_outflow_bps.unwrap_or(circuit_breaker.max_outflow_bps);
+        let window_slots = window_slots.unwrap_or(circuit_breaker.window_slots);
+        validate_circuit_breaker_config(max_outflow_bps, window_slots)?;
+
+        circuit_breaker.max_outflow_bps = max_outflow_bps;
+        circuit_breaker.window_slots = window_slots;
+        ctx.accounts.audit_log.record(
+            ctx.accounts.guardian.key(),
+            AdminAction::UpdateCircuitBreaker,
+            circuit_breaker.key(),
+            Clock::get()?.slot,
+        )?;
+        msg!("Circuit breaker updated: {} bps per {} slots", max_outflow_bps, window_slots);
+        Ok(())
+    }
+
+    pub fn resume_withdrawals(ctx: Context<UpdateCircuitBreaker>) -> Result<()> {
+        let slot = Clock::get()?.slot;
+        ctx.accounts.circuit_breaker.resume(slot);
+        ctx.accounts.audit_log.record(
+            ctx.accounts.guardian.key(),
+            AdminAction::ResumeWithdrawals,
+            ctx.accounts.circuit_breaker.key(),
+            slot,
+        )?;
+        emit!(WithdrawalsResumed {
+            guardian: ctx.accounts.guardian.key(),
+            slot,
+        });
+        Ok(())
+    }
+
+    pub fn set_pool_freeze(ctx: Context<SetPoolFreeze>, withdrawals_frozen: bool, deposits_frozen: bool) -> Result<()> {
+        let pool_freeze = &mut ctx.accounts.pool_freeze;
+        pool_freeze.withdrawals_frozen = withdrawals_frozen;
+        pool_freeze.deposits_frozen = deposits_frozen;
+        pool_freeze.bump = ctx.bumps.pool_freeze;
+        ctx.accounts.audit_log.record(
+            ctx.accounts.guardian.key(),
+            AdminAction::SetPoolFreeze,
+            pool_freeze.key(),
+            Clock::get()?.slot,
+        )?;
+        msg!("Pool freeze set: withdrawals {}, deposits {}", withdrawals_frozen, deposits_frozen);
+        emit!(PoolFreezeUpdated {
+            guardian: ctx.accounts.guardian.key(),
+            withdrawals_frozen,
+            deposits_frozen,
+            slot: Clock::get()?.slot,
+        });
+        Ok(())
+    }
+
+    /// Authority-only: moves the guardian role off the authority's key.
+    pub fn set_guardian(ctx: Context<SetGuardian>, guardian: Pubkey) -> Result<()> {
+        validate_guardian(&guardian)?;
+        let circuit_breaker = &mut ctx.accounts.circuit_breaker;
+        let previous_guardian = circuit_breaker.guardian;
+        circuit_breaker.guardian = guardian;
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::SetGuardian,
+            guardian,
+            Clock::get()?.slot,
+        )?;
+        emit!(GuardianUpdated {
+            previous_guardian,
+            guardian,
+        });
+        Ok(())
+    }
+
+    /// Authority-only: delegates `role` to `holder`, or returns it to the
+    /// authority with the default key.
+    pub fn set_role(ctx: Context<SetRole>, role: Role, holder: Pubkey) -> Result<()> {
+        let previous_holder = ctx.accounts.role_config.set(role, holder);
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::SetRole,
+            holder,
+            Clock::get()?.slot,
+        )?;
+        emit!(RoleUpdated {
+            role,
+            previous_holder,
+            holder,
+        });
+        Ok(())
+    }
+
+    /// Authority-only: sets where the fee collector sweeps the fee vault to,
+    /// creating the vault on first use.
+    pub fn set_fee_payout(ctx: Context<SetFeePayout>, payout: Pubkey) -> Result<()> {
+        validate_fee_payout(&payout)?;
+        let fee_vault = &mut ctx.accounts.fee_vault;
+        let old_payout = fee_vault.payout;
+        fee_vault.payout = payout;
+        fee_vault.bump = ctx.bumps.fee_vault;
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::SetFeePayout,
+            payout,
+            Clock::get()?.slot,
+        )?;
+        emit!(FeePayoutUpdated {
+            old_payout,
+            new_payout: payout,
+        });
+        Ok(())
+    }
+
+    pub fn sweep_fees(ctx: Context<SweepFees>) -> Result<()> {
+        let amount = ctx.accounts.sweep()?;
+        let payout = ctx.accounts.payout.key();
+        ctx.accounts.audit_log.record(
+            ctx.accounts.collector.key(),
+            AdminAction::SweepFees,
+            payout,
+            Clock::get()?.slot,
+        )?;
+        msg!("Swept {} lamports of fees to {}", amount, payout);
+        emit!(FeesSwept {
+            collector: ctx.accounts.collector.key(),
+            payout,
+            amount,
+        });
+        Ok(())
+    }
+
+    pub fn queue_config_update(
+        ctx: Context<QueueConfigUpdate>,
+        deposit_fee_rate: Option<u16>,
+        withdrawal_fee_rate: Option<u16>,
+        fee_error_margin: Option<u16>,
+        max_deposit_amount: Option<u64>,
+    ) -> Result<()> {
+        let slot = Clock::get()?.slot;
+        let pending = &mut ctx.accounts.pending_config;
+        pending.deposit_fee_rate = deposit_fee_rate;
+        pending.withdrawal_fee_rate = withdrawal_fee_rate;
+        pending.fee_error_margin = fee_error_margin;
+        pending.max_deposit_amount = max_deposit_amount;
+        pending.effective_slot = timelock::effective_slot(slot)?;
+        pending.bump = ctx.bumps.pending_config;
+        pending.validate(&ctx.accounts.global_config)?;
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::QueueConfigUpdate,
+            pending.key(),
+            slot,
+        )?;
+
+        msg!("Config update queued, executable from slot {}", pending.effective_slot);
+        emit!(ConfigUpdateQueued {
+            deposit_fee_rate,
+            withdrawal_fee_rate,
+            fee_error_margin,
+            max_deposit_amount,
+            effective_slot: pending.effective_slot,
+        });
+        Ok(())
+    }
+
+    pub fn execute_config_update(ctx: Context<ExecuteConfigUpdate>) -> Result<()> {
+        let pending = &ctx.accounts.pending_config;
+        require!(pending.is_ready(Clock::get()?.slot), PoolError::ConfigUpdateNotReady);
+        // Re-checked in case the caps changed in an upgrade since queueing
+        pending.validate(&ctx.accounts.global_config)?;
+
+        let global_config = &mut ctx.accounts.global_config;
+        if let Some(rate) = pending.deposit_fee_rate {
+            global_config.deposit_fee_rate = rate;
+        }
+        if let Some(rate) = pending.withdrawal_fee_rate {
+            global_config.withdrawal_fee_rate = rate;
+        }
+        if let Some(margin) = pending.fee_error_margin {
+            global_config.fee_error_margin = margin;
+        }
+        let tree_account = &mut ctx.accounts.tree_account.load_mut()?;
+        if let Some(limit) = pending.max_deposit_amount {
+            tree_account.max_deposit_amount = limit;
+        }
+
+        ctx.accounts.audit_log.record(
+            global_config.authority,
+            AdminAction::ExecuteConfigUpdate,
+            ctx.accounts.pending_config.key(),
+            Clock::get()?.slot,
+        )?;
+
+        msg!("Config update executed");
+        emit!(ConfigUpdateExecuted {
+            deposit_fee_rate: global_config.deposit_fee_rate,
+            withdrawal_fee_rate: global_config.withdrawal_fee_rate,
+            fee_error_margin: global_config.fee_error_margin,
+            max_deposit_amount: tree_account.max_deposit_amount,
+        });
+        Ok(())
+    }
+
+    pub fn cancel_config_update(ctx: Context<CancelConfigUpdate>) -> Result<()> {
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::CancelConfigUpdate,
+            ctx.accounts.pending_config.key(),
+            Clock::get()?.slot,
+        )?;
+        emit!(ConfigUpdateCancelled {
+            effective_slot: ctx.accounts.pending_config.effective_slot,
+        });
+        Ok(())
+    }
+
+    /// First step of an authority handover, see `PendingAuthority`. Proposing
+    /// again replaces the pending proposal.
+    pub fn propose_authority(ctx: Context<ProposeAuthority>, new_authority: Pubkey) -> Result<()> {
+        require!(new_authority != Pubkey::default(), PoolError::InvalidAuthority);
+
+        let pending = &mut ctx.accounts.pending_authority;
+        pending.authority = ctx.accounts.authority.key();
+        pending.new_authority = new_authority;
+        pending.bump = ctx.bumps.pending_authority;
+
+        let authority = pending.authority;
+        ctx.accounts.audit_log.record(authority, AdminAction::ProposeAuthority, new_authority, Clock::get()?.slot)?;
+
+        msg!("Authority transfer proposed: {} -> {}", authority, new_authority);
+        emit!(AuthorityTransferProposed {
+            authority,
+            new_authority,
+        });
+        Ok(())
+    }
+
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let previous_authority = ctx.accounts.pending_authority.authority;
+        let new_authority = ctx.accounts.new_authority.key();
+
+        ctx.accounts.tree_account.load_mut()?.authority = new_authority;
+        ctx.accounts.tree_token_account.authority = new_authority;
+        ctx.accounts.global_config.authority = new_authority;
+
+        ctx.accounts.audit_log.record(new_authority, AdminAction::AcceptAuthority, previous_authority, Clock::get()?.slot)?;
+
+        msg!("Authority transferred: {} -> {}", previous_authority, new_authority);
+        emit!(AuthorityTransferred {
+            previous_authority,
+            new_authority,
+        });
+        Ok(())
+    }
+
+    /// Upgrades the tree account to the current layout, see `Migration`.
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+        let tree_info = ctx.accounts.tree_account.to_account_info();
+        let from_version = merkle_tree_account_version(&tree_info.try_borrow_data()?)?;
+        require!(from_version < MERKLE_TREE_ACCOUNT_VERSION, PoolError::AccountAlreadyMigrated);
+
+        let mut version = from_version;
+        while let Some(migration) = next_migration(version) {
+            tree_info.realloc(migration.new_len, true)?;
+            migration.apply(&mut tree_info.try_borrow_mut_data()?)?;
+            version = migration.to_version();
+        }
+        ctx.accounts.fund_rent()?;
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::MigrateAccount,
+            tree_info.key(),
+            Clock::get()?.slot,
+        )?;
+
+        msg!("Tree account migrated: v{} -> v{}", from_version, version);
+        emit!(AccountMigrated {
+            account: tree_info.key(),
+            from_version,
+            to_version: version,
+        });
+        Ok(())
+    }
+
+    /// Moves the tree's subtrees and root history to its `TreeStorage`,
+    /// leaving the tree account as metadata that links to it. The tree must
+    /// be at layout v3.
+    pub fn split_tree_storage(ctx: Context<SplitTreeStorage>) -> Result<()> {
+        let tree_key = ctx.accounts.tree_account.key();
+        let storage_key = ctx.accounts.tree_storage.key();
+        {
+            let tree = ctx.accounts.tree_account.load()?;
+            ctx.accounts.tree_storage.load_init()?.copy_from(tree_key, &tree);
+        }
+        {
+            let tree_info = ctx.accounts.tree_account.to_account_info();
+            set_tree_storage_key(&mut tree_info.try_borrow_mut_data()?, &storage_key)?;
+        }
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::SplitTreeStorage,
+            storage_key,
+            Clock::get()?.slot,
+        )?;
+        emit!(TreeStorageSplit {
+            tree_account: tree_key,
+            tree_storage: storage_key,
+        });
+        Ok(())
+    }
+
+    pub fn initialize_pool_registry(ctx: Context<InitializePoolRegistry>) -> Result<()> {
+        ctx.accounts.pool_registry.bump = ctx.bumps.pool_registry;
+        ctx.accounts.register_sol_pool()?;
+        msg!("Pool registry initialized");
+        Ok(())
+    }
+
+    pub fn set_pool_status(ctx: Context<SetPoolStatus>, tree_account: Pubkey, status: PoolStatus) -> Result<()> {
+        ctx.accounts.pool_registry.set_status(&tree_account, status)?;
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::for_pool_status(status),
+            tree_account,
+            Clock::get()?.slot,
+        )?;
+        msg!("Pool {} status set to {:?}", tree_account, status);
+        Ok(())
+    }
+
+    pub fn configure_pool_factory(
+        ctx: Context<ConfigurePoolFactory>,
+        treasury: Pubkey,
+        creation_fee: u64,
+    ) -> Result<()> {
+        validate_creation_fee(creation_fee)?;
+
+        let pool_factory = &mut ctx.accounts.pool_factory;
+        pool_factory.treasury = treasury;
+        pool_factory.creation_fee = creation_fee;
+        pool_factory.bump = ctx.bumps.pool_factory;
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::ConfigurePoolFactory,
+            treasury,
+            Clock::get()?.slot,
+        )?;
+        msg!("Pool creation fee set to {} lamports, paid to {}", creation_fee, treasury);
+        Ok(())
+    }
+
+    /// Opens a shielded pool for `mint`, see `TokenPool`. The tree is created
+    /// at the current account layout, so it never needs `migrate_account`.
+    pub fn initialize_token_pool(ctx: Context<InitializeTokenPool>) -> Result<()> {
+        ctx.accounts.pay_creation_fee()?;
+        ctx.accounts.create_vault(ctx.bumps.vault)?;
+        // `initialize_account3` has checked the mint by now
+        let decimals = mint_decimals(&ctx.accounts.mint.try_borrow_data()?)?;
+
+        let mint = ctx.accounts.mint.key();
+        let tree_key = ctx.accounts.tree_account.key();
+        {
+            let tree_account = &mut ctx.accounts.tree_account.load_init()?;
+            // Only used for pausing; see `PoolFactory`
+            tree_account.authority = ctx.accounts.global_config.authority;
+            tree_account.next_index = 0;
+            tree_account.root_index = 0;
+            tree_account.bump = ctx.bumps.tree_account;
+            // Amounts are in the mint's base units, so the default cap depends on its decimals
+            tree_account.max_deposit_amount = default_max_deposit_amount(decimals);
+            tree_account.height = MERKLE_TREE_HEIGHT;
+            tree_account.root_history_size = 100;
+            MerkleTree::initialize::<Poseidon>(tree_account)?;
+        }
+        {
+            let tree_info = ctx.accounts.tree_account.to_account_info();
+            let mut tree_data = tree_info.try_borrow_mut_data()?;
+            stamp_current_version(&mut tree_data)?;
+            record_root_slot(&mut tree_data, 0, Clock::get()?.slot);
+        }
+
+        let token_pool = &mut ctx.accounts.token_pool;
+        token_pool.mint = mint;
+        token_pool.tree_account = tree_key;
+        token_pool.vault = ctx.accounts.vault.key();
+        token_pool.creator = ctx.accounts.payer.key();
+        token_pool.decimals = decimals;
+        token_pool.bump = ctx.bumps.token_pool;
+        token_pool.vault_bump = ctx.bumps.vault;
+
+        let token_pool_key = token_pool.key();
+        // A full registry must not block pool creation, or anyone could fill
+        // it and stop every later pool
+        let registered = !ctx.accounts.pool_registry.is_full();
+        if registered {
+            ctx.accounts.pool_registry.register(mint, tree_key, token_pool_key)?;
+        } else {
+            msg!("Pool registry is full, token pool for {} is not listed", mint);
+        }
+
+        msg!("Token pool initialized for mint {}", mint);
+        emit!(TokenPoolCreated {
+            mint,
+            tree_account: tree_key,
+            vault: ctx.accounts.vault.key(),
+            creator: ctx.accounts.payer.key(),
+            creation_fee: ctx.accounts.pool_factory.creation_fee,
+            registered,
+        });
+        Ok(())
+    }
+
+    /// Deposit limits are timelocked like `max_deposit_amount`: queued here,
+    /// applied by `execute_deposit_limits` after `CONFIG_UPDATE_DELAY_SLOTS`.
+    pub fn queue_deposit_limits(
+        ctx: Context<QueueDepositLimits>,
+        max_per_transaction: u64,
+        max_per_slot: u64,
+    ) -> Result<()> {
+        validate_deposit_limits(max_per_transaction, max_per_slot)?;
+
+        let pending = &mut ctx.accounts.pending_deposit_limits;
+        pending.tree_account = ctx.accounts.tree_account.key();
+        pending.max_per_transaction = max_per_transaction;
+        pending.max_per_slot = max_per_slot;
+        pending.effective_slot = timelock::effective_slot(Clock::get()?.slot)?;
+        pending.bump = ctx.bumps.pending_deposit_limits;
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::QueueDepositLimits,
+            pending.tree_account,
+            Clock::get()?.slot,
+        )?;
+
+        msg!("Deposit limits queued, executable from slot {}", pending.effective_slot);
+        emit!(DepositLimitsQueued {
+            tree_account: pending.tree_account,
+            max_per_transaction,
+            max_per_slot,
+            effective_slot: pending.effective_slot,
+        });
+        Ok(())
+    }
+
+    pub fn execute_deposit_limits(ctx: Context<ExecuteDepositLimits>) -> Result<()> {
+        let pending = &ctx.accounts.pending_deposit_limits;
+        require!(pending.is_ready(Clock::get()?.slot), PoolError::ConfigUpdateNotReady);
+
+        let deposit_limits = &mut ctx.accounts.deposit_limits;
+        deposit_limits.tree_account = pending.tree_account;
+        deposit_limits.max_per_transaction = pending.max_per_transaction;
+        deposit_limits.max_per_slot = pending.max_per_slot;
+        deposit_limits.bump = ctx.bumps.deposit_limits;
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::SetDepositLimits,
+            deposit_limits.tree_account,
+            Clock::get()?.slot,
+        )?;
+
+        msg!(
+            "Deposit limits set: {} per transaction, {} per slot",
+            deposit_limits.max_per_transaction,
+            deposit_limits.max_per_slot
+        );
+        emit!(DepositLimitsUpdated {
+            tree_account: deposit_limits.tree_account,
+            max_per_transaction: deposit_limits.max_per_transaction,
+            max_per_slot: deposit_limits.max_per_slot,
+        });
+        Ok(())
+    }
+
+    pub fn cancel_deposit_limits(ctx: Context<CancelDepositLimits>) -> Result<()> {
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::CancelDepositLimits,
+            ctx.accounts.pending_deposit_limits.tree_account,
+            Clock::get()?.slot,
+        )?;
+        emit!(DepositLimitsCancelled {
+            tree_account: ctx.accounts.pending_deposit_limits.tree_account,
+            effective_slot: ctx.accounts.pending_deposit_limits.effective_slot,
+        });
+        Ok(())
+    }
+
+    pub fn set_max_encrypted_output_len(ctx: Context<SetMaxEncryptedOutputLen>, max_len: u16) -> Result<()> {
+        validate_max_encrypted_output_len(max_len)?;
+        let config = &mut ctx.accounts.encrypted_output_config;
+        config.max_len = max_len;
+        config.bump = ctx.bumps.encrypted_output_config;
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::SetMaxEncryptedOutputLen,
+            config.key(),
+            Clock::get()?.slot,
+        )?;
+        msg!("Max encrypted output length set to {} bytes", max_len);
+        Ok(())
+    }
+
+    pub fn set_output_storage_mode(ctx: Context<SetOutputStorageMode>, mode: OutputStorageMode) -> Result<()> {
+        let config = &mut ctx.accounts.output_storage_config;
+        config.mode = mode;
+        config.bump = ctx.bumps.output_storage_config;
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::SetOutputStorageMode,
+            config.key(),
+            Clock::get()?.slot,
+        )?;
+        msg!("Output storage mode set to {:?}", mode);
+        emit!(OutputStorageModeUpdated { mode });
+        Ok(())
+    }
+
+    /// Turns on or off writing every appended leaf to a `LeafPage`, for
+    /// clients that sync from accounts alone.
+    pub fn set_leaf_storage(ctx: Context<SetLeafStorage>, enabled: bool) -> Result<()> {
+        let config = &mut ctx.accounts.leaf_storage_config;
+        config.enabled = enabled;
+        config.bump = ctx.bumps.leaf_storage_config;
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::SetLeafStorage,
+            config.key(),
+            Clock::get()?.slot,
+        )?;
+        emit!(LeafStorageUpdated { enabled });
+        Ok(())
+    }
+
+    pub fn initialize_leaf_page(ctx: Context<InitializeLeafPage>, page_index: u64) -> Result<()> {
+        let next_index = ctx.accounts.tree_account.load()?.next_index;
+        validate_new_leaf_page(page_index, next_index)?;
+        let tree_key = ctx.accounts.tree_account.key();
+        ctx.accounts.leaf_page.load_init()?.init(tree_key, page_index);
+        emit!(LeafPageInitialized {
+            tree_account: tree_key,
+            leaf_page: ctx.accounts.leaf_page.key(),
+            page_index,
+        });
+        Ok(())
+    }
+
+    pub fn set_fee_recipient(ctx: Context<SetFeeRecipient>, fee_recipient: Pubkey) -> Result<()> {
+        validate_configured_fee_recipient(&fee_recipient)?;
+        let config = &mut ctx.accounts.fee_recipient_config;
+        let old_fee_recipient = config.fee_recipient;
+        config.fee_recipient = fee_recipient;
+        config.bump = ctx.bumps.fee_recipient_config;
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::SetFeeRecipient,
+            fee_recipient,
+            Clock::get()?.slot,
+        )?;
+
+        msg!("Protocol fee recipient set to {}", fee_recipient);
+        emit!(FeeRecipientUpdated {
+            old_fee_recipient,
+            new_fee_recipient: fee_recipient,
+        });
+        Ok(())
+    }
+
+    /// Queues a fee schedule for the pool of `mint`, `SOL_ADDRESS` for the SOL
+    /// pool, applied by `execute_fee_schedule` after `CONFIG_UPDATE_DELAY_SLOTS`.
+    /// Flat amounts are in that mint's base units.
+    pub fn queue_fee_schedule(
+        ctx: Context<QueueFeeSchedule>,
+        mint: Pubkey,
+        base_fee: u64,
+        tiers: Vec<FeeTier>,
+        fee_cap: u64,
+    ) -> Result<()> {
+        let decimals = pool_decimals(&mint, ctx.accounts.token_pool.as_deref())?;
+        validate_base_fee(base_fee, decimals)?;
+        validate_fee_tiers(&tiers)?;
+        validate_fee_cap(fee_cap, base_fee)?;
+
+        let slot = Clock::get()?.slot;
+        let pending = &mut ctx.accounts.pending_fee_schedule;
+        pending.mint = mint;
+        pending.base_fee = base_fee;
+        pending.tiers = tiers.clone();
+        pending.fee_cap = fee_cap;
+        pending.queued_by = ctx.accounts.authority.key();
+        pending.effective_slot = timelock::effective_slot(slot)?;
+        pending.bump = ctx.bumps.pending_fee_schedule;
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::QueueFeeSchedule,
+            pending.key(),
+            slot,
+        )?;
+
+        msg!("Fee schedule for {} queued, executable from slot {}", mint, pending.effective_slot);
+        emit!(FeeScheduleQueued {
+            mint,
+            base_fee,
+            tiers,
+            fee_cap,
+            effective_slot: pending.effective_slot,
+        });
+        Ok(())
+    }
+
+    pub fn execute_fee_schedule(ctx: Context<ExecuteFeeSchedule>, _mint: Pubkey) -> Result<()> {
+        let pending = &ctx.accounts.pending_fee_schedule;
+        let slot = Clock::get()?.slot;
+        require!(pending.is_ready(slot), PoolError::ConfigUpdateNotReady);
+
+        let fee_schedule = &mut ctx.accounts.fee_schedule;
+        fee_schedule.mint = pending.mint;
+        fee_schedule.base_fee = pending.base_fee;
+        fee_schedule.tiers = pending.tiers.clone();
+        fee_schedule.fee_cap = pending.fee_cap;
+        fee_schedule.bump = ctx.bumps.fee_schedule;
+        ctx.accounts.audit_log.record(
+            pending.queued_by,
+            AdminAction::SetFeeSchedule,
+            fee_schedule.key(),
+            slot,
+        )?;
+
+        msg!(
+            "Withdrawal base fee for {} set to {} with {} fee tiers",
+            fee_schedule.mint,
+            fee_schedule.base_fee,
+            fee_schedule.tiers.len()
+        );
+        emit!(FeeScheduleUpdated {
+            mint: fee_schedule.mint,
+            base_fee: fee_schedule.base_fee,
+            tiers: fee_schedule.tiers.clone(),
+            fee_cap: fee_schedule.fee_cap,
+        });
+        Ok(())
+    }
+
+    pub fn cancel_fee_schedule(ctx: Context<CancelFeeSchedule>, mint: Pubkey) -> Result<()> {
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::CancelFeeSchedule,
+            ctx.accounts.pending_fee_schedule.key(),
+            Clock::get()?.slot,
+        )?;
+        emit!(FeeScheduleCancelled {
+            mint,
+            effective_slot: ctx.accounts.pending_fee_schedule.effective_slot,
+        });
+        Ok(())
+    }
+
+    /// Queues a replacement for the parties whose withdrawals owe no fee,
+    /// applied by `execute_fee_exemptions` after `CONFIG_UPDATE_DELAY_SLOTS`.
+    pub fn queue_fee_exemptions(ctx: Context<QueueFeeExemptions>, exempt: Vec<Pubkey>) -> Result<()> {
+        validate_fee_exemptions(&exempt)?;
+
+        let slot = Clock::get()?.slot;
+        let pending = &mut ctx.accounts.pending_fee_exemptions;
+        pending.exempt = exempt.clone();
+        pending.queued_by = ctx.accounts.authority.key();
+        pending.effective_slot = timelock::effective_slot(slot)?;
+        pending.bump = ctx.bumps.pending_fee_exemptions;
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::QueueFeeExemptions,
+            pending.key(),
+            slot,
+        )?;
+
+        msg!("Fee exemptions queued, executable from slot {}", pending.effective_slot);
+        emit!(FeeExemptionsQueued {
+            exempt,
+            effective_slot: pending.effective_slot,
+        });
+        Ok(())
+    }
+
+    pub fn execute_fee_exemptions(ctx: Context<ExecuteFeeExemptions>) -> Result<()> {
+        let pending = &ctx.accounts.pending_fee_exemptions;
+        let slot = Clock::get()?.slot;
+        require!(pending.is_ready(slot), PoolError::ConfigUpdateNotReady);
+
+        let fee_exemptions = &mut ctx.accounts.fee_exemptions;
+        fee_exemptions.exempt = pending.exempt.clone();
+        fee_exemptions.bump = ctx.bumps.fee_exemptions;
+        ctx.accounts.audit_log.record(
+            pending.queued_by,
+            AdminAction::SetFeeExemptions,
+            fee_exemptions.key(),
+            slot,
+        )?;
+        emit!(FeeExemptionsUpdated {
+            exempt: fee_exemptions.exempt.clone(),
+        });
+        Ok(())
+    }
+
+    pub fn cancel_fee_exemptions(ctx: Context<CancelFeeExemptions>) -> Result<()> {
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::CancelFeeExemptions,
+            ctx.accounts.pending_fee_exemptions.key(),
+            Clock::get()?.slot,
+        )?;
+        emit!(FeeExemptionsCancelled {
+            effective_slot: ctx.accounts.pending_fee_exemptions.effective_slot,
+        });
+        Ok(())
+    }
+
+    /// Starts allocating the tree's separate root history. It takes effect
+    /// once `grow_root_history` has brought it to full size.
+    pub fn initialize_root_history(ctx: Context<InitializeRootHistory>) -> Result<()> {
+        ctx.accounts.create(ctx.bumps.root_history)?;
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::InitializeRootHistory,
+            ctx.accounts.root_history.key(),
+            Clock::get()?.slot,
+        )?;
+        Ok(())
+    }
+
+    pub fn grow_root_history(ctx: Context<GrowRootHistory>) -> Result<()> {
+        if ctx.accounts.grow()? {
+            msg!("Root history of {} complete", ctx.accounts.tree_account.key());
+            emit!(RootHistoryInitialized {
+                tree_account: ctx.accounts.tree_account.key(),
+                root_history: ctx.accounts.root_history.key(),
+                capacity: ROOT_HISTORY_CAPACITY as u64,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn initialize_root_checkpoints(ctx: Context<InitializeRootCheckpoints>, interval_slots: u64) -> Result<()> {
+        validate_checkpoint_interval(interval_slots)?;
+        {
+            let checkpoints = &mut ctx.accounts.root_checkpoints.load_init()?;
+            checkpoints.tree_account = ctx.accounts.tree_account.key();
+            checkpoints.interval_slots = interval_slots;
+            checkpoints.len = 0;
+            checkpoints.bump = ctx.bumps.root_checkpoints;
+        }
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::InitializeRootCheckpoints,
+            ctx.accounts.root_checkpoints.key(),
+            Clock::get()?.slot,
+        )?;
+        Ok(())
+    }
+
+    /// Permissionless crank: records the tree's current root and slot in its
+    /// checkpoint timeline, at most once per interval.
+    pub fn checkpoint_root(ctx: Context<CheckpointRoot>) -> Result<()> {
+        let checkpoint = ctx.accounts.append(Clock::get()?.slot)?;
+        emit!(RootCheckpointed {
+            tree_account: ctx.accounts.tree_account.key(),
+            slot: checkpoint.slot,
+            next_index: checkpoint.next_index,
+            root: checkpoint.root,
+        });
+        Ok(())
+    }
+
+    /// Exports the tree's current root, leaf count and slot as a finalized
+    /// Wormhole message, for light clients on other chains. `next_index`
+    /// must be the tree's current one, which keys the message account.
+    pub fn post_root_attestation(ctx: Context<PostRootAttestation>, next_index: u64) -> Result<()> {
+        let attestation = {
+            let tree = ctx.accounts.tree_account.load()?;
+            require!(tree.next_index == next_index, PoolError::StaleRootAttestation);
+            RootAttestation {
+                tree_account: ctx.accounts.tree_account.key(),
+                next_index,
+                root: tree.root,
+                slot: Clock::get()?.slot,
+            }
+        };
+        ctx.accounts.post(&attestation, ctx.bumps.message, ctx.bumps.emitter)
+    }
+
+    /// Bounds the age of roots withdrawals may use; 0 removes the bound.
+    pub fn set_root_policy(ctx: Context<SetRootPolicy>, max_root_age_slots: u64) -> Result<()> {
+        validate_max_root_age(max_root_age_slots)?;
+        let root_policy = &mut ctx.accounts.root_policy;
+        root_policy.max_root_age_slots = max_root_age_slots;
+        root_policy.bump = ctx.bumps.root_policy;
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::SetRootPolicy,
+            root_policy.key(),
+            Clock::get()?.slot,
+        )?;
+        emit!(RootPolicyUpdated { max_root_age_slots });
+        Ok(())
+    }
+
+    /// Registers the signer as a relayer charging `withdrawal_fee_rate`, or
+    /// updates the rate of an existing registration.
+    pub fn set_relayer_fee_rate(ctx: Context<SetRelayerFeeRate>, withdrawal_fee_rate: u16) -> Result<()> {
+        validate_relayer_fee_rate(withdrawal_fee_rate, ctx.accounts.global_config.withdrawal_fee_rate)?;
+        let relayer_account = &mut ctx.accounts.relayer_account;
+        if relayer_account.relayer == Pubkey::default() {
+            relayer_account.relayer = ctx.accounts.relayer.key();
+            relayer_account.registered_slot = Clock::get()?.slot;
+            relayer_account.bump = ctx.bumps.relayer_account;
+        }
+        relayer_account.withdrawal_fee_rate = withdrawal_fee_rate;
+
+        emit!(RelayerFeeRateUpdated {
+            relayer: relayer_account.relayer,
+            withdrawal_fee_rate,
+        });
+        Ok(())
+    }
+
+    /// Adds `amount` lamports to the signer's relayer bond.
+    pub fn bond_relayer(ctx: Context<UpdateRelayer>, amount: u64) -> Result<()> {
+        require!(amount > 0, PoolError::InvalidBondAmount);
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.relayer.to_account_info(),
+                    to: ctx.accounts.relayer_account.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        let relayer_account = &mut ctx.accounts.relayer_account;
+        relayer_account.bond = relayer_account.bond.checked_add(amount).ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        emit!(RelayerBonded {
+            relayer: relayer_account.relayer,
+            amount,
+            bond: relayer_account.bond,
+        });
+        Ok(())
+    }
+
+    /// Starts the unbonding period. The relayer's own rate stops applying
+    /// immediately, but its bond stays slashable until it deregisters.
+    pub fn request_relayer_deregistration(ctx: Context<UpdateRelayer>) -> Result<()> {
+        let relayer_account = &mut ctx.accounts.relayer_account;
+        if relayer_account.unbonding_slot == 0 {
+            relayer_account.unbonding_slot = Clock::get()?.slot;
+        }
+        Ok(())
+    }
+
+    pub fn slash_relayer(
+        ctx: Context<SlashRelayer>,
+        amount: u64,
+        reason: SlashReason,
+        evidence: [u8; 32],
+    ) -> Result<()> {
+        let relayer_account = &mut ctx.accounts.relayer_account;
+        relayer_account.validate_slash(amount)?;
+        relayer_account.bond -= amount;
+        relayer_account.sub_lamports(amount)?;
+        ctx.accounts.treasury.add_lamports(amount)?;
+        ctx.accounts.audit_log.record(
+            ctx.accounts.guardian.key(),
+            AdminAction::SlashRelayer,
+            relayer_account.relayer,
+            Clock::get()?.slot,
+        )?;
+
+        msg!("Slashed {} lamports from relayer {}", amount, relayer_account.relayer);
+        emit!(RelayerSlashed {
+            relayer: relayer_account.relayer,
+            amount,
+            reason,
+            evidence,
+            treasury: ctx.accounts.treasury.key(),
+        });
+        Ok(())
+    }
+
+    /// Closes the registration once unbonding is over, returning the
+    /// remaining bond and rent.
+    pub fn deregister_relayer(ctx: Context<DeregisterRelayer>) -> Result<()> {
+        ctx.accounts.relayer_account.validate_deregistration(Clock::get()?.slot)?;
+        emit!(RelayerDeregistered {
+            relayer: ctx.accounts.relayer.key(),
+        });
+        Ok(())
+    }
+
+    /// Sweeps tokens sent to a pool PDA by mistake to `recovery`. Never the
+    /// pool's own asset.
+    pub fn rescue_tokens(ctx: Context<RescueTokens>) -> Result<()> {
+        let accounts = &ctx.accounts;
+        validate_rescue(
+            accounts.token_pool.as_deref(),
+            &accounts.source.key(),
+            &accounts.source.mint,
+        )?;
+        let amount = accounts.sweep()?;
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::RescueTokens,
+            ctx.accounts.source.key(),
+            Clock::get()?.slot,
+        )?;
+
+        emit!(TokensRescued {
+            source: ctx.accounts.source.key(),
+            mint: ctx.accounts.source.mint,
+            amount,
+            recovery: ctx.accounts.recovery.key(),
+        });
+        Ok(())
+    }
+
+    /// Points idle SOL staking at `stake_pool` and bounds the share staked.
+    pub fn configure_yield(ctx: Context<ConfigureYield>, max_staked_bps: u16) -> Result<()> {
+        validate_yield_config(max_staked_bps)?;
+        let config = &mut ctx.accounts.yield_config;
+        require!(
+            config.staked_lamports == 0 || config.stake_pool == ctx.accounts.stake_pool.key(),
+            PoolError::InvalidYieldConfig
+        );
+        config.stake_pool = ctx.accounts.stake_pool.key();
+        config.max_staked_bps = max_staked_bps;
+        config.bump = ctx.bumps.yield_config;
+        config.vault_bump = Pubkey::find_program_address(&[b"yield_vault"], ctx.program_id).1;
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::ConfigureYield,
+            config.stake_pool,
+            Clock::get()?.slot,
+        )?;
+
+        msg!("Yield: stake pool {}, max staked {} bps", config.stake_pool, max_staked_bps);
+        Ok(())
+    }
+
+    /// Deposits `lamports` of the SOL pool into the configured stake pool.
+    pub fn stake_idle_sol(ctx: Context<StakeIdleSol>, lamports: u64) -> Result<()> {
+        let liquid = liquid_lamports(&ctx.accounts.tree_token_account.to_account_info())?;
+        ctx.accounts.yield_config.record_stake(lamports, liquid)?;
+        ctx.accounts.deposit_sol(lamports)?;
+
+        let config = &ctx.accounts.yield_config;
+        emit!(SolStaked {
+            stake_pool: config.stake_pool,
+            lamports,
+            staked_lamports: config.staked_lamports,
+        });
+        Ok(())
+    }
+
+    /// Redeems `pool_tokens` for SOL paid back into the pool. Open to anyone
+    /// while the staked share is over the limit.
+    pub fn unstake_sol(ctx: Context<UnstakeSol>, pool_tokens: u64) -> Result<()> {
+        let liquid = liquid_lamports(&ctx.accounts.tree_token_account.to_account_info())?;
+        require!(
+            ctx.accounts.caller.key() == ctx.accounts.global_config.authority
+                || ctx.accounts.yield_config.needs_liquidity(liquid),
+            PoolError::UnstakeNotNeeded
+        );
+        let lamports = ctx.accounts.withdraw_sol(pool_tokens)?;
+        let config = &mut ctx.accounts.yield_config;
+        config.record_unstake(lamports)?;
+
+        emit!(SolUnstaked {
+            stake_pool: config.stake_pool,
+            pool_tokens,
+            lamports,
+            staked_lamports: config.staked_lamports,
+            realized_yield: config.realized_yield,
+        });
+        Ok(())
+    }
+
+    /// Sets up the token pool for an LST preset, with deposit limits given in
+    /// lamports of SOL value. See `LstConfig`.
+    pub fn configure_lst_pool(
+        ctx: Context<ConfigureLstPool>,
+        preset: LstPreset,
+        max_per_transaction_lamports: u64,
+        max_per_slot_lamports: u64,
+    ) -> Result<()> {
+        validate_deposit_limits(max_per_transaction_lamports, max_per_slot_lamports)?;
+        validate_lst_mint(preset, &ctx.accounts.mint.try_borrow_data()?)?;
+        let rate = preset.exchange_rate(&ctx.accounts.rate_account.try_borrow_data()?)?;
+
+        let lst_config = &mut ctx.accounts.lst_config;
+        lst_config.tree_account = ctx.accounts.tree_account.key();
+        lst_config.preset = preset;
+        lst_config.max_per_transaction_lamports = max_per_transaction_lamports;
+        lst_config.max_per_slot_lamports = max_per_slot_lamports;
+        lst_config.set_rate(rate, Clock::get()?.slot);
+        lst_config.bump = ctx.bumps.lst_config;
+
+        let deposit_limits = &mut ctx.accounts.deposit_limits;
+        lst_config.apply_limits(deposit_limits);
+        deposit_limits.bump = ctx.bumps.deposit_limits;
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::ConfigureLstPool,
+            lst_config.tree_account,
+            Clock::get()?.slot,
+        )?;
+
+        msg!("LST pool {:?}: {} lamports per transaction, {} per slot", preset, max_per_transaction_lamports, max_per_slot_lamports);
+        Ok(())
+    }
+
+    pub fn refresh_lst_rate(ctx: Context<RefreshLstRate>) -> Result<()> {
+        let lst_config = &mut ctx.accounts.lst_config;
+        let rate = lst_config.preset.exchange_rate(&ctx.accounts.rate_account.try_borrow_data()?)?;
+        lst_config.set_rate(rate, Clock::get()?.slot);
+        let deposit_limits = &mut ctx.accounts.deposit_limits;
+        lst_config.apply_limits(deposit_limits);
+
+        emit!(LstRateRefreshed {
+            tree_account: lst_config.tree_account,
+            rate_numerator: lst_config.rate_numerator,
+            rate_denominator: lst_config.rate_denominator,
+            max_per_transaction: deposit_limits.max_per_transaction,
+            max_per_slot: deposit_limits.max_per_slot,
+        });
+        Ok(())
+    }
+
+    /// Replaces the programs withdrawals may swap through.
+    pub fn set_swap_programs(ctx: Context<SetSwapPrograms>, programs: Vec<Pubkey>) -> Result<()> {
+        validate_swap_programs(&programs)?;
+        let swap_allowlist = &mut ctx.accounts.swap_allowlist;
+        swap_allowlist.programs = programs.clone();
+        swap_allowlist.bump = ctx.bumps.swap_allowlist;
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::SetSwapPrograms,
+            swap_allowlist.key(),
+            Clock::get()?.slot,
+        )?;
+        emit!(SwapAllowlistUpdated { programs });
+        Ok(())
+    }
+
+    /// Replaces the programs withdrawals may CPI into.
+    pub fn set_cpi_programs(ctx: Context<SetCpiPrograms>, programs: Vec<Pubkey>) -> Result<()> {
+        validate_cpi_programs(&programs)?;
+        let cpi_allowlist = &mut ctx.accounts.cpi_allowlist;
+        cpi_allowlist.programs = programs.clone();
+        cpi_allowlist.bump = ctx.bumps.cpi_allowlist;
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::SetCpiPrograms,
+            cpi_allowlist.key(),
+            Clock::get()?.slot,
+        )?;
+        emit!(CpiAllowlistUpdated { programs });
+        Ok(())
+    }
+
+    /// Pays out a queued withdrawal once its delay has passed.
+    pub fn execute_withdrawal(ctx: Context<ExecuteWithdrawal>) -> Result<()> {
+        let queued = &ctx.accounts.queued_withdrawal;
+        queued.validate_executable(Clock::get()?.slot)?;
+
+        let total = queued.amount.checked_add(queued.fee).ok_or(ErrorCode::ArithmeticOverflow)?;
+        ctx.accounts.tree_token_account.sub_lamports(total)?;
+        ctx.accounts.recipient.add_lamports(queued.amount)?;
+        ctx.accounts.fee_recipient.add_lamports(queued.fee)?;
+
+        emit!(WithdrawalExecuted {
+            queued_withdrawal: queued.key(),
+            recipient: queued.recipient,
+            amount: queued.amount,
+            fee: queued.fee,
+        });
+        Ok(())
+    }
+
+    /// Records a relayer's commitment to a withdrawal it will reveal in a
+    /// later slot.
+    pub fn commit_submission(ctx: Context<CommitSubmission>, commitment: [u8; 32]) -> Result<()> {
+        let submission = &mut ctx.accounts.submission_commitment;
+        submission.relayer = ctx.accounts.relayer.key();
+        submission.commitment = commitment;
+        submission.committed_slot = Clock::get()?.slot;
+        submission.bump = ctx.bumps.submission_commitment;
+
+        emit!(SubmissionCommitted {
+            relayer: submission.relayer,
+            commitment,
+        });
+        Ok(())
+    }
+
+    pub fn close_submission_commitment(_ctx: Context<CloseSubmissionCommitment>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Switches nullifier PDAs to `version`, checking the old derivation too
+    /// for the next `window_slots`.
+    pub fn begin_nullifier_seed_migration(
+        ctx: Context<BeginNullifierSeedMigration>,
+        version: NullifierSeedVersion,
+        window_slots: u64,
+    ) -> Result<()> {
+        let slot = Clock::get()?.slot;
+        let config = &mut ctx.accounts.nullifier_seed_config;
+        let from_version = config.version;
+        config.begin_migration(version, window_slots, slot)?;
+        config.bump = ctx.bumps.nullifier_seed_config;
+        ctx.accounts.audit_log.record(
+            ctx.accounts.authority.key(),
+            AdminAction::BeginNullifierSeedMigration,
+            config.key(),
+            slot,
+        )?;
+
+        emit!(NullifierSeedMigrationStarted {
+            from_version,
+            to_version: version,
+            migration_end_slot: config.migration_end_slot,
+        });
+        Ok(())
+    }
+
+    /// Carries a nullifier spent under the legacy derivation over to the
+    /// current one. `index` is its input slot in the spending proof.
+    pub fn migrate_nullifier(
+        ctx: Context<MigrateNullifier>,
+        tree_account: Pubkey,
+        index: u8,
+        nullifier: [u8; 32],
+    ) -> Result<()> {
+        let config = &ctx.accounts.nullifier_seed_config;
+        require!(config.migration_open(Clock::get()?.slot), PoolError::InvalidNullifierMigrationSource);
+        let legacy_version = config.legacy_version.ok_or(PoolError::InvalidNullifierMigrationSource)?;
+        require!(
+            ctx.accounts.legacy_nullifier.key() == legacy_version.address(index, &tree_account, &nullifier).0
+                && nullifier_spent(&ctx.accounts.legacy_nullifier),
+            PoolError::InvalidNullifierMigrationSource
+        );
+        let (address, bump) = config.version.address(index, &tree_account, &nullifier);
+        require_keys_eq!(ctx.accounts.nullifier.key(), address, PoolError::InvalidNullifierMigrationSource);
+        if nullifier_spent(&ctx.accounts.nullifier) {
+            return Ok(());
+        }
+        ctx.accounts.create_nullifier(config.version.seeds(index, &tree_account, &nullifier), bump)
+    }
+
+    pub fn initialize_deposit_queue(ctx: Context<InitializeDepositQueue>) -> Result<()> {
+        let deposit_queue = &mut ctx.accounts.deposit_queue;
+        deposit_queue.tree_account = ctx.accounts.tree_account.key();
+        deposit_queue.bump = ctx.bumps.deposit_queue;
+        Ok(())
+    }
+
+    /// Inserts up to `max_batch` queued deposit commitments into the tree.
+    pub fn process_queue(ctx: Context<ProcessQueue>, max_batch: u8) -> Result<()> {
+        let commitments = ctx.accounts.deposit_queue.take_batch(max_batch)?;
+        let tree_account = &mut ctx.accounts.tree_account.load_mut()?;
+        let first_leaf_index = tree_account.next_index;
+        for commitment in commitments.iter() {
+            MerkleTree::append::<Poseidon>(*commitment, tree_account)?;
+        }
+
+        msg!("Inserted {} queued deposits at leaf {}", commitments.len(), first_leaf_index);
+        emit!(DepositQueueProcessed {
+            tree_account: ctx.accounts.tree_account.key(),
+            first_leaf_index,
+            commitments,
+            root: tree_account.root,
+        });
+        Ok(())
+    }
+
+    #[cfg(feature = "concurrent-tree")]
+    pub fn initialize_concurrent_tree(ctx: Context<InitializeConcurrentTree>) -> Result<()> {
+        let tree_account = &mut ctx.accounts.tree_account.load_init()?;
+        tree_account.authority = ctx.accounts.authority.key();
+        tree_account.tree.initialize::<Poseidon>()?;
+        msg!("Concurrent Merkle tree initialized with root {:?}", tree_account.tree.root());
+        Ok(())
+    }
+
     // Other contract methods omitted for brevity...
 }
\ No newline at end of file
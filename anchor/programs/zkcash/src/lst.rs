@@ -0,0 +1,233 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token;
+use crate::errors::PoolError;
+use crate::treasury_yield::SPL_STAKE_POOL_PROGRAM_ID;
+use crate::{mint_decimals, AuditLog, DepositLimits, ErrorCode, MerkleTreeAccount, TokenPool};
+
+pub const MSOL_MINT: Pubkey = pubkey!("mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So");
+pub const JITOSOL_MINT: Pubkey = pubkey!("J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn");
+pub const MARINADE_PROGRAM_ID: Pubkey = pubkey!("MarBmsSgKXdrN1egZf5sqe1TMai9K1rChYNDJgjq7aD");
+pub const MARINADE_STATE: Pubkey = pubkey!("8szGkuLTAux9XMgZ2vtY39jVSowEcpBfFfD8hXSEqdGC");
+pub const JITO_STAKE_POOL: Pubkey = pubkey!("Jito4APyf642JPZPx3hGc6WWJ8zPKtRbRs4P815Awbb");
+
+/// Marinade's `State`: `msol_mint` right after the discriminator, and
+/// `msol_price` as lamports per mSOL scaled by 2^32.
+const MARINADE_MSOL_MINT_OFFSET: usize = 8;
+const MARINADE_MSOL_PRICE_OFFSET: usize = 512;
+const MARINADE_PRICE_DENOMINATOR: u64 = 1 << 32;
+
+/// spl-stake-pool's `StakePool`, Borsh encoded with no discriminator.
+const STAKE_POOL_MINT_OFFSET: usize = 162;
+const STAKE_POOL_TOTAL_LAMPORTS_OFFSET: usize = 258;
+const STAKE_POOL_TOKEN_SUPPLY_OFFSET: usize = 266;
+
+/// Slots a refreshed rate stays usable for, about an epoch's worth of minutes.
+pub const MAX_LST_RATE_AGE: u64 = 9_000;
+
+/// Liquid staking tokens with first-class pool support. Each names where its
+/// SOL exchange rate is read from, so deposit limits can be set in SOL.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace)]
+pub enum LstPreset {
+    #[default]
+    Msol,
+    JitoSol,
+}
+
+impl LstPreset {
+    pub fn mint(&self) -> Pubkey {
+        match self {
+            LstPreset::Msol => MSOL_MINT,
+            LstPreset::JitoSol => JITOSOL_MINT,
+        }
+    }
+
+    pub fn decimals(&self) -> u8 {
+        9
+    }
+
+    /// The account holding the exchange rate, and its owner.
+    pub fn rate_account(&self) -> (Pubkey, Pubkey) {
+        match self {
+            LstPreset::Msol => (MARINADE_STATE, MARINADE_PROGRAM_ID),
+            LstPreset::JitoSol => (JITO_STAKE_POOL, SPL_STAKE_POOL_PROGRAM_ID),
+        }
+    }
+
+    /// Lamports per token as `(numerator, denominator)`, read from the rate
+    /// account's data. Checks the account is the one backing this mint.
+    pub fn exchange_rate(&self, data: &[u8]) -> Result<(u64, u64)> {
+        let (mint_offset, numerator, denominator) = match self {
+            LstPreset::Msol => (
+                MARINADE_MSOL_MINT_OFFSET,
+                read_u64(data, MARINADE_MSOL_PRICE_OFFSET)?,
+                MARINADE_PRICE_DENOMINATOR,
+            ),
+            LstPreset::JitoSol => (
+                STAKE_POOL_MINT_OFFSET,
+                read_u64(data, STAKE_POOL_TOTAL_LAMPORTS_OFFSET)?,
+                read_u64(data, STAKE_POOL_TOKEN_SUPPLY_OFFSET)?,
+            ),
+        };
+        let mint = data
+            .get(mint_offset..mint_offset + 32)
+            .ok_or(PoolError::InvalidLstRate)?;
+        require!(mint == self.mint().as_ref(), PoolError::InvalidLstRate);
+        // An LST never trades below one lamport per base unit
+        require!(denominator > 0 && numerator >= denominator, PoolError::InvalidLstRate);
+        Ok((numerator, denominator))
+    }
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+    let bytes = data.get(offset..offset + 8).ok_or(PoolError::InvalidLstRate)?;
+    Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// Checks an SPL mint account has the preset's decimals, so base-unit amounts
+/// mean what the limits assume.
+pub fn validate_lst_mint(preset: LstPreset, mint_data: &[u8]) -> Result<()> {
+    let decimals = mint_decimals(mint_data).map_err(|_| PoolError::LstMintMismatch)?;
+    require!(decimals == preset.decimals(), PoolError::LstMintMismatch);
+    Ok(())
+}
+
+/// Deposit limits of an LST pool, in lamports of SOL value. `refresh_lst_rate`
+/// converts them at the current exchange rate into the pool's
+/// `DepositLimits`, which `transact` already enforces in token units. The
+/// limits then stay steady in SOL terms while the LST accrues yield.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct LstConfig {
+    pub tree_account: Pubkey,
+    pub preset: LstPreset,
+    pub max_per_transaction_lamports: u64,
+    pub max_per_slot_lamports: u64,
+    pub rate_numerator: u64,
+    pub rate_denominator: u64,
+    pub rate_updated_slot: u64,
+    pub bump: u8,
+}
+
+#[event]
+pub struct LstRateRefreshed {
+    pub tree_account: Pubkey,
+    pub rate_numerator: u64,
+    pub rate_denominator: u64,
+    pub max_per_transaction: u64,
+    pub max_per_slot: u64,
+}
+
+impl LstConfig {
+    /// The SOL value of `amount` base units, at the last refreshed rate.
+    pub fn lamports_value(&self, amount: u64) -> Result<u64> {
+        let value = amount as u128 * self.rate_numerator as u128 / self.rate_denominator as u128;
+        Ok(u64::try_from(value).map_err(|_| ErrorCode::ArithmeticOverflow)?)
+    }
+
+    /// Base units worth at most `lamports`, rounding down.
+    pub fn token_amount(&self, lamports: u64) -> u64 {
+        // numerator >= denominator, so this never exceeds `lamports`
+        (lamports as u128 * self.rate_denominator as u128 / self.rate_numerator as u128) as u64
+    }
+
+    pub fn set_rate(&mut self, rate: (u64, u64), slot: u64) {
+        (self.rate_numerator, self.rate_denominator) = rate;
+        self.rate_updated_slot = slot;
+    }
+
+    /// Errors unless the rate was refreshed within `MAX_LST_RATE_AGE` slots.
+    /// `transact` checks this for deposits into an LST pool.
+    pub fn validate_rate_fresh(&self, slot: u64) -> Result<()> {
+        require!(
+            self.rate_denominator > 0 && slot.saturating_sub(self.rate_updated_slot) <= MAX_LST_RATE_AGE,
+            PoolError::StaleLstRate
+        );
+        Ok(())
+    }
+
+    /// Writes the SOL limits, converted at the current rate, into
+    /// `deposit_limits`.
+    pub fn apply_limits(&self, deposit_limits: &mut DepositLimits) {
+        deposit_limits.tree_account = self.tree_account;
+        deposit_limits.max_per_transaction = self.token_amount(self.max_per_transaction_lamports);
+        deposit_limits.max_per_slot = self.token_amount(self.max_per_slot_lamports);
+    }
+}
+
+/// Marks the token pool for `preset`'s mint as an LST pool. Token pools have
+/// no admin of their own, so this is the global authority's call.
+#[derive(Accounts)]
+#[instruction(preset: LstPreset)]
+pub struct ConfigureLstPool<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + LstConfig::INIT_SPACE,
+        seeds = [b"lst_config", tree_account.key().as_ref()],
+        bump
+    )]
+    pub lst_config: Account<'info, LstConfig>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + DepositLimits::INIT_SPACE,
+        seeds = [b"deposit_limits", tree_account.key().as_ref()],
+        bump
+    )]
+    pub deposit_limits: Account<'info, DepositLimits>,
+    #[account(
+        seeds = [b"token_pool", mint.key().as_ref()],
+        bump = token_pool.bump,
+        has_one = mint,
+        has_one = tree_account
+    )]
+    pub token_pool: Account<'info, TokenPool>,
+    /// CHECK: pinned to the preset's mint; decimals checked by `validate_lst_mint`
+    #[account(
+        address = preset.mint() @ PoolError::LstMintMismatch,
+        owner = token::ID
+    )]
+    pub mint: UncheckedAccount<'info>,
+    #[account(
+        constraint = tree_account.load()?.authority == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub tree_account: AccountLoader<'info, MerkleTreeAccount>,
+    /// CHECK: address and owner checked against the preset
+    #[account(
+        address = preset.rate_account().0 @ PoolError::InvalidLstRate,
+        owner = preset.rate_account().1 @ PoolError::InvalidLstRate
+    )]
+    pub rate_account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
+
+/// Permissionless: anyone can pull the latest rate into the pool's limits.
+#[derive(Accounts)]
+pub struct RefreshLstRate<'info> {
+    #[account(
+        mut,
+        seeds = [b"lst_config", lst_config.tree_account.as_ref()],
+        bump = lst_config.bump
+    )]
+    pub lst_config: Account<'info, LstConfig>,
+    #[account(
+        mut,
+        seeds = [b"deposit_limits", lst_config.tree_account.as_ref()],
+        bump = deposit_limits.bump
+    )]
+    pub deposit_limits: Account<'info, DepositLimits>,
+    /// CHECK: address and owner checked against the preset
+    #[account(
+        address = lst_config.preset.rate_account().0 @ PoolError::InvalidLstRate,
+        owner = lst_config.preset.rate_account().1 @ PoolError::InvalidLstRate
+    )]
+    pub rate_account: UncheckedAccount<'info>,
+}
@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::invoke;
+use crate::errors::PoolError;
+
+/// SPL Memo v2. It takes the memo as its instruction data and needs no
+/// accounts unless it is asked to check signers, which we never do.
+pub const SPL_MEMO_PROGRAM_ID: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+/// Longest memo a withdrawal can carry. Exchange deposit memos and
+/// destination tags are far shorter; the cap keeps the hashed ExtData and the
+/// event small.
+pub const MAX_MEMO_LEN: usize = 128;
+
+/// Emitted for withdrawals that carry a memo, so an exchange watching
+/// `recipient` can credit the right account without talking to the sender.
+#[event]
+pub struct WithdrawalMemo {
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub memo: Vec<u8>,
+}
+
+pub fn validate_memo(memo: &[u8]) -> Result<()> {
+    require!(memo.len() <= MAX_MEMO_LEN, PoolError::MemoTooLong);
+    Ok(())
+}
+
+/// Must be called from `transact` after a withdrawal has paid `recipient`,
+/// with the memo that went into the ExtData hash. Deposits and transfers have
+/// no external recipient to address, so their memo is only hashed.
+pub fn emit_withdrawal_memo(recipient: Pubkey, amount: u64, memo: &[u8]) {
+    if memo.is_empty() {
+        return;
+    }
+    emit!(WithdrawalMemo {
+        recipient,
+        amount,
+        memo: memo.to_vec(),
+    });
+}
+
+/// A memo CPI is only for withdrawals, the only transactions with an
+/// external recipient to credit, and needs a non-empty UTF-8 memo: the Memo
+/// program rejects anything else, which would fail the withdrawal only
+/// after its proof had been verified.
+pub fn validate_memo_cpi(memo: &[u8], memo_cpi: bool, ext_amount: i64) -> Result<()> {
+    if memo_cpi {
+        require!(
+            ext_amount < 0 && !memo.is_empty() && std::str::from_utf8(memo).is_ok(),
+            PoolError::InvalidMemoCpi
+        );
+    }
+    Ok(())
+}
+
+/// The SPL Memo instruction carrying `memo`.
+pub fn spl_memo_instruction(memo: &[u8]) -> Instruction {
+    Instruction {
+        program_id: SPL_MEMO_PROGRAM_ID,
+        accounts: vec![],
+        data: memo.to_vec(),
+    }
+}
+
+/// Must be called from `transact` after a withdrawal whose ExtData set
+/// `memo_cpi` has paid its recipient, with the hashed memo. Many exchanges
+/// credit a deposit only when the transaction carries a Memo instruction;
+/// emitted from the pool, it is bound by the proof, so a relayer can't drop
+/// or change it.
+pub fn post_withdrawal_memo(memo_program: &AccountInfo, memo: &[u8]) -> Result<()> {
+    require_keys_eq!(memo_program.key(), SPL_MEMO_PROGRAM_ID, PoolError::InvalidMemoProgram);
+    invoke(&spl_memo_instruction(memo), std::slice::from_ref(memo_program))?;
+    Ok(())
+}
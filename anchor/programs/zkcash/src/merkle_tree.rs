@@ -1,56 +1,157 @@
 // Adapted from https://github.com/Lightprotocol/light-protocol/blob/b2a236409bb7797615d217fbf4fff498c852d25e/sparse-merkle-tree/src/merkle_tree.rs
-use light_hasher::Hasher;
-use crate::{MerkleTreeAccount, ErrorCode};
+use crate::{MerkleTreeAccount, ErrorCode, TreeStorage};
+use crate::errors::PoolError;
+use crate::utils::log_compute_units;
+use crate::zero_hashes::ZeroHashes;
 use anchor_lang::prelude::*;
 
 pub struct MerkleTree;
 
+/// Where `MerkleTreeAccount`'s fields sit in the raw account data, for
+/// off-chain readers that parse a fetched account instead of linking this
+/// crate. The account is zero-copy `repr(C)` with no padding before
+/// `height`, all integers little-endian:
+///
+/// | offset               | size                      | field                |
+/// |----------------------|---------------------------|----------------------|
+/// | 0                    | 8                         | discriminator        |
+/// | `AUTHORITY`          | 32                        | `authority`          |
+/// | `NEXT_INDEX`         | 8                         | `next_index`         |
+/// | `SUBTREES`           | 32 × `SUBTREE_SLOTS`      | `subtrees`           |
+/// | `ROOT`               | 32                        | `root`               |
+/// | `ROOT_HISTORY`       | 32 × `ROOT_HISTORY_SLOTS` | `root_history`       |
+/// | `ROOT_INDEX`         | 8                         | `root_index`         |
+/// | `MAX_DEPOSIT_AMOUNT` | 8                         | `max_deposit_amount` |
+/// | `HEIGHT`             | 1                         | `height`             |
+///
+/// Only the first `height` subtrees are in use. Entry `i` is the last left
+/// node written at level `i`, or the level's zero hash before any was; with
+/// `next_index` they are all `append` reads, so a client holding them can
+/// keep appending locally and track the root without replaying leaves.
+pub mod layout {
+    pub const SUBTREE_SLOTS: usize = 26;
+    pub const ROOT_HISTORY_SLOTS: usize = 100;
+
+    pub const AUTHORITY: usize = 8;
+    pub const NEXT_INDEX: usize = AUTHORITY + 32;
+    pub const SUBTREES: usize = NEXT_INDEX + 8;
+    pub const ROOT: usize = SUBTREES + 32 * SUBTREE_SLOTS;
+    pub const ROOT_HISTORY: usize = ROOT + 32;
+    pub const ROOT_INDEX: usize = ROOT_HISTORY + 32 * ROOT_HISTORY_SLOTS;
+    pub const MAX_DEPOSIT_AMOUNT: usize = ROOT_INDEX + 8;
+    pub const HEIGHT: usize = MAX_DEPOSIT_AMOUNT + 8;
+}
+
 impl MerkleTree {
-    pub fn initialize<H: Hasher>(tree_account: &mut MerkleTreeAccount) -> Result<()> {
+    pub fn initialize<H: ZeroHashes>(tree_account: &mut MerkleTreeAccount) -> Result<()> {
         let height = tree_account.height as usize;
+        require!(
+            height > 0 && height <= tree_account.subtrees.len(),
+            PoolError::InvalidTreeHeight
+        );
         
         // Initialize empty subtrees
-        let zero_bytes = H::zero_bytes();
         for i in 0..height {
-            tree_account.subtrees[i] = zero_bytes[i];
+            tree_account.subtrees[i] = H::zero_hash(i)?;
         }
 
         // Set initial root
-        let initial_root = H::zero_bytes()[height];
+        let initial_root = H::zero_hash(height)?;
         tree_account.root = initial_root;
         tree_account.root_history[0] = initial_root;
         
         Ok(())
     }
 
-    pub fn append<H: Hasher>(
+    /// The subtrees `append` hashes new leaves against, one per level; see
+    /// `layout`.
+    pub fn filled_subtrees(tree_account: &MerkleTreeAccount) -> &[[u8; 32]] {
+        &tree_account.subtrees[..tree_account.height as usize]
+    }
+
+    /// Roots of empty subtrees for levels `0..=height`, i.e. the initial
+    /// `filled_subtrees` and the siblings of a path with nothing beside it.
+    pub fn zero_hashes<H: ZeroHashes>(height: usize) -> Result<&'static [[u8; 32]]> {
+        H::ZERO_HASHES
+            .get(..=height)
+            .ok_or_else(|| error!(PoolError::InvalidTreeHeight))
+    }
+
+    pub fn append<H: ZeroHashes>(
         leaf: [u8; 32],
         tree_account: &mut MerkleTreeAccount,
     ) -> Result<Vec<[u8; 32]>> {
         let height = tree_account.height as usize;
-        let root_history_size = tree_account.root_history_size as usize;
         
         // Check if tree is full before appending
-        // Maximum capacity is 2^height leaves
-        let max_capacity = 1u64 << height; // 2^height
         require!(
-            tree_account.next_index < max_capacity,
+            tree_account.next_index < Self::leaf_capacity(height)?,
+            ErrorCode::MerkleTreeFull
+        );
+        log_compute_units("append:start");
+
+        let next_index = tree_account.next_index;
+        let (root, proof) = Self::hash_up::<H>(leaf, next_index, &mut tree_account.subtrees[..height])?;
+        
+        tree_account.root = root;
+        tree_account.next_index = tree_account.next_index
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        
+        let new_root_index = Self::next_root_index(tree_account.root_index, tree_account.root_history_size)?;
+        tree_account.root_index = new_root_index;
+        tree_account.root_history[new_root_index as usize] = root;
+        log_compute_units("append:end");
+        
+        Ok(proof)
+    }
+
+    /// `append` for a tree whose subtrees and root history have moved to
+    /// its `TreeStorage`. The tree account keeps `next_index` and `root`.
+    pub fn append_split<H: ZeroHashes>(
+        leaf: [u8; 32],
+        tree_account: &mut MerkleTreeAccount,
+        storage: &mut TreeStorage,
+    ) -> Result<Vec<[u8; 32]>> {
+        let height = tree_account.height as usize;
+        require!(
+            tree_account.next_index < Self::leaf_capacity(height)?,
             ErrorCode::MerkleTreeFull
         );
+        log_compute_units("append_split:start");
+
+        let next_index = tree_account.next_index;
+        let (root, proof) = Self::hash_up::<H>(leaf, next_index, &mut storage.subtrees[..height])?;
+        tree_account.root = root;
+        tree_account.next_index = next_index
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        storage.push_root(root, tree_account.root_history_size)?;
+        log_compute_units("append_split:end");
+
+        Ok(proof)
+    }
 
-        let mut current_index = tree_account.next_index as usize;
+    /// Hashes `leaf` at `index` up to the root, updating the filled
+    /// `subtrees` (one per level) on the way. Returns the new root and the
+    /// leaf's Merkle proof.
+    fn hash_up<H: ZeroHashes>(
+        leaf: [u8; 32],
+        index: u64,
+        subtrees: &mut [[u8; 32]],
+    ) -> Result<([u8; 32], Vec<[u8; 32]>)> {
+        let height = subtrees.len();
+        let zero_hashes = Self::zero_hashes::<H>(height)?;
+        let mut current_index = index as usize;
         let mut current_level_hash = leaf;
         let mut left;
         let mut right;
         let mut proof: Vec<[u8; 32]> = vec![[0u8; 32]; height];
 
-        for i in 0..height {
-            let subtree = &mut tree_account.subtrees[i];
-            let zero_byte = H::zero_bytes()[i];
-            
+        for (i, subtree) in subtrees.iter_mut().enumerate() {
             if current_index % 2 == 0 {
                 left = current_level_hash;
-                right = zero_byte;
+                right = zero_hashes[i];
                 *subtree = current_level_hash;
                 proof[i] = right;
             } else {
@@ -61,32 +162,56 @@ impl MerkleTree {
             current_level_hash = H::hashv(&[&left, &right]).unwrap();
             current_index /= 2;
         }
-        
-        tree_account.root = current_level_hash;
-        tree_account.next_index = tree_account.next_index
-            .checked_add(1)
-            .ok_or(ErrorCode::ArithmeticOverflow)?;
-        
-        let new_root_index = (tree_account.root_index as usize)
+
+        Ok((current_level_hash, proof))
+    }
+
+    /// Leaves a tree of `height` holds, `2^height`. Fails with
+    /// `InvalidTreeHeight` where that doesn't fit a u64.
+    pub fn leaf_capacity(height: usize) -> Result<u64> {
+        u32::try_from(height)
+            .ok()
+            .and_then(|height| 1u64.checked_shl(height))
+            .ok_or_else(|| error!(PoolError::InvalidTreeHeight))
+    }
+
+    /// The root history slot after `root_index` in a ring of
+    /// `root_history_size` entries, wrapping to 0 after the last.
+    pub fn next_root_index(root_index: u64, root_history_size: u64) -> Result<u64> {
+        root_index
             .checked_add(1)
-            .ok_or(ErrorCode::ArithmeticOverflow)? % root_history_size;
-        tree_account.root_index = new_root_index as u64;
-        tree_account.root_history[new_root_index] = current_level_hash;
-        
-        Ok(proof)
+            .ok_or(ErrorCode::ArithmeticOverflow)?
+            .checked_rem(root_history_size)
+            .ok_or_else(|| error!(ErrorCode::ArithmeticOverflow))
     }
 
     pub fn is_known_root(tree_account: &MerkleTreeAccount, root: [u8; 32]) -> bool {
+        Self::history_contains(
+            &tree_account.root_history,
+            tree_account.root_history_size as usize,
+            tree_account.root_index as usize,
+            root,
+        )
+    }
+
+    /// Walks the first `root_history_size` entries of `root_history` back
+    /// from `root_index`, newest first, wrapping once. The zero root is
+    /// never known.
+    pub fn history_contains(
+        root_history: &[[u8; 32]],
+        root_history_size: usize,
+        root_index: usize,
+        root: [u8; 32],
+    ) -> bool {
         if root == [0u8; 32] {
             return false;
         }
         
-        let root_history_size = tree_account.root_history_size as usize;
-        let current_root_index = tree_account.root_index as usize;
+        let current_root_index = root_index;
         let mut i = current_root_index;
         
         loop {
-            if root == tree_account.root_history[i] {
+            if root == root_history[i] {
                 return true;
             }
             
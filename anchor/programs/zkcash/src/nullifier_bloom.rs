@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+use crate::MerkleTreeAccount;
+
+/// Bytes of filter, 65536 bits: each bit index is a 16-bit slice of the
+/// nullifier.
+pub const NULLIFIER_BLOOM_BYTES: usize = 8192;
+
+/// Bits set per nullifier. With 10,000 spends the false positive rate is
+/// about 4%.
+pub const NULLIFIER_BLOOM_HASHES: usize = 4;
+
+/// Bloom filter of a tree's spent nullifiers, at
+/// `["nullifier_bloom", tree_account]`. Wallets fetch this one account to
+/// tell which of their notes are probably spent before scanning or
+/// submitting. A miss means definitely unspent; a hit still needs the
+/// nullifier PDA to confirm. Nullifiers are Poseidon outputs, so their low
+/// bytes are already uniform and serve as the hash functions.
+#[account(zero_copy)]
+pub struct NullifierBloomFilter {
+    pub tree_account: Pubkey,
+    /// Nullifiers inserted so far, for estimating the false positive rate.
+    pub inserted: u64,
+    pub bits: [u8; NULLIFIER_BLOOM_BYTES],
+}
+
+/// The filter's bit indices for `nullifier`: the last
+/// `NULLIFIER_BLOOM_HASHES` big-endian u16s of it.
+pub fn nullifier_bloom_indices(nullifier: &[u8; 32]) -> [usize; NULLIFIER_BLOOM_HASHES] {
+    core::array::from_fn(|i| {
+        let end = 32 - 2 * i;
+        u16::from_be_bytes([nullifier[end - 2], nullifier[end - 1]]) as usize
+    })
+}
+
+impl NullifierBloomFilter {
+    /// Marks `nullifier` spent. `transact` calls this for both input
+    /// nullifiers.
+    pub fn insert(&mut self, nullifier: &[u8; 32]) {
+        for index in nullifier_bloom_indices(nullifier) {
+            self.bits[index / 8] |= 1 << (index % 8);
+        }
+        self.inserted = self.inserted.saturating_add(1);
+    }
+
+    pub fn might_contain(&self, nullifier: &[u8; 32]) -> bool {
+        nullifier_bloom_indices(nullifier)
+            .iter()
+            .all(|index| self.bits[index / 8] & (1 << (index % 8)) != 0)
+    }
+}
+
+/// Anyone can create a tree's filter; it only covers spends after creation.
+#[derive(Accounts)]
+pub struct InitializeNullifierBloomFilter<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + std::mem::size_of::<NullifierBloomFilter>(),
+        seeds = [b"nullifier_bloom", tree_account.key().as_ref()],
+        bump
+    )]
+    pub nullifier_bloom: AccountLoader<'info, NullifierBloomFilter>,
+    pub tree_account: AccountLoader<'info, MerkleTreeAccount>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,217 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::errors::PoolError;
+use crate::{AuditLog, ErrorCode, GlobalConfig};
+
+/// Shortest migration window, about two weeks: long enough for a crank to
+/// carry every legacy nullifier over with `migrate_nullifier`.
+pub const MIN_NULLIFIER_MIGRATION_SLOTS: u64 = 3_024_000;
+
+/// How nullifier PDAs are derived. `index` is the nullifier's position in
+/// the proof, which v0 puts in the prefix.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NullifierSeedVersion {
+    /// `["nullifier0" | "nullifier1", nullifier]`, as first deployed.
+    #[default]
+    V0,
+    /// `["nullifier", tree_account, nullifier]`: scoped to the tree, and the
+    /// same address whichever input slot the nullifier is in.
+    V1,
+}
+
+impl NullifierSeedVersion {
+    pub fn seeds(&self, index: u8, tree_account: &Pubkey, nullifier: &[u8; 32]) -> Vec<Vec<u8>> {
+        match self {
+            NullifierSeedVersion::V0 => {
+                let prefix: &[u8] = if index == 0 { b"nullifier0" } else { b"nullifier1" };
+                vec![prefix.to_vec(), nullifier.to_vec()]
+            }
+            NullifierSeedVersion::V1 => vec![b"nullifier".to_vec(), tree_account.to_bytes().to_vec(), nullifier.to_vec()],
+        }
+    }
+
+    pub fn address(&self, index: u8, tree_account: &Pubkey, nullifier: &[u8; 32]) -> (Pubkey, u8) {
+        let seeds = self.seeds(index, tree_account, nullifier);
+        let seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+        Pubkey::find_program_address(&seeds, &crate::ID)
+    }
+}
+
+/// Which derivation spends use, at `["nullifier_seed_config"]`. While a
+/// migration is open, spends create the nullifier under `version` but must
+/// also find it absent under `legacy_version`, so notes spent before the
+/// switch stay spent. Once `migration_end_slot` passes only `version` is
+/// checked, so every legacy nullifier has to be carried over with
+/// `migrate_nullifier` before then.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct NullifierSeedConfig {
+    pub version: NullifierSeedVersion,
+    pub legacy_version: Option<NullifierSeedVersion>,
+    pub migration_end_slot: u64,
+    pub bump: u8,
+}
+
+/// A spend hit a nullifier that is already spent. Carries only the
+/// nullifier and the root the proof was made against: enough for
+/// monitoring to tell a wallet resubmitting a spent note from repeated
+/// probing, without revealing anything else about the spender.
+#[event]
+pub struct DoubleSpendAttempted {
+    pub nullifier: [u8; 32],
+    pub root: [u8; 32],
+}
+
+#[event]
+pub struct NullifierSeedMigrationStarted {
+    pub from_version: NullifierSeedVersion,
+    pub to_version: NullifierSeedVersion,
+    pub migration_end_slot: u64,
+}
+
+impl NullifierSeedConfig {
+    pub fn migration_open(&self, slot: u64) -> bool {
+        self.legacy_version.is_some() && slot < self.migration_end_slot
+    }
+
+    /// Derivations a spend at `slot` must find unused: the current one first,
+    /// then the legacy one while the migration is open.
+    pub fn versions_to_check(&self, slot: u64) -> Vec<NullifierSeedVersion> {
+        let mut versions = vec![self.version];
+        if self.migration_open(slot) {
+            versions.extend(self.legacy_version);
+        }
+        versions
+    }
+
+    /// Switches spends to `to_version` and opens a window of `window_slots`
+    /// for carrying legacy nullifiers over.
+    pub fn begin_migration(&mut self, to_version: NullifierSeedVersion, window_slots: u64, slot: u64) -> Result<()> {
+        require!(
+            to_version != self.version
+                && !self.migration_open(slot)
+                && window_slots >= MIN_NULLIFIER_MIGRATION_SLOTS,
+            PoolError::InvalidNullifierMigration
+        );
+        self.legacy_version = Some(self.version);
+        self.version = to_version;
+        self.migration_end_slot = slot.checked_add(window_slots).ok_or(ErrorCode::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
+
+/// Whether a nullifier account marks its nullifier spent.
+pub fn nullifier_spent(account: &AccountInfo) -> bool {
+    account.owner == &crate::ID && account.lamports() > 0
+}
+
+/// Fails with `NullifierAlreadySpent` if `account` marks `nullifier` spent,
+/// emitting `DoubleSpendAttempted` first. The spend reverts, but the event
+/// stays in the failed transaction's logs, which RPC nodes keep. `transact`
+/// calls this for each address `versions_to_check` gives before creating
+/// the nullifier, instead of leaving the check to `init`, which fails
+/// before any handler code runs.
+pub fn check_nullifier_unspent(account: &AccountInfo, nullifier: &[u8; 32], root: &[u8; 32]) -> Result<()> {
+    if nullifier_spent(account) {
+        emit!(DoubleSpendAttempted {
+            nullifier: *nullifier,
+            root: *root,
+        });
+        return err!(PoolError::NullifierAlreadySpent);
+    }
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct BeginNullifierSeedMigration<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + NullifierSeedConfig::INIT_SPACE,
+        seeds = [b"nullifier_seed_config"],
+        bump
+    )]
+    pub nullifier_seed_config: Account<'info, NullifierSeedConfig>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
+
+/// Permissionless: copies one legacy nullifier to its current address while
+/// the migration is open. Both addresses are checked by the handler.
+#[derive(Accounts)]
+pub struct MigrateNullifier<'info> {
+    #[account(
+        seeds = [b"nullifier_seed_config"],
+        bump = nullifier_seed_config.bump
+    )]
+    pub nullifier_seed_config: Account<'info, NullifierSeedConfig>,
+    /// CHECK: must derive from the legacy seeds and be spent
+    pub legacy_nullifier: UncheckedAccount<'info>,
+    /// CHECK: must derive from the current seeds; created here
+    #[account(mut)]
+    pub nullifier: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateNullifier<'info> {
+    /// Creates the current-version nullifier as an empty, program-owned,
+    /// rent-exempt account, which is all a spend checks for. Like Anchor's
+    /// `init`, it tolerates lamports sent to the address beforehand, so the
+    /// migration can't be blocked by pre-funding it.
+    pub fn create_nullifier(&self, seeds: Vec<Vec<u8>>, bump: u8) -> Result<()> {
+        let bump = [bump];
+        let mut signer_seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+        signer_seeds.push(&bump);
+        let signer_seeds = [signer_seeds.as_slice()];
+        let rent = Rent::get()?.minimum_balance(0);
+        let system = self.system_program.to_account_info();
+        let payer = self.payer.to_account_info();
+        let nullifier = self.nullifier.to_account_info();
+
+        let current = nullifier.lamports();
+        if current == 0 {
+            return system_program::create_account(
+                CpiContext::new_with_signer(
+                    system,
+                    system_program::CreateAccount { from: payer, to: nullifier },
+                    &signer_seeds,
+                ),
+                rent,
+                0,
+                &crate::ID,
+            );
+        }
+        if current < rent {
+            system_program::transfer(
+                CpiContext::new(
+                    system.clone(),
+                    system_program::Transfer { from: payer, to: nullifier.clone() },
+                ),
+                rent - current,
+            )?;
+        }
+        system_program::assign(
+            CpiContext::new_with_signer(
+                system,
+                system_program::Assign { account_to_assign: nullifier },
+                &signer_seeds,
+            ),
+            &crate::ID,
+        )
+    }
+}
@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use ark_serialize::{CanonicalDeserialize, Compress, Validate};
+use crate::errors::PoolError;
+use crate::groth16::{is_less_than_bn254_field_size_be, BN254_FQ_MODULUS_BE};
+use crate::utils::change_endianness_inplace;
+use crate::Proof;
+
+type G1 = ark_bn254::g1::G1Affine;
+
+/// Borsh length of `Proof`: the three points, then the seven public inputs.
+pub const PROOF_LEN: usize = 64 + 128 + 64 + 7 * 32;
+
+impl Proof {
+    /// Public inputs in the order the circuit declares them.
+    pub fn public_inputs(&self) -> [[u8; 32]; 7] {
+        [
+            self.root,
+            self.public_amount,
+            self.ext_data_hash,
+            self.input_nullifiers[0],
+            self.input_nullifiers[1],
+            self.output_commitments[0],
+            self.output_commitments[1],
+        ]
+    }
+
+    /// Cheap structural checks to run before any pairing work: `proof_a` and
+    /// `proof_c` are points on the curve, `proof_b`'s coordinates are base
+    /// field elements, and every public input is below the scalar field size.
+    pub fn validate(&self) -> Result<()> {
+        validate_g1(&self.proof_a)?;
+        validate_g1(&self.proof_c)?;
+        require!(
+            self.proof_b.chunks(32).all(|coordinate| coordinate < BN254_FQ_MODULUS_BE.as_slice()),
+            PoolError::InvalidProofEncoding
+        );
+        require!(
+            self.public_inputs().iter().all(is_less_than_bn254_field_size_be),
+            PoolError::PublicInputOutOfField
+        );
+        Ok(())
+    }
+}
+
+/// Parses and validates a Borsh-encoded proof, e.g. from a relayer request.
+impl TryFrom<&[u8]> for Proof {
+    type Error = anchor_lang::error::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self> {
+        require!(bytes.len() == PROOF_LEN, PoolError::InvalidProofDataLength);
+        let proof = Proof::try_from_slice(bytes)?;
+        proof.validate()?;
+        Ok(proof)
+    }
+}
+
+/// Checks a big-endian `(x, y)` G1 point, as the alt_bn128 syscalls take it,
+/// is on the curve.
+fn validate_g1(point: &[u8; 64]) -> Result<()> {
+    let mut le = *point;
+    change_endianness_inplace(&mut le);
+    G1::deserialize_with_mode(&le[..], Compress::No, Validate::Yes).map_err(|_| PoolError::InvalidProofEncoding)?;
+    Ok(())
+}
@@ -0,0 +1,153 @@
+use anchor_lang::prelude::*;
+use crate::errors::PoolError;
+use crate::utils::SOL_ADDRESS;
+use crate::{AuditLog, ErrorCode, GlobalConfig};
+
+/// Entries the registry can hold. Keeps the account under the 10 KiB that
+/// `init` can allocate.
+pub const MAX_REGISTERED_POOLS: usize = 64;
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolStatus {
+    Active,
+    /// Transacts are halted; see `CircuitBreaker` for automatic pauses.
+    Paused,
+    /// Replaced by a newer tree for the same mint and kept only for withdrawals.
+    Retired,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolEntry {
+    /// `SOL_ADDRESS` for the native SOL pool.
+    pub mint: Pubkey,
+    pub tree_account: Pubkey,
+    pub config: Pubkey,
+    pub status: PoolStatus,
+}
+
+/// Every pool this program has deployed, so clients can discover them with a
+/// single account fetch. Pool creation instructions append to it while there
+/// is room and tree rotation retires the old entry. Token pools created once
+/// it is full are still found through their `TokenPool` accounts.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct PoolRegistry {
+    #[max_len(MAX_REGISTERED_POOLS)]
+    pub pools: Vec<PoolEntry>,
+    pub bump: u8,
+}
+
+#[event]
+pub struct PoolRegistered {
+    pub mint: Pubkey,
+    pub tree_account: Pubkey,
+    pub config: Pubkey,
+}
+
+#[event]
+pub struct PoolStatusChanged {
+    pub tree_account: Pubkey,
+    pub status: PoolStatus,
+}
+
+impl PoolRegistry {
+    pub fn register(&mut self, mint: Pubkey, tree_account: Pubkey, config: Pubkey) -> Result<()> {
+        require!(self.find(&tree_account).is_none(), PoolError::PoolAlreadyRegistered);
+        require!(self.pools.len() < MAX_REGISTERED_POOLS, PoolError::PoolRegistryFull);
+        self.pools.push(PoolEntry {
+            mint,
+            tree_account,
+            config,
+            status: PoolStatus::Active,
+        });
+        emit!(PoolRegistered {
+            mint,
+            tree_account,
+            config,
+        });
+        Ok(())
+    }
+
+    pub fn set_status(&mut self, tree_account: &Pubkey, status: PoolStatus) -> Result<()> {
+        let entry = self
+            .pools
+            .iter_mut()
+            .find(|entry| entry.tree_account == *tree_account)
+            .ok_or(PoolError::PoolNotRegistered)?;
+        entry.status = status;
+        emit!(PoolStatusChanged {
+            tree_account: *tree_account,
+            status,
+        });
+        Ok(())
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.pools.len() >= MAX_REGISTERED_POOLS
+    }
+
+    pub fn find(&self, tree_account: &Pubkey) -> Option<&PoolEntry> {
+        self.pools.iter().find(|entry| entry.tree_account == *tree_account)
+    }
+
+    /// The active pool for `mint`, if any.
+    pub fn active_pool(&self, mint: &Pubkey) -> Option<&PoolEntry> {
+        self.pools
+            .iter()
+            .find(|entry| entry.mint == *mint && entry.status == PoolStatus::Active)
+    }
+}
+
+/// Creates the registry with the SOL pool from `initialize` as its first entry.
+#[derive(Accounts)]
+pub struct InitializePoolRegistry<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PoolRegistry::INIT_SPACE,
+        seeds = [b"pool_registry"],
+        bump
+    )]
+    pub pool_registry: Account<'info, PoolRegistry>,
+    /// CHECK: only its address is recorded
+    #[account(seeds = [b"merkle_tree"], bump)]
+    pub tree_account: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializePoolRegistry<'info> {
+    pub fn register_sol_pool(&mut self) -> Result<()> {
+        self.pool_registry.register(SOL_ADDRESS, self.tree_account.key(), self.global_config.key())
+    }
+}
+
+#[derive(Accounts)]
+pub struct SetPoolStatus<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool_registry"],
+        bump = pool_registry.bump
+    )]
+    pub pool_registry: Account<'info, PoolRegistry>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
@@ -0,0 +1,197 @@
+use anchor_lang::prelude::*;
+use crate::errors::PoolError;
+use crate::utils::MAX_FEE_RATE;
+use crate::{AuditLog, CircuitBreaker, ErrorCode, FeeRecipientConfig, GlobalConfig};
+
+/// Bond a relayer must keep posted for its own fee rate to apply, 1 SOL.
+pub const MIN_RELAYER_BOND: u64 = 1_000_000_000;
+
+/// Slots between requesting deregistration and getting the bond back, about
+/// two days, so misbehavior found after the fact can still be slashed.
+pub const RELAYER_UNBONDING_SLOTS: u64 = 432_000;
+
+/// A relayer's registration at `["relayer", relayer]`. Registered relayers
+/// set their own withdrawal fee rate, so they can compete on price while the
+/// program still verifies what users are charged. The protocol's cut stays at
+/// the global rate whatever the relayer charges. The bond is held as
+/// lamports in this account, above its rent.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct RelayerAccount {
+    pub relayer: Pubkey,
+    /// Basis points charged on withdrawals this relayer submits, at least
+    /// `GlobalConfig::withdrawal_fee_rate`. The relayer is paid the part
+    /// above the global rate.
+    pub withdrawal_fee_rate: u16,
+    pub registered_slot: u64,
+    /// Lamports posted as bond, less anything slashed.
+    pub bond: u64,
+    /// When deregistration was requested, or 0.
+    pub unbonding_slot: u64,
+    pub bump: u8,
+}
+
+/// What a slash is for. The evidence hash identifies the on-chain record,
+/// e.g. the transaction that overcharged or the withdrawal left unrelayed.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlashReason {
+    FeeManipulation,
+    Censorship,
+}
+
+#[event]
+pub struct RelayerFeeRateUpdated {
+    pub relayer: Pubkey,
+    pub withdrawal_fee_rate: u16,
+}
+
+#[event]
+pub struct RelayerBonded {
+    pub relayer: Pubkey,
+    pub amount: u64,
+    pub bond: u64,
+}
+
+#[event]
+pub struct RelayerSlashed {
+    pub relayer: Pubkey,
+    pub amount: u64,
+    pub reason: SlashReason,
+    pub evidence: [u8; 32],
+    pub treasury: Pubkey,
+}
+
+#[event]
+pub struct RelayerDeregistered {
+    pub relayer: Pubkey,
+}
+
+/// A relayer's rate is bounded below by the global rate, so it can't undercut
+/// the protocol's minimum, and above by `MAX_FEE_RATE`.
+pub fn validate_relayer_fee_rate(withdrawal_fee_rate: u16, global_withdrawal_fee_rate: u16) -> Result<()> {
+    require!(
+        withdrawal_fee_rate >= global_withdrawal_fee_rate,
+        PoolError::RelayerFeeRateBelowGlobal
+    );
+    require!(withdrawal_fee_rate <= MAX_FEE_RATE, PoolError::WithdrawalFeeRateTooHigh);
+    Ok(())
+}
+
+impl RelayerAccount {
+    /// Whether the relayer's own rate applies: it has at least
+    /// `MIN_RELAYER_BOND` at stake and isn't on its way out.
+    pub fn is_active(&self) -> bool {
+        self.bond >= MIN_RELAYER_BOND && self.unbonding_slot == 0
+    }
+
+    pub fn validate_slash(&self, amount: u64) -> Result<()> {
+        require!(amount > 0 && amount <= self.bond, PoolError::InvalidSlashAmount);
+        Ok(())
+    }
+
+    pub fn validate_deregistration(&self, slot: u64) -> Result<()> {
+        require!(
+            self.unbonding_slot != 0 && slot >= self.unbonding_slot.saturating_add(RELAYER_UNBONDING_SLOTS),
+            PoolError::RelayerStillBonded
+        );
+        Ok(())
+    }
+}
+
+/// The `relayer_fee_rate` `FeeSchedule::validate_fee` adds to the relayer's
+/// share: how far the submitting relayer's own rate is above the global
+/// rate, if it is registered and active, and nothing otherwise. The global
+/// rate can rise past a rate set before, which leaves no premium. `transact`
+/// must only pass the account of the transaction's signer.
+pub fn relayer_fee_premium(relayer: Option<&RelayerAccount>, global_withdrawal_fee_rate: u16) -> u16 {
+    relayer
+        .filter(|relayer| relayer.is_active())
+        .map_or(0, |relayer| relayer.withdrawal_fee_rate.saturating_sub(global_withdrawal_fee_rate))
+}
+
+/// Registers the signer as a relayer, or updates its rate if it already is.
+#[derive(Accounts)]
+pub struct SetRelayerFeeRate<'info> {
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = 8 + RelayerAccount::INIT_SPACE,
+        seeds = [b"relayer", relayer.key().as_ref()],
+        bump
+    )]
+    pub relayer_account: Account<'info, RelayerAccount>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Used by `bond_relayer` and `request_relayer_deregistration`.
+#[derive(Accounts)]
+pub struct UpdateRelayer<'info> {
+    #[account(
+        mut,
+        seeds = [b"relayer", relayer.key().as_ref()],
+        bump = relayer_account.bump,
+        has_one = relayer
+    )]
+    pub relayer_account: Account<'info, RelayerAccount>,
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DeregisterRelayer<'info> {
+    #[account(
+        mut,
+        close = relayer,
+        seeds = [b"relayer", relayer.key().as_ref()],
+        bump = relayer_account.bump,
+        has_one = relayer
+    )]
+    pub relayer_account: Account<'info, RelayerAccount>,
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+}
+
+/// Confiscates part of a relayer's bond into the protocol treasury, the
+/// configured fee recipient. Guardian-only, like the other incident
+/// responses.
+#[derive(Accounts)]
+pub struct SlashRelayer<'info> {
+    #[account(
+        mut,
+        seeds = [b"relayer", relayer_account.relayer.as_ref()],
+        bump = relayer_account.bump
+    )]
+    pub relayer_account: Account<'info, RelayerAccount>,
+    #[account(
+        seeds = [b"fee_recipient_config"],
+        bump = fee_recipient_config.bump
+    )]
+    pub fee_recipient_config: Account<'info, FeeRecipientConfig>,
+    /// CHECK: receives lamports only, pinned to the fee recipient
+    #[account(
+        mut,
+        address = fee_recipient_config.fee_recipient @ PoolError::FeeRecipientMismatch
+    )]
+    pub treasury: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"circuit_breaker"],
+        bump = circuit_breaker.bump,
+        has_one = guardian @ ErrorCode::Unauthorized
+    )]
+    pub circuit_breaker: Account<'info, CircuitBreaker>,
+    pub guardian: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount};
+use crate::errors::PoolError;
+use crate::{AuditLog, ErrorCode, GlobalConfig, TokenPool, TreeTokenAccount};
+
+#[event]
+pub struct TokensRescued {
+    pub source: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub recovery: Pubkey,
+}
+
+/// Tokens a pool PDA owns can't be pool funds unless they are the pool's
+/// own asset in its vault: the SOL pool holds lamports, not tokens, and a
+/// token pool holds only `mint` in `vault`.
+pub fn validate_rescue(token_pool: Option<&TokenPool>, source: &Pubkey, source_mint: &Pubkey) -> Result<()> {
+    if let Some(pool) = token_pool {
+        require!(
+            *source != pool.vault && *source_mint != pool.mint,
+            PoolError::CannotRescuePoolAsset
+        );
+    }
+    Ok(())
+}
+
+/// Sweeps a stray token account owned by a pool PDA to `recovery`. Without
+/// `token_pool` the owner is the SOL pool's `["tree_token"]` account;
+/// with it, that token pool's PDA.
+#[derive(Accounts)]
+pub struct RescueTokens<'info> {
+    #[account(mut)]
+    pub source: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = recovery.mint == source.mint @ PoolError::CannotRescuePoolAsset
+    )]
+    pub recovery: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [b"tree_token"],
+        bump = tree_token_account.bump
+    )]
+    pub tree_token_account: Account<'info, TreeTokenAccount>,
+    #[account(
+        seeds = [b"token_pool", token_pool.mint.as_ref()],
+        bump = token_pool.bump
+    )]
+    pub token_pool: Option<Account<'info, TokenPool>>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
+
+impl<'info> RescueTokens<'info> {
+    /// Transfers the whole balance of `source`, signing as its pool owner.
+    pub fn sweep(&self) -> Result<u64> {
+        let amount = self.source.amount;
+        let (owner, seeds): (AccountInfo<'info>, Vec<Vec<u8>>) = match &self.token_pool {
+            Some(pool) => (
+                pool.to_account_info(),
+                vec![b"token_pool".to_vec(), pool.mint.to_bytes().to_vec(), vec![pool.bump]],
+            ),
+            None => (
+                self.tree_token_account.to_account_info(),
+                vec![b"tree_token".to_vec(), vec![self.tree_token_account.bump]],
+            ),
+        };
+        require_keys_eq!(self.source.owner, owner.key(), PoolError::CannotRescuePoolAsset);
+        let seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                token::Transfer {
+                    from: self.source.to_account_info(),
+                    to: self.recovery.to_account_info(),
+                    authority: owner,
+                },
+                &[&seeds],
+            ),
+            amount,
+        )?;
+        Ok(amount)
+    }
+}
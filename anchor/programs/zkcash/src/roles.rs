@@ -0,0 +1,107 @@
+use anchor_lang::prelude::*;
+use crate::{AuditLog, ErrorCode, GlobalConfig};
+
+/// Duties the authority can hand to another key. The authority itself is
+/// the admin role and the circuit breaker's guardian is the guardian role;
+/// both already have their own keys, so only these are delegated here.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    /// Fee schedule and fee exemptions.
+    FeeManager,
+    /// Tree layout upkeep: root history, storage split, leaf pages and
+    /// root checkpoints.
+    TreeOperator,
+    /// Sweeping the fee vault to its payout address, and nothing else.
+    FeeCollector,
+}
+
+/// Holders of the delegated roles, at `["role_config"]`. A role left at the
+/// default key is held by the authority; once delegated, only the delegate
+/// holds it, so each key can do exactly its own duties. Every role-gated
+/// instruction requires this account, so a delegation can't be skipped by
+/// leaving it out; `initialize_role_config` creates it empty.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct RoleConfig {
+    pub fee_manager: Pubkey,
+    pub tree_operator: Pubkey,
+    pub fee_collector: Pubkey,
+    pub bump: u8,
+}
+
+#[event]
+pub struct RoleUpdated {
+    pub role: Role,
+    pub previous_holder: Pubkey,
+    pub holder: Pubkey,
+}
+
+impl RoleConfig {
+    /// The key `role` is delegated to, if any.
+    pub fn delegate(&self, role: Role) -> Option<Pubkey> {
+        let holder = match role {
+            Role::FeeManager => self.fee_manager,
+            Role::TreeOperator => self.tree_operator,
+            Role::FeeCollector => self.fee_collector,
+        };
+        (holder != Pubkey::default()).then_some(holder)
+    }
+
+    /// Delegates `role` to `holder`, or hands it back to the authority with
+    /// the default key. Returns the previous delegate.
+    pub fn set(&mut self, role: Role, holder: Pubkey) -> Pubkey {
+        let slot = match role {
+            Role::FeeManager => &mut self.fee_manager,
+            Role::TreeOperator => &mut self.tree_operator,
+            Role::FeeCollector => &mut self.fee_collector,
+        };
+        std::mem::replace(slot, holder)
+    }
+}
+
+/// Whether `signer` holds `role`: its delegate if it has one, otherwise
+/// `authority`. Instructions gated on a role take `role_config` and check
+/// this on their signer instead of `has_one`.
+pub fn holds_role(role_config: &RoleConfig, role: Role, authority: &Pubkey, signer: &Pubkey) -> bool {
+    role_config.delegate(role).unwrap_or(*authority) == *signer
+}
+
+/// Anyone can create the empty config, which leaves every role with the
+/// authority, as `initialize_audit_log` does for the log.
+#[derive(Accounts)]
+pub struct InitializeRoleConfig<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + RoleConfig::INIT_SPACE,
+        seeds = [b"role_config"],
+        bump
+    )]
+    pub role_config: Account<'info, RoleConfig>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetRole<'info> {
+    #[account(
+        mut,
+        seeds = [b"role_config"],
+        bump = role_config.bump
+    )]
+    pub role_config: Account<'info, RoleConfig>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
@@ -0,0 +1,142 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use crate::wormhole::{post_wormhole_message, WORMHOLE_PROGRAM_ID};
+use crate::MerkleTreeAccount;
+
+/// Layout version of `RootAttestation`, for light clients on other chains.
+pub const ROOT_ATTESTATION_VERSION: u8 = 1;
+
+/// Payload of the Wormhole message a tree's state is exported with: once
+/// guardians sign it, a verifier contract on another chain knows the tree
+/// had `root` after `next_index` leaves at `slot`, and can accept inclusion
+/// proofs against it. Posted from the `["root_emitter"]` PDA rather than the
+/// withdrawal emitter, so the counterpart tells the two payloads apart by
+/// emitter. Big-endian, like `WithdrawalAttestation`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RootAttestation {
+    pub tree_account: Pubkey,
+    pub next_index: u64,
+    pub root: [u8; 32],
+    pub slot: u64,
+}
+
+impl RootAttestation {
+    pub const LEN: usize = 1 + 32 + 8 + 32 + 8;
+
+    pub fn to_payload(&self) -> Vec<u8> {
+        let mut payload = Vec::with_capacity(Self::LEN);
+        payload.push(ROOT_ATTESTATION_VERSION);
+        payload.extend_from_slice(self.tree_account.as_ref());
+        payload.extend_from_slice(&self.next_index.to_be_bytes());
+        payload.extend_from_slice(&self.root);
+        payload.extend_from_slice(&self.slot.to_be_bytes());
+        payload
+    }
+}
+
+#[event]
+pub struct RootAttested {
+    pub tree_account: Pubkey,
+    pub next_index: u64,
+    pub root: [u8; 32],
+    pub slot: u64,
+    pub message: Pubkey,
+}
+
+/// Permissionless: anyone may export a tree's state, paying the bridge fee
+/// and the message's rent. `message` is our PDA at
+/// `["root_attestation", tree_account, next_index as u64 LE]`, so each tree
+/// state is posted at most once.
+#[derive(Accounts)]
+#[instruction(next_index: u64)]
+pub struct PostRootAttestation<'info> {
+    pub tree_account: AccountLoader<'info, MerkleTreeAccount>,
+    /// CHECK: the core bridge's config, address checked
+    #[account(
+        mut,
+        seeds = [b"Bridge"],
+        bump,
+        seeds::program = WORMHOLE_PROGRAM_ID
+    )]
+    pub bridge: UncheckedAccount<'info>,
+    /// CHECK: created by the core bridge
+    #[account(
+        mut,
+        seeds = [b"root_attestation", tree_account.key().as_ref(), &next_index.to_le_bytes()],
+        bump
+    )]
+    pub message: UncheckedAccount<'info>,
+    /// CHECK: signing PDA only
+    #[account(
+        seeds = [b"root_emitter"],
+        bump
+    )]
+    pub emitter: UncheckedAccount<'info>,
+    /// CHECK: the emitter's sequence tracker, owned by the core bridge
+    #[account(
+        mut,
+        seeds = [b"Sequence", emitter.key().as_ref()],
+        bump,
+        seeds::program = WORMHOLE_PROGRAM_ID
+    )]
+    pub sequence: UncheckedAccount<'info>,
+    /// CHECK: receives the bridge fee, address checked
+    #[account(
+        mut,
+        seeds = [b"fee_collector"],
+        bump,
+        seeds::program = WORMHOLE_PROGRAM_ID
+    )]
+    pub fee_collector: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: address checked
+    #[account(address = WORMHOLE_PROGRAM_ID)]
+    pub wormhole_program: UncheckedAccount<'info>,
+    /// CHECK: address checked
+    #[account(address = sysvar::clock::ID)]
+    pub clock: UncheckedAccount<'info>,
+    /// CHECK: address checked
+    #[account(address = sysvar::rent::ID)]
+    pub rent: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> PostRootAttestation<'info> {
+    /// Pays the bridge fee from `payer` and posts `attestation` as a
+    /// finalized Wormhole message.
+    pub fn post(&self, attestation: &RootAttestation, message_bump: u8, emitter_bump: u8) -> Result<()> {
+        let next_index = attestation.next_index.to_le_bytes();
+        let message_seeds: &[&[u8]] = &[
+            b"root_attestation",
+            attestation.tree_account.as_ref(),
+            &next_index,
+            &[message_bump],
+        ];
+        let emitter_seeds: &[&[u8]] = &[b"root_emitter", &[emitter_bump]];
+        post_wormhole_message(
+            [
+                self.bridge.to_account_info(),
+                self.message.to_account_info(),
+                self.emitter.to_account_info(),
+                self.sequence.to_account_info(),
+                self.payer.to_account_info(),
+                self.fee_collector.to_account_info(),
+                self.clock.to_account_info(),
+                self.rent.to_account_info(),
+                self.system_program.to_account_info(),
+            ],
+            attestation.to_payload(),
+            &[message_seeds, emitter_seeds],
+        )?;
+
+        emit!(RootAttested {
+            tree_account: attestation.tree_account,
+            next_index: attestation.next_index,
+            root: attestation.root,
+            slot: attestation.slot,
+            message: self.message.key(),
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,190 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use bytemuck::{Pod, Zeroable};
+use crate::errors::PoolError;
+use crate::{holds_role, AuditLog, ErrorCode, GlobalConfig, MerkleTreeAccount, Role, RoleConfig};
+
+/// Shortest interval the tree operator may set. At one checkpoint a minute the
+/// log still grows by under 70 KiB a day.
+pub const MIN_CHECKPOINT_INTERVAL_SLOTS: u64 = 150;
+
+pub const ROOT_CHECKPOINTS_HEADER_LEN: usize = 8 + std::mem::size_of::<RootCheckpoints>();
+
+pub const ROOT_CHECKPOINT_LEN: usize = std::mem::size_of::<RootCheckpoint>();
+
+/// An append-only timeline of a tree's roots, at
+/// `["root_checkpoints", tree_account]`, for bridges and auditors that need
+/// to know which root the tree had at a given slot without replaying its
+/// history. The header is followed by `len` `RootCheckpoint`s in slot
+/// order; `checkpoint_root` appends one at most every `interval_slots`.
+#[account(zero_copy)]
+pub struct RootCheckpoints {
+    pub tree_account: Pubkey,
+    pub interval_slots: u64,
+    pub len: u64,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+}
+
+/// One entry of the timeline: the tree's root and leaf count at `slot`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct RootCheckpoint {
+    pub slot: u64,
+    pub next_index: u64,
+    pub root: [u8; 32],
+}
+
+#[event]
+pub struct RootCheckpointed {
+    pub tree_account: Pubkey,
+    pub slot: u64,
+    pub next_index: u64,
+    pub root: [u8; 32],
+}
+
+pub fn validate_checkpoint_interval(interval_slots: u64) -> Result<()> {
+    require!(
+        interval_slots >= MIN_CHECKPOINT_INTERVAL_SLOTS,
+        PoolError::InvalidCheckpointInterval
+    );
+    Ok(())
+}
+
+/// Number of checkpoints stored after the header in raw account data.
+pub fn root_checkpoint_count(data: &[u8]) -> Result<usize> {
+    let entries = data
+        .len()
+        .checked_sub(ROOT_CHECKPOINTS_HEADER_LEN)
+        .ok_or(PoolError::InvalidAccountLayout)?;
+    require!(entries % ROOT_CHECKPOINT_LEN == 0, PoolError::InvalidAccountLayout);
+    Ok(entries / ROOT_CHECKPOINT_LEN)
+}
+
+/// Checkpoint `index` from raw account data. Read unaligned, so it works
+/// on data fetched off-chain as well.
+pub fn root_checkpoint(data: &[u8], index: usize) -> Option<RootCheckpoint> {
+    let offset = ROOT_CHECKPOINTS_HEADER_LEN.checked_add(index.checked_mul(ROOT_CHECKPOINT_LEN)?)?;
+    let bytes = data.get(offset..offset + ROOT_CHECKPOINT_LEN)?;
+    Some(bytemuck::pod_read_unaligned(bytes))
+}
+
+/// The latest checkpoint taken at or before `slot`, i.e. the tree state a
+/// reader at that slot could have seen checkpointed. Binary search, since
+/// checkpoints are appended in slot order.
+pub fn checkpoint_at_or_before(data: &[u8], slot: u64) -> Result<Option<RootCheckpoint>> {
+    let (mut low, mut high) = (0, root_checkpoint_count(data)?);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        match root_checkpoint(data, mid) {
+            Some(checkpoint) if checkpoint.slot <= slot => low = mid + 1,
+            _ => high = mid,
+        }
+    }
+    Ok(low.checked_sub(1).and_then(|index| root_checkpoint(data, index)))
+}
+
+/// Whether a new checkpoint is due at `slot`, given the last one taken.
+pub fn checkpoint_due(last: Option<&RootCheckpoint>, interval_slots: u64, slot: u64) -> bool {
+    match last {
+        Some(last) => slot >= last.slot.saturating_add(interval_slots),
+        None => true,
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeRootCheckpoints<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = ROOT_CHECKPOINTS_HEADER_LEN,
+        seeds = [b"root_checkpoints", tree_account.key().as_ref()],
+        bump
+    )]
+    pub root_checkpoints: AccountLoader<'info, RootCheckpoints>,
+    pub tree_account: AccountLoader<'info, MerkleTreeAccount>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(
+        seeds = [b"role_config"],
+        bump = role_config.bump
+    )]
+    pub role_config: Account<'info, RoleConfig>,
+    #[account(
+        mut,
+        constraint = holds_role(&role_config, Role::TreeOperator, &global_config.authority, &authority.key())
+            @ ErrorCode::Unauthorized
+    )]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
+
+/// Permissionless crank; the payer funds the rent of the entry it adds.
+#[derive(Accounts)]
+pub struct CheckpointRoot<'info> {
+    #[account(
+        mut,
+        seeds = [b"root_checkpoints", tree_account.key().as_ref()],
+        bump = root_checkpoints.load()?.bump
+    )]
+    pub root_checkpoints: AccountLoader<'info, RootCheckpoints>,
+    pub tree_account: AccountLoader<'info, MerkleTreeAccount>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CheckpointRoot<'info> {
+    /// Appends the tree's current root at `slot`. Fails with
+    /// `CheckpointTooSoon` within `interval_slots` of the last checkpoint.
+    pub fn append(&self, slot: u64) -> Result<RootCheckpoint> {
+        let (interval_slots, len) = {
+            let header = self.root_checkpoints.load()?;
+            (header.interval_slots, header.len)
+        };
+        let info = self.root_checkpoints.to_account_info();
+        {
+            let data = info.try_borrow_data()?;
+            require!(root_checkpoint_count(&data)? == len as usize, PoolError::InvalidAccountLayout);
+            let last = (len as usize).checked_sub(1).and_then(|index| root_checkpoint(&data, index));
+            require!(
+                checkpoint_due(last.as_ref(), interval_slots, slot),
+                PoolError::CheckpointTooSoon
+            );
+        }
+
+        let checkpoint = {
+            let tree = self.tree_account.load()?;
+            RootCheckpoint { slot, next_index: tree.next_index, root: tree.root }
+        };
+        let offset = ROOT_CHECKPOINTS_HEADER_LEN + len as usize * ROOT_CHECKPOINT_LEN;
+        info.realloc(offset + ROOT_CHECKPOINT_LEN, false)?;
+
+        let shortfall = Rent::get()?.minimum_balance(info.data_len()).saturating_sub(info.lamports());
+        if shortfall > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: self.payer.to_account_info(),
+                        to: info.clone(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+
+        info.try_borrow_mut_data()?[offset..].copy_from_slice(bytemuck::bytes_of(&checkpoint));
+        self.root_checkpoints.load_mut()?.len = len + 1;
+        Ok(checkpoint)
+    }
+}
@@ -0,0 +1,311 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+use crate::errors::PoolError;
+use crate::merkle_tree::MerkleTree;
+use crate::{holds_role, AuditLog, ErrorCode, GlobalConfig, MerkleTreeAccount, Role, RoleConfig};
+
+/// Roots the separate history keeps, ten times the tree account's own.
+pub const ROOT_HISTORY_CAPACITY: usize = 1024;
+
+/// Buckets in `RootHistory::index`, twice the capacity so probe sequences
+/// stay short when the history is full.
+pub const ROOT_INDEX_BUCKETS: usize = 2 * ROOT_HISTORY_CAPACITY;
+
+pub const ROOT_HISTORY_ACCOUNT_LEN: usize = 8 + std::mem::size_of::<RootHistory>();
+
+/// A tree's root history kept outside `MerkleTreeAccount`, at
+/// `["root_history", tree_account]`, so a busy pool can accept proofs
+/// against roots more than 100 appends old without growing the tree
+/// account. Once it exists `transact` records every new root here and checks
+/// withdrawal roots against it instead of the tree's own history.
+///
+/// At about 36 KiB it is larger than one instruction may allocate, so
+/// `initialize_root_history` creates it at 10 KiB and `grow_root_history`
+/// extends it; it only becomes a `RootHistory` once fully grown.
+///
+/// `index` is an open-addressed table from a root's leading bytes to its
+/// slot in `roots`, kept in step by `push`, so `is_known_root` costs a
+/// couple of probes rather than a scan of the whole history.
+#[account(zero_copy)]
+pub struct RootHistory {
+    pub tree_account: Pubkey,
+    /// Slot of `roots` holding the current root.
+    pub root_index: u64,
+    pub roots: [[u8; 32]; ROOT_HISTORY_CAPACITY],
+    /// Slot + 1 of an indexed root, or 0 for an empty bucket. Linear
+    /// probing, with backward-shift deletion so no tombstones build up.
+    pub index: [u16; ROOT_INDEX_BUCKETS],
+}
+
+#[event]
+pub struct RootHistoryInitialized {
+    pub tree_account: Pubkey,
+    pub root_history: Pubkey,
+    pub capacity: u64,
+}
+
+/// Home bucket of `root`. Roots are big-endian field elements, so the first
+/// byte is bounded by the modulus but the ones after it are uniform.
+fn root_bucket(root: &[u8; 32]) -> usize {
+    let prefix = u64::from_be_bytes(root[..8].try_into().unwrap());
+    (prefix % ROOT_INDEX_BUCKETS as u64) as usize
+}
+
+impl RootHistory {
+    /// Starts the history at the tree's current root.
+    pub fn seed(&mut self, tree_account: Pubkey, root: [u8; 32]) {
+        self.tree_account = tree_account;
+        self.root_index = 0;
+        self.roots.fill([0u8; 32]);
+        self.roots[0] = root;
+        self.rebuild_index();
+    }
+
+    /// Records a root the tree just moved to. `transact` calls this after
+    /// each append, alongside the tree's own history.
+    pub fn push(&mut self, root: [u8; 32]) {
+        self.root_index = (self.root_index + 1) % ROOT_HISTORY_CAPACITY as u64;
+        let slot = self.root_index as usize;
+        self.unindex(slot);
+        self.roots[slot] = root;
+        self.index_slot(slot);
+    }
+
+    pub fn is_known_root(&self, root: &[u8; 32]) -> bool {
+        if *root == [0u8; 32] {
+            return false;
+        }
+        let mut bucket = root_bucket(root);
+        while self.index[bucket] != 0 {
+            if self.roots[self.index[bucket] as usize - 1] == *root {
+                return true;
+            }
+            bucket = (bucket + 1) % ROOT_INDEX_BUCKETS;
+        }
+        false
+    }
+
+    /// Re-derives `index` from `roots`, for histories written before the
+    /// index existed.
+    pub fn rebuild_index(&mut self) {
+        self.index.fill(0);
+        for slot in 0..ROOT_HISTORY_CAPACITY {
+            self.index_slot(slot);
+        }
+    }
+
+    fn index_slot(&mut self, slot: usize) {
+        if self.roots[slot] == [0u8; 32] {
+            return;
+        }
+        let mut bucket = root_bucket(&self.roots[slot]);
+        while self.index[bucket] != 0 {
+            bucket = (bucket + 1) % ROOT_INDEX_BUCKETS;
+        }
+        self.index[bucket] = slot as u16 + 1;
+    }
+
+    /// Drops the entry for `slot` before its root is overwritten, shifting
+    /// later entries of the probe run back so none becomes unreachable.
+    fn unindex(&mut self, slot: usize) {
+        if self.roots[slot] == [0u8; 32] {
+            return;
+        }
+        let entry = slot as u16 + 1;
+        let mut hole = root_bucket(&self.roots[slot]);
+        while self.index[hole] != entry {
+            if self.index[hole] == 0 {
+                return;
+            }
+            hole = (hole + 1) % ROOT_INDEX_BUCKETS;
+        }
+        self.index[hole] = 0;
+
+        let mut next = hole;
+        loop {
+            next = (next + 1) % ROOT_INDEX_BUCKETS;
+            let moved = self.index[next];
+            if moved == 0 {
+                return;
+            }
+            // An entry can fill the hole unless its home bucket lies
+            // cyclically in (hole, next], where it is already reachable.
+            let home = root_bucket(&self.roots[moved as usize - 1]);
+            let reachable = if hole <= next {
+                hole < home && home <= next
+            } else {
+                hole < home || home <= next
+            };
+            if !reachable {
+                self.index[hole] = moved;
+                self.index[next] = 0;
+                hole = next;
+            }
+        }
+    }
+}
+
+/// Whether a proof against `root` is acceptable: checked against the
+/// separate history when the tree has one, else against the tree's own.
+pub fn is_known_root(tree: &MerkleTreeAccount, root_history: Option<&RootHistory>, root: &[u8; 32]) -> bool {
+    match root_history {
+        Some(root_history) => root_history.is_known_root(root),
+        None => MerkleTree::is_known_root(tree, *root),
+    }
+}
+
+/// Length of the root history account after one more growth step.
+pub fn next_root_history_len(current_len: usize) -> Result<usize> {
+    require!(current_len < ROOT_HISTORY_ACCOUNT_LEN, PoolError::RootHistoryAlreadyAllocated);
+    Ok((current_len + MAX_PERMITTED_DATA_INCREASE).min(ROOT_HISTORY_ACCOUNT_LEN))
+}
+
+/// Gated on the tree operator because, once the history exists, every
+/// `transact` on the tree has to pass it.
+#[derive(Accounts)]
+pub struct InitializeRootHistory<'info> {
+    /// CHECK: created here at its first 10 KiB; typed once fully grown
+    #[account(
+        mut,
+        seeds = [b"root_history", tree_account.key().as_ref()],
+        bump
+    )]
+    pub root_history: UncheckedAccount<'info>,
+    pub tree_account: AccountLoader<'info, MerkleTreeAccount>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(
+        seeds = [b"role_config"],
+        bump = role_config.bump
+    )]
+    pub role_config: Account<'info, RoleConfig>,
+    #[account(
+        mut,
+        constraint = holds_role(&role_config, Role::TreeOperator, &global_config.authority, &authority.key())
+            @ ErrorCode::Unauthorized
+    )]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
+
+impl<'info> InitializeRootHistory<'info> {
+    /// Creates the account at `MAX_PERMITTED_DATA_INCREASE` bytes, owned by
+    /// the program. Like Anchor's `init`, it tolerates lamports sent to the
+    /// address beforehand.
+    pub fn create(&self, bump: u8) -> Result<()> {
+        let tree_key = self.tree_account.key();
+        let signer_seeds: &[&[u8]] = &[b"root_history", tree_key.as_ref(), &[bump]];
+        let rent = Rent::get()?.minimum_balance(MAX_PERMITTED_DATA_INCREASE);
+        let system = self.system_program.to_account_info();
+        let authority = self.authority.to_account_info();
+        let root_history = self.root_history.to_account_info();
+
+        let current = root_history.lamports();
+        if current == 0 {
+            return system_program::create_account(
+                CpiContext::new_with_signer(
+                    system,
+                    system_program::CreateAccount { from: authority, to: root_history },
+                    &[signer_seeds],
+                ),
+                rent,
+                MAX_PERMITTED_DATA_INCREASE as u64,
+                &crate::ID,
+            );
+        }
+        if current < rent {
+            system_program::transfer(
+                CpiContext::new(
+                    system.clone(),
+                    system_program::Transfer { from: authority, to: root_history.clone() },
+                ),
+                rent - current,
+            )?;
+        }
+        system_program::allocate(
+            CpiContext::new_with_signer(
+                system.clone(),
+                system_program::Allocate { account_to_allocate: root_history.clone() },
+                &[signer_seeds],
+            ),
+            MAX_PERMITTED_DATA_INCREASE as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(
+                system,
+                system_program::Assign { account_to_assign: root_history },
+                &[signer_seeds],
+            ),
+            &crate::ID,
+        )
+    }
+}
+
+/// Permissionless: anyone can pay to finish allocating a history the
+/// authority started.
+#[derive(Accounts)]
+pub struct GrowRootHistory<'info> {
+    /// CHECK: owner and seeds checked; typed once fully grown
+    #[account(
+        mut,
+        seeds = [b"root_history", tree_account.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub root_history: UncheckedAccount<'info>,
+    pub tree_account: AccountLoader<'info, MerkleTreeAccount>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> GrowRootHistory<'info> {
+    /// Extends the account by one step and tops its rent up. Returns whether
+    /// it has reached `ROOT_HISTORY_ACCOUNT_LEN`, in which case the
+    /// discriminator is written and the history seeded with the tree's
+    /// current root. A history that already had its discriminator, grown
+    /// again to make room for `index`, keeps its roots and is reindexed.
+    pub fn grow(&self) -> Result<bool> {
+        let root_history = self.root_history.to_account_info();
+        let new_len = next_root_history_len(root_history.data_len())?;
+        root_history.realloc(new_len, true)?;
+
+        let shortfall = Rent::get()?.minimum_balance(new_len).saturating_sub(root_history.lamports());
+        if shortfall > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: self.payer.to_account_info(),
+                        to: root_history.clone(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+        if new_len < ROOT_HISTORY_ACCOUNT_LEN {
+            return Ok(false);
+        }
+
+        let root = self.tree_account.load()?.root;
+        let mut data = root_history.try_borrow_mut_data()?;
+        if data[..8] == *RootHistory::DISCRIMINATOR {
+            bytemuck::from_bytes_mut::<RootHistory>(&mut data[8..]).rebuild_index();
+            return Ok(true);
+        }
+        data[..8].copy_from_slice(RootHistory::DISCRIMINATOR);
+        bytemuck::from_bytes_mut::<RootHistory>(&mut data[8..]).seed(self.tree_account.key(), root);
+        Ok(true)
+    }
+}
@@ -0,0 +1,78 @@
+use anchor_lang::prelude::*;
+use crate::errors::PoolError;
+use crate::{AuditLog, ErrorCode, GlobalConfig};
+
+/// Smallest nonzero bound, about a minute, so a policy can't leave
+/// withdrawals without any usable root.
+pub const MIN_MAX_ROOT_AGE_SLOTS: u64 = 150;
+
+/// Bounds how old a withdrawal's root may be, independently of how many
+/// roots the history holds. A busy pool cycles its history in minutes and a
+/// quiet one keeps roots for weeks; this gives both the same freshness
+/// guarantee. Tighter bounds shrink the window a relayer has to land a proof
+/// before it must be regenerated.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct RootPolicy {
+    /// 0 disables the bound.
+    pub max_root_age_slots: u64,
+    pub bump: u8,
+}
+
+#[event]
+pub struct RootPolicyUpdated {
+    pub max_root_age_slots: u64,
+}
+
+pub fn validate_max_root_age(max_root_age_slots: u64) -> Result<()> {
+    require!(
+        max_root_age_slots == 0 || max_root_age_slots >= MIN_MAX_ROOT_AGE_SLOTS,
+        PoolError::InvalidRootPolicy
+    );
+    Ok(())
+}
+
+impl RootPolicy {
+    /// Checks a withdrawal's root, created at `root_slot` as found by
+    /// `known_root_slot`, is recent enough at `slot`. A root with no known
+    /// slot predates the tree's v2 migration and counts as stale while the
+    /// bound is on. Deposits and transfers are never held to it.
+    pub fn validate_root_age(&self, ext_amount: i64, root_slot: Option<u64>, slot: u64) -> Result<()> {
+        if self.max_root_age_slots == 0 || ext_amount >= 0 {
+            return Ok(());
+        }
+        let root_slot = root_slot.ok_or(PoolError::StaleRoot)?;
+        require!(
+            slot.saturating_sub(root_slot) <= self.max_root_age_slots,
+            PoolError::StaleRoot
+        );
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct SetRootPolicy<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + RootPolicy::INIT_SPACE,
+        seeds = [b"root_policy"],
+        bump
+    )]
+    pub root_policy: Account<'info, RootPolicy>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
@@ -0,0 +1,56 @@
+use crate::{MerkleTreeAccount, MERKLE_TREE_ACCOUNT_V1_LEN, MERKLE_TREE_ACCOUNT_VERSION_OFFSET};
+
+/// Capacity of `MerkleTreeAccount::root_history`, one slot per entry.
+pub const ROOT_SLOT_ENTRIES: usize = 100;
+
+/// The slots live right after the v1 layout, as little-endian u64s indexed
+/// like `root_history`. They sit outside the zero-copy struct, so they are
+/// read and written on the raw account data.
+pub const ROOT_SLOTS_OFFSET: usize = MERKLE_TREE_ACCOUNT_V1_LEN;
+
+const ROOT_SLOTS_VERSION: u8 = 2;
+
+/// Checked on length and version byte alone, so it also works on a tree
+/// created in this instruction, before Anchor writes its discriminator.
+fn has_root_slots(data: &[u8]) -> bool {
+    data.len() >= ROOT_SLOTS_OFFSET + 8 * ROOT_SLOT_ENTRIES
+        && data[MERKLE_TREE_ACCOUNT_VERSION_OFFSET] >= ROOT_SLOTS_VERSION
+}
+
+/// Records that the root at `root_index` was created at `slot`. `transact`
+/// calls this after its appends, once the zero-copy borrow is released.
+/// Trees not yet migrated to v2 are left alone.
+pub fn record_root_slot(data: &mut [u8], root_index: u64, slot: u64) {
+    if has_root_slots(data) {
+        let offset = ROOT_SLOTS_OFFSET + 8 * (root_index as usize % ROOT_SLOT_ENTRIES);
+        data[offset..offset + 8].copy_from_slice(&slot.to_le_bytes());
+    }
+}
+
+/// The slot the root at `root_index` was created at, or `None` if the tree
+/// predates v2 or the root predates its migration.
+pub fn root_slot(data: &[u8], root_index: u64) -> Option<u64> {
+    if !has_root_slots(data) {
+        return None;
+    }
+    let offset = ROOT_SLOTS_OFFSET + 8 * (root_index as usize % ROOT_SLOT_ENTRIES);
+    let slot = u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+    (slot != 0).then_some(slot)
+}
+
+/// Where `root` sits in the history, newest first, like `is_known_root`.
+pub fn root_history_index(tree: &MerkleTreeAccount, root: &[u8; 32]) -> Option<u64> {
+    if *root == [0u8; 32] {
+        return None;
+    }
+    let size = tree.root_history_size as u64;
+    (0..size)
+        .map(|age| (tree.root_index + size - age) % size)
+        .find(|&index| tree.root_history[index as usize] == *root)
+}
+
+/// The slot `root` was created at, if it is still in the history and its
+/// slot is known. For "root not known" diagnostics and root age policies.
+pub fn known_root_slot(tree: &MerkleTreeAccount, data: &[u8], root: &[u8; 32]) -> Option<u64> {
+    root_history_index(tree, root).and_then(|index| root_slot(data, index))
+}
@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+use crate::ErrorCode;
+
+/// Running totals for the pool, so dashboards can show activity and TVL
+/// without replaying every transact. Amounts are in lamports. The anonymity
+/// set size is the tree's `next_index` and TVL the tree token account's
+/// balance, both already on-chain.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct PoolStats {
+    pub deposit_count: u64,
+    pub withdrawal_count: u64,
+    /// Transacts with `ext_amount == 0`, i.e. shielded-to-shielded transfers.
+    pub transfer_count: u64,
+    pub total_deposited: u64,
+    pub total_withdrawn: u64,
+    pub total_fees: u64,
+    pub bump: u8,
+}
+
+impl PoolStats {
+    /// Records one transact. Must be called from `transact` after the proof and
+    /// the transfers succeeded, with the same `ext_amount` and `fee`.
+    pub fn record(&mut self, ext_amount: i64, fee: u64) -> Result<()> {
+        let amount = ext_amount.unsigned_abs();
+        if ext_amount > 0 {
+            self.deposit_count = self.deposit_count.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+            self.total_deposited = self.total_deposited.checked_add(amount).ok_or(ErrorCode::ArithmeticOverflow)?;
+        } else if ext_amount < 0 {
+            self.withdrawal_count = self.withdrawal_count.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+            self.total_withdrawn = self.total_withdrawn.checked_add(amount).ok_or(ErrorCode::ArithmeticOverflow)?;
+        } else {
+            self.transfer_count = self.transfer_count.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        }
+        self.total_fees = self.total_fees.checked_add(fee).ok_or(ErrorCode::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
+
+/// Creates the stats PDA. Separate from `initialize` so pools deployed before
+/// stats existed can add it; totals only cover transacts after creation.
+#[derive(Accounts)]
+pub struct InitializePoolStats<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + PoolStats::INIT_SPACE,
+        seeds = [b"pool_stats"],
+        bump
+    )]
+    pub pool_stats: Account<'info, PoolStats>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
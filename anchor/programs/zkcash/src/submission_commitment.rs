@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use crate::errors::PoolError;
+use crate::Proof;
+
+/// A reveal must land at least this many slots after its commitment, so the
+/// commitment is already on chain when the payload first becomes visible.
+pub const MIN_REVEAL_DELAY_SLOTS: u64 = 1;
+
+/// Commitments not revealed within this many slots, about a minute, are
+/// stale; the relayer can only close them.
+pub const MAX_REVEAL_DELAY_SLOTS: u64 = 150;
+
+/// A relayer's claim on a withdrawal it hasn't shown yet, at
+/// `["submission_commitment", commitment]`. Revealing the payload is what
+/// exposes it to the mempool, and by then only the relayer that committed
+/// to it can submit it: anyone copying the reveal would need a commitment of
+/// their own from an earlier slot, which they couldn't have made without the
+/// payload.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct SubmissionCommitment {
+    pub relayer: Pubkey,
+    pub commitment: [u8; 32],
+    pub committed_slot: u64,
+    pub bump: u8,
+}
+
+#[event]
+pub struct SubmissionCommitted {
+    pub relayer: Pubkey,
+    pub commitment: [u8; 32],
+}
+
+/// sha256 of the relayer and the Borsh-encoded proof. The proof's public
+/// inputs include the ExtData hash, so this binds the whole withdrawal.
+pub fn submission_commitment_hash(relayer: &Pubkey, proof: &Proof) -> Result<[u8; 32]> {
+    let proof_bytes = proof.try_to_vec()?;
+    Ok(hashv(&[relayer.as_ref(), &proof_bytes]).to_bytes())
+}
+
+impl SubmissionCommitment {
+    /// Checks that `proof`, submitted by `relayer` at `slot`, is what this
+    /// commitment was made for and that the reveal is in its window.
+    /// `transact` calls this in commit-reveal mode and closes the account to
+    /// the relayer.
+    pub fn validate_reveal(&self, relayer: &Pubkey, proof: &Proof, slot: u64) -> Result<()> {
+        require!(
+            self.relayer == *relayer && self.commitment == submission_commitment_hash(relayer, proof)?,
+            PoolError::SubmissionCommitmentMismatch
+        );
+        let elapsed = slot.saturating_sub(self.committed_slot);
+        require!(
+            (MIN_REVEAL_DELAY_SLOTS..=MAX_REVEAL_DELAY_SLOTS).contains(&elapsed),
+            PoolError::RevealOutsideWindow
+        );
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(commitment: [u8; 32])]
+pub struct CommitSubmission<'info> {
+    #[account(
+        init,
+        payer = relayer,
+        space = 8 + SubmissionCommitment::INIT_SPACE,
+        seeds = [b"submission_commitment", commitment.as_ref()],
+        bump
+    )]
+    pub submission_commitment: Account<'info, SubmissionCommitment>,
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Lets a relayer reclaim the rent of a commitment it won't reveal.
+#[derive(Accounts)]
+pub struct CloseSubmissionCommitment<'info> {
+    #[account(
+        mut,
+        close = relayer,
+        seeds = [b"submission_commitment", submission_commitment.commitment.as_ref()],
+        bump = submission_commitment.bump,
+        has_one = relayer
+    )]
+    pub submission_commitment: Account<'info, SubmissionCommitment>,
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+}
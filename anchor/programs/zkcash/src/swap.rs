@@ -0,0 +1,149 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use crate::errors::PoolError;
+use crate::{AuditLog, ErrorCode, GlobalConfig};
+
+/// Swap programs the allowlist can hold.
+pub const MAX_SWAP_PROGRAMS: usize = 8;
+
+/// SPL `Account::amount`.
+const TOKEN_ACCOUNT_AMOUNT_OFFSET: usize = 64;
+
+/// A withdrawal that swaps the unshielded amount before delivering it,
+/// committed to in ExtData from `EXT_DATA_VERSION_SWAP`. The route's
+/// accounts come from the transaction, but its instruction data must hash to
+/// `route_hash` and the recipient's `output_mint` token account must gain at
+/// least `min_output_amount`, so a relayer can neither reroute nor sandwich
+/// the swap beyond the slippage the user signed off on.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwapParams {
+    pub program: Pubkey,
+    pub output_mint: Pubkey,
+    pub min_output_amount: u64,
+    /// sha256 of the swap instruction data.
+    pub route_hash: [u8; 32],
+}
+
+/// Programs withdrawals may swap through, e.g. the Jupiter aggregator.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct SwapAllowlist {
+    #[max_len(MAX_SWAP_PROGRAMS)]
+    pub programs: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+#[event]
+pub struct SwapAllowlistUpdated {
+    pub programs: Vec<Pubkey>,
+}
+
+#[event]
+pub struct WithdrawalSwapped {
+    pub program: Pubkey,
+    pub output_mint: Pubkey,
+    pub input_amount: u64,
+    pub output_amount: u64,
+}
+
+pub fn validate_swap_programs(programs: &[Pubkey]) -> Result<()> {
+    require!(programs.len() <= MAX_SWAP_PROGRAMS, PoolError::InvalidSwapAllowlist);
+    require!(
+        programs.iter().all(|program| *program != Pubkey::default() && *program != crate::ID),
+        PoolError::InvalidSwapAllowlist
+    );
+    Ok(())
+}
+
+impl SwapAllowlist {
+    /// Checks `params` names an allowlisted program and that `route_data` is
+    /// the route the user committed to.
+    pub fn validate_swap(&self, params: &SwapParams, route_data: &[u8]) -> Result<()> {
+        require!(self.programs.contains(&params.program), PoolError::SwapProgramNotAllowed);
+        require!(hash(route_data).to_bytes() == params.route_hash, PoolError::SwapRouteMismatch);
+        Ok(())
+    }
+}
+
+/// The `amount` field of an SPL token account.
+pub fn token_account_amount(account: &AccountInfo) -> Result<u64> {
+    let data = account.try_borrow_data()?;
+    let bytes = data
+        .get(TOKEN_ACCOUNT_AMOUNT_OFFSET..TOKEN_ACCOUNT_AMOUNT_OFFSET + 8)
+        .ok_or(PoolError::SwapOutputTooLow)?;
+    Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// Runs the committed swap route with `route_accounts`, signing for the
+/// pool-owned account that holds the unshielded input, and returns how much
+/// `output_account` received. Fails unless that is at least
+/// `params.min_output_amount`. `output_account` must be the withdrawal's
+/// recipient, checked by the caller against ExtData and `params.output_mint`.
+pub fn execute_swap<'info>(
+    params: &SwapParams,
+    route_data: Vec<u8>,
+    route_accounts: &[AccountInfo<'info>],
+    output_account: &AccountInfo<'info>,
+    signer_seeds: &[&[u8]],
+) -> Result<u64> {
+    let instruction = pda_signed_instruction(params.program, route_data, route_accounts, signer_seeds)?;
+    let before = token_account_amount(output_account)?;
+    invoke_signed(&instruction, route_accounts, &[signer_seeds])?;
+    let received = token_account_amount(output_account)?.saturating_sub(before);
+    require!(received >= params.min_output_amount, PoolError::SwapOutputTooLow);
+    Ok(received)
+}
+
+/// An instruction for `program` over `accounts`, marking the PDA derived from
+/// `signer_seeds` as a signer so `invoke_signed` can sign for it.
+pub(crate) fn pda_signed_instruction(
+    program: Pubkey,
+    data: Vec<u8>,
+    accounts: &[AccountInfo],
+    signer_seeds: &[&[u8]],
+) -> Result<Instruction> {
+    let signer = Pubkey::create_program_address(signer_seeds, &crate::ID)
+        .map_err(|_| ProgramError::InvalidSeeds)?;
+    let accounts = accounts
+        .iter()
+        .map(|account| AccountMeta {
+            pubkey: *account.key,
+            is_signer: account.is_signer || *account.key == signer,
+            is_writable: account.is_writable,
+        })
+        .collect();
+    Ok(Instruction {
+        program_id: program,
+        accounts,
+        data,
+    })
+}
+
+#[derive(Accounts)]
+pub struct SetSwapPrograms<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + SwapAllowlist::INIT_SPACE,
+        seeds = [b"swap_allowlist"],
+        bump
+    )]
+    pub swap_allowlist: Account<'info, SwapAllowlist>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
@@ -0,0 +1,159 @@
+use anchor_lang::prelude::*;
+use crate::errors::PoolError;
+use crate::utils::validate_fee_config;
+use crate::{AuditLog, ErrorCode, GlobalConfig, MerkleTreeAccount};
+
+/// Slots between queueing a config change and being able to execute it,
+/// roughly one day at 400ms slots.
+pub const CONFIG_UPDATE_DELAY_SLOTS: u64 = 216_000;
+
+/// A queued change to the pool's fees or deposit limit. `None` fields are left
+/// as they are. There is at most one pending update; queueing again replaces
+/// it and restarts the delay.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct PendingConfigUpdate {
+    pub deposit_fee_rate: Option<u16>,
+    pub withdrawal_fee_rate: Option<u16>,
+    pub fee_error_margin: Option<u16>,
+    pub max_deposit_amount: Option<u64>,
+    pub effective_slot: u64,
+    pub bump: u8,
+}
+
+#[event]
+pub struct ConfigUpdateQueued {
+    pub deposit_fee_rate: Option<u16>,
+    pub withdrawal_fee_rate: Option<u16>,
+    pub fee_error_margin: Option<u16>,
+    pub max_deposit_amount: Option<u64>,
+    pub effective_slot: u64,
+}
+
+#[event]
+pub struct ConfigUpdateExecuted {
+    pub deposit_fee_rate: u16,
+    pub withdrawal_fee_rate: u16,
+    pub fee_error_margin: u16,
+    pub max_deposit_amount: u64,
+}
+
+#[event]
+pub struct ConfigUpdateCancelled {
+    pub effective_slot: u64,
+}
+
+impl PendingConfigUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.deposit_fee_rate.is_none()
+            && self.withdrawal_fee_rate.is_none()
+            && self.fee_error_margin.is_none()
+            && self.max_deposit_amount.is_none()
+    }
+
+    /// Checks the update against `global_config` as it would look once applied.
+    pub fn validate(&self, global_config: &GlobalConfig) -> Result<()> {
+        require!(!self.is_empty(), PoolError::EmptyConfigUpdate);
+        validate_fee_config(
+            self.deposit_fee_rate.unwrap_or(global_config.deposit_fee_rate),
+            self.withdrawal_fee_rate.unwrap_or(global_config.withdrawal_fee_rate),
+            self.fee_error_margin.unwrap_or(global_config.fee_error_margin),
+        )
+    }
+
+    pub fn is_ready(&self, slot: u64) -> bool {
+        slot >= self.effective_slot
+    }
+}
+
+pub fn effective_slot(queued_at: u64) -> Result<u64> {
+    Ok(queued_at
+        .checked_add(CONFIG_UPDATE_DELAY_SLOTS)
+        .ok_or(ErrorCode::ArithmeticOverflow)?)
+}
+
+#[derive(Accounts)]
+pub struct QueueConfigUpdate<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + PendingConfigUpdate::INIT_SPACE,
+        seeds = [b"pending_config"],
+        bump
+    )]
+    pub pending_config: Account<'info, PendingConfigUpdate>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
+
+/// Anyone can execute a ready update; the rent goes back to the authority.
+#[derive(Accounts)]
+pub struct ExecuteConfigUpdate<'info> {
+    #[account(
+        mut,
+        seeds = [b"pending_config"],
+        bump = pending_config.bump,
+        close = authority
+    )]
+    pub pending_config: Account<'info, PendingConfigUpdate>,
+    #[account(
+        mut,
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(
+        mut,
+        seeds = [b"merkle_tree"],
+        bump = tree_account.load()?.bump
+    )]
+    pub tree_account: AccountLoader<'info, MerkleTreeAccount>,
+    /// CHECK: rent recipient only, pinned to the config authority by `has_one`
+    #[account(mut)]
+    pub authority: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
+
+#[derive(Accounts)]
+pub struct CancelConfigUpdate<'info> {
+    #[account(
+        mut,
+        seeds = [b"pending_config"],
+        bump = pending_config.bump,
+        close = authority
+    )]
+    pub pending_config: Account<'info, PendingConfigUpdate>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
@@ -0,0 +1,260 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::{self, Token, TokenAccount};
+use crate::errors::PoolError;
+use crate::utils::{check_public_amount_u128, FeeRates, SOL_ADDRESS, SOL_DECIMALS};
+use crate::{AuditLog, ErrorCode, FeeSchedule, GlobalConfig, MerkleTreeAccount, PoolRegistry, MERKLE_TREE_ACCOUNT_LEN};
+
+/// Lowest `creation_fee` the factory accepts, 0.1 SOL. Pool creation is
+/// permissionless, so this is what filling the registry with throwaway mints
+/// costs.
+pub const MIN_POOL_CREATION_FEE: u64 = 100_000_000;
+
+/// A new token pool's per-transaction deposit cap, in whole tokens: the
+/// same 1000 the SOL pool starts with.
+pub const DEFAULT_MAX_DEPOSIT_TOKENS: u64 = 1_000;
+
+/// SPL `Mint::decimals`.
+const MINT_DECIMALS_OFFSET: usize = 44;
+
+/// Sets what it costs to open a token pool and where that fee goes. The
+/// global authority keeps only this and pausing through `set_pool_status`;
+/// token pools themselves have no admin.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct PoolFactory {
+    pub treasury: Pubkey,
+    /// Lamports paid to `treasury` by whoever creates a pool, at least
+    /// `MIN_POOL_CREATION_FEE`.
+    pub creation_fee: u64,
+    pub bump: u8,
+}
+
+/// One shielded pool for an SPL mint. Its tree is at `["merkle_tree", mint]`
+/// and its tokens sit in `vault`, a token account owned by this PDA.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct TokenPool {
+    pub mint: Pubkey,
+    pub tree_account: Pubkey,
+    pub vault: Pubkey,
+    pub creator: Pubkey,
+    /// The mint's decimals, read when the pool is created.
+    pub decimals: u8,
+    pub bump: u8,
+    pub vault_bump: u8,
+}
+
+#[event]
+pub struct TokenPoolCreated {
+    pub mint: Pubkey,
+    pub tree_account: Pubkey,
+    pub vault: Pubkey,
+    pub creator: Pubkey,
+    pub creation_fee: u64,
+    /// False if the registry was full and the pool was not listed in it.
+    pub registered: bool,
+}
+
+pub fn validate_creation_fee(creation_fee: u64) -> Result<()> {
+    require!(creation_fee >= MIN_POOL_CREATION_FEE, PoolError::CreationFeeTooLow);
+    Ok(())
+}
+
+/// The decimals of an SPL mint, from its account data.
+pub fn mint_decimals(mint_data: &[u8]) -> Result<u8> {
+    Ok(*mint_data.get(MINT_DECIMALS_OFFSET).ok_or(PoolError::InvalidMintAccount)?)
+}
+
+/// The decimals of the pool for `mint`: lamports for `SOL_ADDRESS`, the
+/// recorded ones for a token pool. `token_pool` must be the mint's own.
+pub fn pool_decimals(mint: &Pubkey, token_pool: Option<&TokenPool>) -> Result<u8> {
+    if *mint == SOL_ADDRESS {
+        return Ok(SOL_DECIMALS);
+    }
+    let token_pool = token_pool.ok_or(PoolError::UnknownPoolMint)?;
+    require!(token_pool.mint == *mint, PoolError::UnknownPoolMint);
+    Ok(token_pool.decimals)
+}
+
+/// `tokens` whole tokens in the base units of a mint with `decimals`.
+pub fn base_units(tokens: u64, decimals: u8) -> Result<u128> {
+    Ok(10u128
+        .checked_pow(decimals as u32)
+        .and_then(|unit| unit.checked_mul(tokens as u128))
+        .ok_or(ErrorCode::ArithmeticOverflow)?)
+}
+
+/// `DEFAULT_MAX_DEPOSIT_TOKENS` in the mint's base units. Mints with so
+/// many decimals that it doesn't fit a u64 start uncapped.
+pub fn default_max_deposit_amount(decimals: u8) -> u64 {
+    base_units(DEFAULT_MAX_DEPOSIT_TOKENS, decimals)
+        .ok()
+        .and_then(|amount| u64::try_from(amount).ok())
+        .unwrap_or(u64::MAX)
+}
+
+impl TokenPool {
+    /// `check_public_amount` for a transaction on this pool. `transact`
+    /// calls this for token pools instead of the lamport version: amounts
+    /// are in the mint's base units and are mapped into the field as u128.
+    pub fn check_public_amount(&self, ext_amount: i64, fee: u64, public_amount_bytes: [u8; 32]) -> bool {
+        check_public_amount_u128(ext_amount as i128, fee as u128, public_amount_bytes)
+    }
+
+    /// `FeeSchedule::validate_fee` for a transaction on this pool, in the
+    /// mint's base units. The rate products are taken over u128, so
+    /// high-decimal mints can't overflow them. `fee_schedule` must be the
+    /// one at `["fee_schedule", mint]`, or the default if there is none: the
+    /// flat amounts of another mint's schedule mean nothing here.
+    /// `relayer_fee_rate` is the submitting relayer's `relayer_fee_premium`.
+    pub fn validate_fee(
+        &self,
+        fee_schedule: &FeeSchedule,
+        ext_amount: i64,
+        fee: u64,
+        rates: &FeeRates,
+        relayer_fee_rate: u16,
+    ) -> Result<()> {
+        require!(
+            fee_schedule.mint == self.mint || fee_schedule.mint == Pubkey::default(),
+            PoolError::FeeScheduleMintMismatch
+        );
+        fee_schedule.validate_fee(
+            ext_amount as i128,
+            fee as u128,
+            rates.deposit_fee_rate,
+            rates.withdrawal_fee_rate,
+            relayer_fee_rate,
+            rates.fee_error_margin,
+        )
+    }
+}
+
+#[derive(Accounts)]
+pub struct ConfigurePoolFactory<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + PoolFactory::INIT_SPACE,
+        seeds = [b"pool_factory"],
+        bump
+    )]
+    pub pool_factory: Account<'info, PoolFactory>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
+
+/// Anyone can create the pool for a mint that doesn't have one yet.
+#[derive(Accounts)]
+pub struct InitializeTokenPool<'info> {
+    #[account(
+        seeds = [b"pool_factory"],
+        bump = pool_factory.bump,
+        has_one = treasury @ ErrorCode::Unauthorized
+    )]
+    pub pool_factory: Account<'info, PoolFactory>,
+    /// CHECK: fee recipient only, pinned to the factory's treasury by `has_one`
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+    /// CHECK: owned by the token program; `initialize_account3` rejects it
+    /// unless it is an initialized mint
+    #[account(owner = token::ID)]
+    pub mint: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = MERKLE_TREE_ACCOUNT_LEN,
+        seeds = [b"merkle_tree", mint.key().as_ref()],
+        bump
+    )]
+    pub tree_account: AccountLoader<'info, MerkleTreeAccount>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + TokenPool::INIT_SPACE,
+        seeds = [b"token_pool", mint.key().as_ref()],
+        bump
+    )]
+    pub token_pool: Account<'info, TokenPool>,
+    /// CHECK: created and initialized as a token account by `create_vault`
+    #[account(
+        mut,
+        seeds = [b"tree_token", mint.key().as_ref()],
+        bump
+    )]
+    pub vault: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"pool_registry"],
+        bump = pool_registry.bump
+    )]
+    pub pool_registry: Account<'info, PoolRegistry>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeTokenPool<'info> {
+    /// Creates the vault by hand rather than with `token::` constraints, which
+    /// would pull in token-2022 and with it a solana-program that conflicts
+    /// with light-hasher's. For the same reason the mint is taken unchecked.
+    pub fn create_vault(&self, vault_bump: u8) -> Result<()> {
+        let mint = self.mint.key();
+        let seeds: &[&[u8]] = &[b"tree_token", mint.as_ref(), &[vault_bump]];
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                self.system_program.to_account_info(),
+                system_program::CreateAccount {
+                    from: self.payer.to_account_info(),
+                    to: self.vault.to_account_info(),
+                },
+                &[seeds],
+            ),
+            Rent::get()?.minimum_balance(TokenAccount::LEN),
+            TokenAccount::LEN as u64,
+            &token::ID,
+        )?;
+        token::initialize_account3(CpiContext::new(
+            self.token_program.to_account_info(),
+            token::InitializeAccount3 {
+                account: self.vault.to_account_info(),
+                mint: self.mint.to_account_info(),
+                authority: self.token_pool.to_account_info(),
+            },
+        ))
+    }
+
+    pub fn pay_creation_fee(&self) -> Result<()> {
+        let fee = self.pool_factory.creation_fee;
+        system_program::transfer(
+            CpiContext::new(
+                self.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: self.payer.to_account_info(),
+                    to: self.treasury.to_account_info(),
+                },
+            ),
+            fee,
+        )
+    }
+}
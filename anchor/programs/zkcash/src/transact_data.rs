@@ -0,0 +1,184 @@
+//! Zero-copy layout of the transact instruction data.
+//!
+//! The Borsh arguments of `transact` are decoded into an owned `Proof` and
+//! two `Vec<u8>` encrypted outputs before the handler runs, which for the
+//! largest instruction in the program costs CU and most of the heap. This
+//! layout lets the proof and every variable-length field be read in place
+//! from the instruction data instead:
+//!
+//! ```text
+//! proof              PROOF_LEN bytes, identical to the Borsh `Proof`
+//! header             EXT_DATA_HEADER_LEN bytes, see `ExtDataHeader`
+//! encrypted_output1  header.encrypted_output1_len bytes
+//! encrypted_output2  header.encrypted_output2_len bytes
+//! memo               header.memo_len bytes
+//! options            header.options_len bytes, Borsh `TransactOptions`
+//! ```
+//!
+//! Integers are little-endian, as in Borsh. Every field is a byte array, so
+//! the structs have alignment 1 and cast from any offset.
+
+use anchor_lang::prelude::*;
+use bytemuck::{Pod, Zeroable};
+use crate::errors::PoolError;
+use crate::utils::ExtDataFields;
+use crate::{CpiTarget, ForeignRecipient, Proof, SwapParams, PROOF_LEN};
+
+/// `Proof` as it sits in instruction data: the same bytes as its Borsh
+/// encoding, borrowed rather than copied out.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable, PartialEq, Eq)]
+pub struct ProofData {
+    pub proof_a: [u8; 64],
+    pub proof_b: [u8; 128],
+    pub proof_c: [u8; 64],
+    pub root: [u8; 32],
+    pub public_amount: [u8; 32],
+    pub ext_data_hash: [u8; 32],
+    pub input_nullifiers: [[u8; 32]; 2],
+    pub output_commitments: [[u8; 32]; 2],
+}
+
+const _: () = assert!(std::mem::size_of::<ProofData>() == PROOF_LEN);
+
+impl From<&ProofData> for Proof {
+    fn from(data: &ProofData) -> Self {
+        Proof {
+            proof_a: data.proof_a,
+            proof_b: data.proof_b,
+            proof_c: data.proof_c,
+            root: data.root,
+            public_amount: data.public_amount,
+            ext_data_hash: data.ext_data_hash,
+            input_nullifiers: data.input_nullifiers,
+            output_commitments: data.output_commitments,
+        }
+    }
+}
+
+/// The fixed-size part of ExtData, followed in the instruction data by the
+/// variable-length fields whose lengths it carries.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable, PartialEq, Eq)]
+pub struct ExtDataHeader {
+    /// ExtData layout the client hashed with, see `EXT_DATA_VERSION`.
+    pub version: u8,
+    pub recipient: [u8; 32],
+    pub ext_amount: [u8; 8],
+    pub fee: [u8; 8],
+    pub fee_recipient: [u8; 32],
+    pub mint_address: [u8; 32],
+    pub expiry_slot: [u8; 8],
+    pub max_fee: [u8; 8],
+    pub encrypted_output1_len: [u8; 2],
+    pub encrypted_output2_len: [u8; 2],
+    pub memo_len: [u8; 2],
+    pub options_len: [u8; 2],
+}
+
+pub const EXT_DATA_HEADER_LEN: usize = std::mem::size_of::<ExtDataHeader>();
+
+/// The optional ExtData fields. All of them are fixed-size, so decoding
+/// these few bytes with Borsh allocates nothing.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TransactOptions {
+    pub relayer: Option<Pubkey>,
+    pub swap: Option<SwapParams>,
+    pub cpi: Option<CpiTarget>,
+    pub foreign_recipient: Option<ForeignRecipient>,
+    pub memo_cpi: bool,
+}
+
+/// Transact instruction data read in place.
+#[derive(Clone, Copy, Debug)]
+pub struct TransactData<'a> {
+    pub proof: &'a ProofData,
+    pub version: u8,
+    pub ext_data: ExtDataFields<'a>,
+}
+
+/// Splits `len` bytes off the front of `data`.
+fn take<'a>(data: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+    require!(data.len() >= len, PoolError::InvalidTransactData);
+    let (head, tail) = data.split_at(len);
+    *data = tail;
+    Ok(head)
+}
+
+/// Reads transact instruction data, discriminator already stripped. Fails
+/// with `InvalidProofDataLength` if the proof is cut short and with
+/// `InvalidTransactData` if the rest doesn't match its header exactly.
+pub fn parse_transact_data(mut data: &[u8]) -> Result<TransactData<'_>> {
+    require!(data.len() >= PROOF_LEN, PoolError::InvalidProofDataLength);
+    let proof: &ProofData = bytemuck::from_bytes(take(&mut data, PROOF_LEN)?);
+    let header: &ExtDataHeader = bytemuck::from_bytes(take(&mut data, EXT_DATA_HEADER_LEN)?);
+
+    let encrypted_output1 = take(&mut data, u16::from_le_bytes(header.encrypted_output1_len) as usize)?;
+    let encrypted_output2 = take(&mut data, u16::from_le_bytes(header.encrypted_output2_len) as usize)?;
+    let memo = take(&mut data, u16::from_le_bytes(header.memo_len) as usize)?;
+    let mut options_bytes = take(&mut data, u16::from_le_bytes(header.options_len) as usize)?;
+    require!(data.is_empty(), PoolError::InvalidTransactData);
+
+    let options = TransactOptions::deserialize(&mut options_bytes).map_err(|_| PoolError::InvalidTransactData)?;
+    require!(options_bytes.is_empty(), PoolError::InvalidTransactData);
+
+    Ok(TransactData {
+        proof,
+        version: header.version,
+        ext_data: ExtDataFields {
+            recipient: Pubkey::new_from_array(header.recipient),
+            ext_amount: i64::from_le_bytes(header.ext_amount),
+            encrypted_output1,
+            encrypted_output2,
+            fee: u64::from_le_bytes(header.fee),
+            fee_recipient: Pubkey::new_from_array(header.fee_recipient),
+            mint_address: Pubkey::new_from_array(header.mint_address),
+            memo,
+            expiry_slot: u64::from_le_bytes(header.expiry_slot),
+            max_fee: u64::from_le_bytes(header.max_fee),
+            relayer: options.relayer,
+            swap: options.swap,
+            cpi: options.cpi,
+            foreign_recipient: options.foreign_recipient,
+            memo_cpi: options.memo_cpi,
+        },
+    })
+}
+
+/// Builds the instruction data `parse_transact_data` reads, discriminator
+/// not included. For clients and tests; the program never encodes.
+pub fn encode_transact_data(proof: &Proof, version: u8, ext_data: &ExtDataFields) -> Result<Vec<u8>> {
+    let len = |bytes: &[u8]| -> Result<[u8; 2]> {
+        Ok(u16::try_from(bytes.len()).map_err(|_| PoolError::InvalidTransactData)?.to_le_bytes())
+    };
+    let options = TransactOptions {
+        relayer: ext_data.relayer,
+        swap: ext_data.swap,
+        cpi: ext_data.cpi,
+        foreign_recipient: ext_data.foreign_recipient,
+        memo_cpi: ext_data.memo_cpi,
+    }
+    .try_to_vec()?;
+    let header = ExtDataHeader {
+        version,
+        recipient: ext_data.recipient.to_bytes(),
+        ext_amount: ext_data.ext_amount.to_le_bytes(),
+        fee: ext_data.fee.to_le_bytes(),
+        fee_recipient: ext_data.fee_recipient.to_bytes(),
+        mint_address: ext_data.mint_address.to_bytes(),
+        expiry_slot: ext_data.expiry_slot.to_le_bytes(),
+        max_fee: ext_data.max_fee.to_le_bytes(),
+        encrypted_output1_len: len(ext_data.encrypted_output1)?,
+        encrypted_output2_len: len(ext_data.encrypted_output2)?,
+        memo_len: len(ext_data.memo)?,
+        options_len: len(&options)?,
+    };
+
+    let mut data = proof.try_to_vec()?;
+    data.extend_from_slice(bytemuck::bytes_of(&header));
+    data.extend_from_slice(ext_data.encrypted_output1);
+    data.extend_from_slice(ext_data.encrypted_output2);
+    data.extend_from_slice(ext_data.memo);
+    data.extend_from_slice(&options);
+    Ok(data)
+}
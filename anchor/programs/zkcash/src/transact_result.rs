@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+use crate::ErrorCode;
+
+/// What `transact` hands back through return data, so a client can build
+/// Merkle paths for its change notes from the transaction's metadata (or a
+/// simulation) instead of re-scanning the chain for its commitment events.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TransactResult {
+    /// Leaf index of each output commitment, in output order.
+    pub leaf_indices: [u64; 2],
+    /// Tree root after both outputs were appended.
+    pub root: [u8; 32],
+}
+
+impl TransactResult {
+    /// For the two outputs appended at `first_leaf_index` and the one after.
+    pub fn new(first_leaf_index: u64, root: [u8; 32]) -> Result<Self> {
+        let second_leaf_index = first_leaf_index
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        Ok(TransactResult {
+            leaf_indices: [first_leaf_index, second_leaf_index],
+            root,
+        })
+    }
+}
@@ -0,0 +1,362 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token::{self, Token};
+use crate::errors::PoolError;
+use crate::{AuditLog, ErrorCode, GlobalConfig, TreeTokenAccount};
+
+pub const SPL_STAKE_POOL_PROGRAM_ID: Pubkey = pubkey!("SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy");
+pub const STAKE_PROGRAM_ID: Pubkey = pubkey!("Stake11111111111111111111111111111111111111");
+const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// spl-stake-pool instruction tags. The instructions are encoded by hand
+/// because the crate's solana-program conflicts with light-hasher's.
+const DEPOSIT_SOL: u8 = 14;
+const WITHDRAW_SOL: u8 = 16;
+
+/// Ceiling on `max_staked_bps`: at least half the pool's SOL stays liquid.
+pub const MAX_STAKED_BPS: u16 = 5_000;
+
+/// Lets the authority put part of the idle SOL pool into one allowlisted SPL
+/// stake pool. `staked_lamports` is what went in at cost, so the share staked
+/// is bounded against the pool's total SOL, liquid plus staked, and whatever
+/// comes back above cost is booked as yield.
+///
+/// The pool tokens are held in the associated token account of the
+/// `["yield_vault"]` PDA. Unstaking pays straight back into the tree token
+/// account. Anyone may unstake once withdrawals have pushed the staked share
+/// over `max_staked_bps`, so liquidity never depends on the authority.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct YieldConfig {
+    pub stake_pool: Pubkey,
+    /// Most of the pool's total SOL that may be staked, in basis points.
+    pub max_staked_bps: u16,
+    /// Lamports staked and not yet withdrawn, at cost.
+    pub staked_lamports: u64,
+    /// Lamports withdrawn above cost.
+    pub realized_yield: u64,
+    pub bump: u8,
+    pub vault_bump: u8,
+}
+
+#[event]
+pub struct SolStaked {
+    pub stake_pool: Pubkey,
+    pub lamports: u64,
+    pub staked_lamports: u64,
+}
+
+#[event]
+pub struct SolUnstaked {
+    pub stake_pool: Pubkey,
+    pub pool_tokens: u64,
+    pub lamports: u64,
+    pub staked_lamports: u64,
+    pub realized_yield: u64,
+}
+
+pub fn validate_yield_config(max_staked_bps: u16) -> Result<()> {
+    require!(max_staked_bps <= MAX_STAKED_BPS, PoolError::InvalidYieldConfig);
+    Ok(())
+}
+
+/// Where the pool tokens for `pool_mint` are held.
+pub fn yield_token_account(yield_vault: &Pubkey, pool_mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[yield_vault.as_ref(), token::ID.as_ref(), pool_mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+impl YieldConfig {
+    fn exceeds_limit(&self, staked: u64, liquid_lamports: u64) -> bool {
+        let total = liquid_lamports as u128 + staked as u128;
+        staked as u128 * 10000 > total * self.max_staked_bps as u128
+    }
+
+    /// Whether withdrawals have drained the liquid side enough that anyone may
+    /// unstake.
+    pub fn needs_liquidity(&self, liquid_lamports: u64) -> bool {
+        self.exceeds_limit(self.staked_lamports, liquid_lamports)
+    }
+
+    /// Books `amount` lamports moving out of the `liquid_lamports` the tree
+    /// token account holds above rent.
+    pub fn record_stake(&mut self, amount: u64, liquid_lamports: u64) -> Result<()> {
+        require!(amount > 0 && amount <= liquid_lamports, PoolError::StakeLimitExceeded);
+        let staked = self.staked_lamports
+            .checked_add(amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        require!(
+            !self.exceeds_limit(staked, liquid_lamports - amount),
+            PoolError::StakeLimitExceeded
+        );
+        self.staked_lamports = staked;
+        Ok(())
+    }
+
+    /// Books `lamports` received back from the stake pool: principal first,
+    /// anything above it is yield.
+    pub fn record_unstake(&mut self, lamports: u64) -> Result<()> {
+        let principal = lamports.min(self.staked_lamports);
+        self.staked_lamports -= principal;
+        self.realized_yield = self.realized_yield
+            .checked_add(lamports - principal)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
+
+/// Lamports the tree token account holds above its rent-exempt minimum.
+pub fn liquid_lamports(tree_token_account: &AccountInfo) -> Result<u64> {
+    let rent = Rent::get()?.minimum_balance(tree_token_account.data_len());
+    Ok(tree_token_account.lamports().saturating_sub(rent))
+}
+
+#[derive(Accounts)]
+pub struct ConfigureYield<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + YieldConfig::INIT_SPACE,
+        seeds = [b"yield_config"],
+        bump
+    )]
+    pub yield_config: Account<'info, YieldConfig>,
+    /// CHECK: stored only; deposits are validated against it by the stake
+    /// pool program
+    #[account(owner = SPL_STAKE_POOL_PROGRAM_ID)]
+    pub stake_pool: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
+
+/// The stake pool's own accounts are passed unchecked: the stake pool program
+/// validates each of them against `stake_pool`, which is pinned here.
+#[derive(Accounts)]
+pub struct StakeIdleSol<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_config"],
+        bump = yield_config.bump,
+        has_one = stake_pool
+    )]
+    pub yield_config: Account<'info, YieldConfig>,
+    #[account(
+        mut,
+        seeds = [b"tree_token"],
+        bump = tree_token_account.bump
+    )]
+    pub tree_token_account: Account<'info, TreeTokenAccount>,
+    /// CHECK: system-owned PDA that funds the deposit and owns the pool tokens
+    #[account(
+        mut,
+        seeds = [b"yield_vault"],
+        bump = yield_config.vault_bump
+    )]
+    pub yield_vault: UncheckedAccount<'info>,
+    /// CHECK: pinned by `has_one`
+    #[account(mut)]
+    pub stake_pool: UncheckedAccount<'info>,
+    /// CHECK: validated by the stake pool program
+    pub stake_pool_withdraw_authority: UncheckedAccount<'info>,
+    /// CHECK: validated by the stake pool program
+    #[account(mut)]
+    pub reserve_stake: UncheckedAccount<'info>,
+    /// CHECK: must be the vault's associated token account, so the pool
+    /// tokens can't be minted to anyone else
+    #[account(
+        mut,
+        address = yield_token_account(&yield_vault.key(), &pool_mint.key()) @ PoolError::InvalidYieldTokenAccount
+    )]
+    pub pool_token_account: UncheckedAccount<'info>,
+    /// CHECK: validated by the stake pool program
+    #[account(mut)]
+    pub manager_fee_account: UncheckedAccount<'info>,
+    /// CHECK: validated by the stake pool program
+    #[account(mut)]
+    pub pool_mint: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    pub authority: Signer<'info>,
+    /// CHECK: address checked
+    #[account(address = SPL_STAKE_POOL_PROGRAM_ID)]
+    pub stake_pool_program: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> StakeIdleSol<'info> {
+    /// Moves `lamports` to the yield vault and deposits them with `DepositSol`.
+    /// The vault is also the referrer, so any referral fee comes back to us.
+    pub fn deposit_sol(&self, lamports: u64) -> Result<()> {
+        self.tree_token_account.sub_lamports(lamports)?;
+        self.yield_vault.add_lamports(lamports)?;
+
+        let mut data = vec![DEPOSIT_SOL];
+        data.extend_from_slice(&lamports.to_le_bytes());
+        let instruction = Instruction {
+            program_id: SPL_STAKE_POOL_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(self.stake_pool.key(), false),
+                AccountMeta::new_readonly(self.stake_pool_withdraw_authority.key(), false),
+                AccountMeta::new(self.reserve_stake.key(), false),
+                AccountMeta::new(self.yield_vault.key(), true),
+                AccountMeta::new(self.pool_token_account.key(), false),
+                AccountMeta::new(self.manager_fee_account.key(), false),
+                AccountMeta::new(self.pool_token_account.key(), false),
+                AccountMeta::new(self.pool_mint.key(), false),
+                AccountMeta::new_readonly(self.system_program.key(), false),
+                AccountMeta::new_readonly(self.token_program.key(), false),
+            ],
+            data,
+        };
+        invoke_signed(
+            &instruction,
+            &[
+                self.stake_pool.to_account_info(),
+                self.stake_pool_withdraw_authority.to_account_info(),
+                self.reserve_stake.to_account_info(),
+                self.yield_vault.to_account_info(),
+                self.pool_token_account.to_account_info(),
+                self.manager_fee_account.to_account_info(),
+                self.pool_mint.to_account_info(),
+                self.system_program.to_account_info(),
+                self.token_program.to_account_info(),
+            ],
+            &[&[b"yield_vault", &[self.yield_config.vault_bump]]],
+        )?;
+        Ok(())
+    }
+}
+
+/// Signed by the authority, or by anyone while `needs_liquidity` holds.
+#[derive(Accounts)]
+pub struct UnstakeSol<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_config"],
+        bump = yield_config.bump,
+        has_one = stake_pool
+    )]
+    pub yield_config: Account<'info, YieldConfig>,
+    #[account(
+        mut,
+        seeds = [b"tree_token"],
+        bump = tree_token_account.bump
+    )]
+    pub tree_token_account: Account<'info, TreeTokenAccount>,
+    /// CHECK: PDA that owns the pool tokens and signs their burn
+    #[account(
+        seeds = [b"yield_vault"],
+        bump = yield_config.vault_bump
+    )]
+    pub yield_vault: UncheckedAccount<'info>,
+    /// CHECK: pinned by `has_one`
+    #[account(mut)]
+    pub stake_pool: UncheckedAccount<'info>,
+    /// CHECK: validated by the stake pool program
+    pub stake_pool_withdraw_authority: UncheckedAccount<'info>,
+    /// CHECK: the vault's associated token account
+    #[account(
+        mut,
+        address = yield_token_account(&yield_vault.key(), &pool_mint.key()) @ PoolError::InvalidYieldTokenAccount
+    )]
+    pub pool_token_account: UncheckedAccount<'info>,
+    /// CHECK: validated by the stake pool program
+    #[account(mut)]
+    pub reserve_stake: UncheckedAccount<'info>,
+    /// CHECK: validated by the stake pool program
+    #[account(mut)]
+    pub manager_fee_account: UncheckedAccount<'info>,
+    /// CHECK: validated by the stake pool program
+    #[account(mut)]
+    pub pool_mint: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    pub caller: Signer<'info>,
+    /// CHECK: address checked
+    #[account(address = sysvar::clock::ID)]
+    pub clock: UncheckedAccount<'info>,
+    /// CHECK: address checked
+    #[account(address = sysvar::stake_history::ID)]
+    pub stake_history: UncheckedAccount<'info>,
+    /// CHECK: address checked
+    #[account(address = STAKE_PROGRAM_ID)]
+    pub stake_program: UncheckedAccount<'info>,
+    /// CHECK: address checked
+    #[account(address = SPL_STAKE_POOL_PROGRAM_ID)]
+    pub stake_pool_program: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> UnstakeSol<'info> {
+    /// Burns `pool_tokens` with `WithdrawSol`, paying the SOL into the tree
+    /// token account. Returns the lamports received.
+    pub fn withdraw_sol(&self, pool_tokens: u64) -> Result<u64> {
+        let before = self.tree_token_account.get_lamports();
+        let mut data = vec![WITHDRAW_SOL];
+        data.extend_from_slice(&pool_tokens.to_le_bytes());
+        let instruction = Instruction {
+            program_id: SPL_STAKE_POOL_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(self.stake_pool.key(), false),
+                AccountMeta::new_readonly(self.stake_pool_withdraw_authority.key(), false),
+                AccountMeta::new_readonly(self.yield_vault.key(), true),
+                AccountMeta::new(self.pool_token_account.key(), false),
+                AccountMeta::new(self.reserve_stake.key(), false),
+                AccountMeta::new(self.tree_token_account.key(), false),
+                AccountMeta::new(self.manager_fee_account.key(), false),
+                AccountMeta::new(self.pool_mint.key(), false),
+                AccountMeta::new_readonly(self.clock.key(), false),
+                AccountMeta::new_readonly(self.stake_history.key(), false),
+                AccountMeta::new_readonly(self.stake_program.key(), false),
+                AccountMeta::new_readonly(self.token_program.key(), false),
+            ],
+            data,
+        };
+        invoke_signed(
+            &instruction,
+            &[
+                self.stake_pool.to_account_info(),
+                self.stake_pool_withdraw_authority.to_account_info(),
+                self.yield_vault.to_account_info(),
+                self.pool_token_account.to_account_info(),
+                self.reserve_stake.to_account_info(),
+                self.tree_token_account.to_account_info(),
+                self.manager_fee_account.to_account_info(),
+                self.pool_mint.to_account_info(),
+                self.clock.to_account_info(),
+                self.stake_history.to_account_info(),
+                self.stake_program.to_account_info(),
+                self.token_program.to_account_info(),
+            ],
+            &[&[b"yield_vault", &[self.yield_config.vault_bump]]],
+        )?;
+        let after = self.tree_token_account.get_lamports();
+        Ok(after.saturating_sub(before))
+    }
+}
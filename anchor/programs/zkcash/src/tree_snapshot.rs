@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+use crate::errors::PoolError;
+use crate::MerkleTreeAccount;
+
+pub const TREE_SNAPSHOT_VERSION: u8 = 1;
+
+/// Snapshot bytes per page. With the page header this stays under the
+/// runtime's 1024-byte return data limit.
+pub const TREE_SNAPSHOT_PAGE_BYTES: usize = 992;
+
+/// Everything a client needs to continue a tree locally from its current
+/// state: the filled subtrees to append to, and the root history to tell
+/// which roots the program still accepts. Borsh encoded, then split into
+/// `TreeSnapshotPage`s.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TreeSnapshot {
+    pub version: u8,
+    pub tree_account: Pubkey,
+    pub height: u8,
+    pub next_index: u64,
+    pub root: [u8; 32],
+    /// Filled subtree at each level, leaves first; `height` entries.
+    pub subtrees: Vec<[u8; 32]>,
+    /// The root history ring as stored, `root_index` being the newest.
+    pub root_history: Vec<[u8; 32]>,
+    pub root_index: u64,
+}
+
+/// One page of an encoded `TreeSnapshot`. Pages are read in separate
+/// transactions, so each carries the tree's `next_index`: a client that
+/// sees it change between pages must start over.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TreeSnapshotPage {
+    pub next_index: u64,
+    pub page: u16,
+    pub total_pages: u16,
+    pub bytes: Vec<u8>,
+}
+
+impl TreeSnapshot {
+    pub fn from_tree(tree_account: Pubkey, tree: &MerkleTreeAccount) -> Self {
+        TreeSnapshot {
+            version: TREE_SNAPSHOT_VERSION,
+            tree_account,
+            height: tree.height,
+            next_index: tree.next_index,
+            root: tree.root,
+            subtrees: tree.subtrees[..tree.height as usize].to_vec(),
+            root_history: tree.root_history[..tree.root_history_size as usize].to_vec(),
+            root_index: tree.root_index,
+        }
+    }
+
+    pub fn page(&self, page: u16) -> Result<TreeSnapshotPage> {
+        let bytes = self.try_to_vec()?;
+        let total_pages = bytes.len().div_ceil(TREE_SNAPSHOT_PAGE_BYTES);
+        require!((page as usize) < total_pages, PoolError::InvalidSnapshotPage);
+        let start = page as usize * TREE_SNAPSHOT_PAGE_BYTES;
+        let end = (start + TREE_SNAPSHOT_PAGE_BYTES).min(bytes.len());
+        Ok(TreeSnapshotPage {
+            next_index: self.next_index,
+            page,
+            total_pages: total_pages as u16,
+            bytes: bytes[start..end].to_vec(),
+        })
+    }
+}
+
+/// Read-only and permissionless; meant to be simulated, page by page.
+#[derive(Accounts)]
+pub struct ExportTreeSnapshot<'info> {
+    pub tree_account: AccountLoader<'info, MerkleTreeAccount>,
+}
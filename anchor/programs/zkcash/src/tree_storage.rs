@@ -0,0 +1,138 @@
+use anchor_lang::prelude::*;
+use crate::errors::PoolError;
+use crate::merkle_tree::layout::{ROOT_HISTORY_SLOTS, SUBTREE_SLOTS};
+use crate::merkle_tree::MerkleTree;
+use crate::{
+    holds_role, merkle_tree_account_version, AuditLog, ErrorCode, GlobalConfig, MerkleTreeAccount, Role, RoleConfig,
+    MERKLE_TREE_ACCOUNT_V2_LEN, MERKLE_TREE_ACCOUNT_VERSION_OFFSET,
+};
+
+/// Where a v3 tree account stores its `TreeStorage` address, right after
+/// the v2 layout. Like the root slots it sits outside the zero-copy struct
+/// and is read on the raw account data.
+pub const TREE_STORAGE_KEY_OFFSET: usize = MERKLE_TREE_ACCOUNT_V2_LEN;
+
+const TREE_STORAGE_VERSION: u8 = 3;
+
+/// A tree's bulk storage, at `["tree_storage", tree_account]`: the filled
+/// subtrees and the root ring buffer that otherwise fill most of
+/// `MerkleTreeAccount`. Once a tree has one, the tree account is metadata
+/// (authority, `next_index`, `root`, limits) and links to it, so the
+/// storage can be reallocated, e.g. for a longer history or a canopy,
+/// without moving the tree account's address.
+#[account(zero_copy)]
+pub struct TreeStorage {
+    pub tree_account: Pubkey,
+    /// Slot of `root_history` holding the current root.
+    pub root_index: u64,
+    pub subtrees: [[u8; 32]; SUBTREE_SLOTS],
+    pub root_history: [[u8; 32]; ROOT_HISTORY_SLOTS],
+}
+
+pub const TREE_STORAGE_ACCOUNT_LEN: usize = 8 + std::mem::size_of::<TreeStorage>();
+
+#[event]
+pub struct TreeStorageSplit {
+    pub tree_account: Pubkey,
+    pub tree_storage: Pubkey,
+}
+
+impl TreeStorage {
+    /// Takes over the tree's subtrees and root history as they are now.
+    pub fn copy_from(&mut self, tree_account: Pubkey, tree: &MerkleTreeAccount) {
+        self.tree_account = tree_account;
+        self.root_index = tree.root_index;
+        self.subtrees.copy_from_slice(&tree.subtrees);
+        self.root_history.copy_from_slice(&tree.root_history);
+    }
+
+    /// Records the root the tree just moved to in a ring of
+    /// `root_history_size` entries.
+    pub fn push_root(&mut self, root: [u8; 32], root_history_size: u64) -> Result<()> {
+        require!(
+            root_history_size > 0 && root_history_size as usize <= ROOT_HISTORY_SLOTS,
+            PoolError::InvalidAccountLayout
+        );
+        self.root_index = MerkleTree::next_root_index(self.root_index, root_history_size)?;
+        self.root_history[self.root_index as usize] = root;
+        Ok(())
+    }
+
+    pub fn is_known_root(&self, root: &[u8; 32], root_history_size: u64) -> bool {
+        *root != [0u8; 32]
+            && self.root_history[..(root_history_size as usize).min(ROOT_HISTORY_SLOTS)].contains(root)
+    }
+}
+
+/// Checked on length and version byte alone, like `has_root_slots`.
+fn has_storage_link(data: &[u8]) -> bool {
+    data.len() >= TREE_STORAGE_KEY_OFFSET + 32
+        && data[MERKLE_TREE_ACCOUNT_VERSION_OFFSET] >= TREE_STORAGE_VERSION
+}
+
+/// The tree's `TreeStorage`, or `None` if it predates v3 or was never split.
+pub fn tree_storage_key(data: &[u8]) -> Option<Pubkey> {
+    if !has_storage_link(data) {
+        return None;
+    }
+    let key = Pubkey::new_from_array(data[TREE_STORAGE_KEY_OFFSET..TREE_STORAGE_KEY_OFFSET + 32].try_into().unwrap());
+    (key != Pubkey::default()).then_some(key)
+}
+
+/// Links the tree to its storage. Fails on trees not migrated to v3 and on
+/// trees already split.
+pub fn set_tree_storage_key(data: &mut [u8], tree_storage: &Pubkey) -> Result<()> {
+    require!(merkle_tree_account_version(data)? >= TREE_STORAGE_VERSION, PoolError::InvalidAccountLayout);
+    require!(tree_storage_key(data).is_none(), PoolError::TreeStorageAlreadySplit);
+    data[TREE_STORAGE_KEY_OFFSET..TREE_STORAGE_KEY_OFFSET + 32].copy_from_slice(tree_storage.as_ref());
+    Ok(())
+}
+
+/// Fails unless `tree_storage` is the storage the tree links to, so an
+/// instruction can't be handed another tree's storage.
+pub fn validate_tree_storage(tree_data: &[u8], tree_storage: &Pubkey) -> Result<()> {
+    require!(
+        tree_storage_key(tree_data) == Some(*tree_storage),
+        PoolError::TreeStorageMismatch
+    );
+    Ok(())
+}
+
+/// Moves a tree's bulk storage out of the tree account. Gated on the tree
+/// operator because every later append on the tree has to pass the storage.
+#[derive(Accounts)]
+pub struct SplitTreeStorage<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = TREE_STORAGE_ACCOUNT_LEN,
+        seeds = [b"tree_storage", tree_account.key().as_ref()],
+        bump
+    )]
+    pub tree_storage: AccountLoader<'info, TreeStorage>,
+    #[account(mut)]
+    pub tree_account: AccountLoader<'info, MerkleTreeAccount>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(
+        seeds = [b"role_config"],
+        bump = role_config.bump
+    )]
+    pub role_config: Account<'info, RoleConfig>,
+    #[account(
+        mut,
+        constraint = holds_role(&role_config, Role::TreeOperator, &global_config.authority, &authority.key())
+            @ ErrorCode::Unauthorized
+    )]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
@@ -1,6 +1,12 @@
 use crate::Proof;
 use crate::groth16::{Groth16Verifier, Groth16Verifyingkey};
 use crate::ErrorCode;
+use crate::errors::PoolError;
+use crate::fee_schedule::{min_acceptable_fee, FeeSchedule};
+use crate::relayer::{relayer_fee_premium, RelayerAccount};
+use crate::swap::SwapParams;
+use crate::withdraw_cpi::CpiTarget;
+use crate::wormhole::{validate_foreign_recipient, ForeignRecipient};
 use ark_bn254;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
 use std::ops::Neg;
@@ -12,6 +18,22 @@ use anchor_lang::solana_program::hash::hash;
 type G1 = ark_bn254::g1::G1Affine;
 
 pub const SOL_ADDRESS: Pubkey = anchor_lang::pubkey!("11111111111111111111111111111112");
+/// Decimals of the SOL pool's amounts, which are lamports.
+pub const SOL_DECIMALS: u8 = 9;
+
+/// Logs the remaining compute units under `label` when built with the `cu-bench`
+/// feature, so program-tests can attribute CU to sections of an instruction.
+/// Compiles to nothing otherwise.
+#[inline(always)]
+pub fn log_compute_units(label: &str) {
+    #[cfg(feature = "cu-bench")]
+    {
+        msg!("cu-bench: {}", label);
+        anchor_lang::solana_program::log::sol_log_compute_units();
+    }
+    #[cfg(not(feature = "cu-bench"))]
+    let _ = label;
+}
 
 pub const VERIFYING_KEY: Groth16Verifyingkey =  Groth16Verifyingkey {
 	nr_pubinputs: 7,
@@ -94,21 +116,30 @@ pub fn check_public_amount(ext_amount: i64, fee: u64, public_amount_bytes: [u8;
         msg!("can't use i64::MIN as ext_amount"); 
         return false;
     }
+    check_public_amount_u128(ext_amount as i128, fee as u128, public_amount_bytes)
+}
+
+/**
+ * `check_public_amount` over 128-bit amounts, which token pools check through
+ * `TokenPool::check_public_amount`. Any u128 is far below the BN254 field
+ * size, so the field mapping never wraps.
+ *
+ * @param ext_amount The external amount in the mint's base units, as i128.
+ * @param fee The fee (non-negative) in the mint's base units, as u128.
+ * @param public_amount_bytes The public amount to verify against, as a 32-byte array (big-endian).
+ */
+pub fn check_public_amount_u128(ext_amount: i128, fee: u128, public_amount_bytes: [u8; 32]) -> bool {
+    if ext_amount == i128::MIN {
+        msg!("can't use i128::MIN as ext_amount");
+        return false;
+    }
 
     // Convert to field elements for proper BN254 arithmetic
     let fee_fr = Fr::from(fee);
-    let ext_amount_fr = if ext_amount >= 0 {
-        Fr::from(ext_amount as u64)
-    } else {
-        let abs_ext_amount = match ext_amount.checked_neg() {
-            Some(val) => val,
-            None => return false,
-        };
-        Fr::from(abs_ext_amount as u64)
-    };
+    let ext_amount_fr = Fr::from(ext_amount.unsigned_abs());
 
     // return false if the deposit amount is barely enough to cover the fee
-    if ext_amount >= 0 && ext_amount_fr <= fee_fr {
+    if ext_amount >= 0 && ext_amount.unsigned_abs() <= fee {
         return false;
     }
 
@@ -152,119 +183,241 @@ pub fn validate_fee(
     withdrawal_fee_rate: u16,
     fee_error_margin: u16,
 ) -> Result<()> {
-    if ext_amount > 0 {
-        // Deposit: check fee against deposit rate
-        let expected_fee = (ext_amount as u128)
-            .checked_mul(deposit_fee_rate as u128)
-            .ok_or(ErrorCode::ArithmeticOverflow)?
-            .checked_div(10000)
-            .ok_or(ErrorCode::ArithmeticOverflow)? as u64;
-        
-        // Calculate minimum acceptable fee: expected_fee * (1 - fee_error_margin/10000)
-        let min_acceptable_fee = if expected_fee > 0 {
-            let error_multiplier = 10000u128.checked_sub(fee_error_margin as u128)
-                .ok_or(ErrorCode::ArithmeticOverflow)?;
-            (expected_fee as u128)
-                .checked_mul(error_multiplier)
-                .ok_or(ErrorCode::ArithmeticOverflow)?
-                .checked_div(10000)
-                .ok_or(ErrorCode::ArithmeticOverflow)? as u64
-        } else {
-            0 // If expected fee is 0, minimum is also 0
-        };
-        
-        require!(
-            provided_fee >= min_acceptable_fee,
-            ErrorCode::InvalidFeeAmount
-        );
-    } else if ext_amount < 0 {
-        // Withdrawal: check fee against withdrawal rate
-        let withdrawal_amount = ext_amount.checked_neg()
-            .ok_or(ErrorCode::ArithmeticOverflow)? as u64;
-        
-        let expected_fee = (withdrawal_amount as u128)
-            .checked_mul(withdrawal_fee_rate as u128)
-            .ok_or(ErrorCode::ArithmeticOverflow)?
-            .checked_div(10000)
-            .ok_or(ErrorCode::ArithmeticOverflow)? as u64;
-        
-        // Calculate minimum acceptable fee: expected_fee * (1 - fee_error_margin/10000)
-        let min_acceptable_fee = if expected_fee > 0 {
-            let error_multiplier = 10000u128.checked_sub(fee_error_margin as u128)
-                .ok_or(ErrorCode::ArithmeticOverflow)?;
-            (expected_fee as u128)
-                .checked_mul(error_multiplier)
-                .ok_or(ErrorCode::ArithmeticOverflow)?
-                .checked_div(10000)
-                .ok_or(ErrorCode::ArithmeticOverflow)? as u64
-        } else {
-            0 // If expected fee is 0, minimum is also 0
-        };
-        
-        require!(
-            provided_fee >= min_acceptable_fee,
-            ErrorCode::InvalidFeeAmount
-        );
+    validate_fee_u128(
+        ext_amount as i128,
+        provided_fee as u128,
+        deposit_fee_rate,
+        withdrawal_fee_rate,
+        fee_error_margin,
+    )
+}
+
+/**
+ * `validate_fee` over 128-bit amounts, in the mint's base units. Products that
+ * would overflow a u128 fail with `ArithmeticOverflow` rather than wrapping.
+ * Pools with a `FeeSchedule` validate through `FeeSchedule::validate_fee`
+ * instead; this is the same check under the default schedule.
+ */
+pub fn validate_fee_u128(
+    ext_amount: i128,
+    provided_fee: u128,
+    deposit_fee_rate: u16,
+    withdrawal_fee_rate: u16,
+    fee_error_margin: u16,
+) -> Result<()> {
+    FeeSchedule::default().validate_fee(
+        ext_amount,
+        provided_fee,
+        deposit_fee_rate,
+        withdrawal_fee_rate,
+        0,
+        fee_error_margin,
+    )
+}
+
+/// The fee settings transact checks against, as stored in `GlobalConfig`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeRates {
+    pub deposit_fee_rate: u16,
+    pub withdrawal_fee_rate: u16,
+    pub fee_error_margin: u16,
+}
+
+impl From<&crate::GlobalConfig> for FeeRates {
+    fn from(global_config: &crate::GlobalConfig) -> Self {
+        FeeRates {
+            deposit_fee_rate: global_config.deposit_fee_rate,
+            withdrawal_fee_rate: global_config.withdrawal_fee_rate,
+            fee_error_margin: global_config.fee_error_margin,
+        }
     }
-    // For ext_amount == 0, no fee validation needed
-    
+}
+
+/// The fees transact accepts for one `ext_amount`: anything from `minimum`
+/// up. `expected` is what a client should pay; the margin below it absorbs
+/// rate changes between building a transaction and it landing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeBounds {
+    pub minimum: u64,
+    pub expected: u64,
+}
+
+/// The fee transact expects for `ext_amount`, with the program's own
+/// rounding: `FeeSchedule::expected_fee` under the pool's `fee_schedule`,
+/// from `["fee_schedule", mint]` or the default if there is none, plus the
+/// premium of the submitting `relayer`, if registered. Zero when `exempt`,
+/// which should be `FeeExemptions::waives_fee` for the withdrawal. Usable
+/// off-chain, so clients compute the same number instead of redoing the
+/// basis-point math.
+pub fn expected_fee(
+    ext_amount: i64,
+    fee_schedule: &FeeSchedule,
+    rates: &FeeRates,
+    relayer: Option<&RelayerAccount>,
+    exempt: bool,
+) -> Result<u64> {
+    if exempt {
+        return Ok(0);
+    }
+    let expected_fee = fee_schedule.expected_fee(
+        ext_amount as i128,
+        rates.deposit_fee_rate,
+        rates.withdrawal_fee_rate,
+        relayer_fee_premium(relayer, rates.withdrawal_fee_rate),
+    )?;
+    Ok(u64::try_from(expected_fee).map_err(|_| ErrorCode::ArithmeticOverflow)?)
+}
+
+/// The range of fees transact accepts for `ext_amount`, under the same
+/// schedule, relayer and exemption as `expected_fee`.
+pub fn fee_bounds(
+    ext_amount: i64,
+    fee_schedule: &FeeSchedule,
+    rates: &FeeRates,
+    relayer: Option<&RelayerAccount>,
+    exempt: bool,
+) -> Result<FeeBounds> {
+    let expected = expected_fee(ext_amount, fee_schedule, rates, relayer, exempt)?;
+    let minimum = min_acceptable_fee(expected as u128, rates.fee_error_margin)?;
+    Ok(FeeBounds {
+        // Never above `expected`, so it fits
+        minimum: minimum as u64,
+        expected,
+    })
+}
+
+/// Highest `deposit_fee_rate` or `withdrawal_fee_rate` the program accepts, in basis points.
+pub const MAX_FEE_RATE: u16 = 500;
+/// Highest `fee_error_margin` the program accepts, in basis points.
+pub const MAX_FEE_ERROR_MARGIN: u16 = 10000;
+
+/**
+ * Rejects fee settings above the compile-time maximums, regardless of who signs the update.
+ * Must be checked wherever GlobalConfig fees are written: initialize and update_global_config.
+ */
+pub fn validate_fee_config(
+    deposit_fee_rate: u16,
+    withdrawal_fee_rate: u16,
+    fee_error_margin: u16,
+) -> Result<()> {
+    require!(deposit_fee_rate <= MAX_FEE_RATE, PoolError::DepositFeeRateTooHigh);
+    require!(withdrawal_fee_rate <= MAX_FEE_RATE, PoolError::WithdrawalFeeRateTooHigh);
+    require!(fee_error_margin <= MAX_FEE_ERROR_MARGIN, PoolError::FeeErrorMarginTooHigh);
     Ok(())
 }
 
-pub fn verify_proof(proof: Proof, verifying_key: Groth16Verifyingkey) -> bool {
-    let mut public_inputs_vec: [[u8; 32]; 7] = [[0u8; 32]; 7];
-
-    public_inputs_vec[0] = proof.root;
-    public_inputs_vec[1] = proof.public_amount;
-    public_inputs_vec[2] = proof.ext_data_hash;
-    public_inputs_vec[3] = proof.input_nullifiers[0];
-    public_inputs_vec[4] = proof.input_nullifiers[1];
-    public_inputs_vec[5] = proof.output_commitments[0];
-    public_inputs_vec[6] = proof.output_commitments[1];
-
-     // First deserialize PROOF_A into a G1 point
-     let g1_point = match G1::deserialize_with_mode(
-        &*[&change_endianness(&proof.proof_a[0..64]), &[0u8][..]].concat(),
-        Compress::No,
-        Validate::Yes,
-    ) {
-        Ok(point) => point,
-        Err(_) => return false,
-    };
-    
-    let mut proof_a_neg = [0u8; 65];
-    if g1_point
-        .neg()
+/// Verifies `proof` against `verifying_key`, failing with the `PoolError`
+/// that says why: a bad point encoding, a public input outside the field, a
+/// malformed key, or a failed pairing.
+pub fn verify_proof(proof: Proof, verifying_key: Groth16Verifyingkey) -> Result<()> {
+    verify_proof_with_inputs(&proof, &proof.public_inputs(), &verifying_key)
+}
+
+/// `verify_proof` against public inputs other than the proof's own seven,
+/// for circuits that take more, such as the versioned circuit.
+pub fn verify_proof_with_inputs<const NR_INPUTS: usize>(
+    proof: &Proof,
+    public_inputs: &[[u8; 32]; NR_INPUTS],
+    verifying_key: &Groth16Verifyingkey,
+) -> Result<()> {
+    log_compute_units("verify_proof:start");
+
+    // First deserialize PROOF_A into a G1 point, converted on the stack
+    let mut proof_a = proof.proof_a;
+    change_endianness_inplace(&mut proof_a);
+    let g1_point = G1::deserialize_with_mode(&proof_a[..], Compress::No, Validate::Yes)
+        .map_err(|_| PoolError::InvalidProofEncoding)?;
+
+    // Then write its negation back into the same buffer, big-endian again
+    let proof_a_neg = g1_point.neg();
+    proof_a_neg
         .x
-        .serialize_with_mode(&mut proof_a_neg[..32], Compress::No)
-        .is_err() {
-        return false;
-    }
-    if g1_point
-        .neg()
+        .serialize_with_mode(&mut proof_a[..32], Compress::No)
+        .map_err(|_| PoolError::InvalidProofEncoding)?;
+    proof_a_neg
         .y
-        .serialize_with_mode(&mut proof_a_neg[32..], Compress::No)
-        .is_err() {
-        return false;
-    }
-
-    let proof_a: [u8; 64] = match change_endianness(&proof_a_neg[..64]).try_into() {
-        Ok(array) => array,
-        Err(_) => return false,
-    };
+        .serialize_with_mode(&mut proof_a[32..], Compress::No)
+        .map_err(|_| PoolError::InvalidProofEncoding)?;
+    change_endianness_inplace(&mut proof_a);
 
-    let mut verifier = match Groth16Verifier::new(
+    let mut verifier = Groth16Verifier::new(
         &proof_a,
         &proof.proof_b,
         &proof.proof_c,
-        &public_inputs_vec,
-        &verifying_key
-    ) {
-        Ok(v) => v,
-        Err(_) => return false,
-    };
+        public_inputs,
+        verifying_key
+    )
+    .map_err(PoolError::from)?;
+
+    verifier.verify().map_err(PoolError::from)?;
+    log_compute_units("verify_proof:end");
+    Ok(())
+}
 
-    verifier.verify().unwrap_or(false)
+/// ExtData as it was first hashed, with no version prefix. Kept so clients
+/// that predate versioning keep producing valid proofs.
+pub const EXT_DATA_VERSION_LEGACY: u8 = 0;
+
+/// Newest ExtData layout: a version byte, then every field including the
+/// memo, expiry slot, max fee, relayer, swap, CPI target, foreign recipient
+/// and memo CPI flag. Each later layout appends its fields to the previous
+/// one and bumps this; `calculate_versioned_ext_data_hash` serializes a field
+/// only from the version that introduced it.
+pub const EXT_DATA_VERSION: u8 = 8;
+
+/// First version whose hash commits to `expiry_slot`.
+pub const EXT_DATA_VERSION_EXPIRY: u8 = 2;
+
+/// First version whose hash commits to `max_fee`.
+pub const EXT_DATA_VERSION_MAX_FEE: u8 = 3;
+
+/// First version whose hash commits to `relayer`.
+pub const EXT_DATA_VERSION_RELAYER: u8 = 4;
+
+/// First version whose hash commits to `swap`.
+pub const EXT_DATA_VERSION_SWAP: u8 = 5;
+
+/// First version whose hash commits to `cpi`.
+pub const EXT_DATA_VERSION_CPI: u8 = 6;
+
+/// First version whose hash commits to `foreign_recipient`.
+pub const EXT_DATA_VERSION_FOREIGN_RECIPIENT: u8 = 7;
+
+/// First version whose hash commits to `memo_cpi`.
+pub const EXT_DATA_VERSION_MEMO_CPI: u8 = 8;
+
+/// The ExtData fields that go into the hash, for any version.
+#[derive(Clone, Copy, Debug)]
+pub struct ExtDataFields<'a> {
+    pub recipient: Pubkey,
+    pub ext_amount: i64,
+    pub encrypted_output1: &'a [u8],
+    pub encrypted_output2: &'a [u8],
+    pub fee: u64,
+    pub fee_recipient: Pubkey,
+    pub mint_address: Pubkey,
+    pub memo: &'a [u8],
+    /// Last slot the transaction may land in; 0 for no deadline. Only
+    /// hashed from `EXT_DATA_VERSION_EXPIRY`.
+    pub expiry_slot: u64,
+    /// Highest fee the user agreed to. Only hashed from
+    /// `EXT_DATA_VERSION_MAX_FEE`.
+    pub max_fee: u64,
+    /// The only signer allowed to submit the transaction, if any. Only hashed
+    /// from `EXT_DATA_VERSION_RELAYER`.
+    pub relayer: Option<Pubkey>,
+    /// Swap to route the withdrawal through, if any. Only hashed from
+    /// `EXT_DATA_VERSION_SWAP`.
+    pub swap: Option<SwapParams>,
+    /// Program the withdrawal exits into, if any. Only hashed from
+    /// `EXT_DATA_VERSION_CPI`.
+    pub cpi: Option<CpiTarget>,
+    /// Recipient on another chain, paid through a Wormhole message. Only
+    /// hashed from `EXT_DATA_VERSION_FOREIGN_RECIPIENT`.
+    pub foreign_recipient: Option<ForeignRecipient>,
+    /// Whether the withdrawal also CPIs the memo into the SPL Memo program.
+    /// Only hashed from `EXT_DATA_VERSION_MEMO_CPI`.
+    pub memo_cpi: bool,
 }
 
 /**
@@ -280,6 +433,62 @@ pub fn calculate_complete_ext_data_hash(
     fee_recipient: Pubkey,
     mint_address: Pubkey,
 ) -> Result<[u8; 32]> {
+    calculate_ext_data_hash_with_memo(
+        recipient,
+        ext_amount,
+        encrypted_output1,
+        encrypted_output2,
+        fee,
+        fee_recipient,
+        mint_address,
+        &[],
+    )
+}
+
+/**
+ * Same as `calculate_complete_ext_data_hash`, with the memo serialized after
+ * `mint_address`. An empty memo adds no bytes, so ExtData without a memo
+ * hashes exactly as it did before memos existed.
+ */
+#[allow(clippy::too_many_arguments)]
+pub fn calculate_ext_data_hash_with_memo(
+    recipient: Pubkey,
+    ext_amount: i64,
+    encrypted_output1: &[u8],
+    encrypted_output2: &[u8],
+    fee: u64,
+    fee_recipient: Pubkey,
+    mint_address: Pubkey,
+    memo: &[u8],
+) -> Result<[u8; 32]> {
+    calculate_versioned_ext_data_hash(
+        EXT_DATA_VERSION_LEGACY,
+        &ExtDataFields {
+            recipient,
+            ext_amount,
+            encrypted_output1,
+            encrypted_output2,
+            fee,
+            fee_recipient,
+            mint_address,
+            memo,
+            expiry_slot: 0,
+            max_fee: fee,
+            relayer: None,
+            swap: None,
+            cpi: None,
+            foreign_recipient: None,
+            memo_cpi: false,
+        },
+    )
+}
+
+/**
+ * sha256 of the ExtData serialized in the layout of `version`. The client
+ * sends the version it hashed with, so old and new clients can be served
+ * side by side while they upgrade.
+ */
+pub fn calculate_versioned_ext_data_hash(version: u8, ext_data: &ExtDataFields) -> Result<[u8; 32]> {
     #[derive(AnchorSerialize)]
     struct CompleteExtData {
         pub recipient: Pubkey,
@@ -292,28 +501,170 @@ pub fn calculate_complete_ext_data_hash(
     }
     
     let complete_ext_data = CompleteExtData {
-        recipient,
-        ext_amount,
-        encrypted_output1: encrypted_output1.to_vec(),
-        encrypted_output2: encrypted_output2.to_vec(),
-        fee,
-        fee_recipient,
-        mint_address,
+        recipient: ext_data.recipient,
+        ext_amount: ext_data.ext_amount,
+        encrypted_output1: ext_data.encrypted_output1.to_vec(),
+        encrypted_output2: ext_data.encrypted_output2.to_vec(),
+        fee: ext_data.fee,
+        fee_recipient: ext_data.fee_recipient,
+        mint_address: ext_data.mint_address,
     };
     
     let mut serialized_ext_data = Vec::new();
-    complete_ext_data.serialize(&mut serialized_ext_data)?;
+    match version {
+        EXT_DATA_VERSION_LEGACY => {
+            complete_ext_data.serialize(&mut serialized_ext_data)?;
+            if !ext_data.memo.is_empty() {
+                ext_data.memo.to_vec().serialize(&mut serialized_ext_data)?;
+            }
+        }
+        1..=EXT_DATA_VERSION => {
+            serialized_ext_data.push(version);
+            complete_ext_data.serialize(&mut serialized_ext_data)?;
+            ext_data.memo.to_vec().serialize(&mut serialized_ext_data)?;
+            if version >= EXT_DATA_VERSION_EXPIRY {
+                ext_data.expiry_slot.serialize(&mut serialized_ext_data)?;
+            }
+            if version >= EXT_DATA_VERSION_MAX_FEE {
+                ext_data.max_fee.serialize(&mut serialized_ext_data)?;
+            }
+            if version >= EXT_DATA_VERSION_RELAYER {
+                ext_data.relayer.serialize(&mut serialized_ext_data)?;
+            }
+            if version >= EXT_DATA_VERSION_SWAP {
+                ext_data.swap.serialize(&mut serialized_ext_data)?;
+            }
+            if version >= EXT_DATA_VERSION_CPI {
+                ext_data.cpi.serialize(&mut serialized_ext_data)?;
+            }
+            if version >= EXT_DATA_VERSION_FOREIGN_RECIPIENT {
+                ext_data.foreign_recipient.serialize(&mut serialized_ext_data)?;
+            }
+            if version >= EXT_DATA_VERSION_MEMO_CPI {
+                ext_data.memo_cpi.serialize(&mut serialized_ext_data)?;
+            }
+        }
+        _ => return err!(PoolError::UnsupportedExtDataVersion),
+    }
     let calculated_ext_data_hash = hash(&serialized_ext_data).to_bytes();
     
     Ok(calculated_ext_data_hash)
 }
 
+/**
+ * Rejects transactions landing after the `expiry_slot` the user committed to
+ * in ExtData, so a relayer can't hold a signed withdrawal and submit it later.
+ * An `expiry_slot` of 0 means no deadline.
+ */
+pub fn validate_expiry_slot(expiry_slot: u64, current_slot: u64) -> Result<()> {
+    require!(expiry_slot == 0 || current_slot <= expiry_slot, PoolError::ProofExpired);
+    Ok(())
+}
+
+/**
+ * Rejects a fee above the `max_fee` the user committed to in ExtData.
+ * `validate_fee` only bounds the fee from below, so this is what stops a
+ * relayer charging more than the user authorized. Applies from
+ * `EXT_DATA_VERSION_MAX_FEE`; older versions commit to no cap.
+ */
+pub fn validate_max_fee(fee: u64, max_fee: u64) -> Result<()> {
+    require!(fee <= max_fee, PoolError::FeeExceedsMaxFee);
+    Ok(())
+}
+
+/**
+ * Rejects transactions submitted by anyone but the `relayer` bound in ExtData.
+ * A relayer watching for pending transactions could otherwise resubmit the
+ * proof as its own signer; `fee_recipient` is hashed already, but the
+ * submission itself is not. `None` lets anyone submit.
+ */
+pub fn validate_relayer(relayer: Option<Pubkey>, signer: Pubkey) -> Result<()> {
+    if let Some(relayer) = relayer {
+        require!(relayer == signer, PoolError::RelayerMismatch);
+    }
+    Ok(())
+}
+
+/**
+ * A withdrawal is paid out one way: a transfer to `recipient`, a swap, a
+ * CPI, or a Wormhole message to a foreign recipient. Rejects ExtData
+ * committing to more than one, and foreign recipients Wormhole can't pay.
+ */
+pub fn validate_withdrawal_mode(ext_data: &ExtDataFields) -> Result<()> {
+    let modes = [
+        ext_data.swap.is_some(),
+        ext_data.cpi.is_some(),
+        ext_data.foreign_recipient.is_some(),
+    ];
+    require!(
+        modes.iter().filter(|set| **set).count() <= 1,
+        PoolError::ConflictingWithdrawalModes
+    );
+    if let Some(foreign_recipient) = &ext_data.foreign_recipient {
+        validate_foreign_recipient(foreign_recipient)?;
+    }
+    Ok(())
+}
+
+/**
+ * Rejects proofs whose two input nullifiers or two output commitments are
+ * equal. The circuit and the nullifier PDAs already rule both out, but each
+ * only indirectly; this fails early with a specific error should a circuit
+ * edge case or a malformed client input ever get that far.
+ */
+pub fn validate_distinct_proof_elements(proof: &Proof) -> Result<()> {
+    require!(
+        proof.input_nullifiers[0] != proof.input_nullifiers[1],
+        PoolError::DuplicateNullifier
+    );
+    require!(
+        proof.output_commitments[0] != proof.output_commitments[1],
+        PoolError::DuplicateCommitment
+    );
+    Ok(())
+}
+
+/**
+ * Withdrawals keep the standard two-output shape even when there is no
+ * change: both output commitments set and both encrypted outputs present
+ * and the same length. The client pads with a zero-amount note encrypted
+ * like any other, so a withdrawal that empties the wallet can't be told
+ * apart from one that leaves change.
+ */
+pub fn validate_withdrawal_output_shape(
+    ext_amount: i64,
+    proof: &Proof,
+    encrypted_output1: &[u8],
+    encrypted_output2: &[u8],
+) -> Result<()> {
+    if ext_amount >= 0 {
+        return Ok(());
+    }
+    require!(
+        proof.output_commitments.iter().all(|commitment| *commitment != [0u8; 32])
+            && !encrypted_output1.is_empty()
+            && encrypted_output1.len() == encrypted_output2.len(),
+        PoolError::DegenerateOutputShape
+    );
+    Ok(())
+}
+
+/// Reverses each 32-byte word of `bytes` in place, converting field
+/// elements between the big-endian encoding the alt_bn128 syscalls take and
+/// the little-endian one arkworks reads. A trailing partial word is reversed
+/// on its own, as in `change_endianness`.
+pub fn change_endianness_inplace<const N: usize>(bytes: &mut [u8; N]) {
+    for word in bytes.chunks_mut(32) {
+        word.reverse();
+    }
+}
+
+/// Allocating form of `change_endianness_inplace`, for callers holding a
+/// slice of unknown length. The verifier path uses the in-place form.
 pub fn change_endianness(bytes: &[u8]) -> Vec<u8> {
-    let mut vec = Vec::new();
-    for b in bytes.chunks(32) {
-        for byte in b.iter().rev() {
-            vec.push(*byte);
-        }
+    let mut vec = Vec::with_capacity(bytes.len());
+    for word in bytes.chunks(32) {
+        vec.extend(word.iter().rev());
     }
     vec
 }
@@ -0,0 +1,139 @@
+//! Kani proof harnesses for the arithmetic the pool's soundness rests on:
+//! where `append` writes a leaf and the next root, how the root history
+//! wraps, and that fee and public amount checks reject rather than overflow.
+//! Built only by `cargo kani --features verification`.
+//!
+//! The harnesses check the helpers the program calls, not copies of them, so
+//! a change that breaks a property fails here. Hashing is left out: the
+//! properties are about indices and amounts, and Poseidon is out of reach
+//! of a bit-precise model checker anyway.
+
+use crate::fee_schedule::FeeSchedule;
+use crate::merkle_tree::layout::ROOT_HISTORY_SLOTS;
+use crate::merkle_tree::MerkleTree;
+use crate::utils::{check_public_amount, validate_fee, validate_fee_u128};
+use crate::zero_hashes::MAX_HEIGHT;
+
+/// Ring size for the root history scan, small enough to unwind fully.
+const HISTORY_RING: usize = 4;
+
+#[kani::proof]
+fn leaf_capacity_is_two_to_the_height() {
+    let height: usize = kani::any();
+    match MerkleTree::leaf_capacity(height) {
+        Ok(capacity) => {
+            assert!(height < 64);
+            assert_eq!(capacity, 1u64 << height);
+        }
+        Err(_) => assert!(height >= 64),
+    }
+}
+
+/// Every index `append` accepts has a path of exactly `height` levels, and
+/// incrementing it leaves the tree at most full.
+#[kani::proof]
+fn append_index_stays_in_tree() {
+    let height: usize = kani::any();
+    kani::assume(height <= MAX_HEIGHT);
+    let capacity = MerkleTree::leaf_capacity(height).unwrap();
+    let next_index: u64 = kani::any();
+    kani::assume(next_index < capacity);
+
+    assert!(next_index.checked_add(1).is_some_and(|after| after <= capacity));
+    // `hash_up` halves the index once per level and must end at the root
+    assert_eq!(next_index >> height, 0);
+}
+
+#[kani::proof]
+fn next_root_index_wraps_within_history() {
+    let root_history_size: u64 = kani::any();
+    let root_index: u64 = kani::any();
+    kani::assume(root_history_size > 0 && root_history_size <= ROOT_HISTORY_SLOTS as u64);
+    kani::assume(root_index < root_history_size);
+
+    let next = MerkleTree::next_root_index(root_index, root_history_size).unwrap();
+    assert!(next < root_history_size);
+    if root_index == root_history_size - 1 {
+        assert_eq!(next, 0);
+    } else {
+        assert_eq!(next, root_index + 1);
+    }
+}
+
+#[kani::proof]
+fn next_root_index_rejects_empty_history() {
+    assert!(MerkleTree::next_root_index(kani::any(), 0).is_err());
+}
+
+/// Wherever the ring's head is, `is_known_root` sees exactly the live
+/// entries: a root counts if and only if it is in the first
+/// `root_history_size` slots and isn't zero.
+#[kani::proof]
+#[kani::unwind(33)]
+fn history_scan_visits_every_live_root() {
+    let root_history: [[u8; 32]; HISTORY_RING] = kani::any();
+    let root_history_size: usize = kani::any();
+    let root_index: usize = kani::any();
+    kani::assume(root_history_size > 0 && root_history_size <= HISTORY_RING);
+    kani::assume(root_index < root_history_size);
+    let root: [u8; 32] = kani::any();
+
+    assert_eq!(
+        MerkleTree::history_contains(&root_history, root_history_size, root_index, root),
+        root != [0u8; 32] && root_history[..root_history_size].contains(&root)
+    );
+}
+
+/// Any amount, fee and rates: `validate_fee` returns, failing on overflow
+/// instead of wrapping into a fee it would accept.
+#[kani::proof]
+fn validate_fee_never_panics() {
+    let _ = validate_fee(kani::any(), kani::any(), kani::any(), kani::any(), kani::any());
+}
+
+/// With a margin of at most 100%, the fee the schedule expects is always
+/// accepted, so an honest client can't be locked out by rounding.
+#[kani::proof]
+fn expected_fee_is_accepted() {
+    let ext_amount: i64 = kani::any();
+    let deposit_fee_rate: u16 = kani::any();
+    let withdrawal_fee_rate: u16 = kani::any();
+    let fee_error_margin: u16 = kani::any();
+    kani::assume(fee_error_margin <= 10000);
+
+    let schedule = FeeSchedule::default();
+    if let Ok(expected_fee) = schedule.expected_fee(ext_amount as i128, deposit_fee_rate, withdrawal_fee_rate, 0) {
+        assert!(validate_fee_u128(
+            ext_amount as i128,
+            expected_fee,
+            deposit_fee_rate,
+            withdrawal_fee_rate,
+            fee_error_margin,
+        )
+        .is_ok());
+    }
+}
+
+/// `i64::MIN` has no absolute value in an i64, so it is refused outright.
+#[kani::proof]
+fn check_public_amount_rejects_i64_min() {
+    assert!(!check_public_amount(i64::MIN, kani::any(), kani::any()));
+}
+
+/// A deposit that doesn't cover its fee would mint a negative public amount.
+#[kani::proof]
+fn check_public_amount_rejects_deposits_not_covering_fee() {
+    let ext_amount: i64 = kani::any();
+    let fee: u64 = kani::any();
+    kani::assume(ext_amount >= 0 && ext_amount as u64 <= fee);
+
+    assert!(!check_public_amount(ext_amount, fee, kani::any()));
+}
+
+/// The field arithmetic takes any amount and fee without panicking. By far
+/// the slowest harness: the solver works through the 256-bit Montgomery
+/// multiplications symbolically.
+#[kani::proof]
+fn check_public_amount_never_panics() {
+    let _ = check_public_amount(kani::any(), kani::any(), kani::any());
+}
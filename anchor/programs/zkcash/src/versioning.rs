@@ -0,0 +1,167 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+use crate::errors::PoolError;
+use crate::{AuditLog, ErrorCode, GlobalConfig, MerkleTreeAccount, ROOT_SLOT_ENTRIES};
+
+/// Length of a `MerkleTreeAccount` as first deployed, discriminator included.
+/// These accounts carry no version marker and are version 0. Frozen: it must
+/// not follow `size_of::<MerkleTreeAccount>()` if that struct ever grows.
+pub const MERKLE_TREE_ACCOUNT_V0_LEN: usize = 4136;
+
+/// Offset of the version byte, the first byte after the v0 layout.
+pub const MERKLE_TREE_ACCOUNT_VERSION_OFFSET: usize = MERKLE_TREE_ACCOUNT_V0_LEN;
+
+/// v1 appends 8 bytes: the version byte and padding to keep the account
+/// 8-byte aligned for zero-copy.
+pub const MERKLE_TREE_ACCOUNT_V1_LEN: usize = MERKLE_TREE_ACCOUNT_V0_LEN + 8;
+
+/// v2 appends the slot each root in the history was created at, see
+/// `root_slots`.
+pub const MERKLE_TREE_ACCOUNT_V2_LEN: usize = MERKLE_TREE_ACCOUNT_V1_LEN + 8 * ROOT_SLOT_ENTRIES;
+
+/// v3 appends the address of the tree's `TreeStorage`, see `tree_storage`.
+pub const MERKLE_TREE_ACCOUNT_V3_LEN: usize = MERKLE_TREE_ACCOUNT_V2_LEN + 32;
+
+pub const MERKLE_TREE_ACCOUNT_VERSION: u8 = 3;
+
+/// Length of a tree account at `MERKLE_TREE_ACCOUNT_VERSION`, for trees that
+/// are created already current instead of migrated.
+pub const MERKLE_TREE_ACCOUNT_LEN: usize = MERKLE_TREE_ACCOUNT_V3_LEN;
+
+/// One layout upgrade. `migrate_account` reallocs the account to `new_len`
+/// (zero-filled), then `map_fields` moves or fills in fields on the resized
+/// data. Steps only ever append, so the v0 prefix that `AccountLoader` reads
+/// stays where it is.
+pub struct Migration {
+    pub from_version: u8,
+    pub new_len: usize,
+    pub map_fields: fn(&mut [u8]),
+}
+
+/// Upgrades in order; a new layout adds one step here and bumps
+/// `MERKLE_TREE_ACCOUNT_VERSION`.
+pub const MERKLE_TREE_MIGRATIONS: &[Migration] = &[
+    Migration {
+        from_version: 0,
+        new_len: MERKLE_TREE_ACCOUNT_V1_LEN,
+        // v1 only adds the version byte
+        map_fields: |_| {},
+    },
+    Migration {
+        from_version: 1,
+        new_len: MERKLE_TREE_ACCOUNT_V2_LEN,
+        // Roots already in the history keep slot 0, i.e. unknown
+        map_fields: |_| {},
+    },
+    Migration {
+        from_version: 2,
+        new_len: MERKLE_TREE_ACCOUNT_V3_LEN,
+        // No storage until `split_tree_storage` runs
+        map_fields: |_| {},
+    },
+];
+
+impl Migration {
+    pub fn to_version(&self) -> u8 {
+        self.from_version + 1
+    }
+
+    /// Maps the fields and stamps the new version. `data` must already be
+    /// `new_len` bytes.
+    pub fn apply(&self, data: &mut [u8]) -> Result<()> {
+        require!(data.len() == self.new_len, PoolError::InvalidAccountLayout);
+        (self.map_fields)(data);
+        data[MERKLE_TREE_ACCOUNT_VERSION_OFFSET] = self.to_version();
+        Ok(())
+    }
+}
+
+pub fn next_migration(version: u8) -> Option<&'static Migration> {
+    MERKLE_TREE_MIGRATIONS.iter().find(|m| m.from_version == version)
+}
+
+/// Marks a freshly created `MERKLE_TREE_ACCOUNT_LEN` account as current.
+pub fn stamp_current_version(data: &mut [u8]) -> Result<()> {
+    require!(data.len() == MERKLE_TREE_ACCOUNT_LEN, PoolError::InvalidAccountLayout);
+    data[MERKLE_TREE_ACCOUNT_VERSION_OFFSET] = MERKLE_TREE_ACCOUNT_VERSION;
+    Ok(())
+}
+
+/// Reads the layout version of raw `MerkleTreeAccount` data.
+pub fn merkle_tree_account_version(data: &[u8]) -> Result<u8> {
+    require!(
+        data.len() >= MERKLE_TREE_ACCOUNT_V0_LEN && data.starts_with(MerkleTreeAccount::DISCRIMINATOR),
+        PoolError::InvalidAccountLayout
+    );
+    if data.len() == MERKLE_TREE_ACCOUNT_V0_LEN {
+        return Ok(0);
+    }
+    let version = data[MERKLE_TREE_ACCOUNT_VERSION_OFFSET];
+    require!(
+        version > 0 && version <= MERKLE_TREE_ACCOUNT_VERSION,
+        PoolError::InvalidAccountLayout
+    );
+    Ok(version)
+}
+
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+}
+
+/// Runs every pending step on the tree account in one instruction. Each step
+/// is one realloc, so it must grow the account by at most 10 KiB.
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    /// CHECK: taken raw because an old layout may be shorter than
+    /// `MerkleTreeAccount`; the discriminator and length are checked by
+    /// `merkle_tree_account_version`
+    #[account(
+        mut,
+        seeds = [b"merkle_tree"],
+        bump,
+        owner = crate::ID
+    )]
+    pub tree_account: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    /// Pays the rent for the added bytes.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
+
+impl<'info> MigrateAccount<'info> {
+    /// Tops the tree account up to rent exemption at its current length.
+    pub fn fund_rent(&self) -> Result<()> {
+        let tree_info = self.tree_account.to_account_info();
+        let required = Rent::get()?.minimum_balance(tree_info.data_len());
+        let shortfall = required.saturating_sub(tree_info.lamports());
+        if shortfall == 0 {
+            return Ok(());
+        }
+        system_program::transfer(
+            CpiContext::new(
+                self.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: self.authority.to_account_info(),
+                    to: tree_info,
+                },
+            ),
+            shortfall,
+        )
+    }
+}
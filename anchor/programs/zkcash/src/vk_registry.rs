@@ -0,0 +1,211 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use crate::errors::PoolError;
+use crate::groth16::Groth16Verifyingkey;
+use crate::utils::VERIFYING_KEY;
+use crate::{AuditLog, ErrorCode, GlobalConfig};
+
+/// Epochs between queueing a verifying key and it becoming active, so users
+/// and auditors can check the new circuit before any proof depends on it.
+pub const VK_ROTATION_DELAY_EPOCHS: u64 = 2;
+
+/// Epochs the replaced key is still accepted after a rotation, so proofs
+/// generated just before it don't fail.
+pub const VK_GRACE_EPOCHS: u64 = 1;
+
+/// sha256 of a verifying key: `nr_pubinputs` as u64 LE, then alpha, beta,
+/// gamma, delta and each IC point in the big-endian encoding the verifier
+/// takes. Auditors recompute it from the circuit's `verification_key.json`
+/// converted the same way.
+pub fn verifying_key_hash(verifying_key: &Groth16Verifyingkey) -> [u8; 32] {
+    let nr_pubinputs = (verifying_key.nr_pubinputs as u64).to_le_bytes();
+    let mut parts: Vec<&[u8]> = vec![
+        nr_pubinputs.as_slice(),
+        verifying_key.vk_alpha_g1.as_slice(),
+        verifying_key.vk_beta_g2.as_slice(),
+        verifying_key.vk_gamme_g2.as_slice(),
+        verifying_key.vk_delta_g2.as_slice(),
+    ];
+    parts.extend(verifying_key.vk_ic.iter().map(|point| point.as_slice()));
+    hashv(&parts).to_bytes()
+}
+
+/// Hash of the verifying key compiled into the running program.
+pub fn active_verifying_key_hash() -> [u8; 32] {
+    verifying_key_hash(&VERIFYING_KEY)
+}
+
+/// Last verifying key hash recorded on-chain, at `["verifying_key_record"]`.
+/// Kept apart from `GlobalConfig` so that account's layout doesn't change.
+/// The key ships in the program binary, so a circuit upgrade is a program
+/// upgrade followed by `record_verifying_key`, which emits the new hash.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct VerifyingKeyRecord {
+    pub vk_hash: [u8; 32],
+    pub updated_slot: u64,
+    pub bump: u8,
+}
+
+#[event]
+pub struct VerifyingKeyUpdated {
+    /// All zeroes the first time a key is recorded.
+    pub previous_hash: [u8; 32],
+    pub vk_hash: [u8; 32],
+    pub slot: u64,
+}
+
+impl VerifyingKeyRecord {
+    /// Records `vk_hash` and returns whether it changed.
+    pub fn update(&mut self, vk_hash: [u8; 32], slot: u64) -> bool {
+        if self.vk_hash == vk_hash {
+            return false;
+        }
+        emit!(VerifyingKeyUpdated {
+            previous_hash: self.vk_hash,
+            vk_hash,
+            slot,
+        });
+        self.vk_hash = vk_hash;
+        self.updated_slot = slot;
+        true
+    }
+}
+
+/// Fails unless `claimed_hash` is the hash of the key the program verifies
+/// proofs with.
+pub fn validate_verifying_key_hash(claimed_hash: &[u8; 32]) -> Result<()> {
+    require!(
+        *claimed_hash == active_verifying_key_hash(),
+        PoolError::VerifyingKeyMismatch
+    );
+    Ok(())
+}
+
+/// Which verifying keys proofs may use, by hash, at `["vk_rotation"]`. Keys
+/// are compiled in today; once they live in accounts, `transact` loads the
+/// one a proof names and checks it with `validate_proof_key`.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct VerifyingKeyRotation {
+    pub active_hash: [u8; 32],
+    pub pending_hash: Option<[u8; 32]>,
+    pub activation_epoch: u64,
+    /// The key `active_hash` replaced, accepted until `grace_end_epoch`.
+    pub previous_hash: Option<[u8; 32]>,
+    pub grace_end_epoch: u64,
+    pub bump: u8,
+}
+
+#[event]
+pub struct VerifyingKeyRotationQueued {
+    pub vk_hash: [u8; 32],
+    pub activation_epoch: u64,
+}
+
+#[event]
+pub struct VerifyingKeyRotated {
+    pub previous_hash: [u8; 32],
+    pub vk_hash: [u8; 32],
+    pub grace_end_epoch: u64,
+}
+
+impl VerifyingKeyRotation {
+    /// Queues `vk_hash` to become active `VK_ROTATION_DELAY_EPOCHS` after
+    /// `epoch`. Queueing again replaces the pending key and restarts the
+    /// delay. A fresh account starts from the compiled-in key.
+    pub fn queue(&mut self, vk_hash: [u8; 32], epoch: u64) -> Result<()> {
+        if self.active_hash == [0u8; 32] {
+            self.active_hash = active_verifying_key_hash();
+        }
+        require!(
+            vk_hash != [0u8; 32] && vk_hash != self.active_hash,
+            PoolError::InvalidVerifyingKeyRotation
+        );
+        self.pending_hash = Some(vk_hash);
+        self.activation_epoch = epoch
+            .checked_add(VK_ROTATION_DELAY_EPOCHS)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Makes the pending key active and opens the grace window for the one
+    /// it replaces.
+    pub fn activate(&mut self, epoch: u64) -> Result<()> {
+        let vk_hash = self.pending_hash.ok_or(PoolError::InvalidVerifyingKeyRotation)?;
+        require!(epoch >= self.activation_epoch, PoolError::VerifyingKeyRotationNotReady);
+        self.previous_hash = Some(self.active_hash);
+        self.active_hash = vk_hash;
+        self.pending_hash = None;
+        self.grace_end_epoch = epoch.checked_add(VK_GRACE_EPOCHS).ok_or(ErrorCode::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    pub fn accepts(&self, vk_hash: &[u8; 32], epoch: u64) -> bool {
+        *vk_hash == self.active_hash || (self.previous_hash == Some(*vk_hash) && epoch < self.grace_end_epoch)
+    }
+
+    pub fn validate_proof_key(&self, vk_hash: &[u8; 32], epoch: u64) -> Result<()> {
+        require!(self.accepts(vk_hash, epoch), PoolError::VerifyingKeyNotAccepted);
+        Ok(())
+    }
+}
+
+/// Permissionless: the recorded hash can only ever be the compiled-in key's,
+/// so anyone may sync it after an upgrade.
+#[derive(Accounts)]
+pub struct RecordVerifyingKey<'info> {
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + VerifyingKeyRecord::INIT_SPACE,
+        seeds = [b"verifying_key_record"],
+        bump
+    )]
+    pub verifying_key_record: Account<'info, VerifyingKeyRecord>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Reads nothing but the program itself, so it takes no accounts.
+#[derive(Accounts)]
+pub struct AssertVerifyingKey {}
+
+#[derive(Accounts)]
+pub struct QueueVerifyingKeyRotation<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + VerifyingKeyRotation::INIT_SPACE,
+        seeds = [b"vk_rotation"],
+        bump
+    )]
+    pub vk_rotation: Account<'info, VerifyingKeyRotation>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
+
+/// Anyone can activate a key once its delay has passed.
+#[derive(Accounts)]
+pub struct ActivateVerifyingKeyRotation<'info> {
+    #[account(
+        mut,
+        seeds = [b"vk_rotation"],
+        bump = vk_rotation.bump
+    )]
+    pub vk_rotation: Account<'info, VerifyingKeyRotation>,
+}
@@ -0,0 +1,111 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::program::invoke_signed;
+use crate::errors::PoolError;
+use crate::swap::pda_signed_instruction;
+use crate::{AuditLog, ErrorCode, GlobalConfig};
+
+/// Destination programs the allowlist can hold.
+pub const MAX_CPI_PROGRAMS: usize = 16;
+
+/// A withdrawal that exits into a CPI rather than a transfer, e.g. a stake
+/// delegation, lending deposit or vault deposit, committed to in ExtData from
+/// `EXT_DATA_VERSION_CPI`. Only the program and instruction data are
+/// committed: the accounts come from the transaction, so the destination
+/// instruction must itself name where the funds end up, as a deposit with
+/// an explicit owner does.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CpiTarget {
+    pub program: Pubkey,
+    /// sha256 of the instruction data.
+    pub data_hash: [u8; 32],
+}
+
+/// Programs withdrawals may CPI into.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct CpiAllowlist {
+    #[max_len(MAX_CPI_PROGRAMS)]
+    pub programs: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+#[event]
+pub struct CpiAllowlistUpdated {
+    pub programs: Vec<Pubkey>,
+}
+
+#[event]
+pub struct WithdrawalCpi {
+    pub program: Pubkey,
+    pub data_hash: [u8; 32],
+    pub amount: u64,
+}
+
+/// The system and token programs are left out: a transfer withdrawal already
+/// covers them, and a raw transfer CPI would let the relayer pick the
+/// destination account.
+pub fn validate_cpi_programs(programs: &[Pubkey]) -> Result<()> {
+    require!(programs.len() <= MAX_CPI_PROGRAMS, PoolError::InvalidCpiAllowlist);
+    require!(
+        programs.iter().all(|program| {
+            *program != Pubkey::default()
+                && *program != crate::ID
+                && *program != anchor_lang::system_program::ID
+                && *program != anchor_spl::token::ID
+        }),
+        PoolError::InvalidCpiAllowlist
+    );
+    Ok(())
+}
+
+impl CpiAllowlist {
+    /// Checks `target` names an allowlisted program and that `data` is the
+    /// instruction the user committed to.
+    pub fn validate_cpi(&self, target: &CpiTarget, data: &[u8]) -> Result<()> {
+        require!(self.programs.contains(&target.program), PoolError::CpiProgramNotAllowed);
+        require!(hash(data).to_bytes() == target.data_hash, PoolError::CpiDataMismatch);
+        Ok(())
+    }
+}
+
+/// CPIs the committed instruction, signing with `signer_seeds`. The signer
+/// must be an escrow holding only this withdrawal's amount, never the pool
+/// vault, since the relayer chooses the accounts.
+pub fn execute_withdrawal_cpi<'info>(
+    target: &CpiTarget,
+    data: Vec<u8>,
+    accounts: &[AccountInfo<'info>],
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    let instruction = pda_signed_instruction(target.program, data, accounts, signer_seeds)?;
+    invoke_signed(&instruction, accounts, &[signer_seeds])?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetCpiPrograms<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + CpiAllowlist::INIT_SPACE,
+        seeds = [b"cpi_allowlist"],
+        bump
+    )]
+    pub cpi_allowlist: Account<'info, CpiAllowlist>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"audit_log"],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
@@ -0,0 +1,129 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::sysvar;
+use crate::errors::PoolError;
+use crate::{ErrorCode, TreeTokenAccount};
+
+/// Shortest delay between queueing a withdrawal and paying it out, about a
+/// minute.
+pub const MIN_WITHDRAWAL_DELAY_SLOTS: u64 = 150;
+
+/// Width of the random window added on top, about an hour. Exits queued
+/// close together get spread over it, so a payout can't be matched to the
+/// deposit it follows by timing alone.
+pub const WITHDRAWAL_DELAY_WINDOW_SLOTS: u64 = 9_000;
+
+/// `SlotHashes` starts with a u64 length, then `(slot, hash)` pairs, newest
+/// first.
+const SLOT_HASHES_NEWEST_HASH: core::ops::Range<usize> = 16..48;
+
+/// A withdrawal whose proof was verified and whose nullifiers are spent, but
+/// whose payout waits until `executable_slot`. Created by `transact` in
+/// queued mode at `["queued_withdrawal", nullifier0]`; the payout stays in
+/// the tree token account until then.
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct QueuedWithdrawal {
+    pub recipient: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    /// Who paid the rent, and gets it back on execution.
+    pub payer: Pubkey,
+    pub queued_slot: u64,
+    pub executable_slot: u64,
+    pub bump: u8,
+}
+
+#[event]
+pub struct WithdrawalQueued {
+    pub queued_withdrawal: Pubkey,
+    pub executable_slot: u64,
+}
+
+#[event]
+pub struct WithdrawalExecuted {
+    pub queued_withdrawal: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+}
+
+/// Slots to wait before paying out. Drawn from the newest slot hash at
+/// queueing time, which neither the user nor the relayer can pick, mixed
+/// with the nullifier so withdrawals queued in the same slot still differ.
+pub fn withdrawal_delay(nullifier0: &[u8; 32], slot_hash: &[u8; 32]) -> u64 {
+    let digest = hashv(&[nullifier0, slot_hash]).to_bytes();
+    let jitter = u64::from_le_bytes(digest[..8].try_into().unwrap()) % WITHDRAWAL_DELAY_WINDOW_SLOTS;
+    MIN_WITHDRAWAL_DELAY_SLOTS + jitter
+}
+
+/// The newest hash in the `SlotHashes` sysvar account.
+pub fn newest_slot_hash(slot_hashes: &AccountInfo) -> Result<[u8; 32]> {
+    require_keys_eq!(*slot_hashes.key, sysvar::slot_hashes::ID, ErrorCode::Unauthorized);
+    let data = slot_hashes.try_borrow_data()?;
+    let hash = data.get(SLOT_HASHES_NEWEST_HASH).ok_or(PoolError::InvalidSlotHashes)?;
+    Ok(hash.try_into().unwrap())
+}
+
+impl QueuedWithdrawal {
+    /// Records a verified withdrawal for later payout. `transact` calls this
+    /// instead of transferring to the recipient.
+    #[allow(clippy::too_many_arguments)]
+    pub fn queue(
+        &mut self,
+        recipient: Pubkey,
+        fee_recipient: Pubkey,
+        amount: u64,
+        fee: u64,
+        payer: Pubkey,
+        slot: u64,
+        delay: u64,
+        bump: u8,
+    ) -> Result<()> {
+        self.recipient = recipient;
+        self.fee_recipient = fee_recipient;
+        self.amount = amount;
+        self.fee = fee;
+        self.payer = payer;
+        self.queued_slot = slot;
+        self.executable_slot = slot.checked_add(delay).ok_or(ErrorCode::ArithmeticOverflow)?;
+        self.bump = bump;
+        Ok(())
+    }
+
+    pub fn validate_executable(&self, slot: u64) -> Result<()> {
+        require!(slot >= self.executable_slot, PoolError::WithdrawalNotYetExecutable);
+        Ok(())
+    }
+}
+
+/// Permissionless: anyone can crank a due withdrawal, and several fit in one
+/// transaction so due exits go out together. The record is closed back to
+/// whoever queued it.
+#[derive(Accounts)]
+pub struct ExecuteWithdrawal<'info> {
+    #[account(
+        mut,
+        close = payer,
+        has_one = recipient,
+        has_one = fee_recipient,
+        has_one = payer
+    )]
+    pub queued_withdrawal: Account<'info, QueuedWithdrawal>,
+    #[account(
+        mut,
+        seeds = [b"tree_token"],
+        bump = tree_token_account.bump
+    )]
+    pub tree_token_account: Account<'info, TreeTokenAccount>,
+    /// CHECK: pinned to the queued recipient by `has_one`
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+    /// CHECK: pinned to the queued fee recipient by `has_one`
+    #[account(mut)]
+    pub fee_recipient: UncheckedAccount<'info>,
+    /// CHECK: rent refund only, pinned by `has_one`
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+}
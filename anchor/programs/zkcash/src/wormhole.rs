@@ -0,0 +1,232 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::system_program;
+use crate::errors::PoolError;
+
+pub const WORMHOLE_PROGRAM_ID: Pubkey = pubkey!("worm2ZoG2kUd4vFXhvjh93UUH596ayRfgQ2MgjNMTth");
+
+/// Wormhole's own chain id for Solana; a foreign recipient must be elsewhere.
+pub const WORMHOLE_CHAIN_ID_SOLANA: u16 = 1;
+
+/// Core bridge `post_message` instruction tag. Encoded by hand for the same
+/// reason as the stake pool instructions: the SDK's solana-program conflicts
+/// with light-hasher's.
+const POST_MESSAGE: u8 = 1;
+
+/// `BridgeData::config.fee` in the core bridge's `["Bridge"]` account.
+const BRIDGE_FEE_OFFSET: usize = 16;
+
+/// Wait for finality before guardians sign, so a rolled back withdrawal can
+/// never be released on the other chain.
+const CONSISTENCY_LEVEL_FINALIZED: u8 = 1;
+
+/// Layout version of `WithdrawalAttestation`, for the counterpart contract.
+pub const WITHDRAWAL_ATTESTATION_VERSION: u8 = 1;
+
+/// Who gets a cross-chain withdrawal, committed to in ExtData from
+/// `EXT_DATA_VERSION_FOREIGN_RECIPIENT`. `address` is left-padded to 32
+/// bytes, as in every Wormhole payload.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ForeignRecipient {
+    pub chain_id: u16,
+    pub address: [u8; 32],
+}
+
+pub fn validate_foreign_recipient(recipient: &ForeignRecipient) -> Result<()> {
+    require!(
+        recipient.chain_id != 0
+            && recipient.chain_id != WORMHOLE_CHAIN_ID_SOLANA
+            && recipient.address != [0u8; 32],
+        PoolError::InvalidForeignRecipient
+    );
+    Ok(())
+}
+
+/// Payload of the Wormhole message posted instead of paying a Solana
+/// recipient. The withdrawn amount is locked in the `["bridge_escrow"]` PDA
+/// instead of being paid out; the counterpart contract releases the same amount on `recipient.chain_id`
+/// once guardians sign the message. Fields are big-endian, as Wormhole
+/// payloads conventionally are, so EVM contracts can decode them directly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WithdrawalAttestation {
+    pub recipient: ForeignRecipient,
+    pub mint: Pubkey,
+    pub amount: u64,
+    /// The withdrawal's input nullifiers, so the counterpart can refuse to
+    /// release the same withdrawal twice.
+    pub nullifiers: [[u8; 32]; 2],
+}
+
+impl WithdrawalAttestation {
+    pub const LEN: usize = 1 + 2 + 32 + 32 + 8 + 64;
+
+    pub fn to_payload(&self) -> Vec<u8> {
+        let mut payload = Vec::with_capacity(Self::LEN);
+        payload.push(WITHDRAWAL_ATTESTATION_VERSION);
+        payload.extend_from_slice(&self.recipient.chain_id.to_be_bytes());
+        payload.extend_from_slice(&self.recipient.address);
+        payload.extend_from_slice(self.mint.as_ref());
+        payload.extend_from_slice(&self.amount.to_be_bytes());
+        payload.extend_from_slice(&self.nullifiers[0]);
+        payload.extend_from_slice(&self.nullifiers[1]);
+        payload
+    }
+}
+
+#[event]
+pub struct CrossChainWithdrawal {
+    pub chain_id: u16,
+    pub recipient: [u8; 32],
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub message: Pubkey,
+}
+
+/// The Wormhole accounts of a cross-chain withdrawal. `message` is our PDA
+/// at `["wormhole_message", nullifier0]`, so each withdrawal posts exactly
+/// one message; `emitter` is the program's emitter at `["emitter"]`, which
+/// the counterpart contract trusts.
+#[derive(Accounts)]
+#[instruction(nullifier0: [u8; 32])]
+pub struct PostWithdrawalMessage<'info> {
+    /// CHECK: the core bridge's config, address checked
+    #[account(
+        mut,
+        seeds = [b"Bridge"],
+        bump,
+        seeds::program = WORMHOLE_PROGRAM_ID
+    )]
+    pub bridge: UncheckedAccount<'info>,
+    /// CHECK: created by the core bridge
+    #[account(
+        mut,
+        seeds = [b"wormhole_message", nullifier0.as_ref()],
+        bump
+    )]
+    pub message: UncheckedAccount<'info>,
+    /// CHECK: signing PDA only
+    #[account(
+        seeds = [b"emitter"],
+        bump
+    )]
+    pub emitter: UncheckedAccount<'info>,
+    /// CHECK: the emitter's sequence tracker, owned by the core bridge
+    #[account(
+        mut,
+        seeds = [b"Sequence", emitter.key().as_ref()],
+        bump,
+        seeds::program = WORMHOLE_PROGRAM_ID
+    )]
+    pub sequence: UncheckedAccount<'info>,
+    /// CHECK: receives the bridge fee, address checked
+    #[account(
+        mut,
+        seeds = [b"fee_collector"],
+        bump,
+        seeds::program = WORMHOLE_PROGRAM_ID
+    )]
+    pub fee_collector: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: address checked
+    #[account(address = WORMHOLE_PROGRAM_ID)]
+    pub wormhole_program: UncheckedAccount<'info>,
+    /// CHECK: address checked
+    #[account(address = sysvar::clock::ID)]
+    pub clock: UncheckedAccount<'info>,
+    /// CHECK: address checked
+    #[account(address = sysvar::rent::ID)]
+    pub rent: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> PostWithdrawalMessage<'info> {
+    /// Pays the bridge fee from `payer` and posts `attestation` as a
+    /// finalized Wormhole message.
+    pub fn post(&self, attestation: &WithdrawalAttestation, message_bump: u8, emitter_bump: u8) -> Result<()> {
+        let message_seeds: &[&[u8]] = &[b"wormhole_message", &attestation.nullifiers[0], &[message_bump]];
+        let emitter_seeds: &[&[u8]] = &[b"emitter", &[emitter_bump]];
+        post_wormhole_message(
+            [
+                self.bridge.to_account_info(),
+                self.message.to_account_info(),
+                self.emitter.to_account_info(),
+                self.sequence.to_account_info(),
+                self.payer.to_account_info(),
+                self.fee_collector.to_account_info(),
+                self.clock.to_account_info(),
+                self.rent.to_account_info(),
+                self.system_program.to_account_info(),
+            ],
+            attestation.to_payload(),
+            &[message_seeds, emitter_seeds],
+        )?;
+
+        emit!(CrossChainWithdrawal {
+            chain_id: attestation.recipient.chain_id,
+            recipient: attestation.recipient.address,
+            mint: attestation.mint,
+            amount: attestation.amount,
+            message: self.message.key(),
+        });
+        Ok(())
+    }
+}
+
+/// Pays the core bridge's fee from `payer` and CPIs `post_message` with
+/// `payload` at finalized consistency. `accounts` are in the order the core
+/// bridge takes them: bridge, message, emitter, sequence, payer,
+/// fee_collector, clock, rent, system_program; `signer_seeds` sign for the
+/// message and emitter PDAs.
+pub(crate) fn post_wormhole_message<'info>(
+    accounts: [AccountInfo<'info>; 9],
+    payload: Vec<u8>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let [bridge, message, emitter, sequence, payer, fee_collector, clock, rent, system] = &accounts;
+    let fee = {
+        let data = bridge.try_borrow_data()?;
+        let bytes = data
+            .get(BRIDGE_FEE_OFFSET..BRIDGE_FEE_OFFSET + 8)
+            .ok_or(PoolError::InvalidWormholeBridge)?;
+        u64::from_le_bytes(bytes.try_into().unwrap())
+    };
+    if fee > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: fee_collector.clone(),
+                },
+            ),
+            fee,
+        )?;
+    }
+
+    let mut data = vec![POST_MESSAGE];
+    // PostMessageData { nonce: u32, payload: Vec<u8>, consistency_level: u8 }
+    0u32.serialize(&mut data)?;
+    payload.serialize(&mut data)?;
+    data.push(CONSISTENCY_LEVEL_FINALIZED);
+
+    let instruction = Instruction {
+        program_id: WORMHOLE_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(bridge.key(), false),
+            AccountMeta::new(message.key(), true),
+            AccountMeta::new_readonly(emitter.key(), true),
+            AccountMeta::new(sequence.key(), false),
+            AccountMeta::new(payer.key(), true),
+            AccountMeta::new(fee_collector.key(), false),
+            AccountMeta::new_readonly(clock.key(), false),
+            AccountMeta::new_readonly(rent.key(), false),
+            AccountMeta::new_readonly(system.key(), false),
+        ],
+        data,
+    };
+    invoke_signed(&instruction, &accounts, signer_seeds)?;
+    Ok(())
+}
@@ -0,0 +1,47 @@
+//! Empty-subtree hashes for each hasher the Merkle tree supports, generated by
+//! `build.rs` so the tables cover every height up to `MAX_HEIGHT` rather than
+//! whatever a dependency happens to ship.
+
+use crate::errors::PoolError;
+use anchor_lang::prelude::*;
+use light_hasher::{Hasher, Keccak, Poseidon, Sha256};
+
+include!(concat!(env!("OUT_DIR"), "/zero_hashes.rs"));
+
+/// A hasher with a table of empty-subtree roots, where `ZERO_HASHES[i]` is the
+/// root of an empty tree of height `i`. Implement this to use a hasher other
+/// than the ones below with `MerkleTree`.
+pub trait ZeroHashes: Hasher {
+    const ZERO_HASHES: &'static [[u8; 32]];
+
+    /// Fails for heights the table doesn't cover.
+    fn zero_hash(level: usize) -> Result<[u8; 32]> {
+        Self::ZERO_HASHES
+            .get(level)
+            .copied()
+            .ok_or_else(|| error!(PoolError::InvalidTreeHeight))
+    }
+
+    /// The empty siblings of a tree of height `HEIGHT`, levels
+    /// `0..HEIGHT`, as a fixed-size array. Checking the height once here
+    /// lets append loops index by level without a bounds check or `Result`
+    /// per level.
+    fn zero_hashes_for_height<const HEIGHT: usize>() -> Result<&'static [[u8; 32]; HEIGHT]> {
+        Self::ZERO_HASHES
+            .get(..HEIGHT)
+            .and_then(|levels| levels.try_into().ok())
+            .ok_or_else(|| error!(PoolError::InvalidTreeHeight))
+    }
+}
+
+impl ZeroHashes for Poseidon {
+    const ZERO_HASHES: &'static [[u8; 32]] = &POSEIDON_ZERO_HASHES;
+}
+
+impl ZeroHashes for Sha256 {
+    const ZERO_HASHES: &'static [[u8; 32]] = &SHA256_ZERO_HASHES;
+}
+
+impl ZeroHashes for Keccak {
+    const ZERO_HASHES: &'static [[u8; 32]] = &KECCAK_ZERO_HASHES;
+}
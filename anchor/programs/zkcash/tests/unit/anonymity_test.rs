@@ -0,0 +1,74 @@
+use std::mem::MaybeUninit;
+use zkcash::errors::PoolError;
+use zkcash::{AnonymityStats, MerkleTreeAccount, DEPOSIT_EPOCH_WINDOW};
+
+fn tree(next_index: u64) -> MerkleTreeAccount {
+    let mut uninit: MaybeUninit<MerkleTreeAccount> = MaybeUninit::uninit();
+    unsafe {
+        let ptr = uninit.as_mut_ptr();
+        std::ptr::write_bytes(ptr, 0, 1);
+        (*ptr).height = 26;
+        (*ptr).root_history_size = 100;
+        (*ptr).next_index = next_index;
+        uninit.assume_init()
+    }
+}
+
+#[test]
+fn test_only_value_spends_count_as_spent() {
+    let mut stats = AnonymityStats::default();
+    stats.record(1_000, 5, 0).unwrap();
+    stats.record(0, 0, 0).unwrap();
+    assert_eq!(stats.spent_lower_bound, 0);
+
+    stats.record(-1_000, 5, 0).unwrap();
+    stats.record(0, 5, 0).unwrap();
+    assert_eq!(stats.spent_lower_bound, 2);
+}
+
+#[test]
+fn test_deposits_are_counted_per_epoch() {
+    let mut stats = AnonymityStats::default();
+    for epoch in [10, 10, 11, 13, 13, 13] {
+        stats.record(1_000, 0, epoch).unwrap();
+    }
+
+    assert_eq!(stats.deposits_in_window(13, 1).unwrap(), 3);
+    assert_eq!(stats.deposits_in_window(13, 3).unwrap(), 4);
+    assert_eq!(stats.deposits_in_window(13, 4).unwrap(), 6);
+    assert_eq!(stats.deposits_in_window(14, 1).unwrap(), 0);
+}
+
+#[test]
+fn test_old_epochs_are_overwritten() {
+    let mut stats = AnonymityStats::default();
+    stats.record(1_000, 0, 2).unwrap();
+    stats.record(1_000, 0, 2 + DEPOSIT_EPOCH_WINDOW as u64).unwrap();
+
+    let epoch = 2 + DEPOSIT_EPOCH_WINDOW as u64;
+    assert_eq!(stats.deposits_in_window(epoch, DEPOSIT_EPOCH_WINDOW as u8).unwrap(), 1);
+}
+
+#[test]
+fn test_window_must_fit_the_ring() {
+    let stats = AnonymityStats::default();
+    for epochs in [0, DEPOSIT_EPOCH_WINDOW as u8 + 1] {
+        let err = stats.deposits_in_window(5, epochs).unwrap_err();
+        assert_eq!(err, PoolError::InvalidMetricsWindow.into());
+    }
+}
+
+#[test]
+fn test_metrics_from_tree_and_stats() {
+    let mut stats = AnonymityStats::default();
+    stats.record(1_000, 0, 7).unwrap();
+    stats.record(-400, 0, 7).unwrap();
+
+    let metrics = stats.metrics(&tree(1 << 20), 7, 2).unwrap();
+    assert_eq!(metrics.leaf_count, 1 << 20);
+    assert_eq!(metrics.capacity, 1 << 26);
+    assert_eq!(metrics.occupancy_bps, 156);
+    assert_eq!(metrics.unspent_upper_bound, (1 << 20) - 1);
+    assert_eq!(metrics.recent_deposits, 1);
+    assert_eq!(metrics.epochs, 2);
+}
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::Pubkey;
+use zkcash::{AdminAction, AuditLog, PoolStatus, MAX_AUDIT_ENTRIES};
+
+#[test]
+fn test_entries_are_sequenced() {
+    let mut log = AuditLog::default();
+    let actor = Pubkey::new_unique();
+    let target = Pubkey::new_unique();
+    log.record(actor, AdminAction::PausePool, target, 10).unwrap();
+    log.record(actor, AdminAction::ResumePool, target, 12).unwrap();
+
+    assert_eq!(log.next_sequence, 2);
+    assert_eq!(log.entries[1].sequence, 1);
+    assert_eq!(log.entries[1].slot, 12);
+    assert_eq!(log.entries[1].action, AdminAction::ResumePool);
+    assert_eq!(log.entries[1].actor, actor);
+}
+
+#[test]
+fn test_oldest_entries_evicted_when_full() {
+    let mut log = AuditLog::default();
+    for slot in 0..MAX_AUDIT_ENTRIES as u64 + 3 {
+        log.record(Pubkey::default(), AdminAction::SetFeeRecipient, Pubkey::default(), slot).unwrap();
+    }
+    assert_eq!(log.entries.len(), MAX_AUDIT_ENTRIES);
+    assert_eq!(log.entries[0].sequence, 3);
+    assert_eq!(log.entries.last().unwrap().sequence, MAX_AUDIT_ENTRIES as u64 + 2);
+    assert_eq!(log.next_sequence, MAX_AUDIT_ENTRIES as u64 + 3);
+}
+
+#[test]
+fn test_pool_status_actions() {
+    assert_eq!(AdminAction::for_pool_status(PoolStatus::Active), AdminAction::ResumePool);
+    assert_eq!(AdminAction::for_pool_status(PoolStatus::Paused), AdminAction::PausePool);
+    assert_eq!(AdminAction::for_pool_status(PoolStatus::Retired), AdminAction::RetirePool);
+}
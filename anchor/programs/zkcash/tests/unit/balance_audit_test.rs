@@ -0,0 +1,42 @@
+use zkcash::{BalanceAudit, PoolStats};
+
+fn stats(deposited: u64, withdrawn: u64, fees: u64) -> PoolStats {
+    PoolStats {
+        total_deposited: deposited,
+        total_withdrawn: withdrawn,
+        total_fees: fees,
+        ..PoolStats::default()
+    }
+}
+
+#[test]
+fn test_balanced_pool_has_no_drift() {
+    let audit = BalanceAudit::new(&stats(1_000, 300, 10), 690, 0).unwrap();
+    assert_eq!(audit.expected_lamports, 690);
+    assert_eq!(audit.drift, 0);
+    assert!(!audit.has_deficit());
+
+    // Staked SOL still counts as the pool's
+    let audit = BalanceAudit::new(&stats(1_000, 300, 10), 490, 200).unwrap();
+    assert_eq!(audit.actual_lamports, 690);
+    assert_eq!(audit.drift, 0);
+}
+
+#[test]
+fn test_reports_surplus_and_deficit() {
+    let surplus = BalanceAudit::new(&stats(1_000, 0, 0), 1_050, 0).unwrap();
+    assert_eq!(surplus.drift, 50);
+    assert!(!surplus.has_deficit());
+
+    let deficit = BalanceAudit::new(&stats(1_000, 0, 0), 900, 0).unwrap();
+    assert_eq!(deficit.drift, -100);
+    assert!(deficit.has_deficit());
+}
+
+#[test]
+fn test_outflows_before_stats_existed() {
+    // Withdrawals of deposits made before the stats account was created
+    let audit = BalanceAudit::new(&stats(100, 500, 0), 2_000, 0).unwrap();
+    assert_eq!(audit.expected_lamports, 0);
+    assert_eq!(audit.drift, 2_400);
+}
@@ -0,0 +1,82 @@
+use bytemuck::Zeroable;
+use light_hasher::Poseidon;
+use zkcash::errors::PoolError;
+use zkcash::{canopy_node_count, fill_in_proof_from_canopy, update_canopy, ConcurrentMerkleTree};
+
+const DEPTH: usize = 4;
+const CANOPY: usize = 2;
+
+type SmallTree = ConcurrentMerkleTree<DEPTH, 8>;
+
+fn leaf(i: u64) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes[24..].copy_from_slice(&(i + 1).to_be_bytes());
+    bytes
+}
+
+/// A tree with `count` leaves, its canopy and every append's proof.
+fn tree_with_canopy(count: u64) -> (SmallTree, Vec<[u8; 32]>, Vec<[[u8; 32]; DEPTH]>) {
+    let mut tree = SmallTree::zeroed();
+    tree.initialize::<Poseidon>().unwrap();
+    let mut canopy = vec![[0u8; 32]; canopy_node_count(CANOPY)];
+    let mut proofs = Vec::new();
+    for i in 0..count {
+        proofs.push(tree.append::<Poseidon>(leaf(i)).unwrap());
+        update_canopy(&mut canopy, tree.latest_change_log());
+    }
+    (tree, canopy, proofs)
+}
+
+#[test]
+fn test_canopy_node_count() {
+    assert_eq!(canopy_node_count(1), 2);
+    assert_eq!(canopy_node_count(2), 6);
+    assert_eq!(canopy_node_count(8), 510);
+}
+
+#[test]
+fn test_truncated_proof_is_completed_from_canopy() {
+    let (tree, canopy, proofs) = tree_with_canopy(5);
+    let full = proofs[4];
+
+    let filled = fill_in_proof_from_canopy::<Poseidon, DEPTH>(&canopy, 4, &full[..DEPTH - CANOPY]).unwrap();
+    assert_eq!(filled, full);
+    assert!(tree.verify_leaf::<Poseidon>(tree.root(), leaf(4), &filled, 4));
+}
+
+#[test]
+fn test_empty_canopy_slots_read_as_zero_hashes() {
+    // With one leaf the whole right half is empty
+    let (tree, canopy, proofs) = tree_with_canopy(1);
+
+    let filled = fill_in_proof_from_canopy::<Poseidon, DEPTH>(&canopy, 0, &proofs[0][..DEPTH - CANOPY]).unwrap();
+    assert_eq!(filled, proofs[0]);
+    assert!(tree.verify_leaf::<Poseidon>(tree.root(), leaf(0), &filled, 0));
+}
+
+#[test]
+fn test_stale_lower_proof_verifies_with_canopy() {
+    let (tree, canopy, proofs) = tree_with_canopy(7);
+    // Leaf 1's upper siblings changed since its append; the canopy has them
+    let root_after_leaf_1 = {
+        let (tree, _, _) = tree_with_canopy(2);
+        tree.root()
+    };
+
+    let filled = fill_in_proof_from_canopy::<Poseidon, DEPTH>(&canopy, 1, &proofs[1][..DEPTH - CANOPY]).unwrap();
+    assert!(tree.verify_leaf::<Poseidon>(root_after_leaf_1, leaf(1), &filled, 1));
+}
+
+#[test]
+fn test_full_length_proofs_pass_through() {
+    let (_, canopy, proofs) = tree_with_canopy(3);
+    let filled = fill_in_proof_from_canopy::<Poseidon, DEPTH>(&canopy, 2, &proofs[2]).unwrap();
+    assert_eq!(filled, proofs[2]);
+}
+
+#[test]
+fn test_other_proof_lengths_rejected() {
+    let (_, canopy, proofs) = tree_with_canopy(3);
+    let err = fill_in_proof_from_canopy::<Poseidon, DEPTH>(&canopy, 2, &proofs[2][..1]).unwrap_err();
+    assert_eq!(err, PoolError::InvalidProofLength.into());
+}
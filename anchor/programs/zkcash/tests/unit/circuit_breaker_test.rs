@@ -0,0 +1,142 @@
+use anchor_lang::prelude::Pubkey;
+use zkcash::errors::PoolError;
+use zkcash::{validate_circuit_breaker_config, validate_guardian, CircuitBreaker, PoolFreeze};
+
+const POOL_BALANCE: u64 = 1_000_000_000_000;
+const WINDOW: u64 = 1_000;
+
+// 10% of the pool per 1000 slots
+fn breaker() -> CircuitBreaker {
+    CircuitBreaker {
+        max_outflow_bps: 1000,
+        window_slots: WINDOW,
+        ..CircuitBreaker::default()
+    }
+}
+
+#[test]
+fn test_outflow_below_limit_does_not_trip() {
+    let mut breaker = breaker();
+    let limit = breaker.outflow_limit(POOL_BALANCE);
+    assert_eq!(limit, 100_000_000_000);
+
+    assert!(!breaker.record_outflow(limit / 2, POOL_BALANCE, 10).unwrap());
+    assert!(!breaker.record_outflow(limit / 2, POOL_BALANCE, 20).unwrap());
+    assert!(!breaker.withdrawals_paused);
+    assert_eq!(breaker.window_outflow, limit);
+}
+
+#[test]
+fn test_crossing_the_limit_pauses_withdrawals() {
+    let mut breaker = breaker();
+    let limit = breaker.outflow_limit(POOL_BALANCE);
+
+    assert!(!breaker.record_outflow(limit - 1, POOL_BALANCE, 10).unwrap());
+    assert!(breaker.record_outflow(2, POOL_BALANCE, 11).unwrap());
+    assert!(breaker.withdrawals_paused);
+
+    // Everything after the trip is rejected until resumed
+    assert!(breaker.record_outflow(1, POOL_BALANCE, 12).is_err());
+}
+
+#[test]
+fn test_single_outflow_above_limit_is_rejected() {
+    let mut breaker = breaker();
+    let limit = breaker.outflow_limit(POOL_BALANCE);
+
+    assert!(breaker.record_outflow(limit + 1, POOL_BALANCE, 10).is_err());
+    assert!(!breaker.withdrawals_paused);
+}
+
+#[test]
+fn test_zero_outflow_is_ignored() {
+    let mut breaker = breaker();
+    assert!(!breaker.record_outflow(0, 0, 10).unwrap());
+    assert_eq!(breaker.window_outflow, 0);
+}
+
+#[test]
+fn test_previous_window_is_carried_over_proportionally() {
+    let mut breaker = breaker();
+    let limit = breaker.outflow_limit(POOL_BALANCE);
+    breaker.record_outflow(limit, POOL_BALANCE, 0).unwrap();
+
+    // A quarter into the next window, 3/4 of the previous outflow still counts
+    let slot = WINDOW + WINDOW / 4;
+    assert!(!breaker.record_outflow(limit / 4, POOL_BALANCE, slot).unwrap());
+    assert_eq!(breaker.previous_window_outflow, limit);
+    assert_eq!(breaker.window_outflow, limit / 4);
+    assert!(breaker.record_outflow(1, POOL_BALANCE, slot).unwrap());
+}
+
+#[test]
+fn test_outflow_expires_after_two_windows() {
+    let mut breaker = breaker();
+    let limit = breaker.outflow_limit(POOL_BALANCE);
+    breaker.record_outflow(limit, POOL_BALANCE, 0).unwrap();
+
+    assert!(!breaker.record_outflow(limit, POOL_BALANCE, 2 * WINDOW).unwrap());
+    assert_eq!(breaker.previous_window_outflow, 0);
+    assert_eq!(breaker.window_start, 2 * WINDOW);
+}
+
+#[test]
+fn test_limit_follows_pool_balance() {
+    let mut breaker = breaker();
+    assert!(breaker.record_outflow(200, 1_000, 10).is_err());
+    assert!(!breaker.record_outflow(100, 1_000, 10).unwrap());
+    assert!(breaker.record_outflow(10, 900, 11).unwrap());
+}
+
+#[test]
+fn test_resume_clears_pause_and_window() {
+    let mut breaker = breaker();
+    let limit = breaker.outflow_limit(POOL_BALANCE);
+    breaker.record_outflow(limit, POOL_BALANCE, 10).unwrap();
+    breaker.record_outflow(1, POOL_BALANCE, 11).unwrap();
+    assert!(breaker.withdrawals_paused);
+
+    breaker.resume(500);
+    assert!(!breaker.withdrawals_paused);
+    assert_eq!(breaker.window_start, 500);
+    assert_eq!(breaker.window_outflow, 0);
+    assert_eq!(breaker.previous_window_outflow, 0);
+    assert!(!breaker.record_outflow(limit, POOL_BALANCE, 501).unwrap());
+}
+
+#[test]
+fn test_validate_config() {
+    assert!(validate_circuit_breaker_config(1, 1).is_ok());
+    assert!(validate_circuit_breaker_config(10000, 216_000).is_ok());
+    assert!(validate_circuit_breaker_config(0, 1000).is_err());
+    assert!(validate_circuit_breaker_config(10001, 1000).is_err());
+    assert!(validate_circuit_breaker_config(1000, 0).is_err());
+}
+
+#[test]
+fn test_guardian_cannot_be_cleared() {
+    assert!(validate_guardian(&Pubkey::new_unique()).is_ok());
+    assert_eq!(validate_guardian(&Pubkey::default()).unwrap_err(), PoolError::InvalidGuardian.into());
+}
+
+#[test]
+fn test_withdrawal_freeze_keeps_deposits_open() {
+    let freeze = PoolFreeze {
+        withdrawals_frozen: true,
+        ..PoolFreeze::default()
+    };
+    assert_eq!(freeze.check(-1).unwrap_err(), PoolError::WithdrawalsFrozen.into());
+    assert!(freeze.check(1_000).is_ok());
+    assert!(freeze.check(0).is_ok());
+}
+
+#[test]
+fn test_deposit_freeze_keeps_withdrawals_open() {
+    let freeze = PoolFreeze {
+        deposits_frozen: true,
+        ..PoolFreeze::default()
+    };
+    assert_eq!(freeze.check(1).unwrap_err(), PoolError::DepositsFrozen.into());
+    assert!(freeze.check(-1_000).is_ok());
+    assert!(freeze.check(0).is_ok());
+}
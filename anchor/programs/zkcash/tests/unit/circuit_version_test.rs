@@ -0,0 +1,56 @@
+use zkcash::errors::PoolError;
+use zkcash::{
+    validate_min_circuit_version, verify_versioned_proof, versioned_public_inputs, CircuitVersionConfig, Proof,
+    CIRCUIT_VERSION, CIRCUIT_VERSION_LEGACY, CIRCUIT_VERSION_V2,
+};
+
+fn proof() -> Proof {
+    Proof {
+        proof_a: [1u8; 64],
+        proof_b: [2u8; 128],
+        proof_c: [3u8; 64],
+        root: [4u8; 32],
+        public_amount: [5u8; 32],
+        ext_data_hash: [6u8; 32],
+        input_nullifiers: [[7u8; 32], [8u8; 32]],
+        output_commitments: [[9u8; 32], [10u8; 32]],
+    }
+}
+
+#[test]
+fn version_is_the_last_public_input() {
+    let proof = proof();
+    let inputs = versioned_public_inputs(&proof, CIRCUIT_VERSION_V2);
+    assert_eq!(inputs[..7], proof.public_inputs());
+    let mut version = [0u8; 32];
+    version[31] = CIRCUIT_VERSION_V2;
+    assert_eq!(inputs[7], version);
+}
+
+#[test]
+fn config_rejects_deprecated_and_unsupported_versions() {
+    let config = CircuitVersionConfig::default();
+    assert!(config.check(CIRCUIT_VERSION_LEGACY).is_ok());
+    assert_eq!(config.check(0).unwrap_err(), PoolError::DeprecatedCircuitVersion.into());
+    // v2 has no verifying key until its setup is done
+    assert_eq!(config.check(CIRCUIT_VERSION_V2).unwrap_err(), PoolError::UnsupportedCircuitVersion.into());
+    assert_eq!(config.check(CIRCUIT_VERSION + 1).unwrap_err(), PoolError::UnsupportedCircuitVersion.into());
+
+    let retired = CircuitVersionConfig { min_version: CIRCUIT_VERSION_V2, bump: 0 };
+    assert_eq!(retired.check(CIRCUIT_VERSION_LEGACY).unwrap_err(), PoolError::DeprecatedCircuitVersion.into());
+}
+
+#[test]
+fn min_version_must_be_verifiable() {
+    assert!(validate_min_circuit_version(CIRCUIT_VERSION_LEGACY).is_ok());
+    assert_eq!(validate_min_circuit_version(0).unwrap_err(), PoolError::InvalidCircuitVersion.into());
+    assert_eq!(validate_min_circuit_version(CIRCUIT_VERSION_V2).unwrap_err(), PoolError::InvalidCircuitVersion.into());
+}
+
+#[test]
+fn unsupported_version_fails_before_verification() {
+    assert_eq!(
+        verify_versioned_proof(&proof(), CIRCUIT_VERSION_V2).unwrap_err(),
+        PoolError::UnsupportedCircuitVersion.into()
+    );
+}
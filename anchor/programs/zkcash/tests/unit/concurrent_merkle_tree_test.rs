@@ -0,0 +1,121 @@
+use bytemuck::Zeroable;
+use light_hasher::{Hasher, Poseidon};
+use std::mem::MaybeUninit;
+use zkcash::{merkle_tree::MerkleTree, ConcurrentMerkleTree, MerkleTreeAccount, PoolConcurrentMerkleTree};
+
+type SmallTree = ConcurrentMerkleTree<4, 4>;
+
+fn leaf(i: u64) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes[24..].copy_from_slice(&(i + 1).to_be_bytes());
+    bytes
+}
+
+fn small_tree() -> SmallTree {
+    let mut tree = SmallTree::zeroed();
+    tree.initialize::<Poseidon>().unwrap();
+    tree
+}
+
+fn sparse_tree_account() -> MerkleTreeAccount {
+    let mut uninit: MaybeUninit<MerkleTreeAccount> = MaybeUninit::uninit();
+    unsafe {
+        let ptr = uninit.as_mut_ptr();
+        std::ptr::write_bytes(ptr, 0, 1);
+        (*ptr).height = 26;
+        (*ptr).root_history_size = 100;
+        uninit.assume_init()
+    }
+}
+
+#[test]
+fn test_roots_match_the_sparse_tree() {
+    let mut concurrent = Box::new(PoolConcurrentMerkleTree::zeroed());
+    concurrent.initialize::<Poseidon>().unwrap();
+    let mut sparse = sparse_tree_account();
+    MerkleTree::initialize::<Poseidon>(&mut sparse).unwrap();
+    assert_eq!(concurrent.root(), sparse.root);
+
+    for i in 0..9 {
+        let concurrent_proof = concurrent.append::<Poseidon>(leaf(i)).unwrap();
+        let sparse_proof = MerkleTree::append::<Poseidon>(leaf(i), &mut sparse).unwrap();
+        assert_eq!(concurrent.root(), sparse.root);
+        assert_eq!(concurrent_proof.to_vec(), sparse_proof);
+    }
+    assert_eq!(concurrent.next_index(), 9);
+}
+
+#[test]
+fn test_current_proofs_verify() {
+    let mut tree = small_tree();
+    let mut proofs = Vec::new();
+    for i in 0..3 {
+        proofs.push(tree.append::<Poseidon>(leaf(i)).unwrap());
+    }
+
+    // Only the last proof is for the current root
+    assert!(tree.verify_leaf::<Poseidon>(tree.root(), leaf(2), &proofs[2], 2));
+    assert!(!tree.verify_leaf::<Poseidon>(tree.root(), leaf(1), &proofs[2], 2));
+    assert!(!tree.verify_leaf::<Poseidon>(tree.root(), leaf(2), &proofs[2], 1));
+}
+
+#[test]
+fn test_stale_proofs_are_fast_forwarded() {
+    let mut tree = small_tree();
+    tree.append::<Poseidon>(leaf(0)).unwrap();
+    let proof = tree.append::<Poseidon>(leaf(1)).unwrap();
+    let root = tree.root();
+
+    // Other writers append before the proof lands
+    for i in 2..5 {
+        tree.append::<Poseidon>(leaf(i)).unwrap();
+    }
+
+    assert_ne!(tree.root(), root);
+    assert!(tree.verify_leaf::<Poseidon>(root, leaf(1), &proof, 1));
+    assert!(!tree.verify_leaf::<Poseidon>(root, leaf(0), &proof, 1));
+}
+
+#[test]
+fn test_proofs_older_than_the_buffer_are_rejected() {
+    let mut tree = small_tree();
+    let proof = tree.append::<Poseidon>(leaf(0)).unwrap();
+    let root = tree.root();
+
+    for i in 1..4 {
+        tree.append::<Poseidon>(leaf(i)).unwrap();
+    }
+    assert!(tree.verify_leaf::<Poseidon>(root, leaf(0), &proof, 0));
+
+    // The change log for `root` is overwritten once the buffer wraps
+    tree.append::<Poseidon>(leaf(4)).unwrap();
+    assert!(!tree.verify_leaf::<Poseidon>(root, leaf(0), &proof, 0));
+}
+
+#[test]
+fn test_leaves_appended_after_the_root_do_not_verify() {
+    let mut tree = small_tree();
+    let empty_root = tree.root();
+    let proof = tree.append::<Poseidon>(leaf(0)).unwrap();
+
+    assert!(!tree.verify_leaf::<Poseidon>(empty_root, leaf(0), &proof, 0));
+    assert!(!tree.verify_leaf::<Poseidon>(tree.root(), leaf(0), &proof, 1));
+}
+
+#[test]
+fn test_append_rejects_when_full() {
+    let mut tree = small_tree();
+    for i in 0..16 {
+        tree.append::<Poseidon>(leaf(i)).unwrap();
+    }
+
+    assert!(tree.append::<Poseidon>(leaf(16)).is_err());
+    assert_eq!(tree.root(), {
+        let mut node = leaf(15);
+        let proof = tree.rightmost_proof.proof;
+        for sibling in proof.iter() {
+            node = Poseidon::hashv(&[sibling, &node]).unwrap();
+        }
+        node
+    });
+}
@@ -0,0 +1,68 @@
+use zkcash::{effective_slot, validate_deposit_limits, DepositLimits, PendingDepositLimits, CONFIG_UPDATE_DELAY_SLOTS};
+
+fn limits(max_per_transaction: u64, max_per_slot: u64) -> DepositLimits {
+    DepositLimits {
+        max_per_transaction,
+        max_per_slot,
+        ..DepositLimits::default()
+    }
+}
+
+#[test]
+fn test_deposit_within_limits() {
+    let mut limits = limits(1_000, 2_500);
+    limits.record_deposit(1_000, 10).unwrap();
+    limits.record_deposit(1_000, 10).unwrap();
+
+    assert_eq!(limits.current_slot, 10);
+    assert_eq!(limits.deposited_in_slot, 2_000);
+}
+
+#[test]
+fn test_per_transaction_limit() {
+    let mut limits = limits(1_000, 10_000);
+    assert!(limits.record_deposit(1_001, 10).is_err());
+    // A rejected deposit doesn't count towards the slot
+    assert_eq!(limits.deposited_in_slot, 0);
+}
+
+#[test]
+fn test_per_slot_limit() {
+    let mut limits = limits(1_000, 2_500);
+    limits.record_deposit(1_000, 10).unwrap();
+    limits.record_deposit(1_000, 10).unwrap();
+
+    assert!(limits.record_deposit(1_000, 10).is_err());
+    limits.record_deposit(500, 10).unwrap();
+    assert_eq!(limits.deposited_in_slot, 2_500);
+}
+
+#[test]
+fn test_slot_total_resets_on_new_slot() {
+    let mut limits = limits(1_000, 1_000);
+    limits.record_deposit(1_000, 10).unwrap();
+    assert!(limits.record_deposit(1, 10).is_err());
+
+    limits.record_deposit(1_000, 11).unwrap();
+    assert_eq!(limits.current_slot, 11);
+    assert_eq!(limits.deposited_in_slot, 1_000);
+}
+
+#[test]
+fn test_validate_deposit_limits() {
+    validate_deposit_limits(1, 1).unwrap();
+    validate_deposit_limits(1_000, u64::MAX).unwrap();
+    assert!(validate_deposit_limits(0, 1_000).is_err());
+    assert!(validate_deposit_limits(1_000, 999).is_err());
+}
+
+#[test]
+fn test_pending_limits_wait_for_the_config_delay() {
+    let pending = PendingDepositLimits {
+        effective_slot: effective_slot(100).unwrap(),
+        ..PendingDepositLimits::default()
+    };
+    assert!(!pending.is_ready(100));
+    assert!(!pending.is_ready(99 + CONFIG_UPDATE_DELAY_SLOTS));
+    assert!(pending.is_ready(100 + CONFIG_UPDATE_DELAY_SLOTS));
+}
@@ -0,0 +1,51 @@
+use zkcash::errors::PoolError;
+use zkcash::{DepositQueue, MAX_QUEUED_DEPOSITS, MAX_QUEUE_BATCH};
+
+fn commitment(i: usize) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes[..8].copy_from_slice(&(i as u64).to_le_bytes());
+    bytes
+}
+
+#[test]
+fn test_batches_come_out_in_deposit_order() {
+    let mut queue = DepositQueue::default();
+    for i in 0..5 {
+        queue.enqueue(commitment(i)).unwrap();
+    }
+
+    assert_eq!(queue.take_batch(3).unwrap(), vec![commitment(0), commitment(1), commitment(2)]);
+    assert_eq!(queue.take_batch(MAX_QUEUE_BATCH).unwrap(), vec![commitment(3), commitment(4)]);
+    assert!(queue.pending.is_empty());
+    assert_eq!(queue.total_enqueued, 5);
+}
+
+#[test]
+fn test_enqueue_rejects_when_full() {
+    let mut queue = DepositQueue::default();
+    for i in 0..MAX_QUEUED_DEPOSITS {
+        queue.enqueue(commitment(i)).unwrap();
+    }
+
+    assert_eq!(queue.enqueue(commitment(0)).unwrap_err(), PoolError::DepositQueueFull.into());
+
+    // Draining a batch frees room again
+    queue.take_batch(1).unwrap();
+    assert!(queue.enqueue(commitment(0)).is_ok());
+}
+
+#[test]
+fn test_empty_queue_cannot_be_processed() {
+    let mut queue = DepositQueue::default();
+    assert_eq!(queue.take_batch(1).unwrap_err(), PoolError::DepositQueueEmpty.into());
+}
+
+#[test]
+fn test_batch_size_is_bounded() {
+    let mut queue = DepositQueue::default();
+    queue.enqueue(commitment(0)).unwrap();
+
+    assert_eq!(queue.take_batch(0).unwrap_err(), PoolError::InvalidQueueBatchSize.into());
+    assert_eq!(queue.take_batch(MAX_QUEUE_BATCH + 1).unwrap_err(), PoolError::InvalidQueueBatchSize.into());
+    assert_eq!(queue.pending.len(), 1);
+}
@@ -0,0 +1,58 @@
+use zkcash::errors::PoolError;
+use zkcash::{
+    validate_max_encrypted_output_len, EncryptedOutputConfig, OutputStorageConfig, OutputStorageMode,
+    DEFAULT_MAX_ENCRYPTED_OUTPUT_LEN, MAX_ENCRYPTED_OUTPUT_LEN, MIN_ENCRYPTED_OUTPUT_LEN,
+};
+
+#[test]
+fn test_default_limit_fits_client_notes() {
+    let config = EncryptedOutputConfig::default();
+    assert_eq!(config.max_len, DEFAULT_MAX_ENCRYPTED_OUTPUT_LEN);
+    let note = vec![0u8; MIN_ENCRYPTED_OUTPUT_LEN as usize];
+    assert!(config.validate_outputs(&note, &note).is_ok());
+    assert!(validate_max_encrypted_output_len(DEFAULT_MAX_ENCRYPTED_OUTPUT_LEN).is_ok());
+}
+
+#[test]
+fn test_outputs_at_the_limit_are_accepted() {
+    let config = EncryptedOutputConfig { max_len: 200, bump: 0 };
+    assert!(config.validate_outputs(&[0u8; 200], &[0u8; 200]).is_ok());
+    assert!(config.validate_outputs(&[], &[]).is_ok());
+}
+
+#[test]
+fn test_either_oversized_output_is_rejected() {
+    let config = EncryptedOutputConfig { max_len: 200, bump: 0 };
+
+    let err = config.validate_outputs(&[0u8; 201], &[0u8; 121]).unwrap_err();
+    assert_eq!(err, PoolError::EncryptedOutputTooLarge.into());
+    let err = config.validate_outputs(&[0u8; 121], &[0u8; 201]).unwrap_err();
+    assert_eq!(err, PoolError::EncryptedOutputTooLarge.into());
+}
+
+#[test]
+fn test_limit_must_stay_in_range() {
+    assert!(validate_max_encrypted_output_len(MIN_ENCRYPTED_OUTPUT_LEN).is_ok());
+    assert!(validate_max_encrypted_output_len(MAX_ENCRYPTED_OUTPUT_LEN).is_ok());
+
+    let err = validate_max_encrypted_output_len(MIN_ENCRYPTED_OUTPUT_LEN - 1).unwrap_err();
+    assert_eq!(err, PoolError::InvalidEncryptedOutputLimit.into());
+    let err = validate_max_encrypted_output_len(MAX_ENCRYPTED_OUTPUT_LEN + 1).unwrap_err();
+    assert_eq!(err, PoolError::InvalidEncryptedOutputLimit.into());
+}
+
+#[test]
+fn test_outputs_are_stored_by_default() {
+    let config = OutputStorageConfig::default();
+    assert_eq!(config.mode, OutputStorageMode::Account);
+    assert_eq!(config.stored_output(&[7u8; 136]), &[7u8; 136]);
+}
+
+#[test]
+fn test_event_only_mode_stores_nothing() {
+    let config = OutputStorageConfig {
+        mode: OutputStorageMode::EventOnly,
+        bump: 0,
+    };
+    assert!(config.stored_output(&[7u8; 136]).is_empty());
+}
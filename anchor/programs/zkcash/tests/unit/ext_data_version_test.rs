@@ -0,0 +1,474 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use zkcash::errors::PoolError;
+use zkcash::utils::{
+    calculate_complete_ext_data_hash, calculate_ext_data_hash_with_memo, calculate_versioned_ext_data_hash,
+    validate_expiry_slot, validate_max_fee, validate_relayer, ExtDataFields, EXT_DATA_VERSION,
+    EXT_DATA_VERSION_EXPIRY, EXT_DATA_VERSION_LEGACY, EXT_DATA_VERSION_MAX_FEE, EXT_DATA_VERSION_RELAYER,
+    EXT_DATA_VERSION_SWAP, EXT_DATA_VERSION_CPI, validate_withdrawal_mode,
+    EXT_DATA_VERSION_FOREIGN_RECIPIENT, EXT_DATA_VERSION_MEMO_CPI,
+};
+use zkcash::{CpiTarget, ForeignRecipient, SwapParams};
+
+fn fields(memo: &[u8]) -> ExtDataFields<'_> {
+    ExtDataFields {
+        recipient: Pubkey::new_from_array([1u8; 32]),
+        ext_amount: -1_000,
+        encrypted_output1: b"encrypted_output_1_data",
+        encrypted_output2: b"encrypted_output_2_data",
+        fee: 10,
+        fee_recipient: Pubkey::new_from_array([2u8; 32]),
+        mint_address: Pubkey::new_from_array([3u8; 32]),
+        memo,
+        expiry_slot: 0,
+        max_fee: 10,
+        relayer: None,
+        swap: None,
+        cpi: None,
+        foreign_recipient: None,
+        memo_cpi: false,
+    }
+}
+
+/// The v1 preimage built by hand: version byte, fields, memo.
+fn v1_preimage(ext_data: &ExtDataFields) -> Vec<u8> {
+    let mut expected = vec![1u8];
+    expected.extend_from_slice(ext_data.recipient.as_ref());
+    expected.extend_from_slice(&ext_data.ext_amount.to_le_bytes());
+    expected.extend_from_slice(&(ext_data.encrypted_output1.len() as u32).to_le_bytes());
+    expected.extend_from_slice(ext_data.encrypted_output1);
+    expected.extend_from_slice(&(ext_data.encrypted_output2.len() as u32).to_le_bytes());
+    expected.extend_from_slice(ext_data.encrypted_output2);
+    expected.extend_from_slice(&ext_data.fee.to_le_bytes());
+    expected.extend_from_slice(ext_data.fee_recipient.as_ref());
+    expected.extend_from_slice(ext_data.mint_address.as_ref());
+    expected.extend_from_slice(&(ext_data.memo.len() as u32).to_le_bytes());
+    expected.extend_from_slice(ext_data.memo);
+    expected
+}
+
+/// The v2 preimage: the v1 layout under a new version byte, then the expiry slot.
+fn v2_preimage(ext_data: &ExtDataFields) -> Vec<u8> {
+    let mut expected = v1_preimage(ext_data);
+    expected[0] = 2;
+    expected.extend_from_slice(&ext_data.expiry_slot.to_le_bytes());
+    expected
+}
+
+#[test]
+fn test_legacy_version_matches_unversioned_hash() {
+    let ext_data = fields(&[]);
+    let unversioned = calculate_complete_ext_data_hash(
+        ext_data.recipient,
+        ext_data.ext_amount,
+        ext_data.encrypted_output1,
+        ext_data.encrypted_output2,
+        ext_data.fee,
+        ext_data.fee_recipient,
+        ext_data.mint_address,
+    )
+    .unwrap();
+    assert_eq!(calculate_versioned_ext_data_hash(EXT_DATA_VERSION_LEGACY, &ext_data).unwrap(), unversioned);
+
+    let ext_data = fields(b"memo");
+    let with_memo = calculate_ext_data_hash_with_memo(
+        ext_data.recipient,
+        ext_data.ext_amount,
+        ext_data.encrypted_output1,
+        ext_data.encrypted_output2,
+        ext_data.fee,
+        ext_data.fee_recipient,
+        ext_data.mint_address,
+        ext_data.memo,
+    )
+    .unwrap();
+    assert_eq!(calculate_versioned_ext_data_hash(EXT_DATA_VERSION_LEGACY, &ext_data).unwrap(), with_memo);
+}
+
+#[test]
+fn test_v1_layout() {
+    for memo in [&b""[..], b"dest-tag-42"] {
+        let ext_data = fields(memo);
+        assert_eq!(
+            calculate_versioned_ext_data_hash(1, &ext_data).unwrap(),
+            hash(&v1_preimage(&ext_data)).to_bytes()
+        );
+    }
+}
+
+#[test]
+fn test_v2_layout() {
+    for expiry_slot in [0, 1, 350_000_000, u64::MAX] {
+        let ext_data = ExtDataFields { expiry_slot, ..fields(b"dest-tag-42") };
+        assert_eq!(
+            calculate_versioned_ext_data_hash(EXT_DATA_VERSION_EXPIRY, &ext_data).unwrap(),
+            hash(&v2_preimage(&ext_data)).to_bytes()
+        );
+    }
+}
+
+#[test]
+fn test_expiry_slot_only_hashed_from_v2() {
+    let without = fields(&[]);
+    let with = ExtDataFields { expiry_slot: 1_000, ..without };
+
+    for version in [EXT_DATA_VERSION_LEGACY, 1] {
+        assert_eq!(
+            calculate_versioned_ext_data_hash(version, &with).unwrap(),
+            calculate_versioned_ext_data_hash(version, &without).unwrap()
+        );
+    }
+    assert_ne!(
+        calculate_versioned_ext_data_hash(EXT_DATA_VERSION_EXPIRY, &with).unwrap(),
+        calculate_versioned_ext_data_hash(EXT_DATA_VERSION_EXPIRY, &without).unwrap()
+    );
+}
+
+/// The v3 preimage: the v2 layout under a new version byte, then the max fee.
+fn v3_preimage(ext_data: &ExtDataFields) -> Vec<u8> {
+    let mut expected = v2_preimage(ext_data);
+    expected[0] = 3;
+    expected.extend_from_slice(&ext_data.max_fee.to_le_bytes());
+    expected
+}
+
+#[test]
+fn test_v3_layout() {
+    for max_fee in [0, 10, u64::MAX] {
+        let ext_data = ExtDataFields { max_fee, expiry_slot: 42, ..fields(&[]) };
+        assert_eq!(
+            calculate_versioned_ext_data_hash(EXT_DATA_VERSION_MAX_FEE, &ext_data).unwrap(),
+            hash(&v3_preimage(&ext_data)).to_bytes()
+        );
+    }
+}
+
+#[test]
+fn test_max_fee_only_hashed_from_v3() {
+    let base = fields(&[]);
+    let raised = ExtDataFields { max_fee: 1_000, ..base };
+
+    for version in EXT_DATA_VERSION_LEGACY..EXT_DATA_VERSION_MAX_FEE {
+        assert_eq!(
+            calculate_versioned_ext_data_hash(version, &raised).unwrap(),
+            calculate_versioned_ext_data_hash(version, &base).unwrap()
+        );
+    }
+    assert_ne!(
+        calculate_versioned_ext_data_hash(EXT_DATA_VERSION_MAX_FEE, &raised).unwrap(),
+        calculate_versioned_ext_data_hash(EXT_DATA_VERSION_MAX_FEE, &base).unwrap()
+    );
+}
+
+/// The v4 preimage: the v3 layout under a new version byte, then the relayer
+/// as a Borsh `Option`.
+fn v4_preimage(ext_data: &ExtDataFields) -> Vec<u8> {
+    let mut expected = v3_preimage(ext_data);
+    expected[0] = 4;
+    match ext_data.relayer {
+        Some(relayer) => {
+            expected.push(1);
+            expected.extend_from_slice(relayer.as_ref());
+        }
+        None => expected.push(0),
+    }
+    expected
+}
+
+#[test]
+fn test_v4_layout() {
+    for relayer in [None, Some(Pubkey::new_from_array([4u8; 32]))] {
+        let ext_data = ExtDataFields { relayer, ..fields(b"memo") };
+        assert_eq!(
+            calculate_versioned_ext_data_hash(EXT_DATA_VERSION_RELAYER, &ext_data).unwrap(),
+            hash(&v4_preimage(&ext_data)).to_bytes()
+        );
+    }
+}
+
+#[test]
+fn test_relayer_only_hashed_from_v4() {
+    let base = fields(&[]);
+    let bound = ExtDataFields { relayer: Some(Pubkey::new_from_array([4u8; 32])), ..base };
+
+    for version in EXT_DATA_VERSION_LEGACY..EXT_DATA_VERSION_RELAYER {
+        assert_eq!(
+            calculate_versioned_ext_data_hash(version, &bound).unwrap(),
+            calculate_versioned_ext_data_hash(version, &base).unwrap()
+        );
+    }
+    assert_ne!(
+        calculate_versioned_ext_data_hash(EXT_DATA_VERSION_RELAYER, &bound).unwrap(),
+        calculate_versioned_ext_data_hash(EXT_DATA_VERSION_RELAYER, &base).unwrap()
+    );
+}
+
+fn swap_params() -> SwapParams {
+    SwapParams {
+        program: Pubkey::new_from_array([5u8; 32]),
+        output_mint: Pubkey::new_from_array([6u8; 32]),
+        min_output_amount: 990,
+        route_hash: [7u8; 32],
+    }
+}
+
+/// The v5 preimage: the v4 layout under a new version byte, then the swap
+/// as a Borsh `Option`.
+fn v5_preimage(ext_data: &ExtDataFields) -> Vec<u8> {
+    let mut expected = v4_preimage(ext_data);
+    expected[0] = 5;
+    match ext_data.swap {
+        Some(swap) => {
+            expected.push(1);
+            expected.extend_from_slice(swap.program.as_ref());
+            expected.extend_from_slice(swap.output_mint.as_ref());
+            expected.extend_from_slice(&swap.min_output_amount.to_le_bytes());
+            expected.extend_from_slice(&swap.route_hash);
+        }
+        None => expected.push(0),
+    }
+    expected
+}
+
+#[test]
+fn test_v5_layout() {
+    for swap in [None, Some(swap_params())] {
+        let ext_data = ExtDataFields { swap, ..fields(b"memo") };
+        assert_eq!(
+            calculate_versioned_ext_data_hash(EXT_DATA_VERSION_SWAP, &ext_data).unwrap(),
+            hash(&v5_preimage(&ext_data)).to_bytes()
+        );
+    }
+}
+
+#[test]
+fn test_swap_only_hashed_from_v5() {
+    let base = fields(&[]);
+    let swapped = ExtDataFields { swap: Some(swap_params()), ..base };
+
+    for version in EXT_DATA_VERSION_LEGACY..EXT_DATA_VERSION_SWAP {
+        assert_eq!(
+            calculate_versioned_ext_data_hash(version, &swapped).unwrap(),
+            calculate_versioned_ext_data_hash(version, &base).unwrap()
+        );
+    }
+    assert_ne!(
+        calculate_versioned_ext_data_hash(EXT_DATA_VERSION_SWAP, &swapped).unwrap(),
+        calculate_versioned_ext_data_hash(EXT_DATA_VERSION_SWAP, &base).unwrap()
+    );
+}
+
+fn cpi_target() -> CpiTarget {
+    CpiTarget {
+        program: Pubkey::new_from_array([8u8; 32]),
+        data_hash: [9u8; 32],
+    }
+}
+
+/// The v6 preimage: the v5 layout under a new version byte, then the CPI
+/// target as a Borsh `Option`.
+fn v6_preimage(ext_data: &ExtDataFields) -> Vec<u8> {
+    let mut expected = v5_preimage(ext_data);
+    expected[0] = 6;
+    match ext_data.cpi {
+        Some(cpi) => {
+            expected.push(1);
+            expected.extend_from_slice(cpi.program.as_ref());
+            expected.extend_from_slice(&cpi.data_hash);
+        }
+        None => expected.push(0),
+    }
+    expected
+}
+
+#[test]
+fn test_v6_layout() {
+    for cpi in [None, Some(cpi_target())] {
+        let ext_data = ExtDataFields { cpi, ..fields(b"memo") };
+        assert_eq!(
+            calculate_versioned_ext_data_hash(EXT_DATA_VERSION_CPI, &ext_data).unwrap(),
+            hash(&v6_preimage(&ext_data)).to_bytes()
+        );
+    }
+}
+
+#[test]
+fn test_cpi_only_hashed_from_v6() {
+    let base = fields(&[]);
+    let with_cpi = ExtDataFields { cpi: Some(cpi_target()), ..base };
+
+    for version in EXT_DATA_VERSION_LEGACY..EXT_DATA_VERSION_CPI {
+        assert_eq!(
+            calculate_versioned_ext_data_hash(version, &with_cpi).unwrap(),
+            calculate_versioned_ext_data_hash(version, &base).unwrap()
+        );
+    }
+    assert_ne!(
+        calculate_versioned_ext_data_hash(EXT_DATA_VERSION_CPI, &with_cpi).unwrap(),
+        calculate_versioned_ext_data_hash(EXT_DATA_VERSION_CPI, &base).unwrap()
+    );
+}
+
+#[test]
+fn test_swap_and_cpi_are_exclusive() {
+    let base = fields(&[]);
+    assert!(validate_withdrawal_mode(&base).is_ok());
+    assert!(validate_withdrawal_mode(&ExtDataFields { swap: Some(swap_params()), ..base }).is_ok());
+    assert!(validate_withdrawal_mode(&ExtDataFields { cpi: Some(cpi_target()), ..base }).is_ok());
+    let both = ExtDataFields { swap: Some(swap_params()), cpi: Some(cpi_target()), ..base };
+    assert_eq!(validate_withdrawal_mode(&both).unwrap_err(), PoolError::ConflictingWithdrawalModes.into());
+}
+
+fn foreign_recipient() -> ForeignRecipient {
+    ForeignRecipient {
+        chain_id: 2,
+        address: [10u8; 32],
+    }
+}
+
+/// The v7 preimage: the v6 layout under a new version byte, then the
+/// foreign recipient as a Borsh `Option`.
+fn v7_preimage(ext_data: &ExtDataFields) -> Vec<u8> {
+    let mut expected = v6_preimage(ext_data);
+    expected[0] = 7;
+    match ext_data.foreign_recipient {
+        Some(recipient) => {
+            expected.push(1);
+            expected.extend_from_slice(&recipient.chain_id.to_le_bytes());
+            expected.extend_from_slice(&recipient.address);
+        }
+        None => expected.push(0),
+    }
+    expected
+}
+
+#[test]
+fn test_v7_layout() {
+    for foreign_recipient in [None, Some(foreign_recipient())] {
+        let ext_data = ExtDataFields { foreign_recipient, ..fields(b"memo") };
+        assert_eq!(
+            calculate_versioned_ext_data_hash(EXT_DATA_VERSION_FOREIGN_RECIPIENT, &ext_data).unwrap(),
+            hash(&v7_preimage(&ext_data)).to_bytes()
+        );
+    }
+}
+
+#[test]
+fn test_foreign_recipient_only_hashed_from_v7() {
+    let base = fields(&[]);
+    let bridged = ExtDataFields { foreign_recipient: Some(foreign_recipient()), ..base };
+
+    for version in EXT_DATA_VERSION_LEGACY..EXT_DATA_VERSION_FOREIGN_RECIPIENT {
+        assert_eq!(
+            calculate_versioned_ext_data_hash(version, &bridged).unwrap(),
+            calculate_versioned_ext_data_hash(version, &base).unwrap()
+        );
+    }
+    assert_ne!(
+        calculate_versioned_ext_data_hash(EXT_DATA_VERSION_FOREIGN_RECIPIENT, &bridged).unwrap(),
+        calculate_versioned_ext_data_hash(EXT_DATA_VERSION_FOREIGN_RECIPIENT, &base).unwrap()
+    );
+}
+
+/// The v8 preimage: the v7 layout under a new version byte, then the memo
+/// CPI flag as a Borsh `bool`.
+fn v8_preimage(ext_data: &ExtDataFields) -> Vec<u8> {
+    let mut expected = v7_preimage(ext_data);
+    expected[0] = 8;
+    expected.push(ext_data.memo_cpi as u8);
+    expected
+}
+
+#[test]
+fn test_v8_layout() {
+    for memo_cpi in [false, true] {
+        let ext_data = ExtDataFields { memo_cpi, ..fields(b"memo") };
+        assert_eq!(
+            calculate_versioned_ext_data_hash(EXT_DATA_VERSION_MEMO_CPI, &ext_data).unwrap(),
+            hash(&v8_preimage(&ext_data)).to_bytes()
+        );
+    }
+}
+
+#[test]
+fn test_memo_cpi_only_hashed_from_v8() {
+    let base = fields(b"memo");
+    let memo_cpi = ExtDataFields { memo_cpi: true, ..base };
+
+    for version in EXT_DATA_VERSION_LEGACY..EXT_DATA_VERSION_MEMO_CPI {
+        assert_eq!(
+            calculate_versioned_ext_data_hash(version, &memo_cpi).unwrap(),
+            calculate_versioned_ext_data_hash(version, &base).unwrap()
+        );
+    }
+    assert_ne!(
+        calculate_versioned_ext_data_hash(EXT_DATA_VERSION_MEMO_CPI, &memo_cpi).unwrap(),
+        calculate_versioned_ext_data_hash(EXT_DATA_VERSION_MEMO_CPI, &base).unwrap()
+    );
+}
+
+#[test]
+fn test_foreign_recipient_excludes_other_modes() {
+    let bridged = ExtDataFields { foreign_recipient: Some(foreign_recipient()), ..fields(&[]) };
+    assert!(validate_withdrawal_mode(&bridged).is_ok());
+    for conflicting in [
+        ExtDataFields { swap: Some(swap_params()), ..bridged },
+        ExtDataFields { cpi: Some(cpi_target()), ..bridged },
+    ] {
+        let err = validate_withdrawal_mode(&conflicting).unwrap_err();
+        assert_eq!(err, PoolError::ConflictingWithdrawalModes.into());
+    }
+}
+
+#[test]
+fn test_bound_relayer_must_sign() {
+    let relayer = Pubkey::new_from_array([4u8; 32]);
+    let front_runner = Pubkey::new_from_array([5u8; 32]);
+
+    assert!(validate_relayer(Some(relayer), relayer).is_ok());
+    let err = validate_relayer(Some(relayer), front_runner).unwrap_err();
+    assert_eq!(err, PoolError::RelayerMismatch.into());
+}
+
+#[test]
+fn test_unbound_relayer_allows_any_signer() {
+    assert!(validate_relayer(None, Pubkey::new_from_array([5u8; 32])).is_ok());
+}
+
+#[test]
+fn test_fee_above_max_fee_is_rejected() {
+    assert!(validate_max_fee(0, 0).is_ok());
+    assert!(validate_max_fee(10, 10).is_ok());
+    assert!(validate_max_fee(9, 10).is_ok());
+    let err = validate_max_fee(11, 10).unwrap_err();
+    assert_eq!(err, PoolError::FeeExceedsMaxFee.into());
+}
+
+#[test]
+fn test_expiry_slot_is_inclusive() {
+    assert!(validate_expiry_slot(100, 99).is_ok());
+    assert!(validate_expiry_slot(100, 100).is_ok());
+    let err = validate_expiry_slot(100, 101).unwrap_err();
+    assert_eq!(err, PoolError::ProofExpired.into());
+}
+
+#[test]
+fn test_zero_expiry_slot_never_expires() {
+    assert!(validate_expiry_slot(0, 0).is_ok());
+    assert!(validate_expiry_slot(0, u64::MAX).is_ok());
+}
+
+#[test]
+fn test_versions_hash_differently() {
+    let ext_data = fields(&[]);
+    assert_ne!(
+        calculate_versioned_ext_data_hash(EXT_DATA_VERSION_LEGACY, &ext_data).unwrap(),
+        calculate_versioned_ext_data_hash(EXT_DATA_VERSION, &ext_data).unwrap()
+    );
+}
+
+#[test]
+fn test_unknown_version_is_rejected() {
+    let err = calculate_versioned_ext_data_hash(EXT_DATA_VERSION + 1, &fields(&[])).unwrap_err();
+    assert_eq!(err, PoolError::UnsupportedExtDataVersion.into());
+}
@@ -0,0 +1,46 @@
+use anchor_lang::prelude::Pubkey;
+use zkcash::errors::PoolError;
+use zkcash::{
+    effective_slot, validate_fee_exemptions, FeeExemptions, PendingFeeExemptions, CONFIG_UPDATE_DELAY_SLOTS,
+    MAX_FEE_EXEMPTIONS,
+};
+
+#[test]
+fn test_listed_recipient_or_signer_is_exempt_on_withdrawal() {
+    let treasury = Pubkey::new_unique();
+    let exemptions = FeeExemptions {
+        exempt: vec![treasury],
+        ..Default::default()
+    };
+    let other = Pubkey::new_unique();
+
+    assert!(exemptions.waives_fee(-1_000, &treasury, &other));
+    assert!(exemptions.waives_fee(-1_000, &other, &treasury));
+    assert!(!exemptions.waives_fee(-1_000, &other, &other));
+    // Deposits and transfers pay as usual
+    assert!(!exemptions.waives_fee(1_000, &treasury, &treasury));
+    assert!(!exemptions.waives_fee(0, &treasury, &treasury));
+}
+
+#[test]
+fn test_validate_fee_exemptions() {
+    let party = Pubkey::new_unique();
+    assert!(validate_fee_exemptions(&[]).is_ok());
+    assert!(validate_fee_exemptions(&[party, Pubkey::new_unique()]).is_ok());
+
+    let too_many: Vec<Pubkey> = (0..=MAX_FEE_EXEMPTIONS).map(|_| Pubkey::new_unique()).collect();
+    for exempt in [vec![party, party], vec![Pubkey::default()], too_many] {
+        assert_eq!(validate_fee_exemptions(&exempt).unwrap_err(), PoolError::InvalidFeeExemptions.into());
+    }
+}
+
+#[test]
+fn test_pending_exemptions_wait_for_the_config_delay() {
+    let pending = PendingFeeExemptions {
+        effective_slot: effective_slot(100).unwrap(),
+        ..PendingFeeExemptions::default()
+    };
+    assert!(!pending.is_ready(100));
+    assert!(!pending.is_ready(99 + CONFIG_UPDATE_DELAY_SLOTS));
+    assert!(pending.is_ready(100 + CONFIG_UPDATE_DELAY_SLOTS));
+}
@@ -0,0 +1,27 @@
+use anchor_lang::prelude::Pubkey;
+use zkcash::errors::PoolError;
+use zkcash::{validate_configured_fee_recipient, FeeRecipientConfig};
+
+#[test]
+fn test_configured_recipient_accepted() {
+    let fee_recipient = Pubkey::new_unique();
+    let config = FeeRecipientConfig { fee_recipient, bump: 0 };
+    assert!(config.validate_protocol_fee_recipient(&fee_recipient).is_ok());
+}
+
+#[test]
+fn test_other_recipient_rejected() {
+    let config = FeeRecipientConfig {
+        fee_recipient: Pubkey::new_unique(),
+        bump: 0,
+    };
+    let err = config.validate_protocol_fee_recipient(&Pubkey::new_unique()).unwrap_err();
+    assert_eq!(err, PoolError::FeeRecipientMismatch.into());
+}
+
+#[test]
+fn test_default_pubkey_cannot_be_configured() {
+    let err = validate_configured_fee_recipient(&Pubkey::default()).unwrap_err();
+    assert_eq!(err, PoolError::InvalidFeeRecipient.into());
+    assert!(validate_configured_fee_recipient(&Pubkey::new_unique()).is_ok());
+}
@@ -0,0 +1,172 @@
+use zkcash::errors::PoolError;
+use zkcash::utils::validate_fee_u128;
+use zkcash::utils::MAX_FEE_RATE;
+use zkcash::{
+    effective_slot, max_base_fee, validate_base_fee, validate_fee_tiers, validate_fee_cap, FeeSchedule,
+    FeeSplit, FeeTier, PendingFeeSchedule, CONFIG_UPDATE_DELAY_SLOTS, MAX_BASE_FEE, MAX_FEE_TIERS,
+};
+
+fn schedule(base_fee: u64) -> FeeSchedule {
+    FeeSchedule { base_fee, ..Default::default() }
+}
+
+#[test]
+fn test_base_fee_applies_to_withdrawals_only() {
+    let schedule = schedule(5_000);
+    // 0.25% of 1_000_000 plus the base fee
+    assert_eq!(schedule.expected_fee(-1_000_000, 0, 25, 0).unwrap(), 2_500 + 5_000);
+    assert_eq!(schedule.expected_fee(1_000_000, 25, 0, 0).unwrap(), 2_500);
+    assert_eq!(schedule.expected_fee(0, 25, 25, 0).unwrap(), 0);
+}
+
+#[test]
+fn test_tiny_withdrawal_still_owes_base_fee() {
+    let schedule = schedule(5_000);
+    // The percentage rounds to nothing, the base fee doesn't
+    assert!(schedule.validate_fee(-100, 0, 0, 25, 0, 0).is_err());
+    assert!(schedule.validate_fee(-100, 5_000, 0, 25, 0, 0).is_ok());
+    // The error margin covers the base fee too
+    assert!(schedule.validate_fee(-100, 4_750, 0, 25, 0, 500).is_ok());
+    assert!(schedule.validate_fee(-100, 4_749, 0, 25, 0, 500).is_err());
+}
+
+#[test]
+fn test_default_schedule_matches_rate_only_fee() {
+    for (ext_amount, fee) in [(-1_000_000i128, 2_375u128), (-1_000_000, 2_374), (1_000_000, 0)] {
+        assert_eq!(
+            FeeSchedule::default().validate_fee(ext_amount, fee, 25, 25, 0, 500).is_ok(),
+            validate_fee_u128(ext_amount, fee, 25, 25, 500).is_ok()
+        );
+    }
+}
+
+#[test]
+fn test_validate_base_fee() {
+    assert!(validate_base_fee(0, 9).is_ok());
+    assert!(validate_base_fee(MAX_BASE_FEE, 9).is_ok());
+    assert_eq!(validate_base_fee(MAX_BASE_FEE + 1, 9).unwrap_err(), PoolError::BaseFeeTooHigh.into());
+}
+
+#[test]
+fn test_base_fee_bound_is_per_mint() {
+    // A hundredth of a whole token: 0.01 SOL, 0.01 USDC
+    assert_eq!(max_base_fee(9), MAX_BASE_FEE);
+    assert_eq!(max_base_fee(6), 10_000);
+    assert_eq!(max_base_fee(0), 0);
+    assert_eq!(max_base_fee(u8::MAX), u64::MAX);
+    // The SOL pool's cap would be 10 USDC at 6 decimals
+    assert_eq!(validate_base_fee(MAX_BASE_FEE, 6).unwrap_err(), PoolError::BaseFeeTooHigh.into());
+    assert!(validate_base_fee(10_000, 6).is_ok());
+}
+
+
+fn tier(min_amount: u64, fee_rate: u16) -> FeeTier {
+    FeeTier { min_amount, fee_rate }
+}
+
+#[test]
+fn test_withdrawals_use_the_highest_tier_reached() {
+    let schedule = FeeSchedule {
+        tiers: vec![tier(1_000_000, 20), tier(10_000_000, 10)],
+        ..Default::default()
+    };
+    assert_eq!(schedule.withdrawal_fee_rate(999_999, 25), 25);
+    assert_eq!(schedule.withdrawal_fee_rate(1_000_000, 25), 20);
+    assert_eq!(schedule.withdrawal_fee_rate(u128::MAX, 25), 10);
+
+    assert_eq!(schedule.expected_fee(-10_000_000, 25, 25, 0).unwrap(), 10_000);
+    // Deposits ignore the tiers
+    assert_eq!(schedule.expected_fee(10_000_000, 25, 25, 0).unwrap(), 25_000);
+}
+
+#[test]
+fn test_validate_fee_tiers() {
+    assert!(validate_fee_tiers(&[]).is_ok());
+    assert!(validate_fee_tiers(&[tier(1, MAX_FEE_RATE), tier(2, 0)]).is_ok());
+
+    let invalid = [
+        vec![tier(2, 10), tier(1, 5)],
+        vec![tier(1, 10), tier(1, 5)],
+        vec![tier(0, 10)],
+        vec![tier(1, MAX_FEE_RATE + 1)],
+        (1..=MAX_FEE_TIERS as u64 + 1).map(|min_amount| tier(min_amount, 0)).collect(),
+    ];
+    for tiers in invalid {
+        assert_eq!(validate_fee_tiers(&tiers).unwrap_err(), PoolError::InvalidFeeTiers.into());
+    }
+}
+
+#[test]
+fn test_cap_clamps_large_withdrawals_before_the_margin() {
+    let schedule = FeeSchedule {
+        base_fee: 5_000,
+        fee_cap: 1_000_000,
+        ..Default::default()
+    };
+    // 0.25% of 1_000 SOL would be 2.5 SOL
+    let ext_amount = -1_000_000_000_000i128;
+    assert_eq!(schedule.expected_fee(ext_amount, 25, 25, 0).unwrap(), 1_000_000);
+    assert!(schedule.validate_fee(ext_amount, 950_000, 25, 25, 0, 500).is_ok());
+    assert!(schedule.validate_fee(ext_amount, 949_999, 25, 25, 0, 500).is_err());
+
+    // Small withdrawals are under the cap
+    assert_eq!(schedule.expected_fee(-1_000_000, 25, 25, 0).unwrap(), 7_500);
+    // Deposits aren't capped
+    assert_eq!(schedule.expected_fee(-ext_amount, 25, 25, 0).unwrap(), 2_500_000_000);
+}
+
+#[test]
+fn test_validate_fee_cap() {
+    assert!(validate_fee_cap(0, 5_000).is_ok());
+    assert!(validate_fee_cap(5_000, 5_000).is_ok());
+    assert_eq!(validate_fee_cap(4_999, 5_000).unwrap_err(), PoolError::MaxFeeBelowBaseFee.into());
+}
+#[test]
+fn test_fee_splits_between_protocol_and_relayer() {
+    let schedule = schedule(5_000);
+    // The rate's share is the protocol's, the base fee the relayer's
+    assert_eq!(schedule.protocol_fee(-1_000_000, 0, 25).unwrap(), 2_500);
+    assert_eq!(schedule.expected_split(-1_000_000, 0, 25, 0).unwrap().relayer, 5_000);
+    assert_eq!(
+        schedule.split_fee(-1_000_000, 7_500, 0, 25).unwrap(),
+        FeeSplit { protocol: 2_500, relayer: 5_000 }
+    );
+    // Overpaying goes to the relayer, underpaying comes out of its share
+    assert_eq!(
+        schedule.split_fee(-1_000_000, 8_000, 0, 25).unwrap(),
+        FeeSplit { protocol: 2_500, relayer: 5_500 }
+    );
+    assert_eq!(
+        schedule.split_fee(-1_000_000, 7_125, 0, 25).unwrap(),
+        FeeSplit { protocol: 2_500, relayer: 4_625 }
+    );
+    assert_eq!(schedule.split_fee(-1_000_000, 2_000, 0, 25).unwrap(), FeeSplit { protocol: 2_000, relayer: 0 });
+    // Deposits owe no base fee, so their whole fee is the protocol's
+    assert_eq!(schedule.split_fee(1_000_000, 2_500, 25, 0).unwrap(), FeeSplit { protocol: 2_500, relayer: 0 });
+}
+
+#[test]
+fn test_cap_takes_from_the_protocol_cut_before_the_base_fee() {
+    let schedule = FeeSchedule {
+        base_fee: 5_000,
+        fee_cap: 1_000_000,
+        ..Default::default()
+    };
+    let ext_amount = -1_000_000_000_000i128;
+    assert_eq!(schedule.protocol_fee(ext_amount, 25, 25).unwrap(), 995_000);
+    assert_eq!(
+        schedule.split_fee(ext_amount, 1_000_000, 25, 25).unwrap(),
+        FeeSplit { protocol: 995_000, relayer: 5_000 }
+    );
+}
+
+#[test]
+fn test_pending_schedule_waits_for_the_config_delay() {
+    let pending = PendingFeeSchedule {
+        effective_slot: effective_slot(100).unwrap(),
+        ..PendingFeeSchedule::default()
+    };
+    assert!(!pending.is_ready(100));
+    assert!(!pending.is_ready(99 + CONFIG_UPDATE_DELAY_SLOTS));
+    assert!(pending.is_ready(100 + CONFIG_UPDATE_DELAY_SLOTS));
+}
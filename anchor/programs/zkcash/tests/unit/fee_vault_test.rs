@@ -0,0 +1,16 @@
+use anchor_lang::prelude::Pubkey;
+use zkcash::errors::PoolError;
+use zkcash::{sweepable_fees, validate_fee_payout};
+
+#[test]
+fn test_sweep_leaves_the_vault_rent_exempt() {
+    assert_eq!(sweepable_fees(5_000_000, 1_000_000), 4_000_000);
+    assert_eq!(sweepable_fees(1_000_000, 1_000_000), 0);
+    assert_eq!(sweepable_fees(500_000, 1_000_000), 0);
+}
+
+#[test]
+fn test_payout_must_be_set() {
+    assert!(validate_fee_payout(&Pubkey::new_unique()).is_ok());
+    assert_eq!(validate_fee_payout(&Pubkey::default()).unwrap_err(), PoolError::InvalidFeePayout.into());
+}
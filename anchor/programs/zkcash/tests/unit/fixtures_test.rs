@@ -0,0 +1,128 @@
+use anchor_lang::prelude::*;
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, PrimeField};
+use light_hasher::Poseidon;
+use serde::Deserialize;
+use std::mem::MaybeUninit;
+use std::str::FromStr;
+use zkcash::merkle_tree::MerkleTree;
+use zkcash::utils::{calculate_complete_ext_data_hash, check_public_amount, verify_proof, VERIFYING_KEY};
+use zkcash::{MerkleTreeAccount, Proof};
+
+// Generated by `zkcash-fixture-gen scenario`, see fixture-gen/src/main.rs
+const FIXTURES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../program-tests/tests/fixtures");
+const SOL_MINT_ADDRESS: &str = "11111111111111111111111111111112";
+
+#[derive(Deserialize)]
+pub(super) struct ProofFixture {
+    proof_a: Vec<u8>,
+    proof_b: Vec<u8>,
+    proof_c: Vec<u8>,
+    root: Vec<u8>,
+    public_amount: Vec<u8>,
+    ext_data_hash: Vec<u8>,
+    input_nullifiers: Vec<Vec<u8>>,
+    output_commitments: Vec<Vec<u8>>,
+}
+
+#[derive(Deserialize)]
+pub(super) struct TransactFixture {
+    pub(super) proof: ProofFixture,
+    ext_amount: i64,
+    fee: u64,
+    recipient: String,
+    fee_recipient: String,
+    encrypted_output1: Vec<u8>,
+    encrypted_output2: Vec<u8>,
+}
+
+pub(super) fn load(name: &str) -> TransactFixture {
+    let path = format!("{}/{}.json", FIXTURES_DIR, name);
+    let json = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("failed to read {}: {}", path, e));
+    serde_json::from_str(&json).unwrap()
+}
+
+fn array<const N: usize>(bytes: &[u8]) -> [u8; N] {
+    bytes.try_into().unwrap()
+}
+
+pub(super) fn to_proof(fixture: &ProofFixture) -> Proof {
+    Proof {
+        proof_a: array(&fixture.proof_a),
+        proof_b: array(&fixture.proof_b),
+        proof_c: array(&fixture.proof_c),
+        root: array(&fixture.root),
+        public_amount: array(&fixture.public_amount),
+        ext_data_hash: array(&fixture.ext_data_hash),
+        input_nullifiers: [array(&fixture.input_nullifiers[0]), array(&fixture.input_nullifiers[1])],
+        output_commitments: [array(&fixture.output_commitments[0]), array(&fixture.output_commitments[1])],
+    }
+}
+
+fn assert_fixture_is_consistent(name: &str) {
+    let fixture = load(name);
+    let proof = to_proof(&fixture.proof);
+
+    assert!(verify_proof(proof.clone(), VERIFYING_KEY), "{} proof does not verify", name);
+    assert!(
+        check_public_amount(fixture.ext_amount, fixture.fee, proof.public_amount),
+        "{} public amount does not match ext_amount and fee",
+        name
+    );
+
+    let hash = calculate_complete_ext_data_hash(
+        Pubkey::from_str(&fixture.recipient).unwrap(),
+        fixture.ext_amount,
+        &fixture.encrypted_output1,
+        &fixture.encrypted_output2,
+        fixture.fee,
+        Pubkey::from_str(&fixture.fee_recipient).unwrap(),
+        Pubkey::from_str(SOL_MINT_ADDRESS).unwrap(),
+    )
+    .unwrap();
+    let expected = Fr::from_le_bytes_mod_order(&hash).into_bigint().to_bytes_be();
+    assert_eq!(proof.ext_data_hash.to_vec(), expected, "{} proof is bound to a different ExtData", name);
+}
+
+#[test]
+fn test_deposit_fixture_is_consistent() {
+    assert_fixture_is_consistent("deposit");
+}
+
+#[test]
+fn test_withdraw_fixture_is_consistent() {
+    assert_fixture_is_consistent("withdraw");
+}
+
+fn create_tree_account() -> MerkleTreeAccount {
+    let mut uninit: MaybeUninit<MerkleTreeAccount> = MaybeUninit::uninit();
+    unsafe {
+        let ptr = uninit.as_mut_ptr();
+        std::ptr::write_bytes(ptr, 0, 1);
+        (*ptr).height = 26;
+        (*ptr).root_history_size = 100;
+        uninit.assume_init()
+    }
+}
+
+#[test]
+fn test_fixture_roots_follow_the_pool_tree() {
+    let deposit = to_proof(&load("deposit").proof);
+    let withdraw = to_proof(&load("withdraw").proof);
+
+    let mut account = create_tree_account();
+    MerkleTree::initialize::<Poseidon>(&mut account).unwrap();
+    assert_eq!(deposit.root, account.root, "deposit must be proven against the empty tree");
+
+    for commitment in deposit.output_commitments {
+        MerkleTree::append::<Poseidon>(commitment, &mut account).unwrap();
+    }
+    assert_eq!(withdraw.root, account.root, "withdraw must be proven against the tree after the deposit");
+}
+
+#[test]
+fn test_tampered_fixture_is_rejected() {
+    let mut proof = to_proof(&load("withdraw").proof);
+    proof.public_amount = to_proof(&load("deposit").proof).public_amount;
+    assert!(!verify_proof(proof, VERIFYING_KEY));
+}
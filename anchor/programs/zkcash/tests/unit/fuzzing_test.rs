@@ -0,0 +1,166 @@
+//! Runs the fuzz harnesses in `zkcash::fuzzing` on inputs `arbitrary`
+//! builds from seeded random bytes, so `cargo test --features fuzzing`
+//! checks what the cargo-fuzz targets check without nightly or libFuzzer.
+//!
+//! `test_write_seed_corpus` is ignored: it writes the starting corpus for
+//! `cargo fuzz run` into `fuzz/corpus/<target>/`, real proofs and instruction
+//! data first so libFuzzer mutates from inputs that get past the parsers.
+
+use anchor_lang::prelude::*;
+use arbitrary::{Arbitrary, Unstructured};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::path::Path;
+use zkcash::fuzzing::{
+    change_endianness_roundtrip, groth16_verifier_new, transact_data_bytes, transact_data_structured,
+    verify_proof_bytes, TransactInput, VerifierInput,
+};
+use zkcash::utils::ExtDataFields;
+use zkcash::{encode_transact_data, EXT_DATA_VERSION, PROOF_LEN};
+use super::proof_test::valid_proof;
+
+const CASES: usize = 256;
+
+/// Enough raw bytes for the largest input, a `TransactInput` with its
+/// variable-length fields filled.
+const MAX_INPUT_LEN: usize = 4096;
+
+fn random_bytes(rng: &mut StdRng) -> Vec<u8> {
+    let len = rng.random_range(0..MAX_INPUT_LEN);
+    (0..len).map(|_| rng.random()).collect()
+}
+
+/// `CASES` inputs of type `T`, the same ones on every run.
+fn arbitrary_inputs<T: for<'a> Arbitrary<'a>>(seed: u64) -> Vec<T> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..CASES)
+        .map(|_| {
+            let bytes = random_bytes(&mut rng);
+            T::arbitrary(&mut Unstructured::new(&bytes)).unwrap()
+        })
+        .collect()
+}
+
+fn valid_proof_bytes() -> Vec<u8> {
+    valid_proof().try_to_vec().unwrap()
+}
+
+fn valid_transact_data() -> Vec<u8> {
+    let ext_data = ExtDataFields {
+        recipient: Pubkey::new_from_array([1u8; 32]),
+        ext_amount: -1_000,
+        encrypted_output1: &[0xab; 80],
+        encrypted_output2: &[0xcd; 80],
+        fee: 10,
+        fee_recipient: Pubkey::new_from_array([2u8; 32]),
+        mint_address: Pubkey::new_from_array([3u8; 32]),
+        memo: b"memo",
+        expiry_slot: 0,
+        max_fee: 10,
+        relayer: None,
+        swap: None,
+        cpi: None,
+        foreign_recipient: None,
+        memo_cpi: false,
+    };
+    encode_transact_data(&valid_proof(), EXT_DATA_VERSION, &ext_data).unwrap()
+}
+
+/// The valid input with the first and then the last byte of each 32-byte
+/// word flipped. Flipping the top byte usually pushes a field element out of
+/// range and the bottom one never does, so both sides of every check run,
+/// which random bytes rarely get past the length checks to do.
+fn single_byte_mutations(valid: &[u8]) -> impl Iterator<Item = Vec<u8>> + '_ {
+    (0..valid.len()).filter(|i| i % 32 == 0 || i % 32 == 31).map(move |i| {
+        let mut mutated = valid.to_vec();
+        mutated[i] ^= 0xff;
+        mutated
+    })
+}
+
+#[test]
+fn test_groth16_verifier_new_arbitrary() {
+    for input in arbitrary_inputs::<VerifierInput>(1) {
+        groth16_verifier_new(&input);
+    }
+}
+
+#[test]
+fn test_verify_proof_arbitrary() {
+    let mut rng = StdRng::seed_from_u64(2);
+    for _ in 0..CASES {
+        verify_proof_bytes(&random_bytes(&mut rng));
+        // Right length, so the point and field checks run
+        let bytes: Vec<u8> = (0..PROOF_LEN).map(|_| rng.random()).collect();
+        verify_proof_bytes(&bytes);
+    }
+}
+
+#[test]
+fn test_verify_proof_mutated() {
+    let valid = valid_proof_bytes();
+    verify_proof_bytes(&valid);
+    for mutated in single_byte_mutations(&valid) {
+        verify_proof_bytes(&mutated);
+    }
+}
+
+#[test]
+fn test_change_endianness_arbitrary() {
+    let mut rng = StdRng::seed_from_u64(3);
+    for _ in 0..CASES {
+        change_endianness_roundtrip(&random_bytes(&mut rng));
+    }
+    for len in [0, 1, 31, 32, 33, 63, 64, 65] {
+        change_endianness_roundtrip(&vec![7u8; len]);
+    }
+}
+
+#[test]
+fn test_transact_data_arbitrary() {
+    let mut rng = StdRng::seed_from_u64(4);
+    for _ in 0..CASES {
+        transact_data_bytes(&random_bytes(&mut rng));
+    }
+}
+
+#[test]
+fn test_transact_data_mutated() {
+    let valid = valid_transact_data();
+    transact_data_bytes(&valid);
+    for mutated in single_byte_mutations(&valid) {
+        transact_data_bytes(&mutated);
+    }
+}
+
+#[test]
+fn test_transact_data_structured_arbitrary() {
+    for input in arbitrary_inputs::<TransactInput>(5) {
+        transact_data_structured(&input);
+    }
+}
+
+#[test]
+#[ignore = "writes the cargo-fuzz seed corpus"]
+fn test_write_seed_corpus() {
+    let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/corpus");
+    let write = |target: &str, inputs: Vec<Vec<u8>>| {
+        let dir = corpus.join(target);
+        std::fs::create_dir_all(&dir).unwrap();
+        for (i, input) in inputs.into_iter().enumerate() {
+            std::fs::write(dir.join(format!("seed-{i:04}")), input).unwrap();
+        }
+    };
+
+    let mut rng = StdRng::seed_from_u64(6);
+    let mut random = |count: usize| -> Vec<Vec<u8>> { (0..count).map(|_| random_bytes(&mut rng)).collect() };
+
+    let proof = valid_proof_bytes();
+    let transact_data = valid_transact_data();
+    write("verify_proof", [vec![proof.clone()], random(CASES)].concat());
+    write("change_endianness", [vec![proof], random(CASES)].concat());
+    write("transact_data", [vec![transact_data], random(CASES)].concat());
+    // `arbitrary` decodes any bytes, so random ones are already valid inputs
+    write("groth16_verifier_new", random(CASES));
+    write("transact_data_structured", random(CASES));
+}
@@ -1,7 +1,11 @@
-use zkcash::groth16::{Groth16Verifier, Groth16Verifyingkey, is_less_than_bn254_field_size_be};
-use zkcash::errors::Groth16Error;
+use zkcash::groth16::{
+    g1_msm, is_less_than_bn254_field_size_be, validate_g1_point, validate_g2_point, Groth16Verifier, Groth16Verifyingkey,
+    BN254_FQ_MODULUS_BE, BN254_FR_MODULUS_BE, G1_INFINITY,
+};
+use solana_bn254::prelude::{alt_bn128_addition, alt_bn128_multiplication};
+use zkcash::errors::{Groth16Error, PoolError};
 use ark_bn254;
-use ark_ff::PrimeField;
+use ark_ff::{BigInteger, PrimeField};
 use ark_bn254::Fr;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
 use zkcash::utils::{change_endianness, VERIFYING_KEY};
@@ -70,6 +74,18 @@ fn test_is_less_than_bn254_field_size_be() {
         .try_into()
         .unwrap();
     assert!(!is_less_than_bn254_field_size_be(&bytes));
+
+    let mut below = bytes;
+    below[31] -= 1;
+    assert!(is_less_than_bn254_field_size_be(&below));
+}
+
+#[test]
+fn modulus_constants_match_arkworks() {
+    let fr: [u8; 32] = BigUint::from(ark_bn254::Fr::MODULUS).to_bytes_be().try_into().unwrap();
+    let fq: [u8; 32] = BigUint::from(ark_bn254::Fq::MODULUS).to_bytes_be().try_into().unwrap();
+    assert_eq!(BN254_FR_MODULUS_BE, fr);
+    assert_eq!(BN254_FQ_MODULUS_BE, fq);
 }
 
 #[test]
@@ -284,4 +300,177 @@ fn ext_data_hash_should_match() {
     
     // Compare field elements with field elements
     assert_eq!(computed_hash_fr, provided_proof_hash_fr);
-}
\ No newline at end of file
+}
+
+fn mapped(error: Groth16Error) -> anchor_lang::error::Error {
+    PoolError::from(error).into()
+}
+
+#[test]
+fn groth16_errors_map_to_distinct_pool_errors() {
+    assert_eq!(mapped(Groth16Error::InvalidG1Length), PoolError::InvalidProofEncoding.into());
+    assert_eq!(mapped(Groth16Error::InvalidG2Length), PoolError::InvalidProofEncoding.into());
+    assert_eq!(mapped(Groth16Error::PublicInputGreaterThanFieldSize), PoolError::PublicInputOutOfField.into());
+    assert_eq!(mapped(Groth16Error::ProofVerificationFailed), PoolError::ProofPairingFailed.into());
+    assert_eq!(mapped(Groth16Error::InvalidBatch), PoolError::InvalidProofBatch.into());
+    assert_eq!(mapped(Groth16Error::G1PointNotOnCurve), PoolError::ProofPointNotOnCurve.into());
+    assert_eq!(mapped(Groth16Error::G2PointNotInSubgroup), PoolError::ProofPointNotInSubgroup.into());
+    assert_eq!(mapped(Groth16Error::InvalidPublicInputsLength), PoolError::MalformedVerifyingKey.into());
+    assert_eq!(mapped(Groth16Error::PreparingInputsG1MulFailed), PoolError::MalformedVerifyingKey.into());
+}
+fn negated_proof_a() -> [u8; 64] {
+    let g1_point = G1::deserialize_with_mode(
+        &*[&change_endianness(&PROOF_A[0..64]), &[0u8][..]].concat(),
+        Compress::No,
+        Validate::Yes,
+    )
+    .unwrap();
+    let mut proof_a_neg = [0u8; 65];
+    g1_point.neg().x.serialize_with_mode(&mut proof_a_neg[..32], Compress::No).unwrap();
+    g1_point.neg().y.serialize_with_mode(&mut proof_a_neg[32..], Compress::No).unwrap();
+    change_endianness(&proof_a_neg[..64]).try_into().unwrap()
+}
+
+#[test]
+fn batch_of_valid_proofs_should_succeed() {
+    let proof_a = negated_proof_a();
+    let mut verifiers = [
+        Groth16Verifier::new(&proof_a, &PROOF_B, &PROOF_C, &PUBLIC_INPUTS, &VERIFYING_KEY).unwrap(),
+        Groth16Verifier::new(&proof_a, &PROOF_B, &PROOF_C, &PUBLIC_INPUTS, &VERIFYING_KEY).unwrap(),
+        Groth16Verifier::new(&proof_a, &PROOF_B, &PROOF_C, &PUBLIC_INPUTS, &VERIFYING_KEY).unwrap(),
+    ];
+    assert_eq!(Groth16Verifier::verify_batch(&mut verifiers), Ok(true));
+    assert_eq!(Groth16Verifier::verify_batch(&mut verifiers[..1]), Ok(true));
+}
+
+#[test]
+fn batch_with_one_invalid_proof_should_not_succeed() {
+    let proof_a = negated_proof_a();
+    let mut verifiers = [
+        Groth16Verifier::new(&proof_a, &PROOF_B, &PROOF_C, &PUBLIC_INPUTS, &VERIFYING_KEY).unwrap(),
+        Groth16Verifier::new(&PROOF_A, &PROOF_B, &PROOF_C, &PUBLIC_INPUTS, &VERIFYING_KEY).unwrap(),
+    ];
+    assert_eq!(
+        Groth16Verifier::verify_batch(&mut verifiers),
+        Err(Groth16Error::ProofVerificationFailed)
+    );
+
+    let mut public_inputs = PUBLIC_INPUTS;
+    public_inputs[1][31] ^= 1;
+    let mut verifiers = [
+        Groth16Verifier::new(&proof_a, &PROOF_B, &PROOF_C, &PUBLIC_INPUTS, &VERIFYING_KEY).unwrap(),
+        Groth16Verifier::new(&proof_a, &PROOF_B, &PROOF_C, &public_inputs, &VERIFYING_KEY).unwrap(),
+    ];
+    assert_eq!(
+        Groth16Verifier::verify_batch(&mut verifiers),
+        Err(Groth16Error::ProofVerificationFailed)
+    );
+}
+
+#[test]
+fn empty_or_mixed_key_batch_should_not_succeed() {
+    let mut empty: [Groth16Verifier<7>; 0] = [];
+    assert_eq!(Groth16Verifier::verify_batch(&mut empty), Err(Groth16Error::InvalidBatch));
+
+    let other_key = Groth16Verifyingkey {
+        vk_alpha_g1: VERIFYING_KEY.vk_beta_g2[..64].try_into().unwrap(),
+        ..VERIFYING_KEY
+    };
+    let proof_a = negated_proof_a();
+    let mut verifiers = [
+        Groth16Verifier::new(&proof_a, &PROOF_B, &PROOF_C, &PUBLIC_INPUTS, &VERIFYING_KEY).unwrap(),
+        Groth16Verifier::new(&proof_a, &PROOF_B, &PROOF_C, &PUBLIC_INPUTS, &other_key).unwrap(),
+    ];
+    assert_eq!(Groth16Verifier::verify_batch(&mut verifiers), Err(Groth16Error::InvalidBatch));
+}
+
+/// The one-term-at-a-time fold `prepare_inputs` used before `g1_msm`.
+fn naive_msm(base: &[u8; 64], points: &[[u8; 64]], scalars: &[[u8; 32]]) -> [u8; 64] {
+    let mut sum = *base;
+    for (point, scalar) in points.iter().zip(scalars) {
+        let term = alt_bn128_multiplication(&[&point[..], &scalar[..]].concat()).unwrap();
+        sum = alt_bn128_addition(&[&term[..], &sum[..]].concat()).unwrap().try_into().unwrap();
+    }
+    sum
+}
+
+#[test]
+fn msm_matches_the_naive_fold() {
+    let base = VERIFYING_KEY.vk_ic[0];
+    let points = &VERIFYING_KEY.vk_ic[1..];
+    assert_eq!(g1_msm(&base, points, &PUBLIC_INPUTS).unwrap(), naive_msm(&base, points, &PUBLIC_INPUTS));
+
+    let mut scalars = PUBLIC_INPUTS;
+    scalars[0] = [0u8; 32];
+    scalars[1] = [0u8; 32];
+    scalars[1][31] = 1;
+    assert_eq!(g1_msm(&base, points, &scalars).unwrap(), naive_msm(&base, points, &scalars));
+    assert_eq!(g1_msm(&G1_INFINITY, points, &scalars).unwrap(), naive_msm(&G1_INFINITY, points, &scalars));
+}
+
+#[test]
+fn msm_of_zero_scalars_is_the_base() {
+    let base = VERIFYING_KEY.vk_ic[0];
+    let scalars = [[0u8; 32]; 7];
+    assert_eq!(g1_msm(&base, &VERIFYING_KEY.vk_ic[1..], &scalars).unwrap(), base);
+    assert_eq!(
+        g1_msm(&base, &VERIFYING_KEY.vk_ic[1..], &scalars[..6]),
+        Err(Groth16Error::InvalidPublicInputsLength)
+    );
+}
+
+/// A point on the G2 twist curve outside the prime-order subgroup, in the
+/// syscalls' big-endian `(x_c1, x_c0, y_c1, y_c0)` layout.
+fn g2_point_outside_subgroup() -> [u8; 128] {
+    let point = (1u64..)
+        .find_map(|i| {
+            let x = ark_bn254::Fq2::new(ark_bn254::Fq::from(i), ark_bn254::Fq::from(1u64));
+            ark_bn254::G2Affine::get_point_from_x_unchecked(x, true)
+        })
+        .filter(|point| !point.is_in_correct_subgroup_assuming_on_curve())
+        .unwrap();
+    let mut bytes = [0u8; 128];
+    for (chunk, coordinate) in bytes.chunks_mut(32).zip([point.x.c1, point.x.c0, point.y.c1, point.y.c0]) {
+        chunk.copy_from_slice(&coordinate.into_bigint().to_bytes_be());
+    }
+    bytes
+}
+
+#[test]
+fn proof_points_should_validate() {
+    assert_eq!(validate_g1_point(&PROOF_A), Ok(()));
+    assert_eq!(validate_g1_point(&PROOF_C), Ok(()));
+    assert_eq!(validate_g2_point(&PROOF_B), Ok(()));
+}
+
+#[test]
+fn off_curve_points_should_not_validate() {
+    let mut proof_c = PROOF_C;
+    proof_c[63] ^= 1;
+    assert_eq!(validate_g1_point(&proof_c), Err(Groth16Error::G1PointNotOnCurve));
+    assert_eq!(validate_g1_point(&[0u8; 64]), Err(Groth16Error::G1PointNotOnCurve));
+
+    let mut proof_b = PROOF_B;
+    proof_b[127] ^= 1;
+    assert_eq!(validate_g2_point(&proof_b), Err(Groth16Error::G2PointNotOnCurve));
+}
+
+#[test]
+fn g2_point_outside_subgroup_should_not_validate() {
+    assert_eq!(validate_g2_point(&g2_point_outside_subgroup()), Err(Groth16Error::G2PointNotInSubgroup));
+}
+
+#[test]
+fn verify_checks_points_but_verify_unchecked_does_not() {
+    let proof_a = negated_proof_a();
+    let proof_b = g2_point_outside_subgroup();
+    let mut verifier = Groth16Verifier::new(&proof_a, &proof_b, &PROOF_C, &PUBLIC_INPUTS, &VERIFYING_KEY).unwrap();
+    assert_eq!(verifier.verify(), Err(Groth16Error::G2PointNotInSubgroup));
+    assert_eq!(verifier.verify_unchecked(), Err(Groth16Error::ProofVerificationFailed));
+
+    let mut verifiers = [
+        Groth16Verifier::new(&proof_a, &PROOF_B, &PROOF_C, &PUBLIC_INPUTS, &VERIFYING_KEY).unwrap(),
+        Groth16Verifier::new(&proof_a, &proof_b, &PROOF_C, &PUBLIC_INPUTS, &VERIFYING_KEY).unwrap(),
+    ];
+    assert_eq!(Groth16Verifier::verify_batch(&mut verifiers), Err(Groth16Error::G2PointNotInSubgroup));
+}
@@ -0,0 +1,63 @@
+use light_hasher::{Hasher, Poseidon};
+use std::mem::MaybeUninit;
+use zkcash::errors::PoolError;
+use zkcash::{merkle_tree::MerkleTree, validate_inclusion, verify_inclusion, MerkleTreeAccount};
+
+fn tree() -> MerkleTreeAccount {
+    let mut uninit: MaybeUninit<MerkleTreeAccount> = MaybeUninit::uninit();
+    let mut account = unsafe {
+        let ptr = uninit.as_mut_ptr();
+        std::ptr::write_bytes(ptr, 0, 1);
+        (*ptr).height = 26;
+        (*ptr).root_history_size = 100;
+        uninit.assume_init()
+    };
+    MerkleTree::initialize::<Poseidon>(&mut account).unwrap();
+    account
+}
+
+fn leaf(i: u64) -> [u8; 32] {
+    Poseidon::hash(&i.to_be_bytes()).unwrap()
+}
+
+#[test]
+fn test_append_proof_verifies_against_its_root() {
+    let mut tree = tree();
+    for i in 0..5 {
+        let path = MerkleTree::append::<Poseidon>(leaf(i), &mut tree).unwrap();
+        assert!(verify_inclusion::<Poseidon>(leaf(i), i, &path, tree.root));
+        assert!(validate_inclusion::<Poseidon>(&tree, None, leaf(i), i, &path, tree.root).is_ok());
+    }
+}
+
+#[test]
+fn test_rejects_wrong_leaf_or_index() {
+    let mut tree = tree();
+    MerkleTree::append::<Poseidon>(leaf(0), &mut tree).unwrap();
+    let path = MerkleTree::append::<Poseidon>(leaf(1), &mut tree).unwrap();
+
+    assert!(!verify_inclusion::<Poseidon>(leaf(2), 1, &path, tree.root));
+    assert!(!verify_inclusion::<Poseidon>(leaf(1), 0, &path, tree.root));
+    // An index beyond the path's height can't be placed in the tree
+    assert!(!verify_inclusion::<Poseidon>(leaf(1), 1 << 26, &path, tree.root));
+    let err = validate_inclusion::<Poseidon>(&tree, None, leaf(1), 0, &path, tree.root).unwrap_err();
+    assert_eq!(err, PoolError::InvalidInclusionProof.into());
+}
+
+#[test]
+fn test_rejects_unknown_root() {
+    let mut tree = tree();
+    let path = MerkleTree::append::<Poseidon>(leaf(0), &mut tree).unwrap();
+    let err = validate_inclusion::<Poseidon>(&tree, None, leaf(0), 0, &path, leaf(9)).unwrap_err();
+    assert_eq!(err, PoolError::UnknownInclusionRoot.into());
+}
+
+#[test]
+fn test_rejects_unfilled_leaves() {
+    // The empty leaf, with its path of zero hashes, hashes to the empty root
+    let tree = tree();
+    let path: Vec<[u8; 32]> = (0..26).map(|level| tree.subtrees[level]).collect();
+    assert!(verify_inclusion::<Poseidon>([0u8; 32], 0, &path, tree.root));
+    let err = validate_inclusion::<Poseidon>(&tree, None, [0u8; 32], 0, &path, tree.root).unwrap_err();
+    assert_eq!(err, PoolError::InvalidInclusionProof.into());
+}
@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+use zkcash::errors::PoolError;
+use zkcash::utils::ExtDataFields;
+use zkcash::{validate_ext_data_inputs, InputLimits, SwapParams, CpiTarget, MAX_MEMO_LEN};
+
+const LIMITS: InputLimits = InputLimits { max_encrypted_output_len: 64, max_deposit_amount: 1_000_000 };
+
+fn fields() -> ExtDataFields<'static> {
+    ExtDataFields {
+        recipient: Pubkey::new_from_array([1u8; 32]),
+        ext_amount: -1_000,
+        encrypted_output1: b"encrypted_output_1_data",
+        encrypted_output2: b"encrypted_output_2_data",
+        fee: 10,
+        fee_recipient: Pubkey::new_from_array([2u8; 32]),
+        mint_address: Pubkey::new_from_array([3u8; 32]),
+        memo: &[],
+        expiry_slot: 0,
+        max_fee: 10,
+        relayer: None,
+        swap: None,
+        cpi: None,
+        foreign_recipient: None,
+        memo_cpi: false,
+    }
+}
+
+fn assert_err(ext_data: ExtDataFields, expected: PoolError) {
+    assert_eq!(validate_ext_data_inputs(&ext_data, &LIMITS).unwrap_err(), expected.into());
+}
+
+#[test]
+fn accepts_well_formed_inputs() {
+    assert!(validate_ext_data_inputs(&fields(), &LIMITS).is_ok());
+    let deposit = ExtDataFields { ext_amount: 1_000_000, fee: 0, recipient: Pubkey::default(), ..fields() };
+    assert!(validate_ext_data_inputs(&deposit, &LIMITS).is_ok());
+}
+
+#[test]
+fn rejects_ext_amount_out_of_range() {
+    assert_err(ExtDataFields { ext_amount: i64::MIN, ..fields() }, PoolError::ExtAmountOutOfRange);
+    assert_err(ExtDataFields { ext_amount: 1_000_001, fee: 0, ..fields() }, PoolError::ExtAmountOutOfRange);
+}
+
+#[test]
+fn rejects_fee_out_of_range() {
+    assert_err(ExtDataFields { ext_amount: i64::MIN + 1, fee: 2, ..fields() }, PoolError::FeeOutOfRange);
+    assert_err(ExtDataFields { ext_amount: 100, fee: 100, ..fields() }, PoolError::FeeOutOfRange);
+}
+
+#[test]
+fn rejects_bad_encrypted_outputs() {
+    assert_err(ExtDataFields { encrypted_output2: &[], ..fields() }, PoolError::EmptyEncryptedOutput);
+    let long = [0u8; 65];
+    assert_err(ExtDataFields { encrypted_output1: &long, ..fields() }, PoolError::EncryptedOutputTooLarge);
+}
+
+#[test]
+fn rejects_long_memo() {
+    let memo = [0u8; MAX_MEMO_LEN + 1];
+    assert_err(ExtDataFields { memo: &memo, ..fields() }, PoolError::MemoTooLong);
+}
+
+#[test]
+fn rejects_memo_cpi_without_a_utf8_withdrawal_memo() {
+    let memo_cpi = ExtDataFields { memo: b"104837261", memo_cpi: true, ..fields() };
+    assert!(validate_ext_data_inputs(&memo_cpi, &LIMITS).is_ok());
+
+    assert_err(ExtDataFields { memo: &[], ..memo_cpi }, PoolError::InvalidMemoCpi);
+    assert_err(ExtDataFields { memo: &[0xff, 0xfe], ..memo_cpi }, PoolError::InvalidMemoCpi);
+    let deposit = ExtDataFields { ext_amount: 1_000, fee: 0, ..memo_cpi };
+    assert_err(deposit, PoolError::InvalidMemoCpi);
+}
+
+#[test]
+fn rejects_withdrawal_to_default_recipient() {
+    assert_err(ExtDataFields { recipient: Pubkey::default(), ..fields() }, PoolError::InvalidRecipient);
+}
+
+#[test]
+fn rejects_conflicting_withdrawal_modes() {
+    let ext_data = ExtDataFields {
+        swap: Some(SwapParams::default()),
+        cpi: Some(CpiTarget::default()),
+        ..fields()
+    };
+    assert_err(ext_data, PoolError::ConflictingWithdrawalModes);
+}
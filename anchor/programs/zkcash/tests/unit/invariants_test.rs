@@ -0,0 +1,44 @@
+use zkcash::{BalanceAudit, InvariantReport, PoolStats};
+
+fn audit(deposited: u64, liquid: u64) -> BalanceAudit {
+    let stats = PoolStats {
+        total_deposited: deposited,
+        ..PoolStats::default()
+    };
+    BalanceAudit::new(&stats, liquid, 0).unwrap()
+}
+
+#[test]
+fn test_healthy_pool_passes() {
+    let report = InvariantReport::new(&audit(1_000, 1_000), 4, 26);
+    assert!(report.solvent && report.within_capacity && report.passed());
+    assert_eq!(report.liabilities, 1_000);
+    assert_eq!(report.capacity, 1 << 26);
+
+    // A surplus is not a failure
+    assert!(InvariantReport::new(&audit(1_000, 1_050), 4, 26).passed());
+}
+
+#[test]
+fn test_deficit_fails_solvency() {
+    let report = InvariantReport::new(&audit(1_000, 999), 4, 26);
+    assert!(!report.solvent);
+    assert!(report.within_capacity);
+    assert!(!report.passed());
+}
+
+#[test]
+fn test_next_index_past_capacity_fails() {
+    // A full tree is fine; one leaf past it is not
+    assert!(InvariantReport::new(&audit(0, 0), 1 << 4, 4).passed());
+
+    let report = InvariantReport::new(&audit(0, 0), (1 << 4) + 1, 4);
+    assert!(report.solvent);
+    assert!(!report.within_capacity);
+    assert!(!report.passed());
+}
+
+#[test]
+fn test_capacity_saturates_for_oversized_heights() {
+    assert_eq!(InvariantReport::new(&audit(0, 0), 0, 64).capacity, u64::MAX);
+}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::Pubkey;
+use zkcash::errors::PoolError;
+use zkcash::{leaf_page_index, validate_new_leaf_page, LeafPage, LEAF_PAGE_ACCOUNT_LEN, LEAVES_PER_PAGE};
+
+fn page(page_index: u64) -> Box<LeafPage> {
+    let mut page: Box<LeafPage> = Box::new(bytemuck::Zeroable::zeroed());
+    page.init(Pubkey::new_unique(), page_index);
+    page
+}
+
+#[test]
+fn test_page_fits_one_instruction() {
+    assert!(LEAF_PAGE_ACCOUNT_LEN <= 10 * 1024);
+    // Both outputs of a transact share a page
+    assert_eq!(LEAVES_PER_PAGE % 2, 0);
+}
+
+#[test]
+fn test_pages_fill_in_order() {
+    let mut page = page(2);
+    let first = 2 * LEAVES_PER_PAGE as u64;
+    assert_eq!(leaf_page_index(first), 2);
+    assert_eq!(leaf_page_index(first - 1), 1);
+
+    assert_eq!(page.push(first + 1, [1u8; 32]).unwrap_err(), PoolError::LeafPageOutOfOrder.into());
+    for i in 0..LEAVES_PER_PAGE as u64 {
+        page.push(first + i, [i as u8; 32]).unwrap();
+    }
+    assert_eq!(page.stored_leaves().len(), LEAVES_PER_PAGE);
+    assert_eq!(page.stored_leaves()[7], [7u8; 32]);
+    // Full
+    let next = first + LEAVES_PER_PAGE as u64;
+    assert_eq!(page.push(next, [1u8; 32]).unwrap_err(), PoolError::LeafPageOutOfOrder.into());
+}
+
+#[test]
+fn test_new_pages_start_ahead_of_the_tree() {
+    let per_page = LEAVES_PER_PAGE as u64;
+    assert!(validate_new_leaf_page(0, 0).is_ok());
+    assert!(validate_new_leaf_page(1, 0).is_ok());
+    // Too far ahead
+    assert!(validate_new_leaf_page(2, 0).is_err());
+    // The page being filled already has leaves it would miss
+    assert!(validate_new_leaf_page(0, 10).is_err());
+    assert!(validate_new_leaf_page(1, 10).is_ok());
+    assert!(validate_new_leaf_page(1, per_page).is_ok());
+    assert!(validate_new_leaf_page(u64::MAX, 0).is_err());
+}
@@ -0,0 +1,88 @@
+use anchor_lang::prelude::Pubkey;
+use zkcash::errors::PoolError;
+use zkcash::{validate_lst_mint, DepositLimits, LstConfig, LstPreset, MAX_LST_RATE_AGE};
+
+fn marinade_state(mint: &Pubkey, msol_price: u64) -> Vec<u8> {
+    let mut data = vec![0u8; 600];
+    data[8..40].copy_from_slice(mint.as_ref());
+    data[512..520].copy_from_slice(&msol_price.to_le_bytes());
+    data
+}
+
+fn stake_pool(mint: &Pubkey, total_lamports: u64, pool_token_supply: u64) -> Vec<u8> {
+    let mut data = vec![0u8; 400];
+    data[162..194].copy_from_slice(mint.as_ref());
+    data[258..266].copy_from_slice(&total_lamports.to_le_bytes());
+    data[266..274].copy_from_slice(&pool_token_supply.to_le_bytes());
+    data
+}
+
+fn config(rate: (u64, u64)) -> LstConfig {
+    let mut config = LstConfig {
+        max_per_transaction_lamports: 1_000,
+        max_per_slot_lamports: 5_000,
+        ..Default::default()
+    };
+    config.set_rate(rate, 100);
+    config
+}
+
+#[test]
+fn test_marinade_rate() {
+    let price = (1u64 << 32) * 5 / 4;
+    let data = marinade_state(&LstPreset::Msol.mint(), price);
+    assert_eq!(LstPreset::Msol.exchange_rate(&data).unwrap(), (price, 1 << 32));
+}
+
+#[test]
+fn test_stake_pool_rate() {
+    let data = stake_pool(&LstPreset::JitoSol.mint(), 1_200, 1_000);
+    assert_eq!(LstPreset::JitoSol.exchange_rate(&data).unwrap(), (1_200, 1_000));
+}
+
+#[test]
+fn test_rate_account_for_other_mint_rejected() {
+    let data = stake_pool(&Pubkey::new_unique(), 1_200, 1_000);
+    let err = LstPreset::JitoSol.exchange_rate(&data).unwrap_err();
+    assert_eq!(err, PoolError::InvalidLstRate.into());
+}
+
+#[test]
+fn test_rate_below_par_or_truncated_rejected() {
+    let mint = LstPreset::JitoSol.mint();
+    for data in [stake_pool(&mint, 999, 1_000), stake_pool(&mint, 0, 0), vec![0u8; 100]] {
+        assert_eq!(LstPreset::JitoSol.exchange_rate(&data).unwrap_err(), PoolError::InvalidLstRate.into());
+    }
+}
+
+#[test]
+fn test_mint_decimals_checked() {
+    let mut mint = vec![0u8; 82];
+    mint[44] = 9;
+    assert!(validate_lst_mint(LstPreset::Msol, &mint).is_ok());
+    mint[44] = 6;
+    assert_eq!(validate_lst_mint(LstPreset::Msol, &mint).unwrap_err(), PoolError::LstMintMismatch.into());
+}
+
+#[test]
+fn test_limits_follow_exchange_rate() {
+    let mut deposit_limits = DepositLimits::default();
+    let mut config = config((1, 1));
+    config.apply_limits(&mut deposit_limits);
+    assert_eq!((deposit_limits.max_per_transaction, deposit_limits.max_per_slot), (1_000, 5_000));
+
+    // At 1.25 SOL per token the same SOL limits allow fewer tokens
+    config.set_rate((5, 4), 200);
+    config.apply_limits(&mut deposit_limits);
+    assert_eq!((deposit_limits.max_per_transaction, deposit_limits.max_per_slot), (800, 4_000));
+    assert_eq!(config.lamports_value(800).unwrap(), 1_000);
+}
+
+#[test]
+fn test_rate_freshness() {
+    let config = config((5, 4));
+    assert!(config.validate_rate_fresh(100 + MAX_LST_RATE_AGE).is_ok());
+    let err = config.validate_rate_fresh(101 + MAX_LST_RATE_AGE).unwrap_err();
+    assert_eq!(err, PoolError::StaleLstRate.into());
+    assert_eq!(LstConfig::default().validate_rate_fresh(0).unwrap_err(), PoolError::StaleLstRate.into());
+}
@@ -0,0 +1,208 @@
+//! Known proof malleations, each of which must be rejected. A change to the
+//! verifier, the proof parser or the point validation that lets one through
+//! fails here with the class it reopened.
+//!
+//! Groth16 proofs are malleable by design, and one malleation does verify:
+//! negating both A and B. It is pinned below so that nothing in the program
+//! ever comes to rely on proof bytes being unique; replays are stopped by
+//! the nullifiers, which no malleation changes.
+
+use anchor_lang::prelude::*;
+use num_bigint::BigUint;
+use zkcash::errors::PoolError;
+use zkcash::groth16::{BN254_FQ_MODULUS_BE, BN254_FR_MODULUS_BE};
+use zkcash::utils::{verify_proof, VERIFYING_KEY};
+use zkcash::Proof;
+use super::fixtures_test::{load, to_proof};
+use super::proof_test::valid_proof;
+
+/// A second proof for the same circuit, over different public inputs.
+pub(super) fn deposit_proof() -> Proof {
+    to_proof(&load("deposit").proof)
+}
+
+fn rejects(proof: Proof, expected: PoolError) {
+    assert_eq!(verify_proof(proof, VERIFYING_KEY).unwrap_err(), expected.into());
+}
+
+pub(super) fn with_public_inputs(proof: &Proof, inputs: [[u8; 32]; 7]) -> Proof {
+    Proof {
+        root: inputs[0],
+        public_amount: inputs[1],
+        ext_data_hash: inputs[2],
+        input_nullifiers: [inputs[3], inputs[4]],
+        output_commitments: [inputs[5], inputs[6]],
+        ..proof.clone()
+    }
+}
+
+fn be_bytes(value: BigUint) -> [u8; 32] {
+    let bytes = value.to_bytes_be();
+    let mut padded = [0u8; 32];
+    padded[32 - bytes.len()..].copy_from_slice(&bytes);
+    padded
+}
+
+/// `value + modulus`: the same residue, encoded above the modulus.
+pub(super) fn plus_modulus(value: &[u8], modulus: &[u8; 32]) -> [u8; 32] {
+    be_bytes(BigUint::from_bytes_be(value) + BigUint::from_bytes_be(modulus))
+}
+
+/// `-value` in the base field, for a nonzero coordinate.
+fn negate_coordinate(value: &[u8]) -> [u8; 32] {
+    be_bytes(BigUint::from_bytes_be(&BN254_FQ_MODULUS_BE) - BigUint::from_bytes_be(value))
+}
+
+/// `-(x, y) = (x, -y)` on G1.
+pub(super) fn negate_g1(point: &[u8; 64]) -> [u8; 64] {
+    let mut negated = *point;
+    negated[32..].copy_from_slice(&negate_coordinate(&point[32..]));
+    negated
+}
+
+/// `-(x, y) = (x, -y)` on G2, in the `(x_c1, x_c0, y_c1, y_c0)` layout.
+pub(super) fn negate_g2(point: &[u8; 128]) -> [u8; 128] {
+    let mut negated = *point;
+    negated[64..96].copy_from_slice(&negate_coordinate(&point[64..96]));
+    negated[96..].copy_from_slice(&negate_coordinate(&point[96..]));
+    negated
+}
+
+/// Swaps two 32-byte limbs of a G2 point.
+pub(super) fn swap_limbs(point: &[u8; 128], i: usize, j: usize) -> [u8; 128] {
+    let mut swapped = *point;
+    swapped[i * 32..(i + 1) * 32].copy_from_slice(&point[j * 32..(j + 1) * 32]);
+    swapped[j * 32..(j + 1) * 32].copy_from_slice(&point[i * 32..(i + 1) * 32]);
+    swapped
+}
+
+#[test]
+fn test_unmodified_proofs_verify() {
+    assert!(verify_proof(valid_proof(), VERIFYING_KEY).is_ok());
+    assert!(verify_proof(deposit_proof(), VERIFYING_KEY).is_ok());
+}
+
+#[test]
+fn test_negated_a_rejected() {
+    for proof in [valid_proof(), deposit_proof()] {
+        rejects(Proof { proof_a: negate_g1(&proof.proof_a), ..proof }, PoolError::ProofPairingFailed);
+    }
+}
+
+#[test]
+fn test_negated_b_or_c_rejected() {
+    let proof = valid_proof();
+    rejects(Proof { proof_b: negate_g2(&proof.proof_b), ..proof.clone() }, PoolError::ProofPairingFailed);
+    rejects(Proof { proof_c: negate_g1(&proof.proof_c), ..proof }, PoolError::ProofPairingFailed);
+}
+
+/// e(-A, -B) = e(A, B), so this is the same proof to the pairing check.
+#[test]
+fn test_negated_a_and_b_verifies() {
+    let proof = valid_proof();
+    let malleated = Proof { proof_a: negate_g1(&proof.proof_a), proof_b: negate_g2(&proof.proof_b), ..proof.clone() };
+    assert_ne!(malleated.proof_a, proof.proof_a);
+    assert!(verify_proof(malleated.clone(), VERIFYING_KEY).is_ok());
+    assert_eq!(malleated.input_nullifiers, proof.input_nullifiers);
+}
+
+/// B's coordinates are Fq2 elements `c0 + c1 * u`; every reordering of its
+/// four limbs leaves the twist curve.
+#[test]
+fn test_swapped_b_limbs_rejected() {
+    let proof = valid_proof();
+    let swaps = [
+        (0, 1), // x_c1 <-> x_c0
+        (2, 3), // y_c1 <-> y_c0
+        (0, 2), // x_c1 <-> y_c1
+        (1, 3), // x_c0 <-> y_c0
+    ];
+    for (i, j) in swaps {
+        let proof_b = swap_limbs(&proof.proof_b, i, j);
+        rejects(Proof { proof_b, ..proof.clone() }, PoolError::ProofPointNotOnCurve);
+    }
+
+    let both = swap_limbs(&swap_limbs(&proof.proof_b, 0, 1), 2, 3);
+    rejects(Proof { proof_b: both, ..proof.clone() }, PoolError::ProofPointNotOnCurve);
+    let x_for_y = swap_limbs(&swap_limbs(&proof.proof_b, 0, 2), 1, 3);
+    rejects(Proof { proof_b: x_for_y, ..proof }, PoolError::ProofPointNotOnCurve);
+}
+
+#[test]
+fn test_public_inputs_from_another_proof_rejected() {
+    let proof = valid_proof();
+    let deposit = deposit_proof();
+    rejects(with_public_inputs(&proof, deposit.public_inputs()), PoolError::ProofPairingFailed);
+    rejects(with_public_inputs(&deposit, proof.public_inputs()), PoolError::ProofPairingFailed);
+
+    // One input at a time, so no single input goes unbound
+    for i in 0..7 {
+        let mut inputs = proof.public_inputs();
+        inputs[i] = deposit.public_inputs()[i];
+        rejects(with_public_inputs(&proof, inputs), PoolError::ProofPairingFailed);
+    }
+}
+
+#[test]
+fn test_permuted_public_inputs_rejected() {
+    let proof = valid_proof();
+    let permutations = [
+        (3, 4), // input nullifiers swapped
+        (5, 6), // output commitments swapped
+        (3, 5), // a nullifier passed off as a commitment
+        (0, 2), // root and ExtData hash
+    ];
+    for (i, j) in permutations {
+        let mut inputs = proof.public_inputs();
+        inputs.swap(i, j);
+        rejects(with_public_inputs(&proof, inputs), PoolError::ProofPairingFailed);
+    }
+}
+
+/// `x + r` is `x` to the circuit, so accepting it would give every input a
+/// second encoding and every nullifier a second spelling.
+#[test]
+fn test_public_input_plus_scalar_modulus_rejected() {
+    let proof = valid_proof();
+    for i in 0..7 {
+        let mut inputs = proof.public_inputs();
+        inputs[i] = plus_modulus(&inputs[i], &BN254_FR_MODULUS_BE);
+        let malleated = with_public_inputs(&proof, inputs);
+        assert_eq!(
+            Proof::try_from(malleated.try_to_vec().unwrap().as_slice()).unwrap_err(),
+            PoolError::PublicInputOutOfField.into()
+        );
+        rejects(malleated, PoolError::PublicInputOutOfField);
+    }
+}
+
+/// Coordinates `x + q` name the same point. Only x coordinates are shifted:
+/// arkworks reads flags from the top bits of y, which would muddle the case.
+#[test]
+fn test_point_coordinate_plus_base_modulus_rejected() {
+    let proof = valid_proof();
+    let shifted = |point: &[u8], limb: usize| plus_modulus(&point[limb * 32..(limb + 1) * 32], &BN254_FQ_MODULUS_BE);
+
+    let mut proof_a = proof.proof_a;
+    proof_a[..32].copy_from_slice(&shifted(&proof.proof_a, 0));
+    let mut proof_c = proof.proof_c;
+    proof_c[..32].copy_from_slice(&shifted(&proof.proof_c, 0));
+    let mut proof_b_c1 = proof.proof_b;
+    proof_b_c1[..32].copy_from_slice(&shifted(&proof.proof_b, 0));
+    let mut proof_b_c0 = proof.proof_b;
+    proof_b_c0[32..64].copy_from_slice(&shifted(&proof.proof_b, 1));
+
+    let cases = [
+        (Proof { proof_a, ..proof.clone() }, PoolError::InvalidProofEncoding),
+        (Proof { proof_c, ..proof.clone() }, PoolError::ProofPointNotOnCurve),
+        (Proof { proof_b: proof_b_c1, ..proof.clone() }, PoolError::ProofPointNotOnCurve),
+        (Proof { proof_b: proof_b_c0, ..proof }, PoolError::ProofPointNotOnCurve),
+    ];
+    for (malleated, expected) in cases {
+        assert_eq!(
+            Proof::try_from(malleated.try_to_vec().unwrap().as_slice()).unwrap_err(),
+            PoolError::InvalidProofEncoding.into()
+        );
+        rejects(malleated, expected);
+    }
+}
@@ -0,0 +1,120 @@
+use anchor_lang::prelude::*;
+use zkcash::errors::PoolError;
+use zkcash::utils::{calculate_complete_ext_data_hash, calculate_ext_data_hash_with_memo};
+use zkcash::{
+    post_withdrawal_memo, spl_memo_instruction, validate_memo, validate_memo_cpi, MAX_MEMO_LEN,
+    SPL_MEMO_PROGRAM_ID,
+};
+
+fn hash_with_memo(memo: &[u8]) -> [u8; 32] {
+    calculate_ext_data_hash_with_memo(
+        Pubkey::new_from_array([1u8; 32]),
+        -1_000,
+        b"encrypted_output_1_data",
+        b"encrypted_output_2_data",
+        10,
+        Pubkey::new_from_array([2u8; 32]),
+        Pubkey::new_from_array([3u8; 32]),
+        memo,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_empty_memo_keeps_the_existing_hash() {
+    let without_memo = calculate_complete_ext_data_hash(
+        Pubkey::new_from_array([1u8; 32]),
+        -1_000,
+        b"encrypted_output_1_data",
+        b"encrypted_output_2_data",
+        10,
+        Pubkey::new_from_array([2u8; 32]),
+        Pubkey::new_from_array([3u8; 32]),
+    )
+    .unwrap();
+
+    assert_eq!(hash_with_memo(&[]), without_memo);
+}
+
+#[test]
+fn test_memo_is_bound_by_the_hash() {
+    assert_ne!(hash_with_memo(b"123456"), hash_with_memo(&[]));
+    assert_ne!(hash_with_memo(b"123456"), hash_with_memo(b"123457"));
+    // Length-prefixed, so a trailing zero byte is a different memo
+    assert_ne!(hash_with_memo(b"1"), hash_with_memo(b"1\0"));
+}
+
+#[test]
+fn test_memo_is_serialized_after_mint_address() {
+    #[derive(AnchorSerialize)]
+    struct ExtDataWithMemo {
+        recipient: Pubkey,
+        ext_amount: i64,
+        encrypted_output1: Vec<u8>,
+        encrypted_output2: Vec<u8>,
+        fee: u64,
+        fee_recipient: Pubkey,
+        mint_address: Pubkey,
+        memo: Vec<u8>,
+    }
+
+    let mut serialized = Vec::new();
+    ExtDataWithMemo {
+        recipient: Pubkey::new_from_array([1u8; 32]),
+        ext_amount: -1_000,
+        encrypted_output1: b"encrypted_output_1_data".to_vec(),
+        encrypted_output2: b"encrypted_output_2_data".to_vec(),
+        fee: 10,
+        fee_recipient: Pubkey::new_from_array([2u8; 32]),
+        mint_address: Pubkey::new_from_array([3u8; 32]),
+        memo: b"dest-tag-42".to_vec(),
+    }
+    .serialize(&mut serialized)
+    .unwrap();
+
+    assert_eq!(
+        hash_with_memo(b"dest-tag-42"),
+        anchor_lang::solana_program::hash::hash(&serialized).to_bytes()
+    );
+}
+
+#[test]
+fn test_validate_memo() {
+    assert!(validate_memo(&[]).is_ok());
+    assert!(validate_memo(&[7u8; MAX_MEMO_LEN]).is_ok());
+
+    let err = validate_memo(&[7u8; MAX_MEMO_LEN + 1]).unwrap_err();
+    assert_eq!(err, PoolError::MemoTooLong.into());
+}
+
+#[test]
+fn test_validate_memo_cpi() {
+    assert!(validate_memo_cpi(b"104837261", true, -1_000).is_ok());
+    // Without the flag the memo is only hashed, whatever it holds
+    assert!(validate_memo_cpi(&[0xff], false, 1_000).is_ok());
+
+    for (memo, ext_amount) in [(&b""[..], -1_000), (&[0xffu8][..], -1_000), (&b"104837261"[..], 1_000)] {
+        let err = validate_memo_cpi(memo, true, ext_amount).unwrap_err();
+        assert_eq!(err, PoolError::InvalidMemoCpi.into());
+    }
+}
+
+#[test]
+fn test_spl_memo_instruction_carries_the_memo_alone() {
+    let instruction = spl_memo_instruction(b"104837261");
+    assert_eq!(instruction.program_id, SPL_MEMO_PROGRAM_ID);
+    assert!(instruction.accounts.is_empty());
+    assert_eq!(instruction.data, b"104837261");
+}
+
+#[test]
+fn test_post_withdrawal_memo_rejects_other_programs() {
+    let key = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let mut lamports = 0;
+    let mut data = [];
+    let impostor = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, true, 0);
+
+    let err = post_withdrawal_memo(&impostor, b"104837261").unwrap_err();
+    assert_eq!(err, PoolError::InvalidMemoProgram.into());
+}
@@ -0,0 +1,149 @@
+use light_hasher::{Hasher, Poseidon};
+use proptest::prelude::*;
+use std::mem::MaybeUninit;
+use zkcash::{merkle_tree::MerkleTree, MerkleTreeAccount};
+
+fn create_test_account(height: u8, root_history_size: u8) -> MerkleTreeAccount {
+    let mut uninit: MaybeUninit<MerkleTreeAccount> = MaybeUninit::uninit();
+
+    unsafe {
+        let ptr = uninit.as_mut_ptr();
+        std::ptr::write_bytes(ptr, 0, 1); // Zero-initialize the entire struct
+
+        (*ptr).height = height;
+        (*ptr).root_history_size = root_history_size;
+
+        uninit.assume_init()
+    }
+}
+
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    Poseidon::hashv(&[left, right]).unwrap()
+}
+
+/// Reference tree that rebuilds every level from the full leaf list, sharing
+/// nothing with the filled-subtree logic under test except the hash function.
+struct ReferenceTree {
+    height: usize,
+    zeros: Vec<[u8; 32]>,
+    leaves: Vec<[u8; 32]>,
+}
+
+impl ReferenceTree {
+    fn new(height: usize) -> Self {
+        // Derived here rather than taken from Poseidon::zero_bytes()
+        let mut zeros = vec![[0u8; 32]];
+        for level in 0..height {
+            zeros.push(hash_pair(&zeros[level], &zeros[level]));
+        }
+        ReferenceTree { height, zeros, leaves: Vec::new() }
+    }
+
+    fn levels(&self) -> Vec<Vec<[u8; 32]>> {
+        let mut levels = vec![self.leaves.clone()];
+        for level in 0..self.height {
+            let next = levels[level]
+                .chunks(2)
+                .map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(&self.zeros[level])))
+                .collect();
+            levels.push(next);
+        }
+        levels
+    }
+
+    /// Root plus the sibling hashes from the leaf at `index` up to it.
+    fn root_and_path(&self, index: usize) -> ([u8; 32], Vec<[u8; 32]>) {
+        let levels = self.levels();
+        let root = levels[self.height].first().copied().unwrap_or(self.zeros[self.height]);
+        let path = (0..self.height)
+            .map(|level| {
+                let sibling = (index >> level) ^ 1;
+                levels[level].get(sibling).copied().unwrap_or(self.zeros[level])
+            })
+            .collect();
+        (root, path)
+    }
+
+    fn root(&self) -> [u8; 32] {
+        self.root_and_path(0).0
+    }
+}
+
+// Leaves must be canonical field elements; a zero top byte keeps them below the modulus
+fn leaf_strategy() -> impl Strategy<Value = [u8; 32]> {
+    any::<[u8; 32]>().prop_map(|mut leaf| {
+        leaf[0] = 0;
+        leaf
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn test_append_matches_reference_tree(leaves in prop::collection::vec(leaf_strategy(), 1..24)) {
+        let mut account = create_test_account(26, 100);
+        MerkleTree::initialize::<Poseidon>(&mut account).unwrap();
+        let mut reference = ReferenceTree::new(26);
+        prop_assert_eq!(account.root, reference.root());
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = MerkleTree::append::<Poseidon>(*leaf, &mut account).unwrap();
+            reference.leaves.push(*leaf);
+
+            // Root and the returned path must agree with a full rebuild after every append
+            let (root, path) = reference.root_and_path(index);
+            prop_assert_eq!(account.root, root);
+            prop_assert_eq!(proof, path);
+            prop_assert_eq!(account.next_index, index as u64 + 1);
+        }
+    }
+
+    #[test]
+    fn test_small_tree_fills_to_capacity(height in 1u8..=5, leaves in prop::collection::vec(leaf_strategy(), 32)) {
+        let capacity = 1usize << height;
+        let mut account = create_test_account(height, 100);
+        MerkleTree::initialize::<Poseidon>(&mut account).unwrap();
+        let mut reference = ReferenceTree::new(height as usize);
+
+        // Fill every slot, including the rightmost path
+        for leaf in &leaves[..capacity] {
+            MerkleTree::append::<Poseidon>(*leaf, &mut account).unwrap();
+            reference.leaves.push(*leaf);
+            prop_assert_eq!(account.root, reference.root());
+        }
+
+        // A full tree rejects the next leaf without touching state
+        let root = account.root;
+        prop_assert!(MerkleTree::append::<Poseidon>([1u8; 32], &mut account).is_err());
+        prop_assert_eq!(account.root, root);
+        prop_assert_eq!(account.next_index, capacity as u64);
+    }
+
+    #[test]
+    fn test_root_history_window(
+        root_history_size in 1u8..=100,
+        leaves in prop::collection::vec(leaf_strategy(), 1..120),
+    ) {
+        // History bookkeeping doesn't depend on height, so keep hashing cheap
+        let size = root_history_size as usize;
+        let mut account = create_test_account(8, root_history_size);
+        MerkleTree::initialize::<Poseidon>(&mut account).unwrap();
+
+        let mut roots = vec![account.root];
+        for leaf in &leaves {
+            MerkleTree::append::<Poseidon>(*leaf, &mut account).unwrap();
+            roots.push(account.root);
+
+            // The current root always sits at root_index
+            prop_assert_eq!(account.root_index as usize, (roots.len() - 1) % size);
+            prop_assert_eq!(account.root_history[account.root_index as usize], account.root);
+        }
+
+        // Exactly the last `size` roots stay known once the ring buffer wraps
+        let known_from = roots.len().saturating_sub(size);
+        for (i, root) in roots.iter().enumerate() {
+            prop_assert_eq!(MerkleTree::is_known_root(&account, *root), i >= known_from, "root {} of {}", i, roots.len());
+        }
+    }
+}
@@ -250,4 +250,79 @@ fn test_root_history_wraparound() {
     // But the current root should be known
     assert!(MerkleTree::is_known_root(&account, account.root), 
            "Current root should be known");
-}
\ No newline at end of file
+}
+#[test]
+fn test_layout_matches_account() {
+    use std::mem::{offset_of, size_of_val};
+    use zkcash::merkle_tree::layout;
+
+    let account = create_test_account();
+    assert_eq!(layout::AUTHORITY, 8 + offset_of!(MerkleTreeAccount, authority));
+    assert_eq!(layout::NEXT_INDEX, 8 + offset_of!(MerkleTreeAccount, next_index));
+    assert_eq!(layout::SUBTREES, 8 + offset_of!(MerkleTreeAccount, subtrees));
+    assert_eq!(layout::ROOT, 8 + offset_of!(MerkleTreeAccount, root));
+    assert_eq!(layout::ROOT_HISTORY, 8 + offset_of!(MerkleTreeAccount, root_history));
+    assert_eq!(layout::ROOT_INDEX, 8 + offset_of!(MerkleTreeAccount, root_index));
+    assert_eq!(layout::MAX_DEPOSIT_AMOUNT, 8 + offset_of!(MerkleTreeAccount, max_deposit_amount));
+    assert_eq!(layout::HEIGHT, 8 + offset_of!(MerkleTreeAccount, height));
+    assert_eq!(32 * layout::SUBTREE_SLOTS, size_of_val(&account.subtrees));
+    assert_eq!(32 * layout::ROOT_HISTORY_SLOTS, size_of_val(&account.root_history));
+}
+
+#[test]
+fn test_filled_subtrees_resume_appends() {
+    let mut account = create_test_account();
+    MerkleTree::initialize::<Poseidon>(&mut account).unwrap();
+    let zero_hashes = MerkleTree::zero_hashes::<Poseidon>(26).unwrap();
+    assert_eq!(zero_hashes.len(), 27);
+    assert_eq!(MerkleTree::filled_subtrees(&account), &zero_hashes[..26]);
+    assert_eq!(account.root, zero_hashes[26]);
+
+    for i in 0..3u64 {
+        let leaf = Poseidon::hash(&i.to_be_bytes()).unwrap();
+        MerkleTree::append::<Poseidon>(leaf, &mut account).unwrap();
+    }
+
+    // A copy holding only the filled subtrees and next_index reaches the
+    // same root as the original
+    let mut resumed = create_test_account();
+    resumed.subtrees[..26].copy_from_slice(MerkleTree::filled_subtrees(&account));
+    resumed.next_index = account.next_index;
+    let leaf = Poseidon::hash(&3u64.to_be_bytes()).unwrap();
+    MerkleTree::append::<Poseidon>(leaf, &mut account).unwrap();
+    MerkleTree::append::<Poseidon>(leaf, &mut resumed).unwrap();
+    assert_eq!(resumed.root, account.root);
+
+    assert!(MerkleTree::zero_hashes::<Poseidon>(64).is_err());
+}
+
+#[test]
+fn test_leaf_capacity() {
+    assert_eq!(MerkleTree::leaf_capacity(0).unwrap(), 1);
+    assert_eq!(MerkleTree::leaf_capacity(26).unwrap(), 1 << 26);
+    assert_eq!(MerkleTree::leaf_capacity(63).unwrap(), 1 << 63);
+    // 2^64 doesn't fit, where a plain shift would panic or wrap
+    assert!(MerkleTree::leaf_capacity(64).is_err());
+    assert!(MerkleTree::leaf_capacity(usize::MAX).is_err());
+}
+
+#[test]
+fn test_next_root_index() {
+    assert_eq!(MerkleTree::next_root_index(0, 100).unwrap(), 1);
+    assert_eq!(MerkleTree::next_root_index(99, 100).unwrap(), 0);
+    assert_eq!(MerkleTree::next_root_index(0, 1).unwrap(), 0);
+    assert!(MerkleTree::next_root_index(0, 0).is_err());
+    assert!(MerkleTree::next_root_index(u64::MAX, 100).is_err());
+}
+
+#[test]
+fn test_history_contains_only_live_roots() {
+    let history = [[1u8; 32], [2u8; 32], [3u8; 32], [4u8; 32]];
+    for root_index in 0..3 {
+        assert!(MerkleTree::history_contains(&history, 3, root_index, [1u8; 32]));
+        assert!(MerkleTree::history_contains(&history, 3, root_index, [3u8; 32]));
+        // Past the history size, so no longer live
+        assert!(!MerkleTree::history_contains(&history, 3, root_index, [4u8; 32]));
+    }
+    assert!(!MerkleTree::history_contains(&[[0u8; 32]; 4], 4, 0, [0u8; 32]));
+}
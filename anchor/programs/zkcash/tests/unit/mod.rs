@@ -1,3 +1,58 @@
 mod merkle_tree_test; 
+mod merkle_tree_fuzz_test;
 mod utils_test;
-mod groth16_test;
\ No newline at end of file
+mod groth16_test;
+mod fixtures_test;
+mod zero_hashes_test;
+mod stats_test;
+mod circuit_breaker_test;
+mod timelock_test;
+mod versioning_test;
+mod registry_test;
+mod deposit_limits_test;
+mod memo_test;
+mod ext_data_version_test;
+mod encrypted_output_test;
+mod deposit_queue_test;
+mod concurrent_merkle_tree_test;
+mod canopy_test;
+mod transact_result_test;
+mod fee_recipient_test;
+mod treasury_yield_test;
+mod lst_test;
+mod swap_test;
+mod withdraw_cpi_test;
+mod wormhole_test;
+mod audit_log_test;
+mod proof_test;
+mod fee_schedule_test;
+mod fee_exemption_test;
+mod relayer_test;
+mod withdrawal_queue_test;
+mod submission_commitment_test;
+mod nullifier_seeds_test;
+mod nullifier_bloom_test;
+mod balance_audit_test;
+mod rescue_test;
+mod tree_snapshot_test;
+mod root_slots_test;
+mod root_policy_test;
+mod root_history_test;
+mod inclusion_test;
+mod anonymity_test;
+mod vk_registry_test;
+mod input_validation_test;
+mod transact_data_test;
+mod circuit_version_test;
+mod tree_storage_test;
+mod leaf_pages_test;
+mod root_checkpoints_test;
+mod root_attestation_test;
+mod roles_test;
+mod fee_vault_test;
+mod invariants_test;
+#[cfg(feature = "fuzzing")]
+mod fuzzing_test;
+mod malleability_test;
+mod verifier_parity_test;
+mod token_pool_test;
\ No newline at end of file
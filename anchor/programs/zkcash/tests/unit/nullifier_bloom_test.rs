@@ -0,0 +1,39 @@
+use zkcash::{nullifier_bloom_indices, NullifierBloomFilter, NULLIFIER_BLOOM_BYTES, NULLIFIER_BLOOM_HASHES};
+
+fn empty_filter() -> NullifierBloomFilter {
+    NullifierBloomFilter {
+        tree_account: Default::default(),
+        inserted: 0,
+        bits: [0; NULLIFIER_BLOOM_BYTES],
+    }
+}
+
+#[test]
+fn test_indices_are_the_low_u16s() {
+    let mut nullifier = [0u8; 32];
+    nullifier[24..].copy_from_slice(&[0x00, 0x04, 0x00, 0x03, 0x00, 0x02, 0xff, 0xff]);
+    assert_eq!(nullifier_bloom_indices(&nullifier), [0xffff, 2, 3, 4]);
+    assert_eq!(NULLIFIER_BLOOM_HASHES * 16, 64);
+}
+
+#[test]
+fn test_inserted_nullifiers_are_found() {
+    let mut filter = empty_filter();
+    let nullifiers: Vec<[u8; 32]> = (0..100u8).map(|i| [i.wrapping_mul(37); 32]).collect();
+    for nullifier in &nullifiers {
+        filter.insert(nullifier);
+    }
+    assert_eq!(filter.inserted, 100);
+    assert!(nullifiers.iter().all(|nullifier| filter.might_contain(nullifier)));
+}
+
+#[test]
+fn test_empty_filter_contains_nothing() {
+    let filter = empty_filter();
+    assert!(!filter.might_contain(&[0u8; 32]));
+    assert!(!filter.might_contain(&[0xffu8; 32]));
+
+    let mut filter = empty_filter();
+    filter.insert(&[1u8; 32]);
+    assert!(!filter.might_contain(&[2u8; 32]));
+}
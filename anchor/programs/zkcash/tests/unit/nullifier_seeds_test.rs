@@ -0,0 +1,71 @@
+use anchor_lang::prelude::{AccountInfo, Pubkey};
+use zkcash::errors::PoolError;
+use zkcash::{check_nullifier_unspent, NullifierSeedConfig, NullifierSeedVersion, MIN_NULLIFIER_MIGRATION_SLOTS};
+
+#[test]
+fn test_v0_matches_deployed_derivation() {
+    let nullifier = [5u8; 32];
+    let tree = Pubkey::new_unique();
+    let (address, _) = NullifierSeedVersion::V0.address(1, &tree, &nullifier);
+    let expected = Pubkey::find_program_address(&[b"nullifier1", &nullifier], &zkcash::ID).0;
+    assert_eq!(address, expected);
+    // v0 ignores the tree
+    assert_eq!(NullifierSeedVersion::V0.address(1, &Pubkey::new_unique(), &nullifier).0, expected);
+}
+
+#[test]
+fn test_v1_is_scoped_to_the_tree_not_the_slot() {
+    let nullifier = [5u8; 32];
+    let tree = Pubkey::new_unique();
+    let (address, _) = NullifierSeedVersion::V1.address(0, &tree, &nullifier);
+    assert_eq!(NullifierSeedVersion::V1.address(1, &tree, &nullifier).0, address);
+    assert_ne!(NullifierSeedVersion::V1.address(0, &Pubkey::new_unique(), &nullifier).0, address);
+    assert_ne!(NullifierSeedVersion::V0.address(0, &tree, &nullifier).0, address);
+}
+
+#[test]
+fn test_both_versions_checked_during_migration() {
+    let mut config = NullifierSeedConfig::default();
+    assert_eq!(config.versions_to_check(0), vec![NullifierSeedVersion::V0]);
+
+    config.begin_migration(NullifierSeedVersion::V1, MIN_NULLIFIER_MIGRATION_SLOTS, 100).unwrap();
+    let end = 100 + MIN_NULLIFIER_MIGRATION_SLOTS;
+    assert_eq!(config.migration_end_slot, end);
+    assert_eq!(
+        config.versions_to_check(end - 1),
+        vec![NullifierSeedVersion::V1, NullifierSeedVersion::V0]
+    );
+    assert_eq!(config.versions_to_check(end), vec![NullifierSeedVersion::V1]);
+}
+
+#[test]
+fn test_rejects_invalid_migrations() {
+    let mut config = NullifierSeedConfig::default();
+    let err = config.begin_migration(NullifierSeedVersion::V1, MIN_NULLIFIER_MIGRATION_SLOTS - 1, 0).unwrap_err();
+    assert_eq!(err, PoolError::InvalidNullifierMigration.into());
+    let err = config.begin_migration(NullifierSeedVersion::V0, MIN_NULLIFIER_MIGRATION_SLOTS, 0).unwrap_err();
+    assert_eq!(err, PoolError::InvalidNullifierMigration.into());
+
+    config.begin_migration(NullifierSeedVersion::V1, MIN_NULLIFIER_MIGRATION_SLOTS, 0).unwrap();
+    // No second migration while the first is open
+    let err = config.begin_migration(NullifierSeedVersion::V0, MIN_NULLIFIER_MIGRATION_SLOTS, 1).unwrap_err();
+    assert_eq!(err, PoolError::InvalidNullifierMigration.into());
+}
+
+#[test]
+fn test_spent_nullifier_is_rejected() {
+    let (nullifier, root) = ([5u8; 32], [6u8; 32]);
+    let key = Pubkey::new_unique();
+    let system = Pubkey::default();
+    let (mut lamports, mut data) = (0u64, Vec::<u8>::new());
+
+    let unused = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &system, false, 0);
+    assert!(check_nullifier_unspent(&unused, &nullifier, &root).is_ok());
+
+    let (mut lamports, mut data) = (890_880u64, Vec::<u8>::new());
+    let spent = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &zkcash::ID, false, 0);
+    assert_eq!(
+        check_nullifier_unspent(&spent, &nullifier, &root).unwrap_err(),
+        PoolError::NullifierAlreadySpent.into()
+    );
+}
@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use ark_ff::PrimeField;
+use num_bigint::BigUint;
+use zkcash::errors::PoolError;
+use zkcash::{Proof, PROOF_LEN};
+use super::utils_test::{PROOF_A, PROOF_B, PROOF_C, PUBLIC_INPUTS};
+
+pub(super) fn valid_proof() -> Proof {
+    Proof {
+        root: PUBLIC_INPUTS[0],
+        public_amount: PUBLIC_INPUTS[1],
+        ext_data_hash: PUBLIC_INPUTS[2],
+        input_nullifiers: [PUBLIC_INPUTS[3], PUBLIC_INPUTS[4]],
+        output_commitments: [PUBLIC_INPUTS[5], PUBLIC_INPUTS[6]],
+        proof_a: PROOF_A,
+        proof_b: PROOF_B,
+        proof_c: PROOF_C,
+    }
+}
+
+#[test]
+fn test_valid_proof_round_trips() {
+    let bytes = valid_proof().try_to_vec().unwrap();
+    assert_eq!(bytes.len(), PROOF_LEN);
+
+    let proof = Proof::try_from(bytes.as_slice()).unwrap();
+    assert_eq!(proof.public_inputs(), PUBLIC_INPUTS);
+    assert_eq!(proof.proof_b, PROOF_B);
+}
+
+#[test]
+fn test_rejects_wrong_length() {
+    let bytes = valid_proof().try_to_vec().unwrap();
+    let err = Proof::try_from(&bytes[..PROOF_LEN - 1]).err().unwrap();
+    assert_eq!(err, PoolError::InvalidProofDataLength.into());
+}
+
+#[test]
+fn test_rejects_point_off_the_curve() {
+    let mut proof = valid_proof();
+    proof.proof_c[63] ^= 1;
+    let err = Proof::try_from(proof.try_to_vec().unwrap().as_slice()).err().unwrap();
+    assert_eq!(err, PoolError::InvalidProofEncoding.into());
+}
+
+#[test]
+fn test_rejects_g2_coordinate_outside_the_field() {
+    let mut proof = valid_proof();
+    proof.proof_b[..32].copy_from_slice(&[0xff; 32]);
+    assert_eq!(proof.validate().unwrap_err(), PoolError::InvalidProofEncoding.into());
+}
+
+#[test]
+fn test_rejects_public_input_outside_the_field() {
+    let mut proof = valid_proof();
+    proof.input_nullifiers[1] = BigUint::from(ark_bn254::Fr::MODULUS).to_bytes_be().try_into().unwrap();
+    assert_eq!(proof.validate().unwrap_err(), PoolError::PublicInputOutOfField.into());
+}
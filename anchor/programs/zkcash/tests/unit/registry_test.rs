@@ -0,0 +1,72 @@
+use anchor_lang::prelude::Pubkey;
+use zkcash::utils::SOL_ADDRESS;
+use zkcash::errors::PoolError;
+use zkcash::{validate_creation_fee, PoolRegistry, PoolStatus, MAX_REGISTERED_POOLS, MIN_POOL_CREATION_FEE};
+
+#[test]
+fn test_register_adds_active_entry() {
+    let mut registry = PoolRegistry::default();
+    let tree = Pubkey::new_unique();
+    let config = Pubkey::new_unique();
+    registry.register(SOL_ADDRESS, tree, config).unwrap();
+
+    let entry = registry.find(&tree).unwrap();
+    assert_eq!(entry.mint, SOL_ADDRESS);
+    assert_eq!(entry.config, config);
+    assert_eq!(entry.status, PoolStatus::Active);
+    assert_eq!(registry.active_pool(&SOL_ADDRESS), Some(entry));
+}
+
+#[test]
+fn test_register_rejects_duplicate_tree() {
+    let mut registry = PoolRegistry::default();
+    let tree = Pubkey::new_unique();
+    registry.register(SOL_ADDRESS, tree, Pubkey::new_unique()).unwrap();
+
+    assert!(registry.register(Pubkey::new_unique(), tree, Pubkey::new_unique()).is_err());
+    assert_eq!(registry.pools.len(), 1);
+}
+
+#[test]
+fn test_register_rejects_when_full() {
+    let mut registry = PoolRegistry::default();
+    for _ in 0..MAX_REGISTERED_POOLS {
+        assert!(!registry.is_full());
+        registry.register(Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()).unwrap();
+    }
+
+    assert!(registry.is_full());
+    assert!(registry.register(Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()).is_err());
+    assert_eq!(registry.pools.len(), MAX_REGISTERED_POOLS);
+}
+
+#[test]
+fn test_retired_pool_is_not_active() {
+    let mut registry = PoolRegistry::default();
+    let old_tree = Pubkey::new_unique();
+    let new_tree = Pubkey::new_unique();
+    registry.register(SOL_ADDRESS, old_tree, Pubkey::new_unique()).unwrap();
+    registry.set_status(&old_tree, PoolStatus::Retired).unwrap();
+    assert!(registry.active_pool(&SOL_ADDRESS).is_none());
+
+    // Rotation: the new tree for the same mint becomes the active one
+    registry.register(SOL_ADDRESS, new_tree, Pubkey::new_unique()).unwrap();
+    assert_eq!(registry.active_pool(&SOL_ADDRESS).unwrap().tree_account, new_tree);
+    assert_eq!(registry.find(&old_tree).unwrap().status, PoolStatus::Retired);
+}
+
+#[test]
+fn test_set_status_rejects_unknown_pool() {
+    let mut registry = PoolRegistry::default();
+    assert!(registry.set_status(&Pubkey::new_unique(), PoolStatus::Paused).is_err());
+}
+
+#[test]
+fn test_creation_fee_has_a_floor() {
+    assert_eq!(validate_creation_fee(0).unwrap_err(), PoolError::CreationFeeTooLow.into());
+    assert_eq!(
+        validate_creation_fee(MIN_POOL_CREATION_FEE - 1).unwrap_err(),
+        PoolError::CreationFeeTooLow.into()
+    );
+    assert!(validate_creation_fee(MIN_POOL_CREATION_FEE).is_ok());
+}
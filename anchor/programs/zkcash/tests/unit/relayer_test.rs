@@ -0,0 +1,119 @@
+use anchor_lang::prelude::Pubkey;
+use zkcash::errors::PoolError;
+use zkcash::utils::MAX_FEE_RATE;
+use zkcash::{
+    relayer_fee_premium, validate_relayer_fee_rate, FeeSchedule, FeeSplit, FeeTier, RelayerAccount, MIN_RELAYER_BOND,
+    RELAYER_UNBONDING_SLOTS,
+};
+
+fn relayer(withdrawal_fee_rate: u16) -> RelayerAccount {
+    RelayerAccount {
+        relayer: Pubkey::new_unique(),
+        withdrawal_fee_rate,
+        bond: MIN_RELAYER_BOND,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_relayer_premium_is_its_rate_above_the_global_rate() {
+    assert_eq!(relayer_fee_premium(None, 25), 0);
+    assert_eq!(relayer_fee_premium(Some(&relayer(25)), 25), 0);
+    assert_eq!(relayer_fee_premium(Some(&relayer(40)), 25), 15);
+    // A rate the global rate has since risen past earns nothing extra
+    assert_eq!(relayer_fee_premium(Some(&relayer(40)), 50), 0);
+}
+
+#[test]
+fn test_premium_goes_to_the_relayer_not_the_protocol() {
+    let schedule = FeeSchedule {
+        base_fee: 5_000,
+        ..Default::default()
+    };
+    let premium = relayer_fee_premium(Some(&relayer(40)), 25);
+    // 0.40% of 1_000_000: 0.25% for the protocol, 0.15% and the base fee for the relayer
+    assert_eq!(
+        schedule.expected_split(-1_000_000, 0, 25, premium).unwrap(),
+        FeeSplit { protocol: 2_500, relayer: 1_500 + 5_000 }
+    );
+    assert!(schedule.validate_fee(-1_000_000, 9_000, 0, 25, premium, 0).is_ok());
+    assert!(schedule.validate_fee(-1_000_000, 8_999, 0, 25, premium, 0).is_err());
+    assert_eq!(
+        schedule.split_fee(-1_000_000, 9_000, 0, 25).unwrap(),
+        FeeSplit { protocol: 2_500, relayer: 6_500 }
+    );
+    // Deposits owe no premium
+    assert_eq!(schedule.expected_fee(1_000_000, 25, 25, premium).unwrap(), 2_500);
+
+    // Tiers lower the protocol's cut only
+    let schedule = FeeSchedule {
+        tiers: vec![FeeTier { min_amount: 1_000_000, fee_rate: 5 }],
+        ..Default::default()
+    };
+    assert_eq!(
+        schedule.expected_split(-1_000_000, 0, 25, premium).unwrap(),
+        FeeSplit { protocol: 500, relayer: 1_500 }
+    );
+}
+
+#[test]
+fn test_cap_takes_the_premium_before_the_protocol_cut() {
+    let schedule = FeeSchedule {
+        base_fee: 5_000,
+        fee_cap: 1_000_000,
+        ..Default::default()
+    };
+    // 0.25% of 100 SOL is 0.25 SOL, far above the cap
+    let split = schedule.expected_split(-100_000_000_000, 0, 25, 15).unwrap();
+    assert_eq!(split, FeeSplit { protocol: 995_000, relayer: 5_000 });
+    // Below the cap the premium is paid in full
+    let split = schedule.expected_split(-100_000_000, 0, 25, 15).unwrap();
+    assert_eq!(split, FeeSplit { protocol: 250_000, relayer: 150_000 + 5_000 });
+    // And shrinks to what is left of it above
+    let split = schedule.expected_split(-300_000_000, 0, 25, 15).unwrap();
+    assert_eq!(split, FeeSplit { protocol: 750_000, relayer: 245_000 + 5_000 });
+}
+
+#[test]
+fn test_validate_relayer_fee_rate() {
+    assert!(validate_relayer_fee_rate(25, 25).is_ok());
+    assert!(validate_relayer_fee_rate(MAX_FEE_RATE, 25).is_ok());
+    assert!(validate_relayer_fee_rate(0, 0).is_ok());
+    for rate in [0, 24] {
+        assert_eq!(validate_relayer_fee_rate(rate, 25).unwrap_err(), PoolError::RelayerFeeRateBelowGlobal.into());
+    }
+    assert_eq!(
+        validate_relayer_fee_rate(MAX_FEE_RATE + 1, 25).unwrap_err(),
+        PoolError::WithdrawalFeeRateTooHigh.into()
+    );
+}
+
+#[test]
+fn test_unbonded_or_unbonding_relayer_earns_no_premium() {
+    let mut underbonded = relayer(40);
+    underbonded.bond = MIN_RELAYER_BOND - 1;
+    assert_eq!(relayer_fee_premium(Some(&underbonded), 25), 0);
+
+    let mut unbonding = relayer(40);
+    unbonding.unbonding_slot = 100;
+    assert_eq!(relayer_fee_premium(Some(&unbonding), 25), 0);
+}
+
+#[test]
+fn test_slash_is_bounded_by_the_bond() {
+    let relayer = relayer(10);
+    assert!(relayer.validate_slash(MIN_RELAYER_BOND).is_ok());
+    for amount in [0, MIN_RELAYER_BOND + 1] {
+        assert_eq!(relayer.validate_slash(amount).unwrap_err(), PoolError::InvalidSlashAmount.into());
+    }
+}
+
+#[test]
+fn test_deregistration_waits_out_unbonding() {
+    let mut relayer = relayer(10);
+    assert_eq!(relayer.validate_deregistration(u64::MAX).unwrap_err(), PoolError::RelayerStillBonded.into());
+
+    relayer.unbonding_slot = 1_000;
+    assert!(relayer.validate_deregistration(1_000 + RELAYER_UNBONDING_SLOTS - 1).is_err());
+    assert!(relayer.validate_deregistration(1_000 + RELAYER_UNBONDING_SLOTS).is_ok());
+}
\ No newline at end of file
@@ -0,0 +1,23 @@
+use anchor_lang::prelude::Pubkey;
+use zkcash::errors::PoolError;
+use zkcash::{validate_rescue, TokenPool};
+
+#[test]
+fn test_sol_pool_tokens_are_all_rescuable() {
+    assert!(validate_rescue(None, &Pubkey::new_unique(), &Pubkey::new_unique()).is_ok());
+}
+
+#[test]
+fn test_token_pool_keeps_its_own_mint() {
+    let pool = TokenPool {
+        mint: Pubkey::new_unique(),
+        vault: Pubkey::new_unique(),
+        ..TokenPool::default()
+    };
+    let err = validate_rescue(Some(&pool), &pool.vault, &pool.mint).unwrap_err();
+    assert_eq!(err, PoolError::CannotRescuePoolAsset.into());
+    let err = validate_rescue(Some(&pool), &Pubkey::new_unique(), &pool.mint).unwrap_err();
+    assert_eq!(err, PoolError::CannotRescuePoolAsset.into());
+
+    assert!(validate_rescue(Some(&pool), &Pubkey::new_unique(), &Pubkey::new_unique()).is_ok());
+}
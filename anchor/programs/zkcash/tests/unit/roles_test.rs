@@ -0,0 +1,30 @@
+use anchor_lang::prelude::Pubkey;
+use zkcash::{holds_role, Role, RoleConfig};
+
+#[test]
+fn test_authority_holds_every_role_by_default() {
+    let authority = Pubkey::new_unique();
+    let config = RoleConfig::default();
+    for role in [Role::FeeManager, Role::TreeOperator, Role::FeeCollector] {
+        assert!(holds_role(&config, role, &authority, &authority));
+        assert!(!holds_role(&config, role, &authority, &Pubkey::new_unique()));
+    }
+}
+
+#[test]
+fn test_delegated_role_is_held_by_the_delegate_alone() {
+    let authority = Pubkey::new_unique();
+    let fee_manager = Pubkey::new_unique();
+    let mut config = RoleConfig::default();
+    assert_eq!(config.set(Role::FeeManager, fee_manager), Pubkey::default());
+
+    assert!(holds_role(&config, Role::FeeManager, &authority, &fee_manager));
+    assert!(!holds_role(&config, Role::FeeManager, &authority, &authority));
+    // Other roles stay with the authority
+    assert!(holds_role(&config, Role::TreeOperator, &authority, &authority));
+    assert!(!holds_role(&config, Role::TreeOperator, &authority, &fee_manager));
+
+    assert_eq!(config.set(Role::FeeManager, Pubkey::default()), fee_manager);
+    assert_eq!(config.delegate(Role::FeeManager), None);
+    assert!(holds_role(&config, Role::FeeManager, &authority, &authority));
+}
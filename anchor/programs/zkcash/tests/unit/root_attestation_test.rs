@@ -0,0 +1,21 @@
+use anchor_lang::prelude::Pubkey;
+use zkcash::{RootAttestation, ROOT_ATTESTATION_VERSION};
+
+#[test]
+fn test_payload_layout() {
+    let attestation = RootAttestation {
+        tree_account: Pubkey::new_from_array([1u8; 32]),
+        next_index: 0x0102_0304_0506_0708,
+        root: [2u8; 32],
+        slot: 0x1112_1314_1516_1718,
+    };
+    let payload = attestation.to_payload();
+
+    assert_eq!(payload.len(), RootAttestation::LEN);
+    assert_eq!(payload[0], ROOT_ATTESTATION_VERSION);
+    assert_eq!(payload[1..33], [1u8; 32]);
+    assert_eq!(payload[33..41], [1, 2, 3, 4, 5, 6, 7, 8]);
+    assert_eq!(payload[41..73], [2u8; 32]);
+    assert_eq!(payload[73..81], [0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18]);
+}
+
@@ -0,0 +1,77 @@
+use zkcash::errors::PoolError;
+use zkcash::{
+    checkpoint_at_or_before, checkpoint_due, root_checkpoint, root_checkpoint_count, validate_checkpoint_interval,
+    RootCheckpoint, MIN_CHECKPOINT_INTERVAL_SLOTS, ROOT_CHECKPOINTS_HEADER_LEN, ROOT_CHECKPOINT_LEN,
+};
+
+fn checkpoint(slot: u64) -> RootCheckpoint {
+    RootCheckpoint {
+        slot,
+        next_index: slot / 10,
+        root: [slot as u8; 32],
+    }
+}
+
+fn account_data(slots: &[u64]) -> Vec<u8> {
+    let mut data = vec![0u8; ROOT_CHECKPOINTS_HEADER_LEN];
+    for slot in slots {
+        data.extend_from_slice(bytemuck::bytes_of(&checkpoint(*slot)));
+    }
+    data
+}
+
+#[test]
+fn test_checkpoint_layout() {
+    assert_eq!(ROOT_CHECKPOINT_LEN, 48);
+    assert_eq!(ROOT_CHECKPOINTS_HEADER_LEN, 8 + 32 + 8 + 8 + 8);
+}
+
+#[test]
+fn test_checkpoints_read_from_raw_data() {
+    let data = account_data(&[100, 250, 400]);
+    assert_eq!(root_checkpoint_count(&data).unwrap(), 3);
+    assert_eq!(root_checkpoint(&data, 1), Some(checkpoint(250)));
+    assert_eq!(root_checkpoint(&data, 3), None);
+
+    // Unaligned, as in data fetched off-chain
+    let mut shifted = vec![0u8];
+    shifted.extend_from_slice(&data);
+    assert_eq!(root_checkpoint(&shifted[1..], 2), Some(checkpoint(400)));
+
+    assert_eq!(
+        root_checkpoint_count(&data[..data.len() - 1]).unwrap_err(),
+        PoolError::InvalidAccountLayout.into()
+    );
+    assert_eq!(
+        root_checkpoint_count(&data[..ROOT_CHECKPOINTS_HEADER_LEN - 1]).unwrap_err(),
+        PoolError::InvalidAccountLayout.into()
+    );
+}
+
+#[test]
+fn test_checkpoint_at_or_before() {
+    let data = account_data(&[100, 250, 400]);
+    assert_eq!(checkpoint_at_or_before(&data, 99).unwrap(), None);
+    assert_eq!(checkpoint_at_or_before(&data, 100).unwrap(), Some(checkpoint(100)));
+    assert_eq!(checkpoint_at_or_before(&data, 399).unwrap(), Some(checkpoint(250)));
+    assert_eq!(checkpoint_at_or_before(&data, u64::MAX).unwrap(), Some(checkpoint(400)));
+    assert_eq!(checkpoint_at_or_before(&account_data(&[]), 100).unwrap(), None);
+}
+
+#[test]
+fn test_checkpoint_due_after_interval() {
+    assert!(checkpoint_due(None, 150, 0));
+    let last = checkpoint(1_000);
+    assert!(!checkpoint_due(Some(&last), 150, 1_149));
+    assert!(checkpoint_due(Some(&last), 150, 1_150));
+    assert!(!checkpoint_due(Some(&checkpoint(u64::MAX)), 150, u64::MAX - 1));
+}
+
+#[test]
+fn test_checkpoint_interval_floor() {
+    validate_checkpoint_interval(MIN_CHECKPOINT_INTERVAL_SLOTS).unwrap();
+    assert_eq!(
+        validate_checkpoint_interval(MIN_CHECKPOINT_INTERVAL_SLOTS - 1).unwrap_err(),
+        PoolError::InvalidCheckpointInterval.into()
+    );
+}
@@ -0,0 +1,143 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+use light_hasher::{Hasher, Poseidon};
+use std::mem::MaybeUninit;
+use zkcash::errors::PoolError;
+use zkcash::{
+    is_known_root, merkle_tree::MerkleTree, next_root_history_len, MerkleTreeAccount, RootHistory,
+    ROOT_HISTORY_ACCOUNT_LEN, ROOT_HISTORY_CAPACITY, ROOT_INDEX_BUCKETS,
+};
+
+fn tree() -> MerkleTreeAccount {
+    let mut uninit: MaybeUninit<MerkleTreeAccount> = MaybeUninit::uninit();
+    let mut account = unsafe {
+        let ptr = uninit.as_mut_ptr();
+        std::ptr::write_bytes(ptr, 0, 1);
+        (*ptr).height = 26;
+        (*ptr).root_history_size = 100;
+        uninit.assume_init()
+    };
+    MerkleTree::initialize::<Poseidon>(&mut account).unwrap();
+    account
+}
+
+fn root_history(tree: &MerkleTreeAccount) -> Box<RootHistory> {
+    let mut root_history: Box<RootHistory> = Box::new(bytemuck::Zeroable::zeroed());
+    root_history.seed(Pubkey::new_unique(), tree.root);
+    root_history
+}
+
+#[test]
+fn test_holds_more_roots_than_the_tree() {
+    assert!(ROOT_HISTORY_CAPACITY > tree().root_history.len());
+}
+
+#[test]
+fn test_grows_in_permitted_steps() {
+    let mut len = MAX_PERMITTED_DATA_INCREASE;
+    let mut steps = 0;
+    while len < ROOT_HISTORY_ACCOUNT_LEN {
+        let next = next_root_history_len(len).unwrap();
+        assert!(next - len <= MAX_PERMITTED_DATA_INCREASE);
+        len = next;
+        steps += 1;
+    }
+    assert_eq!(len, ROOT_HISTORY_ACCOUNT_LEN);
+    assert_eq!(steps, 3);
+    let err = next_root_history_len(len).unwrap_err();
+    assert_eq!(err, PoolError::RootHistoryAlreadyAllocated.into());
+}
+
+#[test]
+fn test_keeps_roots_the_tree_has_forgotten() {
+    let mut tree = tree();
+    let mut root_history = root_history(&tree);
+    let initial_root = tree.root;
+
+    for i in 0..200u64 {
+        let leaf = Poseidon::hash(&i.to_be_bytes()).unwrap();
+        MerkleTree::append::<Poseidon>(leaf, &mut tree).unwrap();
+        root_history.push(tree.root);
+    }
+    assert!(!is_known_root(&tree, None, &initial_root));
+    assert!(is_known_root(&tree, Some(&root_history), &initial_root));
+    assert!(is_known_root(&tree, Some(&root_history), &tree.root));
+}
+
+#[test]
+fn test_wraps_after_capacity() {
+    let tree = tree();
+    let mut root_history = root_history(&tree);
+    for i in 1..=ROOT_HISTORY_CAPACITY as u64 {
+        let mut root = [0u8; 32];
+        root[..8].copy_from_slice(&i.to_le_bytes());
+        root_history.push(root);
+    }
+    // The seeded root was overwritten by the last push
+    assert_eq!(root_history.root_index, 0);
+    assert!(!root_history.is_known_root(&tree.root));
+}
+
+#[test]
+fn test_zero_root_is_never_known() {
+    let tree = tree();
+    let root_history = root_history(&tree);
+    // Unwritten slots are zero
+    assert!(!root_history.is_known_root(&[0u8; 32]));
+}
+
+#[test]
+fn test_index_agrees_with_a_scan_across_wraps() {
+    let tree = tree();
+    let mut root_history = root_history(&tree);
+    let mut pushed = vec![tree.root];
+    for i in 0..(ROOT_HISTORY_CAPACITY as u64 * 5 / 2) {
+        let root = Poseidon::hash(&i.to_be_bytes()).unwrap();
+        root_history.push(root);
+        pushed.push(root);
+    }
+    for root in &pushed {
+        assert_eq!(root_history.is_known_root(root), root_history.roots.contains(root));
+    }
+    assert_eq!(
+        root_history.index.iter().filter(|entry| **entry != 0).count(),
+        ROOT_HISTORY_CAPACITY
+    );
+}
+
+#[test]
+fn test_colliding_roots_stay_reachable() {
+    let tree = tree();
+    let mut root_history = root_history(&tree);
+    // Same leading bytes, so every root probes from the same bucket
+    for i in 1..=(ROOT_HISTORY_CAPACITY as u64 + 10) {
+        let mut root = [0u8; 32];
+        root[24..].copy_from_slice(&i.to_be_bytes());
+        root_history.push(root);
+        assert!(root_history.is_known_root(&root));
+    }
+    for i in 1..=10u64 {
+        let mut root = [0u8; 32];
+        root[24..].copy_from_slice(&i.to_be_bytes());
+        assert!(!root_history.is_known_root(&root));
+    }
+    assert!(ROOT_INDEX_BUCKETS > ROOT_HISTORY_CAPACITY);
+}
+
+#[test]
+fn test_rebuilt_index_matches_the_maintained_one() {
+    let tree = tree();
+    let mut root_history = root_history(&tree);
+    for i in 0..300u64 {
+        root_history.push(Poseidon::hash(&i.to_be_bytes()).unwrap());
+    }
+    let maintained = root_history.index;
+    root_history.index = [0u16; ROOT_INDEX_BUCKETS];
+    assert!(!root_history.is_known_root(&root_history.roots[1]));
+    root_history.rebuild_index();
+    for root in root_history.roots.iter().filter(|root| **root != [0u8; 32]) {
+        assert!(root_history.is_known_root(root));
+    }
+    let occupied = |index: &[u16]| index.iter().filter(|entry| **entry != 0).count();
+    assert_eq!(occupied(&root_history.index), occupied(&maintained));
+}
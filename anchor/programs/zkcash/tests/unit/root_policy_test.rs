@@ -0,0 +1,41 @@
+use zkcash::errors::PoolError;
+use zkcash::{validate_max_root_age, RootPolicy, MIN_MAX_ROOT_AGE_SLOTS};
+
+fn policy(max_root_age_slots: u64) -> RootPolicy {
+    RootPolicy {
+        max_root_age_slots,
+        bump: 255,
+    }
+}
+
+#[test]
+fn test_validate_max_root_age() {
+    assert!(validate_max_root_age(0).is_ok());
+    assert!(validate_max_root_age(MIN_MAX_ROOT_AGE_SLOTS).is_ok());
+    let err = validate_max_root_age(MIN_MAX_ROOT_AGE_SLOTS - 1).unwrap_err();
+    assert_eq!(err, PoolError::InvalidRootPolicy.into());
+}
+
+#[test]
+fn test_disabled_policy_accepts_any_root() {
+    assert!(policy(0).validate_root_age(-1, None, 1_000_000).is_ok());
+    assert!(policy(0).validate_root_age(-1, Some(1), 1_000_000).is_ok());
+}
+
+#[test]
+fn test_withdrawal_root_must_be_recent() {
+    let policy = policy(1_000);
+    assert!(policy.validate_root_age(-1, Some(5_000), 6_000).is_ok());
+    let err = policy.validate_root_age(-1, Some(5_000), 6_001).unwrap_err();
+    assert_eq!(err, PoolError::StaleRoot.into());
+    // Slot unknown: the root predates the v2 migration
+    let err = policy.validate_root_age(-1, None, 6_000).unwrap_err();
+    assert_eq!(err, PoolError::StaleRoot.into());
+}
+
+#[test]
+fn test_deposits_are_not_bound() {
+    let policy = policy(1_000);
+    assert!(policy.validate_root_age(1, None, 1_000_000).is_ok());
+    assert!(policy.validate_root_age(0, Some(1), 1_000_000).is_ok());
+}
@@ -0,0 +1,73 @@
+use anchor_lang::Discriminator;
+use light_hasher::Poseidon;
+use std::mem::MaybeUninit;
+use zkcash::{
+    known_root_slot, merkle_tree::MerkleTree, next_migration, record_root_slot, root_history_index, root_slot,
+    MerkleTreeAccount, MERKLE_TREE_ACCOUNT_V0_LEN, MERKLE_TREE_ACCOUNT_V1_LEN, MERKLE_TREE_ACCOUNT_VERSION,
+    ROOT_SLOT_ENTRIES,
+};
+
+fn tree() -> MerkleTreeAccount {
+    let mut uninit: MaybeUninit<MerkleTreeAccount> = MaybeUninit::uninit();
+    let mut account = unsafe {
+        let ptr = uninit.as_mut_ptr();
+        std::ptr::write_bytes(ptr, 0, 1);
+        (*ptr).height = 26;
+        (*ptr).root_history_size = 100;
+        uninit.assume_init()
+    };
+    MerkleTree::initialize::<Poseidon>(&mut account).unwrap();
+    account
+}
+
+/// Raw data of a tree account migrated to `version`.
+fn account_data(version: u8) -> Vec<u8> {
+    let mut data = vec![0u8; MERKLE_TREE_ACCOUNT_V0_LEN];
+    data[..8].copy_from_slice(MerkleTreeAccount::DISCRIMINATOR);
+    for from_version in 0..version {
+        let migration = next_migration(from_version).unwrap();
+        data.resize(migration.new_len, 0);
+        migration.apply(&mut data).unwrap();
+    }
+    data
+}
+
+#[test]
+fn test_entries_cover_root_history() {
+    assert_eq!(tree().root_history.len(), ROOT_SLOT_ENTRIES);
+}
+
+#[test]
+fn test_records_and_reads_slots() {
+    let mut data = account_data(MERKLE_TREE_ACCOUNT_VERSION);
+    assert_eq!(root_slot(&data, 3), None);
+    record_root_slot(&mut data, 3, 1_234);
+    assert_eq!(root_slot(&data, 3), Some(1_234));
+    // Indices wrap with the ring
+    assert_eq!(root_slot(&data, 3 + ROOT_SLOT_ENTRIES as u64), Some(1_234));
+}
+
+#[test]
+fn test_older_layouts_have_no_slots() {
+    let mut data = account_data(1);
+    assert_eq!(data.len(), MERKLE_TREE_ACCOUNT_V1_LEN);
+    record_root_slot(&mut data, 0, 99);
+    assert_eq!(data, account_data(1));
+    assert_eq!(root_slot(&data, 0), None);
+}
+
+#[test]
+fn test_finds_slot_of_known_root() {
+    let mut tree = tree();
+    let mut data = account_data(MERKLE_TREE_ACCOUNT_VERSION);
+    for (slot, leaf) in (10..13u64).zip(1..4u8) {
+        MerkleTree::append::<Poseidon>([leaf; 32], &mut tree).unwrap();
+        record_root_slot(&mut data, tree.root_index, slot);
+    }
+    assert_eq!(root_history_index(&tree, &tree.root), Some(tree.root_index));
+    assert_eq!(known_root_slot(&tree, &data, &tree.root), Some(12));
+    let older = tree.root_history[tree.root_index as usize - 2];
+    assert_eq!(known_root_slot(&tree, &data, &older), Some(10));
+    assert_eq!(known_root_slot(&tree, &data, &[7u8; 32]), None);
+    assert_eq!(root_history_index(&tree, &[0u8; 32]), None);
+}
@@ -0,0 +1,59 @@
+use zkcash::PoolStats;
+
+#[test]
+fn test_record_deposit() {
+    let mut stats = PoolStats::default();
+    stats.record(1_000_000_000, 0).unwrap();
+
+    assert_eq!(stats.deposit_count, 1);
+    assert_eq!(stats.total_deposited, 1_000_000_000);
+    assert_eq!(stats.withdrawal_count, 0);
+    assert_eq!(stats.total_withdrawn, 0);
+    assert_eq!(stats.total_fees, 0);
+}
+
+#[test]
+fn test_record_withdrawal_counts_amount_and_fee() {
+    let mut stats = PoolStats::default();
+    stats.record(-500_000_000, 5_000_000).unwrap();
+
+    assert_eq!(stats.withdrawal_count, 1);
+    assert_eq!(stats.total_withdrawn, 500_000_000);
+    assert_eq!(stats.total_fees, 5_000_000);
+    assert_eq!(stats.deposit_count, 0);
+}
+
+#[test]
+fn test_record_transfer_only_adds_fee() {
+    let mut stats = PoolStats::default();
+    stats.record(0, 10_000).unwrap();
+
+    assert_eq!(stats.transfer_count, 1);
+    assert_eq!(stats.deposit_count, 0);
+    assert_eq!(stats.withdrawal_count, 0);
+    assert_eq!(stats.total_fees, 10_000);
+}
+
+#[test]
+fn test_record_accumulates() {
+    let mut stats = PoolStats::default();
+    stats.record(2_000, 0).unwrap();
+    stats.record(3_000, 0).unwrap();
+    stats.record(-1_500, 15).unwrap();
+    stats.record(i64::MIN, 0).unwrap();
+
+    assert_eq!(stats.deposit_count, 2);
+    assert_eq!(stats.total_deposited, 5_000);
+    assert_eq!(stats.withdrawal_count, 2);
+    assert_eq!(stats.total_withdrawn, 1_500 + i64::MIN.unsigned_abs());
+    assert_eq!(stats.total_fees, 15);
+}
+
+#[test]
+fn test_record_overflow_fails() {
+    let mut stats = PoolStats { total_fees: u64::MAX, ..PoolStats::default() };
+    assert!(stats.record(1, 1).is_err());
+
+    let mut stats = PoolStats { total_deposited: u64::MAX, ..PoolStats::default() };
+    assert!(stats.record(1, 0).is_err());
+}
@@ -0,0 +1,47 @@
+use anchor_lang::prelude::Pubkey;
+use zkcash::errors::PoolError;
+use zkcash::{submission_commitment_hash, SubmissionCommitment, MAX_REVEAL_DELAY_SLOTS};
+use super::proof_test::valid_proof;
+
+fn committed(relayer: Pubkey) -> SubmissionCommitment {
+    SubmissionCommitment {
+        relayer,
+        commitment: submission_commitment_hash(&relayer, &valid_proof()).unwrap(),
+        committed_slot: 100,
+        bump: 255,
+    }
+}
+
+#[test]
+fn test_reveal_matches_commitment() {
+    let relayer = Pubkey::new_unique();
+    let commitment = committed(relayer);
+    assert!(commitment.validate_reveal(&relayer, &valid_proof(), 101).is_ok());
+    assert!(commitment.validate_reveal(&relayer, &valid_proof(), 100 + MAX_REVEAL_DELAY_SLOTS).is_ok());
+}
+
+#[test]
+fn test_rejects_other_relayer_or_payload() {
+    let relayer = Pubkey::new_unique();
+    let commitment = committed(relayer);
+
+    // A relayer copying the reveal from the mempool
+    let err = commitment.validate_reveal(&Pubkey::new_unique(), &valid_proof(), 101).unwrap_err();
+    assert_eq!(err, PoolError::SubmissionCommitmentMismatch.into());
+
+    let mut altered = valid_proof();
+    altered.ext_data_hash[31] ^= 1;
+    let err = commitment.validate_reveal(&relayer, &altered, 101).unwrap_err();
+    assert_eq!(err, PoolError::SubmissionCommitmentMismatch.into());
+}
+
+#[test]
+fn test_rejects_reveal_outside_window() {
+    let relayer = Pubkey::new_unique();
+    let commitment = committed(relayer);
+
+    let err = commitment.validate_reveal(&relayer, &valid_proof(), 100).unwrap_err();
+    assert_eq!(err, PoolError::RevealOutsideWindow.into());
+    let err = commitment.validate_reveal(&relayer, &valid_proof(), 101 + MAX_REVEAL_DELAY_SLOTS).unwrap_err();
+    assert_eq!(err, PoolError::RevealOutsideWindow.into());
+}
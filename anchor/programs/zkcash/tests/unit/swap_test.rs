@@ -0,0 +1,57 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::hash;
+use zkcash::errors::PoolError;
+use zkcash::{validate_swap_programs, SwapAllowlist, SwapParams, MAX_SWAP_PROGRAMS};
+
+const ROUTE: &[u8] = b"route instruction data";
+
+fn allowlisted() -> (SwapAllowlist, SwapParams) {
+    let program = Pubkey::new_unique();
+    let allowlist = SwapAllowlist {
+        programs: vec![program],
+        bump: 0,
+    };
+    let params = SwapParams {
+        program,
+        output_mint: Pubkey::new_unique(),
+        min_output_amount: 1_000,
+        route_hash: hash(ROUTE).to_bytes(),
+    };
+    (allowlist, params)
+}
+
+#[test]
+fn test_committed_route_accepted() {
+    let (allowlist, params) = allowlisted();
+    assert!(allowlist.validate_swap(&params, ROUTE).is_ok());
+}
+
+#[test]
+fn test_unlisted_program_rejected() {
+    let (allowlist, params) = allowlisted();
+    let params = SwapParams {
+        program: Pubkey::new_unique(),
+        ..params
+    };
+    let err = allowlist.validate_swap(&params, ROUTE).unwrap_err();
+    assert_eq!(err, PoolError::SwapProgramNotAllowed.into());
+}
+
+#[test]
+fn test_other_route_rejected() {
+    let (allowlist, params) = allowlisted();
+    let err = allowlist.validate_swap(&params, b"relayer's route").unwrap_err();
+    assert_eq!(err, PoolError::SwapRouteMismatch.into());
+}
+
+#[test]
+fn test_allowlist_validation() {
+    assert!(validate_swap_programs(&[Pubkey::new_unique(); MAX_SWAP_PROGRAMS]).is_ok());
+    for programs in [
+        vec![Pubkey::new_unique(); MAX_SWAP_PROGRAMS + 1],
+        vec![Pubkey::default()],
+        vec![zkcash::ID],
+    ] {
+        assert_eq!(validate_swap_programs(&programs).unwrap_err(), PoolError::InvalidSwapAllowlist.into());
+    }
+}
@@ -0,0 +1,84 @@
+use zkcash::{effective_slot, GlobalConfig, PendingConfigUpdate, CONFIG_UPDATE_DELAY_SLOTS};
+use anchor_lang::prelude::Pubkey;
+
+fn global_config() -> GlobalConfig {
+    GlobalConfig {
+        authority: Pubkey::new_unique(),
+        deposit_fee_rate: 0,
+        withdrawal_fee_rate: 100,
+        fee_error_margin: 500,
+        bump: 255,
+    }
+}
+
+#[test]
+fn test_effective_slot_adds_the_delay() {
+    assert_eq!(effective_slot(1_000).unwrap(), 1_000 + CONFIG_UPDATE_DELAY_SLOTS);
+    assert!(effective_slot(u64::MAX).is_err());
+}
+
+#[test]
+fn test_is_ready_only_from_the_effective_slot() {
+    let pending = PendingConfigUpdate {
+        withdrawal_fee_rate: Some(200),
+        effective_slot: effective_slot(1_000).unwrap(),
+        ..PendingConfigUpdate::default()
+    };
+    assert!(!pending.is_ready(1_000));
+    assert!(!pending.is_ready(pending.effective_slot - 1));
+    assert!(pending.is_ready(pending.effective_slot));
+    assert!(pending.is_ready(pending.effective_slot + 1));
+}
+
+#[test]
+fn test_validate_rejects_an_empty_update() {
+    let pending = PendingConfigUpdate::default();
+    assert!(pending.is_empty());
+    assert!(pending.validate(&global_config()).is_err());
+}
+
+#[test]
+fn test_validate_accepts_deposit_limit_only_update() {
+    let pending = PendingConfigUpdate {
+        max_deposit_amount: Some(500_000_000_000),
+        ..PendingConfigUpdate::default()
+    };
+    assert!(pending.validate(&global_config()).is_ok());
+}
+
+#[test]
+fn test_validate_applies_fee_caps() {
+    let pending = PendingConfigUpdate {
+        withdrawal_fee_rate: Some(501),
+        ..PendingConfigUpdate::default()
+    };
+    assert!(pending.validate(&global_config()).is_err());
+
+    let pending = PendingConfigUpdate {
+        deposit_fee_rate: Some(500),
+        withdrawal_fee_rate: Some(500),
+        fee_error_margin: Some(10000),
+        ..PendingConfigUpdate::default()
+    };
+    assert!(pending.validate(&global_config()).is_ok());
+}
+
+#[test]
+fn test_validate_checks_unchanged_fields_too() {
+    // A stored value above the cap (e.g. set before the caps existed) blocks
+    // updates until it is brought back in range
+    let mut config = global_config();
+    config.withdrawal_fee_rate = 1_000;
+
+    let pending = PendingConfigUpdate {
+        deposit_fee_rate: Some(10),
+        ..PendingConfigUpdate::default()
+    };
+    assert!(pending.validate(&config).is_err());
+
+    let pending = PendingConfigUpdate {
+        withdrawal_fee_rate: Some(100),
+        ..PendingConfigUpdate::default()
+    };
+    assert!(pending.validate(&config).is_ok());
+}
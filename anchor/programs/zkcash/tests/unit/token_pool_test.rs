@@ -0,0 +1,106 @@
+use anchor_lang::prelude::Pubkey;
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, PrimeField};
+use zkcash::errors::PoolError;
+use zkcash::utils::{FeeRates, SOL_ADDRESS, SOL_DECIMALS};
+use zkcash::{base_units, default_max_deposit_amount, mint_decimals, pool_decimals, FeeSchedule, TokenPool};
+
+const RATES: FeeRates = FeeRates {
+    deposit_fee_rate: 0,
+    withdrawal_fee_rate: 25,
+    fee_error_margin: 500,
+};
+
+fn pool(decimals: u8) -> TokenPool {
+    TokenPool {
+        mint: Pubkey::new_unique(),
+        decimals,
+        ..TokenPool::default()
+    }
+}
+
+fn public_amount(fr: Fr) -> [u8; 32] {
+    fr.into_bigint().to_bytes_be().try_into().unwrap()
+}
+
+#[test]
+fn test_mint_decimals_read_from_mint_data() {
+    let mut mint = vec![0u8; 82];
+    mint[44] = 6;
+    assert_eq!(mint_decimals(&mint).unwrap(), 6);
+    assert_eq!(mint_decimals(&mint[..44]).unwrap_err(), PoolError::InvalidMintAccount.into());
+}
+
+#[test]
+fn test_default_deposit_cap_follows_decimals() {
+    // The SOL pool's 1000 SOL default
+    assert_eq!(default_max_deposit_amount(9), 1_000_000_000_000);
+    assert_eq!(default_max_deposit_amount(6), 1_000_000_000);
+    // 1000 tokens at 17 decimals is beyond a u64
+    assert_eq!(default_max_deposit_amount(17), u64::MAX);
+    assert!(base_units(1, u8::MAX).is_err());
+}
+
+#[test]
+fn test_withdrawal_fee_in_base_units_of_9_and_6_decimal_mints() {
+    let schedule = FeeSchedule::default();
+    for decimals in [9, 6] {
+        let pool = pool(decimals);
+        // 0.25% of 10 tokens is 0.025 tokens, less the 5% margin
+        let amount = base_units(10, decimals).unwrap() as i64;
+        let minimum = (base_units(25, decimals).unwrap() / 1000 * 95 / 100) as u64;
+        assert!(pool.validate_fee(&schedule, -amount, minimum, &RATES, 0).is_ok());
+        assert!(pool.validate_fee(&schedule, -amount, minimum - 1, &RATES, 0).is_err());
+        // Free deposits up to the default cap
+        let cap = default_max_deposit_amount(decimals) as i64;
+        assert!(pool.validate_fee(&schedule, cap, 0, &RATES, 0).is_ok());
+
+        let fee = minimum;
+        let withdrawal = -(Fr::from(amount as u64) + Fr::from(fee));
+        assert!(pool.check_public_amount(-amount, fee, public_amount(withdrawal)));
+        let deposit = Fr::from(amount as u64) - Fr::from(fee);
+        assert!(pool.check_public_amount(amount, fee, public_amount(deposit)));
+        assert!(!pool.check_public_amount(amount, fee, public_amount(withdrawal)));
+    }
+}
+
+#[test]
+fn test_token_public_amount_takes_the_full_i64_range() {
+    // The lamport check refuses i64::MIN, which has no i64 absolute value
+    let pool = pool(9);
+    let withdrawal = -Fr::from(1u64 << 63);
+    assert!(pool.check_public_amount(i64::MIN, 0, public_amount(withdrawal)));
+}
+
+#[test]
+fn test_pool_decimals_per_mint() {
+    assert_eq!(pool_decimals(&SOL_ADDRESS, None).unwrap(), SOL_DECIMALS);
+    let usdc = pool(6);
+    assert_eq!(pool_decimals(&usdc.mint, Some(&usdc)).unwrap(), 6);
+
+    // A token mint needs its own pool
+    let other = pool(9);
+    for token_pool in [None, Some(&other)] {
+        assert_eq!(pool_decimals(&usdc.mint, token_pool).unwrap_err(), PoolError::UnknownPoolMint.into());
+    }
+}
+
+#[test]
+fn test_cap_applies_only_to_its_own_mint() {
+    let usdc = pool(6);
+    // 1 USDC, which as lamports would be a thousandth of a SOL
+    let schedule = FeeSchedule {
+        mint: usdc.mint,
+        fee_cap: 1_000_000,
+        ..FeeSchedule::default()
+    };
+    let amount = base_units(10_000, 6).unwrap() as i64;
+    assert!(usdc.validate_fee(&schedule, -amount, 950_000, &RATES, 0).is_ok());
+    assert!(usdc.validate_fee(&schedule, -amount, 949_999, &RATES, 0).is_err());
+
+    let sol = pool(9);
+    assert_eq!(
+        sol.validate_fee(&schedule, -amount, 950_000, &RATES, 0).unwrap_err(),
+        PoolError::FeeScheduleMintMismatch.into()
+    );
+}
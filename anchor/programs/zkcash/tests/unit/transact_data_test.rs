@@ -0,0 +1,113 @@
+use anchor_lang::prelude::*;
+use zkcash::errors::PoolError;
+use zkcash::utils::ExtDataFields;
+use zkcash::{
+    encode_transact_data, parse_transact_data, CpiTarget, ForeignRecipient, Proof, EXT_DATA_HEADER_LEN, PROOF_LEN,
+};
+
+fn proof() -> Proof {
+    Proof {
+        proof_a: [1u8; 64],
+        proof_b: [2u8; 128],
+        proof_c: [3u8; 64],
+        root: [4u8; 32],
+        public_amount: [5u8; 32],
+        ext_data_hash: [6u8; 32],
+        input_nullifiers: [[7u8; 32], [8u8; 32]],
+        output_commitments: [[9u8; 32], [10u8; 32]],
+    }
+}
+
+fn fields() -> ExtDataFields<'static> {
+    ExtDataFields {
+        recipient: Pubkey::new_from_array([1u8; 32]),
+        ext_amount: -1_000,
+        encrypted_output1: b"encrypted_output_1_data",
+        encrypted_output2: b"encrypted_output_2",
+        fee: 10,
+        fee_recipient: Pubkey::new_from_array([2u8; 32]),
+        mint_address: Pubkey::new_from_array([3u8; 32]),
+        memo: b"memo",
+        expiry_slot: 77,
+        max_fee: 12,
+        relayer: Some(Pubkey::new_from_array([4u8; 32])),
+        swap: None,
+        cpi: Some(CpiTarget::default()),
+        foreign_recipient: Some(ForeignRecipient { chain_id: 2, address: [5u8; 32] }),
+        memo_cpi: true,
+    }
+}
+
+fn assert_ext_data_eq(left: &ExtDataFields, right: &ExtDataFields) {
+    assert_eq!(format!("{left:?}"), format!("{right:?}"));
+}
+
+#[test]
+fn round_trips_proof_and_ext_data() {
+    let data = encode_transact_data(&proof(), 7, &fields()).unwrap();
+    let parsed = parse_transact_data(&data).unwrap();
+
+    assert_eq!(parsed.version, 7);
+    assert_eq!(Proof::from(parsed.proof).try_to_vec().unwrap(), proof().try_to_vec().unwrap());
+    assert_ext_data_eq(&parsed.ext_data, &fields());
+}
+
+#[test]
+fn proof_bytes_match_borsh_encoding() {
+    let data = encode_transact_data(&proof(), 7, &fields()).unwrap();
+    assert_eq!(data[..PROOF_LEN], proof().try_to_vec().unwrap());
+}
+
+#[test]
+fn fields_are_borrowed_from_instruction_data() {
+    let data = encode_transact_data(&proof(), 7, &fields()).unwrap();
+    let parsed = parse_transact_data(&data).unwrap();
+    let range = data.as_ptr_range();
+
+    assert_eq!(parsed.proof as *const _ as *const u8, data.as_ptr());
+    assert!(range.contains(&parsed.ext_data.encrypted_output1.as_ptr()));
+    assert_eq!(parsed.ext_data.encrypted_output1.as_ptr(), data[PROOF_LEN + EXT_DATA_HEADER_LEN..].as_ptr());
+}
+
+#[test]
+fn rejects_short_proof() {
+    let data = encode_transact_data(&proof(), 7, &fields()).unwrap();
+    assert_eq!(
+        parse_transact_data(&data[..PROOF_LEN - 1]).unwrap_err(),
+        PoolError::InvalidProofDataLength.into()
+    );
+}
+
+#[test]
+fn rejects_truncated_or_padded_data() {
+    let data = encode_transact_data(&proof(), 7, &fields()).unwrap();
+    assert_eq!(
+        parse_transact_data(&data[..data.len() - 1]).unwrap_err(),
+        PoolError::InvalidTransactData.into()
+    );
+
+    let mut padded = data.clone();
+    padded.push(0);
+    assert_eq!(parse_transact_data(&padded).unwrap_err(), PoolError::InvalidTransactData.into());
+}
+
+#[test]
+fn rejects_malformed_options() {
+    let no_options = ExtDataFields {
+        relayer: None,
+        cpi: None,
+        foreign_recipient: None,
+        memo_cpi: false,
+        ..fields()
+    };
+    let mut data = encode_transact_data(&proof(), 8, &no_options).unwrap();
+    // Four `None` tags and the memo CPI flag; an invalid Option tag can't decode
+    let options_start = data.len() - 5;
+    data[options_start] = 2;
+    assert_eq!(parse_transact_data(&data).unwrap_err(), PoolError::InvalidTransactData.into());
+
+    // Nor can a bool other than 0 or 1
+    let mut data = encode_transact_data(&proof(), 8, &no_options).unwrap();
+    *data.last_mut().unwrap() = 2;
+    assert_eq!(parse_transact_data(&data).unwrap_err(), PoolError::InvalidTransactData.into());
+}
@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+use zkcash::TransactResult;
+
+#[test]
+fn test_outputs_get_consecutive_leaf_indices() {
+    let result = TransactResult::new(41, [7u8; 32]).unwrap();
+    assert_eq!(result.leaf_indices, [41, 42]);
+    assert_eq!(result.root, [7u8; 32]);
+}
+
+#[test]
+fn test_leaf_index_overflow_is_rejected() {
+    assert!(TransactResult::new(u64::MAX, [0u8; 32]).is_err());
+}
+
+#[test]
+fn test_return_data_layout() {
+    // Clients decode this by hand: two little-endian u64s then the root
+    let result = TransactResult::new(5, [9u8; 32]).unwrap();
+    let mut data = Vec::new();
+    result.serialize(&mut data).unwrap();
+
+    assert_eq!(data.len(), 48);
+    assert_eq!(data[..8], 5u64.to_le_bytes());
+    assert_eq!(data[8..16], 6u64.to_le_bytes());
+    assert_eq!(data[16..], [9u8; 32]);
+}
@@ -0,0 +1,59 @@
+use zkcash::errors::PoolError;
+use zkcash::{validate_yield_config, YieldConfig, MAX_STAKED_BPS};
+
+fn config(max_staked_bps: u16) -> YieldConfig {
+    YieldConfig {
+        max_staked_bps,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_max_staked_bps_bounded() {
+    assert!(validate_yield_config(MAX_STAKED_BPS).is_ok());
+    let err = validate_yield_config(MAX_STAKED_BPS + 1).unwrap_err();
+    assert_eq!(err, PoolError::InvalidYieldConfig.into());
+}
+
+#[test]
+fn test_stake_up_to_limit() {
+    let mut config = config(3_000);
+    // 300 of 1000 total may be staked
+    config.record_stake(200, 1_000).unwrap();
+    config.record_stake(100, 800).unwrap();
+    assert_eq!(config.staked_lamports, 300);
+
+    let err = config.record_stake(1, 700).unwrap_err();
+    assert_eq!(err, PoolError::StakeLimitExceeded.into());
+    assert_eq!(config.staked_lamports, 300);
+}
+
+#[test]
+fn test_stake_rejects_zero_and_more_than_liquid() {
+    let mut config = config(MAX_STAKED_BPS);
+    assert_eq!(config.record_stake(0, 1_000).unwrap_err(), PoolError::StakeLimitExceeded.into());
+    assert_eq!(config.record_stake(1_001, 1_000).unwrap_err(), PoolError::StakeLimitExceeded.into());
+}
+
+#[test]
+fn test_needs_liquidity_after_withdrawals() {
+    let mut config = config(MAX_STAKED_BPS);
+    config.record_stake(500, 1_000).unwrap();
+    assert!(!config.needs_liquidity(500));
+    // Withdrawals drained the liquid side below the staked amount
+    assert!(config.needs_liquidity(499));
+}
+
+#[test]
+fn test_unstake_books_principal_then_yield() {
+    let mut config = config(MAX_STAKED_BPS);
+    config.record_stake(500, 1_000).unwrap();
+
+    config.record_unstake(200).unwrap();
+    assert_eq!(config.staked_lamports, 300);
+    assert_eq!(config.realized_yield, 0);
+
+    config.record_unstake(350).unwrap();
+    assert_eq!(config.staked_lamports, 0);
+    assert_eq!(config.realized_yield, 50);
+}
@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use light_hasher::Poseidon;
+use std::mem::MaybeUninit;
+use zkcash::errors::PoolError;
+use zkcash::{merkle_tree::MerkleTree, MerkleTreeAccount, TreeSnapshot, TREE_SNAPSHOT_PAGE_BYTES};
+
+fn populated_tree() -> MerkleTreeAccount {
+    let mut uninit: MaybeUninit<MerkleTreeAccount> = MaybeUninit::uninit();
+    let mut account = unsafe {
+        let ptr = uninit.as_mut_ptr();
+        std::ptr::write_bytes(ptr, 0, 1);
+        (*ptr).height = 26;
+        (*ptr).root_history_size = 100;
+        uninit.assume_init()
+    };
+    MerkleTree::initialize::<Poseidon>(&mut account).unwrap();
+    for i in 1..=5u8 {
+        MerkleTree::append::<Poseidon>([i; 32], &mut account).unwrap();
+    }
+    account
+}
+
+#[test]
+fn test_snapshot_copies_tree_state() {
+    let tree = populated_tree();
+    let snapshot = TreeSnapshot::from_tree(Pubkey::new_unique(), &tree);
+    assert_eq!(snapshot.next_index, 5);
+    assert_eq!(snapshot.subtrees.len(), 26);
+    assert_eq!(snapshot.root_history.len(), 100);
+    assert_eq!(snapshot.root_history[snapshot.root_index as usize], snapshot.root);
+}
+
+#[test]
+fn test_pages_reassemble() {
+    let snapshot = TreeSnapshot::from_tree(Pubkey::new_unique(), &populated_tree());
+    let first = snapshot.page(0).unwrap();
+    assert!(first.total_pages > 1);
+
+    let mut bytes = Vec::new();
+    for page in 0..first.total_pages {
+        let page = snapshot.page(page).unwrap();
+        assert!(page.bytes.len() <= TREE_SNAPSHOT_PAGE_BYTES);
+        // Fits in return data with its header
+        assert!(page.try_to_vec().unwrap().len() <= 1024);
+        assert_eq!(page.next_index, snapshot.next_index);
+        bytes.extend(page.bytes);
+    }
+    assert_eq!(TreeSnapshot::try_from_slice(&bytes).unwrap(), snapshot);
+
+    let err = snapshot.page(first.total_pages).unwrap_err();
+    assert_eq!(err, PoolError::InvalidSnapshotPage.into());
+}
@@ -0,0 +1,84 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::Discriminator;
+use light_hasher::{Hasher, Poseidon};
+use std::mem::MaybeUninit;
+use zkcash::errors::PoolError;
+use zkcash::{
+    merkle_tree::MerkleTree, set_tree_storage_key, stamp_current_version, tree_storage_key, validate_tree_storage,
+    MerkleTreeAccount, TreeStorage, MERKLE_TREE_ACCOUNT_LEN, MERKLE_TREE_ACCOUNT_V2_LEN,
+    MERKLE_TREE_ACCOUNT_VERSION_OFFSET,
+};
+
+fn tree() -> MerkleTreeAccount {
+    let mut uninit: MaybeUninit<MerkleTreeAccount> = MaybeUninit::uninit();
+    let mut account = unsafe {
+        let ptr = uninit.as_mut_ptr();
+        std::ptr::write_bytes(ptr, 0, 1);
+        (*ptr).height = 26;
+        (*ptr).root_history_size = 100;
+        uninit.assume_init()
+    };
+    MerkleTree::initialize::<Poseidon>(&mut account).unwrap();
+    account
+}
+
+fn current_tree_data() -> Vec<u8> {
+    let mut data = vec![0u8; MERKLE_TREE_ACCOUNT_LEN];
+    data[..8].copy_from_slice(MerkleTreeAccount::DISCRIMINATOR);
+    stamp_current_version(&mut data).unwrap();
+    data
+}
+
+#[test]
+fn test_split_append_matches_the_single_account_tree() {
+    let mut single = tree();
+    let mut split = tree();
+    let mut storage: Box<TreeStorage> = Box::new(bytemuck::Zeroable::zeroed());
+    storage.copy_from(Pubkey::new_unique(), &split);
+
+    for i in 0..150u64 {
+        let leaf = Poseidon::hash(&i.to_be_bytes()).unwrap();
+        let proof = MerkleTree::append::<Poseidon>(leaf, &mut single).unwrap();
+        let split_proof = MerkleTree::append_split::<Poseidon>(leaf, &mut split, &mut storage).unwrap();
+        assert_eq!(proof, split_proof);
+    }
+    assert_eq!(split.root, single.root);
+    assert_eq!(split.next_index, single.next_index);
+    assert_eq!(storage.subtrees, single.subtrees);
+    assert_eq!(storage.root_index, single.root_index);
+    assert_eq!(storage.root_history, single.root_history);
+    assert!(storage.is_known_root(&single.root, 100));
+    assert!(!storage.is_known_root(&[0u8; 32], 100));
+}
+
+#[test]
+fn test_link_is_written_once_on_current_trees() {
+    let mut data = current_tree_data();
+    assert_eq!(tree_storage_key(&data), None);
+
+    let storage = Pubkey::new_unique();
+    set_tree_storage_key(&mut data, &storage).unwrap();
+    assert_eq!(tree_storage_key(&data), Some(storage));
+    assert!(validate_tree_storage(&data, &storage).is_ok());
+    assert_eq!(
+        validate_tree_storage(&data, &Pubkey::new_unique()).unwrap_err(),
+        PoolError::TreeStorageMismatch.into()
+    );
+    assert_eq!(
+        set_tree_storage_key(&mut data, &Pubkey::new_unique()).unwrap_err(),
+        PoolError::TreeStorageAlreadySplit.into()
+    );
+}
+
+#[test]
+fn test_link_needs_the_v3_layout() {
+    let mut data = current_tree_data();
+    data.truncate(MERKLE_TREE_ACCOUNT_V2_LEN);
+    data[MERKLE_TREE_ACCOUNT_VERSION_OFFSET] = 2;
+    assert_eq!(tree_storage_key(&data), None);
+    assert!(set_tree_storage_key(&mut data, &Pubkey::new_unique()).is_err());
+    assert_eq!(
+        validate_tree_storage(&data, &Pubkey::new_unique()).unwrap_err(),
+        PoolError::TreeStorageMismatch.into()
+    );
+}
@@ -4,7 +4,7 @@ use ark_ff::{PrimeField, BigInteger};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
 use std::ops::Neg;
 use ark_bn254::Fr;
-use zkcash::{groth16::{is_less_than_bn254_field_size_be, Groth16Verifyingkey}, utils::{change_endianness, check_public_amount, verify_proof, validate_fee, calculate_complete_ext_data_hash, VERIFYING_KEY}, Proof};
+use zkcash::{groth16::{is_less_than_bn254_field_size_be, Groth16Verifyingkey}, utils::{change_endianness, change_endianness_inplace, check_public_amount, check_public_amount_u128, validate_distinct_proof_elements, validate_withdrawal_output_shape, verify_proof, validate_fee, validate_fee_u128, validate_fee_config, expected_fee, fee_bounds, FeeBounds, FeeRates, calculate_complete_ext_data_hash, VERIFYING_KEY, MAX_FEE_RATE, MAX_FEE_ERROR_MARGIN}, errors::PoolError, FeeSchedule, FeeTier, Proof, RelayerAccount, MIN_RELAYER_BOND};
 use anchor_lang::prelude::*;
 
 type G1 = ark_bn254::g1::G1Affine;
@@ -292,6 +292,19 @@ fn test_change_endianness_proof_data() {
     assert_eq!(round_trip, proof_a);
 }
 
+#[test]
+fn test_change_endianness_inplace_matches_allocating() {
+    let mut full: [u8; 64] = std::array::from_fn(|i| i as u8);
+    let expected = change_endianness(&full);
+    change_endianness_inplace(&mut full);
+    assert_eq!(full.as_slice(), expected.as_slice());
+
+    let mut partial: [u8; 40] = std::array::from_fn(|i| i as u8);
+    let expected = change_endianness(&partial);
+    change_endianness_inplace(&mut partial);
+    assert_eq!(partial.as_slice(), expected.as_slice());
+}
+
 #[test]
 fn test_is_less_than_bn254_field_size_be() {
     let bytes = [0u8; 32];
@@ -317,7 +330,7 @@ fn proof_verification_should_succeed() {
         proof_c: PROOF_C,
     };
 
-    assert!(verify_proof(proof, VERIFYING_KEY));
+    assert!(verify_proof(proof, VERIFYING_KEY).is_ok());
 }
 
 #[test]
@@ -333,7 +346,7 @@ fn proof_verification_should_fail_for_wrong_proof_a() {
         proof_c: PROOF_C,
     };
 
-    assert!(!verify_proof(proof, VERIFYING_KEY));
+    assert_eq!(verify_proof(proof, VERIFYING_KEY).unwrap_err(), PoolError::ProofPairingFailed.into());
 }
 
 #[test]
@@ -352,7 +365,7 @@ fn proof_verification_should_fail_for_correct_proof_but_modified_input() {
         proof_c: PROOF_C,
     };
 
-    assert!(!verify_proof(proof, VERIFYING_KEY));
+    assert_eq!(verify_proof(proof, VERIFYING_KEY).unwrap_err(), PoolError::ProofPairingFailed.into());
 }
 
 #[test]
@@ -390,7 +403,7 @@ fn negated_proof_a_verification_should_not_succeed() {
         proof_c: PROOF_C,
     };
 
-    assert!(!verify_proof(proof, VERIFYING_KEY));
+    assert_eq!(verify_proof(proof, VERIFYING_KEY).unwrap_err(), PoolError::ProofPairingFailed.into());
 }
 
 #[test]
@@ -471,7 +484,7 @@ fn wrong_verifying_key_verification_should_not_succeed() {
         proof_c: PROOF_C,
     };
 
-    assert!(!verify_proof(proof, WRONG_VERIFYING_KEY));
+    assert_eq!(verify_proof(proof, WRONG_VERIFYING_KEY).unwrap_err(), PoolError::ProofPairingFailed.into());
 }
 
 #[test]
@@ -490,7 +503,7 @@ fn public_input_greater_than_field_size_should_not_suceed() {
         proof_c: PROOF_C,
     };
 
-    assert!(!verify_proof(proof, VERIFYING_KEY));
+    assert_eq!(verify_proof(proof, VERIFYING_KEY).unwrap_err(), PoolError::PublicInputOutOfField.into());
 }
 
 #[test]
@@ -503,6 +516,34 @@ fn test_check_public_amount_i64_min_overflow() {
     assert!(!result, "i64::MIN should be rejected");
 }
 
+#[test]
+fn test_check_public_amount_u128_beyond_i64() {
+    let ext_amount = u64::MAX as i128 * 10;
+    let fee = 1_000u128;
+    let expected_fr = Fr::from(ext_amount as u128) - Fr::from(fee);
+    assert!(check_public_amount_u128(ext_amount, fee, fr_to_bytes(expected_fr)));
+
+    let expected_fr = -(Fr::from(ext_amount as u128) + Fr::from(fee));
+    assert!(check_public_amount_u128(-ext_amount, fee, fr_to_bytes(expected_fr)));
+
+    assert!(!check_public_amount_u128(i128::MIN, 0, [0u8; 32]));
+    // Deposits must still exceed the fee
+    assert!(!check_public_amount_u128(1_000, 1_000, [0u8; 32]));
+}
+
+#[test]
+fn test_validate_fee_u128_beyond_i64() {
+    // 0.25% of 10x u64::MAX, with a 5% margin
+    let ext_amount = -(u64::MAX as i128 * 10);
+    let expected_fee = (u64::MAX as u128 * 10) * 25 / 10000;
+    let min_fee = expected_fee * 9500 / 10000;
+    assert!(validate_fee_u128(ext_amount, min_fee, 0, 25, 500).is_ok());
+    assert!(validate_fee_u128(ext_amount, min_fee - 1, 0, 25, 500).is_err());
+
+    // Overflowing the rate product is an error, not a wrap
+    assert!(validate_fee_u128(i128::MAX, 0, 25, 0, 0).is_err());
+}
+
 #[test]
 fn test_check_public_amount_neg_overflow_protection() {
     let ext_amount = i64::MIN + 1;
@@ -577,8 +618,8 @@ fn test_verify_proof_with_invalid_proof_a_data() {
         proof_c: PROOF_C,
     };
 
-    // Should return false instead of panicking
-    assert!(!verify_proof(proof, VERIFYING_KEY));
+    // Should return an error instead of panicking
+    assert_eq!(verify_proof(proof, VERIFYING_KEY).unwrap_err(), PoolError::InvalidProofEncoding.into());
 }
 
 #[test]
@@ -597,8 +638,8 @@ fn test_verify_proof_with_all_zero_proof_a() {
         proof_c: PROOF_C,
     };
 
-    // Should return false instead of panicking
-    assert!(!verify_proof(proof, VERIFYING_KEY));
+    // Should return an error instead of panicking
+    assert!(verify_proof(proof, VERIFYING_KEY).is_err());
 }
 
 #[test]
@@ -624,8 +665,8 @@ fn test_verify_proof_with_malformed_verifying_key() {
         proof_c: PROOF_C,
     };
 
-    // Should return false instead of panicking
-    assert!(!verify_proof(proof, MALFORMED_VERIFYING_KEY));
+    // Should return an error instead of panicking
+    assert_eq!(verify_proof(proof, MALFORMED_VERIFYING_KEY).unwrap_err(), PoolError::MalformedVerifyingKey.into());
 }
 
 #[test]
@@ -648,8 +689,8 @@ fn test_verify_proof_with_truncated_proof_a() {
         proof_c: PROOF_C,
     };
 
-    // Should return false instead of panicking
-    assert!(!verify_proof(proof, VERIFYING_KEY));
+    // Should return an error instead of panicking
+    assert_eq!(verify_proof(proof, VERIFYING_KEY).unwrap_err(), PoolError::InvalidProofEncoding.into());
 }
 
 #[test]
@@ -673,9 +714,9 @@ fn test_verify_proof_does_not_panic_on_edge_cases() {
             proof_c: PROOF_C,
         };
 
-        // Each should return false without panicking
+        // Each should return an error without panicking
         let result = verify_proof(proof, VERIFYING_KEY);
-        assert!(!result, "verify_proof should return false for invalid proof_a data");
+        assert_eq!(result.unwrap_err(), PoolError::InvalidProofEncoding.into(), "invalid proof_a data should be an encoding error");
     }
 }
 
@@ -1219,4 +1260,170 @@ fn test_calculate_complete_ext_data_hash_consistency_with_borsh() {
     let manual_hash = hash(&serialized).to_bytes();
     
     assert_eq!(our_hash, manual_hash, "Our function should match manual Borsh serialization");
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_validate_fee_config_accepts_defaults_and_maximums() {
+    assert!(validate_fee_config(0, 100, 500).is_ok());
+    assert!(validate_fee_config(MAX_FEE_RATE, MAX_FEE_RATE, MAX_FEE_ERROR_MARGIN).is_ok());
+}
+
+#[test]
+fn test_validate_fee_config_rejects_deposit_fee_rate_above_max() {
+    let err = validate_fee_config(MAX_FEE_RATE + 1, 0, 0).unwrap_err();
+    assert_eq!(err, PoolError::DepositFeeRateTooHigh.into());
+}
+
+#[test]
+fn test_validate_fee_config_rejects_withdrawal_fee_rate_above_max() {
+    let err = validate_fee_config(0, MAX_FEE_RATE + 1, 0).unwrap_err();
+    assert_eq!(err, PoolError::WithdrawalFeeRateTooHigh.into());
+
+    let err = validate_fee_config(0, 10000, 0).unwrap_err();
+    assert_eq!(err, PoolError::WithdrawalFeeRateTooHigh.into());
+}
+
+#[test]
+fn test_validate_fee_config_rejects_fee_error_margin_above_max() {
+    let err = validate_fee_config(0, 0, MAX_FEE_ERROR_MARGIN + 1).unwrap_err();
+    assert_eq!(err, PoolError::FeeErrorMarginTooHigh.into());
+}
+
+#[test]
+fn test_expected_fee_rounds_like_validate_fee() {
+    let rates = FeeRates { deposit_fee_rate: 25, withdrawal_fee_rate: 50, fee_error_margin: 500 };
+    // 1000 * 25 / 10000 = 2.5 -> 2
+    assert_eq!(expected_fee(1000, &FeeSchedule::default(), &rates, None, false).unwrap(), 2);
+    // 1000 * 50 / 10000 = 5
+    assert_eq!(expected_fee(-1000, &FeeSchedule::default(), &rates, None, false).unwrap(), 5);
+    assert_eq!(expected_fee(0, &FeeSchedule::default(), &rates, None, false).unwrap(), 0);
+}
+
+#[test]
+fn test_fee_bounds_match_validate_fee() {
+    let rates = FeeRates { deposit_fee_rate: 25, withdrawal_fee_rate: 100, fee_error_margin: 500 };
+    for ext_amount in [-1_000_000_007, -1000, -1, 0, 1, 1000, 1_000_000_007, i64::MIN + 1, i64::MAX] {
+        let bounds = fee_bounds(ext_amount, &FeeSchedule::default(), &rates, None, false).unwrap();
+        assert!(bounds.minimum <= bounds.expected);
+        let validate = |fee| {
+            validate_fee(ext_amount, fee, rates.deposit_fee_rate, rates.withdrawal_fee_rate, rates.fee_error_margin)
+        };
+        assert!(validate(bounds.minimum).is_ok());
+        assert!(validate(bounds.expected).is_ok());
+        if bounds.minimum > 0 {
+            assert!(validate(bounds.minimum - 1).is_err());
+        }
+    }
+}
+
+#[test]
+fn test_fee_bounds_follow_the_pool_schedule_and_relayer() {
+    let rates = FeeRates { deposit_fee_rate: 25, withdrawal_fee_rate: 100, fee_error_margin: 500 };
+    let schedule = FeeSchedule {
+        base_fee: 5_000,
+        tiers: vec![FeeTier { min_amount: 1_000_000_000, fee_rate: 10 }],
+        fee_cap: 2_000_000,
+        ..FeeSchedule::default()
+    };
+    // 50 bps above the global rate
+    let relayer = RelayerAccount { withdrawal_fee_rate: 150, bond: MIN_RELAYER_BOND, ..Default::default() };
+
+    // 1% of 0.001 SOL, then the relayer's base fee and 0.5% premium
+    assert_eq!(expected_fee(-1_000_000, &schedule, &rates, Some(&relayer), false).unwrap(), 20_000);
+    // Tiered at 0.1%, then capped with the premium giving way first
+    assert_eq!(expected_fee(-10_000_000_000, &schedule, &rates, Some(&relayer), false).unwrap(), 2_000_000);
+    // An exempt withdrawal owes nothing
+    assert_eq!(
+        fee_bounds(-1_000_000, &schedule, &rates, Some(&relayer), true).unwrap(),
+        FeeBounds { minimum: 0, expected: 0 }
+    );
+
+    for relayer in [None, Some(&relayer)] {
+        let premium = relayer.map_or(0, |relayer| relayer.withdrawal_fee_rate - rates.withdrawal_fee_rate);
+        for ext_amount in [-10_000_000_000, -1_000_000_000, -1_000_000, -1, 0, 1_000_000] {
+            let bounds = fee_bounds(ext_amount, &schedule, &rates, relayer, false).unwrap();
+            let validate = |fee: u64| {
+                schedule.validate_fee(
+                    ext_amount as i128,
+                    fee as u128,
+                    rates.deposit_fee_rate,
+                    rates.withdrawal_fee_rate,
+                    premium,
+                    rates.fee_error_margin,
+                )
+            };
+            assert!(validate(bounds.minimum).is_ok());
+            assert!(validate(bounds.expected).is_ok());
+            if bounds.minimum > 0 {
+                assert!(validate(bounds.minimum - 1).is_err());
+            }
+        }
+    }
+}
+
+fn fixture_proof() -> Proof {
+    Proof {
+        root: PUBLIC_INPUTS[0],
+        public_amount: PUBLIC_INPUTS[1],
+        ext_data_hash: PUBLIC_INPUTS[2],
+        input_nullifiers: [PUBLIC_INPUTS[3], PUBLIC_INPUTS[4]],
+        output_commitments: [PUBLIC_INPUTS[5], PUBLIC_INPUTS[6]],
+        proof_a: PROOF_A,
+        proof_b: PROOF_B,
+        proof_c: PROOF_C,
+    }
+}
+
+#[test]
+fn test_distinct_proof_elements_accepted() {
+    assert!(validate_distinct_proof_elements(&fixture_proof()).is_ok());
+}
+
+#[test]
+fn test_duplicate_nullifiers_rejected() {
+    let mut proof = fixture_proof();
+    proof.input_nullifiers[1] = proof.input_nullifiers[0];
+
+    let err = validate_distinct_proof_elements(&proof).unwrap_err();
+    assert_eq!(err, PoolError::DuplicateNullifier.into());
+}
+
+#[test]
+fn test_duplicate_commitments_rejected() {
+    let mut proof = fixture_proof();
+    proof.output_commitments[1] = proof.output_commitments[0];
+
+    let err = validate_distinct_proof_elements(&proof).unwrap_err();
+    assert_eq!(err, PoolError::DuplicateCommitment.into());
+}
+
+#[test]
+fn test_withdrawal_output_shape_accepted() {
+    let proof = fixture_proof();
+    assert!(validate_withdrawal_output_shape(-1_000, &proof, &[1u8; 136], &[2u8; 136]).is_ok());
+}
+
+#[test]
+fn test_deposits_skip_output_shape_check() {
+    let mut proof = fixture_proof();
+    proof.output_commitments[1] = [0u8; 32];
+    assert!(validate_withdrawal_output_shape(1_000, &proof, &[1u8; 136], &[]).is_ok());
+    assert!(validate_withdrawal_output_shape(0, &proof, &[], &[]).is_ok());
+}
+
+#[test]
+fn test_degenerate_withdrawal_shapes_rejected() {
+    let proof = fixture_proof();
+    let mut zero_commitment = fixture_proof();
+    zero_commitment.output_commitments[1] = [0u8; 32];
+    let shapes: [(&Proof, &[u8], &[u8]); 4] = [
+        (&zero_commitment, &[1u8; 136], &[2u8; 136]),
+        (&proof, &[1u8; 136], &[]),
+        (&proof, &[], &[]),
+        (&proof, &[1u8; 136], &[2u8; 120]),
+    ];
+    for (proof, encrypted_output1, encrypted_output2) in shapes {
+        let err = validate_withdrawal_output_shape(-1_000, proof, encrypted_output1, encrypted_output2).unwrap_err();
+        assert_eq!(err, PoolError::DegenerateOutputShape.into());
+    }
+}
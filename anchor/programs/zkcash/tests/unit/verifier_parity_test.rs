@@ -0,0 +1,166 @@
+//! The program's verifier against an independent arkworks one.
+//!
+//! `verify_proof` runs the pairing through the alt_bn128 syscalls, which on
+//! the host are `solana_bn254`'s software implementations and on chain are
+//! the runtime's. `ark_groth16` shares neither the byte layout handling nor
+//! the point checks, so every vector below must get the same verdict from
+//! both, or host tests would be passing on behaviour the chain doesn't have.
+//!
+//! `test_report_verifier_costs` is ignored: it times both paths and prints
+//! them next to the on-chain CU recorded by the program-tests CU benchmarks.
+
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ff::{BigInt, PrimeField};
+use ark_groth16::{prepare_verifying_key, Groth16, PreparedVerifyingKey, VerifyingKey};
+use num_bigint::BigUint;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+use zkcash::groth16::{BN254_FQ_MODULUS_BE, BN254_FR_MODULUS_BE};
+use zkcash::utils::{verify_proof, VERIFYING_KEY};
+use zkcash::Proof;
+use super::malleability_test::{deposit_proof, negate_g1, negate_g2, plus_modulus, swap_limbs, with_public_inputs};
+use super::proof_test::valid_proof;
+
+const CU_BASELINE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../program-tests/tests/compute_units.json");
+
+fn fq(bytes: &[u8]) -> Option<Fq> {
+    Fq::from_bigint(BigInt::try_from(BigUint::from_bytes_be(bytes)).ok()?)
+}
+
+fn fr(bytes: &[u8; 32]) -> Option<Fr> {
+    Fr::from_bigint(BigInt::try_from(BigUint::from_bytes_be(bytes)).ok()?)
+}
+
+/// A big-endian `(x, y)` point as the syscalls take it, all zeros being the
+/// point at infinity.
+fn g1(bytes: &[u8; 64]) -> Option<G1Affine> {
+    if *bytes == [0u8; 64] {
+        return Some(G1Affine::identity());
+    }
+    let point = G1Affine::new_unchecked(fq(&bytes[..32])?, fq(&bytes[32..])?);
+    (point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve()).then_some(point)
+}
+
+/// A big-endian `(x_c1, x_c0, y_c1, y_c0)` point as the syscalls take it.
+fn g2(bytes: &[u8; 128]) -> Option<G2Affine> {
+    if *bytes == [0u8; 128] {
+        return Some(G2Affine::identity());
+    }
+    let x = Fq2::new(fq(&bytes[32..64])?, fq(&bytes[..32])?);
+    let y = Fq2::new(fq(&bytes[96..])?, fq(&bytes[64..96])?);
+    let point = G2Affine::new_unchecked(x, y);
+    (point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve()).then_some(point)
+}
+
+fn ark_verifying_key() -> PreparedVerifyingKey<Bn254> {
+    prepare_verifying_key(&VerifyingKey {
+        alpha_g1: g1(&VERIFYING_KEY.vk_alpha_g1).unwrap(),
+        beta_g2: g2(&VERIFYING_KEY.vk_beta_g2).unwrap(),
+        gamma_g2: g2(&VERIFYING_KEY.vk_gamme_g2).unwrap(),
+        delta_g2: g2(&VERIFYING_KEY.vk_delta_g2).unwrap(),
+        gamma_abc_g1: VERIFYING_KEY.vk_ic.iter().map(|point| g1(point).unwrap()).collect(),
+    })
+}
+
+/// Anything that doesn't decode to canonical points and field elements is a
+/// rejection, as it is for `verify_proof`.
+fn ark_accepts(verifying_key: &PreparedVerifyingKey<Bn254>, proof: &Proof) -> bool {
+    let decoded = (|| {
+        let ark_proof = ark_groth16::Proof { a: g1(&proof.proof_a)?, b: g2(&proof.proof_b)?, c: g1(&proof.proof_c)? };
+        let inputs: Vec<Fr> = proof.public_inputs().iter().map(fr).collect::<Option<_>>()?;
+        Some((ark_proof, inputs))
+    })();
+    let Some((ark_proof, inputs)) = decoded else {
+        return false;
+    };
+    Groth16::<Bn254>::verify_proof(verifying_key, &ark_proof, &inputs).unwrap_or(false)
+}
+
+fn syscall_accepts(proof: &Proof) -> bool {
+    verify_proof(proof.clone(), VERIFYING_KEY).is_ok()
+}
+
+/// Named proofs with the verdict both paths must reach: the two valid
+/// proofs, the one malleation Groth16 allows, and one of each class
+/// `malleability_test` covers.
+fn vectors() -> Vec<(&'static str, Proof, bool)> {
+    let proof = valid_proof();
+    let deposit = deposit_proof();
+    let with_proof_a = |proof_a| Proof { proof_a, ..proof.clone() };
+    let with_proof_b = |proof_b| Proof { proof_b, ..proof.clone() };
+
+    let mut shifted_a = proof.proof_a;
+    shifted_a[..32].copy_from_slice(&plus_modulus(&proof.proof_a[..32], &BN254_FQ_MODULUS_BE));
+    let mut shifted_b = proof.proof_b;
+    shifted_b[..32].copy_from_slice(&plus_modulus(&proof.proof_b[..32], &BN254_FQ_MODULUS_BE));
+    let mut shifted_inputs = proof.public_inputs();
+    shifted_inputs[3] = plus_modulus(&shifted_inputs[3], &BN254_FR_MODULUS_BE);
+    let mut swapped_nullifiers = proof.public_inputs();
+    swapped_nullifiers.swap(3, 4);
+
+    vec![
+        ("valid", proof.clone(), true),
+        ("valid deposit", deposit.clone(), true),
+        (
+            "negated A and B",
+            Proof { proof_a: negate_g1(&proof.proof_a), proof_b: negate_g2(&proof.proof_b), ..proof.clone() },
+            true,
+        ),
+        ("negated A", with_proof_a(negate_g1(&proof.proof_a)), false),
+        ("negated B", with_proof_b(negate_g2(&proof.proof_b)), false),
+        ("negated C", Proof { proof_c: negate_g1(&proof.proof_c), ..proof.clone() }, false),
+        ("A at infinity", with_proof_a([0u8; 64]), false),
+        ("B x limbs swapped", with_proof_b(swap_limbs(&proof.proof_b, 0, 1)), false),
+        ("B y limbs swapped", with_proof_b(swap_limbs(&proof.proof_b, 2, 3)), false),
+        ("A x plus q", with_proof_a(shifted_a), false),
+        ("B x_c1 plus q", with_proof_b(shifted_b), false),
+        ("inputs from another proof", with_public_inputs(&proof, deposit.public_inputs()), false),
+        ("nullifiers swapped", with_public_inputs(&proof, swapped_nullifiers), false),
+        ("nullifier plus r", with_public_inputs(&proof, shifted_inputs), false),
+    ]
+}
+
+#[test]
+fn test_both_verifiers_reach_the_same_verdict() {
+    let verifying_key = ark_verifying_key();
+    for (name, proof, accepted) in vectors() {
+        assert_eq!(ark_accepts(&verifying_key, &proof), accepted, "{}: arkworks verdict", name);
+        assert_eq!(syscall_accepts(&proof), accepted, "{}: syscall verdict", name);
+    }
+}
+
+fn mean_time(iterations: u32, mut verify: impl FnMut() -> bool) -> Duration {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        assert!(verify());
+    }
+    start.elapsed() / iterations
+}
+
+#[test]
+#[ignore = "timing report, run with --nocapture"]
+fn test_report_verifier_costs() {
+    const ITERATIONS: u32 = 20;
+    let verifying_key = ark_verifying_key();
+    let proof = valid_proof();
+
+    let ark = mean_time(ITERATIONS, || ark_accepts(&verifying_key, &proof));
+    let syscall = mean_time(ITERATIONS, || syscall_accepts(&proof));
+    println!("host wall clock per verification, mean of {}:", ITERATIONS);
+    println!("  arkworks: {:?}", ark);
+    println!("  syscall:  {:?} ({:.2}x arkworks)", syscall, syscall.as_secs_f64() / ark.as_secs_f64());
+
+    // The arkworks pairing needs far more than a transaction's CU budget, so
+    // only the syscall path has an on-chain cost to compare against
+    let baseline: BTreeMap<String, u64> = std::fs::read_to_string(CU_BASELINE)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    println!("on-chain CU, syscall path (arkworks does not fit on chain):");
+    for name in ["deposit/verify_proof", "withdraw/verify_proof"] {
+        match baseline.get(name) {
+            Some(units) => println!("  {}: {} CU", name, units),
+            None => println!("  {}: not recorded, run program-tests' compute_units_test with cu-bench", name),
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use anchor_lang::Discriminator;
+use zkcash::{
+    merkle_tree_account_version, next_migration, stamp_current_version, MerkleTreeAccount,
+    MERKLE_TREE_ACCOUNT_LEN, MERKLE_TREE_ACCOUNT_V0_LEN, MERKLE_TREE_ACCOUNT_V1_LEN,
+    MERKLE_TREE_ACCOUNT_VERSION, MERKLE_TREE_MIGRATIONS,
+};
+
+fn v0_account() -> Vec<u8> {
+    let mut data: Vec<u8> = (0..MERKLE_TREE_ACCOUNT_V0_LEN).map(|i| i as u8).collect();
+    data[..8].copy_from_slice(MerkleTreeAccount::DISCRIMINATOR);
+    data
+}
+
+/// Same loop as `migrate_account`, with `Vec::resize` standing in for realloc.
+fn migrate(data: &mut Vec<u8>) -> u8 {
+    let mut version = merkle_tree_account_version(data).unwrap();
+    while let Some(migration) = next_migration(version) {
+        data.resize(migration.new_len, 0);
+        migration.apply(data).unwrap();
+        version = migration.to_version();
+    }
+    version
+}
+
+#[test]
+fn test_v0_len_matches_current_struct() {
+    // Fails if MerkleTreeAccount changes size; add a migration instead
+    assert_eq!(8 + std::mem::size_of::<MerkleTreeAccount>(), MERKLE_TREE_ACCOUNT_V0_LEN);
+}
+
+#[test]
+fn test_v0_account_has_version_zero() {
+    assert_eq!(merkle_tree_account_version(&v0_account()).unwrap(), 0);
+}
+
+#[test]
+fn test_migrate_v0_to_current() {
+    let original = v0_account();
+    let mut data = original.clone();
+
+    assert_eq!(migrate(&mut data), MERKLE_TREE_ACCOUNT_VERSION);
+    assert_eq!(data.len(), MERKLE_TREE_ACCOUNT_LEN);
+    assert_eq!(merkle_tree_account_version(&data).unwrap(), MERKLE_TREE_ACCOUNT_VERSION);
+    // The v0 fields are untouched and still load as a MerkleTreeAccount
+    assert_eq!(data[..MERKLE_TREE_ACCOUNT_V0_LEN], original[..]);
+    assert_eq!(data.len() % 8, 0);
+}
+
+#[test]
+fn test_migrate_current_is_noop() {
+    let mut data = v0_account();
+    migrate(&mut data);
+    let migrated = data.clone();
+
+    assert!(next_migration(MERKLE_TREE_ACCOUNT_VERSION).is_none());
+    assert_eq!(migrate(&mut data), MERKLE_TREE_ACCOUNT_VERSION);
+    assert_eq!(data, migrated);
+}
+
+#[test]
+fn test_migrations_are_contiguous_and_append_only() {
+    let mut len = MERKLE_TREE_ACCOUNT_V0_LEN;
+    for (i, migration) in MERKLE_TREE_MIGRATIONS.iter().enumerate() {
+        assert_eq!(migration.from_version as usize, i);
+        assert!(migration.new_len > len);
+        assert!(migration.new_len - len <= 10 * 1024);
+        len = migration.new_len;
+    }
+    assert_eq!(MERKLE_TREE_MIGRATIONS.len(), MERKLE_TREE_ACCOUNT_VERSION as usize);
+}
+
+#[test]
+fn test_apply_rejects_wrong_length() {
+    let mut data = v0_account();
+    assert!(MERKLE_TREE_MIGRATIONS[0].apply(&mut data).is_err());
+}
+
+#[test]
+fn test_version_rejects_bad_data() {
+    // Too short
+    let data = v0_account();
+    assert!(merkle_tree_account_version(&data[..MERKLE_TREE_ACCOUNT_V0_LEN - 1]).is_err());
+
+    // Wrong discriminator
+    let mut data = v0_account();
+    data[0] ^= 1;
+    assert!(merkle_tree_account_version(&data).is_err());
+
+    // Grown but carrying an unknown version
+    let mut data = v0_account();
+    data.resize(MERKLE_TREE_ACCOUNT_V1_LEN, 0);
+    assert!(merkle_tree_account_version(&data).is_err());
+    data[MERKLE_TREE_ACCOUNT_V0_LEN] = MERKLE_TREE_ACCOUNT_VERSION + 1;
+    assert!(merkle_tree_account_version(&data).is_err());
+}
+
+#[test]
+fn test_stamp_current_version_matches_migrated_layout() {
+    let mut migrated = v0_account();
+    migrate(&mut migrated);
+
+    let mut created = v0_account();
+    created.resize(MERKLE_TREE_ACCOUNT_LEN, 0);
+    stamp_current_version(&mut created).unwrap();
+    assert_eq!(created, migrated);
+
+    // Only accounts created at the current length can be stamped
+    assert!(stamp_current_version(&mut v0_account()).is_err());
+}
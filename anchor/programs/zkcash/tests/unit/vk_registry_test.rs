@@ -0,0 +1,109 @@
+use zkcash::errors::PoolError;
+use zkcash::groth16::Groth16Verifyingkey;
+use zkcash::utils::VERIFYING_KEY;
+use zkcash::{
+    active_verifying_key_hash, validate_verifying_key_hash, verifying_key_hash, VerifyingKeyRecord, VerifyingKeyRotation,
+    VK_GRACE_EPOCHS, VK_ROTATION_DELAY_EPOCHS,
+};
+
+fn copy_of(verifying_key: &Groth16Verifyingkey<'static>) -> Groth16Verifyingkey<'static> {
+    Groth16Verifyingkey {
+        nr_pubinputs: verifying_key.nr_pubinputs,
+        vk_alpha_g1: verifying_key.vk_alpha_g1,
+        vk_beta_g2: verifying_key.vk_beta_g2,
+        vk_gamme_g2: verifying_key.vk_gamme_g2,
+        vk_delta_g2: verifying_key.vk_delta_g2,
+        vk_ic: verifying_key.vk_ic,
+    }
+}
+
+#[test]
+fn test_active_hash_is_the_compiled_key() {
+    assert_eq!(active_verifying_key_hash(), verifying_key_hash(&VERIFYING_KEY));
+    assert!(validate_verifying_key_hash(&active_verifying_key_hash()).is_ok());
+}
+
+#[test]
+fn test_hash_covers_every_field() {
+    let original = verifying_key_hash(&VERIFYING_KEY);
+
+    let mut key = copy_of(&VERIFYING_KEY);
+    key.vk_delta_g2[0] ^= 1;
+    assert_ne!(verifying_key_hash(&key), original);
+
+    let mut key = copy_of(&VERIFYING_KEY);
+    key.vk_ic = &VERIFYING_KEY.vk_ic[..VERIFYING_KEY.vk_ic.len() - 1];
+    assert_ne!(verifying_key_hash(&key), original);
+
+    let mut key = copy_of(&VERIFYING_KEY);
+    key.nr_pubinputs += 1;
+    assert_ne!(verifying_key_hash(&key), original);
+}
+
+#[test]
+fn test_wrong_claim_rejected() {
+    let mut claimed = active_verifying_key_hash();
+    claimed[31] ^= 1;
+    let err = validate_verifying_key_hash(&claimed).unwrap_err();
+    assert_eq!(err, PoolError::VerifyingKeyMismatch.into());
+}
+
+#[test]
+fn test_record_only_changes_on_a_new_key() {
+    let mut record = VerifyingKeyRecord::default();
+    assert!(record.update([1u8; 32], 10));
+    assert!(!record.update([1u8; 32], 20));
+    assert_eq!(record.updated_slot, 10);
+
+    assert!(record.update([2u8; 32], 30));
+    assert_eq!(record.vk_hash, [2u8; 32]);
+    assert_eq!(record.updated_slot, 30);
+}
+
+#[test]
+fn test_rotation_starts_from_the_compiled_key() {
+    let mut rotation = VerifyingKeyRotation::default();
+    rotation.queue([3u8; 32], 10).unwrap();
+    assert_eq!(rotation.active_hash, active_verifying_key_hash());
+    assert_eq!(rotation.pending_hash, Some([3u8; 32]));
+    assert_eq!(rotation.activation_epoch, 10 + VK_ROTATION_DELAY_EPOCHS);
+}
+
+#[test]
+fn test_rotation_waits_for_the_delay() {
+    let mut rotation = VerifyingKeyRotation::default();
+    rotation.queue([3u8; 32], 10).unwrap();
+    let err = rotation.activate(10 + VK_ROTATION_DELAY_EPOCHS - 1).unwrap_err();
+    assert_eq!(err, PoolError::VerifyingKeyRotationNotReady.into());
+
+    rotation.activate(10 + VK_ROTATION_DELAY_EPOCHS).unwrap();
+    assert_eq!(rotation.active_hash, [3u8; 32]);
+    assert_eq!(rotation.previous_hash, Some(active_verifying_key_hash()));
+    assert_eq!(rotation.pending_hash, None);
+}
+
+#[test]
+fn test_old_key_accepted_during_grace() {
+    let old = active_verifying_key_hash();
+    let mut rotation = VerifyingKeyRotation::default();
+    rotation.queue([3u8; 32], 0).unwrap();
+    let epoch = VK_ROTATION_DELAY_EPOCHS;
+    rotation.activate(epoch).unwrap();
+
+    assert!(rotation.validate_proof_key(&[3u8; 32], epoch).is_ok());
+    assert!(rotation.validate_proof_key(&old, epoch + VK_GRACE_EPOCHS - 1).is_ok());
+    let err = rotation.validate_proof_key(&old, epoch + VK_GRACE_EPOCHS).unwrap_err();
+    assert_eq!(err, PoolError::VerifyingKeyNotAccepted.into());
+    assert!(!rotation.accepts(&[4u8; 32], epoch));
+}
+
+#[test]
+fn test_invalid_rotations_rejected() {
+    let mut rotation = VerifyingKeyRotation::default();
+    for vk_hash in [[0u8; 32], active_verifying_key_hash()] {
+        let err = rotation.queue(vk_hash, 0).unwrap_err();
+        assert_eq!(err, PoolError::InvalidVerifyingKeyRotation.into());
+    }
+    let err = rotation.activate(100).unwrap_err();
+    assert_eq!(err, PoolError::InvalidVerifyingKeyRotation.into());
+}
@@ -0,0 +1,56 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::hash;
+use zkcash::errors::PoolError;
+use zkcash::{validate_cpi_programs, CpiAllowlist, CpiTarget, MAX_CPI_PROGRAMS};
+
+const DATA: &[u8] = b"deposit 5 SOL into vault";
+
+fn allowlisted() -> (CpiAllowlist, CpiTarget) {
+    let program = Pubkey::new_unique();
+    let allowlist = CpiAllowlist {
+        programs: vec![program],
+        bump: 0,
+    };
+    let target = CpiTarget {
+        program,
+        data_hash: hash(DATA).to_bytes(),
+    };
+    (allowlist, target)
+}
+
+#[test]
+fn test_committed_instruction_accepted() {
+    let (allowlist, target) = allowlisted();
+    assert!(allowlist.validate_cpi(&target, DATA).is_ok());
+}
+
+#[test]
+fn test_unlisted_program_rejected() {
+    let (allowlist, target) = allowlisted();
+    let target = CpiTarget {
+        program: Pubkey::new_unique(),
+        ..target
+    };
+    assert_eq!(allowlist.validate_cpi(&target, DATA).unwrap_err(), PoolError::CpiProgramNotAllowed.into());
+}
+
+#[test]
+fn test_other_data_rejected() {
+    let (allowlist, target) = allowlisted();
+    let err = allowlist.validate_cpi(&target, b"deposit 5 SOL for the relayer").unwrap_err();
+    assert_eq!(err, PoolError::CpiDataMismatch.into());
+}
+
+#[test]
+fn test_transfer_programs_cannot_be_allowlisted() {
+    assert!(validate_cpi_programs(&[Pubkey::new_unique(); MAX_CPI_PROGRAMS]).is_ok());
+    for programs in [
+        vec![Pubkey::new_unique(); MAX_CPI_PROGRAMS + 1],
+        vec![Pubkey::default()],
+        vec![zkcash::ID],
+        vec![anchor_lang::system_program::ID],
+        vec![anchor_spl::token::ID],
+    ] {
+        assert_eq!(validate_cpi_programs(&programs).unwrap_err(), PoolError::InvalidCpiAllowlist.into());
+    }
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::Pubkey;
+use zkcash::errors::PoolError;
+use zkcash::{withdrawal_delay, QueuedWithdrawal, MIN_WITHDRAWAL_DELAY_SLOTS, WITHDRAWAL_DELAY_WINDOW_SLOTS};
+
+#[test]
+fn test_delay_stays_within_the_window() {
+    for i in 0..64u8 {
+        let delay = withdrawal_delay(&[i; 32], &[i.wrapping_mul(7); 32]);
+        assert!(delay >= MIN_WITHDRAWAL_DELAY_SLOTS);
+        assert!(delay < MIN_WITHDRAWAL_DELAY_SLOTS + WITHDRAWAL_DELAY_WINDOW_SLOTS);
+    }
+}
+
+#[test]
+fn test_delay_depends_on_nullifier_and_slot_hash() {
+    let delay = withdrawal_delay(&[1; 32], &[2; 32]);
+    assert_eq!(delay, withdrawal_delay(&[1; 32], &[2; 32]));
+    let others = [withdrawal_delay(&[3; 32], &[2; 32]), withdrawal_delay(&[1; 32], &[4; 32])];
+    assert!(others.iter().any(|other| *other != delay));
+}
+
+#[test]
+fn test_queued_withdrawal_waits_for_its_slot() {
+    let mut queued = QueuedWithdrawal::default();
+    queued
+        .queue(Pubkey::new_unique(), Pubkey::new_unique(), 1_000, 10, Pubkey::new_unique(), 500, 200, 255)
+        .unwrap();
+    assert_eq!(queued.queued_slot, 500);
+    assert_eq!(queued.executable_slot, 700);
+
+    assert_eq!(queued.validate_executable(699).unwrap_err(), PoolError::WithdrawalNotYetExecutable.into());
+    assert!(queued.validate_executable(700).is_ok());
+
+    assert!(QueuedWithdrawal::default()
+        .queue(Pubkey::default(), Pubkey::default(), 0, 0, Pubkey::default(), u64::MAX, 1, 0)
+        .is_err());
+}
@@ -0,0 +1,46 @@
+use anchor_lang::prelude::Pubkey;
+use zkcash::errors::PoolError;
+use zkcash::{
+    validate_foreign_recipient, ForeignRecipient, WithdrawalAttestation, WITHDRAWAL_ATTESTATION_VERSION,
+    WORMHOLE_CHAIN_ID_SOLANA,
+};
+
+#[test]
+fn test_payload_layout() {
+    let attestation = WithdrawalAttestation {
+        recipient: ForeignRecipient {
+            chain_id: 2,
+            address: [1u8; 32],
+        },
+        mint: Pubkey::new_from_array([2u8; 32]),
+        amount: 0x0102_0304_0506_0708,
+        nullifiers: [[3u8; 32], [4u8; 32]],
+    };
+    let payload = attestation.to_payload();
+
+    assert_eq!(payload.len(), WithdrawalAttestation::LEN);
+    assert_eq!(payload[0], WITHDRAWAL_ATTESTATION_VERSION);
+    assert_eq!(payload[1..3], [0, 2]);
+    assert_eq!(payload[3..35], [1u8; 32]);
+    assert_eq!(payload[35..67], [2u8; 32]);
+    assert_eq!(payload[67..75], [1, 2, 3, 4, 5, 6, 7, 8]);
+    assert_eq!(payload[75..107], [3u8; 32]);
+    assert_eq!(payload[107..139], [4u8; 32]);
+}
+
+#[test]
+fn test_foreign_recipient_validation() {
+    let valid = ForeignRecipient {
+        chain_id: 2,
+        address: [1u8; 32],
+    };
+    assert!(validate_foreign_recipient(&valid).is_ok());
+
+    for invalid in [
+        ForeignRecipient { chain_id: 0, ..valid },
+        ForeignRecipient { chain_id: WORMHOLE_CHAIN_ID_SOLANA, ..valid },
+        ForeignRecipient { address: [0u8; 32], ..valid },
+    ] {
+        assert_eq!(validate_foreign_recipient(&invalid).unwrap_err(), PoolError::InvalidForeignRecipient.into());
+    }
+}
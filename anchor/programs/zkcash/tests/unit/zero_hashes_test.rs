@@ -0,0 +1,90 @@
+use light_hasher::{Hasher, Keccak, Poseidon, Sha256};
+use std::mem::MaybeUninit;
+use zkcash::{MerkleTreeAccount, merkle_tree::MerkleTree};
+use zkcash::zero_hashes::{ZeroHashes, MAX_HEIGHT};
+
+fn create_test_account(height: u8) -> MerkleTreeAccount {
+    let mut uninit: MaybeUninit<MerkleTreeAccount> = MaybeUninit::uninit();
+    unsafe {
+        let ptr = uninit.as_mut_ptr();
+        std::ptr::write_bytes(ptr, 0, 1);
+        (*ptr).height = height;
+        (*ptr).root_history_size = 100;
+        uninit.assume_init()
+    }
+}
+
+fn assert_table_is_chained<H: ZeroHashes>() {
+    assert_eq!(H::ZERO_HASHES.len(), MAX_HEIGHT + 1);
+    assert_eq!(H::ZERO_HASHES[0], [0u8; 32]);
+    for level in 0..MAX_HEIGHT {
+        let zero = H::ZERO_HASHES[level];
+        assert_eq!(H::ZERO_HASHES[level + 1], H::hashv(&[&zero, &zero]).unwrap(), "level {}", level + 1);
+    }
+}
+
+#[test]
+fn test_zero_hashes_are_chained_hashes_of_the_empty_leaf() {
+    assert_table_is_chained::<Poseidon>();
+    assert_table_is_chained::<Sha256>();
+    assert_table_is_chained::<Keccak>();
+}
+
+#[test]
+fn test_poseidon_zero_hashes_match_light_hasher() {
+    let light_hasher_table = Poseidon::zero_bytes();
+    assert_eq!(Poseidon::ZERO_HASHES, &light_hasher_table[..=MAX_HEIGHT]);
+}
+
+#[test]
+fn test_zero_hash_rejects_levels_past_the_table() {
+    assert_eq!(Poseidon::zero_hash(MAX_HEIGHT).unwrap(), Poseidon::ZERO_HASHES[MAX_HEIGHT]);
+    assert!(Poseidon::zero_hash(MAX_HEIGHT + 1).is_err());
+}
+
+#[test]
+fn test_initialize_uses_the_hasher_table_for_each_height() {
+    for height in [1u8, 8, 20, 26] {
+        let mut account = create_test_account(height);
+        MerkleTree::initialize::<Sha256>(&mut account).unwrap();
+        assert_eq!(account.root, Sha256::ZERO_HASHES[height as usize]);
+        assert_eq!(account.root_history[0], Sha256::ZERO_HASHES[height as usize]);
+        for level in 0..height as usize {
+            assert_eq!(account.subtrees[level], Sha256::ZERO_HASHES[level]);
+        }
+    }
+}
+
+#[test]
+fn test_append_on_small_tree_matches_manual_root() {
+    let mut account = create_test_account(3);
+    MerkleTree::initialize::<Keccak>(&mut account).unwrap();
+
+    let leaf = [7u8; 32];
+    MerkleTree::append::<Keccak>(leaf, &mut account).unwrap();
+
+    let mut expected = leaf;
+    for level in 0..3 {
+        expected = Keccak::hashv(&[&expected, &Keccak::ZERO_HASHES[level]]).unwrap();
+    }
+    assert_eq!(account.root, expected);
+}
+
+#[test]
+fn test_initialize_rejects_unsupported_heights() {
+    let mut account = create_test_account(0);
+    assert!(MerkleTree::initialize::<Poseidon>(&mut account).is_err());
+
+    let too_tall = account.subtrees.len() as u8 + 1;
+    let mut account = create_test_account(too_tall);
+    assert!(MerkleTree::initialize::<Poseidon>(&mut account).is_err());
+}
+
+#[test]
+fn test_zero_hashes_for_height_is_the_table_prefix() {
+    let levels = Poseidon::zero_hashes_for_height::<26>().unwrap();
+    assert_eq!(&levels[..], &Poseidon::ZERO_HASHES[..26]);
+    assert_eq!(Keccak::zero_hashes_for_height::<0>().unwrap().len(), 0);
+    assert!(Poseidon::zero_hashes_for_height::<{ MAX_HEIGHT + 1 }>().is_ok());
+    assert!(Poseidon::zero_hashes_for_height::<{ MAX_HEIGHT + 2 }>().is_err());
+}
@@ -0,0 +1,105 @@
+/**
+ * Differential test vectors from the TypeScript client
+ *
+ * Reads a batch of inputs as JSON on stdin and writes what the TS client
+ * computes for each on stdout, for `client/tests/unit/differential_test.rs`
+ * to compare against the Rust client byte for byte. Run from `anchor/`:
+ *
+ *   npx ts-node --transpile-only tests/differential/vectors.ts < input.json
+ *
+ * Field elements go out as decimal strings and hashes as hex, the forms the
+ * TS client itself produces.
+ */
+
+import BN from 'bn.js';
+import { PublicKey } from '@solana/web3.js';
+import { WasmFactory } from '@lightprotocol/hasher.rs';
+import { Keypair } from '../lib/keypair';
+import { Utxo } from '../lib/utxo';
+import { MerkleTree } from '../lib/merkle_tree';
+import { getExtDataHash } from '../lib/utils';
+import { FIELD_SIZE } from '../lib/constants';
+
+interface UtxoInput {
+  privkey: string;
+  amount: string;
+  blinding: string;
+  index: number;
+  mintAddress: string;
+}
+
+interface ExtDataInput {
+  recipient: string;
+  extAmount: string;
+  encryptedOutput1: string;
+  encryptedOutput2: string;
+  fee: string;
+  feeRecipient: string;
+  mintAddress: string;
+}
+
+interface Input {
+  privkeys: string[];
+  utxos: UtxoInput[];
+  extData: ExtDataInput[];
+  publicAmounts: { extAmount: string; fee: string }[];
+  trees: { levels: number; leaves: string[] }[];
+}
+
+async function readStdin(): Promise<string> {
+  const chunks: Buffer[] = [];
+  for await (const chunk of process.stdin) {
+    chunks.push(chunk as Buffer);
+  }
+  return Buffer.concat(chunks).toString('utf8');
+}
+
+async function main() {
+  const input: Input = JSON.parse(await readStdin());
+  const lightWasm = await WasmFactory.getInstance();
+
+  const pubkeys = input.privkeys.map((privkey) => new Keypair(privkey, lightWasm).pubkey.toString());
+
+  const commitments: string[] = [];
+  const nullifiers: string[] = [];
+  for (const utxo of input.utxos) {
+    const note = new Utxo({
+      lightWasm,
+      amount: utxo.amount,
+      blinding: utxo.blinding,
+      keypair: new Keypair(utxo.privkey, lightWasm),
+      index: utxo.index,
+      mintAddress: utxo.mintAddress,
+    });
+    commitments.push(await note.getCommitment());
+    nullifiers.push(await note.getNullifier());
+  }
+
+  const extDataHashes = input.extData.map((extData) =>
+    Buffer.from(
+      getExtDataHash({
+        recipient: new PublicKey(extData.recipient),
+        extAmount: extData.extAmount,
+        encryptedOutput1: Buffer.from(extData.encryptedOutput1, 'hex'),
+        encryptedOutput2: Buffer.from(extData.encryptedOutput2, 'hex'),
+        fee: extData.fee,
+        feeRecipient: new PublicKey(extData.feeRecipient),
+        mintAddress: new PublicKey(extData.mintAddress),
+      })
+    ).toString('hex')
+  );
+
+  // As the transact tests compute it
+  const publicAmounts = input.publicAmounts.map(({ extAmount, fee }) =>
+    new BN(extAmount).sub(new BN(fee)).add(FIELD_SIZE).mod(FIELD_SIZE).toString()
+  );
+
+  const roots = input.trees.map(({ levels, leaves }) => new MerkleTree(levels, lightWasm, leaves).root());
+
+  process.stdout.write(JSON.stringify({ pubkeys, commitments, nullifiers, extDataHashes, publicAmounts, roots }));
+}
+
+main().catch((error) => {
+  console.error(error);
+  process.exit(1);
+});
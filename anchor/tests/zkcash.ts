@@ -6500,6 +6500,37 @@ describe("zkcash", () => {
     }
   });
 
+  it("Fails to update global config with fee rates above MAX_FEE_RATE (500)", async () => {
+    for (const [depositFeeRate, withdrawalFeeRate, errorName] of [
+      [501, null, "DepositFeeRateTooHigh"],
+      [null, 501, "WithdrawalFeeRateTooHigh"],
+    ]) {
+      try {
+        const modifyComputeUnits = anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({ 
+          units: 1_000_000 
+        });
+        
+        await program.methods
+          .updateGlobalConfig(
+            depositFeeRate, // deposit_fee_rate
+            withdrawalFeeRate, // withdrawal_fee_rate
+            null  // fee_error_margin
+          )
+          .accounts({
+            globalConfig: globalConfigPDA,
+            authority: authority.publicKey,
+          })
+          .signers([authority])
+          .preInstructions([modifyComputeUnits])
+          .rpc();
+
+        expect.fail("Transaction should have failed due to fee rate above the cap");
+      } catch (error) {
+        expect(error.toString()).to.include(errorName as string);
+      }
+    }
+  });
+
   it("Global config update with null values leaves existing values unchanged", async () => {
     // Get the current global config values
     const initialConfig = await program.account.globalConfig.fetch(globalConfigPDA);
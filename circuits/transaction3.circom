@@ -0,0 +1,55 @@
+pragma circom 2.0.0;
+
+include "./transaction.circom";
+
+// Transaction with the circuit version bound in as a public input. A proof
+// only verifies against the version it was generated for, so the program can
+// run several circuits side by side and reject retired ones.
+template VersionedTransaction(levels, nIns, nOuts) {
+    signal input root;
+    signal input publicAmount;
+    signal input extDataHash;
+    signal input mintAddress;
+
+    signal input inputNullifier[nIns];
+    signal input inAmount[nIns];
+    signal input inPrivateKey[nIns];
+    signal input inBlinding[nIns];
+    signal input inPathIndices[nIns];
+    signal input inPathElements[nIns][levels];
+
+    signal input outputCommitment[nOuts];
+    signal input outAmount[nOuts];
+    signal input outPubkey[nOuts];
+    signal input outBlinding[nOuts];
+
+    // Declared last so it is the last public input, after the legacy seven
+    signal input protocolVersion;
+
+    component transaction = Transaction(levels, nIns, nOuts);
+    transaction.root <== root;
+    transaction.publicAmount <== publicAmount;
+    transaction.extDataHash <== extDataHash;
+    transaction.mintAddress <== mintAddress;
+    for (var tx = 0; tx < nIns; tx++) {
+        transaction.inputNullifier[tx] <== inputNullifier[tx];
+        transaction.inAmount[tx] <== inAmount[tx];
+        transaction.inPrivateKey[tx] <== inPrivateKey[tx];
+        transaction.inBlinding[tx] <== inBlinding[tx];
+        transaction.inPathIndices[tx] <== inPathIndices[tx];
+        for (var i = 0; i < levels; i++) {
+            transaction.inPathElements[tx][i] <== inPathElements[tx][i];
+        }
+    }
+    for (var tx = 0; tx < nOuts; tx++) {
+        transaction.outputCommitment[tx] <== outputCommitment[tx];
+        transaction.outAmount[tx] <== outAmount[tx];
+        transaction.outPubkey[tx] <== outPubkey[tx];
+        transaction.outBlinding[tx] <== outBlinding[tx];
+    }
+
+    // Public inputs need a constraint to be bound to the proof
+    signal protocolVersionSquare <== protocolVersion * protocolVersion;
+}
+
+component main {public [root, publicAmount, extDataHash, inputNullifier, outputCommitment, protocolVersion]} = VersionedTransaction(26, 2, 2);